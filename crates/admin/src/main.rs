@@ -0,0 +1,235 @@
+//! Offline admin tool for the member database.
+//!
+//! It uses `memberdb` directly, so it runs the same queries and updates as the bot, and is meant
+//! to be used while the bot is stopped.
+//! ```text
+//! admin [--db <file>] <command>
+//!
+//! check                            Check for database integrity
+//! export <file>                    Export all members and their stats as json
+//! import <file> [apply]            Import members and their stats from a json export
+//! adjust <stat> <target> <amount>  Add to a stat of a profile, `amount` can be negative
+//! migrate                          Apply pending migrations and list all applied migrations
+//! ```
+//! The json format is the one described in [`memberdb::migrate`].
+//! Import is a dry run unless `apply` is given.
+//!
+//! For `adjust`, `stat` is one of "message", "voice", "stream", "online", or "xp", and `target`
+//! is either "d:<discord id>" or "m:<ign>".
+use std::collections::HashMap;
+use std::env;
+use std::process;
+use std::str::FromStr;
+use std::time::Duration;
+
+use anyhow::{bail, Context, Result};
+use tracing::Level;
+
+use memberdb::migrate::{self, ImportPlan};
+use memberdb::model::db::Stat;
+use memberdb::model::discord::DiscordId;
+use memberdb::model::wynn::McId;
+use memberdb::{ConnectSettings, DB};
+use util::some;
+
+/// Database used when `--db` isn't given, which is the one used by the bot
+const DEFAULT_DB: &str = "./database/member.db";
+/// Max amount of database connections, transactions holds one while other queries are made
+const MAX_CONN: u32 = 4;
+/// Stats that can be adjusted
+const ADJUSTABLE_STATS: [&str; 5] = ["message", "voice", "stream", "online", "xp"];
+
+const USAGE: &str = "Usage: admin [--db <file>] <command>
+
+Commands:
+    check                            Check for database integrity
+    export <file>                    Export all members and their stats as json
+    import <file> [apply]            Import members and their stats from a json export
+    adjust <stat> <target> <amount>  Add to a stat of a profile, ex: `adjust xp m:Pucaet -1000`
+    migrate                          Apply pending migrations and list all applied migrations";
+
+#[tokio::main]
+async fn main() {
+    // Only warnings, as sqlx logs every query at info level
+    tracing_subscriber::fmt().with_max_level(Level::WARN).with_writer(std::io::stderr).init();
+
+    let mut args: Vec<String> = env::args().skip(1).collect();
+    let file = match args.iter().position(|arg| arg == "--db") {
+        Some(i) if i + 1 < args.len() => {
+            let file = args.remove(i + 1);
+            args.remove(i);
+            file
+        }
+        Some(_) => exit_with_usage(),
+        None => DEFAULT_DB.to_string(),
+    };
+
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    let command = match args.as_slice() {
+        ["check"] => Command::Check,
+        ["export", file] => Command::Export(file),
+        ["import", file] => Command::Import(file, false),
+        ["import", file, "apply"] => Command::Import(file, true),
+        ["adjust", stat, target, amount] => Command::Adjust(stat, target, amount),
+        ["migrate"] => Command::Migrate,
+        _ => exit_with_usage(),
+    };
+
+    let settings = ConnectSettings { max_connections: MAX_CONN, ..Default::default() };
+    let db = DB::open(&file, &settings, Duration::from_millis(250)).await;
+    // Updates broadcast events, which fails if there are no receivers
+    let _events = db.connect();
+    let result = match command {
+        Command::Check => check(&db).await,
+        Command::Export(file) => export(&db, file).await,
+        Command::Import(file, apply) => import(&db, file, apply).await,
+        Command::Adjust(stat, target, amount) => adjust(&db, stat, target, amount).await,
+        Command::Migrate => list_migrations(&db).await,
+    };
+
+    if let Err(why) = result {
+        eprintln!("Error: {:#}", why);
+        process::exit(1);
+    }
+}
+
+enum Command<'a> {
+    Check,
+    Export(&'a str),
+    /// File to import, and if the import is applied
+    Import(&'a str, bool),
+    /// Stat, target, and amount to add
+    Adjust(&'a str, &'a str, &'a str),
+    Migrate,
+}
+
+fn exit_with_usage() -> ! {
+    eprintln!("{}", USAGE);
+    process::exit(2);
+}
+
+/// Print the integrity issues of the database, fails if there are any
+async fn check(db: &DB) -> Result<()> {
+    let issues = memberdb::check_integrity(db).await?;
+    if issues.is_empty() {
+        println!("No issues found");
+        return Ok(());
+    }
+    for issue in &issues {
+        println!("{}", issue);
+    }
+    bail!("Found {} issues", issues.len())
+}
+
+async fn export(db: &DB, file: &str) -> Result<()> {
+    let members = migrate::export(db).await?;
+    let json = serde_json::to_string_pretty(&members).context("Failed to serialize members")?;
+    std::fs::write(file, json).with_context(|| format!("Failed to write {}", file))?;
+    println!("Exported {} members to {}", members.len(), file);
+    Ok(())
+}
+
+/// Import the members in `file`, or only report what would be imported if `apply` is false.
+///
+/// As the api isn't available, igns without a mcid are resolved via the wynn profiles in the
+/// database.
+async fn import(db: &DB, file: &str, apply: bool) -> Result<()> {
+    let content = std::fs::read_to_string(file).with_context(|| format!("Failed to read {}", file))?;
+    let members = migrate::parse_export(&content)?;
+
+    let mut mcids = HashMap::new();
+    for ign in members.iter().filter(|m| m.mcid.is_none()).filter_map(|m| m.ign.as_ref()) {
+        if let Some(mcid) = McId::from_ign(&mut db.exe(), ign).await? {
+            mcids.insert(ign.to_lowercase(), mcid.0);
+        }
+    }
+
+    let plan = migrate::plan_import(db, members, &mcids).await?;
+    print_plan(&plan);
+    if apply {
+        let count = migrate::import(db, &plan).await?;
+        println!("Imported {} members, skipped {} entries", count, plan.skipped.len());
+    } else {
+        println!(
+            "Dry run: {} members can be imported, {} entries would be skipped",
+            plan.members.len(),
+            plan.skipped.len()
+        );
+    }
+    Ok(())
+}
+
+fn print_plan(plan: &ImportPlan) {
+    for member in &plan.members {
+        println!("+ {} ({})", member.name, member.rank);
+    }
+    for (name, reason) in &plan.skipped {
+        println!("- {}: {}", name, reason);
+    }
+}
+
+/// Add `amount` to both the total and weekly version of a stat of `target`
+async fn adjust(db: &DB, name: &str, target: &str, amount: &str) -> Result<()> {
+    if !ADJUSTABLE_STATS.contains(&name) {
+        bail!("'{}' isn't a stat that can be adjusted", name);
+    }
+    let stat = Stat::from_str(name)?;
+    let amount = match amount.strip_prefix('-') {
+        Some(amount) => -i64::try_from(stat.parse_val(amount)?)?,
+        None => i64::try_from(stat.parse_val(amount)?)?,
+    };
+
+    let mut tx = db.begin().await?;
+    match (&stat, parse_target(target)?) {
+        (Stat::Message | Stat::Voice | Stat::Stream, Target::Discord(id)) => {
+            let id = DiscordId(id.parse().with_context(|| format!("Invalid discord id '{}'", id))?);
+            if !id.exist(&mut db.exe()).await? {
+                bail!("Discord profile {} doesn't exist", id);
+            }
+            match stat {
+                Stat::Message => id.update_message(&mut tx, amount).await?,
+                Stat::Voice => id.update_voice(&mut tx, amount).await?,
+                _ => id.update_stream(&mut tx, amount).await?,
+            }
+        }
+        (Stat::Online | Stat::Xp, Target::Mc(ign)) => {
+            let mcid = some!(
+                McId::from_ign(&mut db.exe(), ign).await?,
+                bail!("Wynn profile of {} doesn't exist", ign)
+            );
+            match stat {
+                Stat::Online => mcid.update_activity(&mut tx, amount).await?,
+                _ => mcid.update_xp(&mut tx, amount).await?,
+            }
+        }
+        _ => bail!("'{}' isn't a stat of {}", name, target),
+    }
+    tx.commit().await?;
+
+    println!("Added {} to {} of {}", amount, name, target);
+    Ok(())
+}
+
+/// Profile whose stat is adjusted
+enum Target<'a> {
+    Discord(&'a str),
+    Mc(&'a str),
+}
+
+fn parse_target(s: &str) -> Result<Target<'_>> {
+    if let Some(id) = s.strip_prefix("d:") {
+        return Ok(Target::Discord(id));
+    }
+    if let Some(ign) = s.strip_prefix("m:") {
+        return Ok(Target::Mc(ign));
+    }
+    bail!("Invalid target '{}', expected \"d:<discord id>\" or \"m:<ign>\"", s)
+}
+
+/// Print the applied migrations, pending ones are already applied when the database is opened
+async fn list_migrations(db: &DB) -> Result<()> {
+    for (version, description) in db.migrations().await? {
+        println!("{} {}", version, description);
+    }
+    Ok(())
+}
@@ -0,0 +1,31 @@
+//! Provides [`Alumni`], the settings of turning members that left the guild into alumni
+use serde::{Deserialize, Serialize};
+
+/// Settings of turning long-standing members into alumni when they leave the guild, instead of
+/// them being removed or keeping their rank.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct Alumni {
+    /// If members are turned into alumni automatically when they leave the guild
+    pub auto: bool,
+    /// Least amount of days a member has to be in the guild for to become an alumnus
+    pub min_tenure_days: u64,
+}
+
+impl Default for Alumni {
+    fn default() -> Self {
+        Self { auto: false, min_tenure_days: 180 }
+    }
+}
+
+impl Alumni {
+    /// Get the least amount of seconds a member has to be in the guild for to become an alumnus,
+    /// `None` if members aren't turned into alumni automatically
+    pub fn min_tenure(&self) -> Option<i64> {
+        if self.auto {
+            Some(i64::try_from(self.min_tenure_days * 86400).unwrap_or(i64::MAX))
+        } else {
+            None
+        }
+    }
+}
@@ -0,0 +1,109 @@
+//! Provides [`TagOrphans`], for finding tagged discord objects that no longer exist
+use std::collections::HashSet;
+
+use anyhow::{Context, Result};
+use serenity::http::Http;
+use serenity::model::guild::Guild;
+use serenity::model::id::{RoleId, UserId};
+use serenity::Error as SerenityError;
+use tokio::sync::RwLock;
+
+use crate::Config;
+
+/// Ids in the [`TagMap`]s and the log subscriptions of [`Config`] that no longer exist in the guild.
+///
+/// Tags of deleted channels and roles are removed when they are deleted, but not if they are
+/// deleted while the bot is offline. Users leaving the guild don't have their tags removed.
+///
+/// [`TagMap`]: crate::tag::TagMap
+#[derive(Debug, Default, Clone)]
+pub struct TagOrphans {
+    /// Channels and categories
+    pub channels: HashSet<u64>,
+    pub roles: HashSet<u64>,
+    pub users: HashSet<u64>,
+}
+
+impl TagOrphans {
+    /// Find the ids in `config` that no longer exist in `guild`.
+    ///
+    /// Channels and users that aren't cached are looked up with the discord api, as archived
+    /// threads and some guild members may not be in the cache.
+    pub async fn find(config: &RwLock<Config>, http: &Http, guild: &Guild) -> Result<Self> {
+        let (channels, roles, users) = {
+            let config = config.read().await;
+            let channels: HashSet<u64> = config
+                .channel_tags
+                .objects()
+                .chain(config.category_tags.objects())
+                .chain(config.text_channel_tags.objects())
+                .chain(config.custom_channel_tags.objects())
+                .chain(config.log_subscriptions.channels())
+                .copied()
+                .collect();
+            let roles: HashSet<u64> = config
+                .user_role_tags
+                .objects()
+                .chain(config.custom_user_role_tags.objects())
+                .copied()
+                .collect();
+            let users: HashSet<u64> =
+                config.user_tags.objects().chain(config.custom_user_tags.objects()).copied().collect();
+            (channels, roles, users)
+        };
+
+        let mut orphans = Self::default();
+        for id in channels {
+            if guild.channels.keys().any(|channel| channel.0 == id) {
+                continue;
+            }
+            if !exists(http.get_channel(id).await).context("Failed to get discord channel")? {
+                orphans.channels.insert(id);
+            }
+        }
+        orphans.roles = roles.into_iter().filter(|id| !guild.roles.contains_key(&RoleId(*id))).collect();
+        for id in users {
+            if guild.members.contains_key(&UserId(id)) {
+                continue;
+            }
+            if !exists(http.get_member(guild.id.0, id).await).context("Failed to get discord member")? {
+                orphans.users.insert(id);
+            }
+        }
+        Ok(orphans)
+    }
+
+    /// Amount of orphaned ids
+    pub fn len(&self) -> usize {
+        self.channels.len() + self.roles.len() + self.users.len()
+    }
+
+    /// Checks if there are no orphaned ids
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Remove the tags of the orphaned ids from `config`
+    pub fn remove_from(&self, config: &mut Config) {
+        config.channel_tags.retain(|id, _| !self.channels.contains(id));
+        config.category_tags.retain(|id, _| !self.channels.contains(id));
+        config.text_channel_tags.retain(|id, _| !self.channels.contains(id));
+        config.user_role_tags.retain(|id, _| !self.roles.contains(id));
+        config.user_tags.retain(|id, _| !self.users.contains(id));
+        config.custom_channel_tags.retain(|id, _| !self.channels.contains(id));
+        config.log_subscriptions.retain(|id| !self.channels.contains(id));
+        config.custom_user_role_tags.retain(|id, _| !self.roles.contains(id));
+        config.custom_user_tags.retain(|id, _| !self.users.contains(id));
+    }
+}
+
+/// Checks if the result of fetching a discord object means that it exists
+fn exists<T>(result: serenity::Result<T>) -> Result<bool> {
+    match result {
+        Ok(_) => Ok(true),
+        Err(SerenityError::Http(why)) if why.status_code().map(|code| code.as_u16()) == Some(404) => {
+            Ok(false)
+        }
+        Err(why) => Err(why.into()),
+    }
+}
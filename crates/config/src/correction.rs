@@ -0,0 +1,34 @@
+//! Provides [`XpCorrections`], the thresholds of which guild xp contributions need staff review
+use serde::{Deserialize, Serialize};
+
+/// Thresholds of suspicious guild xp contributions, usually caused by a mis-parsed API response.
+///
+/// Suspicious contributions are held back and posted to [`TextChannelTag::XpCorrection`]
+/// channels for staff to approve or discard, instead of being applied right away.
+/// They are only held back if there is a channel to review them in.
+///
+/// [`TextChannelTag::XpCorrection`]: crate::tag::TextChannelTag::XpCorrection
+#[derive(Debug, Serialize, Deserialize, Default, Clone)]
+pub struct XpCorrections {
+    /// Contributions of more than this amount of xp in one poll are suspicious
+    pub max_xp: Option<i64>,
+    /// If negative contributions are suspicious
+    #[serde(default)]
+    pub negative: bool,
+}
+
+impl XpCorrections {
+    /// Check if a contribution of `amount` xp is suspicious
+    /// ```
+    /// use config::correction::XpCorrections;
+    ///
+    /// let corrections = XpCorrections { max_xp: Some(1_000_000), negative: true };
+    /// assert!(corrections.is_suspicious(-5));
+    /// assert!(corrections.is_suspicious(2_000_000));
+    /// assert!(!corrections.is_suspicious(500));
+    /// assert!(!XpCorrections::default().is_suspicious(-5));
+    /// ```
+    pub fn is_suspicious(&self, amount: i64) -> bool {
+        (self.negative && amount < 0) || matches!(self.max_xp, Some(max) if amount > max)
+    }
+}
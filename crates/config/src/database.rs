@@ -0,0 +1,38 @@
+//! Provides [`DatabaseSettings`], the settings of the member database
+use serde::{Deserialize, Serialize};
+
+/// Settings of the member database, which are applied when the bot starts
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct DatabaseSettings {
+    /// Queries that take at least this long are logged along with their sql, in milliseconds
+    pub slow_query_ms: u64,
+    /// Amount of prepared statements cached by each database connection
+    pub statement_cache_capacity: usize,
+    /// Max amount of database connections
+    pub max_connections: u32,
+    /// Amount of database connections kept open even when they are idle
+    pub min_connections: u32,
+    /// Journal mode of the database, one of "WAL", "DELETE", "TRUNCATE", "PERSIST", "MEMORY" or
+    /// "OFF"
+    pub journal_mode: String,
+    /// How hard the database syncs writes to disk, one of "NORMAL", "FULL", "EXTRA" or "OFF"
+    pub synchronous: String,
+    /// How long a connection waits for the database to be unlocked by another one before failing,
+    /// in milliseconds
+    pub busy_timeout_ms: u64,
+}
+
+impl Default for DatabaseSettings {
+    fn default() -> Self {
+        Self {
+            slow_query_ms: 250,
+            statement_cache_capacity: 100,
+            max_connections: 5,
+            min_connections: 0,
+            journal_mode: "WAL".to_string(),
+            synchronous: "NORMAL".to_string(),
+            busy_timeout_ms: 5000,
+        }
+    }
+}
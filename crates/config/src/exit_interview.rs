@@ -0,0 +1,33 @@
+//! Provides [`ExitInterview`], the settings of asking members why they left
+use serde::{Deserialize, Serialize};
+
+/// Settings of the question sent via DM to members that left the guild or the discord server.
+///
+/// Their next DM to the bot within the response window is recorded as the answer, and can be
+/// read with the `exitlog` command.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct ExitInterview {
+    /// Question to send, members aren't asked if there is none
+    pub question: Option<String>,
+    /// Amount of hours after leaving a member can still answer the question
+    pub response_hours: u64,
+}
+
+impl Default for ExitInterview {
+    fn default() -> Self {
+        Self { question: None, response_hours: 72 }
+    }
+}
+
+impl ExitInterview {
+    /// Get the amount of seconds after leaving a member can still answer the question
+    /// ```
+    /// use config::exit_interview::ExitInterview;
+    ///
+    /// assert_eq!(ExitInterview::default().response_window(), 72 * 3600);
+    /// ```
+    pub fn response_window(&self) -> i64 {
+        i64::try_from(self.response_hours * 3600).unwrap_or(i64::MAX)
+    }
+}
@@ -0,0 +1,17 @@
+//! Provides [`EventForwarding`], the settings of forwarding bot events to external consumers
+use serde::{Deserialize, Serialize};
+
+/// Settings of forwarding events to a webhook, so external tools (ex: the guild website) can react
+/// to them in real time.
+///
+/// Events are posted to the webhook as json, in the form of
+/// `{"source": "db" | "wynn", "events": [...]}`.
+#[derive(Debug, Serialize, Deserialize, Default, Clone)]
+pub struct EventForwarding {
+    /// Url the events are posted to, forwarding is disabled if there is none
+    pub webhook: Option<String>,
+    /// If wynn events are forwarded in addition to database events.
+    /// Wynn events are sent frequently, as they include player activities.
+    #[serde(default)]
+    pub wynn_events: bool,
+}
@@ -37,40 +37,256 @@
 //!     Ok(())
 //! }
 //! ```
+pub mod alumni;
+pub mod audit;
+pub mod correction;
+pub mod database;
+pub mod exit_interview;
+pub mod forward;
+pub mod linked_roles;
+#[warn(missing_docs, missing_debug_implementations)]
+pub mod locale;
+pub mod log_subscription;
+pub mod message;
+pub mod message_log;
+pub mod migration;
+pub mod milestone;
+pub mod online;
+pub mod promotion;
+pub mod rank_channel;
+pub mod report;
+pub mod reset;
+pub mod stat_audit;
 #[warn(missing_docs, missing_debug_implementations)]
 pub mod tag;
 pub mod utils;
+pub mod voice;
+pub mod war_voice;
 
-use std::sync::Arc;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::{fs, io};
 
-use anyhow::{bail, Result};
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::Utc;
 use serde::{Deserialize, Serialize};
 use serenity::client::Cache;
 use serenity::http::CacheHttp;
 use serenity::model::channel::{Channel, GuildChannel};
 use serenity::model::guild::Member;
 use serenity::prelude::TypeMapKey;
-use tag::{ChannelTag, TagMap, TextChannelTag, UserTag};
+use alumni::Alumni;
+use correction::XpCorrections;
+use database::DatabaseSettings;
+use exit_interview::ExitInterview;
+use forward::EventForwarding;
+use linked_roles::LinkedRoles;
+use locale::Locale;
+use log_subscription::LogSubscriptions;
+use message::MessageLimits;
+use message_log::MessageLog;
+use milestone::Milestones;
+use online::OnlineLimits;
+use promotion::PromotionVotes;
+use rank_channel::RankChannel;
+use report::{SendFailure, SendReport, MAX_PERMANENT_FAILURES};
+use reset::WeeklyReset;
+use stat_audit::StatAudit;
+use tag::{ChannelTag, CustomTag, CustomTagDef, TagMap, TagTarget, TextChannelTag, UserTag};
+use utils::Tags;
+use voice::VoiceTracking;
+use war_voice::WarVoice;
 use tokio::sync::RwLock;
-use tracing::info;
+use tracing::{info, warn};
 
 use event::{DiscordEvent, DiscordSignal};
-use util::{read_json, some, write_json};
+use util::string::StatFormat;
+use util::task::{RestartPolicy, Spawner};
+use util::{some, write_json};
 
 /// Configuration data
 #[derive(Debug, Serialize, Deserialize, Default)]
 pub struct Config {
+    /// Version of the config schema, see [`migration`]
+    #[serde(default)]
+    pub version: u64,
     pub channel_tags: TagMap<u64, ChannelTag>,
     pub category_tags: TagMap<u64, ChannelTag>,
     pub text_channel_tags: TagMap<u64, TextChannelTag>,
+    /// Log events each log channel receives, keyed by the channel id
+    #[serde(default)]
+    pub log_subscriptions: LogSubscriptions,
     pub user_tags: TagMap<u64, UserTag>,
     pub user_role_tags: TagMap<u64, UserTag>,
+    /// Definitions of the custom tags, keyed by their names
+    #[serde(default)]
+    pub custom_tags: HashMap<String, CustomTagDef>,
+    /// Custom tags of discord users
+    #[serde(default)]
+    pub custom_user_tags: TagMap<u64, CustomTag>,
+    /// Custom tags of discord roles, which also apply to the users with the role
+    #[serde(default)]
+    pub custom_user_role_tags: TagMap<u64, CustomTag>,
+    /// Custom tags of discord channels and categories, tags of a category also apply to the
+    /// channels under it
+    #[serde(default)]
+    pub custom_channel_tags: TagMap<u64, CustomTag>,
+    /// Locale of each guild, guilds without one uses the default locale
+    #[serde(default)]
+    pub locales: HashMap<u64, Locale>,
+    /// How stats are displayed in each guild, guilds without one uses the default format
+    #[serde(default)]
+    pub stat_formats: HashMap<u64, StatFormat>,
+    /// How stats are displayed to each discord user, overriding the format of the guild
+    #[serde(default)]
+    pub user_stat_formats: HashMap<u64, StatFormat>,
+    /// Command prefix of each guild, guilds without one uses the prefix in the environment
+    #[serde(default)]
+    pub prefixes: HashMap<u64, String>,
+    /// If management commands called via slash commands respond with messages that are only
+    /// visible to the caller
+    #[serde(default)]
+    pub ephemeral_responses: bool,
+    /// Steps of the milestones announced to [`TextChannelTag::Milestone`] channels
+    ///
+    /// [`TextChannelTag::Milestone`]: crate::tag::TextChannelTag::Milestone
+    #[serde(default)]
+    pub milestones: Milestones,
+    /// Weekly xp requirement of each guild rank, keyed by the rank's name. Guild members with a
+    /// rank that isn't in here don't have a requirement.
+    ///
+    /// Members below the requirement are reported to [`TextChannelTag::XpReport`] channels.
+    ///
+    /// [`TextChannelTag::XpReport`]: crate::tag::TextChannelTag::XpReport
+    #[serde(default)]
+    pub xp_requirements: HashMap<String, i64>,
+    /// Max amount of members of the in-game guild, which the guild api doesn't report.
+    /// When it is set, [`TextChannelTag::Waitlist`] channels are notified when a slot opens while
+    /// there are waitlisted recruits.
+    ///
+    /// [`TextChannelTag::Waitlist`]: crate::tag::TextChannelTag::Waitlist
+    #[serde(default)]
+    pub guild_member_cap: Option<u64>,
+    /// Settings of the promotion votes held in [`TextChannelTag::PromotionVote`] channels
+    ///
+    /// [`TextChannelTag::PromotionVote`]: crate::tag::TextChannelTag::PromotionVote
+    #[serde(default)]
+    pub promotion_votes: PromotionVotes,
+    /// Settings of forwarding database and wynn events to a webhook
+    #[serde(default)]
+    pub event_forwarding: EventForwarding,
+    /// Time of the week the weekly stats are reset at, and if staff has to confirm the reset
+    #[serde(default)]
+    pub weekly_reset: WeeklyReset,
+    /// Limits of the wynncraft online time counted into online stats
+    #[serde(default)]
+    pub online_limits: OnlineLimits,
+    /// Settings of the log of when members sent messages
+    #[serde(default)]
+    pub message_log: MessageLog,
+    /// Limits of the messages counted into message stats
+    #[serde(default)]
+    pub message_limits: MessageLimits,
+    /// Settings of the audit log of where stat increments came from
+    #[serde(default)]
+    pub stat_audit: StatAudit,
+    /// Thresholds of the guild xp contributions held back for staff review
+    #[serde(default)]
+    pub xp_corrections: XpCorrections,
+    /// Percentage of a message counted into message stats for messages sent in a channel, keyed
+    /// by the channel or category id. Messages in channels without a weight are counted in full.
+    #[serde(default)]
+    pub message_weights: HashMap<u64, u32>,
+    /// Settings of the member database
+    #[serde(default)]
+    pub database: DatabaseSettings,
+    /// Settings of which voice chat states accrue voice time
+    #[serde(default)]
+    pub voice_tracking: VoiceTracking,
+    /// Patterns of wynncraft worlds that don't accrue online time, where `*` matches any
+    /// characters, ex: "HB*". Lobby worlds never accrue online time.
+    #[serde(default)]
+    pub ignored_worlds: Vec<String>,
+    /// Names of the other guilds whose level and member count are polled, changes are posted to
+    /// [`TextChannelTag::Intel`] channels
+    ///
+    /// [`TextChannelTag::Intel`]: crate::tag::TextChannelTag::Intel
+    #[serde(default)]
+    pub observed_guilds: Vec<String>,
+    /// Directory the API responses that failed to parse are stored in for inspection, they aren't
+    /// stored if there is none
+    #[serde(default)]
+    pub api_payload_dir: Option<String>,
+    /// If discord ids, igns and names in the log files are replaced with hashes, and message
+    /// metadata is omitted from them, so they can be shared without leaking member data.
+    /// Changed at runtime with the `redactlogs` command.
+    #[serde(default)]
+    pub redact_logs: bool,
+    /// Settings of turning long-standing members into alumni when they leave the guild
+    #[serde(default)]
+    pub alumni: Alumni,
+    /// Settings of asking members why they left the guild or the discord server
+    #[serde(default)]
+    pub exit_interview: ExitInterview,
+    /// Settings of announcing users joining [`ChannelTag::WarVoice`] channels
+    ///
+    /// [`ChannelTag::WarVoice`]: crate::tag::ChannelTag::WarVoice
+    #[serde(default)]
+    pub war_voice: WarVoice,
+    /// Settings of exposing member data through discord's linked roles
+    #[serde(default)]
+    pub linked_roles: LinkedRoles,
+    /// Channels only visible to members of some ranks, keyed by the channel id
+    #[serde(default)]
+    pub rank_channels: HashMap<u64, RankChannel>,
+    /// Names of the feature modules that aren't registered at startup, ex: "recruitment".
+    /// Changes only apply after a restart.
+    #[serde(default)]
+    pub disabled_modules: Vec<String>,
+    /// Amount of consecutive permanent failures of sending messages to each channel
+    #[serde(skip)]
+    send_failures: Mutex<HashMap<u64, u32>>,
 }
 
 impl Config {
-    /// Load config from file
-    pub fn new(file: &str) -> Option<Self> {
-        read_json!(file, Self::default())
+    /// Load config from file, and migrate it to the current version.
+    ///
+    /// If the file doesn't exist, the default config is used.
+    /// If the file can't be loaded, it is backed up so it isn't overwritten by the next save.
+    pub fn new(file: &str) -> Result<Self> {
+        let content = match fs::read_to_string(file) {
+            Ok(content) => content,
+            Err(why) if why.kind() == io::ErrorKind::NotFound => {
+                return Ok(Self { version: migration::CONFIG_VERSION, ..Self::default() })
+            }
+            Err(why) => return Err(why).with_context(|| format!("Failed to read config file '{}'", file)),
+        };
+
+        match Self::parse(&content) {
+            Ok((config, from)) => {
+                if from != migration::CONFIG_VERSION {
+                    info!(from, to = config.version, "Migrated config");
+                }
+                Ok(config)
+            }
+            Err(why) => {
+                let backup = format!("{}.{}.bak", file, Utc::now().format("%Y%m%d%H%M%S"));
+                fs::copy(file, &backup)
+                    .with_context(|| format!("Failed to back up config file to '{}'", backup))?;
+                Err(why.context(format!(
+                    "Failed to load config file '{}', it is backed up to '{}'",
+                    file, backup
+                )))
+            }
+        }
+    }
+
+    /// Parse and migrate a config, returns the config and the version it was at
+    fn parse(content: &str) -> Result<(Self, u64)> {
+        let mut value: serde_json::Value = serde_json::from_str(content).context("Invalid json")?;
+        let from = migration::migrate(&mut value)?;
+        let config = serde_json::from_value(value).context("Invalid config")?;
+        Ok((config, from))
     }
 
     /// Write config to file
@@ -144,21 +360,208 @@ impl Config {
         self.check_memebr_tag(member, &UserTag::NoRoleUpdate)
     }
 
+    /// Checks if a discord member's messages are counted into message stats
+    pub fn is_message_tracked(&self, member: &Member) -> bool {
+        self.check_memebr_tag(member, &UserTag::NoMessageTrack)
+    }
+
+    /// Parse a tag, which can be a custom tag
+    pub fn parse_tag(&self, s: &str) -> Option<Tags> {
+        if let Ok(tag) = s.parse() {
+            return Some(tag);
+        }
+        self.custom_tags.contains_key(s).then(|| Tags::Custom(CustomTag(s.to_string())))
+    }
+
+    /// Create a custom tag, it can't have the same name as an existing tag
+    pub fn create_custom_tag(&mut self, tag: &CustomTag, description: String, target: TagTarget) -> Result<()> {
+        if tag.0.parse::<Tags>().is_ok() || self.custom_tags.contains_key(&tag.0) {
+            bail!("Tag '{}' already exists", tag);
+        }
+        self.custom_tags.insert(tag.0.clone(), CustomTagDef { description, target });
+        Ok(())
+    }
+
+    /// Delete a custom tag, and remove it from all objects
+    pub fn delete_custom_tag(&mut self, tag: &CustomTag) -> Result<()> {
+        if self.custom_tags.remove(&tag.0).is_none() {
+            bail!("Custom tag '{}' doesn't exist", tag);
+        }
+        self.custom_user_tags.remove_tag(tag);
+        self.custom_user_role_tags.remove_tag(tag);
+        self.custom_channel_tags.remove_tag(tag);
+        Ok(())
+    }
+
+    /// Checks if a discord member has a custom tag, either directly or from their roles
+    pub fn member_has_custom_tag(&self, member: &Member, tag: &CustomTag) -> bool {
+        self.custom_user_tags.tagged(&member.user.id.0, tag)
+            || member.roles.iter().any(|role| self.custom_user_role_tags.tagged(&role.0, tag))
+    }
+
+    /// Checks if a channel has a custom tag, either directly or from its parents
+    pub fn channel_has_custom_tag(&self, cache: &Cache, channel: &GuildChannel, tag: &CustomTag) -> bool {
+        if self.custom_channel_tags.tagged(&channel.id.0, tag) {
+            return true;
+        }
+        let (category_id, parent_id) = util::discord::get_channel_parents(cache, channel);
+        [category_id, parent_id].iter().flatten().any(|id| self.custom_channel_tags.tagged(&id.0, tag))
+    }
+
+    /// Get the percentage of a message counted into message stats for messages sent in a channel.
+    ///
+    /// The weight of the channel itself is used first, then the weight of its parent channel
+    /// (for threads), then the weight of its category.
+    pub fn message_weight(&self, cache: &Cache, channel: &GuildChannel) -> u32 {
+        let (category_id, parent_id) = util::discord::get_channel_parents(cache, channel);
+        [Some(channel.id), parent_id, category_id]
+            .iter()
+            .flatten()
+            .find_map(|id| self.message_weights.get(&id.0).copied())
+            .unwrap_or(100)
+    }
+
+    /// Get the locale used in a guild
+    pub fn locale(&self, guild_id: Option<u64>) -> Locale {
+        guild_id.and_then(|id| self.locales.get(&id).copied()).unwrap_or_default()
+    }
+
+    /// Get how stats are displayed to a user in a guild, the format of the user is used first,
+    /// then the format of the guild
+    pub fn stat_format(&self, guild_id: Option<u64>, user_id: u64) -> StatFormat {
+        match self.user_stat_formats.get(&user_id) {
+            Some(format) => *format,
+            None => self.guild_stat_format(guild_id),
+        }
+    }
+
+    /// Get how stats are displayed in a guild, which is also how they are displayed in the logs
+    /// and reports the bot posts there on its own
+    pub fn guild_stat_format(&self, guild_id: Option<u64>) -> StatFormat {
+        guild_id.and_then(|id| self.stat_formats.get(&id)).copied().unwrap_or_default()
+    }
+
+    /// Get the command prefix set for a guild, if there is one
+    pub fn prefix(&self, guild_id: Option<u64>) -> Option<&str> {
+        guild_id.and_then(|id| self.prefixes.get(&id)).map(String::as_str)
+    }
+
     /// Send a message to all the channels with given [`TextChannelTag`]
     ///
+    /// A channel failing doesn't stop the message from being sent to the other channels, instead
+    /// the failures are collected into the returned report.
+    /// Channels that failed permanently too many times in a row are listed in
+    /// [`SendReport::stale`], use [`send`] to have them untagged automatically.
+    ///
     /// [`TextChannelTag`]: crate::tag::TextChannelTag
-    pub async fn send(&self, cache_http: &impl CacheHttp, tag: &TextChannelTag, content: &str) -> Result<()> {
+    pub async fn send(
+        &self, cache_http: &impl CacheHttp, tag: &TextChannelTag, content: &str,
+    ) -> Result<SendReport> {
+        let channels: Vec<u64> = self.text_channel_tags.tagged_objects(tag).copied().collect();
+        self.send_to(cache_http, &channels, content).await
+    }
+
+    /// Send a message to the given channels, the same way as [`Config::send`]
+    pub async fn send_to(
+        &self, cache_http: &impl CacheHttp, channels: &[u64], content: &str,
+    ) -> Result<SendReport> {
+        self.send_counted(cache_http, channels, content, true).await
+    }
+
+    /// Send a message to the given channels like [`Config::send_to`], but failures aren't counted
+    /// towards [`MAX_PERMANENT_FAILURES`], so channels aren't untagged while a message that
+    /// already failed is retried, ex: during an outage of the bot's permissions.
+    pub async fn resend_to(
+        &self, cache_http: &impl CacheHttp, channels: &[u64], content: &str,
+    ) -> Result<SendReport> {
+        self.send_counted(cache_http, channels, content, false).await
+    }
+
+    async fn send_counted(
+        &self, cache_http: &impl CacheHttp, channels: &[u64], content: &str, count_failures: bool,
+    ) -> Result<SendReport> {
         let cache = some!(cache_http.cache(), bail!("No cache"));
         let http = cache_http.http();
-        for channel_id in self.text_channel_tags.tagged_objects(tag) {
-            if let Some(Channel::Guild(channel)) = cache.channel(*channel_id) {
-                channel.say(http, content).await?;
+        let mut report = SendReport::default();
+        for channel_id in channels {
+            let (error, permanent) = match cache.channel(*channel_id) {
+                Some(Channel::Guild(channel)) => match channel.say(http, content).await {
+                    Ok(_) => {
+                        self.send_failures.lock().unwrap().remove(channel_id);
+                        report.sent.push(*channel_id);
+                        continue;
+                    }
+                    Err(why) => {
+                        let permanent = report::is_permanent(&why);
+                        (anyhow!(why), permanent)
+                    }
+                },
+                // The cache may not have loaded the channel yet
+                _ => (anyhow!("Channel not found"), false),
+            };
+
+            if permanent && count_failures {
+                let mut failures = self.send_failures.lock().unwrap();
+                let count = failures.entry(*channel_id).or_default();
+                *count += 1;
+                if *count >= MAX_PERMANENT_FAILURES {
+                    report.stale.push(*channel_id);
+                }
             }
+            report.failures.push(SendFailure { channel_id: *channel_id, error, permanent });
         }
-        Ok(())
+        Ok(report)
     }
 }
 
+/// Send a message to all the channels with given [`TextChannelTag`] via [`Config::send`], and
+/// untag the channels that failed permanently too many times in a row.
+///
+/// [`TextChannelTag`]: crate::tag::TextChannelTag
+pub async fn send(
+    config: &RwLock<Config>, cache_http: &impl CacheHttp, tag: &TextChannelTag, content: &str,
+) -> Result<SendReport> {
+    let report = {
+        let config = config.read().await;
+        config.send(cache_http, tag, content).await?
+    };
+    for failure in &report.failures {
+        warn!(failure.channel_id, ?tag, "Failed to send message to tagged channel: {:#}", failure.error);
+    }
+    if !report.stale.is_empty() {
+        let mut config = config.write().await;
+        for channel_id in &report.stale {
+            warn!(channel_id, ?tag, "Untagging channel that keeps failing");
+            config.text_channel_tags.remove(channel_id, tag);
+            config.send_failures.lock().unwrap().remove(channel_id);
+        }
+    }
+    Ok(report)
+}
+
+/// Send a log message to the given log channels via [`Config::send_to`], and unsubscribe the
+/// channels that failed permanently too many times in a row from all log events.
+pub async fn send_log(
+    config: &RwLock<Config>, cache_http: &impl CacheHttp, channels: &[u64], content: &str,
+) -> Result<SendReport> {
+    let report = {
+        let config = config.read().await;
+        config.send_to(cache_http, channels, content).await?
+    };
+    for failure in &report.failures {
+        warn!(failure.channel_id, "Failed to send message to log channel: {:#}", failure.error);
+    }
+    if !report.stale.is_empty() {
+        let mut config = config.write().await;
+        for channel_id in &report.stale {
+            warn!(channel_id, "Unsubscribing log channel that keeps failing");
+            config.log_subscriptions.remove_channel(*channel_id);
+            config.send_failures.lock().unwrap().remove(channel_id);
+        }
+    }
+    Ok(report)
+}
+
 /// Discord data key for [`Config`]
 /// ```
 /// use std::sync::Arc;
@@ -178,14 +581,18 @@ impl TypeMapKey for Config {
 }
 
 /// Start the loop that keeps the [`Config`] up to date
-pub async fn start_loop(config: Arc<RwLock<Config>>, signal: DiscordSignal) {
-    tokio::spawn(async move {
-        info!("starting config manage loop (discord event)");
-        let mut receiver = signal.connect();
-        loop {
-            let event = receiver.recv().await.unwrap();
-            let (_ctx, event) = event.as_ref();
-            process_discord_event(&config, event).await;
+pub async fn start_loop(spawner: &impl Spawner, config: Arc<RwLock<Config>>, signal: DiscordSignal) {
+    spawner.spawn("config manage (discord event)", RestartPolicy::Always, move || {
+        let config = config.clone();
+        let signal = signal.clone();
+        async move {
+            info!("starting config manage loop (discord event)");
+            let mut receiver = signal.connect();
+            loop {
+                let event = receiver.recv().await.unwrap();
+                let (_ctx, event) = event.as_ref();
+                process_discord_event(&config, event).await;
+            }
         }
     });
 }
@@ -199,11 +606,14 @@ async fn process_discord_event(config: &RwLock<Config>, event: &DiscordEvent) {
             config.channel_tags.remove_all(&channel.id.0);
             config.category_tags.remove_all(&channel.id.0);
             config.text_channel_tags.remove_all(&channel.id.0);
+            config.custom_channel_tags.remove_all(&channel.id.0);
+            config.log_subscriptions.remove_channel(channel.id.0);
         }
         DiscordEvent::RoleDelete { id, .. } => {
             info!("Discord role deleted, updating config");
             let mut config = config.write().await;
             config.user_role_tags.remove_all(&id.0);
+            config.custom_user_role_tags.remove_all(&id.0);
         }
         _ => {}
     }
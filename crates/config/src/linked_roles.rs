@@ -0,0 +1,47 @@
+//! Provides [`LinkedRoles`], the settings of exposing member data through discord's linked roles
+use serde::{Deserialize, Serialize};
+
+/// Settings of exposing member data as role connection metadata, so roles can be gated by it
+/// through discord's linked roles.
+///
+/// The client secret of the application is read from the `DISCORD_CLIENT_SECRET` environment
+/// variable.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct LinkedRoles {
+    /// If the OAuth2 server and the metadata pushing are running
+    pub enabled: bool,
+    /// Address the OAuth2 server listens on
+    pub address: String,
+    /// Public url of the OAuth2 server, which is `address` behind a proxy or port forward.
+    ///
+    /// `{url}/linked-role` has to be set as the application's linked roles verification url, and
+    /// `{url}/callback` as one of its OAuth2 redirects.
+    pub url: String,
+    /// Amount of seconds between pushing the metadata of all linked users
+    pub push_interval: u64,
+}
+
+impl Default for LinkedRoles {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            address: "0.0.0.0:8080".to_string(),
+            url: "http://localhost:8080".to_string(),
+            push_interval: 3600,
+        }
+    }
+}
+
+impl LinkedRoles {
+    /// Get the OAuth2 redirect uri
+    /// ```
+    /// use config::linked_roles::LinkedRoles;
+    ///
+    /// let settings = LinkedRoles { url: "https://bot.example.com/".to_string(), ..Default::default() };
+    /// assert_eq!(settings.redirect_uri(), "https://bot.example.com/callback");
+    /// ```
+    pub fn redirect_uri(&self) -> String {
+        format!("{}/callback", self.url.trim_end_matches('/'))
+    }
+}
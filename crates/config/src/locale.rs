@@ -0,0 +1,41 @@
+//! Provides [`Locale`], the language bot responses are written in
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use util::ioerr;
+
+/// All variants of [`Locale`]
+pub const LOCALES: [Locale; 2] = [Locale::En, Locale::Fr];
+
+/// Language of user-facing bot responses
+#[derive(Debug, Serialize, Deserialize, Hash, Eq, PartialEq, Clone, Copy, Default)]
+pub enum Locale {
+    /// English
+    #[default]
+    En,
+    /// French
+    Fr,
+}
+
+impl FromStr for Locale {
+    type Err = std::io::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s.to_lowercase().as_str() {
+            "en" => Self::En,
+            "fr" => Self::Fr,
+            _ => return ioerr!("Failed to parse '{}' as Locale", s),
+        })
+    }
+}
+
+impl fmt::Display for Locale {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::En => write!(f, "en"),
+            Self::Fr => write!(f, "fr"),
+        }
+    }
+}
@@ -0,0 +1,212 @@
+//! Provides [`LogSubscriptions`], which log events each log channel receives
+//!
+//! Each log channel subscribes to any subset of the [`LogEvent`]s, ex: a channel can receive
+//! guild member joins and leaves, but not their xp contributions.
+//! ```
+//! use config::log_subscription::{LogEvent, LogSubscriptions};
+//!
+//! let mut subscriptions = LogSubscriptions::default();
+//! subscriptions.subscribe(123, LogEvent::MemberJoin);
+//! subscriptions.subscribe(123, LogEvent::MemberLeave);
+//! subscriptions.subscribe(456, LogEvent::Xp);
+//!
+//! assert!(subscriptions.subscribed(123, &LogEvent::MemberJoin));
+//! assert!(!subscriptions.subscribed(123, &LogEvent::Xp));
+//! assert_eq!(subscriptions.subscribers(&LogEvent::Xp).collect::<Vec<_>>(), vec![&456]);
+//!
+//! // Channels without subscriptions are removed
+//! subscriptions.unsubscribe(456, &LogEvent::Xp);
+//! assert_eq!(subscriptions.channels().count(), 1);
+//! ```
+use std::collections::hash_map::Keys;
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use util::{impl_debug_display, ioerr};
+
+/// All variants of [`LogEvent`]
+pub const LOG_EVENTS: [LogEvent; 11] = [
+    LogEvent::MemberJoin,
+    LogEvent::MemberLeave,
+    LogEvent::RankChange,
+    LogEvent::NameChange,
+    LogEvent::RankExpire,
+    LogEvent::LevelUp,
+    LogEvent::GuildChange,
+    LogEvent::Xp,
+    LogEvent::PlayerJoin,
+    LogEvent::PlayerLeave,
+    LogEvent::PlayerMove,
+];
+
+/// Names of the groups of [`LogEvent`]s, see [`LogEvent::group`]
+pub const LOG_EVENT_GROUPS: [&str; 6] = ["all", "member", "guild", "level", "xp", "online"];
+
+/// Events that can be logged to log channels
+#[derive(Debug, Serialize, Deserialize, Hash, Eq, PartialEq, Clone, Copy)]
+pub enum LogEvent {
+    /// A player joined the guild
+    MemberJoin,
+    /// A player left the guild
+    MemberLeave,
+    /// Guild rank of a guild member changed
+    RankChange,
+    /// A guild member changed their ign
+    NameChange,
+    /// Temporary rank of a member expired
+    RankExpire,
+    /// The guild leveled up
+    LevelUp,
+    /// The guild's tag or banner changed
+    GuildChange,
+    /// Guild members contributed xp, logged once per log interval
+    Xp,
+    /// A guild member logged in
+    PlayerJoin,
+    /// A guild member logged off
+    PlayerLeave,
+    /// A guild member moved to another world
+    PlayerMove,
+}
+
+impl LogEvent {
+    /// Describe the event
+    pub fn describe(&self) -> &str {
+        match self {
+            Self::MemberJoin => "Player joined the guild",
+            Self::MemberLeave => "Player left the guild",
+            Self::RankChange => "Guild rank of a member changed",
+            Self::NameChange => "Member changed their ign",
+            Self::RankExpire => "Temporary rank of a member expired",
+            Self::LevelUp => "Guild leveled up",
+            Self::GuildChange => "Guild tag or banner changed",
+            Self::Xp => "Guild xp contributions",
+            Self::PlayerJoin => "Member logged in",
+            Self::PlayerLeave => "Member logged off",
+            Self::PlayerMove => "Member moved to another world",
+        }
+    }
+
+    /// Get the events in a group, the groups are listed in [`LOG_EVENT_GROUPS`]
+    /// ```
+    /// use config::log_subscription::{LogEvent, LOG_EVENTS};
+    ///
+    /// assert_eq!(LogEvent::group("all"), Some(LOG_EVENTS.to_vec()));
+    /// assert_eq!(LogEvent::group("xp"), Some(vec![LogEvent::Xp]));
+    /// assert_eq!(LogEvent::group("Xp"), None);
+    /// ```
+    pub fn group(name: &str) -> Option<Vec<Self>> {
+        Some(match name {
+            "all" => LOG_EVENTS.to_vec(),
+            "member" => {
+                vec![
+                    Self::MemberJoin,
+                    Self::MemberLeave,
+                    Self::RankChange,
+                    Self::NameChange,
+                    Self::RankExpire,
+                ]
+            }
+            "guild" => vec![Self::LevelUp, Self::GuildChange],
+            "level" => vec![Self::LevelUp],
+            "xp" => vec![Self::Xp],
+            "online" => vec![Self::PlayerJoin, Self::PlayerLeave, Self::PlayerMove],
+            _ => return None,
+        })
+    }
+
+    /// Parse an event or a group of events
+    pub fn parse_many(s: &str) -> Option<Vec<Self>> {
+        match s.parse() {
+            Ok(event) => Some(vec![event]),
+            Err(_) => Self::group(s),
+        }
+    }
+}
+
+impl FromStr for LogEvent {
+    type Err = std::io::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "MemberJoin" => Self::MemberJoin,
+            "MemberLeave" => Self::MemberLeave,
+            "RankChange" => Self::RankChange,
+            "NameChange" => Self::NameChange,
+            "RankExpire" => Self::RankExpire,
+            "LevelUp" => Self::LevelUp,
+            "GuildChange" => Self::GuildChange,
+            "Xp" => Self::Xp,
+            "PlayerJoin" => Self::PlayerJoin,
+            "PlayerLeave" => Self::PlayerLeave,
+            "PlayerMove" => Self::PlayerMove,
+            _ => return ioerr!("Failed to parse '{}' as LogEvent", s),
+        })
+    }
+}
+
+impl_debug_display!(LogEvent);
+
+/// A map between log channels and the events they subscribed to
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(transparent)]
+pub struct LogSubscriptions {
+    map: HashMap<u64, HashSet<LogEvent>>,
+}
+
+impl LogSubscriptions {
+    /// Get the events a channel subscribed to
+    pub fn events(&self, channel: u64) -> Option<&HashSet<LogEvent>> {
+        self.map.get(&channel)
+    }
+
+    /// Get the channels that subscribed to any event
+    pub fn channels(&self) -> Keys<'_, u64, HashSet<LogEvent>> {
+        self.map.keys()
+    }
+
+    /// Get the log channels and the events they subscribed to
+    pub fn iter(&self) -> impl Iterator<Item = (&u64, &HashSet<LogEvent>)> {
+        self.map.iter()
+    }
+
+    /// Get the channels that subscribed to an event
+    pub fn subscribers<'a>(&'a self, event: &'a LogEvent) -> impl Iterator<Item = &'a u64> + 'a {
+        self.map.iter().filter(|(_, events)| events.contains(event)).map(|(channel, _)| channel)
+    }
+
+    /// Check if a channel subscribed to an event
+    pub fn subscribed(&self, channel: u64, event: &LogEvent) -> bool {
+        self.map.get(&channel).is_some_and(|events| events.contains(event))
+    }
+
+    /// Subscribe a channel to an event, returns false if it already is
+    pub fn subscribe(&mut self, channel: u64, event: LogEvent) -> bool {
+        self.map.entry(channel).or_default().insert(event)
+    }
+
+    /// Unsubscribe a channel from an event, returns false if it wasn't subscribed
+    pub fn unsubscribe(&mut self, channel: u64, event: &LogEvent) -> bool {
+        let events = match self.map.get_mut(&channel) {
+            Some(events) => events,
+            None => return false,
+        };
+        let removed = events.remove(event);
+        if events.is_empty() {
+            self.map.remove(&channel);
+        }
+        removed
+    }
+
+    /// Unsubscribe a channel from all events
+    pub fn remove_channel(&mut self, channel: u64) {
+        self.map.remove(&channel);
+    }
+
+    /// Only keep the channels for which `f` returns true
+    pub fn retain(&mut self, mut f: impl FnMut(&u64) -> bool) {
+        self.map.retain(|channel, _| f(channel));
+    }
+}
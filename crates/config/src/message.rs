@@ -0,0 +1,23 @@
+//! Provides [`MessageLimits`], the limits of which messages are counted into message stats
+use serde::{Deserialize, Serialize};
+
+/// Limits on the messages counted into members' message stats, so members spamming for
+/// leaderboard positions don't inflate them.
+///
+/// All messages are counted if there are no limits.
+#[derive(Debug, Serialize, Deserialize, Default, Clone)]
+pub struct MessageLimits {
+    /// Messages shorter than this many characters (ignoring surrounding whitespace) aren't counted
+    pub min_length: Option<usize>,
+    /// Max amount of messages counted per user in a minute
+    pub max_per_minute: Option<u32>,
+    /// If a message with the same content as the user's previous message isn't counted
+    #[serde(default)]
+    pub ignore_repeats: bool,
+    /// If messages sent by bots aren't counted
+    #[serde(default)]
+    pub ignore_bots: bool,
+    /// If messages sent by webhooks aren't counted
+    #[serde(default)]
+    pub ignore_webhooks: bool,
+}
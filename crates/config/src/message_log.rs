@@ -0,0 +1,24 @@
+//! Provides [`MessageLog`], the settings of the member message log
+use serde::{Deserialize, Serialize};
+
+/// Settings of the log of when and where members sent messages, which is used to know when
+/// members were last active in discord
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct MessageLog {
+    /// How long logged messages are kept for, in days
+    pub retention_days: u64,
+}
+
+impl MessageLog {
+    /// How long logged messages are kept for, in seconds
+    pub fn retention(&self) -> i64 {
+        i64::try_from(self.retention_days).unwrap_or(i64::MAX / 86400) * 86400
+    }
+}
+
+impl Default for MessageLog {
+    fn default() -> Self {
+        Self { retention_days: 90 }
+    }
+}
@@ -0,0 +1,97 @@
+//! Versioning of the config file, and migrations of config files written by older versions.
+//!
+//! The config file stores the version of its schema under the "version" key, files without one
+//! are version 0.
+//! When the schema changes in a way that breaks deserialization of older files (ex: renaming a
+//! tag), [`CONFIG_VERSION`] is bumped and a step that converts the previous version is added to
+//! [`MIGRATIONS`].
+use anyhow::{bail, Context, Result};
+use serde_json::{Map, Value};
+
+/// Current version of the config schema
+pub const CONFIG_VERSION: u64 = 2;
+
+/// A step that converts a config of one version to the next version
+type Migration = fn(&mut Value) -> Result<()>;
+
+/// Migration steps, the step at index `i` converts version `i` to version `i + 1`
+const MIGRATIONS: [Migration; CONFIG_VERSION as usize] = [
+    // The version field is introduced, nothing else changed
+    |_| Ok(()),
+    migrate_log_tags,
+];
+
+/// Names of the removed log tags, and the log events their channels are subscribed to instead
+const LOG_TAGS: [(&str, &str); 4] =
+    [("GuildMemberLog", "member"), ("GuildLevelLog", "level"), ("XpLog", "xp"), ("OnlineLog", "online")];
+
+/// Replace the log tags of text channels with log subscriptions to the events the tags logged
+fn migrate_log_tags(config: &mut Value) -> Result<()> {
+    let channels = match config.pointer_mut("/text_channel_tags/map") {
+        Some(channels) => channels.as_object_mut().context("text_channel_tags isn't a json object")?,
+        None => return Ok(()),
+    };
+    let mut subscriptions = Map::new();
+    for (channel, tags) in channels.iter_mut() {
+        let tags = tags.as_array_mut().context("Text channel tags isn't a json array")?;
+        let mut events = Vec::new();
+        tags.retain(|tag| match LOG_TAGS.iter().find(|(name, _)| tag.as_str() == Some(name)) {
+            Some((_, group)) => {
+                let group = crate::log_subscription::LogEvent::group(group).unwrap_or_default();
+                events.extend(group.into_iter().map(|event| Value::from(event.to_string())));
+                false
+            }
+            None => true,
+        });
+        if !events.is_empty() {
+            subscriptions.insert(channel.clone(), Value::Array(events));
+        }
+    }
+    channels.retain(|_, tags| tags.as_array().is_none_or(|tags| !tags.is_empty()));
+    config["log_subscriptions"] = Value::Object(subscriptions);
+    Ok(())
+}
+
+/// Get the version of a config
+pub fn version(config: &Value) -> Result<u64> {
+    match config.get("version") {
+        Some(version) => version.as_u64().context("Config version isn't a positive integer"),
+        None => Ok(0),
+    }
+}
+
+/// Migrate a config to [`CONFIG_VERSION`], returns the version it was at.
+///
+/// Configs of a newer version can't be migrated, as they may not be understood.
+/// ```
+/// # use config::migration::{migrate, CONFIG_VERSION};
+/// let mut config = serde_json::json!({ "channel_tags": {} });
+/// assert_eq!(migrate(&mut config).unwrap(), 0);
+/// assert_eq!(config["version"], CONFIG_VERSION);
+///
+/// let mut config = serde_json::json!({ "version": CONFIG_VERSION + 1 });
+/// assert!(migrate(&mut config).is_err());
+///
+/// // Log tags are replaced by log subscriptions
+/// let mut config = serde_json::json!({
+///     "version": 1,
+///     "text_channel_tags": { "map": { "1": ["XpLog", "Summary"], "2": ["GuildLevelLog"] } }
+/// });
+/// assert_eq!(migrate(&mut config).unwrap(), 1);
+/// assert_eq!(config["text_channel_tags"]["map"], serde_json::json!({ "1": ["Summary"] }));
+/// assert_eq!(config["log_subscriptions"], serde_json::json!({ "1": ["Xp"], "2": ["LevelUp"] }));
+/// ```
+pub fn migrate(config: &mut Value) -> Result<u64> {
+    if !config.is_object() {
+        bail!("Config isn't a json object");
+    }
+    let from = version(config)?;
+    if from > CONFIG_VERSION {
+        bail!("Config version {} is newer than the supported version {}", from, CONFIG_VERSION);
+    }
+    for (version, step) in MIGRATIONS.iter().enumerate().skip(from as usize) {
+        step(config).with_context(|| format!("Failed to migrate config from version {}", version))?;
+    }
+    config["version"] = CONFIG_VERSION.into();
+    Ok(from)
+}
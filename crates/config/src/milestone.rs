@@ -0,0 +1,35 @@
+//! Provides [`Milestones`], the thresholds of guild milestone announcements
+use serde::{Deserialize, Serialize};
+
+/// Steps at which guild milestones are announced, a milestone is reached every time a value
+/// reaches a multiple of its step.
+///
+/// Milestones without a step are not announced.
+#[derive(Debug, Serialize, Deserialize, Default, Clone)]
+pub struct Milestones {
+    /// Step of the in-game guild member count
+    pub guild_members: Option<u64>,
+    /// Step of the amount of members in the member database
+    pub members: Option<u64>,
+    /// Step of the guild level
+    pub guild_level: Option<u64>,
+}
+
+/// Get the highest milestone that is reached when a value increases from `old` to `new`.
+/// ```
+/// # use config::milestone::reached;
+/// assert!(reached(Some(10), 29, 30) == Some(30));
+/// assert!(reached(Some(10), 29, 41) == Some(40));
+/// assert!(reached(Some(10), 30, 31).is_none());
+/// assert!(reached(Some(10), 31, 30).is_none());
+/// assert!(reached(None, 29, 30).is_none());
+/// ```
+pub fn reached(step: Option<u64>, old: u64, new: u64) -> Option<u64> {
+    let step = step.filter(|step| *step > 0)?;
+    let milestone = new / step * step;
+    if milestone > old && milestone > 0 {
+        Some(milestone)
+    } else {
+        None
+    }
+}
@@ -0,0 +1,15 @@
+//! Provides [`OnlineLimits`], the limits of how much wynncraft online time is counted
+use serde::{Deserialize, Serialize};
+
+/// Limits on the wynncraft online time counted into members' online stats, so players idling on
+/// the server (ex: in housing) don't inflate them.
+///
+/// Online time is counted in full if there are no limits.
+#[derive(Debug, Serialize, Deserialize, Default, Clone)]
+pub struct OnlineLimits {
+    /// Max online time counted per player each day (UTC), in seconds
+    pub daily_cap: Option<u64>,
+    /// If a player stays on the same world without contributing guild xp for longer than this
+    /// (in seconds), the rest of their stay isn't counted until they change world or contribute.
+    pub afk_after: Option<u64>,
+}
@@ -0,0 +1,18 @@
+//! Provides [`PromotionVotes`], the settings of promotion votes
+use serde::{Deserialize, Serialize};
+
+/// Settings of the votes held by staff members to change a member's rank
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct PromotionVotes {
+    /// How long a vote stays open, in seconds
+    pub window: u64,
+    /// Least amount of staff members that need to approve the rank change for it to pass
+    pub min_approvals: u64,
+}
+
+impl Default for PromotionVotes {
+    fn default() -> Self {
+        Self { window: 86400, min_approvals: 3 }
+    }
+}
@@ -0,0 +1,84 @@
+//! Provides [`RankChannel`], the settings of a channel that is only visible to members of some
+//! ranks
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use serenity::model::permissions::Permissions;
+
+use util::ioerr;
+
+/// All variants of [`PermissionTemplate`]
+pub const PERMISSION_TEMPLATES: [PermissionTemplate; 3] =
+    [PermissionTemplate::Read, PermissionTemplate::Chat, PermissionTemplate::Voice];
+
+/// Set of permissions granted to the members that can access a rank channel
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PermissionTemplate {
+    /// Can see the channel and read its history
+    Read,
+    /// Can read, send messages and react in the channel
+    #[default]
+    Chat,
+    /// Can see, connect and speak in a voice channel
+    Voice,
+}
+
+impl PermissionTemplate {
+    /// Get the permissions of the template
+    /// ```
+    /// use config::rank_channel::PermissionTemplate;
+    /// use serenity::model::permissions::Permissions;
+    ///
+    /// assert!(PermissionTemplate::Chat.permissions().contains(Permissions::SEND_MESSAGES));
+    /// assert!(!PermissionTemplate::Read.permissions().contains(Permissions::SEND_MESSAGES));
+    /// ```
+    pub fn permissions(&self) -> Permissions {
+        match self {
+            Self::Read => Permissions::VIEW_CHANNEL | Permissions::READ_MESSAGE_HISTORY,
+            Self::Chat => {
+                Permissions::VIEW_CHANNEL
+                    | Permissions::READ_MESSAGE_HISTORY
+                    | Permissions::SEND_MESSAGES
+                    | Permissions::ADD_REACTIONS
+            }
+            Self::Voice => Permissions::VIEW_CHANNEL | Permissions::CONNECT | Permissions::SPEAK,
+        }
+    }
+}
+
+impl FromStr for PermissionTemplate {
+    type Err = std::io::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s.to_lowercase().as_str() {
+            "read" => Self::Read,
+            "chat" => Self::Chat,
+            "voice" => Self::Voice,
+            _ => return ioerr!("Failed to parse '{}' as PermissionTemplate", s),
+        })
+    }
+}
+
+impl fmt::Display for PermissionTemplate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Read => write!(f, "Read"),
+            Self::Chat => write!(f, "Chat"),
+            Self::Voice => write!(f, "Voice"),
+        }
+    }
+}
+
+/// A channel only visible to members of `min_rank` and above.
+///
+/// Access is granted through permission overwrites of the members, which are synced when their
+/// rank changes. The channel itself should be hidden from everyone else.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct RankChannel {
+    /// Name of the lowest member rank that can access the channel, ex: "Pilot"
+    pub min_rank: String,
+    /// Permissions granted to the members that can access the channel
+    #[serde(default)]
+    pub template: PermissionTemplate,
+}
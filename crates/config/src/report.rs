@@ -0,0 +1,44 @@
+//! Provides [`SendReport`], the outcome of sending a message to tagged channels
+use serenity::model::error::Error as ModelError;
+use serenity::Error as SerenityError;
+
+/// Amount of consecutive permanent failures after which a channel gets untagged
+pub const MAX_PERMANENT_FAILURES: u32 = 3;
+
+/// Failure of sending a message to a channel
+#[derive(Debug)]
+pub struct SendFailure {
+    pub channel_id: u64,
+    pub error: anyhow::Error,
+    /// If the failure won't go away by retrying, ex: the channel is deleted or the bot lacks
+    /// permissions to send messages in it.
+    pub permanent: bool,
+}
+
+/// Outcome of sending a message to all the channels with a tag
+#[derive(Debug, Default)]
+pub struct SendReport {
+    /// Channels the message is sent to
+    pub sent: Vec<u64>,
+    /// Channels the message failed to be sent to
+    pub failures: Vec<SendFailure>,
+    /// Channels that reached [`MAX_PERMANENT_FAILURES`] and should be untagged
+    pub stale: Vec<u64>,
+}
+
+impl SendReport {
+    /// Checks if the message is sent to every channel
+    pub fn is_ok(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
+
+/// Checks if an error from sending a message is caused by the channel being deleted or the bot
+/// lacking permissions, which retrying won't fix.
+pub fn is_permanent(why: &SerenityError) -> bool {
+    match why {
+        SerenityError::Http(why) => matches!(why.status_code().map(|code| code.as_u16()), Some(403 | 404)),
+        SerenityError::Model(ModelError::InvalidPermissions(_)) => true,
+        _ => false,
+    }
+}
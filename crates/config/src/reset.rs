@@ -0,0 +1,62 @@
+//! Provides [`WeeklyReset`], the settings of when the weekly reset happens
+use std::time::Duration;
+
+use anyhow::{anyhow, bail, Result};
+use chrono::{FixedOffset, Weekday};
+use serde::{Deserialize, Serialize};
+
+use event::timer::WeeklyResetTime;
+
+/// Time of the week the weekly stats are reset at, and if staff has to confirm the reset.
+///
+/// A report of the week is made before every reset, and posted to [`TextChannelTag::WeeklyReport`]
+/// channels.
+/// If `confirm` is enabled, staff can confirm or skip the reset via the buttons under the report,
+/// and the reset proceeds automatically after the grace window.
+///
+/// [`TextChannelTag::WeeklyReport`]: crate::tag::TextChannelTag::WeeklyReport
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct WeeklyReset {
+    /// Day of the week, ex: "Sun" or "sunday"
+    pub day: String,
+    /// Hour of the day, from 0 to 23
+    pub hour: u32,
+    /// Offset from UTC of the timezone `day` and `hour` are in, in minutes
+    pub utc_offset: i32,
+    /// If the reset waits for staff confirmation
+    pub confirm: bool,
+    /// Minutes the reset waits for staff confirmation, before proceeding automatically
+    pub grace_minutes: u64,
+}
+
+impl Default for WeeklyReset {
+    fn default() -> Self {
+        Self { day: "Sun".to_string(), hour: 0, utc_offset: 0, confirm: false, grace_minutes: 60 }
+    }
+}
+
+impl WeeklyReset {
+    /// Parse the settings into a [`WeeklyResetTime`]
+    pub fn time(&self) -> Result<WeeklyResetTime> {
+        let day: Weekday = self.day.parse().map_err(|_| anyhow!("Invalid day of the week '{}'", self.day))?;
+        if self.hour > 23 {
+            bail!("Invalid hour of the day '{}'", self.hour);
+        }
+        let offset = self
+            .utc_offset
+            .checked_mul(60)
+            .and_then(FixedOffset::east_opt)
+            .ok_or_else(|| anyhow!("Invalid utc offset '{}'", self.utc_offset))?;
+        Ok(WeeklyResetTime { day, hour: self.hour, offset })
+    }
+
+    /// Get how long the reset waits for staff confirmation, `None` if it doesn't
+    pub fn grace(&self) -> Option<Duration> {
+        if self.confirm {
+            Some(Duration::from_secs(self.grace_minutes * 60))
+        } else {
+            None
+        }
+    }
+}
@@ -0,0 +1,26 @@
+//! Provides [`StatAudit`], the settings of the stat increment audit log
+use serde::{Deserialize, Serialize};
+
+/// Settings of the audit log of stat increments, which records where each increment of the
+/// message, voice, stream, online and xp stats came from, so disputed stats can be looked into
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct StatAudit {
+    /// If stat increments are recorded, this is meant for debugging as it grows the database fast
+    pub enabled: bool,
+    /// How long recorded increments are kept for, in days
+    pub retention_days: u64,
+}
+
+impl StatAudit {
+    /// How long recorded increments are kept for, in seconds
+    pub fn retention(&self) -> i64 {
+        i64::try_from(self.retention_days).unwrap_or(i64::MAX / 86400) * 86400
+    }
+}
+
+impl Default for StatAudit {
+    fn default() -> Self {
+        Self { enabled: false, retention_days: 14 }
+    }
+}
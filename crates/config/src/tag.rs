@@ -62,17 +62,25 @@ use serde::{Deserialize, Serialize};
 use util::{impl_debug_display, ioerr};
 
 /// All variants of [`ChannelTag`]
-pub const CHANNEL_TAGS: [ChannelTag; 1] = [ChannelTag::NoTrack];
+pub const CHANNEL_TAGS: [ChannelTag; 2] = [ChannelTag::NoTrack, ChannelTag::WarVoice];
 /// All variants of [`TextChannelTag`]
-pub const TEXT_CHANNEL_TAGS: [TextChannelTag; 5] = [
-    TextChannelTag::GuildMemberLog,
-    TextChannelTag::GuildLevelLog,
-    TextChannelTag::XpLog,
-    TextChannelTag::OnlineLog,
+pub const TEXT_CHANNEL_TAGS: [TextChannelTag; 13] = [
     TextChannelTag::Summary,
+    TextChannelTag::Milestone,
+    TextChannelTag::XpReport,
+    TextChannelTag::PromotionVote,
+    TextChannelTag::Intel,
+    TextChannelTag::WeeklyReport,
+    TextChannelTag::Suggestion,
+    TextChannelTag::XpCorrection,
+    TextChannelTag::GuildGoal,
+    TextChannelTag::LinkConflict,
+    TextChannelTag::Recruitment,
+    TextChannelTag::TicketLog,
+    TextChannelTag::Waitlist,
 ];
 /// All variants of [`UserTag`]
-pub const USER_TAGS: [UserTag; 2] = [UserTag::NoNickUpdate, UserTag::NoRoleUpdate];
+pub const USER_TAGS: [UserTag; 3] = [UserTag::NoNickUpdate, UserTag::NoRoleUpdate, UserTag::NoMessageTrack];
 
 /// Trait for objects that can behave as tags.
 pub trait Tag: Eq + Hash + FromStr + Display + Clone {
@@ -141,6 +149,19 @@ impl<K: Eq + Hash + Clone, T: Tag> TagMap<K, T> {
     pub fn remove_all(&mut self, obj: &K) {
         self.map.remove(obj);
     }
+
+    /// Remove a tag from all objects
+    pub fn remove_tag(&mut self, tag: &T) {
+        self.map.retain(|_, tags| {
+            tags.remove(tag);
+            !tags.is_empty()
+        });
+    }
+
+    /// Only keep the objects for which `f` returns true, given the object and its tags
+    pub fn retain(&mut self, mut f: impl FnMut(&K, &HashSet<T>) -> bool) {
+        self.map.retain(|obj, tags| f(obj, tags));
+    }
 }
 
 impl<K: Eq + Hash + Clone, T: Tag> Default for TagMap<K, T> {
@@ -157,6 +178,8 @@ pub enum UserTag {
     NoNickUpdate,
     /// Bot won't update the roles of the tagged.
     NoRoleUpdate,
+    /// Messages of the tagged aren't counted into message stats.
+    NoMessageTrack,
 }
 
 impl Tag for UserTag {
@@ -164,6 +187,7 @@ impl Tag for UserTag {
         match self {
             Self::NoNickUpdate => "Nickname won't be automatically updated",
             Self::NoRoleUpdate => "Roles won't be automatically updated",
+            Self::NoMessageTrack => "Messages aren't counted into message stats",
         }
     }
 }
@@ -175,6 +199,7 @@ impl FromStr for UserTag {
         Ok(match s {
             "NoNickUpdate" => Self::NoNickUpdate,
             "NoRoleUpdate" => Self::NoRoleUpdate,
+            "NoMessageTrack" => Self::NoMessageTrack,
             _ => return ioerr!("Failed to parse '{}' as UserTag", s),
         })
     }
@@ -187,12 +212,17 @@ impl_debug_display!(UserTag);
 pub enum ChannelTag {
     /// Bot won't track statistics in the tagged channel
     NoTrack,
+    /// Bot announces users joining the tagged voice channel, see [`Config::war_voice`]
+    ///
+    /// [`Config::war_voice`]: crate::Config::war_voice
+    WarVoice,
 }
 
 impl Tag for ChannelTag {
     fn describe(&self) -> &str {
         match self {
             Self::NoTrack => "Statistics won't be tracked in this channel",
+            Self::WarVoice => "Users joining this voice channel are announced in its linked text channel",
         }
     }
 }
@@ -203,6 +233,7 @@ impl FromStr for ChannelTag {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         Ok(match s {
             "NoTrack" => Self::NoTrack,
+            "WarVoice" => Self::WarVoice,
             _ => return ioerr!("Failed to parse '{}' as ChannelTag", s),
         })
     }
@@ -211,28 +242,61 @@ impl FromStr for ChannelTag {
 impl_debug_display!(ChannelTag);
 
 /// Tags to be attached to a text channel
+///
+/// Log channels aren't tagged, instead they subscribe to log events, see [`Config::log_subscriptions`].
+///
+/// [`Config::log_subscriptions`]: crate::Config::log_subscriptions
 #[derive(Debug, Serialize, Deserialize, Hash, Eq, PartialEq, Clone)]
 pub enum TextChannelTag {
-    /// Bot logs guild member events in tagged channel
-    GuildMemberLog,
-    /// Bot logs guild level events in tagged channel
-    GuildLevelLog,
-    /// Bot logs guild member xp contribution events in tagged channel
-    XpLog,
-    /// Bot logs wynncraft player online status events in tagged channel
-    OnlineLog,
-    /// Bot logs weekly stat summaries in tagged channel
+    /// Bot logs daily and weekly stat summaries in tagged channel
     Summary,
+    /// Bot announces guild milestones in tagged channel
+    Milestone,
+    /// Bot reports guild members below the weekly xp requirement in tagged channel
+    XpReport,
+    /// Bot holds promotion votes in tagged channel
+    PromotionVote,
+    /// Bot posts the level and member count changes of observed guilds in tagged channel
+    Intel,
+    /// Bot posts the report made before each weekly reset in tagged channel, where staff confirm
+    /// or skip the reset
+    WeeklyReport,
+    /// Messages sent in tagged channel are reposted by the bot as suggestions members vote on
+    Suggestion,
+    /// Bot posts suspicious guild xp contributions in tagged channel, where staff approve or
+    /// discard them
+    XpCorrection,
+    /// Bot posts the progress of guild goals in tagged channel, and celebrates their completion
+    GuildGoal,
+    /// Bot posts guild joins whose member has a discord link that looks stale in tagged channel,
+    /// where staff keep or unlink it
+    LinkConflict,
+    /// Applicants open recruitment tickets with a button in tagged channel, the tickets are
+    /// private threads of it
+    Recruitment,
+    /// Bot posts the transcripts of closed recruitment tickets in tagged channel
+    TicketLog,
+    /// Bot notifies staff in tagged channel when a guild slot opens while there are waitlisted
+    /// recruits
+    Waitlist,
 }
 
 impl Tag for TextChannelTag {
     fn describe(&self) -> &str {
         match self {
-            Self::GuildMemberLog => "Logs guild member join, leave, and rank / ign change",
-            Self::GuildLevelLog => "Logs guild level up",
-            Self::XpLog => "Logs guild member xp contributions",
-            Self::OnlineLog => "Logs player join / leave and world change",
-            Self::Summary => "Stat leaderboards are posted weekly",
+            Self::Summary => "Daily guild stats and weekly stat leaderboards are posted",
+            Self::Milestone => "Announces guild member count and level milestones",
+            Self::XpReport => "Guild members below the weekly xp requirement are reported",
+            Self::PromotionVote => "Staff members vote on promotions in here",
+            Self::Intel => "Level and member count changes of observed guilds are posted",
+            Self::WeeklyReport => "Reports are posted before weekly resets, staff can confirm or skip them",
+            Self::Suggestion => "Messages are reposted as suggestions, members vote on them with buttons",
+            Self::XpCorrection => "Suspicious xp contributions are posted, staff approve or discard them",
+            Self::GuildGoal => "Daily progress of guild goals is posted, and their completion is celebrated",
+            Self::LinkConflict => "Guild joins with a stale discord link are posted, staff keep or unlink it",
+            Self::Recruitment => "Applicants open recruitment tickets in here, as private threads",
+            Self::TicketLog => "Transcripts of closed recruitment tickets are posted",
+            Self::Waitlist => "Staff are notified when a guild slot opens for waitlisted recruits",
         }
     }
 }
@@ -242,14 +306,87 @@ impl FromStr for TextChannelTag {
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         Ok(match s {
-            "GuildMemberLog" => Self::GuildMemberLog,
-            "GuildLevelLog" => Self::GuildLevelLog,
-            "XpLog" => Self::XpLog,
-            "OnlineLog" => Self::OnlineLog,
             "Summary" => Self::Summary,
+            "Milestone" => Self::Milestone,
+            "XpReport" => Self::XpReport,
+            "PromotionVote" => Self::PromotionVote,
+            "Intel" => Self::Intel,
+            "WeeklyReport" => Self::WeeklyReport,
+            "Suggestion" => Self::Suggestion,
+            "XpCorrection" => Self::XpCorrection,
+            "GuildGoal" => Self::GuildGoal,
+            "LinkConflict" => Self::LinkConflict,
+            "Recruitment" => Self::Recruitment,
+            "TicketLog" => Self::TicketLog,
+            "Waitlist" => Self::Waitlist,
             _ => return ioerr!("Failed to parse '{}' as TextChannelTag", s),
         })
     }
 }
 
 impl_debug_display!(TextChannelTag);
+
+/// A tag created at runtime by admins, its definition is in [`Config::custom_tags`].
+///
+/// Unlike the other tags, it has no meaning to the bot itself, it is for grouping objects so
+/// they can be referenced in the config.
+///
+/// [`Config::custom_tags`]: crate::Config::custom_tags
+#[derive(Debug, Serialize, Deserialize, Hash, Eq, PartialEq, Clone)]
+pub struct CustomTag(pub String);
+
+impl Tag for CustomTag {
+    fn describe(&self) -> &str {
+        "Custom tag"
+    }
+}
+
+impl FromStr for CustomTag {
+    type Err = std::io::Error;
+
+    /// Custom tag names can only contain ascii letters, digits, `_` and `-`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.is_empty() || !s.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-') {
+            return ioerr!("Invalid custom tag name '{}'", s);
+        }
+        Ok(Self(s.to_string()))
+    }
+}
+
+impl Display for CustomTag {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// What kind of objects a custom tag can be attached to
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone, Copy)]
+pub enum TagTarget {
+    /// Discord users and roles
+    User,
+    /// Discord channels and categories
+    Channel,
+}
+
+impl FromStr for TagTarget {
+    type Err = std::io::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s.to_lowercase().as_str() {
+            "user" => Self::User,
+            "channel" => Self::Channel,
+            _ => return ioerr!("Failed to parse '{}' as TagTarget", s),
+        })
+    }
+}
+
+impl_debug_display!(TagTarget);
+
+/// Definition of a [`CustomTag`]
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CustomTagDef {
+    /// What the tag means
+    pub description: String,
+    /// What kind of objects the tag can be attached to
+    pub target: TagTarget,
+}
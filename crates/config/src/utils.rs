@@ -4,7 +4,7 @@ use std::str::FromStr;
 
 use util::ioerr;
 
-use crate::tag::{ChannelTag, Tag, TextChannelTag, UserTag};
+use crate::tag::{ChannelTag, CustomTag, Tag, TextChannelTag, UserTag};
 
 /// All possible tags.
 ///
@@ -22,12 +22,17 @@ use crate::tag::{ChannelTag, Tag, TextChannelTag, UserTag};
 /// [`FromStr`] of [`Tags`] works by simply trying to parse the string into each tag types using
 /// their own [`FromStr`], and return the value if it succeeds.
 ///
+/// Custom tags aren't parsed by [`FromStr`], as they are only known by the config, use
+/// [`Config::parse_tag`] instead.
+///
 /// [`FromStr`]: std::str::FromStr
+/// [`Config::parse_tag`]: crate::Config::parse_tag
 #[derive(Debug, Eq, Hash, Clone, PartialEq)]
 pub enum Tags {
     Channel(ChannelTag),
     TextChannel(TextChannelTag),
     User(UserTag),
+    Custom(CustomTag),
 }
 
 impl Tag for Tags {
@@ -36,6 +41,7 @@ impl Tag for Tags {
             Self::Channel(t) => t.describe(),
             Self::TextChannel(t) => t.describe(),
             Self::User(t) => t.describe(),
+            Self::Custom(t) => t.describe(),
         }
     }
 }
@@ -66,6 +72,7 @@ impl fmt::Display for Tags {
             Self::Channel(t) => t.fmt(f),
             Self::TextChannel(t) => t.fmt(f),
             Self::User(t) => t.fmt(f),
+            Self::Custom(t) => t.fmt(f),
         }
     }
 }
@@ -0,0 +1,138 @@
+//! Provides [`VoiceTracking`], the settings of which voice chat states accrue voice time
+use serde::{Deserialize, Serialize};
+
+/// How the voice time of muted users is counted
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone, Copy)]
+pub enum MutedPolicy {
+    /// Muted users don't accrue voice time
+    Exclude,
+    /// Muted users accrue voice time at [`VoiceTracking::muted_weight`]
+    Reduced,
+    /// Muted users accrue voice time in full
+    Full,
+}
+
+/// Role of a user in a stage channel
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub enum StageRole {
+    Speaker,
+    Audience,
+}
+
+/// What a user is doing in a voice channel, which determines how their voice time is counted
+#[derive(Debug, Default, Eq, PartialEq, Clone, Copy)]
+pub struct VoiceActivity {
+    /// If the user is muted by the server
+    pub mute: bool,
+    /// If the user is deafened by the server
+    pub deaf: bool,
+    /// If the user has their camera on
+    pub video: bool,
+    /// If the user is sharing their screen
+    pub stream: bool,
+    /// Role of the user if they are in a stage channel
+    pub stage: Option<StageRole>,
+}
+
+/// Multipliers of the voice time counted while users are in a mode, in percentages.
+///
+/// Multipliers of all the modes a user is in are applied, ex: a stage speaker with their camera
+/// on accrues voice time at `video * stage_speaker / 100` percent.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct VoiceMultipliers {
+    /// Multiplier while the camera is on
+    pub video: u32,
+    /// Multiplier while sharing the screen
+    pub stream: u32,
+    /// Multiplier while being a speaker in a stage channel
+    pub stage_speaker: u32,
+    /// Multiplier while being in the audience of a stage channel
+    pub stage_audience: u32,
+}
+
+impl Default for VoiceMultipliers {
+    fn default() -> Self {
+        Self { video: 100, stream: 100, stage_speaker: 100, stage_audience: 100 }
+    }
+}
+
+/// Settings of which voice chat states accrue voice time.
+///
+/// Deafened users never accrue voice time, as they aren't taking part in the voice chat.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct VoiceTracking {
+    /// How the voice time of users that are muted but still listening is counted
+    pub muted: MutedPolicy,
+    /// Percentage of voice time counted for muted users, if `muted` is [`MutedPolicy::Reduced`]
+    pub muted_weight: u32,
+    /// Multipliers of the voice time counted while users are in a mode
+    pub multipliers: VoiceMultipliers,
+}
+
+impl VoiceTracking {
+    /// Get the percentage of voice time counted for a user doing `activity`, `None` if it
+    /// doesn't accrue voice time.
+    ///
+    /// ```
+    /// use config::voice::{MutedPolicy, StageRole, VoiceActivity, VoiceTracking};
+    ///
+    /// let mut policy = VoiceTracking::default();
+    /// let mut activity = VoiceActivity::default();
+    /// assert_eq!(policy.weight(&activity), Some(100));
+    /// activity.mute = true;
+    /// assert_eq!(policy.weight(&activity), None);
+    ///
+    /// policy.muted = MutedPolicy::Reduced;
+    /// policy.muted_weight = 50;
+    /// assert_eq!(policy.weight(&activity), Some(50));
+    /// activity.deaf = true;
+    /// assert_eq!(policy.weight(&activity), None);
+    ///
+    /// policy.muted = MutedPolicy::Full;
+    /// activity = VoiceActivity { deaf: true, ..Default::default() };
+    /// assert_eq!(policy.weight(&activity), None);
+    ///
+    /// policy.multipliers.video = 150;
+    /// policy.multipliers.stage_speaker = 200;
+    /// policy.multipliers.stage_audience = 0;
+    /// activity = VoiceActivity { video: true, stage: Some(StageRole::Speaker), ..Default::default() };
+    /// assert_eq!(policy.weight(&activity), Some(300));
+    /// activity.stage = Some(StageRole::Audience);
+    /// assert_eq!(policy.weight(&activity), None);
+    /// ```
+    pub fn weight(&self, activity: &VoiceActivity) -> Option<u32> {
+        if activity.deaf {
+            return None;
+        }
+        let mut weight = match (activity.mute, self.muted) {
+            (false, _) | (true, MutedPolicy::Full) => 100,
+            (true, MutedPolicy::Reduced) => self.muted_weight.min(100),
+            (true, MutedPolicy::Exclude) => return None,
+        };
+
+        let multipliers = &self.multipliers;
+        let modes = [
+            (activity.video, multipliers.video),
+            (activity.stream, multipliers.stream),
+            (activity.stage == Some(StageRole::Speaker), multipliers.stage_speaker),
+            (activity.stage == Some(StageRole::Audience), multipliers.stage_audience),
+        ];
+        for (_, multiplier) in modes.into_iter().filter(|(active, _)| *active) {
+            weight = weight.saturating_mul(multiplier) / 100;
+        }
+
+        if weight == 0 {
+            None
+        } else {
+            Some(weight)
+        }
+    }
+}
+
+impl Default for VoiceTracking {
+    fn default() -> Self {
+        Self { muted: MutedPolicy::Exclude, muted_weight: 50, multipliers: VoiceMultipliers::default() }
+    }
+}
@@ -0,0 +1,69 @@
+//! Provides [`WarVoice`], the settings of announcing users joining [`ChannelTag::WarVoice`]
+//! channels
+//!
+//! [`ChannelTag::WarVoice`]: crate::tag::ChannelTag::WarVoice
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+/// Settings of announcing users joining [`ChannelTag::WarVoice`] channels, so the others know
+/// how many members are assembled for a war.
+///
+/// [`ChannelTag::WarVoice`]: crate::tag::ChannelTag::WarVoice
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct WarVoice {
+    /// Text channel the joins of each voice channel are announced in, keyed by the voice channel.
+    /// Joins of voice channels that aren't linked are announced in the voice channel's own chat.
+    pub links: HashMap<u64, u64>,
+    /// Least amount of seconds between two announcements of the same voice channel
+    pub cooldown: u64,
+}
+
+impl Default for WarVoice {
+    fn default() -> Self {
+        Self { links: HashMap::new(), cooldown: 300 }
+    }
+}
+
+impl WarVoice {
+    /// Get the text channel the joins of a voice channel are announced in
+    pub fn text_channel(&self, voice_channel: u64) -> u64 {
+        self.links.get(&voice_channel).copied().unwrap_or(voice_channel)
+    }
+}
+
+/// Time of the last announcement of each voice channel, for enforcing [`WarVoice::cooldown`]
+#[derive(Debug, Default)]
+pub struct Cooldowns {
+    last: HashMap<u64, i64>,
+}
+
+impl Cooldowns {
+    /// Create an empty set of cooldowns
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Check if a voice channel can be announced at unix timestamp `now`, and if so, start its
+    /// cooldown.
+    /// ```
+    /// use config::war_voice::Cooldowns;
+    ///
+    /// let mut cooldowns = Cooldowns::new();
+    /// assert!(cooldowns.try_start(1, 1000, 300));
+    /// assert!(!cooldowns.try_start(1, 1299, 300));
+    /// assert!(cooldowns.try_start(2, 1299, 300));
+    /// assert!(cooldowns.try_start(1, 1300, 300));
+    /// ```
+    pub fn try_start(&mut self, channel_id: u64, now: i64, cooldown: u64) -> bool {
+        let cooldown = i64::try_from(cooldown).unwrap_or(i64::MAX);
+        if let Some(last) = self.last.get(&channel_id) {
+            if now.saturating_sub(*last) < cooldown {
+                return false;
+            }
+        }
+        self.last.insert(channel_id, now);
+        true
+    }
+}
@@ -0,0 +1,86 @@
+//! Events of discord, which are only available with the `discord` feature
+use std::sync::Arc;
+
+use serenity::client::{Cache, Context};
+use serenity::http::{CacheHttp, Http};
+use serenity::model::channel::{GuildChannel, Message, Reaction};
+use serenity::model::event::{InviteCreateEvent, InviteDeleteEvent};
+use serenity::model::guild::{Guild, Member, Role};
+use serenity::model::id::{GuildId, RoleId};
+use serenity::model::user::User;
+use serenity::model::voice::VoiceState;
+
+use crate::signal;
+
+/// Useful data to be broadcasted alongside discord events
+#[derive(Debug, Clone)]
+pub struct DiscordContext {
+    pub http: Arc<Http>,
+    pub cache: Arc<Cache>,
+    pub main_guild: Arc<Guild>,
+    /// Id of the shard that received the event
+    pub shard_id: u64,
+}
+
+impl DiscordContext {
+    /// Creates a new event context, returns `None` if the main guild isn't cached yet, which
+    /// happens until the shard it is on is ready.
+    pub fn new(ctx: &Context, main_guild_id: u64) -> Option<Self> {
+        let http = ctx.http.clone();
+        let cache = ctx.cache.clone();
+        let main_guild = Arc::new(cache.guild(main_guild_id)?);
+        Some(Self { http, cache, main_guild, shard_id: ctx.shard_id })
+    }
+}
+
+impl CacheHttp for DiscordContext {
+    fn http(&self) -> &Http {
+        &self.http
+    }
+
+    fn cache(&self) -> Option<&Arc<Cache>> {
+        Some(&self.cache)
+    }
+}
+
+/// Discord events
+#[derive(Debug, Clone)]
+pub enum DiscordEvent {
+    /// The bot is ready
+    Ready,
+    /// A message was send
+    Message { message: Box<Message> },
+    /// A reaction was added to a message
+    ReactionAdd { reaction: Reaction },
+    /// A reaction was removed from a message
+    ReactionRemove { reaction: Reaction },
+    /// User joined a vc
+    VoiceJoin { state: VoiceState },
+    /// User left a vc
+    VoiceLeave { old_state: VoiceState },
+    /// User changed its voice state (ex: mute/unmute)
+    VoiceChange {
+        old_state: Box<VoiceState>,
+        new_state: Box<VoiceState>,
+    },
+    /// Guild channel deleted
+    ChannelDelete { channel: GuildChannel },
+    /// Guild member updated (ex: nick change)
+    MemberUpdate { old: Option<Member>, new: Member },
+    /// Member joins the guild
+    MemberJoin { member: Member },
+    /// Member left the guild
+    MemberLeave {
+        user: User,
+        guild_id: GuildId,
+        member: Option<Member>,
+    },
+    /// Role deleted
+    RoleDelete { id: RoleId, role: Option<Role> },
+    /// Invite created
+    InviteCreate { invite: InviteCreateEvent },
+    /// Invite deleted
+    InviteDelete { invite: InviteDeleteEvent },
+}
+
+signal!(DiscordSignal, DiscordRecv, (DiscordContext, DiscordEvent));
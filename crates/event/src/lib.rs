@@ -28,18 +28,13 @@
 //!     }
 //! }
 //! ```
+#[cfg(feature = "discord")]
+mod discord;
 #[warn(missing_docs, missing_debug_implementations)]
 pub mod timer;
 
-use std::sync::Arc;
-
-use serenity::client::{Cache, Context};
-use serenity::http::{CacheHttp, Http};
-use serenity::model::channel::{GuildChannel, Message};
-use serenity::model::guild::{Guild, Member, Role};
-use serenity::model::id::{GuildId, RoleId};
-use serenity::model::user::User;
-use serenity::model::voice::VoiceState;
+#[cfg(feature = "discord")]
+pub use discord::{DiscordContext, DiscordEvent, DiscordRecv, DiscordSignal};
 
 #[macro_export]
 /// Create an event signal and receiver for an event type
@@ -91,65 +86,3 @@ macro_rules! signal {
         }
     };
 }
-
-/// Useful data to be broadcasted alongside discord events
-#[derive(Debug, Clone)]
-pub struct DiscordContext {
-    pub http: Arc<Http>,
-    pub cache: Arc<Cache>,
-    pub main_guild: Arc<Guild>,
-}
-
-impl DiscordContext {
-    /// Creates a new event context
-    pub fn new(ctx: &Context, main_guild_id: u64) -> Self {
-        let http = ctx.http.clone();
-        let cache = ctx.cache.clone();
-        let main_guild = Arc::new(cache.guild(main_guild_id).expect("Unable to find main guild"));
-        Self { http, cache, main_guild }
-    }
-}
-
-impl CacheHttp for DiscordContext {
-    fn http(&self) -> &Http {
-        &self.http
-    }
-
-    fn cache(&self) -> Option<&Arc<Cache>> {
-        Some(&self.cache)
-    }
-}
-
-/// Discord events
-#[derive(Debug, Clone)]
-pub enum DiscordEvent {
-    /// The bot is ready
-    Ready,
-    /// A message was send
-    Message { message: Box<Message> },
-    /// User joined a vc
-    VoiceJoin { state: VoiceState },
-    /// User left a vc
-    VoiceLeave { old_state: VoiceState },
-    /// User changed its voice state (ex: mute/unmute)
-    VoiceChange {
-        old_state: Box<VoiceState>,
-        new_state: Box<VoiceState>,
-    },
-    /// Guild channel deleted
-    ChannelDelete { channel: GuildChannel },
-    /// Guild member updated (ex: nick change)
-    MemberUpdate { old: Option<Member>, new: Member },
-    /// Member joins the guild
-    MemberJoin { member: Member },
-    /// Member left the guild
-    MemberLeave {
-        user: User,
-        guild_id: GuildId,
-        member: Option<Member>,
-    },
-    /// Role deleted
-    RoleDelete { id: RoleId, role: Option<Role> },
-}
-
-signal!(DiscordSignal, DiscordRecv, (DiscordContext, DiscordEvent));
@@ -1,43 +1,134 @@
 //! Provides the [`TimerEvent`] event
+use std::future::Future;
+
+use anyhow::Result;
 use chrono::offset::Utc;
-use chrono::{Datelike, Duration};
-use tracing::info;
+use chrono::{DateTime, Datelike, Duration, FixedOffset, TimeZone, Weekday};
+use tracing::{info, warn};
+use util::task::{RestartPolicy, Spawner};
 
 /// This type of events are broadcasted during specific datetime, allowing the bot to run datetime specific
 /// tasks
 #[derive(Debug, Clone)]
 pub enum TimerEvent {
-    /// Sent at the start of Sunday at UTC time
+    /// Sent at the start of every day at UTC time
+    Daily,
+    /// Sent at the weekly reset time, see [`WeeklyResetTime`]
     Weekly,
 }
 
 crate::signal!(TimerSignal, TimerRecv, TimerEvent);
 
-/// Start the loop for broadcasting [`TimerEvent`]
-pub async fn start_loop(signal: TimerSignal) {
-    tokio::spawn(async move {
-        info!("Starting timer loop");
-
-        loop {
-            // Calculating amount of time until next sunday
-            let now = Utc::now();
-            let until_midnight = (now + Duration::days(1)).date().and_hms(0, 0, 0) - now;
-            let days_util_sunday = now.date().weekday().num_days_from_sunday();
-            let days_util_sunday = i64::try_from(days_util_sunday).expect("Failed to convert u64 to i64");
-            let until_sunday = until_midnight + Duration::days(6 - days_util_sunday);
-            let until_sunday = until_sunday
-                .to_std()
-                .expect("Failed to convert chrono::Duration to std Duration");
-
-            info!(
-                "Currently {}, duration until next utc sunday: {}",
-                now.format("%Y %b %d (%a) %T"),
-                util::string::fmt_second(until_sunday.as_secs().try_into().unwrap())
-            );
-
-            // Wait until next sunday and broadcast a weekly event
-            tokio::time::sleep(until_sunday).await;
-            signal.signal(TimerEvent::Weekly);
+/// Time of the week [`TimerEvent::Weekly`] is sent at
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WeeklyResetTime {
+    /// Day of the week
+    pub day: Weekday,
+    /// Hour of the day, from 0 to 23
+    pub hour: u32,
+    /// Offset from UTC of the timezone `day` and `hour` are in
+    pub offset: FixedOffset,
+}
+
+impl WeeklyResetTime {
+    /// Get the first reset time that is after `now`
+    pub fn next_after(&self, now: DateTime<Utc>) -> DateTime<Utc> {
+        let local = now.with_timezone(&self.offset).naive_local();
+        let days_until = (7 + self.day.num_days_from_monday() - local.weekday().num_days_from_monday()) % 7;
+        let date = local.date() + Duration::days(days_until.into());
+        let reset = self.offset.from_local_datetime(&date.and_hms(self.hour, 0, 0)).unwrap();
+        let reset = reset.with_timezone(&Utc);
+        if reset > now {
+            reset
+        } else {
+            reset + Duration::weeks(1)
+        }
+    }
+}
+
+/// Longest time the weekly timer sleeps before getting the reset time again, so changes to the reset
+/// time are picked up
+const RESET_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60 * 60);
+
+/// Start the loop for broadcasting [`TimerEvent`].
+///
+/// `weekly_reset` is called to get the current weekly reset time, every time the weekly timer
+/// wakes up.
+pub async fn start_loop<F, Fut>(spawner: &impl Spawner, signal: TimerSignal, weekly_reset: F)
+where
+    F: Fn() -> Fut + Clone + Send + Sync + 'static,
+    Fut: Future<Output = Result<WeeklyResetTime>> + Send,
+{
+    let weekly_signal = signal.clone();
+    spawner.spawn("timer (daily)", RestartPolicy::Always, move || {
+        let signal = signal.clone();
+        async move {
+            info!("Starting timer loop");
+
+            loop {
+                // Calculating amount of time until next midnight
+                let now = Utc::now();
+                let tomorrow = (now + Duration::days(1)).date();
+                let until_midnight = tomorrow.and_hms(0, 0, 0) - now;
+                let until_midnight =
+                    until_midnight.to_std().expect("Failed to convert chrono::Duration to std Duration");
+
+                info!(
+                    "Currently {}, duration until next utc midnight: {}",
+                    now.format("%Y %b %d (%a) %T"),
+                    util::string::fmt_second(until_midnight.as_secs().try_into().unwrap())
+                );
+
+                // Wait until next midnight and broadcast a daily event
+                tokio::time::sleep(until_midnight).await;
+                signal.signal(TimerEvent::Daily);
+            }
+        }
+    });
+
+    spawner.spawn("timer (weekly)", RestartPolicy::Always, move || {
+        let weekly_signal = weekly_signal.clone();
+        let weekly_reset = weekly_reset.clone();
+        async move {
+            info!("Starting weekly timer loop");
+
+            // Last valid reset time, used if the current one is invalid
+            let mut last_reset = None;
+            loop {
+                match weekly_reset().await {
+                    Ok(reset) => {
+                        if last_reset != Some(reset) {
+                            info!(?reset, "Weekly reset time");
+                        }
+                        last_reset = Some(reset);
+                    }
+                    Err(why) => warn!("Invalid weekly reset time: {:#}", why),
+                }
+                let reset = match last_reset {
+                    Some(reset) => reset,
+                    None => {
+                        tokio::time::sleep(RESET_CHECK_INTERVAL).await;
+                        continue;
+                    }
+                };
+
+                let now = Utc::now();
+                let until_reset = (reset.next_after(now) - now)
+                    .to_std()
+                    .expect("Failed to convert chrono::Duration to std Duration");
+                if until_reset > RESET_CHECK_INTERVAL {
+                    tokio::time::sleep(RESET_CHECK_INTERVAL).await;
+                    continue;
+                }
+
+                info!(
+                    "Duration until next weekly reset: {}",
+                    util::string::fmt_second(until_reset.as_secs().try_into().unwrap())
+                );
+
+                tokio::time::sleep(until_reset).await;
+                weekly_signal.signal(TimerEvent::Weekly);
+            }
         }
     });
 }
@@ -0,0 +1,74 @@
+//! Benchmarks of computing the weekly stat leaderboards, which are sent on weekly reset.
+//!
+//! Run with `cargo bench -p memberdb`.
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use serenity::client::Cache;
+use tokio::runtime::Runtime;
+
+use memberdb::model::db::Stat;
+use memberdb::model::discord::DiscordId;
+use memberdb::model::guild::GuildRank;
+use memberdb::model::member::MemberRank;
+use memberdb::model::wynn::McId;
+use memberdb::testing::TestDB;
+use memberdb::DB;
+use util::string::StatFormat;
+
+const WEEKLY_STATS: [Stat; 4] = [Stat::WeeklyMessage, Stat::WeeklyVoice, Stat::WeeklyOnline, Stat::WeeklyXp];
+
+/// Create a database of `size` full members that are in the guild, with varying weekly stats
+async fn seeded_db(size: i64) -> DB {
+    let mut builder = TestDB::new();
+    for i in 0..size {
+        let mcid = format!("{:032x}", i);
+        let ign = format!("Player{}", i);
+        builder = builder.full_member(i, &mcid, &ign, MemberRank::Five).guild_member(
+            &mcid,
+            &ign,
+            GuildRank::Recruit,
+        );
+    }
+    let (db, _events) = builder.build().await.unwrap();
+
+    let mut tx = db.begin().await.unwrap();
+    for i in 0..size {
+        let mcid = McId(format!("{:032x}", i));
+        DiscordId(i).update_message(&mut tx, i % 97).await.unwrap();
+        DiscordId(i).update_voice(&mut tx, i % 89 * 60).await.unwrap();
+        mcid.update_activity(&mut tx, i % 83 * 60).await.unwrap();
+        mcid.update_xp(&mut tx, i % 79 * 1000).await.unwrap();
+    }
+    tx.commit().await.unwrap();
+    db
+}
+
+fn weekly_leaderboards(c: &mut Criterion) {
+    let rt = Runtime::new().unwrap();
+    let cache = Cache::default();
+    let mut group = c.benchmark_group("weekly_leaderboards");
+    group.sample_size(20);
+
+    for size in [100, 1000, 5000] {
+        let db = rt.block_on(seeded_db(size));
+        group.bench_with_input(BenchmarkId::new("separate", size), &db, |b, db| {
+            b.to_async(&rt).iter(|| async {
+                for stat in &WEEKLY_STATS {
+                    memberdb::table::stat_leaderboard(&cache, &StatFormat::default(), db, stat, &Vec::new())
+                        .await
+                        .unwrap();
+                }
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("combined", size), &db, |b, db| {
+            b.to_async(&rt).iter(|| async {
+                memberdb::table::stat_leaderboards(&cache, &StatFormat::default(), db, &WEEKLY_STATS)
+                    .await
+                    .unwrap()
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, weekly_leaderboards);
+criterion_main!(benches);
@@ -0,0 +1,3 @@
+-- Add migration script here
+ALTER TABLE member ADD COLUMN rank_expire INTEGER;
+ALTER TABLE member ADD COLUMN rank_prev TEXT;
@@ -0,0 +1,3 @@
+-- Add migration script here
+ALTER TABLE guild ADD COLUMN joined TEXT;
+ALTER TABLE guild ADD COLUMN wars INTEGER NOT NULL DEFAULT 0;
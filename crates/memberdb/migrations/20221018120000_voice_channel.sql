@@ -0,0 +1,7 @@
+-- Add migration script here
+CREATE TABLE voice_channel (
+    discord INTEGER NOT NULL,
+    channel INTEGER NOT NULL,
+    voice INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY (discord, channel)
+);
@@ -0,0 +1,3 @@
+-- Add migration script here
+ALTER TABLE discord ADD COLUMN stream INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE discord ADD COLUMN stream_week INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,14 @@
+-- Add migration script here
+CREATE TABLE daily_stat (
+    id INTEGER PRIMARY KEY CHECK (id = 0),
+    xp INTEGER NOT NULL DEFAULT 0,
+    message INTEGER NOT NULL DEFAULT 0,
+    online_peak INTEGER NOT NULL DEFAULT 0,
+    joins INTEGER NOT NULL DEFAULT 0,
+    leaves INTEGER NOT NULL DEFAULT 0
+);
+INSERT INTO daily_stat (id) VALUES (0);
+
+CREATE TABLE daily_online (
+    id TEXT PRIMARY KEY
+);
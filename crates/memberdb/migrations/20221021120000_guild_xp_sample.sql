@@ -0,0 +1,6 @@
+-- Add migration script here
+CREATE TABLE guild_xp_sample (
+    time INTEGER PRIMARY KEY,
+    level INTEGER NOT NULL,
+    xp REAL NOT NULL
+);
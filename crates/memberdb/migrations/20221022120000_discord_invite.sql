@@ -0,0 +1,6 @@
+-- Add migration script here
+CREATE TABLE discord_invite (
+    id INTEGER PRIMARY KEY NOT NULL UNIQUE,
+    inviter INTEGER,
+    code TEXT NOT NULL
+);
@@ -0,0 +1,9 @@
+-- Add migration script here
+CREATE TABLE xp_history (
+    id TEXT NOT NULL,
+    week INTEGER NOT NULL,
+    xp INTEGER NOT NULL,
+    required INTEGER NOT NULL,
+    streak INTEGER NOT NULL,
+    PRIMARY KEY (id, week)
+);
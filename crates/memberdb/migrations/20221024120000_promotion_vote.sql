@@ -0,0 +1,14 @@
+-- Add migration script here
+CREATE TABLE promotion_vote (
+    id INTEGER PRIMARY KEY NOT NULL,
+    mid INTEGER NOT NULL,
+    caller INTEGER NOT NULL,
+    old_rank TEXT NOT NULL,
+    rank TEXT NOT NULL,
+    channel INTEGER NOT NULL,
+    message INTEGER NOT NULL,
+    close INTEGER NOT NULL,
+    approvals INTEGER,
+    rejections INTEGER,
+    passed INTEGER CHECK(passed IN (0,1))
+);
@@ -0,0 +1,9 @@
+-- Add migration script here
+CREATE TABLE stat_reset (
+    id INTEGER PRIMARY KEY NOT NULL,
+    stat TEXT NOT NULL,
+    mid INTEGER,
+    caller INTEGER NOT NULL,
+    time INTEGER NOT NULL,
+    count INTEGER NOT NULL
+);
@@ -0,0 +1,12 @@
+-- Add migration script here
+ALTER TABLE daily_stat ADD COLUMN online_samples INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE daily_stat ADD COLUMN online_sum INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE daily_stat ADD COLUMN online_ratio_sum REAL NOT NULL DEFAULT 0;
+
+CREATE TABLE online_history (
+    id INTEGER PRIMARY KEY NOT NULL,
+    time INTEGER NOT NULL,
+    peak_online INTEGER NOT NULL,
+    avg_online_members REAL NOT NULL,
+    avg_online_ratio REAL NOT NULL
+);
@@ -0,0 +1,10 @@
+-- Add migration script here
+CREATE TABLE message_log (
+    id INTEGER PRIMARY KEY NOT NULL,
+    mid INTEGER NOT NULL,
+    channel INTEGER NOT NULL,
+    time INTEGER NOT NULL
+);
+
+CREATE INDEX message_log_mid_time ON message_log (mid, time);
+CREATE INDEX message_log_time ON message_log (time);
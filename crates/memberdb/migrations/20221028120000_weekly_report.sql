@@ -0,0 +1,5 @@
+-- Add migration script here
+CREATE TABLE weekly_report (
+    time INTEGER PRIMARY KEY NOT NULL,
+    report TEXT NOT NULL
+);
@@ -0,0 +1,23 @@
+-- Add migration script here
+CREATE TABLE weekly_backup (
+    time INTEGER PRIMARY KEY NOT NULL
+);
+
+CREATE TABLE weekly_backup_discord (
+    id INTEGER PRIMARY KEY NOT NULL,
+    message_week INTEGER NOT NULL,
+    voice_week INTEGER NOT NULL,
+    stream_week INTEGER NOT NULL
+);
+
+CREATE TABLE weekly_backup_wynn (
+    id TEXT PRIMARY KEY NOT NULL,
+    activity_week INTEGER NOT NULL,
+    activity_avg INTEGER NOT NULL,
+    activity_avg_range INTEGER NOT NULL
+);
+
+CREATE TABLE weekly_backup_guild (
+    id TEXT PRIMARY KEY NOT NULL,
+    xp_week INTEGER NOT NULL
+);
@@ -0,0 +1,10 @@
+-- Add migration script here
+CREATE TABLE guild_rank_history (
+    id INTEGER PRIMARY KEY NOT NULL,
+    mcid TEXT NOT NULL,
+    old TEXT NOT NULL,
+    new TEXT NOT NULL,
+    time INTEGER NOT NULL
+);
+
+CREATE INDEX guild_rank_history_mcid_time ON guild_rank_history (mcid, time);
@@ -0,0 +1,11 @@
+-- Add migration script here
+CREATE TABLE ign_history (
+    id INTEGER PRIMARY KEY NOT NULL,
+    mcid TEXT NOT NULL,
+    old TEXT NOT NULL,
+    new TEXT NOT NULL,
+    time INTEGER NOT NULL
+);
+
+CREATE INDEX ign_history_mcid_time ON ign_history (mcid, time);
+CREATE INDEX ign_history_old ON ign_history (old COLLATE NOCASE);
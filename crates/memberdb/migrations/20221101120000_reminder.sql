@@ -0,0 +1,12 @@
+-- Add migration script here
+CREATE TABLE reminder (
+    id INTEGER PRIMARY KEY NOT NULL,
+    user INTEGER NOT NULL,
+    channel INTEGER NOT NULL,
+    text TEXT NOT NULL,
+    created INTEGER NOT NULL,
+    due INTEGER NOT NULL
+);
+
+CREATE INDEX reminder_user ON reminder (user);
+CREATE INDEX reminder_due ON reminder (due);
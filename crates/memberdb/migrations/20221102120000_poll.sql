@@ -0,0 +1,18 @@
+-- Add migration script here
+CREATE TABLE poll (
+    id INTEGER PRIMARY KEY NOT NULL,
+    creator INTEGER NOT NULL,
+    channel INTEGER NOT NULL,
+    message INTEGER NOT NULL,
+    question TEXT NOT NULL,
+    close INTEGER NOT NULL,
+    closed INTEGER NOT NULL DEFAULT 0 CHECK(closed IN (0,1))
+);
+
+CREATE TABLE poll_option (
+    poll INTEGER NOT NULL,
+    position INTEGER NOT NULL,
+    text TEXT NOT NULL,
+    votes INTEGER,
+    PRIMARY KEY (poll, position)
+);
@@ -0,0 +1,19 @@
+-- Add migration script here
+CREATE TABLE suggestion (
+    id INTEGER PRIMARY KEY NOT NULL,
+    author INTEGER NOT NULL,
+    text TEXT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'open',
+    channel INTEGER,
+    message INTEGER,
+    created INTEGER NOT NULL
+);
+
+CREATE INDEX suggestion_message ON suggestion (message);
+
+CREATE TABLE suggestion_vote (
+    suggestion INTEGER NOT NULL,
+    user INTEGER NOT NULL,
+    up INTEGER NOT NULL CHECK(up IN (0,1)),
+    PRIMARY KEY (suggestion, user)
+);
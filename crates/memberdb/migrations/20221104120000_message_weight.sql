@@ -0,0 +1,4 @@
+-- Add migration script here
+-- Hundredths of a message carried over from weighted messages, added to the message stats once
+-- they add up to a whole message
+ALTER TABLE discord ADD COLUMN message_frac INTEGER NOT NULL DEFAULT 0;
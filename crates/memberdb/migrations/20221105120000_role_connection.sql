@@ -0,0 +1,7 @@
+-- Add migration script here
+CREATE TABLE role_connection (
+    user INTEGER PRIMARY KEY NOT NULL,
+    access_token TEXT NOT NULL,
+    refresh_token TEXT NOT NULL,
+    expires INTEGER NOT NULL
+);
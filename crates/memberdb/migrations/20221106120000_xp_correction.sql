@@ -0,0 +1,8 @@
+-- Add migration script here
+CREATE TABLE xp_correction (
+    id INTEGER PRIMARY KEY NOT NULL,
+    mcid TEXT NOT NULL,
+    ign TEXT NOT NULL,
+    amount INTEGER NOT NULL,
+    time INTEGER NOT NULL
+);
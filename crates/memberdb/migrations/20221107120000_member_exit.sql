@@ -0,0 +1,15 @@
+-- Add migration script here
+CREATE TABLE member_exit (
+    id INTEGER PRIMARY KEY NOT NULL,
+    discord INTEGER,
+    mcid TEXT,
+    ign TEXT,
+    rank TEXT NOT NULL,
+    source TEXT NOT NULL,
+    reason TEXT NOT NULL,
+    time INTEGER NOT NULL,
+    question TEXT,
+    response TEXT
+);
+
+CREATE INDEX member_exit_discord ON member_exit (discord, time);
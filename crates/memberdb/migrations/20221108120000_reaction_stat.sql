@@ -0,0 +1,4 @@
+-- Add migration script here
+-- The reaction column was never written to, it now counts the reactions given
+ALTER TABLE discord RENAME COLUMN reaction TO reaction_given;
+ALTER TABLE discord ADD COLUMN reaction_received INTEGER NOT NULL DEFAULT 0;
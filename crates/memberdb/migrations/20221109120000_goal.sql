@@ -0,0 +1,7 @@
+-- Add migration script here
+CREATE TABLE goal (
+    mid INTEGER NOT NULL,
+    stat TEXT NOT NULL,
+    target INTEGER NOT NULL,
+    PRIMARY KEY (mid, stat)
+);
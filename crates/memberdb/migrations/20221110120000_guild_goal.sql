@@ -0,0 +1,10 @@
+-- Add migration script here
+CREATE TABLE guild_goal (
+    id INTEGER PRIMARY KEY NOT NULL,
+    name TEXT NOT NULL,
+    target INTEGER NOT NULL,
+    progress INTEGER NOT NULL DEFAULT 0,
+    created INTEGER NOT NULL,
+    deadline INTEGER,
+    ended INTEGER
+);
@@ -0,0 +1,8 @@
+-- Add migration script here
+CREATE TABLE global_rank_sample (
+    time INTEGER PRIMARY KEY,
+    rank INTEGER NOT NULL,
+    level INTEGER NOT NULL,
+    xp INTEGER NOT NULL,
+    territories INTEGER NOT NULL
+);
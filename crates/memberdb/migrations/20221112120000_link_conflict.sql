@@ -0,0 +1,16 @@
+-- Add migration script here
+CREATE TABLE link_conflict (
+    id INTEGER PRIMARY KEY NOT NULL,
+    mid INTEGER NOT NULL,
+    discord INTEGER NOT NULL,
+    kind TEXT NOT NULL,
+    previous INTEGER,
+    discord_left INTEGER,
+    mcid TEXT NOT NULL,
+    ign TEXT NOT NULL,
+    rank TEXT NOT NULL,
+    xp INTEGER NOT NULL,
+    joined TEXT NOT NULL,
+    wars INTEGER,
+    time INTEGER NOT NULL
+);
@@ -0,0 +1,12 @@
+-- Add migration script here
+CREATE TABLE ticket (
+    id INTEGER PRIMARY KEY NOT NULL,
+    applicant INTEGER NOT NULL,
+    channel INTEGER NOT NULL,
+    opened INTEGER NOT NULL,
+    closed INTEGER,
+    closed_by INTEGER
+);
+
+CREATE INDEX ticket_applicant ON ticket (applicant);
+CREATE INDEX ticket_channel ON ticket (channel);
@@ -0,0 +1,10 @@
+-- Add migration script here
+CREATE TABLE waitlist (
+    id INTEGER PRIMARY KEY NOT NULL,
+    ign TEXT NOT NULL,
+    discord INTEGER,
+    added INTEGER NOT NULL,
+    added_by INTEGER NOT NULL
+);
+
+CREATE INDEX waitlist_ign ON waitlist (ign COLLATE NOCASE);
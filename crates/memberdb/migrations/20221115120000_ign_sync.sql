@@ -0,0 +1,5 @@
+-- Add migration script here
+CREATE TABLE ign_sync (
+    mcid TEXT PRIMARY KEY NOT NULL,
+    checked INTEGER NOT NULL
+);
@@ -0,0 +1,14 @@
+-- Add migration script here
+CREATE TABLE stat_increments (
+    id INTEGER PRIMARY KEY NOT NULL,
+    discord INTEGER,
+    mcid TEXT,
+    stat TEXT NOT NULL,
+    amount INTEGER NOT NULL,
+    source TEXT NOT NULL,
+    time INTEGER NOT NULL
+);
+
+CREATE INDEX stat_increments_discord_time ON stat_increments (discord, time);
+CREATE INDEX stat_increments_mcid_time ON stat_increments (mcid, time);
+CREATE INDEX stat_increments_time ON stat_increments (time);
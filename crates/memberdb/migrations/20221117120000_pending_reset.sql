@@ -0,0 +1,5 @@
+-- Add migration script here
+CREATE TABLE pending_reset (
+    time INTEGER PRIMARY KEY NOT NULL,
+    deadline INTEGER NOT NULL
+);
@@ -0,0 +1,161 @@
+//! Daily guild stats, which are aggregated throughout the day and reset at every utc midnight.
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use sqlx::query;
+use tracing::info;
+
+use util::ctx;
+
+use crate::events::DBEvent;
+use crate::{Transaction, DB};
+
+#[derive(Serialize, Debug, Clone, Default)]
+/// Guild stats aggregated over a day
+pub struct DailySummary {
+    /// Total xp contributed
+    pub xp: i64,
+    /// Amount of messages sent in tracked channels
+    pub message: i64,
+    /// Amount of unique guild members that were online
+    pub online: i64,
+    /// Highest amount of guild members that were online at the same time
+    pub online_peak: i64,
+    /// Average amount of guild members that were online at the same time
+    pub avg_online_members: f64,
+    /// Average fraction of the guild members that were online at the same time
+    pub avg_online_ratio: f64,
+    /// Amount of players that joined the guild
+    pub joins: i64,
+    /// Amount of players that left the guild
+    pub leaves: i64,
+}
+
+/// Increment the daily xp contribution
+pub async fn update_daily_xp(tx: &mut Transaction, amount: i64) -> Result<()> {
+    query!("UPDATE daily_stat SET xp=xp+?", amount)
+        .execute(&mut tx.tx)
+        .await
+        .context("Failed to update daily_stat.xp")?;
+    Ok(())
+}
+
+/// Increment the daily message count
+pub async fn update_daily_message(tx: &mut Transaction, amount: i64) -> Result<()> {
+    query!("UPDATE daily_stat SET message=message+?", amount)
+        .execute(&mut tx.tx)
+        .await
+        .context("Failed to update daily_stat.message")?;
+    Ok(())
+}
+
+/// Increment the daily guild join count
+pub async fn add_daily_join(tx: &mut Transaction) -> Result<()> {
+    query!("UPDATE daily_stat SET joins=joins+1")
+        .execute(&mut tx.tx)
+        .await
+        .context("Failed to update daily_stat.joins")?;
+    Ok(())
+}
+
+/// Increment the daily guild leave count
+pub async fn add_daily_leave(tx: &mut Transaction) -> Result<()> {
+    query!("UPDATE daily_stat SET leaves=leaves+1")
+        .execute(&mut tx.tx)
+        .await
+        .context("Failed to update daily_stat.leaves")?;
+    Ok(())
+}
+
+/// Record the players that are currently online.
+///
+/// Only guild members among `igns` are counted, and their amount is used to update the daily
+/// online peak and average.
+pub async fn update_daily_online(tx: &mut Transaction, igns: &[&str]) -> Result<()> {
+    let mut online: i64 = 0;
+    for ign in igns {
+        let row = query!("SELECT id FROM wynn WHERE ign=? AND guild", ign)
+            .fetch_optional(&mut tx.tx)
+            .await
+            .context("Failed to fetch wynn.id")?;
+        if let Some(row) = row {
+            online += 1;
+            query!("INSERT OR IGNORE INTO daily_online (id) VALUES (?)", row.id)
+                .execute(&mut tx.tx)
+                .await
+                .context("Failed to insert into daily_online")?;
+        }
+    }
+
+    let members = query!("SELECT COUNT(*) AS count FROM wynn WHERE guild")
+        .fetch_one(&mut tx.tx)
+        .await
+        .context("Failed to count guild members")?
+        .count;
+    let ratio = if members > 0 { online as f64 / members as f64 } else { 0.0 };
+    query!(
+        "UPDATE daily_stat SET online_peak=MAX(online_peak,?),online_samples=online_samples+1,
+            online_sum=online_sum+?,online_ratio_sum=online_ratio_sum+?",
+        online,
+        online,
+        ratio
+    )
+    .execute(&mut tx.tx)
+    .await
+    .context("Failed to update daily_stat online stats")?;
+    Ok(())
+}
+
+/// Get the daily stats aggregated so far
+pub async fn daily_summary(db: &DB) -> Result<DailySummary> {
+    let row = ctx!(
+        query!(
+            "SELECT xp,message,online_peak,joins,leaves,
+                (SELECT COUNT(*) FROM daily_online) AS \"online!: i64\",
+                CASE WHEN online_samples>0 THEN CAST(online_sum AS REAL)/online_samples ELSE 0.0 END
+                    AS \"avg_online_members!: f64\",
+                CASE WHEN online_samples>0 THEN online_ratio_sum/online_samples ELSE 0.0 END
+                    AS \"avg_online_ratio!: f64\"
+            FROM daily_stat"
+        )
+        .fetch_one(&db.pool)
+        .await,
+        "Failed to fetch daily stats"
+    )?;
+    Ok(DailySummary {
+        xp: row.xp,
+        message: row.message,
+        online: row.online,
+        online_peak: row.online_peak,
+        avg_online_members: row.avg_online_members,
+        avg_online_ratio: row.avg_online_ratio,
+        joins: row.joins,
+        leaves: row.leaves,
+    })
+}
+
+/// Reset daily stats to 0, the online stats of the day are kept in the online history
+pub async fn daily_reset(db: &DB) -> Result<()> {
+    let summary = daily_summary(db).await?;
+    let now = ctx!(SystemTime::now().duration_since(UNIX_EPOCH), "Failed to get current unix timestamp")?;
+
+    info!("Resetting daily stats");
+    let mut tx = db.begin().await?;
+    crate::online_history::add_online_history(&mut tx, i64::try_from(now.as_secs())?, &summary).await?;
+    query!(
+        "UPDATE daily_stat SET xp=0,message=0,online_peak=0,joins=0,leaves=0,
+            online_samples=0,online_sum=0,online_ratio_sum=0"
+    )
+    .execute(&mut tx.tx)
+    .await
+    .context("Failed to set daily stats to 0")?;
+    query!("DELETE FROM daily_online")
+        .execute(&mut tx.tx)
+        .await
+        .context("Failed to clear daily_online")?;
+    tx.commit().await?;
+
+    db.signal(DBEvent::DailyReset { summary });
+    Ok(())
+}
@@ -76,6 +76,25 @@ impl MemberId {
             .rank;
         MemberRank::decode(&rank)
     }
+
+    /// Get the amount of members
+    pub async fn count(exe: &mut Executor<'_>) -> Result<i64> {
+        Ok(exe
+            .one(query!("SELECT COUNT(*) AS \"count!: i64\" FROM member"))
+            .await
+            .context("Failed to count members")?
+            .count)
+    }
+
+    /// Get all members that has a linked discord profile, ordered by member id
+    pub async fn with_discord(exe: &mut Executor<'_>) -> Result<Vec<(MemberId, DiscordId)>> {
+        exe.all(
+            query!("SELECT oid AS mid,discord AS \"discord!\" FROM member WHERE discord NOT NULL ORDER BY oid")
+                .map(|r| (MemberId(r.mid), DiscordId(r.discord))),
+        )
+        .await
+        .context("Failed to get all members with discord profile")
+    }
 }
 
 impl DiscordId {
@@ -145,6 +164,33 @@ impl DiscordId {
             .context("Failed to fetch discord.voice_week")?;
         Ok(row.voice_week)
     }
+
+    pub async fn stream_time(&self, exe: &mut Executor<'_>) -> Result<i64> {
+        let row = exe
+            .one(query!("SELECT stream FROM discord WHERE id=?", self))
+            .await
+            .context("Failed to fetch discord.stream")?;
+        Ok(row.stream)
+    }
+
+    pub async fn weekly_stream_time(&self, exe: &mut Executor<'_>) -> Result<i64> {
+        let row = exe
+            .one(query!("SELECT stream_week FROM discord WHERE id=?", self))
+            .await
+            .context("Failed to fetch discord.stream_week")?;
+        Ok(row.stream_week)
+    }
+
+    /// Get the discord user who invited this user to the discord server.
+    ///
+    /// Returns `None` if it isn't known how the user joined, or if the invite had no inviter.
+    pub async fn inviter(&self, exe: &mut Executor<'_>) -> Result<Option<DiscordId>> {
+        let row = exe
+            .optional(query!("SELECT inviter FROM discord_invite WHERE id=?", self))
+            .await
+            .context("Failed to fetch discord_invite.inviter")?;
+        Ok(row.and_then(|row| row.inviter).map(DiscordId))
+    }
 }
 
 impl McId {
@@ -0,0 +1,108 @@
+//! The guild's rank on the global guild leaderboard, which is sampled periodically to track how
+//! it changes over time.
+use anyhow::{Context, Result};
+use serde::Serialize;
+use sqlx::query;
+use tracing::info;
+
+use crate::DB;
+
+/// How long the samples are kept for, in seconds
+pub const SAMPLE_RETENTION: i64 = 365 * 86400;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// The guild's entry on the global guild leaderboard at a point in time
+pub struct RankSample {
+    /// Unix timestamp of when the sample is taken
+    pub time: i64,
+    /// Position on the leaderboard, starting from 1
+    pub rank: i64,
+    pub level: i64,
+    pub xp: i64,
+    pub territories: i64,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+/// Change of the guild's rank over a period of time
+pub struct RankChange {
+    /// Rank at the start of the period
+    pub from: i64,
+    /// Rank at the end of the period
+    pub to: i64,
+}
+
+impl RankChange {
+    /// Amount of positions climbed, negative if the guild dropped
+    pub fn climbed(&self) -> i64 {
+        self.from - self.to
+    }
+}
+
+/// Add a rank sample, and remove the samples that are older than [`SAMPLE_RETENTION`]
+pub async fn add_rank_sample(db: &DB, sample: &RankSample) -> Result<()> {
+    info!(?sample, "Adding global rank sample");
+    let mut tx = db.begin().await?;
+    query!(
+        "INSERT OR REPLACE INTO global_rank_sample (time,rank,level,xp,territories) VALUES (?,?,?,?,?)",
+        sample.time,
+        sample.rank,
+        sample.level,
+        sample.xp,
+        sample.territories
+    )
+    .execute(&mut tx.tx)
+    .await
+    .context("Failed to insert into global_rank_sample")?;
+
+    let expire = sample.time - SAMPLE_RETENTION;
+    query!("DELETE FROM global_rank_sample WHERE time<?", expire)
+        .execute(&mut tx.tx)
+        .await
+        .context("Failed to delete expired global rank samples")?;
+    tx.commit().await
+}
+
+/// Get all rank samples taken since `since`, ordered by time
+pub async fn rank_samples(db: &DB, since: i64) -> Result<Vec<RankSample>> {
+    let rows = query!(
+        "SELECT time,rank,level,xp,territories FROM global_rank_sample WHERE time>=? ORDER BY time",
+        since
+    )
+    .fetch_all(&db.pool)
+    .await
+    .context("Failed to fetch global rank samples")?;
+    Ok(rows
+        .into_iter()
+        .map(|row| RankSample {
+            time: row.time,
+            rank: row.rank,
+            level: row.level,
+            xp: row.xp,
+            territories: row.territories,
+        })
+        .collect())
+}
+
+/// Get the change of rank between the first and last sample taken since `since`.
+///
+/// Returns `None` if there are no samples.
+pub async fn rank_change(db: &DB, since: i64) -> Result<Option<RankChange>> {
+    let samples = rank_samples(db, since).await?;
+    Ok(match (samples.first(), samples.last()) {
+        (Some(first), Some(last)) => Some(RankChange { from: first.rank, to: last.rank }),
+        _ => None,
+    })
+}
+
+/// Get the rank at the end of each of the last `days` days before `now`, oldest first.
+///
+/// The rank of a day is its last sample, days without any samples are skipped.
+pub fn daily_ranks(samples: &[RankSample], now: i64, days: i64) -> Vec<i64> {
+    (0..days)
+        .filter_map(|i| {
+            let start = now - (days - i) * 86400;
+            let end = start + 86400;
+            samples.iter().rev().find(|s| s.time >= start && s.time < end).map(|s| s.rank)
+        })
+        .collect()
+}
@@ -0,0 +1,151 @@
+//! Personal weekly goals of members.
+//!
+//! A member can set a goal for each of the weekly stats, which is checked before the weekly stats
+//! are reset. Goals are kept between weeks, until the member clears them.
+use std::str::FromStr;
+
+use anyhow::{bail, Context, Result};
+use serde::Serialize;
+use sqlx::sqlite::SqliteRow;
+use sqlx::{query, Row};
+use tracing::info;
+
+use crate::model::db::{ProfileType, Stat};
+use crate::model::discord::DiscordId;
+use crate::model::member::MemberId;
+use crate::query_builder::Selectable;
+use crate::{Executor, Transaction, DB};
+
+/// Stats that goals can be set for
+pub const GOAL_STATS: [Stat; 5] =
+    [Stat::WeeklyMessage, Stat::WeeklyVoice, Stat::WeeklyStream, Stat::WeeklyOnline, Stat::WeeklyXp];
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+/// A member's weekly goal and their progress on it
+pub struct Goal {
+    pub mid: MemberId,
+    pub stat: Stat,
+    pub target: i64,
+    /// Current value of the stat
+    pub progress: i64,
+}
+
+impl Goal {
+    /// Whether the goal is reached
+    pub fn achieved(&self) -> bool {
+        self.progress >= self.target
+    }
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+/// A goal that is reached by the end of the week
+pub struct AchievedGoal {
+    pub goal: Goal,
+    pub discord: Option<DiscordId>,
+    pub ign: Option<String>,
+}
+
+/// Parse the name of a stat that goals can be set for
+pub fn parse_goal_stat(s: &str) -> Result<Stat> {
+    let stat = Stat::from_str(s)?;
+    if !GOAL_STATS.contains(&stat) {
+        bail!("Goals can only be set for weekly stats");
+    }
+    Ok(stat)
+}
+
+/// Get the current value of `stat` of member `mid`, 0 if they don't have the corresponding profile
+async fn stat_progress(exe: &mut Executor<'_>, mid: MemberId, stat: &Stat) -> Result<i64> {
+    let column = stat.to_column();
+    let profile = column.profile().context("Stat isn't part of a profile table")?;
+    let condition = match profile {
+        ProfileType::Guild => "id=(SELECT mcid FROM member WHERE oid=?)",
+        ProfileType::Wynn | ProfileType::Discord => "mid=?",
+    };
+    let sql = format!("SELECT {} FROM {} WHERE {}", column.name(), profile.name(), condition);
+    let query = sqlx::query(&sql).bind(mid).try_map(|row: SqliteRow| row.try_get(0));
+    let progress = exe
+        .optional(query)
+        .await
+        .with_context(|| format!("Failed to fetch {}.{}", profile.name(), column.name()))?;
+    Ok(progress.unwrap_or(0))
+}
+
+/// Set member `mid`'s goal of `stat` to `target`, replacing the existing one.
+pub async fn set_goal(tx: &mut Transaction, mid: MemberId, stat: &Stat, target: i64) -> Result<()> {
+    if !GOAL_STATS.contains(stat) {
+        bail!("Goals can only be set for weekly stats");
+    }
+    info!(?mid, ?stat, target, "Setting goal");
+    let name = stat.table_name();
+    query!("INSERT OR REPLACE INTO goal (mid,stat,target) VALUES (?,?,?)", mid, name, target)
+        .execute(&mut tx.tx)
+        .await
+        .context("Failed to insert into goal")?;
+    Ok(())
+}
+
+/// Remove member `mid`'s goal of `stat`, return `false` if they didn't have one.
+pub async fn remove_goal(tx: &mut Transaction, mid: MemberId, stat: &Stat) -> Result<bool> {
+    let name = stat.table_name();
+    let result = query!("DELETE FROM goal WHERE mid=? AND stat=?", mid, name)
+        .execute(&mut tx.tx)
+        .await
+        .context("Failed to delete from goal")?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Remove all goals of member `mid`
+pub async fn remove_member_goals(tx: &mut Transaction, mid: MemberId) -> Result<()> {
+    query!("DELETE FROM goal WHERE mid=?", mid)
+        .execute(&mut tx.tx)
+        .await
+        .context("Failed to delete member's goals")?;
+    Ok(())
+}
+
+impl MemberId {
+    /// Get the member's goals along with their progress, in the order of [`GOAL_STATS`]
+    pub async fn goals(&self, exe: &mut Executor<'_>) -> Result<Vec<Goal>> {
+        let rows = exe
+            .all(query!("SELECT stat,target FROM goal WHERE mid=?", self))
+            .await
+            .context("Failed to fetch member's goals")?;
+
+        let mut goals = Vec::new();
+        for row in rows {
+            let stat = Stat::from_str(&row.stat)?;
+            let progress = stat_progress(exe, *self, &stat).await?;
+            goals.push(Goal { mid: *self, stat, target: row.target, progress });
+        }
+        goals.sort_by_key(|goal| GOAL_STATS.iter().position(|stat| stat == &goal.stat));
+        Ok(goals)
+    }
+}
+
+/// Check everyone's goals against their weekly stats, and return the goals that are reached.
+///
+/// This needs to be called before the weekly stats are reset.
+pub async fn evaluate_goals(db: &DB) -> Result<Vec<AchievedGoal>> {
+    let rows = query!(
+        "SELECT goal.mid,goal.stat,goal.target,member.discord,wynn.ign AS \"ign?\" FROM goal \
+        JOIN member ON member.oid=goal.mid LEFT JOIN wynn ON wynn.id=member.mcid"
+    )
+    .fetch_all(&db.pool)
+    .await
+    .context("Failed to fetch goals")?;
+
+    let mut achieved = Vec::new();
+    for row in rows {
+        let mid = MemberId(row.mid);
+        let stat = Stat::from_str(&row.stat)?;
+        let progress = stat_progress(&mut db.exe(), mid, &stat).await?;
+        let goal = Goal { mid, stat, target: row.target, progress };
+        if goal.achieved() {
+            achieved.push(AchievedGoal { goal, discord: row.discord.map(DiscordId), ign: row.ign });
+        }
+    }
+
+    info!(achieved = achieved.len(), "Evaluated weekly goals");
+    Ok(achieved)
+}
@@ -0,0 +1,150 @@
+//! Guild-wide goals of guild xp, set by staff.
+//!
+//! Every guild xp contribution made while a goal is running counts toward it, and the goal ends
+//! once it is completed or its deadline passes.
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use sqlx::{query, query_as};
+use tracing::info;
+
+use util::ctx;
+
+use crate::events::DBEvent;
+use crate::{Executor, Transaction, DB};
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+/// A collective guild xp goal
+pub struct GuildGoal {
+    pub id: i64,
+    pub name: String,
+    /// Amount of guild xp to contribute
+    pub target: i64,
+    /// Amount of guild xp contributed since the goal is set
+    pub progress: i64,
+    /// Unix timestamp of when the goal is set
+    pub created: i64,
+    /// Unix timestamp of when the goal fails if it isn't completed
+    pub deadline: Option<i64>,
+    /// Unix timestamp of when the goal is completed or its deadline passed
+    pub ended: Option<i64>,
+}
+
+impl GuildGoal {
+    /// Whether the target is reached
+    pub fn completed(&self) -> bool {
+        self.progress >= self.target
+    }
+
+    /// Ratio between the progress and the target
+    pub fn ratio(&self) -> f64 {
+        if self.target > 0 {
+            self.progress as f64 / self.target as f64
+        } else {
+            1.0
+        }
+    }
+}
+
+/// Set a guild goal, and return its id
+pub async fn add_goal(
+    tx: &mut Transaction, name: &str, target: i64, deadline: Option<i64>, now: i64,
+) -> Result<i64> {
+    info!(name, target, ?deadline, "Adding guild goal");
+    let id = query!(
+        "INSERT INTO guild_goal (name,target,created,deadline) VALUES (?,?,?,?)",
+        name,
+        target,
+        now,
+        deadline
+    )
+    .execute(&mut tx.tx)
+    .await
+    .context("Failed to insert into guild_goal")?
+    .last_insert_rowid();
+    Ok(id)
+}
+
+/// Remove a guild goal, return `false` if there is no such goal
+pub async fn remove_goal(tx: &mut Transaction, id: i64) -> Result<bool> {
+    info!(id, "Removing guild goal");
+    let result = query!("DELETE FROM guild_goal WHERE id=?", id)
+        .execute(&mut tx.tx)
+        .await
+        .context("Failed to delete from guild_goal")?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Get all guild goals, the running ones first, then the ended ones from the latest
+pub async fn list_goals(exe: &mut Executor<'_>) -> Result<Vec<GuildGoal>> {
+    exe.all(query_as!(GuildGoal, "SELECT * FROM guild_goal ORDER BY ended IS NOT NULL, ended DESC, created"))
+        .await
+        .context("Failed to fetch guild goals")
+}
+
+/// Get the guild goals that are still running, from the oldest
+pub async fn running_goals(exe: &mut Executor<'_>) -> Result<Vec<GuildGoal>> {
+    exe.all(query_as!(GuildGoal, "SELECT * FROM guild_goal WHERE ended IS NULL ORDER BY created"))
+        .await
+        .context("Failed to fetch running guild goals")
+}
+
+/// Count a guild xp contribution toward all running goals whose deadline hasn't passed.
+///
+/// Goals that are completed by it are ended, and [`DBEvent::GuildGoalComplete`] is sent for each
+/// of them.
+pub async fn add_xp(tx: &mut Transaction, amount: i64) -> Result<()> {
+    let now = ctx!(SystemTime::now().duration_since(UNIX_EPOCH), "Failed to get current unix timestamp")?;
+    let now = i64::try_from(now.as_secs())?;
+    query!(
+        "UPDATE guild_goal SET progress=progress+? WHERE ended IS NULL AND (deadline IS NULL OR deadline>?)",
+        amount,
+        now
+    )
+    .execute(&mut tx.tx)
+    .await
+    .context("Failed to update guild_goal.progress")?;
+
+    let completed = query_as!(
+        GuildGoal,
+        "SELECT * FROM guild_goal WHERE ended IS NULL AND progress>=target ORDER BY created"
+    )
+    .fetch_all(&mut tx.tx)
+    .await
+    .context("Failed to fetch completed guild goals")?;
+    for mut goal in completed {
+        info!(goal.id, goal.name, "Guild goal is completed");
+        query!("UPDATE guild_goal SET ended=? WHERE id=?", now, goal.id)
+            .execute(&mut tx.tx)
+            .await
+            .context("Failed to update guild_goal.ended")?;
+        goal.ended = Some(now);
+        tx.signal(DBEvent::GuildGoalComplete { goal });
+    }
+    Ok(())
+}
+
+/// End the running goals whose deadline has passed by `now`, and return them
+pub async fn end_expired_goals(db: &DB, now: i64) -> Result<Vec<GuildGoal>> {
+    let mut tx = db.begin().await?;
+    let mut expired = query_as!(
+        GuildGoal,
+        "SELECT * FROM guild_goal WHERE ended IS NULL AND deadline<=? ORDER BY created",
+        now
+    )
+    .fetch_all(&mut tx.tx)
+    .await
+    .context("Failed to fetch expired guild goals")?;
+    query!("UPDATE guild_goal SET ended=? WHERE ended IS NULL AND deadline<=?", now, now)
+        .execute(&mut tx.tx)
+        .await
+        .context("Failed to end expired guild goals")?;
+    tx.commit().await?;
+
+    for goal in &mut expired {
+        info!(goal.id, goal.name, "Guild goal deadline has passed");
+        goal.ended = Some(now);
+    }
+    Ok(expired)
+}
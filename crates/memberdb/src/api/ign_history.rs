@@ -0,0 +1,54 @@
+//! History of wynncraft ign changes, so a player can still be found by an ign they no longer use.
+use anyhow::{Context, Result};
+use sqlx::query;
+
+use crate::model::wynn::McId;
+use crate::{Executor, Transaction};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// An ign change of a mc account
+pub struct IgnChange {
+    pub old: String,
+    pub new: String,
+    /// Unix timestamp of when the change is found
+    pub time: i64,
+}
+
+/// Record that mc account `mcid` changed ign from `old` to `new` at unix timestamp `time`
+pub async fn record_ign_change(
+    tx: &mut Transaction, mcid: &McId, old: &str, new: &str, time: i64,
+) -> Result<()> {
+    query!("INSERT INTO ign_history (mcid,old,new,time) VALUES (?,?,?,?)", mcid, old, new, time)
+        .execute(&mut tx.tx)
+        .await
+        .context("Failed to insert into ign_history")?;
+    Ok(())
+}
+
+/// Get the ign changes of a mc account, from oldest to newest
+pub async fn ign_history(exe: &mut Executor<'_>, mcid: &McId) -> Result<Vec<IgnChange>> {
+    let rows = exe
+        .all(query!("SELECT old,new,time FROM ign_history WHERE mcid=? ORDER BY time,id", mcid))
+        .await
+        .context("Failed to fetch ign_history")?;
+    Ok(rows.into_iter().map(|row| IgnChange { old: row.old, new: row.new, time: row.time }).collect())
+}
+
+/// Get the mc accounts that previously used `ign`, with the one that stopped using it most
+/// recently first.
+/// Igns are compared case-insensitively, as mc igns are.
+///
+/// Accounts whose current ign is `ign` are excluded, as they are found by [`McId::from_ign`].
+pub async fn previous_owners(exe: &mut Executor<'_>, ign: &str) -> Result<Vec<McId>> {
+    let rows = exe
+        .all(query!(
+            "SELECT mcid FROM ign_history WHERE old=? COLLATE NOCASE \
+            AND NOT EXISTS (SELECT 1 FROM wynn WHERE id=ign_history.mcid AND ign=? COLLATE NOCASE) \
+            GROUP BY mcid ORDER BY MAX(time) DESC",
+            ign,
+            ign
+        ))
+        .await
+        .context("Failed to fetch ign_history.mcid")?;
+    Ok(rows.into_iter().map(|row| McId(row.mcid)).collect())
+}
@@ -0,0 +1,59 @@
+//! Periodic re-sync of the igns of all mc accounts with Mojang.
+//!
+//! Igns are only updated from the guild api for guild members, so renames of other accounts are
+//! picked up by checking each account in turn, the one checked least recently first.
+use anyhow::{Context, Result};
+use sqlx::query;
+
+use crate::model::wynn::McId;
+use crate::{Executor, Transaction};
+
+/// Get the mc account whose ign is checked least recently, along with its current ign.
+/// Accounts that are never checked come first.
+pub async fn next_account(exe: &mut Executor<'_>) -> Result<Option<(McId, String)>> {
+    let row = exe
+        .optional(query!(
+            "SELECT wynn.id,wynn.ign FROM wynn LEFT JOIN ign_sync ON ign_sync.mcid=wynn.id \
+            ORDER BY IFNULL(ign_sync.checked,0),wynn.id LIMIT 1"
+        ))
+        .await
+        .context("Failed to fetch the next mc account to sync")?;
+    Ok(row.map(|row| (McId(row.id), row.ign)))
+}
+
+/// Get the amount of mc accounts
+pub async fn account_count(exe: &mut Executor<'_>) -> Result<i64> {
+    Ok(exe
+        .one(query!("SELECT COUNT(*) AS \"count!: i64\" FROM wynn"))
+        .await
+        .context("Failed to count mc accounts")?
+        .count)
+}
+
+/// Record that the ign of mc account `mcid` is checked at unix timestamp `time`
+pub async fn mark_checked(tx: &mut Transaction, mcid: &McId, time: i64) -> Result<()> {
+    query!(
+        "INSERT INTO ign_sync (mcid,checked) VALUES (?,?) \
+        ON CONFLICT (mcid) DO UPDATE SET checked=excluded.checked",
+        mcid,
+        time
+    )
+    .execute(&mut tx.tx)
+    .await
+    .context("Failed to update ign_sync")?;
+    Ok(())
+}
+
+/// Update the ign of mc account `mcid` from `old` to the one fetched from Mojang at unix timestamp
+/// `time`, and mark it as checked.
+///
+/// Returns if the ign changed, in which case the change is recorded in the ign history.
+pub async fn sync_ign(tx: &mut Transaction, mcid: &McId, old: &str, new: &str, time: i64) -> Result<bool> {
+    let changed = old != new;
+    if changed {
+        mcid.set_ign(tx, new).await?;
+        crate::ign_history::record_ign_change(tx, mcid, old, new, time).await?;
+    }
+    mark_checked(tx, mcid, time).await?;
+    Ok(changed)
+}
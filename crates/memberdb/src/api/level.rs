@@ -0,0 +1,110 @@
+//! Guild level progress, which is sampled periodically to estimate when the guild levels up.
+//!
+//! The Wynncraft API only provides the guild xp as a percentage of the current level, so the
+//! progress is measured in level percentages, and the estimates assume that the xp required per
+//! level stays the same.
+use anyhow::{Context, Result};
+use sqlx::query;
+use tracing::info;
+
+use crate::DB;
+
+/// How long the samples are kept for, in seconds
+pub const SAMPLE_RETENTION: i64 = 14 * 86400;
+
+#[derive(Debug, Clone, PartialEq)]
+/// The guild level and xp percentage at a point in time
+pub struct XpSample {
+    /// Unix timestamp of when the sample is taken
+    pub time: i64,
+    pub level: i64,
+    /// Percentage of the xp required to reach next level
+    pub xp: f64,
+}
+
+impl XpSample {
+    /// Total progress of the guild, in level percentages
+    pub fn progress(&self) -> f64 {
+        self.level as f64 * 100.0 + self.xp
+    }
+}
+
+/// Add a guild xp sample, and remove the samples that are older than [`SAMPLE_RETENTION`]
+pub async fn add_xp_sample(db: &DB, sample: &XpSample) -> Result<()> {
+    info!(?sample, "Adding guild xp sample");
+    let mut tx = db.begin().await?;
+    query!(
+        "INSERT OR REPLACE INTO guild_xp_sample (time,level,xp) VALUES (?,?,?)",
+        sample.time,
+        sample.level,
+        sample.xp
+    )
+    .execute(&mut tx.tx)
+    .await
+    .context("Failed to insert into guild_xp_sample")?;
+
+    let expire = sample.time - SAMPLE_RETENTION;
+    query!("DELETE FROM guild_xp_sample WHERE time<?", expire)
+        .execute(&mut tx.tx)
+        .await
+        .context("Failed to delete expired guild xp samples")?;
+    tx.commit().await
+}
+
+/// Get all guild xp samples taken since `since`, ordered by time
+pub async fn xp_samples(db: &DB, since: i64) -> Result<Vec<XpSample>> {
+    let rows = query!("SELECT time,level,xp FROM guild_xp_sample WHERE time>=? ORDER BY time", since)
+        .fetch_all(&db.pool)
+        .await
+        .context("Failed to fetch guild xp samples")?;
+    Ok(rows
+        .into_iter()
+        .map(|row| XpSample { time: row.time, level: row.level, xp: row.xp })
+        .collect())
+}
+
+/// Calculate the average progress per day between the first and last sample, in level
+/// percentages.
+///
+/// Returns `None` if the samples span less than an hour.
+pub fn progress_rate(samples: &[XpSample]) -> Option<f64> {
+    let (first, last) = (samples.first()?, samples.last()?);
+    let elapsed = last.time - first.time;
+    if elapsed < 3600 {
+        return None;
+    }
+    Some((last.progress() - first.progress()) / elapsed as f64 * 86400.0)
+}
+
+/// Estimate the amount of seconds until the guild reaches the next level.
+///
+/// Returns `None` if the progress rate can't be calculated or the guild isn't progressing.
+pub fn level_eta(samples: &[XpSample]) -> Option<i64> {
+    let rate = progress_rate(samples)?;
+    if rate <= 0.0 {
+        return None;
+    }
+    let last = samples.last()?;
+    Some(((100.0 - last.xp) / rate * 86400.0) as i64)
+}
+
+/// Calculate the progress made in each of the last `days` days before `now`, oldest first.
+///
+/// The progress of a day is measured from the last sample before it to the last sample within
+/// it, so days without any samples have no progress.
+pub fn daily_progress(samples: &[XpSample], now: i64, days: i64) -> Vec<f64> {
+    (0..days)
+        .map(|i| {
+            let start = now - (days - i) * 86400;
+            let end = start + 86400;
+            let before = samples.iter().rev().find(|s| s.time < start);
+            let mut within = samples.iter().filter(|s| s.time >= start && s.time < end);
+            let first = within.next();
+            let last = within.next_back().or(first);
+            match (before.or(first), last) {
+                (Some(from), Some(to)) => to.progress() - from.progress(),
+                _ => 0.0,
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,313 @@
+//! Guild joins held back for staff review, because the member holding the guild profile doesn't
+//! look like the player that joined.
+//!
+//! A held back join stays in the `link_conflict` table until staff either keep the member's discord
+//! link or unlink it, both of which apply the join. It is dropped if the player leaves the guild
+//! before it is reviewed.
+use std::fmt;
+
+use anyhow::{bail, Context, Result};
+use serde::Serialize;
+use sqlx::{query, query_as};
+use tracing::info;
+
+use crate::api::member_exit::ExitSource;
+use crate::events::DBEvent;
+use crate::model::discord::DiscordId;
+use crate::model::guild::GuildRank;
+use crate::model::member::MemberId;
+use crate::model::wynn::McId;
+use crate::{Executor, Transaction};
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+/// Why a member's discord link looks stale
+pub enum Conflict {
+    /// The linked discord user isn't in the discord server, `left` is when they are recorded
+    /// leaving it
+    DiscordGone { left: Option<i64> },
+    /// The guild profile's previous holder left the guild with a different discord user linked
+    DiscordChanged { previous: DiscordId },
+}
+
+impl fmt::Display for Conflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::DiscordGone { left: Some(left) } => {
+                write!(f, "the linked discord user left the server <t:{}:R>", left)
+            }
+            Self::DiscordGone { left: None } => write!(f, "the linked discord user isn't in the server"),
+            Self::DiscordChanged { previous } => {
+                write!(f, "the player left the guild while linked to <@{}>", previous)
+            }
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+/// A player joining the guild, as reported by the API
+pub struct GuildJoin {
+    pub mcid: McId,
+    pub ign: String,
+    pub rank: GuildRank,
+    pub xp: i64,
+    /// The datetime of when they joined the guild, as appeared in the api
+    pub joined: String,
+    pub wars: Option<i64>,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+/// A guild join waiting for staff review
+pub struct LinkConflict {
+    pub id: i64,
+    /// Member holding the guild profile
+    pub mid: MemberId,
+    /// Discord user linked to the member
+    pub discord: DiscordId,
+    pub conflict: Conflict,
+    pub join: GuildJoin,
+    /// Unix timestamp of when the join is held back
+    pub time: i64,
+}
+
+struct ConflictRow {
+    id: i64,
+    mid: MemberId,
+    discord: DiscordId,
+    kind: String,
+    previous: Option<DiscordId>,
+    discord_left: Option<i64>,
+    mcid: McId,
+    ign: String,
+    rank: GuildRank,
+    xp: i64,
+    joined: String,
+    wars: Option<i64>,
+    time: i64,
+}
+
+impl TryFrom<ConflictRow> for LinkConflict {
+    type Error = anyhow::Error;
+
+    fn try_from(row: ConflictRow) -> Result<Self> {
+        let conflict = match (row.kind.as_str(), row.previous) {
+            ("discord_gone", _) => Conflict::DiscordGone { left: row.discord_left },
+            ("discord_changed", Some(previous)) => Conflict::DiscordChanged { previous },
+            (kind, _) => bail!("Invalid link conflict kind '{}'", kind),
+        };
+        Ok(Self {
+            id: row.id,
+            mid: row.mid,
+            discord: row.discord,
+            conflict,
+            join: GuildJoin {
+                mcid: row.mcid,
+                ign: row.ign,
+                rank: row.rank,
+                xp: row.xp,
+                joined: row.joined,
+                wars: row.wars,
+            },
+            time: row.time,
+        })
+    }
+}
+
+/// Check if the link between the holder of guild profile `mcid` and discord user `discord` looks
+/// stale, `in_server` tells if a discord user is in the discord server.
+async fn find_conflict(
+    exe: &mut Executor<'_>, mcid: &McId, discord: DiscordId, in_server: impl Fn(DiscordId) -> bool,
+) -> Result<Option<Conflict>> {
+    if !in_server(discord) {
+        let left = exe
+            .optional(query!(
+                "SELECT time FROM member_exit WHERE discord=? AND source=? ORDER BY time DESC,id DESC LIMIT 1",
+                discord,
+                ExitSource::Discord
+            ))
+            .await
+            .context("Failed to fetch member_exit.time")?
+            .map(|row| row.time);
+        return Ok(Some(Conflict::DiscordGone { left }));
+    }
+
+    let previous = exe
+        .optional(query!(
+            "SELECT discord AS \"discord: DiscordId\" FROM member_exit WHERE mcid=? AND source=? \
+            ORDER BY time DESC,id DESC LIMIT 1",
+            mcid,
+            ExitSource::Guild
+        ))
+        .await
+        .context("Failed to fetch member_exit.discord")?
+        .and_then(|row| row.discord);
+    Ok(match previous {
+        Some(previous) if previous != discord => Some(Conflict::DiscordChanged { previous }),
+        _ => None,
+    })
+}
+
+/// Check a player joining the guild, and hold the join back for review if the member holding their
+/// guild profile has a discord link that looks stale.
+/// `in_server` tells if a discord user is in the discord server, and `now` is the current unix
+/// timestamp.
+///
+/// Returns `true` if the join is held back, including when it is already waiting for review.
+/// [`DBEvent::LinkConflict`] is sent when a join is newly held back.
+pub async fn hold_join(
+    tx: &mut Transaction, join: &GuildJoin, now: i64, in_server: impl Fn(DiscordId) -> bool,
+) -> Result<bool> {
+    let pending = query!("SELECT id FROM link_conflict WHERE mcid=?", join.mcid)
+        .fetch_optional(&mut tx.tx)
+        .await
+        .context("Failed to fetch link_conflict")?;
+    if pending.is_some() {
+        return Ok(true);
+    }
+
+    let mid = match join.mcid.mid(&mut tx.exe()).await? {
+        Some(mid) => mid,
+        None => return Ok(false),
+    };
+    let discord = match mid.links(&mut tx.exe()).await?.0 {
+        Some(discord) => discord,
+        None => return Ok(false),
+    };
+    let conflict = match find_conflict(&mut tx.exe(), &join.mcid, discord, in_server).await? {
+        Some(conflict) => conflict,
+        None => return Ok(false),
+    };
+
+    info!(?mid, ?discord, ?conflict, ign = join.ign, "Holding back guild join");
+    let (kind, previous, left) = match conflict {
+        Conflict::DiscordGone { left } => ("discord_gone", None, left),
+        Conflict::DiscordChanged { previous } => ("discord_changed", Some(previous), None),
+    };
+    let id = query!(
+        "INSERT INTO link_conflict (mid,discord,kind,previous,discord_left,mcid,ign,rank,xp,joined,wars,time) \
+        VALUES (?,?,?,?,?,?,?,?,?,?,?,?)",
+        mid,
+        discord,
+        kind,
+        previous,
+        left,
+        join.mcid,
+        join.ign,
+        join.rank,
+        join.xp,
+        join.joined,
+        join.wars,
+        now
+    )
+    .execute(&mut tx.tx)
+    .await
+    .context("Failed to insert into link_conflict")?
+    .last_insert_rowid();
+
+    let conflict = LinkConflict { id, mid, discord, conflict, join: join.clone(), time: now };
+    tx.signal(DBEvent::LinkConflict { conflict });
+    Ok(true)
+}
+
+/// Get a held back join
+pub async fn get_conflict(exe: &mut Executor<'_>, id: i64) -> Result<Option<LinkConflict>> {
+    let row = exe
+        .optional(query_as!(
+            ConflictRow,
+            "SELECT id,mid AS \"mid: MemberId\",discord AS \"discord: DiscordId\",kind,\
+            previous AS \"previous: DiscordId\",discord_left,mcid AS \"mcid: McId\",ign,\
+            rank AS \"rank: GuildRank\",xp,joined,wars,time FROM link_conflict WHERE id=?",
+            id
+        ))
+        .await
+        .context("Failed to fetch link_conflict")?;
+    row.map(LinkConflict::try_from).transpose()
+}
+
+/// Get all held back joins, from the oldest to the newest
+pub async fn pending_conflicts(exe: &mut Executor<'_>) -> Result<Vec<LinkConflict>> {
+    let rows = exe
+        .all(query_as!(
+            ConflictRow,
+            "SELECT id,mid AS \"mid: MemberId\",discord AS \"discord: DiscordId\",kind,\
+            previous AS \"previous: DiscordId\",discord_left,mcid AS \"mcid: McId\",ign,\
+            rank AS \"rank: GuildRank\",xp,joined,wars,time FROM link_conflict ORDER BY time,id"
+        ))
+        .await
+        .context("Failed to fetch link_conflict")?;
+    rows.into_iter().map(LinkConflict::try_from).collect()
+}
+
+/// Apply a held back join while keeping the member's discord link, and stop holding it.
+/// Returns `None` if there is no such join, ex: it is already reviewed.
+pub async fn keep_link(tx: &mut Transaction, id: i64) -> Result<Option<LinkConflict>> {
+    let conflict = match take_conflict(tx, id).await? {
+        Some(conflict) => conflict,
+        None => return Ok(None),
+    };
+    info!(?conflict, "Applying held back guild join, keeping discord link");
+    apply_join(tx, &conflict.join).await?;
+    Ok(Some(conflict))
+}
+
+/// Apply a held back join and unlink the member's discord profile, and stop holding it.
+/// Returns `None` if there is no such join, ex: it is already reviewed.
+///
+/// The discord profile is only unlinked if the member holding the guild profile is still linked to
+/// the same discord user.
+pub async fn unlink_discord(tx: &mut Transaction, id: i64) -> Result<Option<LinkConflict>> {
+    let conflict = match take_conflict(tx, id).await? {
+        Some(conflict) => conflict,
+        None => return Ok(None),
+    };
+    info!(?conflict, "Applying held back guild join, unlinking discord");
+    // The guild profile is bound first, so the member is demoted to a guild partial instead of
+    // being removed
+    apply_join(tx, &conflict.join).await?;
+    if let Some(mid) = conflict.join.mcid.mid(&mut tx.exe()).await? {
+        if mid.links(&mut tx.exe()).await?.0 == Some(conflict.discord) {
+            mid.bind_discord(tx, None).await?;
+        }
+    }
+    Ok(Some(conflict))
+}
+
+/// Stop holding the joins of guild profile `mcid` without applying them, return `false` if there
+/// wasn't any.
+pub async fn drop_conflicts(tx: &mut Transaction, mcid: &McId) -> Result<bool> {
+    let result = query!("DELETE FROM link_conflict WHERE mcid=?", mcid)
+        .execute(&mut tx.tx)
+        .await
+        .context("Failed to delete from link_conflict")?;
+    if result.rows_affected() > 0 {
+        info!(?mcid, "Dropped held back guild join");
+    }
+    Ok(result.rows_affected() > 0)
+}
+
+/// Bind the guild profile of a player that joined the guild, skipped if they are already in it
+async fn apply_join(tx: &mut Transaction, join: &GuildJoin) -> Result<()> {
+    if join.mcid.in_guild(&mut tx.exe()).await? {
+        return Ok(());
+    }
+    join.mcid.bind_guild(tx, &join.ign, true, join.rank).await?;
+    // The guild profile may be kept from when they were in the guild before
+    join.mcid.set_rank(tx, join.rank).await?;
+    join.mcid.update_xp(tx, join.xp).await?;
+    join.mcid.set_joined(tx, &join.joined).await?;
+    if let Some(wars) = join.wars {
+        join.mcid.set_wars(tx, wars).await?;
+    }
+    crate::add_daily_join(tx).await
+}
+
+/// Remove a held back join and return it
+async fn take_conflict(tx: &mut Transaction, id: i64) -> Result<Option<LinkConflict>> {
+    let conflict = get_conflict(&mut tx.exe(), id).await?;
+    if conflict.is_some() {
+        query!("DELETE FROM link_conflict WHERE id=?", id)
+            .execute(&mut tx.tx)
+            .await
+            .context("Failed to delete from link_conflict")?;
+    }
+    Ok(conflict)
+}
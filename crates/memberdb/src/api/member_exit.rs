@@ -0,0 +1,234 @@
+//! Records of members leaving the guild or the discord server, kept after the member is gone.
+//!
+//! Each record is a snapshot of the member's profile links, ign and rank when they left, along with
+//! why they left. If the exit interview is enabled, the record also holds the question that was
+//! sent to them and their response.
+use std::fmt;
+use std::str::FromStr;
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use sqlx::query;
+use tracing::info;
+
+use util::{impl_sqlx_type, ioerr};
+
+use crate::events::DBEvent;
+use crate::model::discord::DiscordId;
+use crate::model::member::{MemberId, MemberRank};
+use crate::model::wynn::McId;
+use crate::{Executor, Transaction};
+
+#[derive(Serialize, Debug, Copy, Clone, PartialEq, Eq)]
+/// What a member left
+pub enum ExitSource {
+    /// The discord server
+    Discord,
+    /// The in-game guild
+    Guild,
+}
+
+impl_sqlx_type!(ExitSource);
+
+impl fmt::Display for ExitSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Discord => write!(f, "discord"),
+            Self::Guild => write!(f, "guild"),
+        }
+    }
+}
+
+impl FromStr for ExitSource {
+    type Err = std::io::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "discord" => Ok(Self::Discord),
+            "guild" => Ok(Self::Guild),
+            _ => ioerr!("Failed to parse '{}' as ExitSource", s),
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Copy, Clone, PartialEq, Eq)]
+/// Why a member left
+pub enum ExitReason {
+    /// Removed by staff
+    Kicked,
+    /// Left by themselves
+    Left,
+    /// Removed in a prune of inactive users
+    Purged,
+}
+
+impl_sqlx_type!(ExitReason);
+
+impl fmt::Display for ExitReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Kicked => write!(f, "kicked"),
+            Self::Left => write!(f, "left"),
+            Self::Purged => write!(f, "purged"),
+        }
+    }
+}
+
+impl FromStr for ExitReason {
+    type Err = std::io::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "kicked" => Ok(Self::Kicked),
+            "left" => Ok(Self::Left),
+            "purged" => Ok(Self::Purged),
+            _ => ioerr!("Failed to parse '{}' as ExitReason", s),
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+/// A member leaving the guild or the discord server
+pub struct MemberExit {
+    pub id: i64,
+    /// Discord profile the member had when they left
+    pub discord: Option<DiscordId>,
+    /// Wynn profile the member had when they left
+    pub mcid: Option<McId>,
+    pub ign: Option<String>,
+    /// Member rank when they left
+    pub rank: MemberRank,
+    pub source: ExitSource,
+    pub reason: ExitReason,
+    /// Unix timestamp of when the member left
+    pub time: i64,
+    /// Exit question sent to the member, `None` if they weren't asked
+    pub question: Option<String>,
+    pub response: Option<String>,
+}
+
+/// Record member `mid` leaving at unix timestamp `time`, and return the record.
+///
+/// This has to be called before the member's profiles are unlinked, as they are recorded.
+pub async fn record_exit(
+    tx: &mut Transaction, mid: MemberId, source: ExitSource, reason: ExitReason, time: i64,
+) -> Result<MemberExit> {
+    let (discord, mcid) = mid.links(&mut tx.exe()).await?;
+    let rank = mid.rank(&mut tx.exe()).await?;
+    let ign = match &mcid {
+        Some(mcid) => Some(mcid.ign(&mut tx.exe()).await?),
+        None => None,
+    };
+    info!(?mid, %source, %reason, "Recording member exit");
+    let id = query!(
+        "INSERT INTO member_exit (discord,mcid,ign,rank,source,reason,time) VALUES (?,?,?,?,?,?,?)",
+        discord,
+        mcid,
+        ign,
+        rank,
+        source,
+        reason,
+        time
+    )
+    .execute(&mut tx.tx)
+    .await
+    .context("Failed to insert into member_exit")?
+    .last_insert_rowid();
+
+    let exit =
+        MemberExit { id, discord, mcid, ign, rank, source, reason, time, question: None, response: None };
+    tx.signal(DBEvent::MemberExit { exit: exit.clone() });
+    Ok(exit)
+}
+
+/// Record that the exit question `question` is sent for an exit
+pub async fn set_exit_question(tx: &mut Transaction, id: i64, question: &str) -> Result<()> {
+    query!("UPDATE member_exit SET question=? WHERE id=?", question, id)
+        .execute(&mut tx.tx)
+        .await
+        .context("Failed to set member_exit.question")?;
+    Ok(())
+}
+
+/// Record the response of discord user `discord` to the latest exit question sent to them since
+/// unix timestamp `since`, and return the id of the exit.
+/// Returns `None` if there is no such question that isn't responded to yet.
+pub async fn record_exit_response(
+    tx: &mut Transaction, discord: DiscordId, response: &str, since: i64,
+) -> Result<Option<i64>> {
+    let row = tx
+        .exe()
+        .optional(query!(
+            "SELECT id FROM member_exit WHERE discord=? AND time>=? \
+            AND question NOT NULL AND response IS NULL ORDER BY time DESC,id DESC LIMIT 1",
+            discord,
+            since
+        ))
+        .await
+        .context("Failed to fetch member_exit")?;
+    let id = match row {
+        Some(row) => row.id,
+        None => return Ok(None),
+    };
+    info!(id, ?discord, "Recording exit question response");
+    query!("UPDATE member_exit SET response=? WHERE id=?", response, id)
+        .execute(&mut tx.tx)
+        .await
+        .context("Failed to set member_exit.response")?;
+    Ok(Some(id))
+}
+
+/// Get an exit record
+pub async fn get_exit(exe: &mut Executor<'_>, id: i64) -> Result<Option<MemberExit>> {
+    let row = exe
+        .optional(query!(
+            "SELECT id,discord AS \"discord: DiscordId\",mcid AS \"mcid: McId\",ign,\
+            rank AS \"rank: MemberRank\",source AS \"source: ExitSource\",\
+            reason AS \"reason: ExitReason\",time,question,response FROM member_exit WHERE id=?",
+            id
+        ))
+        .await
+        .context("Failed to fetch member_exit")?;
+    Ok(row.map(|row| MemberExit {
+        id: row.id,
+        discord: row.discord,
+        mcid: row.mcid,
+        ign: row.ign,
+        rank: row.rank,
+        source: row.source,
+        reason: row.reason,
+        time: row.time,
+        question: row.question,
+        response: row.response,
+    }))
+}
+
+/// Get the exit records from the newest to the oldest, optionally only the ones with a reason
+pub async fn list_exits(exe: &mut Executor<'_>, reason: Option<ExitReason>) -> Result<Vec<MemberExit>> {
+    let rows = exe
+        .all(query!(
+            "SELECT id,discord AS \"discord: DiscordId\",mcid AS \"mcid: McId\",ign,\
+            rank AS \"rank: MemberRank\",source AS \"source: ExitSource\",\
+            reason AS \"reason: ExitReason\",time,question,response FROM member_exit \
+            WHERE ? IS NULL OR reason=? ORDER BY time DESC,id DESC",
+            reason,
+            reason
+        ))
+        .await
+        .context("Failed to fetch member_exit")?;
+    Ok(rows
+        .into_iter()
+        .map(|row| MemberExit {
+            id: row.id,
+            discord: row.discord,
+            mcid: row.mcid,
+            ign: row.ign,
+            rank: row.rank,
+            source: row.source,
+            reason: row.reason,
+            time: row.time,
+            question: row.question,
+            response: row.response,
+        })
+        .collect())
+}
@@ -0,0 +1,49 @@
+//! Message log of members, which keeps when and where members sent messages (not their content),
+//! so their last discord activity can be known precisely.
+//!
+//! Only messages sent in tracked channels by discord users linked to a member are logged.
+use anyhow::{Context, Result};
+use sqlx::query;
+use tracing::info;
+
+use crate::model::member::MemberId;
+use crate::{Executor, Transaction, DB};
+
+/// Log a message sent by member `mid` in `channel` at unix timestamp `time`
+pub async fn add_message(tx: &mut Transaction, mid: MemberId, channel: u64, time: i64) -> Result<()> {
+    let channel = i64::try_from(channel).context("Failed to convert channel id into i64")?;
+    query!("INSERT INTO message_log (mid,channel,time) VALUES (?,?,?)", mid, channel, time)
+        .execute(&mut tx.tx)
+        .await
+        .context("Failed to insert into message_log")?;
+    Ok(())
+}
+
+/// Remove the logged messages that are sent before unix timestamp `before`, returns the amount
+/// of messages removed
+pub async fn prune_messages(db: &DB, before: i64) -> Result<u64> {
+    let result = query!("DELETE FROM message_log WHERE time<?", before)
+        .execute(&db.pool)
+        .await
+        .context("Failed to delete expired message log")?;
+    info!(removed = result.rows_affected(), before, "Pruned message log");
+    Ok(result.rows_affected())
+}
+
+/// Remove the logged messages of a member
+pub async fn remove_member_messages(tx: &mut Transaction, mid: MemberId) -> Result<()> {
+    query!("DELETE FROM message_log WHERE mid=?", mid)
+        .execute(&mut tx.tx)
+        .await
+        .context("Failed to delete member's message log")?;
+    Ok(())
+}
+
+/// Get the unix timestamp of the last logged message of a member
+pub async fn last_message_at(exe: &mut Executor<'_>, mid: MemberId) -> Result<Option<i64>> {
+    let row = exe
+        .one(query!("SELECT MAX(time) AS \"time: i64\" FROM message_log WHERE mid=?", mid))
+        .await
+        .context("Failed to fetch last message time")?;
+    Ok(row.time)
+}
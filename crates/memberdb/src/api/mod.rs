@@ -1,7 +1,32 @@
 //! Function for interacting with the database
+pub mod daily;
 pub mod fetch;
+pub mod global_rank;
+pub mod goal;
+pub mod guild_goal;
+pub mod ign_history;
+pub mod ign_sync;
+pub mod level;
+pub mod link_conflict;
+pub mod member_exit;
+pub mod message_log;
+pub mod online_history;
+pub mod poll;
+pub mod promotion_vote;
+pub mod rank_history;
+pub mod reminder;
+pub mod role_connection;
+pub mod stat_increment;
+pub mod stat_reset;
+pub mod suggestion;
 pub mod table;
+pub mod ticket;
 pub mod update;
+pub mod waitlist;
+pub mod weekly_backup;
+pub mod weekly_report;
+pub mod xp_correction;
+pub mod xp_requirement;
 
 use anyhow::Result;
 
@@ -13,7 +38,7 @@ pub async fn check_integrity(db: &DB) -> Result<Vec<String>> {
     let rows = sqlx::query!(
         "SELECT oid FROM member WHERE 
             (discord NOT NULL AND mcid NOT NULL AND type!='full') OR 
-            (discord NOT NULL AND mcid IS NULL AND type!='discord') OR
+            (discord NOT NULL AND mcid IS NULL AND type NOT IN ('discord','guest')) OR
             (discord IS NULL AND mcid NOT NULL AND 
             NOT (SELECT guild FROM wynn WHERE id=member.mcid) AND type!='wynn') OR 
             (discord IS NULL AND mcid NOT NULL AND 
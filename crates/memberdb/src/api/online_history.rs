@@ -0,0 +1,74 @@
+//! Online history of the guild, which keeps the online stats of each day after the daily reset,
+//! so the guild's activity can be compared across weeks.
+use anyhow::{Context, Result};
+use serde::Serialize;
+use sqlx::query;
+
+use crate::api::daily::DailySummary;
+use crate::{Transaction, DB};
+
+/// How long the online history is kept for, in seconds
+pub const HISTORY_RETENTION: i64 = 26 * 7 * 86400;
+
+#[derive(Serialize, Debug, Clone, Default, PartialEq)]
+/// Amount of guild members that were online over a period of time
+pub struct OnlineStats {
+    /// Highest amount of guild members that were online at the same time
+    pub peak_online: i64,
+    /// Average amount of guild members that were online at the same time
+    pub avg_online_members: f64,
+    /// Average fraction of the guild members that were online at the same time
+    pub avg_online_ratio: f64,
+}
+
+/// Add the online stats of a day that ended at unix timestamp `time` into the history, and
+/// remove the history that is older than [`HISTORY_RETENTION`]
+pub async fn add_online_history(tx: &mut Transaction, time: i64, summary: &DailySummary) -> Result<()> {
+    query!(
+        "INSERT INTO online_history (time,peak_online,avg_online_members,avg_online_ratio) VALUES (?,?,?,?)",
+        time,
+        summary.online_peak,
+        summary.avg_online_members,
+        summary.avg_online_ratio
+    )
+    .execute(&mut tx.tx)
+    .await
+    .context("Failed to insert into online_history")?;
+
+    let expire = time - HISTORY_RETENTION;
+    query!("DELETE FROM online_history WHERE time<?", expire)
+        .execute(&mut tx.tx)
+        .await
+        .context("Failed to delete expired online history")?;
+    Ok(())
+}
+
+/// Get the online stats of the days that ended within `since` and `until`
+pub async fn online_stats(db: &DB, since: i64, until: i64) -> Result<OnlineStats> {
+    let row = query!(
+        "SELECT COALESCE(MAX(peak_online),0) AS \"peak_online!: i64\",
+            COALESCE(AVG(avg_online_members),0.0) AS \"avg_online_members!: f64\",
+            COALESCE(AVG(avg_online_ratio),0.0) AS \"avg_online_ratio!: f64\"
+        FROM online_history WHERE time>? AND time<=?",
+        since,
+        until
+    )
+    .fetch_one(&db.pool)
+    .await
+    .context("Failed to fetch online history")?;
+    Ok(OnlineStats {
+        peak_online: row.peak_online,
+        avg_online_members: row.avg_online_members,
+        avg_online_ratio: row.avg_online_ratio,
+    })
+}
+
+/// Get the online stats of each of the last `weeks` weeks before `now`, oldest first
+pub async fn weekly_online_trend(db: &DB, now: i64, weeks: i64) -> Result<Vec<OnlineStats>> {
+    let mut trend = Vec::new();
+    for i in (0..weeks).rev() {
+        let until = now - i * 7 * 86400;
+        trend.push(online_stats(db, until - 7 * 86400, until).await?);
+    }
+    Ok(trend)
+}
@@ -0,0 +1,124 @@
+//! Polls where discord users vote on one of several options.
+//!
+//! A poll is open until its closing time, after which the votes of each option are stored, so
+//! the results can still be viewed after the poll is closed.
+use anyhow::{Context, Result};
+use sqlx::query;
+use tracing::info;
+
+use crate::model::discord::DiscordId;
+use crate::{Executor, Transaction, DB};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// A poll
+pub struct Poll {
+    pub id: i64,
+    /// Discord user that created the poll
+    pub creator: DiscordId,
+    /// Channel the poll message is in
+    pub channel: i64,
+    /// Message users vote on
+    pub message: i64,
+    pub question: String,
+    /// Options of the poll, in the order they are displayed
+    pub options: Vec<String>,
+    /// Unix timestamp of when the poll closes
+    pub close: i64,
+}
+
+/// Open a poll and return its id
+pub async fn open_poll(
+    tx: &mut Transaction, creator: DiscordId, channel: i64, message: i64, question: &str, options: &[String],
+    close: i64,
+) -> Result<i64> {
+    info!(?creator, close, "Opening poll");
+    let id = query!(
+        "INSERT INTO poll (creator,channel,message,question,close) VALUES (?,?,?,?,?)",
+        creator,
+        channel,
+        message,
+        question,
+        close
+    )
+    .execute(&mut tx.tx)
+    .await
+    .context("Failed to insert into poll")?
+    .last_insert_rowid();
+
+    for (position, text) in options.iter().enumerate() {
+        let position = i64::try_from(position)?;
+        query!("INSERT INTO poll_option (poll,position,text) VALUES (?,?,?)", id, position, text)
+            .execute(&mut tx.tx)
+            .await
+            .context("Failed to insert into poll_option")?;
+    }
+    Ok(id)
+}
+
+/// Get a poll
+pub async fn get_poll(exe: &mut Executor<'_>, id: i64) -> Result<Option<Poll>> {
+    let row = exe
+        .optional(query!(
+            "SELECT id,creator AS \"creator: DiscordId\",channel,message,question,close FROM poll WHERE id=?",
+            id
+        ))
+        .await
+        .context("Failed to fetch poll")?;
+    let row = match row {
+        Some(row) => row,
+        None => return Ok(None),
+    };
+    let options = poll_results(exe, id).await?.into_iter().map(|(text, _)| text).collect();
+    Ok(Some(Poll {
+        id: row.id,
+        creator: row.creator,
+        channel: row.channel,
+        message: row.message,
+        question: row.question,
+        options,
+        close: row.close,
+    }))
+}
+
+/// Get all open polls that should be closed at unix timestamp `now`
+pub async fn due_polls(db: &DB, now: i64) -> Result<Vec<Poll>> {
+    let ids = query!("SELECT id FROM poll WHERE NOT closed AND close<=? ORDER BY close,id", now)
+        .fetch_all(&db.pool)
+        .await
+        .context("Failed to fetch due polls")?;
+
+    let mut polls = Vec::with_capacity(ids.len());
+    for row in ids {
+        if let Some(poll) = get_poll(&mut db.exe(), row.id).await? {
+            polls.push(poll);
+        }
+    }
+    Ok(polls)
+}
+
+/// Close a poll with the votes of each of its options, in the same order as [`Poll::options`]
+pub async fn close_poll(tx: &mut Transaction, id: i64, votes: &[i64]) -> Result<()> {
+    info!(id, ?votes, "Closing poll");
+    query!("UPDATE poll SET closed=1 WHERE id=?", id)
+        .execute(&mut tx.tx)
+        .await
+        .context("Failed to close poll")?;
+    for (position, votes) in votes.iter().enumerate() {
+        let position = i64::try_from(position)?;
+        query!("UPDATE poll_option SET votes=? WHERE poll=? AND position=?", votes, id, position)
+            .execute(&mut tx.tx)
+            .await
+            .context("Failed to update poll_option")?;
+    }
+    Ok(())
+}
+
+/// Get the options of a poll along with their votes, the votes are `None` if the poll isn't
+/// closed yet.
+pub async fn poll_results(exe: &mut Executor<'_>, id: i64) -> Result<Vec<(String, Option<i64>)>> {
+    let rows = exe
+        .all(query!("SELECT text,votes FROM poll_option WHERE poll=? ORDER BY position", id))
+        .await
+        .context("Failed to fetch poll_option")?;
+    Ok(rows.into_iter().map(|row| (row.text, row.votes)).collect())
+}
@@ -0,0 +1,110 @@
+//! Votes held by staff members to change a member's rank.
+//!
+//! A vote is open until its closing time, after which it is closed with its result.
+//! Closed votes are kept in the `promotion_vote` table as a record of past votes.
+use anyhow::{Context, Result};
+use sqlx::query;
+use tracing::info;
+
+use crate::model::discord::DiscordId;
+use crate::model::member::{MemberId, MemberRank};
+use crate::{Executor, Transaction, DB};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// An open promotion vote
+pub struct PromotionVote {
+    pub id: i64,
+    pub mid: MemberId,
+    /// Discord user that opened the vote
+    pub caller: DiscordId,
+    /// Rank of the member when the vote is opened
+    pub old_rank: MemberRank,
+    /// Rank the member is changed to if the vote passes
+    pub rank: MemberRank,
+    /// Channel the poll message is in
+    pub channel: i64,
+    /// Poll message of the vote
+    pub message: i64,
+    /// Unix timestamp of when the vote closes
+    pub close: i64,
+}
+
+/// Open a promotion vote and return its id
+#[allow(clippy::too_many_arguments)]
+pub async fn open_promotion_vote(
+    tx: &mut Transaction, mid: MemberId, caller: DiscordId, old_rank: MemberRank, rank: MemberRank,
+    channel: i64, message: i64, close: i64,
+) -> Result<i64> {
+    info!(?mid, ?old_rank, ?rank, close, "Opening promotion vote");
+    let id = query!(
+        "INSERT INTO promotion_vote (mid,caller,old_rank,rank,channel,message,close) VALUES (?,?,?,?,?,?,?)",
+        mid,
+        caller,
+        old_rank,
+        rank,
+        channel,
+        message,
+        close
+    )
+    .execute(&mut tx.tx)
+    .await
+    .context("Failed to insert into promotion_vote")?
+    .last_insert_rowid();
+    Ok(id)
+}
+
+/// Get all open promotion votes that should be closed at unix timestamp `now`
+pub async fn due_promotion_votes(db: &DB, now: i64) -> Result<Vec<PromotionVote>> {
+    let rows = query!(
+        "SELECT id,mid,caller,old_rank,rank,channel,message,close FROM promotion_vote \
+        WHERE passed IS NULL AND close<=?",
+        now
+    )
+    .fetch_all(&db.pool)
+    .await
+    .context("Failed to fetch due promotion votes")?;
+
+    let mut votes = Vec::new();
+    for row in rows {
+        votes.push(PromotionVote {
+            id: row.id,
+            mid: MemberId(row.mid),
+            caller: DiscordId(row.caller),
+            old_rank: MemberRank::decode(&row.old_rank)?,
+            rank: MemberRank::decode(&row.rank)?,
+            channel: row.channel,
+            message: row.message,
+            close: row.close,
+        });
+    }
+    Ok(votes)
+}
+
+/// Close a promotion vote with its result
+pub async fn close_promotion_vote(
+    tx: &mut Transaction, id: i64, approvals: i64, rejections: i64, passed: bool,
+) -> Result<()> {
+    info!(id, approvals, rejections, passed, "Closing promotion vote");
+    query!(
+        "UPDATE promotion_vote SET approvals=?,rejections=?,passed=? WHERE id=?",
+        approvals,
+        rejections,
+        passed,
+        id
+    )
+    .execute(&mut tx.tx)
+    .await
+    .context("Failed to close promotion vote")?;
+    Ok(())
+}
+
+impl MemberId {
+    /// Check if the member has an open promotion vote
+    pub async fn in_promotion_vote(&self, exe: &mut Executor<'_>) -> Result<bool> {
+        Ok(exe
+            .optional(query!("SELECT id FROM promotion_vote WHERE mid=? AND passed IS NULL", self))
+            .await
+            .context("Failed to check if member has an open promotion vote")?
+            .is_some())
+    }
+}
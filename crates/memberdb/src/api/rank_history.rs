@@ -0,0 +1,127 @@
+//! History of guild rank changes, so staff can see how long a guild member has been at each rank
+//! when deciding on promotions.
+use anyhow::{Context, Result};
+use sqlx::query;
+
+use crate::model::guild::GuildRank;
+use crate::model::wynn::McId;
+use crate::{Executor, Transaction};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// A guild rank change of a guild member
+pub struct RankChange {
+    pub old: GuildRank,
+    pub new: GuildRank,
+    /// Unix timestamp of when the change is found
+    pub time: i64,
+}
+
+/// Record that guild profile `mcid` changed guild rank from `old` to `new` at unix timestamp
+/// `time`
+pub async fn record_rank_change(
+    tx: &mut Transaction, mcid: &McId, old: GuildRank, new: GuildRank, time: i64,
+) -> Result<()> {
+    query!("INSERT INTO guild_rank_history (mcid,old,new,time) VALUES (?,?,?,?)", mcid, old, new, time)
+        .execute(&mut tx.tx)
+        .await
+        .context("Failed to insert into guild_rank_history")?;
+    Ok(())
+}
+
+/// Get the guild rank changes of a guild profile, from oldest to newest
+pub async fn rank_history(exe: &mut Executor<'_>, mcid: &McId) -> Result<Vec<RankChange>> {
+    let rows = exe
+        .all(query!(
+            "SELECT old AS \"old: GuildRank\",new AS \"new: GuildRank\",time FROM guild_rank_history \
+            WHERE mcid=? ORDER BY time,id",
+            mcid
+        ))
+        .await
+        .context("Failed to fetch guild_rank_history")?;
+    Ok(rows.into_iter().map(|row| RankChange { old: row.old, new: row.new, time: row.time }).collect())
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// How long guild members stayed at a guild rank before it changed
+pub struct RankTenure {
+    pub rank: GuildRank,
+    /// Average amount of seconds spent at the rank
+    pub average: i64,
+    /// Amount of stays the average is taken from
+    pub samples: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// Amount of guild rank changes in a month
+pub struct MonthlyRankChanges {
+    /// Month in the form of "YYYY-MM"
+    pub month: String,
+    pub promotions: i64,
+    pub demotions: i64,
+}
+
+/// Get the average time guild members stayed at each guild rank, from the highest rank to the
+/// lowest, only counting rank changes since unix timestamp `since`.
+///
+/// A stay starts at a rank change and ends at the next one, so the first recorded change of a
+/// guild member and their current rank aren't counted. Ranks without any stays are left out.
+pub async fn rank_tenures(exe: &mut Executor<'_>, since: i64) -> Result<Vec<RankTenure>> {
+    let rows = exe
+        .all(query!(
+            "SELECT mcid,new AS \"new: GuildRank\",time FROM guild_rank_history \
+            WHERE time>=? ORDER BY mcid,time,id",
+            since
+        ))
+        .await
+        .context("Failed to fetch guild_rank_history")?;
+
+    let mut totals: Vec<(GuildRank, i64, i64)> = Vec::new();
+    for pair in rows.windows(2) {
+        let (start, end) = (&pair[0], &pair[1]);
+        if start.mcid != end.mcid {
+            continue;
+        }
+        let duration = end.time - start.time;
+        match totals.iter_mut().find(|(rank, ..)| *rank == start.new) {
+            Some((_, total, samples)) => {
+                *total += duration;
+                *samples += 1;
+            }
+            None => totals.push((start.new, duration, 1)),
+        }
+    }
+    totals.sort_by_key(|(rank, ..)| std::cmp::Reverse(*rank));
+    Ok(totals
+        .into_iter()
+        .map(|(rank, total, samples)| RankTenure { rank, average: total / samples, samples })
+        .collect())
+}
+
+/// Get the amount of promotions and demotions of each month since unix timestamp `since`, from
+/// the oldest month to the newest. Months without any rank changes are left out.
+pub async fn monthly_rank_changes(exe: &mut Executor<'_>, since: i64) -> Result<Vec<MonthlyRankChanges>> {
+    let rows = exe
+        .all(query!(
+            "SELECT strftime('%Y-%m',time,'unixepoch') AS \"month!: String\",\
+            old AS \"old: GuildRank\",new AS \"new: GuildRank\" FROM guild_rank_history \
+            WHERE time>=? ORDER BY time,id",
+            since
+        ))
+        .await
+        .context("Failed to fetch guild_rank_history")?;
+
+    let mut months: Vec<MonthlyRankChanges> = Vec::new();
+    for row in rows {
+        if months.last().is_none_or(|last| last.month != row.month) {
+            months.push(MonthlyRankChanges { month: row.month, promotions: 0, demotions: 0 });
+        }
+        if let Some(month) = months.last_mut() {
+            if row.new > row.old {
+                month.promotions += 1;
+            } else {
+                month.demotions += 1;
+            }
+        }
+    }
+    Ok(months)
+}
@@ -0,0 +1,103 @@
+//! Reminders set by discord users, which are sent to them once they are due.
+//!
+//! A reminder is removed from the `reminder` table once it is sent or cancelled.
+use anyhow::{Context, Result};
+use sqlx::query;
+use tracing::info;
+
+use crate::model::discord::DiscordId;
+use crate::{Executor, Transaction, DB};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// A reminder that hasn't been sent yet
+pub struct Reminder {
+    pub id: i64,
+    /// Discord user that set the reminder
+    pub user: DiscordId,
+    /// Channel the reminder is set in, which it is sent to if the user can't be DMed
+    pub channel: i64,
+    pub text: String,
+    /// Unix timestamp of when the reminder is set
+    pub created: i64,
+    /// Unix timestamp of when the reminder is due
+    pub due: i64,
+}
+
+/// Add a reminder and return its id
+pub async fn add_reminder(
+    tx: &mut Transaction, user: DiscordId, channel: i64, text: &str, created: i64, due: i64,
+) -> Result<i64> {
+    info!(?user, due, "Adding reminder");
+    let id = query!(
+        "INSERT INTO reminder (user,channel,text,created,due) VALUES (?,?,?,?,?)",
+        user,
+        channel,
+        text,
+        created,
+        due
+    )
+    .execute(&mut tx.tx)
+    .await
+    .context("Failed to insert into reminder")?
+    .last_insert_rowid();
+    Ok(id)
+}
+
+/// Get the reminders of a discord user, from the soonest to be due
+pub async fn user_reminders(exe: &mut Executor<'_>, user: DiscordId) -> Result<Vec<Reminder>> {
+    let rows = exe
+        .all(query!(
+            "SELECT id,user AS \"user: DiscordId\",channel,text,created,due FROM reminder \
+            WHERE user=? ORDER BY due,id",
+            user
+        ))
+        .await
+        .context("Failed to fetch reminders of user")?;
+    Ok(rows
+        .into_iter()
+        .map(|row| Reminder {
+            id: row.id,
+            user: row.user,
+            channel: row.channel,
+            text: row.text,
+            created: row.created,
+            due: row.due,
+        })
+        .collect())
+}
+
+/// Get all reminders that are due at unix timestamp `now`
+pub async fn due_reminders(db: &DB, now: i64) -> Result<Vec<Reminder>> {
+    let rows = query!(
+        "SELECT id,user AS \"user: DiscordId\",channel,text,created,due FROM reminder \
+        WHERE due<=? ORDER BY due,id",
+        now
+    )
+    .fetch_all(&db.pool)
+    .await
+    .context("Failed to fetch due reminders")?;
+    Ok(rows
+        .into_iter()
+        .map(|row| Reminder {
+            id: row.id,
+            user: row.user,
+            channel: row.channel,
+            text: row.text,
+            created: row.created,
+            due: row.due,
+        })
+        .collect())
+}
+
+/// Remove a reminder, returns false if it doesn't exist.
+///
+/// If `user` is given, the reminder is only removed if it is set by them.
+pub async fn remove_reminder(tx: &mut Transaction, id: i64, user: Option<DiscordId>) -> Result<bool> {
+    let result = match user {
+        Some(user) => {
+            query!("DELETE FROM reminder WHERE id=? AND user=?", id, user).execute(&mut tx.tx).await
+        }
+        None => query!("DELETE FROM reminder WHERE id=?", id).execute(&mut tx.tx).await,
+    };
+    Ok(result.context("Failed to delete from reminder")?.rows_affected() > 0)
+}
@@ -0,0 +1,129 @@
+//! OAuth2 tokens of discord users that linked their account for discord's linked roles, and the
+//! metadata pushed to their role connection.
+use anyhow::{Context, Result};
+use sqlx::query;
+use tracing::info;
+
+use crate::model::discord::DiscordId;
+use crate::model::guild::GuildRank;
+use crate::{Executor, Transaction, DB};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// OAuth2 tokens granted by a discord user
+pub struct RoleConnection {
+    pub user: DiscordId,
+    pub access_token: String,
+    pub refresh_token: String,
+    /// Unix timestamp of when the access token expires
+    pub expires: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// Metadata of a discord user's role connection
+pub struct RoleMetadata {
+    /// Linked wynncraft ign
+    pub ign: Option<String>,
+    /// Guild rank, if the user is in the guild
+    pub rank: Option<GuildRank>,
+    /// Weekly guild xp, 0 if the user isn't in the guild
+    pub weekly_xp: i64,
+}
+
+impl RoleMetadata {
+    /// If the user is in the guild
+    pub fn in_guild(&self) -> bool {
+        self.rank.is_some()
+    }
+
+    /// Guild rank as a number from 1 (recruit) to 6 (owner), 0 if the user isn't in the guild.
+    /// ```
+    /// use memberdb::model::guild::GuildRank;
+    /// use memberdb::role_connection::RoleMetadata;
+    ///
+    /// let mut metadata = RoleMetadata { ign: None, rank: None, weekly_xp: 0 };
+    /// assert_eq!(metadata.rank_level(), 0);
+    /// metadata.rank = Some(GuildRank::Recruit);
+    /// assert_eq!(metadata.rank_level(), 1);
+    /// metadata.rank = Some(GuildRank::Owner);
+    /// assert_eq!(metadata.rank_level(), 6);
+    /// ```
+    pub fn rank_level(&self) -> i64 {
+        match self.rank {
+            Some(GuildRank::Recruit) => 1,
+            Some(GuildRank::Recruiter) => 2,
+            Some(GuildRank::Captain) => 3,
+            Some(GuildRank::Strategist) => 4,
+            Some(GuildRank::Chief) => 5,
+            Some(GuildRank::Owner) => 6,
+            None => 0,
+        }
+    }
+}
+
+/// Store the tokens of a discord user, replacing the previous ones
+pub async fn set_role_connection(tx: &mut Transaction, conn: &RoleConnection) -> Result<()> {
+    info!(user = ?conn.user, "Setting role connection");
+    query!(
+        "INSERT INTO role_connection (user,access_token,refresh_token,expires) VALUES (?,?,?,?) \
+        ON CONFLICT(user) DO UPDATE SET \
+        access_token=excluded.access_token,refresh_token=excluded.refresh_token,expires=excluded.expires",
+        conn.user,
+        conn.access_token,
+        conn.refresh_token,
+        conn.expires
+    )
+    .execute(&mut tx.tx)
+    .await
+    .context("Failed to upsert role_connection")?;
+    Ok(())
+}
+
+/// Get all stored role connections
+pub async fn role_connections(db: &DB) -> Result<Vec<RoleConnection>> {
+    let rows = query!(
+        "SELECT user AS \"user: DiscordId\",access_token,refresh_token,expires FROM role_connection \
+        ORDER BY user"
+    )
+    .fetch_all(&db.pool)
+    .await
+    .context("Failed to fetch role connections")?;
+    Ok(rows
+        .into_iter()
+        .map(|row| RoleConnection {
+            user: row.user,
+            access_token: row.access_token,
+            refresh_token: row.refresh_token,
+            expires: row.expires,
+        })
+        .collect())
+}
+
+/// Remove the tokens of a discord user, returns false if there are none
+pub async fn remove_role_connection(tx: &mut Transaction, user: DiscordId) -> Result<bool> {
+    info!(?user, "Removing role connection");
+    let result = query!("DELETE FROM role_connection WHERE user=?", user)
+        .execute(&mut tx.tx)
+        .await
+        .context("Failed to delete from role_connection")?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Get the role connection metadata of a discord user
+pub async fn role_metadata(exe: &mut Executor<'_>, user: DiscordId) -> Result<RoleMetadata> {
+    let row = exe
+        .optional(query!(
+            "SELECT wynn.ign AS \"ign?\",guild.rank AS \"rank?: GuildRank\",guild.xp_week AS \"xp_week?\" \
+            FROM discord \
+            LEFT JOIN member ON member.oid=discord.mid \
+            LEFT JOIN wynn ON wynn.id=member.mcid \
+            LEFT JOIN guild ON guild.id=wynn.id AND wynn.guild \
+            WHERE discord.id=?",
+            user
+        ))
+        .await
+        .context("Failed to fetch role metadata")?;
+    Ok(match row {
+        Some(row) => RoleMetadata { ign: row.ign, rank: row.rank, weekly_xp: row.xp_week.unwrap_or(0) },
+        None => RoleMetadata { ign: None, rank: None, weekly_xp: 0 },
+    })
+}
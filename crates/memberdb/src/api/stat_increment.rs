@@ -0,0 +1,158 @@
+//! Audit log of stat increments, which records where each increment of the message, voice, stream,
+//! online and xp stats came from, so disputed stats and leaderboards can be looked into.
+//!
+//! Increments are only recorded while it is enabled with [`DB::set_stat_audit`], as it is meant
+//! for debugging, and the records are pruned after a while with [`prune_increments`].
+use std::fmt;
+use std::str::FromStr;
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use sqlx::query;
+use tracing::info;
+
+use util::{impl_sqlx_type, ioerr};
+
+use crate::model::discord::DiscordId;
+use crate::model::wynn::McId;
+use crate::{Executor, Transaction, DB};
+
+#[derive(Serialize, Debug, Copy, Clone, PartialEq, Eq)]
+/// A stat whose increments are recorded
+pub enum AuditedStat {
+    Message,
+    Voice,
+    Stream,
+    Online,
+    Xp,
+}
+
+impl_sqlx_type!(AuditedStat);
+
+impl fmt::Display for AuditedStat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Message => write!(f, "message"),
+            Self::Voice => write!(f, "voice"),
+            Self::Stream => write!(f, "stream"),
+            Self::Online => write!(f, "online"),
+            Self::Xp => write!(f, "xp"),
+        }
+    }
+}
+
+impl FromStr for AuditedStat {
+    type Err = std::io::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "message" => Ok(Self::Message),
+            "voice" => Ok(Self::Voice),
+            "stream" => Ok(Self::Stream),
+            "online" => Ok(Self::Online),
+            "xp" => Ok(Self::Xp),
+            _ => ioerr!("Failed to parse '{}' as AuditedStat", s),
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+/// An increment of a stat
+pub struct StatIncrement {
+    pub id: i64,
+    /// Discord profile whose stat is incremented, for the message, voice and stream stats
+    pub discord: Option<DiscordId>,
+    /// Mc account whose stat is incremented, for the online and xp stats
+    pub mcid: Option<McId>,
+    pub stat: AuditedStat,
+    pub amount: i64,
+    /// What the increment came from, ex: "MemberContribute"
+    pub source: String,
+    /// Unix timestamp of when the stat is incremented
+    pub time: i64,
+}
+
+/// Record that `stat` of discord profile `id` is incremented by `amount` from `source` at unix
+/// timestamp `time`.
+/// Nothing is recorded if stat increments weren't recorded when the transaction began.
+pub async fn record_discord(
+    tx: &mut Transaction, id: DiscordId, stat: AuditedStat, amount: i64, source: &str, time: i64,
+) -> Result<()> {
+    record(tx, Some(id), None, stat, amount, source, time).await
+}
+
+/// Record that `stat` of mc account `mcid` is incremented by `amount` from `source` at unix
+/// timestamp `time`.
+/// Nothing is recorded if stat increments weren't recorded when the transaction began.
+pub async fn record_wynn(
+    tx: &mut Transaction, mcid: &McId, stat: AuditedStat, amount: i64, source: &str, time: i64,
+) -> Result<()> {
+    record(tx, None, Some(mcid), stat, amount, source, time).await
+}
+
+async fn record(
+    tx: &mut Transaction, discord: Option<DiscordId>, mcid: Option<&McId>, stat: AuditedStat, amount: i64,
+    source: &str, time: i64,
+) -> Result<()> {
+    if !tx.stat_audit {
+        return Ok(());
+    }
+    query!(
+        "INSERT INTO stat_increments (discord,mcid,stat,amount,source,time) VALUES (?,?,?,?,?,?)",
+        discord,
+        mcid,
+        stat,
+        amount,
+        source,
+        time
+    )
+    .execute(&mut tx.tx)
+    .await
+    .context("Failed to insert into stat_increments")?;
+    Ok(())
+}
+
+/// Remove the increments recorded before unix timestamp `before`, returns the amount of increments
+/// removed
+pub async fn prune_increments(db: &DB, before: i64) -> Result<u64> {
+    let result = query!("DELETE FROM stat_increments WHERE time<?", before)
+        .execute(&db.pool)
+        .await
+        .context("Failed to delete expired stat increments")?;
+    info!(removed = result.rows_affected(), before, "Pruned stat increments");
+    Ok(result.rows_affected())
+}
+
+/// Get the latest `limit` increments of the stats of discord profile `discord` and mc account
+/// `mcid`, newest first.
+/// If `stat` is given, only the increments of that stat are included.
+pub async fn increments(
+    exe: &mut Executor<'_>, discord: Option<DiscordId>, mcid: Option<&McId>, stat: Option<AuditedStat>,
+    limit: i64,
+) -> Result<Vec<StatIncrement>> {
+    let rows = exe
+        .all(query!(
+            "SELECT id,discord AS \"discord: DiscordId\",mcid AS \"mcid: McId\",\
+            stat AS \"stat: AuditedStat\",amount,source,time FROM stat_increments \
+            WHERE (discord=? OR mcid=?) AND (? IS NULL OR stat=?) ORDER BY time DESC,id DESC LIMIT ?",
+            discord,
+            mcid,
+            stat,
+            stat,
+            limit
+        ))
+        .await
+        .context("Failed to fetch stat_increments")?;
+    Ok(rows
+        .into_iter()
+        .map(|row| StatIncrement {
+            id: row.id,
+            discord: row.discord,
+            mcid: row.mcid,
+            stat: row.stat,
+            amount: row.amount,
+            source: row.source,
+            time: row.time,
+        })
+        .collect())
+}
@@ -0,0 +1,58 @@
+//! Manually resetting specific stats, ex: after fixing a bug in their tracking.
+//!
+//! Every reset is recorded in the `stat_reset` table as an audit log.
+use anyhow::{Context, Result};
+use sqlx::query;
+use tracing::info;
+
+use crate::events::DBEvent;
+use crate::model::db::{ProfileType, Stat};
+use crate::model::discord::DiscordId;
+use crate::model::member::MemberId;
+use crate::Transaction;
+
+/// Set `stat` to 0 for member `mid`, or for everyone if `mid` is `None`, and return the amount of
+/// profiles that are reset.
+///
+/// `caller` is the discord user that requested the reset, and `now` is the current unix timestamp.
+pub async fn reset_stat(
+    tx: &mut Transaction, stat: &Stat, mid: Option<MemberId>, caller: DiscordId, now: i64,
+) -> Result<u64> {
+    let column = stat.to_column();
+    let profile = column.profile().context("Stat isn't part of a profile table")?;
+    info!(?stat, ?mid, "Resetting stat");
+
+    let count = match mid {
+        Some(mid) => {
+            let condition = match profile {
+                ProfileType::Guild => "id=(SELECT mcid FROM member WHERE oid=?)",
+                ProfileType::Wynn | ProfileType::Discord => "mid=?",
+            };
+            let sql = format!("UPDATE {} SET {}=0 WHERE {}", profile.name(), column.name(), condition);
+            sqlx::query(&sql).bind(mid).execute(&mut tx.tx).await
+        }
+        None => {
+            let sql = format!("UPDATE {} SET {}=0", profile.name(), column.name());
+            sqlx::query(&sql).execute(&mut tx.tx).await
+        }
+    }
+    .context("Failed to reset stat")?
+    .rows_affected();
+
+    let name = column.name();
+    let recorded_count = i64::try_from(count)?;
+    query!(
+        "INSERT INTO stat_reset (stat,mid,caller,time,count) VALUES (?,?,?,?,?)",
+        name,
+        mid,
+        caller,
+        now,
+        recorded_count
+    )
+    .execute(&mut tx.tx)
+    .await
+    .context("Failed to insert into stat_reset")?;
+
+    tx.signal(DBEvent::StatReset { stat: stat.clone(), mid, caller });
+    Ok(count)
+}
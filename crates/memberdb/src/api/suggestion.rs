@@ -0,0 +1,199 @@
+//! Suggestions submitted by discord users, which other users vote on and staff review.
+//!
+//! Each user has at most one vote on a suggestion, either up or down.
+use std::fmt;
+use std::str::FromStr;
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use sqlx::query;
+use tracing::info;
+
+use util::{impl_sqlx_type, ioerr};
+
+use crate::model::discord::DiscordId;
+use crate::{Executor, Transaction};
+
+#[derive(Serialize, Debug, Copy, Clone, PartialEq, Eq)]
+/// Review status of a suggestion
+pub enum SuggestionStatus {
+    /// Not reviewed by staff yet
+    Open,
+    Accepted,
+    Rejected,
+    Implemented,
+}
+
+impl_sqlx_type!(SuggestionStatus);
+
+impl fmt::Display for SuggestionStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Open => write!(f, "open"),
+            Self::Accepted => write!(f, "accepted"),
+            Self::Rejected => write!(f, "rejected"),
+            Self::Implemented => write!(f, "implemented"),
+        }
+    }
+}
+
+impl FromStr for SuggestionStatus {
+    type Err = std::io::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "open" => Ok(Self::Open),
+            "accepted" => Ok(Self::Accepted),
+            "rejected" => Ok(Self::Rejected),
+            "implemented" => Ok(Self::Implemented),
+            _ => ioerr!("Failed to parse '{}' as SuggestionStatus", s),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// A suggestion along with its votes
+pub struct Suggestion {
+    pub id: i64,
+    /// Discord user that submitted the suggestion
+    pub author: DiscordId,
+    pub text: String,
+    pub status: SuggestionStatus,
+    /// Channel the suggestion message is in, `None` if it isn't posted yet
+    pub channel: Option<i64>,
+    /// Message users vote on, `None` if it isn't posted yet
+    pub message: Option<i64>,
+    /// Unix timestamp of when the suggestion is submitted
+    pub created: i64,
+    pub upvotes: i64,
+    pub downvotes: i64,
+}
+
+/// Add a suggestion and return its id
+pub async fn add_suggestion(
+    tx: &mut Transaction, author: DiscordId, text: &str, created: i64,
+) -> Result<i64> {
+    info!(?author, "Adding suggestion");
+    let id = query!("INSERT INTO suggestion (author,text,created) VALUES (?,?,?)", author, text, created)
+        .execute(&mut tx.tx)
+        .await
+        .context("Failed to insert into suggestion")?
+        .last_insert_rowid();
+    Ok(id)
+}
+
+/// Set the message a suggestion is posted as
+pub async fn set_suggestion_message(tx: &mut Transaction, id: i64, channel: i64, message: i64) -> Result<()> {
+    query!("UPDATE suggestion SET channel=?,message=? WHERE id=?", channel, message, id)
+        .execute(&mut tx.tx)
+        .await
+        .context("Failed to set suggestion message")?;
+    Ok(())
+}
+
+/// Set the review status of a suggestion, returns false if it doesn't exist
+pub async fn set_suggestion_status(tx: &mut Transaction, id: i64, status: SuggestionStatus) -> Result<bool> {
+    info!(id, %status, "Setting suggestion status");
+    let result = query!("UPDATE suggestion SET status=? WHERE id=?", status, id)
+        .execute(&mut tx.tx)
+        .await
+        .context("Failed to set suggestion status")?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Vote on a suggestion.
+///
+/// Voting the same way as the user's existing vote removes it, otherwise it replaces the
+/// existing vote.
+pub async fn vote_suggestion(tx: &mut Transaction, id: i64, user: DiscordId, up: bool) -> Result<()> {
+    let existing = tx
+        .exe()
+        .optional(query!("SELECT up FROM suggestion_vote WHERE suggestion=? AND user=?", id, user))
+        .await
+        .context("Failed to fetch suggestion_vote")?;
+    match existing {
+        Some(row) if (row.up != 0) == up => {
+            query!("DELETE FROM suggestion_vote WHERE suggestion=? AND user=?", id, user)
+                .execute(&mut tx.tx)
+                .await
+                .context("Failed to delete from suggestion_vote")?;
+        }
+        _ => {
+            query!("REPLACE INTO suggestion_vote (suggestion,user,up) VALUES (?,?,?)", id, user, up)
+                .execute(&mut tx.tx)
+                .await
+                .context("Failed to insert into suggestion_vote")?;
+        }
+    }
+    Ok(())
+}
+
+/// Get a suggestion
+pub async fn get_suggestion(exe: &mut Executor<'_>, id: i64) -> Result<Option<Suggestion>> {
+    let row = exe
+        .optional(query!(
+            "SELECT id,author AS \"author: DiscordId\",text,status AS \"status: SuggestionStatus\",\
+            channel,message,created,\
+            (SELECT COUNT(*) FROM suggestion_vote WHERE suggestion=suggestion.id AND up) AS \"upvotes!: i64\",\
+            (SELECT COUNT(*) FROM suggestion_vote WHERE suggestion=suggestion.id AND NOT up) AS \"downvotes!: i64\" \
+            FROM suggestion WHERE id=?",
+            id
+        ))
+        .await
+        .context("Failed to fetch suggestion")?;
+    Ok(row.map(|row| Suggestion {
+        id: row.id,
+        author: row.author,
+        text: row.text,
+        status: row.status,
+        channel: row.channel,
+        message: row.message,
+        created: row.created,
+        upvotes: row.upvotes,
+        downvotes: row.downvotes,
+    }))
+}
+
+/// Get the id of the suggestion posted as `message`
+pub async fn suggestion_by_message(exe: &mut Executor<'_>, message: i64) -> Result<Option<i64>> {
+    Ok(exe
+        .optional(query!("SELECT id FROM suggestion WHERE message=?", message))
+        .await
+        .context("Failed to fetch suggestion by message")?
+        .map(|row| row.id))
+}
+
+/// Get the suggestions, optionally only the ones with `status`, from the highest score (upvotes
+/// minus downvotes) to the lowest
+pub async fn list_suggestions(
+    exe: &mut Executor<'_>, status: Option<SuggestionStatus>,
+) -> Result<Vec<Suggestion>> {
+    let rows = exe
+        .all(query!(
+            "SELECT id,author AS \"author: DiscordId\",text,status AS \"status: SuggestionStatus\",\
+            channel,message,created,\
+            (SELECT COUNT(*) FROM suggestion_vote WHERE suggestion=suggestion.id AND up) AS \"upvotes!: i64\",\
+            (SELECT COUNT(*) FROM suggestion_vote WHERE suggestion=suggestion.id AND NOT up) AS \"downvotes!: i64\" \
+            FROM suggestion WHERE ? IS NULL OR status=? \
+            ORDER BY (SELECT TOTAL(CASE WHEN up THEN 1 ELSE -1 END) FROM suggestion_vote \
+            WHERE suggestion=suggestion.id) DESC,id",
+            status,
+            status
+        ))
+        .await
+        .context("Failed to fetch suggestions")?;
+    Ok(rows
+        .into_iter()
+        .map(|row| Suggestion {
+            id: row.id,
+            author: row.author,
+            text: row.text,
+            status: row.status,
+            channel: row.channel,
+            message: row.message,
+            created: row.created,
+            upvotes: row.upvotes,
+            downvotes: row.downvotes,
+        })
+        .collect())
+}
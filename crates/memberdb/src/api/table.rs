@@ -1,20 +1,56 @@
-//! Functions that fetches multiple rows from database
+//! Functions that fetches multiple rows from database.
+//!
+//! Stats in the returned tables are displayed in the given [`StatFormat`].
 use std::cmp::Ordering;
 
 use anyhow::Result;
-use serenity::client::Cache;
 use sqlx::sqlite::SqliteRow;
-use sqlx::Row;
+use sqlx::{Execute, Row};
+
+use util::string::StatFormat;
 
 use crate::model::db::{Column, Stat};
-use crate::model::discord::DiscordId;
-use crate::query_builder::{Filter, MemberName, QueryAction, QueryBuilder, SelectAction, Selectable, Sort};
+use crate::model::discord::{DiscordId, UserNames};
+use crate::query_builder::{
+    Aggregate, AggregateFn, ChannelVoice, Filter, MemberName, QueryAction, QueryBuilder, SelectAction,
+    Selectable, Sort,
+};
 use crate::DB;
 
 /// Return all members as list with optional filter applied.
 /// Each member is represented as a list with following structure: [ign, discord name, member rank]
 /// If a field doesn't exists, an empty string is used.
-pub async fn list_members(cache: &Cache, db: &DB, filters: &Vec<Filter>) -> Result<Vec<Vec<String>>> {
+pub async fn list_members(
+    names: &dyn UserNames, fmt: &StatFormat, db: &DB, filters: &Vec<Filter>,
+) -> Result<Vec<Vec<String>>> {
+    let query = list_members_query(filters).build();
+
+    let query = sqlx::query(&query).map(|r: SqliteRow| {
+        vec![
+            // ign
+            Column::WIgn.format_val(&r, names, fmt),
+            // discord name
+            match r.get::<Option<DiscordId>, &str>("discord") {
+                Some(id) => names.user_name(id).unwrap_or_default(),
+                None => String::new(),
+            },
+            // member rank
+            Column::MRank.format_val(&r, names, fmt),
+        ]
+    });
+    Ok(db.stats.time(query.sql(), query.fetch_all(&db.pool)).await?)
+}
+
+/// Return the summary row of [`list_members`], see [`summary_row`].
+pub async fn list_members_summary(
+    names: &dyn UserNames, fmt: &StatFormat, db: &DB, filters: &Vec<Filter>,
+) -> Result<Vec<String>> {
+    let mut summary = summary_row(names, fmt, db, list_members_query(filters).build(), &[]).await?;
+    summary.resize(3, String::new());
+    Ok(summary)
+}
+
+fn list_members_query(filters: &Vec<Filter>) -> QueryBuilder {
     let mut query = QueryBuilder::new();
     query
         .with(&Column::WIgn)
@@ -25,22 +61,7 @@ pub async fn list_members(cache: &Cache, db: &DB, filters: &Vec<Filter>) -> Resu
     for filter in filters {
         query.with(filter);
     }
-    let query = query.build();
-
-    let query = sqlx::query(&query).map(|r: SqliteRow| {
-        vec![
-            // ign
-            Column::WIgn.format_val(&r, cache),
-            // discord name
-            match r.get::<Option<DiscordId>, &str>("discord").map(|id| id.to_user(cache)) {
-                Some(Some(u)) => format!("{}#{}", u.name, u.discriminator),
-                _ => String::new(),
-            },
-            // member rank
-            Column::MRank.format_val(&r, cache),
-        ]
-    });
-    Ok(query.fetch_all(&db.pool).await?)
+    query
 }
 
 /// Return a stat leaderboard and its heading.
@@ -51,8 +72,34 @@ pub async fn list_members(cache: &Cache, db: &DB, filters: &Vec<Filter>) -> Resu
 ///
 /// if `no_zero` is true, then rows with stat val of 0 won't be included.
 pub async fn stat_leaderboard(
-    cache: &Cache, db: &DB, stat: &Stat, filters: &Vec<Filter>,
+    names: &dyn UserNames, fmt: &StatFormat, db: &DB, stat: &Stat, filters: &Vec<Filter>,
 ) -> Result<(Vec<Vec<String>>, Vec<String>)> {
+    let stat_col = stat.to_column();
+    let query = stat_leaderboard_query(stat, filters).build_lb("r");
+
+    let query = sqlx::query(&query).map(|r: SqliteRow| {
+        let name = MemberName.format_val(&r, names, fmt);
+        let lb_rank = r.get::<i64, _>("r");
+        let stat_val = stat_col.format_val(&r, names, fmt);
+        vec![lb_rank.to_string(), name, stat_val]
+    });
+    let result = db.stats.time(query.sql(), query.fetch_all(&db.pool)).await?;
+    let header = vec![String::from("#"), String::from("name"), stat_col.table_name().to_string()];
+
+    Ok((result, header))
+}
+
+/// Return the summary row of [`stat_leaderboard`], see [`summary_row`].
+pub async fn stat_leaderboard_summary(
+    names: &dyn UserNames, fmt: &StatFormat, db: &DB, stat: &Stat, filters: &Vec<Filter>,
+) -> Result<Vec<String>> {
+    let query = stat_leaderboard_query(stat, filters).build();
+    let mut summary = summary_row(names, fmt, db, query, &[stat]).await?;
+    summary.insert(1, String::new());
+    Ok(summary)
+}
+
+fn stat_leaderboard_query(stat: &Stat, filters: &Vec<Filter>) -> QueryBuilder {
     let stat_col = stat.to_column();
     let mut query = QueryBuilder::new();
     query.with(stat).with(&Sort::Desc(stat_col.clone())).with(&MemberName);
@@ -72,34 +119,243 @@ pub async fn stat_leaderboard(
         query.filter(stat_col.query_ident().to_string());
     }
     query.with(&stat_col.profile().unwrap());
+    query
+}
+
+/// Return the leaderboards of multiple stats and their headings, which are the same as the ones
+/// returned by [`stat_leaderboard`] without filters.
+///
+/// All leaderboards are ranked within a single query, so the member table is only scanned once.
+pub async fn stat_leaderboards<const N: usize>(
+    names: &dyn UserNames, fmt: &StatFormat, db: &DB, stats: &[Stat; N],
+) -> Result<[(Vec<Vec<String>>, Vec<String>); N]> {
+    let cols = stats.iter().map(Stat::to_column).collect::<Vec<Column>>();
+    let mut query = QueryBuilder::new();
+    query.with(&MemberName);
+
+    let mut non_zero = Vec::with_capacity(N);
+    for col in &cols {
+        // Stat of members without the profile is null, so they are ranked last and filtered out
+        let profile = col.profile().unwrap();
+        let stat = format!("CASE WHEN {} THEN {} END", profile.exist_condition(), col.select_query());
+        query.select(format!("{} AS {}", stat, col.query_ident()));
+        query.select(format!("RANK() OVER(ORDER BY {} DESC NULLS LAST) AS r_{}", stat, col.query_ident()));
+        non_zero.push(col.query_ident());
+    }
+    // Members with all stats being 0 are ranked after everyone else, so they can be skipped
+    query.filter(format!("({})", non_zero.join(" OR ")));
+
+    let query = query.build();
+    let rows = db.stats.time(&query, sqlx::query(&query).fetch_all(&db.pool)).await?;
+
+    Ok(std::array::from_fn(|i| {
+        let col = &cols[i];
+        let rank_ident = format!("r_{}", col.query_ident());
+        let mut lb = rows
+            .iter()
+            .filter(|r| r.get::<Option<i64>, _>(col.query_ident()).unwrap_or(0) != 0)
+            .map(|r| (r.get::<i64, _>(rank_ident.as_str()), r))
+            .collect::<Vec<(i64, &SqliteRow)>>();
+        lb.sort_by_key(|(rank, _)| *rank);
+
+        let result = lb
+            .into_iter()
+            .map(|(rank, r)| {
+                vec![rank.to_string(), MemberName.format_val(r, names, fmt), col.format_val(r, names, fmt)]
+            })
+            .collect();
+        let header = vec![String::from("#"), String::from("name"), col.table_name().to_string()];
+        (result, header)
+    }))
+}
 
-    let query = query.build_lb("r");
+/// Return a leaderboard of voice time spent in a specific voice channel, and its heading.
+///
+/// The leaderboard can be applied with a filter, and members without voice time in the channel
+/// aren't included.
+/// Each row contains following items: [lb rank, name, voice time].
+pub async fn channel_voice_leaderboard(
+    names: &dyn UserNames, fmt: &StatFormat, db: &DB, channel: i64, filters: &Vec<Filter>,
+) -> Result<(Vec<Vec<String>>, Vec<String>)> {
+    let voice = ChannelVoice(channel);
+    let query = channel_voice_leaderboard_query(&voice, filters).build_lb("r");
 
     let query = sqlx::query(&query).map(|r: SqliteRow| {
-        let name = MemberName.format_val(&r, cache);
+        let name = MemberName.format_val(&r, names, fmt);
         let lb_rank = r.get::<i64, _>("r");
-        let stat_val = stat_col.format_val(&r, cache);
+        let stat_val = voice.format_val(&r, names, fmt);
         vec![lb_rank.to_string(), name, stat_val]
     });
-    let result = query.fetch_all(&db.pool).await?;
-    let header = vec![String::from("#"), String::from("name"), stat_col.table_name().to_string()];
+    let result = db.stats.time(query.sql(), query.fetch_all(&db.pool)).await?;
+    let header = vec![String::from("#"), String::from("name"), voice.table_name().to_string()];
 
     Ok((result, header))
 }
 
-/// Fetch values from the database by specifying what columns to select, and actions (like
-/// filtering and ordering) to apply.
-pub async fn make_table(
-    cache: &Cache, db: &DB, cols: &Vec<impl Selectable>, actions: &Vec<impl QueryAction>,
+/// Return the summary row of [`channel_voice_leaderboard`], see [`summary_row`].
+pub async fn channel_voice_leaderboard_summary(
+    names: &dyn UserNames, fmt: &StatFormat, db: &DB, channel: i64, filters: &Vec<Filter>,
+) -> Result<Vec<String>> {
+    let voice = ChannelVoice(channel);
+    let query = channel_voice_leaderboard_query(&voice, filters).build();
+    let mut summary = summary_row(names, fmt, db, query, &[&voice]).await?;
+    summary.insert(1, String::new());
+    Ok(summary)
+}
+
+fn channel_voice_leaderboard_query(voice: &ChannelVoice, filters: &Vec<Filter>) -> QueryBuilder {
+    let mut query = QueryBuilder::new();
+    query
+        .with(voice)
+        .with(&MemberName)
+        .filter(format!("{} > 0", ChannelVoice::IDENT))
+        .order(format!("{} DESC", ChannelVoice::IDENT));
+    for filter in filters {
+        query.with(filter);
+    }
+    query
+}
+
+/// Return a leaderboard of discord users by the amount of members they invited to the discord
+/// server, and its heading.
+///
+/// Only invited users that are currently members are counted.
+/// Each row contains following items: [lb rank, name, recruits].
+/// The name field is the inviter's ign if they are a member with a mc account, otherwise their
+/// discord name is used.
+pub async fn recruiter_leaderboard(names: &dyn UserNames, db: &DB) -> Result<(Vec<Vec<String>>, Vec<String>)> {
+    let rows = db.exe().all(sqlx::query!(
+        "SELECT discord_invite.inviter AS \"inviter!: DiscordId\",wynn.ign AS \"ign?\",COUNT(*) AS \"count!: i64\" \
+        FROM discord_invite \
+        JOIN discord ON discord.id=discord_invite.id \
+        LEFT JOIN discord AS inviter ON inviter.id=discord_invite.inviter \
+        LEFT JOIN member ON member.oid=inviter.mid \
+        LEFT JOIN wynn ON wynn.id=member.mcid \
+        WHERE discord.mid NOT NULL AND discord_invite.inviter NOT NULL \
+        GROUP BY discord_invite.inviter \
+        ORDER BY COUNT(*) DESC"
+    ))
+    .await?;
+
+    let result = rows
+        .into_iter()
+        .enumerate()
+        .map(|(i, row)| {
+            let name = match row.ign {
+                Some(ign) => ign,
+                None => names.user_name(row.inviter).unwrap_or_else(|| row.inviter.to_string()),
+            };
+            vec![(i + 1).to_string(), name, row.count.to_string()]
+        })
+        .collect();
+    let header = vec![String::from("#"), String::from("name"), String::from("recruits")];
+
+    Ok((result, header))
+}
+
+/// Return a table of wynn profiles that aren't linked to a discord profile, but have at least
+/// `min_xp` guild xp or `min_online` seconds of online time, and its heading.
+///
+/// Each row contains following items: [#, ign, guild rank, online, xp], sorted by online time and
+/// then by xp. The guild rank field is empty if the profile isn't in the guild.
+pub async fn unlinked_profiles(
+    fmt: &StatFormat, db: &DB, min_xp: i64, min_online: i64,
+) -> Result<(Vec<Vec<String>>, Vec<String>)> {
+    let rows = db
+        .exe()
+        .all(sqlx::query!(
+            "SELECT wynn.ign,wynn.guild AS \"guild: bool\",guild.rank AS \"rank?\",wynn.activity,\
+            COALESCE(guild.xp,0) AS \"xp!: i64\" \
+            FROM wynn \
+            LEFT JOIN member ON member.oid=wynn.mid \
+            LEFT JOIN guild ON guild.id=wynn.id \
+            WHERE member.discord IS NULL AND (COALESCE(guild.xp,0)>=? OR wynn.activity>=?) \
+            ORDER BY wynn.activity DESC,COALESCE(guild.xp,0) DESC",
+            min_xp,
+            min_online
+        ))
+        .await?;
+
+    let result = rows
+        .into_iter()
+        .enumerate()
+        .map(|(i, row)| {
+            let rank = if row.guild { row.rank.unwrap_or_default() } else { String::new() };
+            vec![(i + 1).to_string(), row.ign, rank, fmt.second(row.activity), fmt.num(row.xp, false)]
+        })
+        .collect();
+    let header = vec![
+        String::from("#"),
+        String::from("ign"),
+        String::from("rank"),
+        String::from("online"),
+        String::from("xp"),
+    ];
+
+    Ok((result, header))
+}
+
+/// Return a table of members grouped by member type, and its heading.
+///
+/// Each row contains following items: [member type, member count, message, voice, online, xp],
+/// where the stats are totaled across members of that type.
+/// If `weekly` is true, the weekly stats are totaled instead.
+/// The last row contains the totals across all member types.
+pub async fn member_type_summary(
+    names: &dyn UserNames, fmt: &StatFormat, db: &DB, weekly: bool,
 ) -> Result<(Vec<Vec<String>>, Vec<String>)> {
+    let stats = if weekly {
+        [Stat::WeeklyMessage, Stat::WeeklyVoice, Stat::WeeklyOnline, Stat::WeeklyXp]
+    } else {
+        [Stat::Message, Stat::Voice, Stat::Online, Stat::Xp]
+    };
+    let mut cols = vec![Aggregate::new(AggregateFn::Count)?];
+    for stat in stats {
+        cols.push(Aggregate::new(AggregateFn::Sum(stat.to_column()))?);
+    }
+
+    // Every member is joined with two rows, so it is counted once in the group of its member type,
+    // and once in the group of all members, which is the last row
+    let member_type = Column::MType.select_query();
     let mut query = QueryBuilder::new();
-    for col in cols {
+    query
+        .select(format!("CASE WHEN totals.total THEN 'total' ELSE {} END AS type", member_type))
+        .join(String::from("JOIN (SELECT 0 AS total UNION ALL SELECT 1) AS totals"))
+        .group(String::from("totals.total"))
+        .group(format!("CASE WHEN totals.total THEN NULL ELSE {} END", member_type))
+        .order(String::from("totals.total"))
+        .order(member_type);
+    for col in &cols {
         query.with(col);
     }
-    for action in actions {
-        query.with(action);
+    let query = query.build();
+
+    let query = sqlx::query(&query).map(|r: SqliteRow| {
+        let mut row = Vec::with_capacity(cols.len() + 1);
+        row.push(Column::MType.format_val(&r, names, fmt));
+        for col in &cols {
+            row.push(col.format_val(&r, names, fmt));
+        }
+        row
+    });
+    let table = db.stats.time(query.sql(), query.fetch_all(&db.pool)).await?;
+
+    let mut header = Vec::with_capacity(cols.len() + 1);
+    header.push(Column::MType.table_name().to_string());
+    for col in &cols {
+        header.push(col.table_name().to_string());
     }
-    let query = query.build_lb("r");
+
+    Ok((table, header))
+}
+
+/// Fetch values from the database by specifying what columns to select, and actions (like
+/// filtering and ordering) to apply.
+pub async fn make_table(
+    names: &dyn UserNames, fmt: &StatFormat, db: &DB, cols: &Vec<impl Selectable>,
+    actions: &Vec<impl QueryAction>,
+) -> Result<(Vec<Vec<String>>, Vec<String>)> {
+    let query = make_table_query(cols, actions).build_lb("r");
 
     let query = sqlx::query(&query).map(|r: SqliteRow| {
         let rank = r.get::<i64, _>("r");
@@ -107,11 +363,11 @@ pub async fn make_table(
         let mut row = Vec::with_capacity(cols.len() + 1);
         row.push(rank.to_string());
         for col in cols {
-            row.push(col.format_val(&r, cache));
+            row.push(col.format_val(&r, names, fmt));
         }
         row
     });
-    let result = query.fetch_all(&db.pool).await?;
+    let result = db.stats.time(query.sql(), query.fetch_all(&db.pool)).await?;
 
     let mut header = Vec::with_capacity(cols.len() + 1);
     header.push(String::from("#"));
@@ -121,3 +377,49 @@ pub async fn make_table(
 
     Ok((result, header))
 }
+
+/// Return the summary row of [`make_table`], see [`summary_row`].
+pub async fn make_table_summary(
+    names: &dyn UserNames, fmt: &StatFormat, db: &DB, cols: &Vec<impl Selectable>,
+    actions: &Vec<impl QueryAction>,
+) -> Result<Vec<String>> {
+    let query = make_table_query(cols, actions).build();
+    let cols = cols.iter().map(|col| col as &dyn Selectable).collect::<Vec<&dyn Selectable>>();
+    summary_row(names, fmt, db, query, &cols).await
+}
+
+fn make_table_query(cols: &Vec<impl Selectable>, actions: &Vec<impl QueryAction>) -> QueryBuilder {
+    let mut query = QueryBuilder::new();
+    for col in cols {
+        query.with(col);
+    }
+    for action in actions {
+        query.with(action);
+    }
+    query
+}
+
+/// Summarize the rows selected by `query` in a single row, which can be appended to a table.
+///
+/// The summary is computed in SQL, by selecting the [`Selectable::summary_query`] of each of
+/// `cols` from `query`.
+/// The first item is "Σ" with the amount of rows, followed by the summarized value of each
+/// column, which is empty if the column can't be summarized.
+async fn summary_row(
+    names: &dyn UserNames, fmt: &StatFormat, db: &DB, query: String, cols: &[&dyn Selectable],
+) -> Result<Vec<String>> {
+    let mut select = vec![String::from("COUNT(*) AS summary_count")];
+    select.extend(cols.iter().filter_map(|col| col.summary_query()));
+    let query = format!("SELECT {} FROM ({})", select.join(","), query);
+    let row = db.stats.time(&query, sqlx::query(&query).fetch_one(&db.pool)).await?;
+
+    let mut summary = Vec::with_capacity(cols.len() + 1);
+    summary.push(format!("Σ {}", row.get::<i64, _>("summary_count")));
+    for col in cols {
+        summary.push(match col.summary_query() {
+            Some(_) => col.format_val(&row, names, fmt),
+            None => String::new(),
+        });
+    }
+    Ok(summary)
+}
@@ -0,0 +1,101 @@
+//! Recruitment tickets, private channels where an applicant talks with staff.
+//!
+//! A ticket is tied to the applicant's discord user, so it stays linked to their member record
+//! once they are added to the database.
+use anyhow::{Context, Result};
+use serde::Serialize;
+use sqlx::{query, query_as};
+use tracing::info;
+
+use crate::model::discord::DiscordId;
+use crate::{Executor, Transaction};
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+/// A recruitment ticket
+pub struct Ticket {
+    pub id: i64,
+    /// Discord user that opened the ticket
+    pub applicant: DiscordId,
+    /// Id of the ticket's channel
+    pub channel: i64,
+    /// Unix timestamp of when the ticket is opened
+    pub opened: i64,
+    /// Unix timestamp of when the ticket is closed
+    pub closed: Option<i64>,
+    /// Discord user that closed the ticket
+    pub closed_by: Option<DiscordId>,
+}
+
+/// Open a ticket, and return its id.
+/// Returns `None` if the applicant already has an open ticket.
+pub async fn open_ticket(
+    tx: &mut Transaction, applicant: DiscordId, channel: i64, now: i64,
+) -> Result<Option<i64>> {
+    if open_ticket_of(&mut tx.exe(), applicant).await?.is_some() {
+        return Ok(None);
+    }
+    info!(?applicant, channel, "Opening ticket");
+    let id = query!("INSERT INTO ticket (applicant,channel,opened) VALUES (?,?,?)", applicant, channel, now)
+        .execute(&mut tx.tx)
+        .await
+        .context("Failed to insert into ticket")?
+        .last_insert_rowid();
+    Ok(Some(id))
+}
+
+/// Close a ticket, return `false` if there is no such open ticket
+pub async fn close_ticket(tx: &mut Transaction, id: i64, by: DiscordId, now: i64) -> Result<bool> {
+    info!(id, ?by, "Closing ticket");
+    let result = query!("UPDATE ticket SET closed=?,closed_by=? WHERE id=? AND closed IS NULL", now, by, id)
+        .execute(&mut tx.tx)
+        .await
+        .context("Failed to update ticket.closed")?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Get the ticket of a channel
+pub async fn ticket_by_channel(exe: &mut Executor<'_>, channel: i64) -> Result<Option<Ticket>> {
+    exe.optional(query_as!(
+        Ticket,
+        "SELECT id,applicant AS \"applicant: DiscordId\",channel,opened,closed,\
+        closed_by AS \"closed_by: DiscordId\" FROM ticket WHERE channel=?",
+        channel
+    ))
+    .await
+    .context("Failed to fetch ticket")
+}
+
+/// Get the open ticket of an applicant
+pub async fn open_ticket_of(exe: &mut Executor<'_>, applicant: DiscordId) -> Result<Option<Ticket>> {
+    exe.optional(query_as!(
+        Ticket,
+        "SELECT id,applicant AS \"applicant: DiscordId\",channel,opened,closed,\
+        closed_by AS \"closed_by: DiscordId\" FROM ticket WHERE applicant=? AND closed IS NULL",
+        applicant
+    ))
+    .await
+    .context("Failed to fetch open ticket")
+}
+
+/// Get all tickets of an applicant, from the newest
+pub async fn tickets_of(exe: &mut Executor<'_>, applicant: DiscordId) -> Result<Vec<Ticket>> {
+    exe.all(query_as!(
+        Ticket,
+        "SELECT id,applicant AS \"applicant: DiscordId\",channel,opened,closed,\
+        closed_by AS \"closed_by: DiscordId\" FROM ticket WHERE applicant=? ORDER BY opened DESC,id DESC",
+        applicant
+    ))
+    .await
+    .context("Failed to fetch tickets")
+}
+
+/// Get all open tickets, from the oldest
+pub async fn open_tickets(exe: &mut Executor<'_>) -> Result<Vec<Ticket>> {
+    exe.all(query_as!(
+        Ticket,
+        "SELECT id,applicant AS \"applicant: DiscordId\",channel,opened,closed,\
+        closed_by AS \"closed_by: DiscordId\" FROM ticket WHERE closed IS NULL ORDER BY opened,id"
+    ))
+    .await
+    .context("Failed to fetch open tickets")
+}
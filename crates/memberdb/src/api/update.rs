@@ -12,6 +12,7 @@
 //!    need to also indicates that they are in guild via `wynn.guild`.
 //! 5. **Member type**
 //!    A member's type needs to correctly describe its linked profiles.
+//!    A member with only a discord profile can be either a discord partial or a guest.
 //! 6. **No empty member**
 //!    Any member that doesn't have any profiles linked are to be deleted.
 //!
@@ -20,20 +21,40 @@
 //! won't attempts to check if that modification is valid.
 //! You need to perform these checks yourself as outlined in the function preconditions, this is to
 //! prevent redundant checks.
+//!
+//! Checks made before the transaction that modifies the database can be outdated by the time it
+//! starts, ex: when two staffs change the same member's rank at once. Guarded functions like
+//! [`MemberId::change_rank`] and [`DiscordId::expect_mid`] fail with [`ConcurrentChange`] instead.
+use std::fmt;
+use std::time::{SystemTime, UNIX_EPOCH};
+
 use anyhow::{Context, Result};
-use serenity::client::Cache;
 use sqlx::query;
 use tracing::{info, instrument, warn};
 
-use util::ctx;
+use util::string::StatFormat;
+use util::{ctx, some};
 
 use crate::events::DBEvent;
 use crate::model::db::Stat;
-use crate::model::discord::DiscordId;
+use crate::model::discord::{DiscordId, UserNames};
 use crate::model::guild::GuildRank;
-use crate::model::member::{MemberId, MemberRank, MemberType};
+use crate::model::member::{MemberId, MemberRank, MemberType, INIT_MEMBER_RANK};
 use crate::model::wynn::McId;
-use crate::{Transaction, DB};
+use crate::{Executor, Transaction, DB};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Error of a guarded modification, the member is changed since it was last read, so the
+/// modification should be retried with fresh checks
+pub struct ConcurrentChange;
+
+impl fmt::Display for ConcurrentChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Member changed concurrently, retry")
+    }
+}
+
+impl std::error::Error for ConcurrentChange {}
 
 impl MemberId {
     /// Add discord partial member, if profile doesn't exist, it is created.
@@ -68,6 +89,33 @@ impl MemberId {
         Ok(mid)
     }
 
+    /// Add guest, if profile doesn't exist, it is created.
+    /// Guests accrue stats like other members, but are given the lowest rank and are kept out of
+    /// rank changes.
+    ///
+    /// # Preconditions
+    /// The given discord id is unlinked
+    #[instrument(skip(tx))]
+    pub async fn add_guest(tx: &mut Transaction, discord_id: DiscordId) -> Result<Self> {
+        info!("Adding guest into database");
+        let mid = query!(
+            "INSERT INTO member (discord,type,rank) VALUES (?,?,?)",
+            discord_id,
+            MemberType::Guest,
+            INIT_MEMBER_RANK
+        )
+        .execute(&mut tx.tx)
+        .await
+        .context("Failed to add guest to database")?
+        .last_insert_rowid();
+        let mid = Self(mid);
+
+        discord_id.link_or_create_unchecked(tx, Some(mid)).await?;
+
+        tx.signal(DBEvent::GuestAdd { mid, discord_id });
+        Ok(mid)
+    }
+
     /// Add wynn partial member, if profile doesn't exist, it is created.
     ///
     /// # Preconditions
@@ -158,17 +206,72 @@ impl MemberId {
         Ok(())
     }
 
-    /// Update a member's rank.
+    /// Update a member's rank, any temporary rank the member has is discarded.
     /// Note that this function won't broadcast the `MemberRankChange` event.
     pub async fn set_rank(self, tx: &mut Transaction, rank: MemberRank) -> Result<()> {
         info!(?self, ?rank, "Updating member rank");
-        query!("UPDATE member SET rank=? WHERE oid=?", rank, self)
+        query!("UPDATE member SET rank=?,rank_expire=NULL,rank_prev=NULL WHERE oid=?", rank, self)
             .execute(&mut tx.tx)
             .await
             .context("Failed to update member.rank")?;
         Ok(())
     }
 
+    /// Temporary update a member's rank, which is reverted once the unix timestamp `expire` is
+    /// reached.
+    /// If the member already has a temporary rank, they will still be reverted to the rank before
+    /// that.
+    /// Note that this function won't broadcast the `MemberRankChange` event.
+    pub async fn set_temp_rank(self, tx: &mut Transaction, rank: MemberRank, expire: i64) -> Result<()> {
+        info!(?self, ?rank, expire, "Temporary updating member rank");
+        query!(
+            "UPDATE member SET rank_prev=COALESCE(rank_prev,rank),rank=?,rank_expire=? WHERE oid=?",
+            rank,
+            expire,
+            self
+        )
+        .execute(&mut tx.tx)
+        .await
+        .context("Failed to temporary update member.rank")?;
+        Ok(())
+    }
+
+    /// Update a member's rank if it is still `old`, otherwise fails with [`ConcurrentChange`].
+    /// If `expire` is given, the rank is temporary like [`MemberId::set_temp_rank`], otherwise any
+    /// temporary rank the member has is discarded like [`MemberId::set_rank`].
+    /// Note that this function won't broadcast the `MemberRankChange` event.
+    pub async fn change_rank(
+        self, tx: &mut Transaction, old: MemberRank, rank: MemberRank, expire: Option<i64>,
+    ) -> Result<()> {
+        info!(?self, ?old, ?rank, expire, "Changing member rank");
+        let result = match expire {
+            Some(expire) => query!(
+                "UPDATE member SET rank_prev=COALESCE(rank_prev,rank),rank=?,rank_expire=? \
+                WHERE oid=? AND rank=?",
+                rank,
+                expire,
+                self,
+                old
+            )
+            .execute(&mut tx.tx)
+            .await
+            .context("Failed to temporary update member.rank")?,
+            None => query!(
+                "UPDATE member SET rank=?,rank_expire=NULL,rank_prev=NULL WHERE oid=? AND rank=?",
+                rank,
+                self,
+                old
+            )
+            .execute(&mut tx.tx)
+            .await
+            .context("Failed to update member.rank")?,
+        };
+        if result.rows_affected() == 0 {
+            return Err(ConcurrentChange.into());
+        }
+        Ok(())
+    }
+
     /// Update member's discord link, and return true if the member is removed or demoted to guild
     /// partial.
     ///
@@ -284,7 +387,7 @@ impl MemberId {
     /// partial.
     ///
     /// # Preconditions
-    /// The new wynn profile is unlinked.
+    /// The new wynn profile is unlinked, and the member isn't a guest.
     #[instrument(skip(tx))]
     pub async fn bind_wynn(&self, tx: &mut Transaction, mcid_new: Option<&McId>, ign: &str) -> Result<bool> {
         let mcid_old = query!("SELECT mcid FROM member where oid=?", self)
@@ -418,7 +521,8 @@ impl MemberId {
             .execute(&mut tx.tx)
             .await
             .context("Failed to delete from member table")?;
-        Ok(())
+        crate::message_log::remove_member_messages(tx, *self).await?;
+        crate::goal::remove_member_goals(tx, *self).await
     }
 
     /// Given a member, unbinds all its profiles, and delete it from database
@@ -450,12 +554,23 @@ impl MemberId {
             .execute(&mut tx.tx)
             .await
             .context("Failed to delete from member table")?;
+        crate::message_log::remove_member_messages(tx, self).await?;
+        crate::goal::remove_member_goals(tx, self).await?;
         tx.signal(DBEvent::MemberRemove { mid: self, discord_id: discord, mcid });
         Ok(())
     }
 }
 
 impl DiscordId {
+    /// Fails with [`ConcurrentChange`] if the discord profile isn't linked to `expected`, which is
+    /// the member it was linked to when checked before the transaction.
+    pub async fn expect_mid(&self, exe: &mut Executor<'_>, expected: Option<MemberId>) -> Result<()> {
+        if self.mid(exe).await? != expected {
+            return Err(ConcurrentChange.into());
+        }
+        Ok(())
+    }
+
     /// Add a new discord profile.
     ///
     /// # Preconditions
@@ -470,6 +585,24 @@ impl DiscordId {
         Ok(())
     }
 
+    /// Record the invite used by a discord user to join the discord server.
+    ///
+    /// The user doesn't need to have a discord profile, and any previous record is overwritten.
+    pub async fn set_inviter(&self, tx: &mut Transaction, inviter: Option<DiscordId>, code: &str) -> Result<()> {
+        info!(?self, ?inviter, code, "Recording discord invite");
+        query!(
+            "INSERT INTO discord_invite (id,inviter,code) VALUES (?,?,?) \
+            ON CONFLICT(id) DO UPDATE SET inviter=excluded.inviter,code=excluded.code",
+            self,
+            inviter,
+            code
+        )
+        .execute(&mut tx.tx)
+        .await
+        .context("Failed to update discord_invite")?;
+        Ok(())
+    }
+
     /// Update a discord profile's message count.
     pub async fn update_message(&self, tx: &mut Transaction, amount: i64) -> Result<()> {
         query!(
@@ -484,6 +617,34 @@ impl DiscordId {
         Ok(())
     }
 
+    /// Update a discord profile's message count with a message weighted by `weight`, in
+    /// percentages.
+    ///
+    /// The fraction of a message that doesn't add up to a whole message is carried over to the
+    /// next weighted message.
+    /// Returns the amount of whole messages added to the message count.
+    pub async fn update_weighted_message(&self, tx: &mut Transaction, weight: u32) -> Result<i64> {
+        let carried = tx
+            .exe()
+            .one(query!("SELECT message_frac FROM discord WHERE id=?", self))
+            .await
+            .context("Failed to fetch discord.message_frac")?
+            .message_frac;
+        let total = carried + i64::from(weight);
+        let (amount, frac) = (total / 100, total % 100);
+        query!(
+            "UPDATE discord SET message=message+?,message_week=message_week+?,message_frac=? WHERE id=?",
+            amount,
+            amount,
+            frac,
+            self
+        )
+        .execute(&mut tx.tx)
+        .await
+        .context("Failed to update discord.message, discord.message_week and discord.message_frac")?;
+        Ok(amount)
+    }
+
     /// Update a discord profile's voice activity.
     pub async fn update_voice(&self, tx: &mut Transaction, amount: i64) -> Result<()> {
         query!("UPDATE discord SET voice=voice+?,voice_week=voice_week+? WHERE id=?", amount, amount, self)
@@ -493,6 +654,52 @@ impl DiscordId {
         Ok(())
     }
 
+    /// Update a discord profile's streaming activity.
+    pub async fn update_stream(&self, tx: &mut Transaction, amount: i64) -> Result<()> {
+        query!("UPDATE discord SET stream=stream+?,stream_week=stream_week+? WHERE id=?", amount, amount, self)
+            .execute(&mut tx.tx)
+            .await
+            .context("Failed to update discord.stream and discord.stream_week")?;
+        Ok(())
+    }
+
+    /// Update the amount of reactions the discord user added to someone else's message, `amount` is
+    /// negative if reactions are removed.
+    /// The amount doesn't go below 0, as reactions added before they were counted can be removed.
+    pub async fn update_reaction_given(&self, tx: &mut Transaction, amount: i64) -> Result<()> {
+        query!("UPDATE discord SET reaction_given=MAX(reaction_given+?,0) WHERE id=?", amount, self)
+            .execute(&mut tx.tx)
+            .await
+            .context("Failed to update discord.reaction_given")?;
+        Ok(())
+    }
+
+    /// Update the amount of reactions someone else added to the discord user's message, `amount` is
+    /// negative if reactions are removed.
+    /// The amount doesn't go below 0, as reactions added before they were counted can be removed.
+    pub async fn update_reaction_received(&self, tx: &mut Transaction, amount: i64) -> Result<()> {
+        query!("UPDATE discord SET reaction_received=MAX(reaction_received+?,0) WHERE id=?", amount, self)
+            .execute(&mut tx.tx)
+            .await
+            .context("Failed to update discord.reaction_received")?;
+        Ok(())
+    }
+
+    /// Update a discord profile's voice activity in a specific voice channel.
+    pub async fn update_channel_voice(&self, tx: &mut Transaction, channel: i64, amount: i64) -> Result<()> {
+        query!(
+            "INSERT INTO voice_channel (discord,channel,voice) VALUES (?,?,?) 
+            ON CONFLICT(discord,channel) DO UPDATE SET voice=voice+excluded.voice",
+            self,
+            channel,
+            amount
+        )
+        .execute(&mut tx.tx)
+        .await
+        .context("Failed to update voice_channel.voice")?;
+        Ok(())
+    }
+
     /// Set a discord profile's member binding to given mid.
     /// This function doesn't ensure database integrity.
     async fn link_unchecked(&self, tx: &mut Transaction, mid: Option<MemberId>) -> Result<()> {
@@ -521,6 +728,15 @@ impl DiscordId {
 }
 
 impl McId {
+    /// Fails with [`ConcurrentChange`] if the wynn profile isn't linked to `expected`, which is
+    /// the member it was linked to when checked before the transaction.
+    pub async fn expect_mid(&self, exe: &mut Executor<'_>, expected: Option<MemberId>) -> Result<()> {
+        if self.mid(exe).await? != expected {
+            return Err(ConcurrentChange.into());
+        }
+        Ok(())
+    }
+
     /// Add a new wynn profile.
     ///
     /// # Preconditions
@@ -596,6 +812,26 @@ impl McId {
         Ok(())
     }
 
+    /// Set the datetime of when a guild profile joined the guild.
+    pub async fn set_joined(&self, tx: &mut Transaction, joined: &str) -> Result<()> {
+        info!(?self, joined, "Updating guild joined date");
+        query!("UPDATE guild SET joined=? WHERE id=?", joined, self)
+            .execute(&mut tx.tx)
+            .await
+            .context("Failed to update guild.joined")?;
+        Ok(())
+    }
+
+    /// Set a guild profile's war count.
+    pub async fn set_wars(&self, tx: &mut Transaction, wars: i64) -> Result<()> {
+        info!(?self, wars, "Updating guild war count");
+        query!("UPDATE guild SET wars=? WHERE id=?", wars, self)
+            .execute(&mut tx.tx)
+            .await
+            .context("Failed to update guild.wars")?;
+        Ok(())
+    }
+
     /// Update a guild profile's xp tracking.
     pub async fn update_xp(&self, tx: &mut Transaction, amount: i64) -> Result<()> {
         info!(?self, amount, "Updating guild xp");
@@ -634,6 +870,49 @@ impl McId {
         Ok(())
     }
 
+    /// Turn the member of a guild profile that is leaving the guild into an alumnus, if they have
+    /// been in the guild for at least `min_tenure` seconds, and return their member id.
+    /// Guild partials are changed to wynn partials, so they aren't removed once their guild profile
+    /// is unbound.
+    /// `now` is the current unix timestamp.
+    ///
+    /// # Preconditions
+    /// The guild profile is still bound, ex: this is called before [`McId::bind_guild`].
+    #[instrument(skip(tx))]
+    pub async fn make_alumni(
+        &self, tx: &mut Transaction, min_tenure: i64, now: i64,
+    ) -> Result<Option<MemberId>> {
+        let mid = some!(self.mid(&mut tx.exe()).await?, return Ok(None));
+        let joined = query!(
+            "SELECT CAST(strftime('%s',joined) AS INTEGER) AS \"joined: i64\" FROM guild WHERE id=?",
+            self
+        )
+        .fetch_one(&mut tx.tx)
+        .await
+        .context("Failed to fetch guild.joined")?
+        .joined;
+        match joined {
+            Some(joined) if now - joined >= min_tenure => {}
+            _ => return Ok(None),
+        }
+
+        if let MemberType::GuildPartial = mid.kind(&mut tx.exe()).await? {
+            info!(?mid, "Updating member type to wynn partial");
+            query!("UPDATE member SET type=? WHERE oid=?", MemberType::WynnPartial, mid)
+                .execute(&mut tx.tx)
+                .await
+                .context("Failed to set member.type to wynn")?;
+        }
+
+        let old = mid.rank(&mut tx.exe()).await?;
+        if old != MemberRank::Alumni {
+            info!(?mid, "Turning member into alumnus");
+            mid.set_rank(tx, MemberRank::Alumni).await?;
+            tx.signal(DBEvent::MemberRankChange { mid, old, new: MemberRank::Alumni });
+        }
+        Ok(Some(mid))
+    }
+
     /// Update guild status.
     /// If wynn or guild profile is missing, new one is created.
     /// If a new guild partial is created, their member id is returned.
@@ -716,17 +995,25 @@ impl McId {
     }
 }
 
-/// Reset weekly stats to 0
-pub async fn weekly_reset(db: &DB, cache: &Cache) -> Result<()> {
-    let v = Vec::new();
-    let message_lb = crate::table::stat_leaderboard(cache, db, &Stat::WeeklyMessage, &v).await?;
-    let voice_lb = crate::table::stat_leaderboard(cache, db, &Stat::WeeklyVoice, &v).await?;
-    let online_lb = crate::table::stat_leaderboard(cache, db, &Stat::WeeklyOnline, &v).await?;
-    let xp_lb = crate::table::stat_leaderboard(cache, db, &Stat::WeeklyXp, &v).await?;
+/// Reset weekly stats to 0, they are backed up beforehand so the reset can be undone via
+/// [`undo_weekly_reset`](crate::weekly_backup::undo_weekly_reset).
+/// The leaderboards of the week are signaled with stats displayed in `fmt`.
+pub async fn weekly_reset(db: &DB, names: &dyn UserNames, fmt: &StatFormat) -> Result<()> {
+    let stats = [Stat::WeeklyMessage, Stat::WeeklyVoice, Stat::WeeklyOnline, Stat::WeeklyXp];
+    let [message_lb, voice_lb, online_lb, xp_lb] =
+        crate::table::stat_leaderboards(names, fmt, db, &stats).await?;
+    let now = ctx!(SystemTime::now().duration_since(UNIX_EPOCH), "Failed to get current unix timestamp")?;
+    let now = i64::try_from(now.as_secs())?;
+    let online = crate::online_history::online_stats(db, now - 7 * 86400, now).await?;
+    let global_rank = crate::global_rank::rank_change(db, now - 7 * 86400).await?;
+
+    let mut tx = db.begin().await?;
+    crate::weekly_backup::backup_weekly_stats(&mut tx, now).await?;
+    tx.commit().await?;
 
     info!("Resetting discord weekly stats");
     ctx!(
-        query!("UPDATE discord SET message_week=0,voice_week=0").execute(&db.pool).await,
+        query!("UPDATE discord SET message_week=0,voice_week=0,stream_week=0").execute(&db.pool).await,
         "Failed to set discord weekly stats to 0"
     )?;
 
@@ -745,6 +1032,34 @@ pub async fn weekly_reset(db: &DB, cache: &Cache) -> Result<()> {
         "Failed to set guild weekly stats to 0"
     )?;
 
-    db.signal(DBEvent::WeeklyReset { message_lb, voice_lb, online_lb, xp_lb });
+    db.signal(DBEvent::WeeklyReset { message_lb, voice_lb, online_lb, xp_lb, online, global_rank });
+    Ok(())
+}
+
+/// Revert all members with expired temporary rank back to their previous rank.
+/// `now` is the current unix timestamp.
+pub async fn revert_expired_ranks(db: &DB, now: i64) -> Result<()> {
+    let rows = ctx!(
+        query!(
+            "SELECT oid AS mid,rank,rank_prev AS \"rank_prev!\" FROM member 
+            WHERE rank_expire NOT NULL AND rank_prev NOT NULL AND rank_expire<=?",
+            now
+        )
+        .fetch_all(&db.pool)
+        .await,
+        "Failed to fetch members with expired rank"
+    )?;
+
+    for row in rows {
+        let mid = MemberId(row.mid);
+        let old = MemberRank::decode(&row.rank)?;
+        let new = MemberRank::decode(&row.rank_prev)?;
+        info!(?mid, ?old, ?new, "Reverting expired temporary rank");
+
+        let mut tx = db.begin().await?;
+        mid.set_rank(&mut tx, new).await?;
+        tx.commit().await?;
+        db.signal(DBEvent::MemberRankExpire { mid, old, new });
+    }
     Ok(())
 }
@@ -0,0 +1,88 @@
+//! Waitlist of recruits accepted while the in-game guild is full.
+//!
+//! Recruits are waitlisted by their ign, and are taken off the waitlist once they join the guild.
+use anyhow::{Context, Result};
+use serde::Serialize;
+use sqlx::{query, query_as};
+use tracing::info;
+
+use crate::model::discord::DiscordId;
+use crate::{Executor, Transaction};
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+/// A waitlisted recruit
+pub struct Recruit {
+    pub id: i64,
+    pub ign: String,
+    /// Discord user of the recruit, if known
+    pub discord: Option<DiscordId>,
+    /// Unix timestamp of when the recruit is waitlisted
+    pub added: i64,
+    /// Discord user that waitlisted the recruit
+    pub added_by: DiscordId,
+}
+
+/// Waitlist a recruit, and return its id
+pub async fn add_recruit(
+    tx: &mut Transaction, ign: &str, discord: Option<DiscordId>, added_by: DiscordId, now: i64,
+) -> Result<i64> {
+    info!(ign, ?discord, ?added_by, "Waitlisting recruit");
+    let id = query!(
+        "INSERT INTO waitlist (ign,discord,added,added_by) VALUES (?,?,?,?)",
+        ign,
+        discord,
+        now,
+        added_by
+    )
+    .execute(&mut tx.tx)
+    .await
+    .context("Failed to insert into waitlist")?
+    .last_insert_rowid();
+    Ok(id)
+}
+
+/// Take a recruit off the waitlist, return `false` if there is no such recruit
+pub async fn remove_recruit(tx: &mut Transaction, id: i64) -> Result<bool> {
+    info!(id, "Removing recruit from waitlist");
+    let result = query!("DELETE FROM waitlist WHERE id=?", id)
+        .execute(&mut tx.tx)
+        .await
+        .context("Failed to delete from waitlist")?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Take the recruits with an ign off the waitlist, ign is case insensitive.
+/// Returns the amount of recruits removed.
+pub async fn remove_ign(tx: &mut Transaction, ign: &str) -> Result<u64> {
+    let result = query!("DELETE FROM waitlist WHERE ign=? COLLATE NOCASE", ign)
+        .execute(&mut tx.tx)
+        .await
+        .context("Failed to delete from waitlist")?;
+    if result.rows_affected() > 0 {
+        info!(ign, "Waitlisted recruit joined the guild");
+    }
+    Ok(result.rows_affected())
+}
+
+/// Get a waitlisted recruit by ign, ign is case insensitive
+pub async fn recruit_by_ign(exe: &mut Executor<'_>, ign: &str) -> Result<Option<Recruit>> {
+    exe.optional(query_as!(
+        Recruit,
+        "SELECT id,ign,discord AS \"discord: DiscordId\",added,added_by AS \"added_by: DiscordId\" \
+        FROM waitlist WHERE ign=? COLLATE NOCASE",
+        ign
+    ))
+    .await
+    .context("Failed to fetch waitlisted recruit")
+}
+
+/// Get all waitlisted recruits, from the earliest waitlisted
+pub async fn waitlist(exe: &mut Executor<'_>) -> Result<Vec<Recruit>> {
+    exe.all(query_as!(
+        Recruit,
+        "SELECT id,ign,discord AS \"discord: DiscordId\",added,added_by AS \"added_by: DiscordId\" \
+        FROM waitlist ORDER BY added,id"
+    ))
+    .await
+    .context("Failed to fetch waitlist")
+}
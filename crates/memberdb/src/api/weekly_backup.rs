@@ -0,0 +1,115 @@
+//! Backup of the weekly stats, so an erroneous weekly reset can be undone.
+//!
+//! Right before the weekly stats are set to 0, they are copied into the `weekly_backup_*` tables,
+//! replacing the previous backup.
+//! Within [`UNDO_WINDOW`] seconds of the reset, [`undo_weekly_reset`] adds the backed up stats
+//! back, keeping the stats gained since the reset.
+use anyhow::{bail, Context, Result};
+use sqlx::query;
+use tracing::info;
+
+use crate::events::DBEvent;
+use crate::{Executor, Transaction};
+
+/// Amount of seconds after the weekly reset during which it can be undone
+pub const UNDO_WINDOW: i64 = 86400;
+
+/// Back up the weekly stats, replacing the previous backup.
+/// `now` is the current unix timestamp.
+pub async fn backup_weekly_stats(tx: &mut Transaction, now: i64) -> Result<()> {
+    info!(now, "Backing up weekly stats");
+    clear_backup(tx).await?;
+    query!("INSERT INTO weekly_backup (time) VALUES (?)", now)
+        .execute(&mut tx.tx)
+        .await
+        .context("Failed to insert into weekly_backup")?;
+    query!(
+        "INSERT INTO weekly_backup_discord (id,message_week,voice_week,stream_week) \
+        SELECT id,message_week,voice_week,stream_week FROM discord"
+    )
+    .execute(&mut tx.tx)
+    .await
+    .context("Failed to back up discord weekly stats")?;
+    query!(
+        "INSERT INTO weekly_backup_wynn (id,activity_week,activity_avg,activity_avg_range) \
+        SELECT id,activity_week,activity_avg,activity_avg_range FROM wynn"
+    )
+    .execute(&mut tx.tx)
+    .await
+    .context("Failed to back up wynn weekly stats")?;
+    query!("INSERT INTO weekly_backup_guild (id,xp_week) SELECT id,xp_week FROM guild")
+        .execute(&mut tx.tx)
+        .await
+        .context("Failed to back up guild weekly stats")?;
+    Ok(())
+}
+
+/// Get the unix timestamp of when the current backup is made, if there is one
+pub async fn backup_time(exe: &mut Executor<'_>) -> Result<Option<i64>> {
+    let row = exe
+        .optional(query!("SELECT time FROM weekly_backup ORDER BY time DESC LIMIT 1"))
+        .await
+        .context("Failed to fetch weekly_backup")?;
+    Ok(row.map(|row| row.time))
+}
+
+/// Undo the last weekly reset by adding the backed up weekly stats back, the backup is removed
+/// afterward.
+/// Stats gained since the reset are kept, while the average activity is restored to its value
+/// before the reset.
+///
+/// `now` is the current unix timestamp, fails if there is no backup or it is older than
+/// [`UNDO_WINDOW`].
+/// Returns the unix timestamp of the reset that is undone.
+pub async fn undo_weekly_reset(tx: &mut Transaction, now: i64) -> Result<i64> {
+    let time = match backup_time(&mut tx.exe()).await? {
+        Some(time) => time,
+        None => bail!("There is no weekly reset to undo"),
+    };
+    if now - time > UNDO_WINDOW {
+        bail!("The last weekly reset is more than {} hours ago", UNDO_WINDOW / 3600);
+    }
+    info!(time, "Undoing weekly reset");
+
+    query!(
+        "UPDATE discord SET \
+        message_week=discord.message_week+b.message_week,\
+        voice_week=discord.voice_week+b.voice_week,\
+        stream_week=discord.stream_week+b.stream_week \
+        FROM (SELECT * FROM weekly_backup_discord) AS b WHERE discord.id=b.id"
+    )
+    .execute(&mut tx.tx)
+    .await
+    .context("Failed to restore discord weekly stats")?;
+    query!(
+        "UPDATE wynn SET \
+        activity_week=wynn.activity_week+b.activity_week,\
+        activity_avg=b.activity_avg,\
+        activity_avg_range=b.activity_avg_range \
+        FROM (SELECT * FROM weekly_backup_wynn) AS b WHERE wynn.id=b.id"
+    )
+    .execute(&mut tx.tx)
+    .await
+    .context("Failed to restore wynn weekly stats")?;
+    query!(
+        "UPDATE guild SET xp_week=guild.xp_week+b.xp_week \
+        FROM (SELECT * FROM weekly_backup_guild) AS b WHERE guild.id=b.id"
+    )
+    .execute(&mut tx.tx)
+    .await
+    .context("Failed to restore guild weekly stats")?;
+
+    clear_backup(tx).await?;
+    tx.signal(DBEvent::WeeklyResetUndo { time });
+    Ok(time)
+}
+
+async fn clear_backup(tx: &mut Transaction) -> Result<()> {
+    for table in ["weekly_backup", "weekly_backup_discord", "weekly_backup_wynn", "weekly_backup_guild"] {
+        sqlx::query(&format!("DELETE FROM {}", table))
+            .execute(&mut tx.tx)
+            .await
+            .with_context(|| format!("Failed to clear {}", table))?;
+    }
+    Ok(())
+}
@@ -0,0 +1,160 @@
+//! Reports of the week that are made before the weekly reset.
+//!
+//! The weekly reset wipes the weekly stats, so a report of who met the xp requirement and who the
+//! top performers are is made and stored in the `weekly_report` table beforehand, allowing the
+//! week to be looked back on even if the reset happened at the wrong time.
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use sqlx::query;
+
+use util::string::StatFormat;
+
+use crate::model::db::Stat;
+use crate::model::discord::UserNames;
+use crate::model::guild::GuildRank;
+use crate::{Executor, DB};
+
+/// Amount of top performers kept for each weekly stat
+pub const TOP_PERFORMERS: usize = 5;
+/// Weekly stats whose top performers are reported, along with their display names
+const REPORTED_STATS: [(Stat, &str); 4] = [
+    (Stat::WeeklyMessage, "Messages"),
+    (Stat::WeeklyVoice, "Voice time"),
+    (Stat::WeeklyOnline, "Online time"),
+    (Stat::WeeklyXp, "Xp contribution"),
+];
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+/// Report of a week, made before its stats are reset
+pub struct WeeklyReport {
+    /// Unix timestamp of when the report is made
+    pub time: i64,
+    /// Igns of the guild members that met the weekly xp requirement of their rank
+    pub met_requirement: Vec<String>,
+    /// Igns of the guild members that are below the weekly xp requirement of their rank
+    pub missed_requirement: Vec<String>,
+    /// Top performers of each weekly stat, in the form of `(stat, leaderboard rows)`
+    pub top: Vec<(Stat, Vec<Vec<String>>)>,
+}
+
+impl WeeklyReport {
+    /// Describe the report in a message
+    pub fn summary(&self) -> String {
+        let mut summary = format!(
+            "> **Weekly report** <t:{}:f>\nXp requirement: **{}** met, **{}** missed",
+            self.time,
+            self.met_requirement.len(),
+            self.missed_requirement.len()
+        );
+        for (stat, rows) in &self.top {
+            let name = REPORTED_STATS.iter().find(|(s, _)| s == stat).map_or("", |(_, name)| name);
+            let top = rows
+                .iter()
+                .filter_map(|row| match row.as_slice() {
+                    [_, name, value, ..] => Some(format!("{} ({})", name, value)),
+                    _ => None,
+                })
+                .collect::<Vec<String>>()
+                .join(", ");
+            let _ = write!(summary, "\n__{}__: {}", name, if top.is_empty() { "-" } else { &top });
+        }
+        summary
+    }
+}
+
+/// Make a report of the current week without changing anything.
+///
+/// `requirements` are the weekly xp requirements of each guild rank, `now` is the current unix
+/// timestamp, and the top performers' stats are displayed in `fmt`.
+pub async fn make_report(
+    db: &DB, names: &dyn UserNames, fmt: &StatFormat, requirements: &HashMap<GuildRank, i64>, now: i64,
+) -> Result<WeeklyReport> {
+    let rows = query!(
+        "SELECT guild.rank AS \"rank: GuildRank\",guild.xp_week,wynn.ign FROM guild \
+        JOIN wynn ON wynn.id=guild.id ORDER BY wynn.ign"
+    )
+    .fetch_all(&db.pool)
+    .await
+    .context("Failed to fetch guild members' weekly xp")?;
+
+    let mut report = WeeklyReport {
+        time: now,
+        met_requirement: Vec::new(),
+        missed_requirement: Vec::new(),
+        top: Vec::new(),
+    };
+    for row in rows {
+        match requirements.get(&row.rank) {
+            Some(required) if row.xp_week < *required => report.missed_requirement.push(row.ign),
+            Some(_) => report.met_requirement.push(row.ign),
+            None => {}
+        }
+    }
+
+    let stats = REPORTED_STATS.map(|(stat, _)| stat);
+    let lbs = crate::table::stat_leaderboards(names, fmt, db, &stats).await?;
+    for (stat, (mut table, _)) in stats.into_iter().zip(lbs) {
+        table.truncate(TOP_PERFORMERS);
+        report.top.push((stat, table));
+    }
+    Ok(report)
+}
+
+/// Store a report, replacing the one made at the same time
+pub async fn store_report(db: &DB, report: &WeeklyReport) -> Result<()> {
+    let content = serde_json::to_string(report).context("Failed to serialize weekly report")?;
+    query!("INSERT OR REPLACE INTO weekly_report (time,report) VALUES (?,?)", report.time, content)
+        .execute(&db.pool)
+        .await
+        .context("Failed to insert into weekly_report")?;
+    Ok(())
+}
+
+/// Get the latest stored report
+pub async fn latest_report(exe: &mut Executor<'_>) -> Result<Option<WeeklyReport>> {
+    let row = exe
+        .optional(query!("SELECT report FROM weekly_report ORDER BY time DESC LIMIT 1"))
+        .await
+        .context("Failed to fetch weekly_report")?;
+    match row {
+        Some(row) => Ok(Some(serde_json::from_str(&row.report).context("Failed to parse weekly report")?)),
+        None => Ok(None),
+    }
+}
+
+/// Remember that the weekly reset of the report made at `time` waits for staff until `deadline`,
+/// so the wait survives a restart. Replaces the previous pending reset.
+pub async fn set_pending_reset(db: &DB, time: i64, deadline: i64) -> Result<()> {
+    let mut tx = db.begin().await?;
+    query!("DELETE FROM pending_reset")
+        .execute(&mut tx.tx)
+        .await
+        .context("Failed to delete from pending_reset")?;
+    query!("INSERT INTO pending_reset (time,deadline) VALUES (?,?)", time, deadline)
+        .execute(&mut tx.tx)
+        .await
+        .context("Failed to insert into pending_reset")?;
+    tx.commit().await?;
+    Ok(())
+}
+
+/// Get the weekly reset that waits for staff, in the form of `(report time, deadline)`
+pub async fn pending_reset(exe: &mut Executor<'_>) -> Result<Option<(i64, i64)>> {
+    let row = exe
+        .optional(query!("SELECT time,deadline FROM pending_reset LIMIT 1"))
+        .await
+        .context("Failed to fetch pending_reset")?;
+    Ok(row.map(|row| (row.time, row.deadline)))
+}
+
+/// Forget the weekly reset that waits for staff, once it is decided on
+pub async fn clear_pending_reset(db: &DB) -> Result<()> {
+    query!("DELETE FROM pending_reset")
+        .execute(&db.pool)
+        .await
+        .context("Failed to delete from pending_reset")?;
+    Ok(())
+}
@@ -0,0 +1,113 @@
+//! Guild xp contributions held back for staff review, because they look like the result of a
+//! mis-parsed API response.
+//!
+//! A held back contribution stays in the `xp_correction` table until staff approve it, which
+//! applies it to the guild member's xp, or discard it.
+use anyhow::{Context, Result};
+use serde::Serialize;
+use sqlx::query;
+use tracing::info;
+
+use crate::model::wynn::McId;
+use crate::{Executor, Transaction};
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+/// A guild xp contribution waiting for staff review
+pub struct XpCorrection {
+    pub id: i64,
+    pub mcid: McId,
+    /// Ign of the guild member when the contribution is made
+    pub ign: String,
+    pub amount: i64,
+    /// Unix timestamp of when the contribution is made
+    pub time: i64,
+}
+
+/// Hold back a guild xp contribution for review, and return it
+pub async fn add_correction(
+    tx: &mut Transaction, mcid: &McId, ign: &str, amount: i64, time: i64,
+) -> Result<XpCorrection> {
+    info!(?mcid, amount, "Holding back guild xp contribution");
+    let id =
+        query!("INSERT INTO xp_correction (mcid,ign,amount,time) VALUES (?,?,?,?)", mcid, ign, amount, time)
+            .execute(&mut tx.tx)
+            .await
+            .context("Failed to insert into xp_correction")?
+            .last_insert_rowid();
+    Ok(XpCorrection { id, mcid: mcid.clone(), ign: ign.to_string(), amount, time })
+}
+
+/// Get a held back contribution
+pub async fn get_correction(exe: &mut Executor<'_>, id: i64) -> Result<Option<XpCorrection>> {
+    let row = exe
+        .optional(query!(
+            "SELECT id,mcid AS \"mcid: McId\",ign,amount,time FROM xp_correction WHERE id=?",
+            id
+        ))
+        .await
+        .context("Failed to fetch xp_correction")?;
+    Ok(row.map(|row| XpCorrection {
+        id: row.id,
+        mcid: row.mcid,
+        ign: row.ign,
+        amount: row.amount,
+        time: row.time,
+    }))
+}
+
+/// Get all held back contributions, from the oldest to the newest
+pub async fn pending_corrections(exe: &mut Executor<'_>) -> Result<Vec<XpCorrection>> {
+    let rows = exe
+        .all(query!("SELECT id,mcid AS \"mcid: McId\",ign,amount,time FROM xp_correction ORDER BY time,id"))
+        .await
+        .context("Failed to fetch xp_correction")?;
+    Ok(rows
+        .into_iter()
+        .map(|row| XpCorrection {
+            id: row.id,
+            mcid: row.mcid,
+            ign: row.ign,
+            amount: row.amount,
+            time: row.time,
+        })
+        .collect())
+}
+
+/// Apply a held back contribution to the guild member's xp, the daily xp and the guild goals, and
+/// stop holding it.
+/// Returns `None` if there is no such contribution, ex: it is already reviewed.
+///
+/// The contribution is counted into the current week and day, not the ones it is made in.
+pub async fn approve_correction(tx: &mut Transaction, id: i64) -> Result<Option<XpCorrection>> {
+    let correction = match take_correction(tx, id).await? {
+        Some(correction) => correction,
+        None => return Ok(None),
+    };
+    info!(?correction, "Approving held back guild xp contribution");
+    correction.mcid.update_xp(tx, correction.amount).await?;
+    crate::update_daily_xp(tx, correction.amount).await?;
+    crate::guild_goal::add_xp(tx, correction.amount).await?;
+    Ok(Some(correction))
+}
+
+/// Stop holding a contribution without applying it.
+/// Returns `None` if there is no such contribution, ex: it is already reviewed.
+pub async fn discard_correction(tx: &mut Transaction, id: i64) -> Result<Option<XpCorrection>> {
+    let correction = take_correction(tx, id).await?;
+    if correction.is_some() {
+        info!(id, "Discarding held back guild xp contribution");
+    }
+    Ok(correction)
+}
+
+/// Remove a held back contribution and return it
+async fn take_correction(tx: &mut Transaction, id: i64) -> Result<Option<XpCorrection>> {
+    let correction = get_correction(&mut tx.exe(), id).await?;
+    if correction.is_some() {
+        query!("DELETE FROM xp_correction WHERE id=?", id)
+            .execute(&mut tx.tx)
+            .await
+            .context("Failed to delete from xp_correction")?;
+    }
+    Ok(correction)
+}
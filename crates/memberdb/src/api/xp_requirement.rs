@@ -0,0 +1,197 @@
+//! Weekly xp requirement of guild members.
+//!
+//! Before the weekly stats are reset, each guild member's weekly xp is checked against the
+//! requirement of their guild rank and recorded in the `xp_history` table, along with the amount of
+//! consecutive weeks they have missed the requirement.
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use sqlx::query;
+use tracing::{info, warn};
+
+use crate::model::guild::GuildRank;
+use crate::model::wynn::McId;
+use crate::{Executor, DB};
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+/// A guild member that didn't meet the weekly xp requirement
+pub struct XpMiss {
+    pub mcid: McId,
+    pub ign: String,
+    pub rank: GuildRank,
+    /// Xp contributed in the week
+    pub xp: i64,
+    /// Xp required for the week
+    pub required: i64,
+    /// Amount of consecutive weeks the requirement is missed, including this week
+    pub streak: i64,
+}
+
+/// Parse the weekly xp requirements from config, which are keyed by guild rank names.
+/// Invalid rank names are ignored.
+pub fn parse_requirements(requirements: &HashMap<String, i64>) -> HashMap<GuildRank, i64> {
+    requirements
+        .iter()
+        .filter_map(|(rank, xp)| match GuildRank::from_str(rank) {
+            Ok(rank) => Some((rank, *xp)),
+            Err(why) => {
+                warn!("Invalid guild rank in weekly xp requirements: {:#}", why);
+                None
+            }
+        })
+        .collect()
+}
+
+/// Check the weekly xp of all guild members against the requirement of their rank, and record the
+/// results into history under `week`.
+///
+/// Guild members whose rank has no requirement aren't recorded.
+/// Returns the members that missed the requirement, sorted by their streak in descending order.
+pub async fn record_weekly_xp(
+    db: &DB, requirements: &HashMap<GuildRank, i64>, week: i64,
+) -> Result<Vec<XpMiss>> {
+    let rows = query!(
+        "SELECT guild.id,guild.rank AS \"rank: GuildRank\",guild.xp_week,wynn.ign FROM guild \
+        JOIN wynn ON wynn.id=guild.id"
+    )
+    .fetch_all(&db.pool)
+    .await
+    .context("Failed to fetch guild members' weekly xp")?;
+
+    let mut misses = Vec::new();
+    let mut tx = db.begin().await?;
+    for row in rows {
+        let required = match requirements.get(&row.rank) {
+            Some(required) => *required,
+            None => continue,
+        };
+        let streak = if row.xp_week < required {
+            let prev = query!("SELECT streak FROM xp_history WHERE id=? ORDER BY week DESC LIMIT 1", row.id)
+                .fetch_optional(&mut tx.tx)
+                .await
+                .context("Failed to fetch xp_history.streak")?;
+            prev.map_or(0, |prev| prev.streak) + 1
+        } else {
+            0
+        };
+
+        query!(
+            "INSERT OR REPLACE INTO xp_history (id,week,xp,required,streak) VALUES (?,?,?,?,?)",
+            row.id,
+            week,
+            row.xp_week,
+            required,
+            streak
+        )
+        .execute(&mut tx.tx)
+        .await
+        .context("Failed to insert into xp_history")?;
+
+        if streak > 0 {
+            misses.push(XpMiss {
+                mcid: McId(row.id),
+                ign: row.ign,
+                rank: row.rank,
+                xp: row.xp_week,
+                required,
+                streak,
+            });
+        }
+    }
+    tx.commit().await?;
+
+    info!(misses = misses.len(), "Recorded weekly xp requirements");
+    misses.sort_by(|a, b| b.streak.cmp(&a.streak).then_with(|| a.xp.cmp(&b.xp)));
+    Ok(misses)
+}
+
+impl McId {
+    /// Get the amount of consecutive weeks the guild member has missed the weekly xp requirement,
+    /// as of the last recorded week.
+    pub async fn xp_miss_streak(&self, exe: &mut Executor<'_>) -> Result<i64> {
+        let row = exe
+            .optional(query!("SELECT streak FROM xp_history WHERE id=? ORDER BY week DESC LIMIT 1", self))
+            .await
+            .context("Failed to fetch xp_history.streak")?;
+        Ok(row.map_or(0, |row| row.streak))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// Outcome of a hypothetical weekly xp requirement for the guild members of a rank
+pub struct RankSimulation {
+    pub rank: GuildRank,
+    /// Hypothetical xp requirement of the rank
+    pub required: i64,
+    /// Amount of guild members of the rank that are evaluated
+    pub members: usize,
+    /// Amount of the evaluated members that would miss the requirement
+    pub failed: usize,
+    /// Amount of failing members by how much of the requirement they met, in quarters.
+    /// ex: `buckets[0]` is the amount of members that met less than 25% of the requirement.
+    pub buckets: [usize; 4],
+    /// Median weekly xp of the evaluated members
+    pub median_xp: i64,
+}
+
+/// Evaluate hypothetical weekly xp requirements against the guild members' weekly xp, without
+/// recording anything.
+///
+/// If `previous` is true, the weekly xp backed up at the last weekly reset is used instead of the
+/// current week's, with members grouped by their current rank.
+/// Ranks without a requirement aren't evaluated, results are sorted by rank from highest to lowest.
+pub async fn simulate_requirements(
+    exe: &mut Executor<'_>, requirements: &HashMap<GuildRank, i64>, previous: bool,
+) -> Result<Vec<RankSimulation>> {
+    let rows: Vec<(GuildRank, i64)> = if previous {
+        exe.all(query!(
+            "SELECT guild.rank AS \"rank: GuildRank\",weekly_backup_guild.xp_week FROM weekly_backup_guild \
+            JOIN guild ON guild.id=weekly_backup_guild.id"
+        ))
+        .await
+        .context("Failed to fetch weekly_backup_guild.xp_week")?
+        .into_iter()
+        .map(|row| (row.rank, row.xp_week))
+        .collect()
+    } else {
+        exe.all(query!("SELECT rank AS \"rank: GuildRank\",xp_week FROM guild"))
+            .await
+            .context("Failed to fetch guild.xp_week")?
+            .into_iter()
+            .map(|row| (row.rank, row.xp_week))
+            .collect()
+    };
+
+    let mut xps: HashMap<GuildRank, Vec<i64>> = HashMap::new();
+    for (rank, xp) in rows {
+        if requirements.contains_key(&rank) {
+            xps.entry(rank).or_default().push(xp);
+        }
+    }
+    let mut results: Vec<RankSimulation> = requirements
+        .iter()
+        .map(|(rank, required)| {
+            let mut xps = xps.remove(rank).unwrap_or_default();
+            xps.sort_unstable();
+            let mut buckets = [0; 4];
+            for xp in xps.iter().filter(|xp| **xp < *required) {
+                // `0 <= xp < required` so the quarter is at most 3, a requirement of 0 or less can
+                // only be missed with negative xp, which met none of it
+                let quarter = if *required > 0 { (xp.max(&0) * 4 / required) as usize } else { 0 };
+                buckets[quarter] += 1;
+            }
+            RankSimulation {
+                rank: *rank,
+                required: *required,
+                members: xps.len(),
+                failed: buckets.iter().sum(),
+                buckets,
+                median_xp: xps.get(xps.len() / 2).copied().unwrap_or(0),
+            }
+        })
+        .collect();
+    results.sort_by_key(|result| std::cmp::Reverse(result.rank));
+    Ok(results)
+}
@@ -1,12 +1,25 @@
 //! Database events
 use event::signal;
+use serde::Serialize;
 
+use crate::api::daily::DailySummary;
+use crate::api::global_rank::RankChange;
+use crate::api::goal::AchievedGoal;
+use crate::api::guild_goal::GuildGoal;
+use crate::api::link_conflict::LinkConflict;
+use crate::api::member_exit::MemberExit;
+use crate::api::online_history::OnlineStats;
+use crate::api::weekly_report::WeeklyReport;
+use crate::api::xp_correction::XpCorrection;
+use crate::api::xp_requirement::XpMiss;
+use crate::model::db::Stat;
 use crate::model::discord::DiscordId;
 use crate::model::guild::GuildRank;
 use crate::model::member::{MemberId, MemberRank, MemberType};
 use crate::model::wynn::McId;
 
-#[derive(Debug, Clone)]
+#[derive(Serialize, Debug, Clone)]
+#[serde(tag = "type")]
 pub enum DBEvent {
     MemberAdd {
         mid: MemberId,
@@ -14,6 +27,11 @@ pub enum DBEvent {
         mcid: Option<McId>,
         rank: MemberRank,
     },
+    /// Sent when a guest is added, guests don't get any rank roles
+    GuestAdd {
+        mid: MemberId,
+        discord_id: DiscordId,
+    },
     MemberRemove {
         mid: MemberId,
         discord_id: Option<DiscordId>,
@@ -32,6 +50,12 @@ pub enum DBEvent {
         old: MemberRank,
         new: MemberRank,
     },
+    /// A temporary rank has expired, and the member is reverted to their previous rank
+    MemberRankExpire {
+        mid: MemberId,
+        old: MemberRank,
+        new: MemberRank,
+    },
     WynnProfileAdd {
         mcid: McId,
         mid: Option<MemberId>,
@@ -66,13 +90,111 @@ pub enum DBEvent {
         // Indicates if the member was/about to be removed
         removed: bool,
     },
+    /// Sent before the weekly reset, with the report of the week
+    WeeklyReport {
+        report: WeeklyReport,
+        // Unix timestamp of when the reset proceeds if staff doesn't decide on it, `None` if it
+        // doesn't wait for staff
+        deadline: Option<i64>,
+    },
+    /// Sent when staff skipped the weekly reset, so the weekly stats are kept
+    WeeklyResetSkip,
+    /// Sent when the weekly reset at unix timestamp `time` is undone
+    WeeklyResetUndo {
+        time: i64,
+    },
     WeeklyReset {
         // All the weekly leaderboards before the reset
         message_lb: (Vec<Vec<String>>, Vec<String>),
         voice_lb: (Vec<Vec<String>>, Vec<String>),
         online_lb: (Vec<Vec<String>>, Vec<String>),
         xp_lb: (Vec<Vec<String>>, Vec<String>),
+        // Online stats of the week before the reset
+        online: OnlineStats,
+        // Change of the guild's global rank in the week before the reset, `None` if it isn't
+        // sampled
+        global_rank: Option<RankChange>,
+    },
+    DailyReset {
+        // The daily stats before the reset
+        summary: DailySummary,
+    },
+    /// Sent after [`DBEvent::WeeklyReset`], with the guild members that didn't meet the weekly xp
+    /// requirement of the week before the reset
+    XpRequirementReport {
+        misses: Vec<XpMiss>,
     },
+    /// Sent after [`DBEvent::WeeklyReset`], with the members' goals that are reached in the week
+    /// before the reset
+    GoalReport {
+        achieved: Vec<AchievedGoal>,
+    },
+    /// Sent daily with the progress of the running guild goals, and the ones whose deadline has
+    /// passed without being completed
+    GuildGoalUpdate {
+        running: Vec<GuildGoal>,
+        expired: Vec<GuildGoal>,
+    },
+    /// Sent when a guild goal is completed
+    GuildGoalComplete {
+        goal: GuildGoal,
+    },
+    /// Sent after a stat is manually reset to 0
+    StatReset {
+        stat: Stat,
+        // Member whose stat is reset, `None` if it is reset for everyone
+        mid: Option<MemberId>,
+        // Discord user that reset the stat
+        caller: DiscordId,
+    },
+    /// Sent when a suspicious guild xp contribution is held back for staff review
+    XpCorrection {
+        correction: XpCorrection,
+    },
+    /// Sent when a guild join is held back for staff review, because the member holding the guild
+    /// profile has a discord link that looks stale
+    LinkConflict {
+        conflict: LinkConflict,
+    },
+    /// Sent when a member leaving the guild or the discord server is recorded
+    MemberExit {
+        exit: MemberExit,
+    },
+}
+
+impl DBEvent {
+    /// Name of the event's variant
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::MemberAdd { .. } => "MemberAdd",
+            Self::GuestAdd { .. } => "GuestAdd",
+            Self::MemberRemove { .. } => "MemberRemove",
+            Self::MemberFullPromote { .. } => "MemberFullPromote",
+            Self::MemberAutoGuildDemote { .. } => "MemberAutoGuildDemote",
+            Self::MemberRankChange { .. } => "MemberRankChange",
+            Self::MemberRankExpire { .. } => "MemberRankExpire",
+            Self::WynnProfileAdd { .. } => "WynnProfileAdd",
+            Self::WynnProfileBind { .. } => "WynnProfileBind",
+            Self::WynnProfileUnbind { .. } => "WynnProfileUnbind",
+            Self::GuildProfileAdd { .. } => "GuildProfileAdd",
+            Self::DiscordProfileAdd { .. } => "DiscordProfileAdd",
+            Self::DiscordProfileBind { .. } => "DiscordProfileBind",
+            Self::DiscordProfileUnbind { .. } => "DiscordProfileUnbind",
+            Self::WeeklyReport { .. } => "WeeklyReport",
+            Self::WeeklyResetSkip => "WeeklyResetSkip",
+            Self::WeeklyResetUndo { .. } => "WeeklyResetUndo",
+            Self::WeeklyReset { .. } => "WeeklyReset",
+            Self::DailyReset { .. } => "DailyReset",
+            Self::XpRequirementReport { .. } => "XpRequirementReport",
+            Self::GoalReport { .. } => "GoalReport",
+            Self::GuildGoalUpdate { .. } => "GuildGoalUpdate",
+            Self::GuildGoalComplete { .. } => "GuildGoalComplete",
+            Self::StatReset { .. } => "StatReset",
+            Self::XpCorrection { .. } => "XpCorrection",
+            Self::LinkConflict { .. } => "LinkConflict",
+            Self::MemberExit { .. } => "MemberExit",
+        }
+    }
 }
 
 signal!(DBSignal, DBRecv, DBEvent);
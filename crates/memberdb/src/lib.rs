@@ -1,56 +1,183 @@
 pub mod api;
 pub mod events;
+#[cfg(feature = "discord")]
 pub mod loops;
+#[cfg(feature = "discord")]
+pub mod message_limiter;
+pub mod migrate;
 pub mod model;
+#[cfg(feature = "discord")]
+pub mod online_limiter;
 pub mod query_builder;
+pub mod query_stats;
+pub mod reset_gate;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+#[cfg(feature = "discord")]
 pub mod utils;
 pub mod voice_tracker;
 
+#[cfg(feature = "discord")]
 use std::collections::HashSet;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::time::Duration;
 
 use anyhow::{Context, Result};
+#[cfg(feature = "discord")]
 use serenity::async_trait;
+#[cfg(feature = "discord")]
 use serenity::prelude::TypeMapKey;
 use sqlx::pool::PoolConnection;
 use sqlx::query::Map;
-use sqlx::sqlite::{SqliteArguments, SqliteConnectOptions, SqlitePoolOptions, SqliteRow};
-use sqlx::Error;
+use sqlx::sqlite::{
+    SqliteArguments, SqliteConnectOptions, SqliteJournalMode, SqlitePoolOptions, SqliteRow, SqliteSynchronous,
+};
+use sqlx::{Error, Execute};
 use sqlx::{Pool, Sqlite};
 use tokio::sync::broadcast::Receiver;
+#[cfg(feature = "discord")]
 use tokio::sync::RwLock;
+use tracing::info;
 
+#[cfg(feature = "discord")]
+use config::database::DatabaseSettings;
+#[cfg(feature = "discord")]
 use wynn::loops::TrackedIgn;
 
-pub use crate::api::fetch::*;
+pub use crate::api::daily::*;
+pub use crate::api::global_rank;
+pub use crate::api::goal;
+pub use crate::api::guild_goal;
+pub use crate::api::ign_history;
+pub use crate::api::ign_sync;
+pub use crate::api::level;
+pub use crate::api::link_conflict;
+pub use crate::api::member_exit;
+pub use crate::api::message_log;
+pub use crate::api::online_history;
+pub use crate::api::poll;
+pub use crate::api::promotion_vote::*;
+pub use crate::api::rank_history;
+pub use crate::api::reminder;
+pub use crate::api::role_connection;
+pub use crate::api::stat_increment;
+pub use crate::api::stat_reset::*;
+pub use crate::api::suggestion;
 pub use crate::api::table;
+pub use crate::api::ticket;
 pub use crate::api::update::*;
+pub use crate::api::waitlist;
+pub use crate::api::weekly_backup;
+pub use crate::api::weekly_report;
+pub use crate::api::xp_correction;
+pub use crate::api::xp_requirement;
 pub use crate::api::*;
 use crate::events::{DBEvent, DBSignal};
+#[cfg(feature = "discord")]
 use crate::model::wynn::McId;
+use crate::query_stats::QueryStats;
 
 pub type Conn = PoolConnection<Sqlite>;
 
+#[derive(Debug, Clone)]
+/// Settings of the database connection pool
+pub struct ConnectSettings {
+    pub max_connections: u32,
+    /// Amount of connections kept open even when they are idle
+    pub min_connections: u32,
+    /// Amount of prepared statements cached by each connection
+    pub statement_cache_capacity: usize,
+    pub journal_mode: SqliteJournalMode,
+    pub synchronous: SqliteSynchronous,
+    /// How long a connection waits for another one to release its lock on the database, before
+    /// failing with "database is locked"
+    pub busy_timeout: Duration,
+}
+
+impl Default for ConnectSettings {
+    /// WAL journal, so reads don't block on writes
+    fn default() -> Self {
+        Self {
+            max_connections: 5,
+            min_connections: 0,
+            statement_cache_capacity: 100,
+            journal_mode: SqliteJournalMode::Wal,
+            synchronous: SqliteSynchronous::Normal,
+            busy_timeout: Duration::from_secs(5),
+        }
+    }
+}
+
 #[derive(Debug)]
 /// A database connection
 pub struct DB {
     pool: Pool<Sqlite>,
     signal: DBSignal,
+    stats: Arc<QueryStats>,
+    /// If stat increments are recorded, see [`stat_increment`]
+    stat_audit: AtomicBool,
 }
 
 impl DB {
     /// Connect to the database
-    pub async fn new(file: &str, max_conn: u32) -> Self {
+    #[cfg(feature = "discord")]
+    pub async fn new(file: &str, settings: &DatabaseSettings) -> Self {
+        let connect = ConnectSettings {
+            max_connections: settings.max_connections,
+            min_connections: settings.min_connections,
+            statement_cache_capacity: settings.statement_cache_capacity,
+            journal_mode: settings.journal_mode.parse().expect("Invalid database journal mode"),
+            synchronous: settings.synchronous.parse().expect("Invalid database synchronous setting"),
+            busy_timeout: Duration::from_millis(settings.busy_timeout_ms),
+        };
+        Self::open(file, &connect, Duration::from_millis(settings.slow_query_ms)).await
+    }
+
+    /// Connect to the database without the bot's config, queries that take at least `slow_query`
+    /// are logged
+    pub async fn open(file: &str, settings: &ConnectSettings, slow_query: Duration) -> Self {
         Self {
-            pool: connect_db(file, max_conn).await,
+            pool: connect_db(file, settings).await,
             signal: DBSignal::new(64),
+            stats: Arc::new(QueryStats::new(slow_query)),
+            stat_audit: AtomicBool::new(false),
         }
     }
 
     /// Begin a transaction
     pub async fn begin(&self) -> Result<Transaction> {
         let tx = self.pool.begin().await.context("Failed to begin db transaction")?;
-        Ok(Transaction { tx, signal: self.signal.clone() })
+        Ok(Transaction {
+            tx,
+            signal: self.signal.clone(),
+            stats: Arc::clone(&self.stats),
+            stat_audit: self.stat_audit.load(Ordering::Relaxed),
+        })
+    }
+
+    /// Set if stat increments are recorded, see [`stat_increment`].
+    /// Transactions that already began aren't affected.
+    pub fn set_stat_audit(&self, enabled: bool) {
+        self.stat_audit.store(enabled, Ordering::Relaxed);
+    }
+
+    /// Check if stat increments are recorded
+    pub fn stat_audit(&self) -> bool {
+        self.stat_audit.load(Ordering::Relaxed)
+    }
+
+    /// Get the timings of the queries ran on this database
+    pub fn query_stats(&self) -> &QueryStats {
+        &self.stats
+    }
+
+    /// Get the migrations applied to the database, in the form of `(version, description)`
+    pub async fn migrations(&self) -> Result<Vec<(i64, String)>> {
+        sqlx::query_as("SELECT version,description FROM _sqlx_migrations ORDER BY version")
+            .fetch_all(&self.pool)
+            .await
+            .context("Failed to fetch applied migrations")
     }
 
     /// Get an event receiver
@@ -60,6 +187,7 @@ impl DB {
 
     /// Broadcast an event
     pub fn signal(&self, event: DBEvent) {
+        info!(event = event.kind(), "Broadcasting db event");
         self.signal.signal(event);
     }
 
@@ -68,6 +196,7 @@ impl DB {
     }
 }
 
+#[cfg(feature = "discord")]
 impl TypeMapKey for DB {
     type Value = Arc<RwLock<DB>>;
 }
@@ -77,6 +206,9 @@ impl TypeMapKey for DB {
 pub struct Transaction {
     tx: sqlx::Transaction<'static, Sqlite>,
     signal: DBSignal,
+    stats: Arc<QueryStats>,
+    /// If stat increments are recorded, as it was when the transaction began
+    stat_audit: bool,
 }
 
 impl Transaction {
@@ -91,6 +223,7 @@ impl Transaction {
 
     /// Broadcast an event
     pub fn signal(&self, event: DBEvent) {
+        info!(event = event.kind(), "Broadcasting db event");
         self.signal.signal(event);
     }
 
@@ -108,12 +241,13 @@ pub enum Executor<'a> {
 type OptionalMap<'q, F> = Map<'q, Sqlite, F, SqliteArguments<'q>>;
 
 macro_rules! query_call {
-    ($self:ident, $query:ident, $method:ident) => {
+    ($self:ident, $query:ident, $method:ident) => {{
+        let sql = $query.sql();
         match $self {
-            Executor::Pool(pool) => $query.$method(&pool.pool).await,
-            Executor::Transaction(tx) => $query.$method(&mut tx.tx).await,
+            Executor::Pool(db) => db.stats.time(sql, $query.$method(&db.pool)).await,
+            Executor::Transaction(tx) => tx.stats.time(sql, $query.$method(&mut tx.tx)).await,
         }
-    };
+    }};
 }
 
 impl<'a> Executor<'a> {
@@ -143,10 +277,19 @@ impl<'a> Executor<'a> {
 }
 
 /// Connect to the database
-async fn connect_db(file: &str, max_conn: u32) -> Pool<Sqlite> {
+async fn connect_db(file: &str, settings: &ConnectSettings) -> Pool<Sqlite> {
+    info!(?settings, "Connecting to database");
+    let options = SqliteConnectOptions::new()
+        .filename(file)
+        .create_if_missing(true)
+        .statement_cache_capacity(settings.statement_cache_capacity)
+        .journal_mode(settings.journal_mode)
+        .synchronous(settings.synchronous)
+        .busy_timeout(settings.busy_timeout);
     let db = SqlitePoolOptions::new()
-        .max_connections(max_conn)
-        .connect_with(SqliteConnectOptions::new().filename(file).create_if_missing(true))
+        .max_connections(settings.max_connections)
+        .min_connections(settings.min_connections)
+        .connect_with(options)
         .await
         .expect("Couldn't connect to database");
     sqlx::migrate!("./migrations")
@@ -156,8 +299,10 @@ async fn connect_db(file: &str, max_conn: u32) -> Pool<Sqlite> {
     db
 }
 
+#[cfg(feature = "discord")]
 pub struct TrackedIgnGetter(pub Arc<RwLock<DB>>);
 
+#[cfg(feature = "discord")]
 #[async_trait]
 impl TrackedIgn for TrackedIgnGetter {
     async fn tracked_ign(&self) -> Result<HashSet<String>> {
@@ -1,117 +1,514 @@
 //! Loops required to manage the database
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use anyhow::{Context, Result};
 use serenity::client::Cache;
 use serenity::http::CacheHttp;
-use serenity::model::channel::Channel;
-use serenity::model::id::ChannelId;
+use serenity::model::channel::{Channel, ChannelType};
+use serenity::model::guild::audit_log::{Action, MemberAction};
+use serenity::model::id::{ChannelId, GuildId, UserId};
+use serenity::model::voice::VoiceState;
+use serenity::model::Timestamp;
 use tokio::sync::{Mutex, RwLock};
 use tokio::time::{self, Duration as ADuration};
-use tracing::{error, info, instrument};
+use tracing::{error, info, instrument, warn};
 
+use config::correction::XpCorrections;
+use config::online::OnlineLimits;
+use config::tag::TextChannelTag;
+use config::voice::{StageRole, VoiceActivity};
 use config::Config;
 use event::timer::{TimerEvent, TimerSignal};
 use event::{DiscordContext, DiscordEvent, DiscordSignal};
+use util::task::{RestartPolicy, Spawner};
 use util::{ctx, ok, some};
+use wynn::api::WynnApi;
 use wynn::cache::Cache as WynnCache;
 use wynn::events::{WynnEvent, WynnSignal};
 
+use crate::api::global_rank::RankSample;
+use crate::api::level::XpSample;
+use crate::api::link_conflict::{self, GuildJoin};
+use crate::api::member_exit::{self, ExitReason, ExitSource};
+use crate::api::stat_increment::AuditedStat;
 use crate::events::DBEvent;
+use crate::message_limiter::MessageLimiter;
 use crate::model::discord::DiscordId;
 use crate::model::guild::GuildRank;
 use crate::model::wynn::McId;
+use crate::online_limiter::OnlineLimiter;
+use crate::reset_gate::{PendingReset, ResetDecision, ResetGate};
 use crate::voice_tracker::VoiceTracker;
-use crate::DB;
+use crate::{Transaction, DB};
+
+/// Amount of the latest audit log entries checked for why a user left the discord guild
+const AUDIT_LOG_LIMIT: u8 = 10;
+/// Max amount of seconds between an audit log entry and a user leaving for it to be the reason
+const AUDIT_LOG_MAX_AGE: i64 = 30;
+/// Duration over which the igns of all mc accounts are re-synced with Mojang
+pub const IGN_SYNC_PERIOD: Duration = Duration::from_secs(7 * 24 * 3600);
+/// Least duration between the ign re-syncs of two mc accounts
+pub const IGN_SYNC_MIN_INTERVAL: Duration = Duration::from_secs(30);
 
 /// Start database managing loops
 #[allow(clippy::too_many_arguments)]
 pub async fn start_loops(
-    db: Arc<RwLock<DB>>, config: Arc<RwLock<Config>>, cache: Arc<Cache>, wynn_cache: Arc<WynnCache>,
-    vt: Arc<Mutex<VoiceTracker>>, wynn_sig: WynnSignal, dc_sig: DiscordSignal, timer_sig: TimerSignal,
+    spawner: &impl Spawner, db: Arc<RwLock<DB>>, config: Arc<RwLock<Config>>, cache: Arc<Cache>,
+    wynn_cache: Arc<WynnCache>, vt: Arc<Mutex<VoiceTracker>>, wynn_sig: WynnSignal, dc_sig: DiscordSignal,
+    timer_sig: TimerSignal, reset_gate: Arc<ResetGate>,
 ) {
+    let main_guild: u64 = std::env::var("MAIN_GUILD")
+        .expect("Expected main guild id in the environment")
+        .parse()
+        .expect("Invalid main guild id");
     let shared_db = db.clone();
-    tokio::spawn(async move {
-        info!("Starting member manage loop (wynn event)");
-        let mut recv = wynn_sig.connect();
-        loop {
-            let events = recv.recv().await.unwrap();
-            let mut events_to_send = Vec::new();
-
-            for event in events.as_ref() {
-                if let Some(ref mut events) = process_wynn_event(&shared_db, event).await {
-                    events_to_send.append(events);
+    let shared_config = config.clone();
+    let shared_cache = cache.clone();
+    spawner.spawn("member manage (wynn event)", RestartPolicy::Always, move || {
+        let wynn_sig = wynn_sig.clone();
+        let shared_db = shared_db.clone();
+        let shared_config = shared_config.clone();
+        let shared_cache = shared_cache.clone();
+        async move {
+            info!("Starting member manage loop (wynn event)");
+            let mut recv = wynn_sig.connect();
+            let mut limiter = OnlineLimiter::new();
+            loop {
+                let events = recv.recv().await.unwrap();
+                let mut events_to_send = Vec::new();
+                let (limits, alumni_tenure, corrections, conflicts) = {
+                    let config = shared_config.read().await;
+                    // Contributions are only held back if there is a channel to review them in
+                    let corrections = config
+                        .text_channel_tags
+                        .tagged_objects(&TextChannelTag::XpCorrection)
+                        .next()
+                        .map(|_| config.xp_corrections.clone());
+                    // Same goes for guild joins
+                    let conflicts = config
+                        .text_channel_tags
+                        .tagged_objects(&TextChannelTag::LinkConflict)
+                        .next()
+                        .map(|_| (shared_cache.as_ref(), GuildId(main_guild)));
+                    (config.online_limits.clone(), config.alumni.min_tenure(), corrections, conflicts)
+                };
+
+                for event in events.as_ref() {
+                    if let Some(ref mut events) = process_wynn_event(
+                        &shared_db,
+                        &mut limiter,
+                        &limits,
+                        alumni_tenure,
+                        corrections.as_ref(),
+                        conflicts,
+                        event,
+                    )
+                    .await
+                    {
+                        events_to_send.append(events);
+                    }
                 }
-            }
+                update_daily_online(&shared_db, events.as_ref()).await;
 
-            if !events_to_send.is_empty() {
-                wynn_sig.signal(events_to_send);
+                if !events_to_send.is_empty() {
+                    wynn_sig.signal(events_to_send);
+                }
             }
         }
     });
 
     let shared_db = db.clone();
+    let shared_config = config.clone();
     let shared_vt = vt.clone();
-    tokio::spawn(async move {
-        info!("Starting member manage loop (discord event)");
-        let mut recv = dc_sig.connect();
-        loop {
-            let event = recv.recv().await.unwrap();
-            let (ctx, event) = event.as_ref();
-            process_discord_event(&shared_db, &config, &shared_vt, event, ctx).await;
+    spawner.spawn("member manage (discord event)", RestartPolicy::Always, move || {
+        let dc_sig = dc_sig.clone();
+        let shared_db = shared_db.clone();
+        let shared_config = shared_config.clone();
+        let shared_vt = shared_vt.clone();
+        async move {
+            info!("Starting member manage loop (discord event)");
+            let mut recv = dc_sig.connect();
+            let mut limiter = MessageLimiter::new();
+            loop {
+                let event = recv.recv().await.unwrap();
+                let (ctx, event) = event.as_ref();
+                process_discord_event(&shared_db, &shared_config, &shared_vt, &mut limiter, event, ctx).await;
+            }
         }
     });
 
     let shared_db = db.clone();
-    tokio::spawn(async move {
-        info!("Starting member manage loop (db event)");
-        let mut recv = {
-            let db = shared_db.read().await;
-            db.connect()
-        };
-        loop {
-            let event = recv.recv().await.unwrap();
-            process_db_event(&shared_db, &wynn_cache, &event).await;
+    let shared_wynn_cache = wynn_cache.clone();
+    spawner.spawn("member manage (db event)", RestartPolicy::Always, move || {
+        let shared_db = shared_db.clone();
+        let shared_wynn_cache = shared_wynn_cache.clone();
+        async move {
+            info!("Starting member manage loop (db event)");
+            let mut recv = {
+                let db = shared_db.read().await;
+                db.connect()
+            };
+            loop {
+                let event = recv.recv().await.unwrap();
+                process_db_event(&shared_db, &shared_wynn_cache, &event).await;
+            }
         }
     });
 
     let shared_db = db.clone();
-    tokio::spawn(async move {
-        // The actual voice tracking update is done here instead of the discord event listening
-        // loop
-        info!("Starting voice tracking update loop");
-        let mut interval = time::interval(ADuration::from_secs(60));
-        loop {
-            interval.tick().await;
-            let mut vt = vt.lock().await;
-            for (id, dur) in vt.track_all_voice() {
-                track_voice_db(&shared_db, *id, dur).await;
+    let shared_wynn_cache = wynn_cache.clone();
+    spawner.spawn("guild xp sampling", RestartPolicy::Always, move || {
+        let wynn_cache = shared_wynn_cache.clone();
+        let shared_db = shared_db.clone();
+        async move {
+            info!("Starting guild xp sampling loop");
+            let mut interval = time::interval(ADuration::from_secs(3600));
+            loop {
+                interval.tick().await;
+                let (level, xp) = {
+                    let guild = wynn_cache.guild.read().await;
+                    let guild = some!(guild.as_ref(), continue);
+                    (guild.level, guild.xp)
+                };
+                let now = ok!(
+                    SystemTime::now().duration_since(UNIX_EPOCH),
+                    "Failed to get current unix timestamp",
+                    continue
+                );
+                let time = ok!(i64::try_from(now.as_secs()), "Failed to convert u64 to i64", continue);
+                let sample = XpSample { time, level: level.into(), xp: xp.into() };
+
+                let db = shared_db.write().await;
+                let _ =
+                    ctx!(crate::level::add_xp_sample(&db, &sample).await, "Failed to add guild xp sample");
             }
         }
     });
 
-    tokio::spawn(async move {
-        info!("Starting member manage loop (timer event)");
-        let mut recv = timer_sig.connect();
-        loop {
-            let event = recv.recv().await.unwrap();
-            match event.as_ref() {
-                TimerEvent::Weekly => {
-                    info!("Starting weekly reset");
-                    let db = db.write().await;
-                    let _ = ctx!(crate::weekly_reset(&db, &cache).await, "Failed weekly reset");
+    let shared_db = db.clone();
+    spawner.spawn("global rank sampling", RestartPolicy::Always, move || {
+        let wynn_cache = wynn_cache.clone();
+        let shared_db = shared_db.clone();
+        async move {
+            info!("Starting global rank sampling loop");
+            let mut interval = time::interval(ADuration::from_secs(3600));
+            loop {
+                interval.tick().await;
+                let (rank, level, xp, territories) = {
+                    let entry = wynn_cache.leaderboard.read().await;
+                    let entry = some!(entry.as_ref(), continue);
+                    (entry.rank, entry.level, entry.xp, entry.territories)
+                };
+                let now = ok!(
+                    SystemTime::now().duration_since(UNIX_EPOCH),
+                    "Failed to get current unix timestamp",
+                    continue
+                );
+                let time = ok!(i64::try_from(now.as_secs()), "Failed to convert u64 to i64", continue);
+                let sample = RankSample {
+                    time,
+                    rank: rank.into(),
+                    level: level.into(),
+                    xp,
+                    territories: territories.into(),
+                };
+
+                let db = shared_db.write().await;
+                let _ = ctx!(
+                    crate::global_rank::add_rank_sample(&db, &sample).await,
+                    "Failed to add global rank sample"
+                );
+            }
+        }
+    });
+
+    let shared_db = db.clone();
+    spawner.spawn("voice tracking", RestartPolicy::Always, move || {
+        let vt = vt.clone();
+        let shared_db = shared_db.clone();
+        async move {
+            // The actual voice tracking update is done here instead of the discord event listening
+            // loop
+            info!("Starting voice tracking update loop");
+            let mut interval = time::interval(ADuration::from_secs(60));
+            loop {
+                interval.tick().await;
+                let mut vt = vt.lock().await;
+                for (id, channel, dur) in vt.track_all_voice() {
+                    track_voice_db(&shared_db, *id, channel, dur).await;
+                }
+                for (id, dur) in vt.track_all_stream() {
+                    track_stream_db(&shared_db, *id, dur).await;
+                }
+            }
+        }
+    });
+
+    resume_pending_reset(&db, &config, &cache, main_guild, &reset_gate).await;
+
+    spawner.spawn("member manage (timer event)", RestartPolicy::Always, move || {
+        let timer_sig = timer_sig.clone();
+        let db = db.clone();
+        let config = config.clone();
+        let cache = cache.clone();
+        let reset_gate = reset_gate.clone();
+        async move {
+            info!("Starting member manage loop (timer event)");
+            let mut recv = timer_sig.connect();
+            loop {
+                let event = recv.recv().await.unwrap();
+                match event.as_ref() {
+                    TimerEvent::Daily => {
+                        info!("Reverting expired temporary ranks");
+                        let now = ok!(
+                            SystemTime::now().duration_since(UNIX_EPOCH),
+                            "Failed to get current unix timestamp",
+                            continue
+                        );
+                        let now = ok!(i64::try_from(now.as_secs()), "Failed to convert u64 to i64", continue);
+                        {
+                            let db = db.write().await;
+                            let _ = ctx!(
+                                crate::revert_expired_ranks(&db, now).await,
+                                "Failed to revert expired temporary ranks"
+                            );
+                        }
+
+                        let (retention, audit_retention) = {
+                            let config = config.read().await;
+                            (config.message_log.retention(), config.stat_audit.retention())
+                        };
+                        {
+                            let db = db.write().await;
+                            let _ = ctx!(
+                                crate::message_log::prune_messages(&db, now - retention).await,
+                                "Failed to prune message log"
+                            );
+                        }
+                        {
+                            let db = db.write().await;
+                            let _ = ctx!(
+                                crate::stat_increment::prune_increments(&db, now - audit_retention).await,
+                                "Failed to prune stat increments"
+                            );
+                        }
+
+                        {
+                            let db = db.write().await;
+                            let expired = ctx!(
+                                crate::guild_goal::end_expired_goals(&db, now).await,
+                                "Failed to end expired guild goals"
+                            );
+                            let running = ctx!(crate::guild_goal::running_goals(&mut db.exe()).await);
+                            if let (Ok(running), Ok(expired)) = (running, expired) {
+                                if !running.is_empty() || !expired.is_empty() {
+                                    db.signal(DBEvent::GuildGoalUpdate { running, expired });
+                                }
+                            }
+                        }
+
+                        info!("Starting daily reset");
+                        let db = db.write().await;
+                        let _ = ctx!(crate::daily_reset(&db).await, "Failed daily reset");
+                    }
+                    TimerEvent::Weekly => {
+                        let now = ok!(
+                            SystemTime::now().duration_since(UNIX_EPOCH),
+                            "Failed to get current unix timestamp",
+                            continue
+                        );
+                        let now = ok!(i64::try_from(now.as_secs()), "Failed to convert u64 to i64", continue);
+                        let (requirements, grace, fmt) = {
+                            let config = config.read().await;
+                            let requirements =
+                                crate::xp_requirement::parse_requirements(&config.xp_requirements);
+                            let fmt = config.guild_stat_format(Some(main_guild));
+                            (requirements, config.weekly_reset.grace(), fmt)
+                        };
+
+                        info!("Making weekly report");
+                        let report = {
+                            let db = db.read().await;
+                            ctx!(
+                                crate::weekly_report::make_report(&db, &cache, &fmt, &requirements, now)
+                                    .await,
+                                "Failed to make weekly report"
+                            )
+                        };
+                        // Without a report, staff has nothing to decide on, so the reset doesn't wait
+                        let pending = match report {
+                            Ok(report) => {
+                                let deadline = grace.map(|grace| {
+                                    now.saturating_add(grace.as_secs().try_into().unwrap_or(i64::MAX))
+                                });
+                                {
+                                    let db = db.write().await;
+                                    let _ = ctx!(
+                                        crate::weekly_report::store_report(&db, &report).await,
+                                        "Failed to store weekly report"
+                                    );
+                                    if let Some(deadline) = deadline {
+                                        let _ = ctx!(
+                                            crate::weekly_report::set_pending_reset(&db, now, deadline).await,
+                                            "Failed to store pending weekly reset"
+                                        );
+                                    }
+                                }
+                                let pending = grace.map(|grace| (reset_gate.open(), grace));
+                                db.read().await.signal(DBEvent::WeeklyReport { report, deadline });
+                                pending
+                            }
+                            Err(_) => None,
+                        };
+                        // Waiting for staff is done separately, so other timed tasks aren't held up
+                        let reset =
+                            weekly_reset(db.clone(), config.clone(), cache.clone(), main_guild, pending, now);
+                        tokio::spawn(reset);
+                    }
+                }
+            }
+        }
+    });
+}
+
+/// Wait for staff to decide on a pending weekly reset, then reset the weekly stats unless it is
+/// skipped. `now` is the time of the report the reset is for.
+async fn weekly_reset(
+    db: Arc<RwLock<DB>>, config: Arc<RwLock<Config>>, cache: Arc<Cache>, main_guild: u64,
+    pending: Option<(PendingReset, Duration)>, now: i64,
+) {
+    if let Some((pending, grace)) = pending {
+        info!(?grace, "Waiting for staff to decide on the weekly reset");
+        let decision = pending.decision(grace).await;
+        {
+            let db = db.write().await;
+            let _ = ctx!(
+                crate::weekly_report::clear_pending_reset(&db).await,
+                "Failed to clear pending weekly reset"
+            );
+        }
+        if decision == ResetDecision::Skip {
+            info!("Weekly reset is skipped by staff");
+            db.read().await.signal(DBEvent::WeeklyResetSkip);
+            return;
+        }
+    }
+
+    let (requirements, fmt) = {
+        let config = config.read().await;
+        let requirements = crate::xp_requirement::parse_requirements(&config.xp_requirements);
+        (requirements, config.guild_stat_format(Some(main_guild)))
+    };
+    info!("Starting weekly reset");
+    let db = db.write().await;
+    // Needs to be recorded before the weekly stats are reset
+    let misses = ctx!(
+        crate::xp_requirement::record_weekly_xp(&db, &requirements, now).await,
+        "Failed to record weekly xp requirements"
+    );
+    let achieved = ctx!(crate::goal::evaluate_goals(&db).await, "Failed to evaluate weekly goals");
+    let _ = ctx!(crate::weekly_reset(&db, &cache, &fmt).await, "Failed weekly reset");
+    if let Ok(misses) = misses {
+        db.signal(DBEvent::XpRequirementReport { misses });
+    }
+    if let Ok(achieved) = achieved {
+        db.signal(DBEvent::GoalReport { achieved });
+    }
+}
+
+/// Keep waiting on the weekly reset that was waiting for staff before the bot stopped, it
+/// proceeds right away if its deadline has passed in the meantime.
+async fn resume_pending_reset(
+    db: &Arc<RwLock<DB>>, config: &Arc<RwLock<Config>>, cache: &Arc<Cache>, main_guild: u64,
+    reset_gate: &ResetGate,
+) {
+    let pending = {
+        let db = db.read().await;
+        let pending = ctx!(crate::weekly_report::pending_reset(&mut db.exe()).await);
+        some!(ok!(pending, return), return)
+    };
+    let (time, deadline) = pending;
+    let now = ok!(SystemTime::now().duration_since(UNIX_EPOCH), "Failed to get current unix timestamp", return);
+    let remaining = u64::try_from(deadline.saturating_sub(now.as_secs() as i64)).unwrap_or(0);
+    info!(time, deadline, "Resuming pending weekly reset");
+    let pending = Some((reset_gate.open(), Duration::from_secs(remaining)));
+    tokio::spawn(weekly_reset(db.clone(), config.clone(), cache.clone(), main_guild, pending, time));
+}
+
+/// Start the loop that re-syncs the igns of all mc accounts with Mojang, so renames of accounts
+/// that aren't in the guild are picked up.
+///
+/// Accounts are checked one at a time, least recently checked first, spread out so every account
+/// is checked about once every [`IGN_SYNC_PERIOD`], but never more often than
+/// [`IGN_SYNC_MIN_INTERVAL`] to stay well under Mojang's rate limit. Progress is kept in the
+/// database, so a restart resumes where it left off.
+pub async fn start_ign_sync_loop(spawner: &impl Spawner, db: Arc<RwLock<DB>>, api: impl WynnApi) {
+    let api = Arc::new(api);
+    spawner.spawn("ign sync", RestartPolicy::Always, move || {
+        let db = db.clone();
+        let api = api.clone();
+        async move {
+            info!("Starting ign sync loop");
+            loop {
+                let count = {
+                    let db = db.read().await;
+                    ctx!(crate::ign_sync::account_count(&mut db.exe()).await)
+                };
+                let interval = match count {
+                    Ok(count) if count > 0 => IGN_SYNC_PERIOD / u32::try_from(count).unwrap_or(u32::MAX),
+                    _ => IGN_SYNC_PERIOD / 7 / 24,
+                };
+                time::sleep(interval.max(IGN_SYNC_MIN_INTERVAL)).await;
+
+                // The account is picked after sleeping, so its ign isn't stale if it changed meanwhile
+                let account = {
+                    let db = db.read().await;
+                    ctx!(crate::ign_sync::next_account(&mut db.exe()).await)
+                };
+                let (mcid, old_ign) = some!(ok!(account, continue), continue);
+                // Accounts that fail are still marked as checked, so they don't hold up the rest
+                let new_ign = match api.get_player(&mcid.0).await {
+                    Ok(ign) => ign,
+                    Err(why) => {
+                        warn!(%mcid, "Failed to fetch ign from Mojang during ign sync: {:#}", why);
+                        old_ign.clone()
+                    }
+                };
+                let now = ok!(
+                    SystemTime::now().duration_since(UNIX_EPOCH),
+                    "Failed to get current unix timestamp",
+                    continue
+                );
+                let now = ok!(i64::try_from(now.as_secs()), "Failed to convert u64 to i64", continue);
+
+                let db = db.write().await;
+                let mut tx = ok!(ctx!(db.begin().await), continue);
+                let changed = ok!(
+                    crate::ign_sync::sync_ign(&mut tx, &mcid, &old_ign, &new_ign, now).await,
+                    "Failed to sync ign",
+                    continue
+                );
+                if ctx!(tx.commit().await).is_ok() && changed {
+                    info!(%mcid, old_ign, new_ign, "Ign sync found a renamed mc account");
                 }
             }
         }
     });
 }
 
-#[instrument(skip(db))]
-/// Updates the database based on WynnEvent
-async fn process_wynn_event(db: &RwLock<DB>, event: &WynnEvent) -> Option<Vec<WynnEvent>> {
+#[instrument(skip(db, limiter, limits))]
+/// Updates the database based on WynnEvent.
+/// `alumni_tenure` is the least amount of seconds a member leaving the guild has to be in it for to
+/// become an alumnus, `None` if members don't become alumni.
+/// `conflicts` is the cache and the id of the main discord guild that joins are checked against
+/// for stale discord links, `None` if joins aren't checked.
+async fn process_wynn_event(
+    db: &RwLock<DB>, limiter: &mut OnlineLimiter, limits: &OnlineLimits, alumni_tenure: Option<i64>,
+    corrections: Option<&XpCorrections>, conflicts: Option<(&Cache, GuildId)>, event: &WynnEvent,
+) -> Option<Vec<WynnEvent>> {
     match event {
-        WynnEvent::MemberJoin { id, rank, ign, xp } => {
+        WynnEvent::MemberJoin { id, rank, ign, xp, joined, wars } => {
             let mcid = McId(id.clone());
             let mid = {
                 let db = db.read().await;
@@ -159,6 +556,35 @@ async fn process_wynn_event(db: &RwLock<DB>, event: &WynnEvent) -> Option<Vec<Wy
                     let mut tx = ok!(ctx!(db.begin().await), return None);
                     // Bind guild profile as member has joined the guild
                     if let Ok(false) = ctx!(mcid.in_guild(&mut tx.exe()).await) {
+                        if let Some((cache, guild_id)) = conflicts {
+                            let join = GuildJoin {
+                                mcid: mcid.clone(),
+                                ign: ign.to_string(),
+                                rank: ok!(ctx!(GuildRank::from_api(rank)), return None),
+                                xp: *xp,
+                                joined: joined.to_string(),
+                                wars: *wars,
+                            };
+                            let now = ok!(
+                                SystemTime::now().duration_since(UNIX_EPOCH),
+                                "Failed to get current unix timestamp",
+                                return None
+                            );
+                            let now =
+                                ok!(i64::try_from(now.as_secs()), "Failed to convert u64 to i64", return None);
+                            let held = link_conflict::hold_join(&mut tx, &join, now, |discord| {
+                                in_guild(cache, guild_id, discord)
+                            })
+                            .await;
+                            if let Ok(true) = ctx!(held, "Failed to check guild join for link conflicts") {
+                                warn!(%id, %ign, "Holding back guild join for review");
+                                let _ = ctx!(tx.commit().await);
+                                // The rank is set once the join is applied
+                                events.retain(|event| !matches!(event, WynnEvent::MemberRankChange { .. }));
+                                return Some(events);
+                            }
+                        }
+
                         info!(%id, %rank, %ign, "Binding guild profile");
                         let rank = ok!(ctx!(GuildRank::from_api(rank)), return None);
                         let _ = ctx!(
@@ -168,7 +594,10 @@ async fn process_wynn_event(db: &RwLock<DB>, event: &WynnEvent) -> Option<Vec<Wy
 
                         info!(%id, xp, "Updates new guild member's xp");
                         ok!(mcid.update_xp(&mut tx, *xp).await, "Failed to update xp", return None);
+                        audit_wynn(&mut tx, &mcid, AuditedStat::Xp, *xp, "MemberJoin").await;
+                        let _ = ctx!(crate::add_daily_join(&mut tx).await);
                     }
+                    update_guild_info(&mut tx, &mcid, joined, wars).await;
                     let _ = ctx!(tx.commit().await);
 
                     return Some(events);
@@ -193,6 +622,9 @@ async fn process_wynn_event(db: &RwLock<DB>, event: &WynnEvent) -> Option<Vec<Wy
                     // following operation won't duplicate their xp as it has been reset.
                     info!(%id, xp, "Updates new guild member's xp");
                     ok!(mcid.update_xp(&mut tx, *xp).await, "Failed to update xp", return None);
+                    audit_wynn(&mut tx, &mcid, AuditedStat::Xp, *xp, "MemberJoin").await;
+                    update_guild_info(&mut tx, &mcid, joined, wars).await;
+                    let _ = ctx!(crate::add_daily_join(&mut tx).await);
 
                     let _ = ctx!(tx.commit().await);
                 }
@@ -202,13 +634,37 @@ async fn process_wynn_event(db: &RwLock<DB>, event: &WynnEvent) -> Option<Vec<Wy
             let mcid = McId(id.clone());
             info!(%id, %rank, %ign, "Removing guild member");
             let rank = ok!(ctx!(GuildRank::from_api(rank)), return None);
+            let now = ok!(
+                SystemTime::now().duration_since(UNIX_EPOCH),
+                "Failed to get current unix timestamp",
+                return None
+            );
+            let now = ok!(i64::try_from(now.as_secs()), "Failed to convert u64 to i64", return None);
             let db = db.write().await;
             let mut tx = ok!(ctx!(db.begin().await), return None);
+            // The join was never applied, so there is nothing else to undo
+            if let Ok(true) = ctx!(link_conflict::drop_conflicts(&mut tx, &mcid).await) {
+                info!(%id, %ign, "Guild member left before their join is reviewed");
+                let _ = ctx!(tx.commit().await);
+                return None;
+            }
+            // The api doesn't tell if the player is kicked, so it is always recorded as left
+            if let Ok(Some(mid)) = ctx!(mcid.mid(&mut tx.exe()).await) {
+                let _ = ctx!(
+                    member_exit::record_exit(&mut tx, mid, ExitSource::Guild, ExitReason::Left, now).await
+                );
+            }
+            if let Some(min_tenure) = alumni_tenure {
+                if let Ok(Some(mid)) = ctx!(mcid.make_alumni(&mut tx, min_tenure, now).await) {
+                    info!(%ign, ?mid, "Guild member left after their tenure, turned into alumnus");
+                }
+            }
             ok!(
                 mcid.bind_guild(&mut tx, ign, false, rank).await,
                 "Failed to unbind guild profile",
                 return None
             );
+            let _ = ctx!(crate::add_daily_leave(&mut tx).await);
             let _ = ctx!(tx.commit().await);
         }
         WynnEvent::MemberRankChange { id, old_rank, new_rank, ign } => {
@@ -217,7 +673,19 @@ async fn process_wynn_event(db: &RwLock<DB>, event: &WynnEvent) -> Option<Vec<Wy
             let rank = ok!(GuildRank::from_api(new_rank), "Error", return None);
             let db = db.write().await;
             let mut tx = ok!(ctx!(db.begin().await), return None);
+            // The old rank is read from the database, as events from different sources format it
+            // differently
+            let old_rank = mcid.rank(&mut tx.exe()).await;
             ok!(mcid.set_rank(&mut tx, rank).await, "Failed to update guild member guild rank", return None);
+            if let Some(old_rank) = old_rank.ok().filter(|old_rank| *old_rank != rank) {
+                let now = ok!(
+                    SystemTime::now().duration_since(UNIX_EPOCH),
+                    "Failed to get current unix timestamp",
+                    return None
+                );
+                let now = ok!(i64::try_from(now.as_secs()), "Failed to convert u64 to i64", return None);
+                let _ = ctx!(crate::rank_history::record_rank_change(&mut tx, &mcid, old_rank, rank, now).await);
+            }
             let _ = ctx!(tx.commit().await);
         }
         WynnEvent::MemberNameChange { id, new_name, .. } => {
@@ -225,19 +693,71 @@ async fn process_wynn_event(db: &RwLock<DB>, event: &WynnEvent) -> Option<Vec<Wy
             info!(%id, %new_name, "Updating guild member ign");
             let db = db.write().await;
             let mut tx = ok!(ctx!(db.begin().await), return None);
+            let old_name = mcid.ign(&mut tx.exe()).await;
             ok!(mcid.set_ign(&mut tx, new_name).await, "Failed to update guild member ign", return None);
+            if let Some(old_name) = old_name.ok().filter(|old_name| old_name != new_name) {
+                let now = ok!(
+                    SystemTime::now().duration_since(UNIX_EPOCH),
+                    "Failed to get current unix timestamp",
+                    return None
+                );
+                let now = ok!(i64::try_from(now.as_secs()), "Failed to convert u64 to i64", return None);
+                let _ = ctx!(
+                    crate::ign_history::record_ign_change(&mut tx, &mcid, &old_name, new_name, now).await
+                );
+            }
             let _ = ctx!(tx.commit().await);
         }
         WynnEvent::MemberContribute { id, old_contrib, new_contrib, ign } => {
             let mcid = McId(id.clone());
             let amount = new_contrib - old_contrib;
+            limiter.mark_active(ign);
+            if corrections.is_some_and(|corrections| corrections.is_suspicious(amount)) {
+                warn!(ign, amount, "Holding back suspicious guild member xp for review");
+                let now = ok!(
+                    SystemTime::now().duration_since(UNIX_EPOCH),
+                    "Failed to get current unix timestamp",
+                    return None
+                );
+                let now = ok!(i64::try_from(now.as_secs()), "Failed to convert u64 to i64", return None);
+                let db = db.write().await;
+                let mut tx = ok!(ctx!(db.begin().await), return None);
+                let correction = ok!(
+                    crate::xp_correction::add_correction(&mut tx, &mcid, ign, amount, now).await,
+                    "Failed to hold back guild member xp",
+                    return None
+                );
+                ok!(ctx!(tx.commit().await), return None);
+                db.signal(DBEvent::XpCorrection { correction });
+                return None;
+            }
             info!(ign, amount, "Updating guild member xp");
             let db = db.write().await;
             let mut tx = ok!(ctx!(db.begin().await), return None);
             ok!(mcid.update_xp(&mut tx, amount).await, "Failed to increment guild member xp", return None);
+            audit_wynn(&mut tx, &mcid, AuditedStat::Xp, amount, "MemberContribute").await;
+            let _ = ctx!(crate::update_daily_xp(&mut tx, amount).await);
+            let _ = ctx!(crate::guild_goal::add_xp(&mut tx, amount).await);
+            let _ = ctx!(tx.commit().await);
+        }
+        WynnEvent::MemberWar { id, new_wars, ign, .. } => {
+            let mcid = McId(id.clone());
+            info!(ign, new_wars, "Updating guild member war count");
+            let db = db.write().await;
+            let mut tx = ok!(ctx!(db.begin().await), return None);
+            ok!(mcid.set_wars(&mut tx, *new_wars).await, "Failed to update guild member war count", return None);
             let _ = ctx!(tx.commit().await);
         }
-        WynnEvent::PlayerStay { ign, world: _world, elapsed } => {
+        WynnEvent::PlayerStay { ign, world, elapsed } => {
+            let now = ok!(
+                SystemTime::now().duration_since(UNIX_EPOCH),
+                "Failed to get current unix timestamp",
+                return None
+            );
+            let counted = limiter.count_stay(limits, ign, world, *elapsed, now.as_secs() / 86400);
+            if counted == 0 {
+                return None;
+            }
             let id = {
                 let db = db.read().await;
                 ok!(McId::from_ign(&mut db.exe(), ign).await, "Failed to get id of ign from db", return None)
@@ -245,7 +765,7 @@ async fn process_wynn_event(db: &RwLock<DB>, event: &WynnEvent) -> Option<Vec<Wy
             if let Some(id) = id {
                 let db = db.write().await;
                 let elapsed = ok!(
-                    i64::try_from(*elapsed),
+                    i64::try_from(counted),
                     "Failed to convert elapsed activity: u64 to i64",
                     return None
                 );
@@ -255,9 +775,11 @@ async fn process_wynn_event(db: &RwLock<DB>, event: &WynnEvent) -> Option<Vec<Wy
                     "Failed to update wynn activity",
                     return None
                 );
+                audit_wynn(&mut tx, &id, AuditedStat::Online, elapsed, "PlayerStay").await;
                 let _ = ctx!(tx.commit().await);
             }
         }
+        WynnEvent::PlayerLeave { ign, .. } => limiter.leave(ign),
         _ => {}
     }
     None
@@ -265,8 +787,8 @@ async fn process_wynn_event(db: &RwLock<DB>, event: &WynnEvent) -> Option<Vec<Wy
 
 /// Updates the database based on discord event
 async fn process_discord_event(
-    db: &RwLock<DB>, config: &RwLock<Config>, vt: &Mutex<VoiceTracker>, event: &DiscordEvent,
-    ctx: &DiscordContext,
+    db: &RwLock<DB>, config: &RwLock<Config>, vt: &Mutex<VoiceTracker>, limiter: &mut MessageLimiter,
+    event: &DiscordEvent, ctx: &DiscordContext,
 ) {
     match event {
         DiscordEvent::Message { message } => {
@@ -276,28 +798,89 @@ async fn process_discord_event(
                 Channel::Guild(c) => c,
                 _ => return,
             };
-            {
+            let (limits, weight) = {
                 let config = config.read().await;
                 if !config.is_channel_tracked(&ctx.cache, &channel) {
                     return;
                 }
-            }
+                let limits = &config.message_limits;
+                if (limits.ignore_bots && message.author.bot)
+                    || (limits.ignore_webhooks && message.webhook_id.is_some())
+                {
+                    return;
+                }
+                if let Some(member) = ctx.cache.member(channel.guild_id, message.author.id) {
+                    if !config.is_message_tracked(&member) {
+                        return;
+                    }
+                }
+                (limits.clone(), config.message_weight(&ctx.cache, &channel))
+            };
+            let now = message.timestamp.unix_timestamp();
+            let counted = limiter.count_message(
+                &limits,
+                message.author.id.0,
+                &message.content,
+                u64::try_from(now).unwrap_or_default(),
+            );
 
             let id = ok!(DiscordId::try_from(message.author.id.0), return);
             let mid = {
                 let db = db.read().await;
                 ok!(id.mid(&mut db.exe()).await, return)
             };
-            if mid.is_some() {
-                let db = db.write().await;
-                let mut tx = ok!(ctx!(db.begin().await), return);
-                ok!(id.update_message(&mut tx, 1).await, "Failed to update discord message stat", return);
-                let _ = ctx!(tx.commit().await);
+            let db = db.write().await;
+            let mut tx = ok!(ctx!(db.begin().await), return);
+            // Non-members have no profile to carry fractions of a message over with
+            let mut amount = if counted { i64::from(weight) / 100 } else { 0 };
+            if let Some(mid) = mid {
+                if counted {
+                    amount = ok!(
+                        id.update_weighted_message(&mut tx, weight).await,
+                        "Failed to update discord message stat",
+                        return
+                    );
+                    if amount > 0 {
+                        audit_discord(&mut tx, id, AuditedStat::Message, amount, "Message").await;
+                    }
+                }
+                // Messages that aren't counted still show that the member is active
+                let _ = ctx!(crate::message_log::add_message(&mut tx, mid, channel.id.0, now).await);
+            }
+            if amount > 0 {
+                let _ = ctx!(crate::update_daily_message(&mut tx, amount).await);
             }
+            let _ = ctx!(tx.commit().await);
+        }
+        DiscordEvent::ReactionAdd { reaction } | DiscordEvent::ReactionRemove { reaction } => {
+            let amount = if matches!(event, DiscordEvent::ReactionAdd { .. }) { 1 } else { -1 };
+            let user_id = some!(reaction.user_id, return);
+            // Checks if the reaction is in a tracked channel
+            if !ok!(is_channel_id_tracked(ctx, config, reaction.channel_id).await, return) {
+                return;
+            }
+            let message = match ctx.cache.message(reaction.channel_id, reaction.message_id) {
+                Some(message) => message,
+                None => ok!(reaction.message(&ctx.http).await, "Failed to get reacted message", return),
+            };
+            // Reactions to your own message or to a bot's message aren't engagement
+            if message.author.bot || message.author.id == user_id {
+                return;
+            }
+
+            // Users without a discord profile, including bots, are skipped by the updates
+            let given = ok!(DiscordId::try_from(user_id.0), return);
+            let received = ok!(DiscordId::try_from(message.author.id.0), return);
+            let db = db.write().await;
+            let mut tx = ok!(ctx!(db.begin().await), return);
+            let _ = ctx!(given.update_reaction_given(&mut tx, amount).await);
+            let _ = ctx!(received.update_reaction_received(&mut tx, amount).await);
+            let _ = ctx!(tx.commit().await);
         }
         DiscordEvent::VoiceJoin { state } => {
+            let channel_id = some!(state.channel_id, return);
             // Checks if the channel is tracked
-            if !ok!(is_channel_id_tracked(ctx, config, some!(state.channel_id, return)).await, return) {
+            if !ok!(is_channel_id_tracked(ctx, config, channel_id).await, return) {
                 return;
             }
 
@@ -309,10 +892,16 @@ async fn process_discord_event(
                 }
             }
 
-            if !state.mute && !state.deaf {
-                info!(id = state.user_id.0, "Begin tracking for user joined voice chat");
-                let mut vt = vt.lock().await;
-                vt.track_voice(&state.user_id.0);
+            let activity = ok!(voice_activity(ctx, state).await, return);
+            let weight = config.read().await.voice_tracking.weight(&activity);
+            let mut vt = vt.lock().await;
+            if let Some(weight) = weight {
+                info!(id = state.user_id.0, weight, "Begin tracking for user joined voice chat");
+                vt.track_voice(&state.user_id.0, channel_id.0, weight);
+            }
+            if state.self_stream == Some(true) {
+                info!(id = state.user_id.0, "Begin stream tracking for user joined voice chat");
+                vt.track_stream(&state.user_id.0);
             }
         }
         DiscordEvent::VoiceLeave { old_state } => {
@@ -329,14 +918,24 @@ async fn process_discord_event(
                 }
             }
 
-            if !old_state.mute && !old_state.deaf {
-                info!(id = old_state.user_id.0, "Finish tracking for user left voice chat");
-                let dur = {
-                    let mut vt = vt.lock().await;
-                    some!(vt.untrack_voice(&old_state.user_id.0), return)
-                };
+            let stream_dur = {
+                let mut vt = vt.lock().await;
+                vt.untrack_stream(&old_state.user_id.0)
+            };
+            if let Some(dur) = stream_dur {
+                info!(id = old_state.user_id.0, "Finish stream tracking for user left voice chat");
+                track_stream_db(db, old_state.user_id.0, dur).await;
+            }
 
-                track_voice_db(db, old_state.user_id.0, dur).await;
+            // The voice policy may have changed since the user started being tracked, so they are
+            // untracked regardless of their state
+            let tracked = {
+                let mut vt = vt.lock().await;
+                vt.untrack_voice(&old_state.user_id.0)
+            };
+            if let Some((channel, dur)) = tracked {
+                info!(id = old_state.user_id.0, "Finish tracking for user left voice chat");
+                track_voice_db(db, old_state.user_id.0, channel, dur).await;
             }
         }
         DiscordEvent::VoiceChange { old_state, new_state } => {
@@ -363,20 +962,61 @@ async fn process_discord_event(
                 }
             }
 
-            let old_active = !old_state.mute && !old_state.deaf && old_tracked;
-            let new_active = !new_state.mute && !new_state.deaf && new_tracked;
+            let old_streaming = old_state.self_stream == Some(true) && old_tracked;
+            let new_streaming = new_state.self_stream == Some(true) && new_tracked;
 
-            if old_active && !new_active {
-                info!(id = old_state.user_id.0, "Finish tracking for user no longer valid for tracking");
+            if old_streaming && !new_streaming {
+                info!(id = old_state.user_id.0, "Finish stream tracking for user stopped streaming");
                 let dur = {
                     let mut vt = vt.lock().await;
-                    some!(vt.untrack_voice(&new_state.user_id.0), return)
+                    vt.untrack_stream(&new_state.user_id.0)
                 };
-                track_voice_db(db, new_state.user_id.0, dur).await;
-            } else if !old_active && new_active {
-                info!(id = old_state.user_id.0, "Begin tracking for user became valid for tracking");
+                if let Some(dur) = dur {
+                    track_stream_db(db, new_state.user_id.0, dur).await;
+                }
+            } else if !old_streaming && new_streaming {
+                info!(id = old_state.user_id.0, "Begin stream tracking for user started streaming");
                 let mut vt = vt.lock().await;
-                vt.track_voice(&new_state.user_id.0);
+                vt.track_stream(&new_state.user_id.0);
+            }
+
+            let old_activity = ok!(voice_activity(ctx, old_state).await, return);
+            let new_activity = ok!(voice_activity(ctx, new_state).await, return);
+            let (old_weight, new_weight) = {
+                let config = config.read().await;
+                let policy = &config.voice_tracking;
+                (
+                    policy.weight(&old_activity).filter(|_| old_tracked),
+                    policy.weight(&new_activity).filter(|_| new_tracked),
+                )
+            };
+            match (old_weight, new_weight) {
+                (Some(_), None) => {
+                    info!(id = old_state.user_id.0, "Finish tracking for user no longer valid for tracking");
+                    let (channel, dur) = {
+                        let mut vt = vt.lock().await;
+                        some!(vt.untrack_voice(&new_state.user_id.0), return)
+                    };
+                    track_voice_db(db, new_state.user_id.0, channel, dur).await;
+                }
+                (None, Some(weight)) => {
+                    info!(id = old_state.user_id.0, "Begin tracking for user became valid for tracking");
+                    let mut vt = vt.lock().await;
+                    vt.track_voice(&new_state.user_id.0, some!(new_state.channel_id, return).0, weight);
+                }
+                (Some(old_weight), Some(weight))
+                    if old_state.channel_id != new_state.channel_id || old_weight != weight =>
+                {
+                    // Flush the duration spent in the old channel or with the old weight before
+                    // tracking the new one
+                    let (channel, dur) = {
+                        let mut vt = vt.lock().await;
+                        let channel = some!(new_state.channel_id, return).0;
+                        some!(vt.track_voice(&new_state.user_id.0, channel, weight), return)
+                    };
+                    track_voice_db(db, new_state.user_id.0, channel, dur).await;
+                }
+                _ => {}
             }
         }
         DiscordEvent::MemberLeave { user, guild_id, .. } => {
@@ -389,8 +1029,17 @@ async fn process_discord_event(
 
                 if let Some(mid) = mid {
                     info!(?mid, discord = user.id.0, "User left discord guild, unbinding discord profile");
+                    let reason = discord_exit_reason(ctx, user.id).await;
+                    let now = ok!(
+                        SystemTime::now().duration_since(UNIX_EPOCH),
+                        "Failed to get current unix timestamp",
+                        return
+                    );
+                    let now = ok!(i64::try_from(now.as_secs()), "Failed to convert u64 to i64", return);
                     let db = db.write().await;
                     let mut tx = ok!(ctx!(db.begin().await), return);
+                    let _ =
+                        ctx!(member_exit::record_exit(&mut tx, mid, ExitSource::Discord, reason, now).await);
                     ok!(mid.bind_discord(&mut tx, None).await, "Failed to unbind discord profile", return);
                     let _ = ctx!(tx.commit().await);
                 }
@@ -400,6 +1049,33 @@ async fn process_discord_event(
     }
 }
 
+/// Find out why a user left the main discord guild from the audit log, users are assumed to have
+/// left by themselves if it can't be read.
+async fn discord_exit_reason(ctx: &DiscordContext, user_id: UserId) -> ExitReason {
+    let logs = ok!(
+        ctx.main_guild.id.audit_logs(&ctx.http, None, None, None, Some(AUDIT_LOG_LIMIT)).await,
+        "Failed to get audit logs",
+        return ExitReason::Left
+    );
+    let now = Timestamp::now().unix_timestamp();
+    for entry in logs.entries {
+        if now - entry.id.created_at().unix_timestamp() > AUDIT_LOG_MAX_AGE {
+            continue;
+        }
+        match entry.action {
+            Action::Member(MemberAction::Kick | MemberAction::BanAdd)
+                if entry.target_id == Some(user_id.0) =>
+            {
+                return ExitReason::Kicked
+            }
+            // Prunes don't have a target
+            Action::Member(MemberAction::Prune) => return ExitReason::Purged,
+            _ => {}
+        }
+    }
+    ExitReason::Left
+}
+
 #[allow(clippy::single_match)]
 async fn process_db_event(db: &RwLock<DB>, cache: &WynnCache, event: &DBEvent) {
     match event {
@@ -415,19 +1091,111 @@ async fn process_db_event(db: &RwLock<DB>, cache: &WynnCache, event: &DBEvent) {
     }
 }
 
+/// Record the players that are online according to a batch of wynn events
+async fn update_daily_online(db: &RwLock<DB>, events: &[WynnEvent]) {
+    let mut igns = Vec::new();
+    for event in events {
+        if let WynnEvent::PlayerJoin { ign, .. } | WynnEvent::PlayerStay { ign, .. } = event {
+            if !igns.contains(&ign.as_str()) {
+                igns.push(ign.as_str());
+            }
+        }
+    }
+    // The batch isn't from the online player list
+    if igns.is_empty() {
+        return;
+    }
+
+    let db = db.write().await;
+    let mut tx = ok!(ctx!(db.begin().await), return);
+    ok!(crate::update_daily_online(&mut tx, &igns).await, "Failed to update daily online stat", return);
+    let _ = ctx!(tx.commit().await);
+}
+
+/// Check if discord user `id` is in discord guild `guild_id`, users are assumed to be in it if the
+/// guild isn't cached.
+fn in_guild(cache: &Cache, guild_id: GuildId, id: DiscordId) -> bool {
+    let user_id = some!(u64::try_from(id.0).ok(), return true);
+    cache.guild_field(guild_id, |guild| guild.members.contains_key(&UserId(user_id))).unwrap_or(true)
+}
+
+/// Update a guild member's joined date and war count
+async fn update_guild_info(tx: &mut Transaction, mcid: &McId, joined: &str, wars: &Option<i64>) {
+    let _ = ctx!(mcid.set_joined(tx, joined).await, "Failed to update joined date");
+    if let Some(wars) = wars {
+        let _ = ctx!(mcid.set_wars(tx, *wars).await, "Failed to update war count");
+    }
+}
+
 /// Update a discord user's voice tracking in database
-async fn track_voice_db(db: &RwLock<DB>, user_id: u64, dur: Duration) {
+async fn track_voice_db(db: &RwLock<DB>, user_id: u64, channel_id: u64, dur: Duration) {
     let dur = ok!(i64::try_from(dur.as_secs()), "Failed to convert u64 to i64 (duration)", return);
     let discord_id = ok!(DiscordId::try_from(user_id), "Failed to convert u64 to i64 (id)", return);
+    let channel_id = ok!(i64::try_from(channel_id), "Failed to convert u64 to i64 (channel id)", return);
 
     let db = db.write().await;
     let mut tx = ok!(ctx!(db.begin().await), return);
     if let Err(why) = discord_id.update_voice(&mut tx, dur).await {
         error!("Failed to update voice chat activity stat: {:#}", why);
     }
+    audit_discord(&mut tx, discord_id, AuditedStat::Voice, dur, "VoiceTracker").await;
+    if let Err(why) = discord_id.update_channel_voice(&mut tx, channel_id, dur).await {
+        error!("Failed to update voice channel activity stat: {:#}", why);
+    }
     let _ = ctx!(tx.commit().await);
 }
 
+/// Update a discord user's streaming tracking in database
+async fn track_stream_db(db: &RwLock<DB>, user_id: u64, dur: Duration) {
+    let dur = ok!(i64::try_from(dur.as_secs()), "Failed to convert u64 to i64 (duration)", return);
+    let discord_id = ok!(DiscordId::try_from(user_id), "Failed to convert u64 to i64 (id)", return);
+
+    let db = db.write().await;
+    let mut tx = ok!(ctx!(db.begin().await), return);
+    if let Err(why) = discord_id.update_stream(&mut tx, dur).await {
+        error!("Failed to update streaming activity stat: {:#}", why);
+    }
+    audit_discord(&mut tx, discord_id, AuditedStat::Stream, dur, "VoiceTracker").await;
+    let _ = ctx!(tx.commit().await);
+}
+
+/// Record a stat increment of a discord profile if stat increments are recorded, see
+/// [`crate::stat_increment`]
+async fn audit_discord(tx: &mut Transaction, id: DiscordId, stat: AuditedStat, amount: i64, source: &str) {
+    let now = ok!(SystemTime::now().duration_since(UNIX_EPOCH), "Failed to get current unix timestamp", return);
+    let now = ok!(i64::try_from(now.as_secs()), "Failed to convert u64 to i64", return);
+    let _ = ctx!(crate::stat_increment::record_discord(tx, id, stat, amount, source, now).await);
+}
+
+/// Record a stat increment of a mc account if stat increments are recorded, see
+/// [`crate::stat_increment`]
+async fn audit_wynn(tx: &mut Transaction, mcid: &McId, stat: AuditedStat, amount: i64, source: &str) {
+    let now = ok!(SystemTime::now().duration_since(UNIX_EPOCH), "Failed to get current unix timestamp", return);
+    let now = ok!(i64::try_from(now.as_secs()), "Failed to convert u64 to i64", return);
+    let _ = ctx!(crate::stat_increment::record_wynn(tx, mcid, stat, amount, source, now).await);
+}
+
+/// Get what a user is doing in the voice channel they are in
+async fn voice_activity(cache_http: &impl CacheHttp, state: &VoiceState) -> Result<VoiceActivity> {
+    let mut stage = None;
+    if let Some(channel_id) = state.channel_id {
+        let channel = channel_id.to_channel(&cache_http).await.context("Failed to get channel data")?;
+        if let Channel::Guild(channel) = channel {
+            if channel.kind == ChannelType::Stage {
+                // Only speakers aren't suppressed in stage channels
+                stage = Some(if state.suppress { StageRole::Audience } else { StageRole::Speaker });
+            }
+        }
+    }
+    Ok(VoiceActivity {
+        mute: state.mute,
+        deaf: state.deaf,
+        video: state.self_video,
+        stream: state.self_stream == Some(true),
+        stage,
+    })
+}
+
 /// Checks if a channel is valid for tracking
 async fn is_channel_id_tracked(
     cache_http: &impl CacheHttp, config: &RwLock<Config>, channel_id: ChannelId,
@@ -0,0 +1,80 @@
+//! Provides [`MessageLimiter`], which applies [`MessageLimits`] to the messages of users.
+use std::collections::HashMap;
+
+use config::message::MessageLimits;
+
+#[derive(Debug, Default)]
+/// Message counting state of a user
+struct UserState {
+    /// Content of the user's previous message
+    last_content: String,
+    /// Minute `counted` is for, in minutes since unix epoch
+    minute: u64,
+    /// Amount of messages counted in `minute`
+    counted: u32,
+}
+
+#[derive(Debug, Default)]
+/// Decides which messages of the users are counted into message stats, according to
+/// [`MessageLimits`].
+/// ```
+/// # use config::message::MessageLimits;
+/// # use memberdb::message_limiter::MessageLimiter;
+/// let limits = MessageLimits {
+///     min_length: Some(3),
+///     max_per_minute: Some(2),
+///     ignore_repeats: true,
+///     ..Default::default()
+/// };
+/// let mut limiter = MessageLimiter::new();
+/// assert!(!limiter.count_message(&limits, 1, " k ", 60));
+/// assert!(limiter.count_message(&limits, 1, "hello", 60));
+/// // Repeating the previous message isn't counted
+/// assert!(!limiter.count_message(&limits, 1, "hello", 61));
+/// assert!(limiter.count_message(&limits, 1, "hello there", 62));
+/// // Only 2 messages are counted per minute, for each user
+/// assert!(!limiter.count_message(&limits, 1, "anyone up for wars", 63));
+/// assert!(limiter.count_message(&limits, 2, "anyone up for wars", 63));
+/// assert!(limiter.count_message(&limits, 1, "wars in 5", 120));
+/// ```
+pub struct MessageLimiter {
+    users: HashMap<u64, UserState>,
+}
+
+impl MessageLimiter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Check if a message sent by `user` with `content` is counted.
+    /// `now` is the current unix timestamp.
+    pub fn count_message(&mut self, limits: &MessageLimits, user: u64, content: &str, now: u64) -> bool {
+        let content = content.trim();
+        if let Some(min_length) = limits.min_length {
+            if content.chars().count() < min_length {
+                return false;
+            }
+        }
+
+        let state = self.users.entry(user).or_default();
+        if limits.ignore_repeats {
+            if state.last_content == content {
+                return false;
+            }
+            state.last_content = content.to_string();
+        }
+
+        if let Some(max) = limits.max_per_minute {
+            let minute = now / 60;
+            if state.minute != minute {
+                state.minute = minute;
+                state.counted = 0;
+            }
+            if state.counted >= max {
+                return false;
+            }
+            state.counted += 1;
+        }
+        true
+    }
+}
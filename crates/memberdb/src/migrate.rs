@@ -0,0 +1,277 @@
+//! Importing of members and their stats from the predecessor bot's JSON export.
+//!
+//! The export is a list of members in the form of:
+//! ```json
+//! [
+//!     {"discord": 658478931682394134, "ign": "Pucaet", "rank": "Cosmonaut", "xp": 1000,
+//!      "message": 20, "voice": 3600, "online": 7200}
+//! ]
+//! ```
+//! Either `discord` or `ign` is required, and stats that are missing defaults to 0.
+//! `rank` can either be a member rank, or a guild rank which is converted to its corresponding
+//! member rank.
+//! `mcid` can be given alongside `ign`, in which case the ign doesn't need to be resolved.
+//!
+//! Importing is done in two steps, first [`plan_import`] checks which members can be imported,
+//! then [`import`] adds them into the database.
+//! [`export`] writes the members of a database in the same format, so they can be imported into
+//! another one.
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use sqlx::query;
+use tracing::info;
+
+use crate::model::discord::DiscordId;
+use crate::model::guild::GuildRank;
+use crate::model::member::{MemberId, MemberRank};
+use crate::model::wynn::McId;
+use crate::DB;
+
+#[derive(Debug, Deserialize, Serialize)]
+/// Member entry of the legacy export
+pub struct LegacyMember {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub discord: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ign: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mcid: Option<String>,
+    pub rank: String,
+    #[serde(default)]
+    pub xp: i64,
+    #[serde(default)]
+    pub message: i64,
+    #[serde(default)]
+    pub voice: i64,
+    #[serde(default)]
+    pub online: i64,
+}
+
+impl LegacyMember {
+    /// Name used to refer to the entry in reports
+    pub fn describe(&self) -> String {
+        match (&self.ign, self.discord) {
+            (Some(ign), _) => ign.clone(),
+            (None, Some(id)) => format!("<@{}>", id),
+            (None, None) => "unnamed entry".to_string(),
+        }
+    }
+}
+
+/// Parse the legacy export
+pub fn parse_export(s: &str) -> Result<Vec<LegacyMember>> {
+    serde_json::from_str(s).context("Failed to parse legacy export")
+}
+
+/// Map a legacy rank to member rank
+pub fn map_rank(rank: &str) -> Option<MemberRank> {
+    if let Ok(rank) = MemberRank::from_str(rank) {
+        return Some(rank);
+    }
+    if let Ok(rank) = GuildRank::from_str(rank).or_else(|_| GuildRank::from_api(rank)) {
+        return Some(rank.to_member_rank());
+    }
+    None
+}
+
+#[derive(Debug)]
+/// A member that is going to be imported
+pub struct PlannedMember {
+    pub name: String,
+    pub discord: Option<DiscordId>,
+    pub mc: Option<(McId, String)>,
+    pub rank: MemberRank,
+    pub xp: i64,
+    pub message: i64,
+    pub voice: i64,
+    pub online: i64,
+}
+
+#[derive(Debug, Default)]
+/// Result of checking a legacy export against the database
+pub struct ImportPlan {
+    /// Members that can be imported
+    pub members: Vec<PlannedMember>,
+    /// Entries that can't be imported, in the form of `(entry name, reason)`
+    pub skipped: Vec<(String, String)>,
+}
+
+/// Check which members in the legacy export can be imported.
+///
+/// `mcids` is a map from lowercased ign to mcid, used to resolve the igns in the export that
+/// don't have a mcid.
+pub async fn plan_import(
+    db: &DB, members: Vec<LegacyMember>, mcids: &HashMap<String, String>,
+) -> Result<ImportPlan> {
+    let mut plan = ImportPlan::default();
+    let mut seen_discord = HashSet::new();
+    let mut seen_mc = HashSet::new();
+
+    for member in members {
+        let name = member.describe();
+        let rank = match map_rank(&member.rank) {
+            Some(rank) => rank,
+            None => {
+                plan.skipped.push((name, format!("Unknown rank `{}`", member.rank)));
+                continue;
+            }
+        };
+
+        let discord = match member.discord {
+            Some(id) => {
+                let id = DiscordId::try_from(id)?;
+                if !seen_discord.insert(id.0) {
+                    plan.skipped.push((name, "Duplicated discord id".to_string()));
+                    continue;
+                }
+                if id.mid(&mut db.exe()).await?.is_some() {
+                    plan.skipped.push((name, "Discord user is already a member".to_string()));
+                    continue;
+                }
+                Some(id)
+            }
+            None => None,
+        };
+
+        let mc = match &member.ign {
+            Some(ign) => {
+                let mcid = match member.mcid.as_ref().or_else(|| mcids.get(&ign.to_lowercase())) {
+                    Some(mcid) => McId(mcid.clone()),
+                    None => {
+                        plan.skipped.push((name, "Unable to resolve ign".to_string()));
+                        continue;
+                    }
+                };
+                if !seen_mc.insert(mcid.0.clone()) {
+                    plan.skipped.push((name, "Duplicated mc account".to_string()));
+                    continue;
+                }
+                if mcid.mid(&mut db.exe()).await?.is_some() {
+                    plan.skipped.push((name, "Mc account is already a member".to_string()));
+                    continue;
+                }
+                Some((mcid, ign.clone()))
+            }
+            None => None,
+        };
+
+        if discord.is_none() && mc.is_none() {
+            plan.skipped.push((name, "No discord id or ign".to_string()));
+            continue;
+        }
+
+        plan.members.push(PlannedMember {
+            name,
+            discord,
+            mc,
+            rank,
+            xp: member.xp,
+            message: member.message,
+            voice: member.voice,
+            online: member.online,
+        });
+    }
+
+    Ok(plan)
+}
+
+/// Add the planned members into the database along with their stats, and return the amount of
+/// members added.
+///
+/// Weekly stats aren't affected. Xp is imported into the player's guild profile, which is created
+/// for players outside of the guild, and the larger value between the current and legacy xp is
+/// kept.
+///
+/// # Preconditions
+/// The database hasn't been changed since the plan is made.
+pub async fn import(db: &DB, plan: &ImportPlan) -> Result<usize> {
+    let mut tx = db.begin().await?;
+    for member in &plan.members {
+        info!(member.name, "Importing legacy member");
+        match (&member.discord, &member.mc) {
+            (Some(id), Some((mcid, ign))) => {
+                MemberId::add_member(&mut tx, *id, mcid, ign, member.rank).await?;
+            }
+            (Some(id), None) => {
+                MemberId::add_discord_partial(&mut tx, *id, member.rank).await?;
+            }
+            (None, Some((mcid, ign))) => {
+                MemberId::add_wynn_partial(&mut tx, mcid, member.rank, ign).await?;
+            }
+            (None, None) => continue,
+        }
+
+        if let Some(id) = &member.discord {
+            query!(
+                "UPDATE discord SET message=message+?,voice=voice+? WHERE id=?",
+                member.message,
+                member.voice,
+                id
+            )
+            .execute(&mut tx.tx)
+            .await
+            .context("Failed to import discord stats")?;
+        }
+        if let Some((mcid, _)) = &member.mc {
+            query!("UPDATE wynn SET activity=activity+? WHERE id=?", member.online, mcid)
+                .execute(&mut tx.tx)
+                .await
+                .context("Failed to import wynn stats")?;
+            if member.xp > 0 {
+                // Players outside of the guild keep their xp in a guild profile with `wynn.guild`
+                // unset, like the members that left the guild
+                query!(
+                    "INSERT INTO guild (id,rank,xp) VALUES (?,?,?) \
+                    ON CONFLICT(id) DO UPDATE SET xp=MAX(xp,excluded.xp)",
+                    mcid,
+                    GuildRank::Recruit,
+                    member.xp
+                )
+                .execute(&mut tx.tx)
+                .await
+                .context("Failed to import guild stats")?;
+            }
+        }
+    }
+    tx.commit().await?;
+
+    Ok(plan.members.len())
+}
+
+/// Get all members of the database in the legacy export format, ordered by member id.
+///
+/// Weekly stats aren't exported, and `xp` is exported for all members with a guild profile,
+/// including the ones that left the guild.
+pub async fn export(db: &DB) -> Result<Vec<LegacyMember>> {
+    let rows = query!(
+        "SELECT member.discord,member.mcid,member.rank,wynn.ign AS \"ign?\",
+            discord.message AS \"message?\",discord.voice AS \"voice?\",
+            wynn.activity AS \"online?\",guild.xp AS \"xp?\"
+        FROM member
+            LEFT JOIN discord ON discord.id=member.discord
+            LEFT JOIN wynn ON wynn.id=member.mcid
+            LEFT JOIN guild ON guild.id=member.mcid
+        ORDER BY member.oid"
+    )
+    .fetch_all(&db.pool)
+    .await
+    .context("Failed to fetch members to export")?;
+
+    let mut members = Vec::with_capacity(rows.len());
+    for row in rows {
+        members.push(LegacyMember {
+            discord: row.discord.map(u64::try_from).transpose()?,
+            ign: row.ign,
+            mcid: row.mcid,
+            rank: MemberRank::decode(&row.rank)?.to_string(),
+            xp: row.xp.unwrap_or_default(),
+            message: row.message.unwrap_or_default(),
+            voice: row.voice.unwrap_or_default(),
+            online: row.online.unwrap_or_default(),
+        });
+    }
+    Ok(members)
+}
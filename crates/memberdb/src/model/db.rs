@@ -1,6 +1,7 @@
 use std::str::FromStr;
 
 use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
 
 use util::{ioerr, ok, ok_some};
 
@@ -8,10 +9,9 @@ use crate::model::discord::{DiscordId, DiscordProfile};
 use crate::model::guild::GuildProfile;
 use crate::model::member::{Member, MemberId};
 use crate::model::wynn::{McId, WynnProfile};
-use crate::utils;
 use crate::DB;
 
-#[derive(Debug)]
+#[derive(Serialize, Deserialize, Debug)]
 /// Types of profiles
 pub enum ProfileType {
     Discord,
@@ -32,7 +32,7 @@ impl FromStr for ProfileType {
     }
 }
 
-#[derive(Debug)]
+#[derive(Serialize, Deserialize, Debug)]
 /// Database profiles
 pub struct Profiles {
     pub member: Option<Member>,
@@ -55,7 +55,7 @@ impl Profiles {
                     Some(id) => ok!(id.get(&mut db.exe()).await, None),
                     None => None,
                 };
-                let (wynn, guild) = utils::get_wynn_guild_profiles(db, &member.mcid).await;
+                let (wynn, guild) = get_wynn_guild_profiles(db, &member.mcid).await;
                 Self {
                     member: Some(member),
                     guild,
@@ -79,7 +79,7 @@ impl Profiles {
                 // Checks if the discord is linked with a member
                 if let Some(mid) = discord.mid {
                     if let Ok(Some(member)) = mid.get(&mut db.exe()).await {
-                        let (wynn, guild) = utils::get_wynn_guild_profiles(db, &member.mcid).await;
+                        let (wynn, guild) = get_wynn_guild_profiles(db, &member.mcid).await;
                         return Self {
                             member: Some(member),
                             guild,
@@ -106,7 +106,7 @@ impl Profiles {
 
     /// Get profiles related to the mcid
     pub async fn from_mc(db: &DB, mcid: &McId) -> Self {
-        let (wynn, guild) = utils::get_wynn_guild_profiles(db, &Some(mcid.clone())).await;
+        let (wynn, guild) = get_wynn_guild_profiles(db, &Some(mcid.clone())).await;
         let (member, discord) = match wynn {
             Some(WynnProfile { mid: Some(mid), .. }) => match mid.get(&mut db.exe()).await {
                 Ok(Some(member)) => match member.discord {
@@ -124,7 +124,7 @@ impl Profiles {
     }
 }
 
-#[derive(Debug)]
+#[derive(Serialize, Deserialize, Debug)]
 /// All ids that are related to the database
 pub struct Ids {
     pub member: Option<MemberId>,
@@ -174,7 +174,7 @@ impl Ids {
     }
 }
 
-#[derive(Debug, Eq, PartialEq, Clone)]
+#[derive(Serialize, Deserialize, Debug, Eq, PartialEq, Clone)]
 /// Represent database columns that can be selected.
 pub enum Column {
     // Discord
@@ -182,6 +182,12 @@ pub enum Column {
     DWeeklyMessage,
     DVoice,
     DWeeklyVoice,
+    DStream,
+    DWeeklyStream,
+    DReactionGiven,
+    DReactionReceived,
+    /// Time of the last message in the message log, which is keyed by member
+    DLastMessage,
     // Wynn
     WGuild,
     WIgn,
@@ -192,6 +198,10 @@ pub enum Column {
     GRank,
     GXp,
     GWeeklyXp,
+    GJoined,
+    GWars,
+    /// Time since the last guild rank change, or since joining the guild if the rank never changed
+    GRankTime,
     // Member
     MId,
     MMcid,
@@ -204,8 +214,10 @@ impl Column {
     /// Return which profile table a column belongs to, None if it is part of the member table
     pub fn profile(&self) -> Option<ProfileType> {
         match self {
-            Self::MId | Self::MRank | Self::MType | Self::MMcid | Self::MDiscord => None,
-            Self::GRank | Self::GXp | Self::GWeeklyXp => Some(ProfileType::Guild),
+            Self::MId | Self::MRank | Self::MType | Self::MMcid | Self::MDiscord | Self::DLastMessage => None,
+            Self::GRank | Self::GXp | Self::GWeeklyXp | Self::GJoined | Self::GWars | Self::GRankTime => {
+                Some(ProfileType::Guild)
+            }
             Self::WGuild | Self::WIgn | Self::WOnline | Self::WWeeklyOnline | Self::WAvgOnline => {
                 Some(ProfileType::Wynn)
             }
@@ -213,6 +225,21 @@ impl Column {
         }
     }
 
+    /// Whether the column's value is a duration of time in seconds
+    pub fn is_duration(&self) -> bool {
+        matches!(
+            self,
+            Self::DVoice
+                | Self::DWeeklyVoice
+                | Self::DStream
+                | Self::DWeeklyStream
+                | Self::WOnline
+                | Self::WWeeklyOnline
+                | Self::WAvgOnline
+                | Self::GRankTime
+        )
+    }
+
     /// Get the column name within the database
     pub fn name(&self) -> &str {
         match self {
@@ -223,6 +250,11 @@ impl Column {
             Self::DWeeklyMessage => "message_week",
             Self::DVoice => "voice",
             Self::DWeeklyVoice => "voice_week",
+            Self::DStream => "stream",
+            Self::DWeeklyStream => "stream_week",
+            Self::DReactionGiven => "reaction_given",
+            Self::DReactionReceived => "reaction_received",
+            Self::DLastMessage => "last_message",
             Self::WGuild => "guild",
             Self::WIgn => "ign",
             Self::WOnline => "activity",
@@ -231,6 +263,9 @@ impl Column {
             Self::GRank | Self::MRank => "rank",
             Self::GXp => "xp",
             Self::GWeeklyXp => "xp_week",
+            Self::GJoined => "joined",
+            Self::GWars => "wars",
+            Self::GRankTime => "rank_time",
             Self::MType => "type",
         }
     }
@@ -246,6 +281,11 @@ impl FromStr for Column {
             "weekly_message" => Self::DWeeklyMessage,
             "voice" => Self::DVoice,
             "weekly_voice" => Self::DWeeklyVoice,
+            "stream" => Self::DStream,
+            "weekly_stream" => Self::DWeeklyStream,
+            "reactions_given" => Self::DReactionGiven,
+            "reactions_received" => Self::DReactionReceived,
+            "last_message" => Self::DLastMessage,
             "mc_id" => Self::MMcid,
             "in_guild" => Self::WGuild,
             "ign" => Self::WIgn,
@@ -255,6 +295,9 @@ impl FromStr for Column {
             "guild_rank" => Self::GRank,
             "xp" => Self::GXp,
             "weekly_xp" => Self::GWeeklyXp,
+            "guild_joined" => Self::GJoined,
+            "wars" => Self::GWars,
+            "rank_time" => Self::GRankTime,
             "id" => Self::MId,
             "rank" => Self::MRank,
             "type" => Self::MType,
@@ -263,13 +306,17 @@ impl FromStr for Column {
     }
 }
 
-#[derive(Debug, Eq, PartialEq, Clone)]
+#[derive(Serialize, Deserialize, Debug, Eq, PartialEq, Clone)]
 /// All tracked stat columns
 pub enum Stat {
     Message,
     WeeklyMessage,
     Voice,
     WeeklyVoice,
+    Stream,
+    WeeklyStream,
+    ReactionGiven,
+    ReactionReceived,
     Online,
     WeeklyOnline,
     AvgOnline,
@@ -285,6 +332,10 @@ impl Stat {
             Column::DWeeklyMessage => Self::WeeklyMessage,
             Column::DVoice => Self::Voice,
             Column::DWeeklyVoice => Self::WeeklyVoice,
+            Column::DStream => Self::Stream,
+            Column::DWeeklyStream => Self::WeeklyStream,
+            Column::DReactionGiven => Self::ReactionGiven,
+            Column::DReactionReceived => Self::ReactionReceived,
             Column::WOnline => Self::Online,
             Column::WWeeklyOnline => Self::WeeklyOnline,
             Column::WAvgOnline => Self::AvgOnline,
@@ -301,6 +352,10 @@ impl Stat {
             Self::WeeklyMessage => Column::DWeeklyMessage,
             Self::Voice => Column::DVoice,
             Self::WeeklyVoice => Column::DWeeklyVoice,
+            Self::Stream => Column::DStream,
+            Self::WeeklyStream => Column::DWeeklyStream,
+            Self::ReactionGiven => Column::DReactionGiven,
+            Self::ReactionReceived => Column::DReactionReceived,
             Self::Online => Column::WOnline,
             Self::WeeklyOnline => Column::WWeeklyOnline,
             Self::AvgOnline => Column::WAvgOnline,
@@ -313,9 +368,13 @@ impl Stat {
     pub fn parse_val(&self, val: &str) -> Result<u64> {
         match self {
             // parse as time duration
-            Self::Voice | Self::WeeklyVoice | Self::Online | Self::WeeklyOnline | Self::AvgOnline => {
-                util::string::parse_second(val)
-            }
+            Self::Voice
+            | Self::WeeklyVoice
+            | Self::Stream
+            | Self::WeeklyStream
+            | Self::Online
+            | Self::WeeklyOnline
+            | Self::AvgOnline => util::string::parse_second(val),
             // parse as number
             _ => match u64::try_from(util::string::parse_num(val)?) {
                 Ok(n) => Ok(n),
@@ -337,3 +396,17 @@ impl FromStr for Stat {
         ioerr!("Failed to parse '{}' as Stat", s)
     }
 }
+
+/// Get wynn and guild profile with specified mcid
+async fn get_wynn_guild_profiles(
+    db: &DB, mcid: &Option<McId>,
+) -> (Option<WynnProfile>, Option<GuildProfile>) {
+    match mcid {
+        Some(id) => {
+            let wynn = ok!(id.get_wynn(&mut db.exe()).await, None);
+            let guild = ok!(id.get_guild(&mut db.exe()).await, None);
+            (wynn, guild)
+        }
+        None => (None, None),
+    }
+}
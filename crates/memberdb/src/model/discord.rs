@@ -2,20 +2,27 @@
 use std::fmt;
 use std::io;
 
+use std::sync::Arc;
+
 use anyhow::Result;
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "discord")]
 use serenity::client::Cache;
+#[cfg(feature = "discord")]
 use serenity::model::id::UserId;
+#[cfg(feature = "discord")]
 use serenity::model::user::User;
 
 use util::ioerr;
 
 use crate::model::member::MemberId;
 
-#[derive(sqlx::Type, Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(sqlx::Type, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
 #[sqlx(transparent)]
 pub struct DiscordId(pub i64);
 
 impl DiscordId {
+    #[cfg(feature = "discord")]
     pub fn to_user(&self, cache: &Cache) -> Option<User> {
         if let Ok(id) = u64::try_from(self.0).map(UserId) {
             return cache.user(id);
@@ -24,6 +31,42 @@ impl DiscordId {
     }
 }
 
+/// Resolves the names of discord users when formatting values
+pub trait UserNames: Sync {
+    /// Get the name of a discord user, `None` if it is unknown
+    fn user_name(&self, id: DiscordId) -> Option<String>;
+}
+
+/// Users are named by their username if they are in the cache, see [`util::discord::user_tag`]
+#[cfg(feature = "discord")]
+impl UserNames for Cache {
+    fn user_name(&self, id: DiscordId) -> Option<String> {
+        id.to_user(self).map(|u| util::discord::user_tag(&u.name, u.discriminator))
+    }
+}
+
+impl<T: UserNames + Send> UserNames for Arc<T> {
+    fn user_name(&self, id: DiscordId) -> Option<String> {
+        self.as_ref().user_name(id)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+/// [`UserNames`] that doesn't need discord, users are named by their ids.
+///
+/// ```
+/// use memberdb::model::discord::{DiscordId, UserIds, UserNames};
+///
+/// assert_eq!(UserIds.user_name(DiscordId(1234)), Some("1234".to_string()));
+/// ```
+pub struct UserIds;
+
+impl UserNames for UserIds {
+    fn user_name(&self, id: DiscordId) -> Option<String> {
+        Some(id.to_string())
+    }
+}
+
 impl fmt::Display for DiscordId {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}", self.0)
@@ -47,13 +90,18 @@ pub struct DiscordProfileRow {
     pub message: i64,
     pub message_week: i64,
     pub image: i64,
-    pub reaction: i64,
+    pub reaction_given: i64,
     pub voice: i64,
     pub voice_week: i64,
     pub activity: i64,
+    pub stream: i64,
+    pub stream_week: i64,
+    /// Hundredths of a message carried over from weighted messages
+    pub message_frac: i64,
+    pub reaction_received: i64,
 }
 
-#[derive(Debug)]
+#[derive(Serialize, Deserialize, Debug)]
 /// Discord table model
 pub struct DiscordProfile {
     pub id: DiscordId,
@@ -61,10 +109,13 @@ pub struct DiscordProfile {
     pub message: i64,
     pub message_week: i64,
     pub image: i64,
-    pub reaction: i64,
+    pub reaction_given: i64,
+    pub reaction_received: i64,
     pub voice: i64,
     pub voice_week: i64,
     pub activity: i64,
+    pub stream: i64,
+    pub stream_week: i64,
 }
 
 impl DiscordProfile {
@@ -76,10 +127,13 @@ impl DiscordProfile {
             message: row.message,
             message_week: row.message_week,
             image: row.image,
-            reaction: row.reaction,
+            reaction_given: row.reaction_given,
+            reaction_received: row.reaction_received,
             voice: row.voice,
             voice_week: row.voice_week,
             activity: row.activity,
+            stream: row.stream,
+            stream_week: row.stream_week,
         })
     }
 }
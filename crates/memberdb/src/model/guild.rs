@@ -2,6 +2,7 @@
 use std::{fmt, str::FromStr};
 
 use anyhow::Result;
+use serde::{Deserialize, Serialize};
 
 use util::{impl_sqlx_type, ioerr};
 
@@ -18,7 +19,7 @@ pub const GUILD_RANKS: [GuildRank; 6] = [
     GuildRank::Recruit,
 ];
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 /// In-game guild ranks
 pub enum GuildRank {
     Recruit,
@@ -101,9 +102,11 @@ pub struct GuildProfileRow {
     pub rank: String,
     pub xp: i64,
     pub xp_week: i64,
+    pub joined: Option<String>,
+    pub wars: i64,
 }
 
-#[derive(Debug)]
+#[derive(Serialize, Deserialize, Debug)]
 /// Guild table model.
 /// This can't be used to query entire guil profile from database, instead query one using
 /// `GuildProfileRow`, and then convert it to `GuildProfile`.
@@ -113,6 +116,9 @@ pub struct GuildProfile {
     pub rank: GuildRank,
     pub xp: i64,
     pub xp_week: i64,
+    /// The datetime of when they joined the guild, as appeared in the wynncraft api
+    pub joined: Option<String>,
+    pub wars: i64,
 }
 
 impl GuildProfile {
@@ -127,6 +133,8 @@ impl GuildProfile {
             rank,
             xp: row.xp,
             xp_week: row.xp_week,
+            joined: row.joined,
+            wars: row.wars,
         })
     }
 }
@@ -3,6 +3,8 @@ use std::fmt;
 use std::str::FromStr;
 
 use anyhow::Result;
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "discord")]
 use serenity::model::guild::{Guild, Role};
 
 use util::{impl_sqlx_type, ioerr};
@@ -10,7 +12,7 @@ use util::{impl_sqlx_type, ioerr};
 use crate::model::discord::DiscordId;
 use crate::model::wynn::McId;
 
-#[derive(sqlx::Type, Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(sqlx::Type, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
 #[sqlx(transparent)]
 pub struct MemberId(pub i64);
 
@@ -20,11 +22,15 @@ impl fmt::Display for MemberId {
     }
 }
 
-#[derive(sqlx::Type, Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(sqlx::Type, Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
 /// Member ranks.
 /// The lower the number the higher the rank.
 /// They are named this way so that when rank names are changed, no refactoring is needed.
+///
+/// [`MemberRank::Alumni`] is given to members that left the guild, it is below all the other ranks
+/// and isn't part of the promotion ladder.
 pub enum MemberRank {
+    Alumni,
     Six,
     Five,
     Four,
@@ -34,7 +40,8 @@ pub enum MemberRank {
     Zero,
 }
 
-pub const MEMBER_RANKS: [MemberRank; 7] = [
+/// All member ranks, from the highest to the lowest
+pub const MEMBER_RANKS: [MemberRank; 8] = [
     MemberRank::Zero,
     MemberRank::One,
     MemberRank::Two,
@@ -42,15 +49,17 @@ pub const MEMBER_RANKS: [MemberRank; 7] = [
     MemberRank::Four,
     MemberRank::Five,
     MemberRank::Six,
+    MemberRank::Alumni,
 ];
 /// Ranks which the bot should have permission over.
 /// This list is determined based on the position of the bot's role within the discord role list.
-pub const MANAGED_MEMBER_RANKS: [MemberRank; 5] = [
+pub const MANAGED_MEMBER_RANKS: [MemberRank; 6] = [
     MemberRank::Two,
     MemberRank::Three,
     MemberRank::Four,
     MemberRank::Five,
     MemberRank::Six,
+    MemberRank::Alumni,
 ];
 pub const INIT_MEMBER_RANK: MemberRank = MemberRank::Six;
 pub const MRANK_ZERO_STR: &str = "Founder";
@@ -60,22 +69,23 @@ pub const MRANK_THREE_STR: &str = "Architect";
 pub const MRANK_FOUR_STR: &str = "Pilot";
 pub const MRANK_FIVE_STR: &str = "Rocketeer";
 pub const MRANK_SIX_STR: &str = "Cadet";
+pub const MRANK_ALUMNI_STR: &str = "Alumni";
 
 impl MemberRank {
-    /// Get the rank that is one higher
+    /// Get the rank that is one higher, alumni can't be promoted
     pub fn promote(&self) -> Option<Self> {
         if let Some(i) = MEMBER_RANKS.iter().position(|r| r == self) {
-            if i > 0 {
+            if i > 0 && *self != Self::Alumni {
                 return Some(MEMBER_RANKS[i - 1]);
             }
         }
         None
     }
 
-    /// Get the rank that is one lower
+    /// Get the rank that is one lower, members can't be demoted into alumni
     pub fn demote(&self) -> Option<Self> {
         if let Some(i) = MEMBER_RANKS.iter().position(|r| r == self) {
-            if i < MEMBER_RANKS.len() - 1 {
+            if i < MEMBER_RANKS.len() - 1 && MEMBER_RANKS[i + 1] != Self::Alumni {
                 return Some(MEMBER_RANKS[i + 1]);
             }
         }
@@ -83,11 +93,13 @@ impl MemberRank {
     }
 
     /// Get the corresponding discord role, aka one that has the same name as the rank
+    #[cfg(feature = "discord")]
     pub fn get_role<'a>(&self, guild: &'a Guild) -> Option<&'a Role> {
         guild.role_by_name(&self.to_string())
     }
 
     /// Get the corresponding group role
+    #[cfg(feature = "discord")]
     pub fn get_group_role<'a>(&self, guild: &'a Guild) -> Option<&'a Role> {
         guild.role_by_name(self.get_group_name())
     }
@@ -103,10 +115,13 @@ impl MemberRank {
             Self::Zero | Self::One | Self::Two => "Mission Specialist",
             Self::Three | Self::Four => "Flight Captains",
             Self::Five | Self::Six => "Passengers",
+            // Alumni don't have a separate group role
+            Self::Alumni => MRANK_ALUMNI_STR,
         }
     }
 
     /// Get the rank from discord role
+    #[cfg(feature = "discord")]
     pub fn from_role(&self, role: &Role) -> Option<Self> {
         Self::from_str(&role.name).ok()
     }
@@ -121,6 +136,7 @@ impl MemberRank {
             "Four" => Self::Four,
             "Five" => Self::Five,
             "Six" => Self::Six,
+            "Alumni" => Self::Alumni,
             s => return ioerr!("Failed to parse '{}' as MemberRank", s),
         })
     }
@@ -135,6 +151,7 @@ impl MemberRank {
             Self::Four => '✲',
             Self::Five => '✮',
             Self::Six => '✧',
+            Self::Alumni => '✦',
         }
     }
 }
@@ -149,6 +166,7 @@ impl fmt::Display for MemberRank {
             Self::Four => write!(f, "{}", MRANK_FOUR_STR),
             Self::Five => write!(f, "{}", MRANK_FIVE_STR),
             Self::Six => write!(f, "{}", MRANK_SIX_STR),
+            Self::Alumni => write!(f, "{}", MRANK_ALUMNI_STR),
         }
     }
 }
@@ -165,18 +183,22 @@ impl FromStr for MemberRank {
             MRANK_FOUR_STR => Ok(Self::Four),
             MRANK_FIVE_STR => Ok(Self::Five),
             MRANK_SIX_STR => Ok(Self::Six),
+            MRANK_ALUMNI_STR => Ok(Self::Alumni),
             _ => ioerr!("Failed to parse '{}' as MemberRank", s),
         }
     }
 }
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, Eq, PartialEq)]
 /// Types of member
 pub enum MemberType {
     Full,
     DiscordPartial,
     WynnPartial,
     GuildPartial,
+    /// Visitor with only a discord profile, whose stats are tracked but isn't a member of the
+    /// guild
+    Guest,
 }
 
 impl_sqlx_type!(MemberType);
@@ -194,6 +216,11 @@ impl MemberType {
     pub fn is_partial(&self) -> bool {
         !self.is_full()
     }
+
+    /// Is guest
+    pub fn is_guest(&self) -> bool {
+        matches!(self, Self::Guest)
+    }
 }
 
 impl fmt::Display for MemberType {
@@ -203,6 +230,7 @@ impl fmt::Display for MemberType {
             Self::GuildPartial => write!(f, "guild"),
             Self::DiscordPartial => write!(f, "discord"),
             Self::WynnPartial => write!(f, "wynn"),
+            Self::Guest => write!(f, "guest"),
         }
     }
 }
@@ -216,6 +244,7 @@ impl FromStr for MemberType {
             "guild" => Ok(Self::GuildPartial),
             "discord" => Ok(Self::DiscordPartial),
             "wynn" => Ok(Self::WynnPartial),
+            "guest" => Ok(Self::Guest),
             _ => ioerr!("Failed to parse '{}' as MemberType", s),
         }
     }
@@ -233,7 +262,7 @@ pub struct MemberRow {
     pub rank: String,
 }
 
-#[derive(Debug)]
+#[derive(Serialize, Deserialize, Debug)]
 /// Member table model.
 /// This can't be used to query entire member from database, instead query one using
 /// `MemberRow`, and then convert it to `Member`.
@@ -1,4 +1,7 @@
-/// Database models
+//! Database models
+//!
+//! Models are serialized with their field and variant names as is, and ids as their inner value,
+//! so renaming any of them breaks previously serialized data.
 pub mod db;
 pub mod discord;
 pub mod guild;
@@ -1,9 +1,11 @@
 //! Models for the wynn table
 use std::fmt;
 
+use serde::{Deserialize, Serialize};
+
 use crate::model::member::MemberId;
 
-#[derive(sqlx::Type, Debug, Clone, PartialEq, Eq)]
+#[derive(sqlx::Type, Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 #[sqlx(transparent)]
 pub struct McId(pub String);
 
@@ -30,7 +32,7 @@ pub struct WynnProfileRow {
     pub activity_avg_range: i64,
 }
 
-#[derive(Debug)]
+#[derive(Serialize, Deserialize, Debug)]
 /// Wynn table model.
 /// This can't be used to query entire wynn profile from database, instead query one using
 /// `WynnProfileRow`, and then convert it to `WynnProfile`.
@@ -0,0 +1,90 @@
+//! Provides [`OnlineLimiter`], which applies [`OnlineLimits`] to the online time of players.
+use std::collections::HashMap;
+
+use config::online::OnlineLimits;
+
+#[derive(Debug, Default)]
+/// Online time tracking state of a player
+struct PlayerState {
+    /// World the player is staying on, empty if they aren't online
+    world: String,
+    /// Seconds the player has stayed on `world` without contributing guild xp
+    idle: u64,
+    /// Day `counted` is for, in days since unix epoch
+    day: u64,
+    /// Seconds of online time counted on `day`
+    counted: u64,
+}
+
+#[derive(Debug, Default)]
+/// Decides how much of the players' online time is counted, according to [`OnlineLimits`].
+/// ```
+/// # use config::online::OnlineLimits;
+/// # use memberdb::online_limiter::OnlineLimiter;
+/// let limits = OnlineLimits { daily_cap: Some(150), afk_after: Some(100) };
+/// let mut limiter = OnlineLimiter::new();
+/// assert_eq!(limiter.count_stay(&limits, "Pucaet", "WC1", 60, 0), 60);
+/// // Only the time before going afk is counted
+/// assert_eq!(limiter.count_stay(&limits, "Pucaet", "WC1", 60, 0), 40);
+/// assert_eq!(limiter.count_stay(&limits, "Pucaet", "WC1", 60, 0), 0);
+/// // Contributing xp makes the player active again, but the daily cap is reached
+/// limiter.mark_active("Pucaet");
+/// assert_eq!(limiter.count_stay(&limits, "Pucaet", "WC1", 60, 0), 50);
+/// // The cap is reset on the next day, and changing world also makes the player active
+/// assert_eq!(limiter.count_stay(&limits, "Pucaet", "WC2", 60, 1), 60);
+/// ```
+pub struct OnlineLimiter {
+    players: HashMap<String, PlayerState>,
+}
+
+impl OnlineLimiter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get how many seconds of a player's `elapsed` seconds of stay on `world` are counted.
+    /// `day` is the current day, in days since unix epoch.
+    pub fn count_stay(
+        &mut self, limits: &OnlineLimits, ign: &str, world: &str, elapsed: u64, day: u64,
+    ) -> u64 {
+        let state = self.players.entry(ign.to_string()).or_default();
+        if state.world != world {
+            state.world = world.to_string();
+            state.idle = 0;
+        }
+        if state.day != day {
+            state.day = day;
+            state.counted = 0;
+        }
+
+        let idle_before = state.idle;
+        state.idle += elapsed;
+        let mut counted = match limits.afk_after {
+            // Only the part of the stay before the player went afk is counted
+            Some(afk_after) => elapsed.min(afk_after.saturating_sub(idle_before)),
+            None => elapsed,
+        };
+        if let Some(cap) = limits.daily_cap {
+            counted = counted.min(cap.saturating_sub(state.counted));
+        }
+        state.counted += counted;
+        counted
+    }
+
+    /// Mark a player as active, which happens when they contribute guild xp
+    pub fn mark_active(&mut self, ign: &str) {
+        if let Some(state) = self.players.get_mut(ign) {
+            state.idle = 0;
+        }
+    }
+
+    /// Stop tracking the world of a player that logged off.
+    /// The online time counted for them today is kept, so logging back on doesn't bypass the
+    /// daily cap.
+    pub fn leave(&mut self, ign: &str) {
+        if let Some(state) = self.players.get_mut(ign) {
+            state.world.clear();
+            state.idle = 0;
+        }
+    }
+}
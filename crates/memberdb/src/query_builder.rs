@@ -5,14 +5,14 @@ use std::collections::HashSet;
 use std::str::FromStr;
 
 use anyhow::Result;
-use serenity::client::Cache;
 use sqlx::sqlite::SqliteRow;
 use sqlx::Row;
 
 use util::ioerr;
+use util::string::StatFormat;
 
 use crate::model::db::{Column, ProfileType, Stat};
-use crate::model::discord::DiscordId;
+use crate::model::discord::{DiscordId, UserNames};
 use crate::model::guild::{GuildRank, GUILD_RANKS};
 use crate::model::member::{MemberRank, MemberType, MEMBER_RANKS};
 
@@ -28,7 +28,7 @@ impl QueryAction for Column {
 }
 
 impl Selectable for Column {
-    fn format_val(&self, row: &SqliteRow, _: &Cache) -> String {
+    fn format_val(&self, row: &SqliteRow, _: &dyn UserNames, fmt: &StatFormat) -> String {
         let ident = self.query_ident();
         match self {
             // Columns of type String
@@ -40,19 +40,37 @@ impl Selectable for Column {
                 row.get::<Option<String>, _>(ident).unwrap_or_default()
             }
             // Columns of type Option<Number>
-            Self::DMessage | Self::DWeeklyMessage | Self::GXp | Self::GWeeklyXp => {
-                match row.get::<Option<i64>, _>(ident) {
-                    Some(n) => util::string::fmt_num(n, true),
-                    None => String::new(),
-                }
-            }
+            Self::DMessage
+            | Self::DWeeklyMessage
+            | Self::DReactionGiven
+            | Self::DReactionReceived
+            | Self::GXp
+            | Self::GWeeklyXp
+            | Self::GWars => match row.get::<Option<i64>, _>(ident) {
+                Some(n) => fmt.num(n, true),
+                None => String::new(),
+            },
             // Columns of type Option<Time Duration>
-            Self::DVoice | Self::DWeeklyVoice | Self::WOnline | Self::WWeeklyOnline | Self::WAvgOnline => {
-                match row.get::<Option<i64>, _>(ident) {
-                    Some(n) => util::string::fmt_second(n),
-                    None => String::new(),
-                }
-            }
+            Self::DVoice
+            | Self::DWeeklyVoice
+            | Self::DStream
+            | Self::DWeeklyStream
+            | Self::WOnline
+            | Self::WWeeklyOnline
+            | Self::WAvgOnline
+            | Self::GRankTime => match row.get::<Option<i64>, _>(ident) {
+                Some(n) => fmt.second(n),
+                None => String::new(),
+            },
+            // Columns of type Option<Datetime String>
+            Self::DLastMessage => match row.get::<Option<String>, _>(ident) {
+                Some(s) => s.get(..16).unwrap_or(&s).to_string(),
+                None => String::new(),
+            },
+            Self::GJoined => match row.get::<Option<String>, _>(ident) {
+                Some(s) => s.get(..10).unwrap_or(&s).to_string(),
+                None => String::new(),
+            },
             // Columns of type Option<Boolean>
             Self::WGuild => match row.get::<Option<i64>, _>(ident) {
                 Some(1) => "true".to_string(),
@@ -75,17 +93,52 @@ impl Selectable for Column {
             _ => self.query_ident(),
         }
     }
+
+    fn summary_query(&self) -> Option<String> {
+        let ident = self.query_ident();
+        match self {
+            // Stats that add up across members
+            Self::DMessage
+            | Self::DWeeklyMessage
+            | Self::DVoice
+            | Self::DWeeklyVoice
+            | Self::DStream
+            | Self::DWeeklyStream
+            | Self::DReactionGiven
+            | Self::DReactionReceived
+            | Self::WOnline
+            | Self::WWeeklyOnline
+            | Self::GXp
+            | Self::GWeeklyXp
+            | Self::GWars => Some(format!("SUM({0}) AS {0}", ident)),
+            // Stats that are already averages or doesn't make sense to add up
+            Self::WAvgOnline | Self::GRankTime => Some(format!("CAST(AVG({0}) AS INTEGER) AS {0}", ident)),
+            _ => None,
+        }
+    }
 }
 
 impl SelectAction for Column {
     fn query_ident(&self) -> &str {
         match self {
             Self::GRank => "guild_rank",
+            Self::GJoined => "guild_joined",
             _ => self.name(),
         }
     }
 
     fn select_query(&self) -> String {
+        if let Self::DLastMessage = self {
+            return "(SELECT datetime(MAX(time),'unixepoch') FROM message_log WHERE mid=member.oid)"
+                .to_string();
+        }
+        if let Self::GRankTime = self {
+            return "(SELECT CAST(strftime('%s','now') AS INTEGER)-COALESCE(\
+                (SELECT MAX(time) FROM guild_rank_history WHERE mcid=guild.id),\
+                CAST(strftime('%s',joined) AS INTEGER)) \
+                FROM guild WHERE id=member.mcid AND (SELECT guild FROM wynn WHERE id=guild.id))"
+                .to_string();
+        }
         match self.profile() {
             // If it is from another table
             Some(profile) => {
@@ -137,16 +190,21 @@ impl ProfileType {
             Self::Discord => "id=member.discord",
         }
     }
+
+    /// The condition of a member having the profile
+    pub fn exist_condition(&self) -> &str {
+        match self {
+            Self::Wynn => "mcid NOT NULL",
+            Self::Guild => "(SELECT guild FROM wynn WHERE mid=member.oid)",
+            Self::Discord => "discord NOT NULL",
+        }
+    }
 }
 
 impl QueryAction for ProfileType {
     /// Filter out members without the profile
     fn apply_action<'a>(&self, builder: &'a mut QueryBuilder) -> &'a mut QueryBuilder {
-        match self {
-            Self::Wynn => builder.filter("mcid NOT NULL".to_string()),
-            Self::Guild => builder.filter("(SELECT guild FROM wynn WHERE mid=member.oid)".to_string()),
-            Self::Discord => builder.filter("discord NOT NULL".to_string()),
-        }
+        builder.filter(self.exist_condition().to_string())
     }
 }
 
@@ -158,8 +216,8 @@ impl QueryAction for Stat {
 }
 
 impl Selectable for Stat {
-    fn format_val(&self, row: &SqliteRow, cache: &Cache) -> String {
-        self.to_column().format_val(row, cache)
+    fn format_val(&self, row: &SqliteRow, names: &dyn UserNames, fmt: &StatFormat) -> String {
+        self.to_column().format_val(row, names, fmt)
     }
 
     fn table_name(&self) -> &str {
@@ -168,6 +226,10 @@ impl Selectable for Stat {
             Self::WeeklyMessage => "weekly_message",
             Self::Voice => "voice",
             Self::WeeklyVoice => "weekly_voice",
+            Self::Stream => "stream",
+            Self::WeeklyStream => "weekly_stream",
+            Self::ReactionGiven => "reactions_given",
+            Self::ReactionReceived => "reactions_received",
             Self::Online => "online",
             Self::WeeklyOnline => "weekly_online",
             Self::AvgOnline => "avg_online",
@@ -175,6 +237,10 @@ impl Selectable for Stat {
             Self::WeeklyXp => "weekly_xp",
         }
     }
+
+    fn summary_query(&self) -> Option<String> {
+        self.to_column().summary_query()
+    }
 }
 
 #[derive(Debug, Eq, PartialEq, Clone)]
@@ -196,6 +262,10 @@ pub enum Filter {
     GuildRank(GuildRank, Ordering),
     /// Filter out members by stat value.
     Stat(Stat, u64, Ordering),
+    /// Filter out members whose stat value is outside of the inclusive range.
+    StatRange(Stat, u64, u64),
+    /// Filter out members by comparing their stat value to another value.
+    StatCompare(Stat, Ordering, StatOperand),
 }
 
 impl QueryAction for Filter {
@@ -242,6 +312,29 @@ impl QueryAction for Filter {
                 let col = stat.to_column();
                 builder.with(&col).filter(format!("{}{}{}", col.query_ident(), cmp, val))
             }
+            Self::StatRange(stat, min, max) => {
+                let col = stat.to_column();
+                builder.with(&col).filter(format!("{} BETWEEN {} AND {}", col.query_ident(), min, max))
+            }
+            Self::StatCompare(stat, ord, operand) => {
+                let cmp = match ord {
+                    Ordering::Equal => "=",
+                    Ordering::Less => "<",
+                    Ordering::Greater => ">",
+                };
+                let col = stat.to_column();
+                let operand = match operand {
+                    StatOperand::Stat(other) => {
+                        let other = other.to_column();
+                        builder.with(&other);
+                        other.query_ident().to_string()
+                    }
+                    StatOperand::Avg(other) => {
+                        format!("(SELECT AVG({}) FROM member)", other.to_column().select_query())
+                    }
+                };
+                builder.with(&col).filter(format!("{}{}{}", col.query_ident(), cmp, operand))
+            }
         }
     }
 }
@@ -253,6 +346,8 @@ impl FromStr for Filter {
     /// - "filter"
     /// - ">filter", "<filter" if it supports ordered filter
     /// - "filter:val", ">filter:val", "<filter:val" if it is a stat filter
+    /// - "filter:min..max" if it is a stat filter
+    /// - "filter>operand", "filter<operand", "filter=operand" if it is a stat filter
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         if !s.is_empty() {
             match s {
@@ -269,6 +364,17 @@ impl FromStr for Filter {
             if let Ok(stat) = Stat::from_str(s) {
                 return Ok(Self::Stat(stat, 1, Ordering::Greater));
             }
+            if let Some(i) = s.find(['<', '>', '=']).filter(|i| *i > 0) {
+                let ord = match &s[i..=i] {
+                    "<" => Ordering::Less,
+                    ">" => Ordering::Greater,
+                    _ => Ordering::Equal,
+                };
+                let (stat, operand) = (Stat::from_str(&s[..i]), StatOperand::from_str(&s[i + 1..]));
+                if let (Ok(stat), Ok(operand)) = (stat, operand) {
+                    return Ok(Self::StatCompare(stat, ord, operand));
+                }
+            }
 
             let (ord, s) = {
                 let symbol = s.chars().next().unwrap();
@@ -282,6 +388,13 @@ impl FromStr for Filter {
             if s.contains(':') {
                 if let Some((stat_name, val)) = s.split_once(':') {
                     if let Ok(stat) = Stat::from_str(stat_name) {
+                        if let (Ordering::Equal, Some((min, max))) = (ord, val.split_once("..")) {
+                            if let (Ok(min), Ok(max)) = (stat.parse_val(min), stat.parse_val(max)) {
+                                if min <= max {
+                                    return Ok(Self::StatRange(stat, min, max));
+                                }
+                            }
+                        }
                         if let Ok(val) = stat.parse_val(val) {
                             return Ok(Self::Stat(stat, val, ord));
                         }
@@ -300,6 +413,33 @@ impl FromStr for Filter {
     }
 }
 
+#[derive(Debug, Eq, PartialEq, Clone)]
+/// Value a member's stat is compared to in [`Filter::StatCompare`]
+pub enum StatOperand {
+    /// Another stat of the same member
+    Stat(Stat),
+    /// Average of a stat across all members who have it
+    Avg(Stat),
+}
+
+impl FromStr for StatOperand {
+    type Err = std::io::Error;
+
+    /// Possible formats:
+    /// "stat" - another stat of the member
+    /// "avg(stat)" - average of the stat
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(stat) = s.strip_prefix("avg(").and_then(|s| s.strip_suffix(')')) {
+            if let Ok(stat) = Stat::from_str(stat) {
+                return Ok(Self::Avg(stat));
+            }
+        } else if let Ok(stat) = Stat::from_str(s) {
+            return Ok(Self::Stat(stat));
+        }
+        ioerr!("Failed to parse '{}' as StatOperand", s)
+    }
+}
+
 #[derive(Debug, Eq, PartialEq, Clone)]
 /// Represent sorting of a column
 pub enum Sort {
@@ -345,11 +485,26 @@ impl FromStr for Sort {
     }
 }
 
+#[derive(Debug, Eq, PartialEq, Clone)]
+/// Groups the rows by a column, so each row represents all members with the same value in that
+/// column.
+///
+/// Only the grouped column and [`Aggregate`] can be selected alongside it.
+pub struct GroupBy(pub Column);
+
+impl QueryAction for GroupBy {
+    /// Selects the column and groups by it, the groups are ordered by the column
+    fn apply_action<'a>(&self, builder: &'a mut QueryBuilder) -> &'a mut QueryBuilder {
+        builder.with(&self.0).group(self.0.query_ident().to_string()).with(&Sort::Asc(self.0.clone()))
+    }
+}
+
 #[derive(Debug)]
-/// Wrapper over `Filter` and `Sort`
+/// Wrapper over `Filter`, `Sort` and `GroupBy`
 pub enum QueryMod {
     Filter(Filter),
     Sort(Sort),
+    GroupBy(GroupBy),
 }
 
 impl QueryAction for QueryMod {
@@ -357,6 +512,7 @@ impl QueryAction for QueryMod {
         match self {
             Self::Filter(filter) => filter.apply_action(builder),
             Self::Sort(sort) => sort.apply_action(builder),
+            Self::GroupBy(group) => group.apply_action(builder),
         }
     }
 }
@@ -375,12 +531,12 @@ impl QueryAction for MemberName {
 
 impl Selectable for MemberName {
     /// Get the name of the member
-    fn format_val(&self, row: &SqliteRow, cache: &Cache) -> String {
+    fn format_val(&self, row: &SqliteRow, names: &dyn UserNames, _: &StatFormat) -> String {
         match row.get(Column::WIgn.query_ident()) {
             Some(ign) => ign,
-            None => match row.get::<Option<DiscordId>, &str>("discord").map(|id| id.to_user(cache)) {
-                Some(Some(u)) => format!("{}#{}", u.name, u.discriminator),
-                _ => String::new(),
+            None => match row.get::<Option<DiscordId>, &str>("discord") {
+                Some(id) => names.user_name(id).unwrap_or_default(),
+                None => String::new(),
             },
         }
     }
@@ -402,6 +558,140 @@ impl FromStr for MemberName {
     }
 }
 
+#[derive(Debug)]
+/// Implements `Selectable` that gives you a member's voice time in a specific voice channel.
+pub struct ChannelVoice(pub i64);
+
+impl ChannelVoice {
+    /// Identifier of the selected value
+    pub const IDENT: &'static str = "channel_voice";
+}
+
+impl QueryAction for ChannelVoice {
+    /// Selects the member's voice time in the channel
+    fn apply_action<'a>(&self, builder: &'a mut QueryBuilder) -> &'a mut QueryBuilder {
+        builder.select(format!(
+            "(SELECT voice FROM voice_channel WHERE discord=member.discord AND channel={}) AS {}",
+            self.0,
+            Self::IDENT
+        ))
+    }
+}
+
+impl Selectable for ChannelVoice {
+    fn format_val(&self, row: &SqliteRow, _: &dyn UserNames, fmt: &StatFormat) -> String {
+        match row.get::<Option<i64>, _>(Self::IDENT) {
+            Some(n) => fmt.second(n),
+            None => String::new(),
+        }
+    }
+
+    fn table_name(&self) -> &str {
+        "voice"
+    }
+
+    fn summary_query(&self) -> Option<String> {
+        Some(format!("SUM({0}) AS {0}", Self::IDENT))
+    }
+}
+
+#[derive(Debug, Eq, PartialEq, Clone)]
+/// Aggregate functions of [`Aggregate`]
+pub enum AggregateFn {
+    Count,
+    Sum(Column),
+    Avg(Column),
+}
+
+#[derive(Debug, Eq, PartialEq, Clone)]
+/// Implements `Selectable` that gives you a value computed across multiple members, which is
+/// either across all selected members, or across each group if [`GroupBy`] is used.
+pub struct Aggregate {
+    func: AggregateFn,
+    ident: String,
+}
+
+impl Aggregate {
+    /// Create an aggregate, fails if the column can't be aggregated.
+    /// Only stats can be aggregated, which are the columns that can be summarized, see
+    /// [`Selectable::summary_query`].
+    pub fn new(func: AggregateFn) -> Result<Self, std::io::Error> {
+        let name = |col: &Column| match Stat::from_column(col) {
+            Some(stat) => stat.table_name().to_string(),
+            None => col.table_name().to_string(),
+        };
+        let ident = match &func {
+            AggregateFn::Count => "count".to_string(),
+            AggregateFn::Sum(col) | AggregateFn::Avg(col) if col.summary_query().is_none() => {
+                return ioerr!("Column '{}' can't be aggregated", name(col));
+            }
+            AggregateFn::Sum(col) => format!("sum_{}", name(col)),
+            AggregateFn::Avg(col) => format!("avg_{}", name(col)),
+        };
+        Ok(Self { func, ident })
+    }
+}
+
+impl QueryAction for Aggregate {
+    /// Selects the aggregated value
+    fn apply_action<'a>(&self, builder: &'a mut QueryBuilder) -> &'a mut QueryBuilder {
+        let select = match &self.func {
+            AggregateFn::Count => "COUNT(*)".to_string(),
+            AggregateFn::Sum(col) => format!("SUM({})", col.select_query()),
+            AggregateFn::Avg(col) => format!("CAST(AVG({}) AS INTEGER)", col.select_query()),
+        };
+        builder.select(format!("{} AS {}", select, self.ident))
+    }
+}
+
+impl Selectable for Aggregate {
+    fn format_val(&self, row: &SqliteRow, _: &dyn UserNames, fmt: &StatFormat) -> String {
+        let n = match row.get::<Option<i64>, _>(self.ident.as_str()) {
+            Some(n) => n,
+            None => return String::new(),
+        };
+        match &self.func {
+            AggregateFn::Sum(col) | AggregateFn::Avg(col) if col.is_duration() => fmt.second(n),
+            _ => fmt.num(n, true),
+        }
+    }
+
+    fn table_name(&self) -> &str {
+        &self.ident
+    }
+
+    fn summary_query(&self) -> Option<String> {
+        match self.func {
+            AggregateFn::Count | AggregateFn::Sum(_) => Some(format!("SUM({0}) AS {0}", self.ident)),
+            AggregateFn::Avg(_) => None,
+        }
+    }
+}
+
+impl FromStr for Aggregate {
+    type Err = std::io::Error;
+
+    /// Possible formats:
+    /// "count" - amount of members
+    /// "sum(column)" - total of the column
+    /// "avg(column)" - average of the column
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "count" {
+            return Self::new(AggregateFn::Count);
+        }
+        if let Some((func, col)) = s.strip_suffix(')').and_then(|s| s.split_once('(')) {
+            if let Ok(col) = Column::from_str(col) {
+                match func {
+                    "sum" => return Self::new(AggregateFn::Sum(col)),
+                    "avg" => return Self::new(AggregateFn::Avg(col)),
+                    _ => {}
+                }
+            }
+        }
+        ioerr!("Failed to parse '{}' as Aggregate", s)
+    }
+}
+
 /// Trait for object that can modify `QueryBuilder`, used through `QueryBuilder.with`
 pub trait QueryAction {
     /// Modify `QueryAction`
@@ -410,10 +700,17 @@ pub trait QueryAction {
 
 /// Trait for extracting value from `SqliteRow`, helps with table display
 pub trait Selectable: QueryAction + Sync {
-    /// Extract value from `SqliteRow` as formatted string
-    fn format_val(&self, _: &SqliteRow, _: &Cache) -> String;
+    /// Extract value from `SqliteRow` as formatted string, discord users are named via `names`, and
+    /// stats are displayed in `fmt`
+    fn format_val(&self, _: &SqliteRow, names: &dyn UserNames, fmt: &StatFormat) -> String;
     /// Get the column name to be displayed in a table
     fn table_name(&self) -> &str;
+    /// Get the select statement that summarizes the value across the rows of a table, which is
+    /// selected from the table with the same identifier.
+    /// Returns `None` if the value can't be summarized.
+    fn summary_query(&self) -> Option<String> {
+        None
+    }
 }
 
 #[derive(Debug)]
@@ -421,6 +718,7 @@ pub trait Selectable: QueryAction + Sync {
 pub enum Selectables {
     Column(Column),
     MemberName(MemberName),
+    Aggregate(Aggregate),
 }
 
 impl FromStr for Selectables {
@@ -433,6 +731,9 @@ impl FromStr for Selectables {
         if let Ok(name) = MemberName::from_str(s) {
             return Ok(Self::MemberName(name));
         }
+        if let Ok(aggregate) = Aggregate::from_str(s) {
+            return Ok(Self::Aggregate(aggregate));
+        }
         ioerr!("Failed to parse '{}' as Selectables", s)
     }
 }
@@ -442,15 +743,17 @@ impl QueryAction for Selectables {
         match self {
             Self::Column(col) => col.apply_action(builder),
             Self::MemberName(name) => name.apply_action(builder),
+            Self::Aggregate(aggregate) => aggregate.apply_action(builder),
         }
     }
 }
 
 impl Selectable for Selectables {
-    fn format_val(&self, row: &SqliteRow, cache: &Cache) -> String {
+    fn format_val(&self, row: &SqliteRow, names: &dyn UserNames, fmt: &StatFormat) -> String {
         match self {
-            Self::Column(col) => col.format_val(row, cache),
-            Self::MemberName(name) => name.format_val(row, cache),
+            Self::Column(col) => col.format_val(row, names, fmt),
+            Self::MemberName(name) => name.format_val(row, names, fmt),
+            Self::Aggregate(aggregate) => aggregate.format_val(row, names, fmt),
         }
     }
 
@@ -458,6 +761,15 @@ impl Selectable for Selectables {
         match self {
             Self::Column(col) => col.table_name(),
             Self::MemberName(name) => name.table_name(),
+            Self::Aggregate(aggregate) => aggregate.table_name(),
+        }
+    }
+
+    fn summary_query(&self) -> Option<String> {
+        match self {
+            Self::Column(col) => col.summary_query(),
+            Self::MemberName(name) => name.summary_query(),
+            Self::Aggregate(aggregate) => aggregate.summary_query(),
         }
     }
 }
@@ -478,7 +790,9 @@ pub trait SelectAction: Selectable {
 /// Dynamic builder for query string
 pub struct QueryBuilder {
     select_tokens: HashSet<String>,
+    join_tokens: Vec<String>,
     where_tokens: HashSet<String>,
+    group_tokens: Vec<String>,
     order_tokens: Vec<String>,
 }
 
@@ -486,7 +800,9 @@ impl QueryBuilder {
     pub fn new() -> Self {
         Self {
             select_tokens: HashSet::new(),
+            join_tokens: Vec::new(),
             where_tokens: HashSet::new(),
+            group_tokens: Vec::new(),
             order_tokens: Vec::new(),
         }
     }
@@ -497,12 +813,28 @@ impl QueryBuilder {
         self
     }
 
+    /// Add a "join" clause, joined with the member table
+    pub fn join(&mut self, token: String) -> &mut Self {
+        if !self.join_tokens.contains(&token) {
+            self.join_tokens.push(token);
+        }
+        self
+    }
+
     /// Add a "where" expression
     pub fn filter(&mut self, token: String) -> &mut Self {
         self.where_tokens.insert(token);
         self
     }
 
+    /// Add a "group by" expression
+    pub fn group(&mut self, token: String) -> &mut Self {
+        if !self.group_tokens.contains(&token) {
+            self.group_tokens.push(token);
+        }
+        self
+    }
+
     /// Add a "order by" expression
     pub fn order(&mut self, token: String) -> &mut Self {
         if !self.order_tokens.contains(&token) {
@@ -525,12 +857,22 @@ impl QueryBuilder {
             String::from("SELECT oid FROM MEMBER")
         };
 
+        for join in self.join_tokens {
+            query.push(' ');
+            query.push_str(&join);
+        }
+
         if !self.where_tokens.is_empty() {
             let filter = self.where_tokens.into_iter().collect::<Vec<String>>().join(" AND ");
             query.push_str(" WHERE ");
             query.push_str(&filter);
         }
 
+        if !self.group_tokens.is_empty() {
+            query.push_str(" GROUP BY ");
+            query.push_str(&self.group_tokens.join(","));
+        }
+
         if !self.order_tokens.is_empty() {
             let order = self.order_tokens.into_iter().collect::<Vec<String>>().join(",");
             query.push_str(" ORDER BY ");
@@ -563,12 +905,22 @@ impl QueryBuilder {
 
         query.push_str(" FROM member ");
 
+        for join in self.join_tokens {
+            query.push_str(&join);
+            query.push(' ');
+        }
+
         if !self.where_tokens.is_empty() {
             let filter = self.where_tokens.into_iter().collect::<Vec<String>>().join(" AND ");
             query.push_str(" WHERE ");
             query.push_str(&filter);
         }
 
+        if !self.group_tokens.is_empty() {
+            query.push_str(" GROUP BY ");
+            query.push_str(&self.group_tokens.join(","));
+        }
+
         query
     }
 }
@@ -0,0 +1,139 @@
+//! Timing of database queries.
+//!
+//! Every query ran through [`Executor`] or the table functions is timed into [`QueryStats`],
+//! which keeps aggregate timings of each statement template, and logs the queries that are slower
+//! than its threshold along with their sql.
+//!
+//! [`Executor`]: crate::Executor
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use tracing::warn;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+/// Aggregate timings of a query
+pub struct QueryTiming {
+    /// Amount of times the query has been ran
+    pub count: u64,
+    pub total: Duration,
+    pub max: Duration,
+}
+
+impl QueryTiming {
+    /// Average time the query took
+    pub fn avg(&self) -> Duration {
+        match u32::try_from(self.count) {
+            Ok(0) => Duration::ZERO,
+            Ok(count) => self.total / count,
+            Err(_) => self.total / u32::MAX,
+        }
+    }
+}
+
+#[derive(Debug)]
+/// Timings of database queries, keyed by their statement [template]
+pub struct QueryStats {
+    /// Queries that took at least this long are logged
+    slow_threshold: Duration,
+    timings: Mutex<HashMap<String, QueryTiming>>,
+}
+
+impl QueryStats {
+    pub fn new(slow_threshold: Duration) -> Self {
+        Self { slow_threshold, timings: Mutex::new(HashMap::new()) }
+    }
+
+    /// Record that a query took `elapsed` to run
+    pub fn record(&self, sql: &str, elapsed: Duration) {
+        if elapsed >= self.slow_threshold {
+            warn!(elapsed_ms = elapsed.as_millis() as u64, sql, "Slow query");
+        }
+
+        let mut timings = self.timings.lock().expect("Query stats is poisoned");
+        let timing = timings.entry(template(sql)).or_default();
+        timing.count += 1;
+        timing.total += elapsed;
+        timing.max = timing.max.max(elapsed);
+    }
+
+    /// Run a query and record how long it took
+    pub async fn time<T>(&self, sql: &str, query: impl Future<Output = T>) -> T {
+        let start = Instant::now();
+        let result = query.await;
+        self.record(sql, start.elapsed());
+        result
+    }
+
+    /// Get the timings of all statement templates, sorted by their average time in descending order
+    pub fn timings(&self) -> Vec<(String, QueryTiming)> {
+        let timings = self.timings.lock().expect("Query stats is poisoned");
+        let mut timings =
+            timings.iter().map(|(sql, timing)| (sql.clone(), timing.clone())).collect::<Vec<_>>();
+        timings.sort_by_key(|(_, timing)| Reverse(timing.avg()));
+        timings
+    }
+}
+
+/// Get the statement template of a query, which is its sql with whitespaces collapsed and literals
+/// replaced by `?`, so queries built with different values are timed together.
+/// ```
+/// use memberdb::query_stats::template;
+///
+/// assert_eq!(
+///     template("SELECT \"count!: i64\" FROM member\n    WHERE id=12 AND ign='a''b' LIMIT ?"),
+///     "SELECT \"count!: i64\" FROM member WHERE id=? AND ign=? LIMIT ?"
+/// );
+/// assert_eq!(
+///     template("SELECT wynn2.xp FROM wynn2 WHERE xp>1.5"),
+///     "SELECT wynn2.xp FROM wynn2 WHERE xp>?"
+/// );
+/// ```
+pub fn template(sql: &str) -> String {
+    let sql = sql.split_whitespace().collect::<Vec<&str>>().join(" ");
+    let mut result = String::with_capacity(sql.len());
+    let mut chars = sql.chars().peekable();
+    // Whether the last character can be part of an identifier, so a following digit isn't a literal
+    let mut in_ident = false;
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' => {
+                // An escaped quote is two quotes, which continues the literal
+                while let Some(c) = chars.next() {
+                    if c == '\'' && chars.next_if_eq(&'\'').is_none() {
+                        break;
+                    }
+                }
+                result.push('?');
+                in_ident = false;
+            }
+            '"' => {
+                result.push(c);
+                for c in chars.by_ref() {
+                    result.push(c);
+                    if c == '"' {
+                        break;
+                    }
+                }
+                in_ident = false;
+            }
+            c if c.is_ascii_digit() && !in_ident => {
+                while chars.next_if(|c| c.is_ascii_digit() || *c == '.').is_some() {}
+                result.push('?');
+            }
+            c => {
+                result.push(c);
+                in_ident = c.is_alphanumeric() || c == '_';
+            }
+        }
+    }
+    result
+}
+
+impl Default for QueryStats {
+    fn default() -> Self {
+        Self::new(Duration::from_millis(250))
+    }
+}
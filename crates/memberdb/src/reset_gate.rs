@@ -0,0 +1,85 @@
+//! Provides [`ResetGate`], which holds back the weekly reset until staff decide on it
+#[cfg(feature = "discord")]
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::Duration;
+
+#[cfg(feature = "discord")]
+use serenity::prelude::TypeMapKey;
+use tokio::sync::oneshot;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Decision of staff on a pending weekly reset
+pub enum ResetDecision {
+    /// Reset the weekly stats
+    Proceed,
+    /// Keep the weekly stats, the reset happens again next week
+    Skip,
+}
+
+#[derive(Debug, Default)]
+/// Holds the pending weekly reset, so staff can decide on it from elsewhere.
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use memberdb::reset_gate::{ResetDecision, ResetGate};
+///
+/// # #[tokio::main]
+/// # async fn main() {
+/// let gate = ResetGate::new();
+/// assert!(!gate.decide(ResetDecision::Skip));
+///
+/// let pending = gate.open();
+/// assert!(gate.decide(ResetDecision::Skip));
+/// assert_eq!(pending.decision(Duration::from_secs(60)).await, ResetDecision::Skip);
+///
+/// // Nobody decided within the grace window
+/// let pending = gate.open();
+/// assert_eq!(pending.decision(Duration::from_millis(10)).await, ResetDecision::Proceed);
+/// assert!(!gate.decide(ResetDecision::Skip));
+/// # }
+/// ```
+pub struct ResetGate {
+    pending: Mutex<Option<oneshot::Sender<ResetDecision>>>,
+}
+
+impl ResetGate {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Make a reset pending, replacing the previous one
+    pub fn open(&self) -> PendingReset {
+        let (sender, receiver) = oneshot::channel();
+        *self.pending.lock().expect("Reset gate is poisoned") = Some(sender);
+        PendingReset(receiver)
+    }
+
+    /// Decide on the pending reset, returns false if there is none
+    pub fn decide(&self, decision: ResetDecision) -> bool {
+        match self.pending.lock().expect("Reset gate is poisoned").take() {
+            Some(sender) => sender.send(decision).is_ok(),
+            None => false,
+        }
+    }
+}
+
+#[cfg(feature = "discord")]
+impl TypeMapKey for ResetGate {
+    type Value = Arc<ResetGate>;
+}
+
+#[derive(Debug)]
+/// A weekly reset waiting for the decision of staff
+pub struct PendingReset(oneshot::Receiver<ResetDecision>);
+
+impl PendingReset {
+    /// Wait for the decision of staff, the reset proceeds if none is made within `grace`
+    pub async fn decision(self, grace: Duration) -> ResetDecision {
+        match tokio::time::timeout(grace, self.0).await {
+            Ok(Ok(decision)) => decision,
+            _ => ResetDecision::Proceed,
+        }
+    }
+}
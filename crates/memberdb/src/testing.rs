@@ -0,0 +1,131 @@
+//! Utilities for testing code that depends on the database.
+//!
+//! [`TestDB`] spins up an in-memory database with all migrations applied, and populates it with
+//! member fixtures before handing it over to the test.
+//!
+//! The database is returned alongside a receiver of its events. The receiver needs to be kept
+//! alive during the test, as broadcasting an event without any receiver panics.
+//! ```
+//! use memberdb::model::member::{MemberRank, MemberType};
+//! use memberdb::model::wynn::McId;
+//! use memberdb::testing::TestDB;
+//!
+//! # #[tokio::main]
+//! # async fn main() {
+//! let (db, _events) = TestDB::new().wynn_partial("0a1b", "Pucaet", MemberRank::Five).build().await.unwrap();
+//! let mid = McId("0a1b".to_string()).mid(&mut db.exe()).await.unwrap().unwrap();
+//! assert!(mid.kind(&mut db.exe()).await.unwrap() == MemberType::WynnPartial);
+//! # }
+//! ```
+use std::str::FromStr;
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+
+use crate::events::{DBRecv, DBSignal};
+use crate::model::discord::DiscordId;
+use crate::model::guild::GuildRank;
+use crate::model::member::{MemberId, MemberRank};
+use crate::model::wynn::McId;
+use crate::query_stats::QueryStats;
+use crate::DB;
+
+#[derive(Debug)]
+/// Members to be added to a test database
+enum Fixture {
+    DiscordPartial(DiscordId, MemberRank),
+    WynnPartial(McId, String, MemberRank),
+    Full(DiscordId, McId, String, MemberRank),
+    Guild(McId, String, GuildRank),
+}
+
+#[derive(Debug, Default)]
+/// Builder for an in-memory database with members already added.
+///
+/// Fixtures are added in the same order as the builder methods are called.
+pub struct TestDB {
+    fixtures: Vec<Fixture>,
+}
+
+impl TestDB {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a discord partial member
+    pub fn discord_partial(mut self, id: i64, rank: MemberRank) -> Self {
+        self.fixtures.push(Fixture::DiscordPartial(DiscordId(id), rank));
+        self
+    }
+
+    /// Add a wynn partial member
+    pub fn wynn_partial(mut self, mcid: &str, ign: &str, rank: MemberRank) -> Self {
+        self.fixtures.push(Fixture::WynnPartial(McId(mcid.to_string()), ign.to_string(), rank));
+        self
+    }
+
+    /// Add a full member
+    pub fn full_member(mut self, id: i64, mcid: &str, ign: &str, rank: MemberRank) -> Self {
+        self.fixtures
+            .push(Fixture::Full(DiscordId(id), McId(mcid.to_string()), ign.to_string(), rank));
+        self
+    }
+
+    /// Put a player into the in-game guild.
+    /// If the player isn't a member, a guild partial member is added.
+    pub fn guild_member(mut self, mcid: &str, ign: &str, rank: GuildRank) -> Self {
+        self.fixtures.push(Fixture::Guild(McId(mcid.to_string()), ign.to_string(), rank));
+        self
+    }
+
+    /// Create the database and add all fixtures.
+    /// Events broadcasted while adding the fixtures are discarded.
+    pub async fn build(self) -> Result<(DB, DBRecv)> {
+        let (db, _fixture_events) = memory_db().await?;
+
+        let mut tx = db.begin().await?;
+        for fixture in self.fixtures {
+            match fixture {
+                Fixture::DiscordPartial(id, rank) => {
+                    MemberId::add_discord_partial(&mut tx, id, rank).await?;
+                }
+                Fixture::WynnPartial(mcid, ign, rank) => {
+                    MemberId::add_wynn_partial(&mut tx, &mcid, rank, &ign).await?;
+                }
+                Fixture::Full(id, mcid, ign, rank) => {
+                    MemberId::add_member(&mut tx, id, &mcid, &ign, rank).await?;
+                }
+                Fixture::Guild(mcid, ign, rank) => {
+                    mcid.bind_guild(&mut tx, &ign, true, rank).await?;
+                }
+            }
+        }
+        tx.commit().await?;
+
+        let events = db.connect();
+        Ok((db, events))
+    }
+}
+
+/// Create an empty in-memory database with all migrations applied, and a receiver of its events
+pub async fn memory_db() -> Result<(DB, DBRecv)> {
+    // An in-memory database only lives as long as its connection, so the pool is limited to a
+    // single connection that is never closed.
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .idle_timeout(None)
+        .max_lifetime(None)
+        .connect_with(SqliteConnectOptions::from_str("sqlite::memory:")?)
+        .await
+        .context("Failed to create in-memory database")?;
+    sqlx::migrate!("./migrations")
+        .run(&pool)
+        .await
+        .context("Failed to run database migrations")?;
+    let signal = DBSignal::new(64);
+    let events = signal.connect();
+    let db = DB { pool, signal, stats: Arc::new(QueryStats::default()), stat_audit: AtomicBool::new(false) };
+    Ok((db, events))
+}
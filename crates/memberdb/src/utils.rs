@@ -7,9 +7,7 @@ use serenity::model::user::User;
 use util::ok;
 
 use crate::model::discord::DiscordId;
-use crate::model::guild::GuildProfile;
 use crate::model::member::{MemberRank, MEMBER_RANKS};
-use crate::model::wynn::{McId, WynnProfile};
 use crate::DB;
 
 /// Get guild role of a user based on `MemberRank::to_string`
@@ -104,20 +102,6 @@ pub async fn add_discord_member_rank(
     Ok(())
 }
 
-/// Get wynn and guild profile with specified mcid
-pub async fn get_wynn_guild_profiles(
-    db: &DB, mcid: &Option<McId>,
-) -> (Option<WynnProfile>, Option<GuildProfile>) {
-    match mcid {
-        Some(id) => {
-            let wynn = ok!(id.get_wynn(&mut db.exe()).await, None);
-            let guild = ok!(id.get_guild(&mut db.exe()).await, None);
-            (wynn, guild)
-        }
-        None => (None, None),
-    }
-}
-
 /// Checks if the discord user is a member
 pub async fn is_discord_member(db: &DB, id: &UserId) -> bool {
     let discord_id = ok!(DiscordId::try_from(id.0), return false);
@@ -1,55 +1,97 @@
 use std::collections::HashMap;
+#[cfg(feature = "discord")]
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 
+#[cfg(feature = "discord")]
 use serenity::prelude::TypeMapKey;
+#[cfg(feature = "discord")]
 use tokio::sync::Mutex;
 
 #[derive(Debug)]
-/// Tracks the voice chat duration of discord members.
-pub struct VoiceTracker(HashMap<u64, Instant>);
+/// Tracks the voice chat duration of discord members, along with the voice channel they are in.
+/// The streaming duration is also tracked separately.
+///
+/// Each member is tracked with a weight, which is the percentage of their vc duration that is
+/// counted, and the returned vc durations have it applied.
+pub struct VoiceTracker {
+    voice: HashMap<u64, VoiceSession>,
+    stream: HashMap<u64, Instant>,
+}
 
 impl VoiceTracker {
     pub fn new() -> Self {
-        Self(HashMap::new())
+        Self { voice: HashMap::new(), stream: HashMap::new() }
     }
 
-    /// Get all tracked vc durations
-    pub fn track_all_voice(&mut self) -> impl Iterator<Item = (&u64, Duration)> {
-        self.0.iter_mut().map(|(k, v)| {
-            let new_instant = Instant::now();
-            let dur = new_instant.saturating_duration_since(*v);
-            *v = new_instant;
-            (k, dur)
-        })
+    /// Get all tracked vc durations, in the form of `(user id, channel id, duration)`
+    pub fn track_all_voice(&mut self) -> impl Iterator<Item = (&u64, u64, Duration)> {
+        self.voice.iter_mut().map(|(k, session)| (k, session.channel, session.flush()))
     }
 
-    /// Get the vc duration of discord member, if there is none, begin duration tracking and return
-    /// `None`
-    pub fn track_voice(&mut self, id: &u64) -> Option<Duration> {
-        match self.0.get_mut(id) {
-            Some(instant) => {
-                let new_instant = Instant::now();
-                let dur = new_instant.saturating_duration_since(*instant);
-                *instant = new_instant;
-                Some(dur)
+    /// Get the vc duration and channel of discord member, then continue tracking them in
+    /// `channel` with `weight`.
+    /// If there is none, begin duration tracking and return `None`
+    pub fn track_voice(&mut self, id: &u64, channel: u64, weight: u32) -> Option<(u64, Duration)> {
+        match self.voice.get_mut(id) {
+            Some(session) => {
+                let result = (session.channel, session.flush());
+                session.channel = channel;
+                session.weight = weight;
+                Some(result)
             }
             None => {
-                self.0.insert(*id, Instant::now());
+                self.voice.insert(*id, VoiceSession { channel, weight, since: Instant::now() });
                 None
             }
         }
     }
 
-    /// Stop the duration tracking of discord member, and returns tracked duration if there is any
-    pub fn untrack_voice(&mut self, id: &u64) -> Option<Duration> {
-        if let Some(instant) = self.0.get(id) {
-            let dur = Instant::now().saturating_duration_since(*instant);
-            self.0.remove(id);
-            Some(dur)
-        } else {
-            None
-        }
+    /// Stop the duration tracking of discord member, and returns tracked channel and duration if
+    /// there is any
+    pub fn untrack_voice(&mut self, id: &u64) -> Option<(u64, Duration)> {
+        self.voice.remove(id).map(|mut session| (session.channel, session.flush()))
+    }
+
+    /// Get all tracked streaming durations
+    pub fn track_all_stream(&mut self) -> impl Iterator<Item = (&u64, Duration)> {
+        self.stream.iter_mut().map(|(k, v)| {
+            let new_instant = Instant::now();
+            let dur = new_instant.saturating_duration_since(*v);
+            *v = new_instant;
+            (k, dur)
+        })
+    }
+
+    /// Begin the streaming duration tracking of discord member, if it isn't tracked already
+    pub fn track_stream(&mut self, id: &u64) {
+        self.stream.entry(*id).or_insert_with(Instant::now);
+    }
+
+    /// Stop the streaming duration tracking of discord member, and returns tracked duration if
+    /// there is any
+    pub fn untrack_stream(&mut self, id: &u64) -> Option<Duration> {
+        self.stream.remove(id).map(|instant| Instant::now().saturating_duration_since(instant))
+    }
+}
+
+#[derive(Debug)]
+/// A member's vc session in a channel
+struct VoiceSession {
+    channel: u64,
+    /// Percentage of the duration that is counted
+    weight: u32,
+    /// When the duration was last flushed
+    since: Instant,
+}
+
+impl VoiceSession {
+    /// Get the weighted duration since last flush, and restart the duration tracking
+    fn flush(&mut self) -> Duration {
+        let now = Instant::now();
+        let dur = now.saturating_duration_since(self.since);
+        self.since = now;
+        dur * self.weight / 100
     }
 }
 
@@ -59,6 +101,7 @@ impl Default for VoiceTracker {
     }
 }
 
+#[cfg(feature = "discord")]
 impl TypeMapKey for VoiceTracker {
     type Value = Arc<Mutex<VoiceTracker>>;
 }
@@ -0,0 +1,49 @@
+use std::str::FromStr;
+
+use memberdb::model::guild::GuildRank;
+use memberdb::model::member::{MemberRank, MemberType};
+use memberdb::model::wynn::McId;
+use memberdb::testing::TestDB;
+
+/// 2022-01-01T00:00:00Z
+const JOINED: i64 = 1640995200;
+const DAY: i64 = 86400;
+
+#[tokio::test]
+async fn long_standing_guild_partial_becomes_alumnus_on_leave() {
+    let (db, _events) = TestDB::new()
+        .guild_member("0a1b", "Pucaet", GuildRank::Captain)
+        .guild_member("2c3d", "Jeron", GuildRank::Recruit)
+        .build()
+        .await
+        .unwrap();
+    let veteran = McId("0a1b".to_string());
+    let newcomer = McId("2c3d".to_string());
+    let mut tx = db.begin().await.unwrap();
+    veteran.set_joined(&mut tx, "2022-01-01T00:00:00.000Z").await.unwrap();
+    newcomer.set_joined(&mut tx, "2022-06-01T00:00:00.000Z").await.unwrap();
+    tx.commit().await.unwrap();
+
+    let now = JOINED + 180 * DAY;
+    let mut tx = db.begin().await.unwrap();
+    let mid = veteran.make_alumni(&mut tx, 180 * DAY, now).await.unwrap().unwrap();
+    veteran.bind_guild(&mut tx, "Pucaet", false, GuildRank::Captain).await.unwrap();
+    assert_eq!(newcomer.make_alumni(&mut tx, 180 * DAY, now).await.unwrap(), None);
+    newcomer.bind_guild(&mut tx, "Jeron", false, GuildRank::Recruit).await.unwrap();
+    tx.commit().await.unwrap();
+
+    assert_eq!(mid.kind(&mut db.exe()).await.unwrap(), MemberType::WynnPartial);
+    assert_eq!(mid.rank(&mut db.exe()).await.unwrap(), MemberRank::Alumni);
+    assert_eq!(newcomer.mid(&mut db.exe()).await.unwrap(), None);
+    assert!(memberdb::check_integrity(&db).await.unwrap().is_empty());
+}
+
+#[test]
+fn alumni_is_below_the_promotion_ladder() {
+    assert_eq!(MemberRank::from_str("Alumni").unwrap(), MemberRank::Alumni);
+    assert!(MemberRank::Alumni < MemberRank::Six);
+    assert_eq!(MemberRank::Alumni.promote(), None);
+    assert_eq!(MemberRank::Alumni.demote(), None);
+    assert_eq!(MemberRank::Six.demote(), None);
+    assert_eq!(MemberRank::Five.demote(), Some(MemberRank::Six));
+}
@@ -0,0 +1,40 @@
+use memberdb::model::discord::DiscordId;
+use memberdb::model::member::MemberRank;
+use memberdb::model::wynn::McId;
+use memberdb::testing::TestDB;
+use memberdb::ConcurrentChange;
+
+#[tokio::test]
+async fn change_rank_guard() {
+    let (db, _events) =
+        TestDB::new().full_member(1, "0a1b", "Pucaet", MemberRank::Five).build().await.unwrap();
+    let mid = DiscordId(1).mid(&mut db.exe()).await.unwrap().unwrap();
+
+    // Someone else already changed the rank
+    let mut tx = db.begin().await.unwrap();
+    let err = mid.change_rank(&mut tx, MemberRank::Four, MemberRank::Three, None).await.unwrap_err();
+    assert!(err.is::<ConcurrentChange>());
+    drop(tx);
+    assert_eq!(mid.rank(&mut db.exe()).await.unwrap(), MemberRank::Five);
+
+    let mut tx = db.begin().await.unwrap();
+    mid.change_rank(&mut tx, MemberRank::Five, MemberRank::Four, None).await.unwrap();
+    tx.commit().await.unwrap();
+    assert_eq!(mid.rank(&mut db.exe()).await.unwrap(), MemberRank::Four);
+}
+
+#[tokio::test]
+async fn expect_mid_guard() {
+    let (db, _events) =
+        TestDB::new().full_member(1, "0a1b", "Pucaet", MemberRank::Five).build().await.unwrap();
+    let mid = DiscordId(1).mid(&mut db.exe()).await.unwrap().unwrap();
+
+    DiscordId(1).expect_mid(&mut db.exe(), Some(mid)).await.unwrap();
+    McId("0a1b".to_string()).expect_mid(&mut db.exe(), Some(mid)).await.unwrap();
+    DiscordId(2).expect_mid(&mut db.exe(), None).await.unwrap();
+
+    let err = DiscordId(1).expect_mid(&mut db.exe(), None).await.unwrap_err();
+    assert!(err.is::<ConcurrentChange>());
+    let err = McId("0a1b".to_string()).expect_mid(&mut db.exe(), None).await.unwrap_err();
+    assert!(err.is::<ConcurrentChange>());
+}
@@ -0,0 +1,18 @@
+use std::time::Duration;
+
+use memberdb::{ConnectSettings, DB};
+
+#[tokio::test]
+async fn file_database_uses_wal_journal_by_default() {
+    let dir = std::env::temp_dir().join(format!("memberdb-connect-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let file = dir.join("member.db");
+    let file = file.to_str().unwrap();
+
+    let db = DB::open(file, &ConnectSettings::default(), Duration::from_millis(250)).await;
+    assert!(!db.migrations().await.unwrap().is_empty());
+    assert!(std::path::Path::new(&format!("{}-wal", file)).exists());
+
+    drop(db);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
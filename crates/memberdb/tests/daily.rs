@@ -0,0 +1,40 @@
+use memberdb::events::DBEvent;
+use memberdb::model::guild::GuildRank;
+use memberdb::testing::TestDB;
+
+#[tokio::test]
+async fn daily_reset_reports_and_clears_stats() {
+    let (db, mut events) = TestDB::new()
+        .guild_member("0a1b", "Pucaet", GuildRank::Recruit)
+        .guild_member("2c3d", "Jeron", GuildRank::Captain)
+        .build()
+        .await
+        .unwrap();
+
+    let mut tx = db.begin().await.unwrap();
+    memberdb::update_daily_xp(&mut tx, 1000).await.unwrap();
+    memberdb::update_daily_message(&mut tx, 3).await.unwrap();
+    memberdb::add_daily_join(&mut tx).await.unwrap();
+    memberdb::add_daily_leave(&mut tx).await.unwrap();
+    memberdb::update_daily_online(&mut tx, &["Pucaet", "Jeron", "NotInGuild"]).await.unwrap();
+    memberdb::update_daily_online(&mut tx, &["Pucaet"]).await.unwrap();
+    tx.commit().await.unwrap();
+
+    memberdb::daily_reset(&db).await.unwrap();
+    let summary = loop {
+        if let DBEvent::DailyReset { summary } = events.recv().await.unwrap().as_ref() {
+            break summary.clone();
+        }
+    };
+    assert_eq!(summary.xp, 1000);
+    assert_eq!(summary.message, 3);
+    assert_eq!(summary.online, 2);
+    assert_eq!(summary.online_peak, 2);
+    assert_eq!(summary.joins, 1);
+    assert_eq!(summary.leaves, 1);
+
+    let summary = memberdb::daily_summary(&db).await.unwrap();
+    assert_eq!(summary.xp, 0);
+    assert_eq!(summary.online, 0);
+    assert_eq!(summary.online_peak, 0);
+}
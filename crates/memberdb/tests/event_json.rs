@@ -0,0 +1,24 @@
+use serde_json::json;
+
+use memberdb::events::DBEvent;
+use memberdb::model::member::{MemberId, MemberRank};
+
+#[test]
+fn db_event_serializes_with_type_tag() {
+    let event = DBEvent::MemberRankChange { mid: MemberId(1), old: MemberRank::Five, new: MemberRank::Four };
+    assert_eq!(
+        serde_json::to_value(&event).unwrap(),
+        json!({"type": "MemberRankChange", "mid": 1, "old": "Five", "new": "Four"})
+    );
+}
+
+#[test]
+fn db_event_kind_matches_type_tag() {
+    let events = [
+        DBEvent::MemberRankChange { mid: MemberId(1), old: MemberRank::Five, new: MemberRank::Four },
+        DBEvent::MemberRemove { mid: MemberId(1), discord_id: None, mcid: None },
+    ];
+    for event in events {
+        assert_eq!(serde_json::to_value(&event).unwrap()["type"], event.kind());
+    }
+}
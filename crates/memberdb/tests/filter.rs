@@ -0,0 +1,151 @@
+#![cfg(feature = "discord")]
+
+use std::cmp::Ordering;
+use std::str::FromStr;
+
+use serenity::client::Cache;
+
+use memberdb::model::db::Stat;
+use memberdb::model::discord::DiscordId;
+use memberdb::model::guild::GuildRank;
+use memberdb::model::member::MemberRank;
+use memberdb::model::wynn::McId;
+use memberdb::query_builder::{Filter, StatOperand};
+use memberdb::testing::TestDB;
+use util::string::StatFormat;
+
+#[test]
+fn range_filter_is_parsed() {
+    assert_eq!(Filter::from_str("xp:1m..5m").unwrap(), Filter::StatRange(Stat::Xp, 1_000_000, 5_000_000));
+    assert_eq!(Filter::from_str("voice:1h..PT2H").unwrap(), Filter::StatRange(Stat::Voice, 3600, 7200));
+    assert_eq!(Filter::from_str(">xp:1m").unwrap(), Filter::Stat(Stat::Xp, 1_000_000, Ordering::Greater));
+    assert!(Filter::from_str("xp:5m..1m").is_err());
+    assert!(Filter::from_str(">xp:1m..5m").is_err());
+    assert!(Filter::from_str("xp:1m..").is_err());
+}
+
+#[tokio::test]
+async fn range_filter_keeps_values_within_range() {
+    let (db, _events) = TestDB::new()
+        .guild_member("0a1b", "Pucaet", GuildRank::Recruit)
+        .guild_member("2c3d", "Jeron", GuildRank::Recruit)
+        .guild_member("4e5f", "SephDark18", GuildRank::Recruit)
+        .guild_member("6a7b", "Nikus", GuildRank::Recruit)
+        .build()
+        .await
+        .unwrap();
+    let mut tx = db.begin().await.unwrap();
+    McId("0a1b".to_string()).update_xp(&mut tx, 500).await.unwrap();
+    McId("2c3d".to_string()).update_xp(&mut tx, 2_000_000).await.unwrap();
+    McId("4e5f".to_string()).update_xp(&mut tx, 5_000_000).await.unwrap();
+    McId("6a7b".to_string()).update_xp(&mut tx, 9_000_000).await.unwrap();
+    tx.commit().await.unwrap();
+
+    let filters = vec![Filter::from_str("xp:1m..5m").unwrap()];
+    let (table, _) = memberdb::table::stat_leaderboard(
+        &Cache::default(),
+        &StatFormat::default(),
+        &db,
+        &Stat::Xp,
+        &filters,
+    )
+    .await
+    .unwrap();
+    let mut names = table.iter().map(|row| row[1].as_str()).collect::<Vec<_>>();
+    names.sort_unstable();
+    assert_eq!(names, vec!["Jeron", "SephDark18"]);
+}
+
+#[tokio::test]
+async fn rank_filter_matches_alumni() {
+    let (db, _events) = TestDB::new()
+        .full_member(1, "0a1b", "Pucaet", MemberRank::Six)
+        .full_member(2, "2c3d", "Jeron", MemberRank::Alumni)
+        .build()
+        .await
+        .unwrap();
+    let mut tx = db.begin().await.unwrap();
+    DiscordId(1).update_voice(&mut tx, 10).await.unwrap();
+    DiscordId(2).update_voice(&mut tx, 10).await.unwrap();
+    tx.commit().await.unwrap();
+
+    // Rank filters include the rank itself
+    for (filter, expected) in [("Alumni", vec!["Jeron"]), ("<Cadet", vec!["Jeron", "Pucaet"])] {
+        let filters = vec![Filter::from_str(filter).unwrap()];
+        let (table, _) = memberdb::table::stat_leaderboard(
+            &Cache::default(),
+            &StatFormat::default(),
+            &db,
+            &Stat::Voice,
+            &filters,
+        )
+        .await
+        .unwrap();
+        let mut names = table.iter().map(|row| row[1].as_str()).collect::<Vec<_>>();
+        names.sort_unstable();
+        assert_eq!(names, expected, "filter {}", filter);
+    }
+}
+
+#[test]
+fn compare_filter_is_parsed() {
+    assert_eq!(
+        Filter::from_str("voice>online").unwrap(),
+        Filter::StatCompare(Stat::Voice, Ordering::Greater, StatOperand::Stat(Stat::Online))
+    );
+    assert_eq!(
+        Filter::from_str("weekly_xp<avg(weekly_xp)").unwrap(),
+        Filter::StatCompare(Stat::WeeklyXp, Ordering::Less, StatOperand::Avg(Stat::WeeklyXp))
+    );
+    assert!(Filter::from_str("voice>avg(name)").is_err());
+    assert!(Filter::from_str("voice>").is_err());
+}
+
+#[tokio::test]
+async fn compare_filter_compares_against_column_and_average() {
+    let (db, _events) = TestDB::new()
+        .full_member(1, "0a1b", "Pucaet", MemberRank::Five)
+        .full_member(2, "2c3d", "Jeron", MemberRank::Five)
+        .full_member(3, "4e5f", "SephDark18", MemberRank::Five)
+        .build()
+        .await
+        .unwrap();
+    let mut tx = db.begin().await.unwrap();
+    DiscordId(1).update_voice(&mut tx, 100).await.unwrap();
+    McId("0a1b".to_string()).update_activity(&mut tx, 10).await.unwrap();
+    DiscordId(2).update_voice(&mut tx, 10).await.unwrap();
+    McId("2c3d".to_string()).update_activity(&mut tx, 100).await.unwrap();
+    DiscordId(3).update_voice(&mut tx, 40).await.unwrap();
+    McId("4e5f".to_string()).update_activity(&mut tx, 30).await.unwrap();
+    tx.commit().await.unwrap();
+
+    let names = |table: Vec<Vec<String>>| {
+        let mut names = table.into_iter().map(|mut row| row.swap_remove(1)).collect::<Vec<_>>();
+        names.sort_unstable();
+        names
+    };
+    let filters = vec![Filter::from_str("voice>online").unwrap()];
+    let (table, _) = memberdb::table::stat_leaderboard(
+        &Cache::default(),
+        &StatFormat::default(),
+        &db,
+        &Stat::Voice,
+        &filters,
+    )
+    .await
+    .unwrap();
+    assert_eq!(names(table), vec!["Pucaet", "SephDark18"]);
+
+    // Average voice time is 50
+    let filters = vec![Filter::from_str("voice<avg(voice)").unwrap()];
+    let (table, _) = memberdb::table::stat_leaderboard(
+        &Cache::default(),
+        &StatFormat::default(),
+        &db,
+        &Stat::Voice,
+        &filters,
+    )
+    .await
+    .unwrap();
+    assert_eq!(names(table), vec!["Jeron", "SephDark18"]);
+}
@@ -0,0 +1,36 @@
+use memberdb::global_rank::{self, RankSample, SAMPLE_RETENTION};
+use memberdb::testing::memory_db;
+
+fn sample(time: i64, rank: i64) -> RankSample {
+    RankSample { time, rank, level: 90, xp: 0, territories: 0 }
+}
+
+#[tokio::test]
+async fn rank_samples_track_changes() {
+    let (db, _) = memory_db().await.unwrap();
+    assert_eq!(global_rank::rank_change(&db, 0).await.unwrap(), None);
+
+    for (time, rank) in [(0, 30), (3600, 29), (86400, 25), (2 * 86400, 27)] {
+        global_rank::add_rank_sample(&db, &sample(time, rank)).await.unwrap();
+    }
+
+    let change = global_rank::rank_change(&db, 0).await.unwrap().unwrap();
+    assert_eq!((change.from, change.to, change.climbed()), (30, 27, 3));
+    let change = global_rank::rank_change(&db, 86400).await.unwrap().unwrap();
+    assert_eq!(change.climbed(), -2);
+
+    let samples = global_rank::rank_samples(&db, 0).await.unwrap();
+    assert_eq!(global_rank::daily_ranks(&samples, 3 * 86400, 3), vec![29, 25, 27]);
+    // Days without samples are skipped
+    assert_eq!(global_rank::daily_ranks(&samples, 5 * 86400, 5), vec![29, 25, 27]);
+}
+
+#[tokio::test]
+async fn old_rank_samples_expire() {
+    let (db, _) = memory_db().await.unwrap();
+    global_rank::add_rank_sample(&db, &sample(0, 30)).await.unwrap();
+    global_rank::add_rank_sample(&db, &sample(SAMPLE_RETENTION + 1, 20)).await.unwrap();
+
+    let samples = global_rank::rank_samples(&db, 0).await.unwrap();
+    assert_eq!(samples, vec![sample(SAMPLE_RETENTION + 1, 20)]);
+}
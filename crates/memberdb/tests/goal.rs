@@ -0,0 +1,44 @@
+use memberdb::goal;
+use memberdb::model::db::Stat;
+use memberdb::model::discord::DiscordId;
+use memberdb::model::guild::GuildRank;
+use memberdb::model::member::MemberRank;
+use memberdb::model::wynn::McId;
+use memberdb::testing::TestDB;
+
+#[tokio::test]
+async fn reached_goals_are_evaluated() {
+    let (db, _events) = TestDB::new()
+        .full_member(1, "0a1b", "Pucaet", MemberRank::Six)
+        .guild_member("0a1b", "Pucaet", GuildRank::Recruit)
+        .build()
+        .await
+        .unwrap();
+    let mid = DiscordId(1).mid(&mut db.exe()).await.unwrap().unwrap();
+
+    assert!(goal::parse_goal_stat("xp").is_err());
+    let mut tx = db.begin().await.unwrap();
+    goal::set_goal(&mut tx, mid, &Stat::WeeklyXp, 1000).await.unwrap();
+    goal::set_goal(&mut tx, mid, &Stat::WeeklyMessage, 10).await.unwrap();
+    assert!(goal::set_goal(&mut tx, mid, &Stat::Xp, 10).await.is_err());
+    McId("0a1b".to_string()).update_xp(&mut tx, 1500).await.unwrap();
+    tx.commit().await.unwrap();
+
+    let goals = mid.goals(&mut db.exe()).await.unwrap();
+    assert_eq!(
+        goals.iter().map(|goal| (goal.stat.clone(), goal.progress)).collect::<Vec<_>>(),
+        vec![(Stat::WeeklyMessage, 0), (Stat::WeeklyXp, 1500)]
+    );
+
+    let achieved = goal::evaluate_goals(&db).await.unwrap();
+    assert_eq!(achieved.len(), 1);
+    assert_eq!(achieved[0].goal.stat, Stat::WeeklyXp);
+    assert_eq!(achieved[0].discord, Some(DiscordId(1)));
+    assert_eq!(achieved[0].ign.as_deref(), Some("Pucaet"));
+
+    let mut tx = db.begin().await.unwrap();
+    assert!(goal::remove_goal(&mut tx, mid, &Stat::WeeklyXp).await.unwrap());
+    assert!(!goal::remove_goal(&mut tx, mid, &Stat::WeeklyXp).await.unwrap());
+    tx.commit().await.unwrap();
+    assert!(goal::evaluate_goals(&db).await.unwrap().is_empty());
+}
@@ -0,0 +1,86 @@
+#![cfg(feature = "discord")]
+
+use std::str::FromStr;
+
+use serenity::client::Cache;
+
+use memberdb::model::db::Column;
+use memberdb::model::guild::GuildRank;
+use memberdb::model::wynn::McId;
+use memberdb::query_builder::{Aggregate, Filter, GroupBy, QueryMod, Selectables};
+use memberdb::testing::TestDB;
+use util::string::StatFormat;
+
+#[test]
+fn only_stats_can_be_aggregated() {
+    assert!(Aggregate::from_str("count").is_ok());
+    assert!(Aggregate::from_str("sum(xp)").is_ok());
+    assert!(Aggregate::from_str("avg(online)").is_ok());
+    assert!(Aggregate::from_str("sum(ign)").is_err());
+    assert!(Aggregate::from_str("max(xp)").is_err());
+}
+
+#[tokio::test]
+async fn rows_are_aggregated_per_group() {
+    let (db, _events) = TestDB::new()
+        .guild_member("0a1b", "Pucaet", GuildRank::Recruit)
+        .guild_member("2c3d", "Jeron", GuildRank::Recruit)
+        .guild_member("4e5f", "SephDark18", GuildRank::Chief)
+        .build()
+        .await
+        .unwrap();
+    let mut tx = db.begin().await.unwrap();
+    McId("0a1b".to_string()).update_xp(&mut tx, 1000).await.unwrap();
+    McId("2c3d".to_string()).update_xp(&mut tx, 3000).await.unwrap();
+    McId("4e5f".to_string()).update_xp(&mut tx, 500).await.unwrap();
+    McId("0a1b".to_string()).update_activity(&mut tx, 3600).await.unwrap();
+    McId("2c3d".to_string()).update_activity(&mut tx, 7200).await.unwrap();
+    tx.commit().await.unwrap();
+
+    let cols = ["guild_rank", "count", "sum(xp)", "avg(online)"]
+        .into_iter()
+        .map(|col| Selectables::from_str(col).unwrap())
+        .collect::<Vec<Selectables>>();
+    let actions = vec![QueryMod::Filter(Filter::InGuild), QueryMod::GroupBy(GroupBy(Column::GRank))];
+    let (table, header) =
+        memberdb::table::make_table(&Cache::default(), &StatFormat::default(), &db, &cols, &actions)
+            .await
+            .unwrap();
+    assert_eq!(header, vec!["#", "guild_rank", "count", "sum_xp", "avg_online"]);
+    assert_eq!(
+        table,
+        vec![vec!["1", "Recruit", "2", "4,000", "1h 30m "], vec!["2", "Chief", "1", "500", "0s"]]
+    );
+
+    let summary =
+        memberdb::table::make_table_summary(&Cache::default(), &StatFormat::default(), &db, &cols, &actions)
+            .await
+            .unwrap();
+    assert_eq!(summary, vec!["Σ 2", "", "3", "4,500", ""]);
+}
+
+#[tokio::test]
+async fn member_type_summary_totals_each_type() {
+    let (db, _events) = TestDB::new()
+        .guild_member("0a1b", "Pucaet", GuildRank::Recruit)
+        .guild_member("2c3d", "Jeron", GuildRank::Captain)
+        .build()
+        .await
+        .unwrap();
+    let mut tx = db.begin().await.unwrap();
+    McId("0a1b".to_string()).update_xp(&mut tx, 1000).await.unwrap();
+    McId("2c3d".to_string()).update_xp(&mut tx, 3000).await.unwrap();
+    tx.commit().await.unwrap();
+
+    let (table, header) =
+        memberdb::table::member_type_summary(&Cache::default(), &StatFormat::default(), &db, false)
+            .await
+            .unwrap();
+    assert_eq!(header, vec!["type", "count", "sum_message", "sum_voice", "sum_online", "sum_xp"]);
+    // Both members are of the same type, so its group has the same totals as all members
+    assert_eq!(table.len(), 2);
+    assert_eq!(table[0][1..], table[1][1..]);
+    assert_eq!(table.last().unwrap()[0], "total");
+    assert_eq!(table.last().unwrap()[1], "2");
+    assert_eq!(table.last().unwrap()[5], "4,000");
+}
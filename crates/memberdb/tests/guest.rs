@@ -0,0 +1,35 @@
+use std::collections::HashMap;
+
+use memberdb::model::discord::{DiscordId, UserIds};
+use memberdb::model::guild::GuildRank;
+use memberdb::model::member::{MemberId, MemberType, INIT_MEMBER_RANK};
+use memberdb::testing::TestDB;
+use memberdb::weekly_report::make_report;
+use util::string::StatFormat;
+
+#[tokio::test]
+async fn guest_accrues_stats_without_being_in_requirement_reports() {
+    let (db, _events) =
+        TestDB::new().guild_member("0a1b", "Pucaet", GuildRank::Recruit).build().await.unwrap();
+
+    let mut tx = db.begin().await.unwrap();
+    let mid = MemberId::add_guest(&mut tx, DiscordId(1)).await.unwrap();
+    DiscordId(1).update_message(&mut tx, 3).await.unwrap();
+    tx.commit().await.unwrap();
+
+    assert_eq!(mid.kind(&mut db.exe()).await.unwrap(), MemberType::Guest);
+    assert_eq!(mid.rank(&mut db.exe()).await.unwrap(), INIT_MEMBER_RANK);
+    assert_eq!(DiscordId(1).weekly_message(&mut db.exe()).await.unwrap(), 3);
+    assert!(memberdb::check_integrity(&db).await.unwrap().is_empty());
+
+    let requirements = HashMap::from([(GuildRank::Recruit, 100)]);
+    let report = make_report(&db, &UserIds, &StatFormat::default(), &requirements, 1000).await.unwrap();
+    assert_eq!(report.missed_requirement, vec!["Pucaet"]);
+    assert!(report.met_requirement.is_empty());
+
+    // Unlinking the only profile of a guest removes them
+    let mut tx = db.begin().await.unwrap();
+    assert!(mid.bind_discord(&mut tx, None).await.unwrap());
+    tx.commit().await.unwrap();
+    assert_eq!(DiscordId(1).mid(&mut db.exe()).await.unwrap(), None);
+}
@@ -0,0 +1,38 @@
+use memberdb::events::DBEvent;
+use memberdb::guild_goal;
+use memberdb::testing::memory_db;
+
+#[tokio::test]
+async fn guild_goals_count_xp_until_they_end() {
+    let (db, mut events) = memory_db().await.unwrap();
+
+    let mut tx = db.begin().await.unwrap();
+    let season = guild_goal::add_goal(&mut tx, "Season xp", 1000, None, 0).await.unwrap();
+    let weekend = guild_goal::add_goal(&mut tx, "Weekend xp", 5000, Some(100), 0).await.unwrap();
+    guild_goal::add_xp(&mut tx, 600).await.unwrap();
+    guild_goal::add_xp(&mut tx, 600).await.unwrap();
+    tx.commit().await.unwrap();
+
+    let event = events.recv().await.unwrap();
+    match event.as_ref() {
+        DBEvent::GuildGoalComplete { goal } => {
+            assert_eq!((goal.id, goal.progress), (season, 1200));
+            assert!(goal.completed() && goal.ended.is_some());
+        }
+        event => panic!("Unexpected event {:?}", event),
+    }
+
+    // The weekend goal's deadline already passed, so it isn't progressing
+    let running = guild_goal::running_goals(&mut db.exe()).await.unwrap();
+    assert_eq!(running.iter().map(|goal| (goal.id, goal.progress)).collect::<Vec<_>>(), vec![(weekend, 0)]);
+
+    let expired = guild_goal::end_expired_goals(&db, 100).await.unwrap();
+    assert_eq!(expired.len(), 1);
+    assert_eq!(expired[0].ended, Some(100));
+    assert!(guild_goal::running_goals(&mut db.exe()).await.unwrap().is_empty());
+
+    let mut tx = db.begin().await.unwrap();
+    assert!(guild_goal::remove_goal(&mut tx, weekend).await.unwrap());
+    tx.commit().await.unwrap();
+    assert_eq!(guild_goal::list_goals(&mut db.exe()).await.unwrap().len(), 1);
+}
@@ -0,0 +1,44 @@
+use memberdb::ign_history::{ign_history, previous_owners, record_ign_change, IgnChange};
+use memberdb::model::guild::GuildRank;
+use memberdb::model::wynn::McId;
+use memberdb::testing::TestDB;
+
+#[tokio::test]
+async fn old_ign_finds_previous_owners() {
+    let (db, _events) = TestDB::new()
+        .guild_member("0a1b", "Pucaet", GuildRank::Recruit)
+        .guild_member("2c3d", "Jeron", GuildRank::Recruit)
+        .build()
+        .await
+        .unwrap();
+    let pucaet = McId("0a1b".to_string());
+    let jeron = McId("2c3d".to_string());
+
+    let mut tx = db.begin().await.unwrap();
+    record_ign_change(&mut tx, &pucaet, "Comonaut", "Pucaet", 100).await.unwrap();
+    tx.commit().await.unwrap();
+    assert_eq!(previous_owners(&mut db.exe(), "Comonaut").await.unwrap(), vec![pucaet.clone()]);
+    assert_eq!(previous_owners(&mut db.exe(), "comonaut").await.unwrap(), vec![pucaet.clone()]);
+    assert!(previous_owners(&mut db.exe(), "Pucaet").await.unwrap().is_empty());
+
+    // Ign passed on to another player, most recent owner comes first
+    let mut tx = db.begin().await.unwrap();
+    record_ign_change(&mut tx, &jeron, "Comonaut", "Jeron", 200).await.unwrap();
+    tx.commit().await.unwrap();
+    assert_eq!(previous_owners(&mut db.exe(), "Comonaut").await.unwrap(), vec![jeron, pucaet.clone()]);
+
+    // A player that switched back to an old ign is found by its current ign instead
+    let mut tx = db.begin().await.unwrap();
+    pucaet.set_ign(&mut tx, "Comonaut").await.unwrap();
+    record_ign_change(&mut tx, &pucaet, "Pucaet", "Comonaut", 300).await.unwrap();
+    tx.commit().await.unwrap();
+    assert_eq!(previous_owners(&mut db.exe(), "Comonaut").await.unwrap().len(), 1);
+
+    assert_eq!(
+        ign_history(&mut db.exe(), &pucaet).await.unwrap(),
+        vec![
+            IgnChange { old: "Comonaut".to_string(), new: "Pucaet".to_string(), time: 100 },
+            IgnChange { old: "Pucaet".to_string(), new: "Comonaut".to_string(), time: 300 },
+        ]
+    );
+}
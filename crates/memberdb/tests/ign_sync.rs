@@ -0,0 +1,45 @@
+use memberdb::ign_history::ign_history;
+use memberdb::ign_sync::{account_count, mark_checked, next_account, sync_ign};
+use memberdb::model::guild::GuildRank;
+use memberdb::model::member::MemberRank;
+use memberdb::model::wynn::McId;
+use memberdb::testing::TestDB;
+
+#[tokio::test]
+async fn accounts_are_synced_least_recently_checked_first() {
+    let (db, _events) = TestDB::new()
+        .guild_member("0a1b", "Pucaet", GuildRank::Recruit)
+        .wynn_partial("2c3d", "Jeron", MemberRank::Five)
+        .build()
+        .await
+        .unwrap();
+    let pucaet = McId("0a1b".to_string());
+    let jeron = McId("2c3d".to_string());
+    assert_eq!(account_count(&mut db.exe()).await.unwrap(), 2);
+
+    // Never checked accounts come first
+    assert_eq!(next_account(&mut db.exe()).await.unwrap(), Some((pucaet.clone(), "Pucaet".to_string())));
+    let mut tx = db.begin().await.unwrap();
+    assert!(!sync_ign(&mut tx, &pucaet, "Pucaet", "Pucaet", 100).await.unwrap());
+    tx.commit().await.unwrap();
+    assert_eq!(next_account(&mut db.exe()).await.unwrap(), Some((jeron.clone(), "Jeron".to_string())));
+
+    // A rename is saved and recorded
+    let mut tx = db.begin().await.unwrap();
+    assert!(sync_ign(&mut tx, &jeron, "Jeron", "Comonaut", 200).await.unwrap());
+    tx.commit().await.unwrap();
+    assert_eq!(jeron.ign(&mut db.exe()).await.unwrap(), "Comonaut");
+    let history = ign_history(&mut db.exe(), &jeron).await.unwrap();
+    assert_eq!(
+        history.iter().map(|c| (c.old.as_str(), c.new.as_str(), c.time)).collect::<Vec<_>>(),
+        vec![("Jeron", "Comonaut", 200)]
+    );
+    assert!(ign_history(&mut db.exe(), &pucaet).await.unwrap().is_empty());
+
+    // Checking an account again moves it to the back
+    assert_eq!(next_account(&mut db.exe()).await.unwrap(), Some((pucaet.clone(), "Pucaet".to_string())));
+    let mut tx = db.begin().await.unwrap();
+    mark_checked(&mut tx, &pucaet, 300).await.unwrap();
+    tx.commit().await.unwrap();
+    assert_eq!(next_account(&mut db.exe()).await.unwrap(), Some((jeron, "Comonaut".to_string())));
+}
@@ -0,0 +1,38 @@
+#![cfg(feature = "discord")]
+
+use serenity::client::Cache;
+
+use memberdb::model::discord::DiscordId;
+use memberdb::model::member::MemberRank;
+use memberdb::testing::TestDB;
+
+const RECRUITER: i64 = 658478931682394134;
+
+#[tokio::test]
+async fn recruiter_leaderboard_only_counts_members() {
+    let (db, _events) = TestDB::new()
+        .full_member(RECRUITER, "0a1b", "Pucaet", MemberRank::Three)
+        .discord_partial(1, MemberRank::Five)
+        .discord_partial(2, MemberRank::Five)
+        .discord_partial(3, MemberRank::Five)
+        .build()
+        .await
+        .unwrap();
+
+    let mut tx = db.begin().await.unwrap();
+    DiscordId(1).set_inviter(&mut tx, Some(DiscordId(RECRUITER)), "abc").await.unwrap();
+    DiscordId(2).set_inviter(&mut tx, Some(DiscordId(RECRUITER)), "abc").await.unwrap();
+    DiscordId(3).set_inviter(&mut tx, Some(DiscordId(4)), "def").await.unwrap();
+    // Not a member, so isn't counted
+    DiscordId(5).set_inviter(&mut tx, Some(DiscordId(RECRUITER)), "abc").await.unwrap();
+    // Rejoining overwrites the previous invite
+    DiscordId(2).set_inviter(&mut tx, Some(DiscordId(RECRUITER)), "ghi").await.unwrap();
+    tx.commit().await.unwrap();
+
+    assert_eq!(DiscordId(1).inviter(&mut db.exe()).await.unwrap(), Some(DiscordId(RECRUITER)));
+    assert_eq!(DiscordId(6).inviter(&mut db.exe()).await.unwrap(), None);
+
+    let (table, header) = memberdb::table::recruiter_leaderboard(&Cache::default(), &db).await.unwrap();
+    assert_eq!(header, vec!["#", "name", "recruits"]);
+    assert_eq!(table, vec![vec!["1", "Pucaet", "2"], vec!["2", "4", "1"]]);
+}
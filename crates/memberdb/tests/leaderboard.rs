@@ -0,0 +1,79 @@
+#![cfg(feature = "discord")]
+
+use serenity::client::Cache;
+
+use memberdb::model::db::Stat;
+use memberdb::model::discord::DiscordId;
+use memberdb::model::guild::GuildRank;
+use memberdb::model::member::MemberRank;
+use memberdb::model::wynn::McId;
+use memberdb::testing::TestDB;
+use util::string::StatFormat;
+
+const WEEKLY_STATS: [Stat; 4] = [Stat::WeeklyMessage, Stat::WeeklyVoice, Stat::WeeklyOnline, Stat::WeeklyXp];
+
+fn sorted(mut table: Vec<Vec<String>>) -> Vec<Vec<String>> {
+    table.sort();
+    table
+}
+
+#[tokio::test]
+async fn combined_leaderboards_match_single_leaderboards() {
+    let (db, _events) = TestDB::new()
+        .full_member(1, "0a1b", "Pucaet", MemberRank::Five)
+        .guild_member("0a1b", "Pucaet", GuildRank::Recruit)
+        .full_member(2, "2c3d", "Jeron", MemberRank::Five)
+        .guild_member("2c3d", "Jeron", GuildRank::Captain)
+        .discord_partial(3, MemberRank::Six)
+        .guild_member("4e5f", "SephDark18", GuildRank::Chief)
+        .wynn_partial("6a7b", "Nikus", MemberRank::Six)
+        .build()
+        .await
+        .unwrap();
+
+    let mut tx = db.begin().await.unwrap();
+    DiscordId(1).update_message(&mut tx, 5).await.unwrap();
+    DiscordId(2).update_message(&mut tx, 5).await.unwrap();
+    DiscordId(3).update_message(&mut tx, 2).await.unwrap();
+    DiscordId(3).update_voice(&mut tx, 60).await.unwrap();
+    McId("0a1b".to_string()).update_activity(&mut tx, 100).await.unwrap();
+    McId("6a7b".to_string()).update_activity(&mut tx, 30).await.unwrap();
+    McId("4e5f".to_string()).update_xp(&mut tx, 1000).await.unwrap();
+    McId("0a1b".to_string()).update_xp(&mut tx, 50).await.unwrap();
+    McId("2c3d".to_string()).update_xp(&mut tx, 50).await.unwrap();
+    // Left the guild, so their guild xp isn't on the leaderboard anymore
+    McId("2c3d".to_string()).bind_guild(&mut tx, "Jeron", false, GuildRank::Captain).await.unwrap();
+    tx.commit().await.unwrap();
+
+    let cache = Cache::default();
+    let lbs =
+        memberdb::table::stat_leaderboards(&cache, &StatFormat::default(), &db, &WEEKLY_STATS).await.unwrap();
+    for (stat, (table, header)) in WEEKLY_STATS.iter().zip(lbs) {
+        let (expected, expected_header) =
+            memberdb::table::stat_leaderboard(&cache, &StatFormat::default(), &db, stat, &Vec::new())
+                .await
+                .unwrap();
+        assert_eq!(header, expected_header);
+        assert_eq!(sorted(table), sorted(expected), "{:?} leaderboard", stat);
+    }
+
+    let [message_lb, _, _, xp_lb] =
+        memberdb::table::stat_leaderboards(&cache, &StatFormat::default(), &db, &WEEKLY_STATS).await.unwrap();
+    assert_eq!(
+        sorted(message_lb.0),
+        vec![vec!["1", "Jeron", "5"], vec!["1", "Pucaet", "5"], vec!["3", "", "2"]]
+    );
+    assert_eq!(xp_lb.0, vec![vec!["1", "SephDark18", "1,000"], vec!["2", "Pucaet", "50"]]);
+}
+
+#[tokio::test]
+async fn combined_leaderboards_of_empty_db_are_empty() {
+    let (db, _events) = TestDB::new().discord_partial(1, MemberRank::Six).build().await.unwrap();
+    let lbs =
+        memberdb::table::stat_leaderboards(&Cache::default(), &StatFormat::default(), &db, &WEEKLY_STATS)
+            .await
+            .unwrap();
+    for (table, _) in lbs {
+        assert!(table.is_empty());
+    }
+}
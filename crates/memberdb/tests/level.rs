@@ -0,0 +1,34 @@
+use memberdb::level::{self, XpSample};
+use memberdb::testing::TestDB;
+
+const DAY: i64 = 86400;
+
+fn sample(time: i64, level: i64, xp: f64) -> XpSample {
+    XpSample { time, level, xp }
+}
+
+#[test]
+fn level_eta_counts_level_ups() {
+    let samples = [sample(0, 70, 80.0), sample(DAY, 71, 10.0), sample(2 * DAY, 71, 40.0)];
+    assert_eq!(level::progress_rate(&samples), Some(30.0));
+    assert_eq!(level::level_eta(&samples), Some(2 * DAY));
+    assert_eq!(level::daily_progress(&samples, 2 * DAY + 1, 2), vec![30.0, 30.0]);
+}
+
+#[test]
+fn level_eta_needs_progress() {
+    assert_eq!(level::progress_rate(&[sample(0, 70, 10.0)]), None);
+    assert_eq!(level::level_eta(&[sample(0, 70, 10.0), sample(DAY, 70, 10.0)]), None);
+}
+
+#[tokio::test]
+async fn xp_samples_expire() {
+    let (db, _events) = TestDB::new().build().await.unwrap();
+    level::add_xp_sample(&db, &sample(0, 70, 10.0)).await.unwrap();
+    level::add_xp_sample(&db, &sample(DAY, 70, 20.0)).await.unwrap();
+    assert_eq!(level::xp_samples(&db, 0).await.unwrap().len(), 2);
+
+    level::add_xp_sample(&db, &sample(level::SAMPLE_RETENTION + DAY, 71, 0.0)).await.unwrap();
+    let samples = level::xp_samples(&db, 0).await.unwrap();
+    assert_eq!(samples, vec![sample(DAY, 70, 20.0), sample(level::SAMPLE_RETENTION + DAY, 71, 0.0)]);
+}
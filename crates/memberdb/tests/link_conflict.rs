@@ -0,0 +1,96 @@
+use memberdb::events::DBEvent;
+use memberdb::link_conflict::{self, Conflict, GuildJoin};
+use memberdb::member_exit::{self, ExitReason, ExitSource};
+use memberdb::model::discord::DiscordId;
+use memberdb::model::guild::GuildRank;
+use memberdb::model::member::{MemberRank, MemberType};
+use memberdb::model::wynn::McId;
+use memberdb::testing::TestDB;
+
+fn join() -> GuildJoin {
+    GuildJoin {
+        mcid: McId("0a1b".to_string()),
+        ign: "Pucaet".to_string(),
+        rank: GuildRank::Captain,
+        xp: 500,
+        joined: "2022-11-01T00:00:00.000Z".to_string(),
+        wars: Some(3),
+    }
+}
+
+#[tokio::test]
+async fn join_with_gone_discord_is_held_until_reviewed() {
+    let (db, mut events) =
+        TestDB::new().full_member(100, "0a1b", "Pucaet", MemberRank::Five).build().await.unwrap();
+    let join = join();
+
+    let mut tx = db.begin().await.unwrap();
+    assert!(link_conflict::hold_join(&mut tx, &join, 1000, |_| false).await.unwrap());
+    // Already waiting for review
+    assert!(link_conflict::hold_join(&mut tx, &join, 2000, |_| false).await.unwrap());
+    tx.commit().await.unwrap();
+
+    let conflict = match events.recv().await.unwrap().as_ref() {
+        DBEvent::LinkConflict { conflict } => conflict.clone(),
+        event => panic!("Unexpected event {:?}", event),
+    };
+    assert_eq!(conflict.discord, DiscordId(100));
+    assert_eq!(conflict.conflict, Conflict::DiscordGone { left: None });
+    assert_eq!(link_conflict::pending_conflicts(&mut db.exe()).await.unwrap(), vec![conflict.clone()]);
+    assert!(!join.mcid.in_guild(&mut db.exe()).await.unwrap());
+
+    let mut tx = db.begin().await.unwrap();
+    assert_eq!(link_conflict::keep_link(&mut tx, conflict.id).await.unwrap(), Some(conflict.clone()));
+    assert_eq!(link_conflict::unlink_discord(&mut tx, conflict.id).await.unwrap(), None);
+    tx.commit().await.unwrap();
+
+    assert!(link_conflict::pending_conflicts(&mut db.exe()).await.unwrap().is_empty());
+    assert!(join.mcid.in_guild(&mut db.exe()).await.unwrap());
+    assert_eq!(join.mcid.rank(&mut db.exe()).await.unwrap(), GuildRank::Captain);
+    assert_eq!(join.mcid.xp(&mut db.exe()).await.unwrap(), 500);
+    let mid = join.mcid.mid(&mut db.exe()).await.unwrap().unwrap();
+    assert_eq!(mid.links(&mut db.exe()).await.unwrap().0, Some(DiscordId(100)));
+}
+
+#[tokio::test]
+async fn unlinking_discord_keeps_the_guild_member() {
+    let (db, _events) =
+        TestDB::new().full_member(100, "0a1b", "Pucaet", MemberRank::Five).build().await.unwrap();
+    let join = join();
+
+    let mut tx = db.begin().await.unwrap();
+    assert!(link_conflict::hold_join(&mut tx, &join, 1000, |_| false).await.unwrap());
+    let id = link_conflict::pending_conflicts(&mut tx.exe()).await.unwrap()[0].id;
+    assert!(link_conflict::unlink_discord(&mut tx, id).await.unwrap().is_some());
+    tx.commit().await.unwrap();
+
+    let mid = join.mcid.mid(&mut db.exe()).await.unwrap().unwrap();
+    assert_eq!(mid.links(&mut db.exe()).await.unwrap().0, None);
+    assert_eq!(mid.kind(&mut db.exe()).await.unwrap(), MemberType::GuildPartial);
+    assert!(join.mcid.in_guild(&mut db.exe()).await.unwrap());
+}
+
+#[tokio::test]
+async fn join_after_discord_change_is_held() {
+    let (db, _events) =
+        TestDB::new().full_member(200, "0a1b", "Pucaet", MemberRank::Five).build().await.unwrap();
+    let join = join();
+    let mid = join.mcid.mid(&mut db.exe()).await.unwrap().unwrap();
+
+    let mut tx = db.begin().await.unwrap();
+    // Discord users in the server with a consistent history don't hold the join back
+    assert!(!link_conflict::hold_join(&mut tx, &join, 500, |_| true).await.unwrap());
+    member_exit::record_exit(&mut tx, mid, ExitSource::Guild, ExitReason::Left, 500).await.unwrap();
+    mid.bind_discord(&mut tx, Some(DiscordId(100))).await.unwrap();
+    assert!(link_conflict::hold_join(&mut tx, &join, 1000, |_| true).await.unwrap());
+    tx.commit().await.unwrap();
+
+    let pending = link_conflict::pending_conflicts(&mut db.exe()).await.unwrap();
+    assert_eq!(pending[0].conflict, Conflict::DiscordChanged { previous: DiscordId(200) });
+
+    // Leaving the guild drops the join
+    let mut tx = db.begin().await.unwrap();
+    assert!(link_conflict::drop_conflicts(&mut tx, &join.mcid).await.unwrap());
+    tx.commit().await.unwrap();
+    assert!(link_conflict::pending_conflicts(&mut db.exe()).await.unwrap().is_empty());
+}
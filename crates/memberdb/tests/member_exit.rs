@@ -0,0 +1,66 @@
+use memberdb::member_exit::{self, ExitReason, ExitSource};
+use memberdb::model::discord::DiscordId;
+use memberdb::model::member::MemberRank;
+use memberdb::model::wynn::McId;
+use memberdb::testing::TestDB;
+
+#[tokio::test]
+async fn exit_records_outlive_the_member() {
+    let (db, _events) = TestDB::new()
+        .full_member(1, "0a1b", "Pucaet", MemberRank::Four)
+        .discord_partial(2, MemberRank::Five)
+        .build()
+        .await
+        .unwrap();
+    let pucaet = DiscordId(1).mid(&mut db.exe()).await.unwrap().unwrap();
+    let partial = DiscordId(2).mid(&mut db.exe()).await.unwrap().unwrap();
+
+    let mut tx = db.begin().await.unwrap();
+    let left =
+        member_exit::record_exit(&mut tx, pucaet, ExitSource::Guild, ExitReason::Left, 1000).await.unwrap();
+    let kicked = member_exit::record_exit(&mut tx, partial, ExitSource::Discord, ExitReason::Kicked, 2000)
+        .await
+        .unwrap();
+    partial.remove(&mut tx).await.unwrap();
+    tx.commit().await.unwrap();
+
+    assert_eq!(left.discord, Some(DiscordId(1)));
+    assert_eq!(left.mcid, Some(McId("0a1b".to_string())));
+    assert_eq!(left.ign.as_deref(), Some("Pucaet"));
+    assert_eq!(left.rank, MemberRank::Four);
+    assert_eq!((kicked.discord, kicked.mcid.clone(), kicked.ign.clone()), (Some(DiscordId(2)), None, None));
+
+    assert_eq!(
+        member_exit::list_exits(&mut db.exe(), None).await.unwrap(),
+        vec![kicked.clone(), left.clone()]
+    );
+    assert_eq!(member_exit::list_exits(&mut db.exe(), Some(ExitReason::Kicked)).await.unwrap(), vec![kicked]);
+    assert!(member_exit::list_exits(&mut db.exe(), Some(ExitReason::Purged)).await.unwrap().is_empty());
+    assert_eq!(member_exit::get_exit(&mut db.exe(), left.id).await.unwrap(), Some(left));
+}
+
+#[tokio::test]
+async fn exit_response_is_only_recorded_once_asked() {
+    let (db, _events) = TestDB::new().discord_partial(1, MemberRank::Five).build().await.unwrap();
+    let mid = DiscordId(1).mid(&mut db.exe()).await.unwrap().unwrap();
+
+    let mut tx = db.begin().await.unwrap();
+    let exit =
+        member_exit::record_exit(&mut tx, mid, ExitSource::Discord, ExitReason::Left, 1000).await.unwrap();
+    // Not asked yet
+    assert_eq!(member_exit::record_exit_response(&mut tx, DiscordId(1), "Hi", 0).await.unwrap(), None);
+    member_exit::set_exit_question(&mut tx, exit.id, "Why did you leave?").await.unwrap();
+    // Left before the response window
+    assert_eq!(member_exit::record_exit_response(&mut tx, DiscordId(1), "Hi", 1001).await.unwrap(), None);
+    assert_eq!(
+        member_exit::record_exit_response(&mut tx, DiscordId(1), "Too busy", 500).await.unwrap(),
+        Some(exit.id)
+    );
+    // Already responded
+    assert_eq!(member_exit::record_exit_response(&mut tx, DiscordId(1), "Hi", 500).await.unwrap(), None);
+    tx.commit().await.unwrap();
+
+    let exit = member_exit::get_exit(&mut db.exe(), exit.id).await.unwrap().unwrap();
+    assert_eq!(exit.question.as_deref(), Some("Why did you leave?"));
+    assert_eq!(exit.response.as_deref(), Some("Too busy"));
+}
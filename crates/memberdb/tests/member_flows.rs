@@ -0,0 +1,134 @@
+#![cfg(feature = "discord")]
+
+use serenity::client::Cache;
+
+use memberdb::events::DBEvent;
+use memberdb::model::discord::DiscordId;
+use memberdb::model::guild::GuildRank;
+use memberdb::model::member::{MemberRank, MemberType};
+use memberdb::model::wynn::McId;
+use memberdb::testing::TestDB;
+use util::string::StatFormat;
+
+const MCID: &str = "0a1b2c3d4e5f";
+const IGN: &str = "Pucaet";
+const DISCORD: i64 = 658478931682394134;
+
+fn mcid() -> McId {
+    McId(MCID.to_string())
+}
+
+#[tokio::test]
+async fn guild_join_and_leave_adds_and_removes_guild_partial() {
+    let (db, _events) = TestDB::new().build().await.unwrap();
+
+    let mut tx = db.begin().await.unwrap();
+    let mid = mcid().bind_guild(&mut tx, IGN, true, GuildRank::Captain).await.unwrap().unwrap();
+    tx.commit().await.unwrap();
+    assert_eq!(mid.kind(&mut db.exe()).await.unwrap(), MemberType::GuildPartial);
+    assert_eq!(mid.rank(&mut db.exe()).await.unwrap(), GuildRank::Captain.to_member_rank());
+
+    let mut tx = db.begin().await.unwrap();
+    assert!(mcid().bind_guild(&mut tx, IGN, false, GuildRank::Captain).await.unwrap().is_none());
+    tx.commit().await.unwrap();
+    assert!(!mid.exist(&mut db.exe()).await.unwrap());
+    assert!(!mcid().in_guild(&mut db.exe()).await.unwrap());
+}
+
+#[tokio::test]
+async fn guild_leave_keeps_full_member() {
+    let (db, _events) = TestDB::new()
+        .full_member(DISCORD, MCID, IGN, MemberRank::Five)
+        .guild_member(MCID, IGN, GuildRank::Recruit)
+        .build()
+        .await
+        .unwrap();
+    let mid = mcid().mid(&mut db.exe()).await.unwrap().unwrap();
+
+    let mut tx = db.begin().await.unwrap();
+    mcid().bind_guild(&mut tx, IGN, false, GuildRank::Recruit).await.unwrap();
+    tx.commit().await.unwrap();
+
+    assert_eq!(mid.kind(&mut db.exe()).await.unwrap(), MemberType::Full);
+    assert!(!mcid().in_guild(&mut db.exe()).await.unwrap());
+}
+
+#[tokio::test]
+async fn wynn_unbind_demotes_full_member_in_guild() {
+    let (db, _events) = TestDB::new()
+        .full_member(DISCORD, MCID, IGN, MemberRank::Five)
+        .guild_member(MCID, IGN, GuildRank::Recruit)
+        .build()
+        .await
+        .unwrap();
+    let mid = mcid().mid(&mut db.exe()).await.unwrap().unwrap();
+
+    let mut tx = db.begin().await.unwrap();
+    assert!(mid.bind_wynn(&mut tx, None, IGN).await.unwrap());
+    tx.commit().await.unwrap();
+
+    assert_eq!(mid.kind(&mut db.exe()).await.unwrap(), MemberType::GuildPartial);
+    assert_eq!(mid.links(&mut db.exe()).await.unwrap(), (None, Some(mcid())));
+    assert!(DiscordId(DISCORD).mid(&mut db.exe()).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn wynn_unbind_removes_wynn_partial_not_in_guild() {
+    let (db, _events) = TestDB::new().wynn_partial(MCID, IGN, MemberRank::Six).build().await.unwrap();
+    let mid = mcid().mid(&mut db.exe()).await.unwrap().unwrap();
+
+    let mut tx = db.begin().await.unwrap();
+    assert!(mid.bind_wynn(&mut tx, None, IGN).await.unwrap());
+    tx.commit().await.unwrap();
+
+    assert!(!mid.exist(&mut db.exe()).await.unwrap());
+    assert!(mcid().mid(&mut db.exe()).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn wynn_bind_promotes_discord_partial() {
+    let (db, _events) = TestDB::new().discord_partial(DISCORD, MemberRank::Six).build().await.unwrap();
+    let mid = DiscordId(DISCORD).mid(&mut db.exe()).await.unwrap().unwrap();
+
+    let mut tx = db.begin().await.unwrap();
+    assert!(!mid.bind_wynn(&mut tx, Some(&mcid()), IGN).await.unwrap());
+    tx.commit().await.unwrap();
+
+    assert_eq!(mid.kind(&mut db.exe()).await.unwrap(), MemberType::Full);
+    assert_eq!(mcid().ign(&mut db.exe()).await.unwrap(), IGN);
+}
+
+#[tokio::test]
+async fn weekly_reset_only_clears_weekly_stats() {
+    let (db, _events) = TestDB::new().discord_partial(DISCORD, MemberRank::Six).build().await.unwrap();
+    let id = DiscordId(DISCORD);
+
+    let mut tx = db.begin().await.unwrap();
+    id.update_message(&mut tx, 3).await.unwrap();
+    id.update_voice(&mut tx, 120).await.unwrap();
+    tx.commit().await.unwrap();
+
+    memberdb::weekly_reset(&db, &Cache::new(), &StatFormat::default()).await.unwrap();
+
+    assert_eq!(id.message(&mut db.exe()).await.unwrap(), 3);
+    assert_eq!(id.weekly_message(&mut db.exe()).await.unwrap(), 0);
+    assert_eq!(id.voice_time(&mut db.exe()).await.unwrap(), 120);
+    assert_eq!(id.weekly_voice_time(&mut db.exe()).await.unwrap(), 0);
+}
+
+#[tokio::test]
+async fn discord_unbind_broadcasts_member_remove() {
+    let (db, mut events) = TestDB::new().discord_partial(DISCORD, MemberRank::Six).build().await.unwrap();
+    let mid = DiscordId(DISCORD).mid(&mut db.exe()).await.unwrap().unwrap();
+
+    let mut tx = db.begin().await.unwrap();
+    assert!(mid.bind_discord(&mut tx, None).await.unwrap());
+    tx.commit().await.unwrap();
+
+    assert!(matches!(
+        events.try_recv().unwrap().as_ref(),
+        DBEvent::MemberRemove { mid: removed, .. } if *removed == mid
+    ));
+    assert!(matches!(events.try_recv().unwrap().as_ref(), DBEvent::DiscordProfileUnbind { removed: true, .. }));
+    assert!(events.try_recv().is_err());
+}
@@ -0,0 +1,67 @@
+#![cfg(feature = "discord")]
+
+use serenity::client::Cache;
+
+use memberdb::message_log;
+use memberdb::model::db::Column;
+use memberdb::model::discord::DiscordId;
+use memberdb::model::member::MemberRank;
+use memberdb::testing::TestDB;
+use util::string::StatFormat;
+
+const DISCORD: i64 = 658478931682394134;
+const CHANNEL: u64 = 1000;
+
+#[tokio::test]
+async fn last_message_is_latest_logged_message() {
+    let (db, _events) = TestDB::new().discord_partial(DISCORD, MemberRank::Six).build().await.unwrap();
+    let mid = DiscordId(DISCORD).mid(&mut db.exe()).await.unwrap().unwrap();
+    assert_eq!(message_log::last_message_at(&mut db.exe(), mid).await.unwrap(), None);
+
+    let mut tx = db.begin().await.unwrap();
+    message_log::add_message(&mut tx, mid, CHANNEL, 200).await.unwrap();
+    message_log::add_message(&mut tx, mid, CHANNEL, 100).await.unwrap();
+    tx.commit().await.unwrap();
+    assert_eq!(message_log::last_message_at(&mut db.exe(), mid).await.unwrap(), Some(200));
+
+    let cols = vec![Column::DLastMessage];
+    let (rows, header) = memberdb::table::make_table(
+        &Cache::default(),
+        &StatFormat::default(),
+        &db,
+        &cols,
+        &Vec::<Column>::new(),
+    )
+    .await
+    .unwrap();
+    assert_eq!(header, vec!["#", "last_message"]);
+    assert_eq!(rows, vec![vec!["1", "1970-01-01 00:03"]]);
+}
+
+#[tokio::test]
+async fn expired_messages_are_pruned() {
+    let (db, _events) = TestDB::new().discord_partial(DISCORD, MemberRank::Six).build().await.unwrap();
+    let mid = DiscordId(DISCORD).mid(&mut db.exe()).await.unwrap().unwrap();
+
+    let mut tx = db.begin().await.unwrap();
+    message_log::add_message(&mut tx, mid, CHANNEL, 100).await.unwrap();
+    message_log::add_message(&mut tx, mid, CHANNEL, 300).await.unwrap();
+    tx.commit().await.unwrap();
+
+    assert_eq!(message_log::prune_messages(&db, 200).await.unwrap(), 1);
+    assert_eq!(message_log::last_message_at(&mut db.exe(), mid).await.unwrap(), Some(300));
+    assert_eq!(message_log::prune_messages(&db, 400).await.unwrap(), 1);
+    assert_eq!(message_log::last_message_at(&mut db.exe(), mid).await.unwrap(), None);
+}
+
+#[tokio::test]
+async fn removed_member_has_no_logged_messages() {
+    let (db, _events) = TestDB::new().discord_partial(DISCORD, MemberRank::Six).build().await.unwrap();
+    let mid = DiscordId(DISCORD).mid(&mut db.exe()).await.unwrap().unwrap();
+
+    let mut tx = db.begin().await.unwrap();
+    message_log::add_message(&mut tx, mid, CHANNEL, 100).await.unwrap();
+    mid.remove(&mut tx).await.unwrap();
+    tx.commit().await.unwrap();
+    assert_eq!(message_log::last_message_at(&mut db.exe(), mid).await.unwrap(), None);
+}
@@ -0,0 +1,24 @@
+use memberdb::model::discord::DiscordId;
+use memberdb::model::member::MemberRank;
+use memberdb::testing::TestDB;
+
+#[tokio::test]
+async fn weighted_messages_carry_fractions_over() {
+    let (db, _events) = TestDB::new().discord_partial(1, MemberRank::Six).build().await.unwrap();
+    let id = DiscordId(1);
+
+    let mut tx = db.begin().await.unwrap();
+    assert_eq!(id.update_weighted_message(&mut tx, 40).await.unwrap(), 0);
+    assert_eq!(id.update_weighted_message(&mut tx, 40).await.unwrap(), 0);
+    assert_eq!(id.update_weighted_message(&mut tx, 40).await.unwrap(), 1);
+    assert_eq!(id.update_weighted_message(&mut tx, 0).await.unwrap(), 0);
+    assert_eq!(id.update_weighted_message(&mut tx, 250).await.unwrap(), 2);
+    tx.commit().await.unwrap();
+
+    // 40 + 40 + 40 + 0 + 250 = 370 hundredths
+    assert_eq!(id.message(&mut db.exe()).await.unwrap(), 3);
+    assert_eq!(id.weekly_message(&mut db.exe()).await.unwrap(), 3);
+    let mut tx = db.begin().await.unwrap();
+    assert_eq!(id.update_weighted_message(&mut tx, 30).await.unwrap(), 1);
+    tx.commit().await.unwrap();
+}
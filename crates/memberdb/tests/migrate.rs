@@ -0,0 +1,90 @@
+use std::collections::HashMap;
+
+use memberdb::migrate::{export, import, parse_export, plan_import};
+use memberdb::model::discord::DiscordId;
+use memberdb::model::member::{MemberRank, MemberType};
+use memberdb::model::wynn::McId;
+use memberdb::testing::TestDB;
+
+const EXPORT: &str = r#"[
+    {"discord": 1, "ign": "Pucaet", "rank": "Chief", "message": 10, "online": 60},
+    {"discord": 2, "rank": "Cosmonaut", "voice": 30},
+    {"ign": "Unknown", "rank": "Recruit"},
+    {"discord": 3, "rank": "Emperor"},
+    {"discord": 4, "rank": "Recruit"}
+]"#;
+
+#[tokio::test]
+async fn legacy_import_skips_invalid_entries() {
+    let (db, _events) = TestDB::new().discord_partial(4, MemberRank::Six).build().await.unwrap();
+    let mcids = HashMap::from([("pucaet".to_string(), "0a1b".to_string())]);
+
+    let plan = plan_import(&db, parse_export(EXPORT).unwrap(), &mcids).await.unwrap();
+    assert_eq!(plan.members.len(), 2);
+    assert_eq!(plan.skipped.len(), 3);
+
+    assert_eq!(import(&db, &plan).await.unwrap(), 2);
+    let mid = McId("0a1b".to_string()).mid(&mut db.exe()).await.unwrap().unwrap();
+    assert_eq!(mid.kind(&mut db.exe()).await.unwrap(), MemberType::Full);
+    assert_eq!(DiscordId(1).message(&mut db.exe()).await.unwrap(), 10);
+    assert_eq!(DiscordId(1).weekly_message(&mut db.exe()).await.unwrap(), 0);
+    assert_eq!(DiscordId(2).voice_time(&mut db.exe()).await.unwrap(), 30);
+}
+
+#[tokio::test]
+async fn export_can_be_imported_into_another_database() {
+    let (db, _events) = TestDB::new()
+        .full_member(1, "0a1b", "Pucaet", MemberRank::Five)
+        .discord_partial(2, MemberRank::Six)
+        .wynn_partial("2c3d", "Jeron", MemberRank::Six)
+        .build()
+        .await
+        .unwrap();
+    let mut tx = db.begin().await.unwrap();
+    DiscordId(1).update_message(&mut tx, 10).await.unwrap();
+    DiscordId(2).update_voice(&mut tx, 30).await.unwrap();
+    McId("2c3d".to_string()).update_activity(&mut tx, 60).await.unwrap();
+    tx.commit().await.unwrap();
+
+    let members = export(&db).await.unwrap();
+    assert_eq!(members.len(), 3);
+    assert_eq!(members[0].mcid.as_deref(), Some("0a1b"));
+    assert_eq!(members[0].rank, MemberRank::Five.to_string());
+    let json = serde_json::to_string(&members).unwrap();
+
+    let (other, _events) = TestDB::new().build().await.unwrap();
+    // Igns are resolved by the exported mcids
+    let plan = plan_import(&other, parse_export(&json).unwrap(), &HashMap::new()).await.unwrap();
+    assert!(plan.skipped.is_empty(), "{:?}", plan.skipped);
+    assert_eq!(import(&other, &plan).await.unwrap(), 3);
+
+    let mid = McId("0a1b".to_string()).mid(&mut other.exe()).await.unwrap().unwrap();
+    assert_eq!(mid.kind(&mut other.exe()).await.unwrap(), MemberType::Full);
+    assert_eq!(DiscordId(1).message(&mut other.exe()).await.unwrap(), 10);
+    assert_eq!(DiscordId(2).voice_time(&mut other.exe()).await.unwrap(), 30);
+    assert_eq!(McId("2c3d".to_string()).online_time(&mut other.exe()).await.unwrap(), 60);
+}
+
+#[tokio::test]
+async fn legacy_import_keeps_xp_of_players_outside_the_guild() {
+    let (db, _events) = TestDB::new().build().await.unwrap();
+    let export_json = r#"[
+        {"discord": 1, "ign": "Pucaet", "mcid": "0a1b", "rank": "Chief", "xp": 1000},
+        {"ign": "Jeron", "mcid": "2c3d", "rank": "Recruit"}
+    ]"#;
+
+    let plan = plan_import(&db, parse_export(export_json).unwrap(), &HashMap::new()).await.unwrap();
+    assert_eq!(import(&db, &plan).await.unwrap(), 2);
+    let pucaet = McId("0a1b".to_string());
+    assert!(!pucaet.in_guild(&mut db.exe()).await.unwrap());
+    assert_eq!(pucaet.xp(&mut db.exe()).await.unwrap(), 1000);
+    assert_eq!(pucaet.weekly_xp(&mut db.exe()).await.unwrap(), 0);
+    // No guild profile is created for players without xp
+    assert!(!McId("2c3d".to_string()).guild_exist(&mut db.exe()).await.unwrap());
+
+    let json = serde_json::to_string(&export(&db).await.unwrap()).unwrap();
+    let (other, _events) = TestDB::new().build().await.unwrap();
+    let plan = plan_import(&other, parse_export(&json).unwrap(), &HashMap::new()).await.unwrap();
+    assert_eq!(import(&other, &plan).await.unwrap(), 2);
+    assert_eq!(pucaet.xp(&mut other.exe()).await.unwrap(), 1000);
+}
@@ -0,0 +1,98 @@
+use serde_json::json;
+
+use memberdb::model::db::{Column, Profiles, Stat};
+use memberdb::model::discord::{DiscordId, DiscordProfile};
+use memberdb::model::guild::{GuildProfile, GuildRank};
+use memberdb::model::member::{Member, MemberId, MemberRank, MemberType};
+use memberdb::model::wynn::{McId, WynnProfile};
+
+fn member_json() -> serde_json::Value {
+    json!({"id": 1, "discord": 658478931682394134_i64, "mcid": "0a1b", "member_type": "Full", "rank": "Five"})
+}
+
+fn guild_json() -> serde_json::Value {
+    json!({
+        "id": "0a1b",
+        "mid": 1,
+        "rank": "Captain",
+        "xp": 12000,
+        "xp_week": 300,
+        "joined": "2022-10-01T12:00:00.000Z",
+        "wars": 3
+    })
+}
+
+#[test]
+fn member_serializes_with_stable_names() {
+    let member = Member {
+        id: MemberId(1),
+        discord: Some(DiscordId(658478931682394134)),
+        mcid: Some(McId("0a1b".to_string())),
+        member_type: MemberType::Full,
+        rank: MemberRank::Five,
+    };
+    assert_eq!(serde_json::to_value(&member).unwrap(), member_json());
+
+    let member: Member = serde_json::from_value(member_json()).unwrap();
+    assert_eq!(member.discord, Some(DiscordId(658478931682394134)));
+    assert_eq!(member.rank, MemberRank::Five);
+}
+
+#[test]
+fn ranks_and_stats_deserialize_from_variant_names() {
+    let ranks: Vec<MemberRank> = serde_json::from_value(json!(["Zero", "Six", "Alumni"])).unwrap();
+    assert_eq!(ranks, vec![MemberRank::Zero, MemberRank::Six, MemberRank::Alumni]);
+    let rank: GuildRank = serde_json::from_value(json!("Strategist")).unwrap();
+    assert_eq!(rank, GuildRank::Strategist);
+    let member_type: MemberType = serde_json::from_value(json!("Guest")).unwrap();
+    assert_eq!(member_type, MemberType::Guest);
+    let stat: Stat = serde_json::from_value(json!("WeeklyXp")).unwrap();
+    assert_eq!(stat, Stat::WeeklyXp);
+    assert_eq!(serde_json::to_value(Column::GWeeklyXp).unwrap(), json!("GWeeklyXp"));
+}
+
+#[test]
+fn profiles_round_trip() {
+    let discord = json!({
+        "id": 658478931682394134_i64,
+        "mid": 1,
+        "message": 10,
+        "message_week": 2,
+        "image": 1,
+        "reaction_given": 4,
+        "reaction_received": 5,
+        "voice": 3600,
+        "voice_week": 60,
+        "activity": 0,
+        "stream": 0,
+        "stream_week": 0
+    });
+    let wynn = json!({
+        "id": "0a1b",
+        "mid": 1,
+        "guild": true,
+        "ign": "Pucaet",
+        "emerald": 100,
+        "emerald_week": 10,
+        "activity": 7200,
+        "activity_week": 600,
+        "activity_avg": 300,
+        "activity_avg_range": 7
+    });
+    let profiles = json!({"member": member_json(), "guild": guild_json(), "discord": discord, "wynn": wynn});
+
+    let parsed: Profiles = serde_json::from_value(profiles.clone()).unwrap();
+    assert!(!parsed.is_none());
+    assert_eq!(serde_json::to_value(&parsed).unwrap(), profiles);
+
+    let _: DiscordProfile = serde_json::from_value(profiles["discord"].clone()).unwrap();
+    let _: WynnProfile = serde_json::from_value(profiles["wynn"].clone()).unwrap();
+    let guild: GuildProfile = serde_json::from_value(guild_json()).unwrap();
+    assert_eq!(guild.rank, GuildRank::Captain);
+}
+
+#[test]
+fn missing_profiles_deserialize_as_none() {
+    let profiles: Profiles = serde_json::from_value(json!({})).unwrap();
+    assert!(profiles.is_none());
+}
@@ -0,0 +1,62 @@
+use memberdb::events::DBEvent;
+use memberdb::model::guild::GuildRank;
+use memberdb::online_history::{self, OnlineStats};
+use memberdb::testing::TestDB;
+use memberdb::DailySummary;
+
+#[tokio::test]
+async fn daily_online_average_is_kept_in_history() {
+    let (db, mut events) = TestDB::new()
+        .guild_member("0a1b", "Pucaet", GuildRank::Recruit)
+        .guild_member("2c3d", "Jeron", GuildRank::Captain)
+        .build()
+        .await
+        .unwrap();
+
+    let mut tx = db.begin().await.unwrap();
+    memberdb::update_daily_online(&mut tx, &["Pucaet", "Jeron"]).await.unwrap();
+    memberdb::update_daily_online(&mut tx, &["Pucaet", "NotInGuild"]).await.unwrap();
+    tx.commit().await.unwrap();
+
+    memberdb::daily_reset(&db).await.unwrap();
+    let summary = loop {
+        if let DBEvent::DailyReset { summary } = events.recv().await.unwrap().as_ref() {
+            break summary.clone();
+        }
+    };
+    assert_eq!(summary.online_peak, 2);
+    assert_eq!(summary.avg_online_members, 1.5);
+    assert_eq!(summary.avg_online_ratio, 0.75);
+
+    let stats = online_history::online_stats(&db, 0, i64::MAX).await.unwrap();
+    assert_eq!(stats, OnlineStats { peak_online: 2, avg_online_members: 1.5, avg_online_ratio: 0.75 });
+    let summary = memberdb::daily_summary(&db).await.unwrap();
+    assert_eq!(summary.avg_online_members, 0.0);
+}
+
+#[tokio::test]
+async fn weekly_online_trend_groups_days_by_week() {
+    let (db, _events) = TestDB::new().build().await.unwrap();
+    const WEEK: i64 = 7 * 86400;
+    let now = 10 * WEEK;
+
+    let mut tx = db.begin().await.unwrap();
+    for (time, peak, ratio) in [(now - WEEK - 86400, 4, 0.2), (now - 86400, 6, 0.4), (now, 10, 0.6)] {
+        let summary = DailySummary {
+            online_peak: peak,
+            avg_online_members: peak as f64 / 2.0,
+            avg_online_ratio: ratio,
+            ..Default::default()
+        };
+        online_history::add_online_history(&mut tx, time, &summary).await.unwrap();
+    }
+    tx.commit().await.unwrap();
+
+    let trend = online_history::weekly_online_trend(&db, now, 3).await.unwrap();
+    assert_eq!(trend.len(), 3);
+    assert_eq!(trend[0], OnlineStats::default());
+    assert_eq!(trend[1], OnlineStats { peak_online: 4, avg_online_members: 2.0, avg_online_ratio: 0.2 });
+    assert_eq!(trend[2].peak_online, 10);
+    assert_eq!(trend[2].avg_online_members, 4.0);
+    assert!((trend[2].avg_online_ratio - 0.5).abs() < 1e-9);
+}
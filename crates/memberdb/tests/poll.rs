@@ -0,0 +1,34 @@
+use memberdb::model::discord::DiscordId;
+use memberdb::poll;
+use memberdb::testing::TestDB;
+
+#[tokio::test]
+async fn poll_results_are_kept_after_closing() {
+    let (db, _events) = TestDB::new().build().await.unwrap();
+    let options = vec!["Saturday".to_string(), "Sunday".to_string()];
+
+    let mut tx = db.begin().await.unwrap();
+    let id = poll::open_poll(&mut tx, DiscordId(1), 10, 20, "Event day?", &options, 1000).await.unwrap();
+    tx.commit().await.unwrap();
+
+    let opened = poll::get_poll(&mut db.exe(), id).await.unwrap().unwrap();
+    assert_eq!(opened.question, "Event day?");
+    assert_eq!(opened.options, options);
+    assert!(poll::due_polls(&db, 999).await.unwrap().is_empty());
+    assert_eq!(poll::due_polls(&db, 1000).await.unwrap(), vec![opened]);
+    assert_eq!(
+        poll::poll_results(&mut db.exe(), id).await.unwrap(),
+        vec![("Saturday".to_string(), None), ("Sunday".to_string(), None)]
+    );
+
+    let mut tx = db.begin().await.unwrap();
+    poll::close_poll(&mut tx, id, &[3, 5]).await.unwrap();
+    tx.commit().await.unwrap();
+
+    assert!(poll::due_polls(&db, 2000).await.unwrap().is_empty());
+    assert_eq!(
+        poll::poll_results(&mut db.exe(), id).await.unwrap(),
+        vec![("Saturday".to_string(), Some(3)), ("Sunday".to_string(), Some(5))]
+    );
+    assert!(poll::get_poll(&mut db.exe(), id + 1).await.unwrap().is_none());
+}
@@ -0,0 +1,51 @@
+use memberdb::model::discord::DiscordId;
+use memberdb::model::member::{MemberId, MemberRank};
+use memberdb::testing::TestDB;
+
+const STAFF: i64 = 658478931682394134;
+
+#[tokio::test]
+async fn promotion_vote_lifecycle() {
+    let (db, _events) = TestDB::new()
+        .discord_partial(STAFF, MemberRank::Zero)
+        .full_member(1, "0a1b", "Pucaet", MemberRank::Five)
+        .build()
+        .await
+        .unwrap();
+    let mid = DiscordId(1).mid(&mut db.exe()).await.unwrap().unwrap();
+    assert!(!mid.in_promotion_vote(&mut db.exe()).await.unwrap());
+
+    let mut tx = db.begin().await.unwrap();
+    let id = memberdb::open_promotion_vote(
+        &mut tx,
+        mid,
+        DiscordId(STAFF),
+        MemberRank::Five,
+        MemberRank::Four,
+        10,
+        20,
+        1000,
+    )
+    .await
+    .unwrap();
+    tx.commit().await.unwrap();
+    assert!(mid.in_promotion_vote(&mut db.exe()).await.unwrap());
+    assert!(!MemberId(mid.0 + 100).in_promotion_vote(&mut db.exe()).await.unwrap());
+
+    // Not due before its closing time
+    assert!(memberdb::due_promotion_votes(&db, 999).await.unwrap().is_empty());
+    let due = memberdb::due_promotion_votes(&db, 1000).await.unwrap();
+    assert_eq!(due.len(), 1);
+    assert_eq!(due[0].id, id);
+    assert_eq!(due[0].mid, mid);
+    assert_eq!(due[0].caller, DiscordId(STAFF));
+    assert_eq!(due[0].old_rank, MemberRank::Five);
+    assert_eq!(due[0].rank, MemberRank::Four);
+    assert_eq!((due[0].channel, due[0].message), (10, 20));
+
+    let mut tx = db.begin().await.unwrap();
+    memberdb::close_promotion_vote(&mut tx, id, 3, 1, true).await.unwrap();
+    tx.commit().await.unwrap();
+    assert!(!mid.in_promotion_vote(&mut db.exe()).await.unwrap());
+    assert!(memberdb::due_promotion_votes(&db, 2000).await.unwrap().is_empty());
+}
@@ -0,0 +1,47 @@
+use std::time::Duration;
+
+use memberdb::model::member::{MemberId, MemberRank};
+use memberdb::query_stats::{QueryStats, QueryTiming};
+use memberdb::testing::TestDB;
+
+#[test]
+fn timings_are_aggregated_by_template() {
+    let stats = QueryStats::new(Duration::from_secs(1));
+    stats.record("SELECT id FROM member WHERE id=1", Duration::from_millis(10));
+    stats.record("SELECT id FROM member WHERE id=2", Duration::from_millis(50));
+    stats.record("SELECT id FROM member WHERE id=3", Duration::from_millis(30));
+    stats.record("SELECT\n        ign FROM wynn", Duration::from_millis(40));
+
+    let timings = stats.timings();
+    assert_eq!(
+        timings,
+        vec![
+            (
+                "SELECT ign FROM wynn".to_string(),
+                QueryTiming { count: 1, total: Duration::from_millis(40), max: Duration::from_millis(40) }
+            ),
+            (
+                "SELECT id FROM member WHERE id=?".to_string(),
+                QueryTiming { count: 3, total: Duration::from_millis(90), max: Duration::from_millis(50) }
+            ),
+        ]
+    );
+    assert_eq!(timings[1].1.avg(), Duration::from_millis(30));
+    assert_eq!(QueryTiming::default().avg(), Duration::ZERO);
+}
+
+#[tokio::test]
+async fn executor_queries_are_timed() {
+    let (db, _events) = TestDB::new().discord_partial(1, MemberRank::Six).build().await.unwrap();
+    let before: u64 = db.query_stats().timings().iter().map(|(_, timing)| timing.count).sum();
+
+    MemberId::count(&mut db.exe()).await.unwrap();
+    let mut tx = db.begin().await.unwrap();
+    MemberId::count(&mut tx.exe()).await.unwrap();
+    tx.commit().await.unwrap();
+
+    let timings = db.query_stats().timings();
+    let after: u64 = timings.iter().map(|(_, timing)| timing.count).sum();
+    assert_eq!(after, before + 2);
+    assert!(timings.iter().any(|(sql, timing)| sql.contains("FROM member") && timing.count >= 2));
+}
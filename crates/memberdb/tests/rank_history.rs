@@ -0,0 +1,105 @@
+#![cfg(feature = "discord")]
+
+use serenity::client::Cache;
+
+use memberdb::model::db::Column;
+use memberdb::model::guild::GuildRank;
+use memberdb::model::wynn::McId;
+use memberdb::rank_history::{
+    monthly_rank_changes, rank_history, rank_tenures, record_rank_change, MonthlyRankChanges, RankChange,
+    RankTenure,
+};
+use memberdb::testing::TestDB;
+use util::string::StatFormat;
+
+#[tokio::test]
+async fn rank_changes_are_listed_oldest_first() {
+    let (db, _events) = TestDB::new()
+        .guild_member("0a1b", "Pucaet", GuildRank::Captain)
+        .guild_member("2c3d", "Jeron", GuildRank::Recruit)
+        .build()
+        .await
+        .unwrap();
+    let mcid = McId("0a1b".to_string());
+    assert!(rank_history(&mut db.exe(), &mcid).await.unwrap().is_empty());
+
+    let mut tx = db.begin().await.unwrap();
+    record_rank_change(&mut tx, &mcid, GuildRank::Recruiter, GuildRank::Captain, 200).await.unwrap();
+    record_rank_change(&mut tx, &mcid, GuildRank::Recruit, GuildRank::Recruiter, 100).await.unwrap();
+    tx.commit().await.unwrap();
+
+    assert_eq!(
+        rank_history(&mut db.exe(), &mcid).await.unwrap(),
+        vec![
+            RankChange { old: GuildRank::Recruit, new: GuildRank::Recruiter, time: 100 },
+            RankChange { old: GuildRank::Recruiter, new: GuildRank::Captain, time: 200 },
+        ]
+    );
+    assert!(rank_history(&mut db.exe(), &McId("2c3d".to_string())).await.unwrap().is_empty());
+
+    let cols = vec![Column::GRankTime];
+    let (rows, header) = memberdb::table::make_table(
+        &Cache::default(),
+        &StatFormat::default(),
+        &db,
+        &cols,
+        &Vec::<Column>::new(),
+    )
+    .await
+    .unwrap();
+    assert_eq!(header, vec!["#", "rank_time"]);
+    assert_eq!(rows.len(), 2);
+    assert!(rows.iter().any(|row| !row[1].is_empty()));
+}
+
+#[tokio::test]
+async fn rank_velocity_is_computed_from_rank_changes() {
+    let (db, _events) = TestDB::new()
+        .guild_member("0a1b", "Pucaet", GuildRank::Captain)
+        .guild_member("2c3d", "Jeron", GuildRank::Recruit)
+        .build()
+        .await
+        .unwrap();
+    let pucaet = McId("0a1b".to_string());
+    let jeron = McId("2c3d".to_string());
+    // 2022-10-01 and 2022-11-01
+    let (oct, nov) = (1664582400, 1667260800);
+    let day = 86400;
+
+    let mut tx = db.begin().await.unwrap();
+    record_rank_change(&mut tx, &pucaet, GuildRank::Recruit, GuildRank::Recruiter, oct).await.unwrap();
+    record_rank_change(&mut tx, &pucaet, GuildRank::Recruiter, GuildRank::Captain, oct + 10 * day)
+        .await
+        .unwrap();
+    record_rank_change(&mut tx, &jeron, GuildRank::Recruit, GuildRank::Recruiter, oct + day).await.unwrap();
+    record_rank_change(&mut tx, &jeron, GuildRank::Recruiter, GuildRank::Captain, oct + 21 * day)
+        .await
+        .unwrap();
+    record_rank_change(&mut tx, &jeron, GuildRank::Captain, GuildRank::Recruit, nov + 2 * day).await.unwrap();
+    tx.commit().await.unwrap();
+
+    assert_eq!(
+        rank_tenures(&mut db.exe(), 0).await.unwrap(),
+        vec![
+            RankTenure { rank: GuildRank::Captain, average: 12 * day, samples: 1 },
+            RankTenure { rank: GuildRank::Recruiter, average: 15 * day, samples: 2 },
+        ]
+    );
+    // Changes before `since` aren't counted
+    assert_eq!(
+        rank_tenures(&mut db.exe(), oct + day).await.unwrap(),
+        vec![
+            RankTenure { rank: GuildRank::Captain, average: 12 * day, samples: 1 },
+            RankTenure { rank: GuildRank::Recruiter, average: 20 * day, samples: 1 },
+        ]
+    );
+
+    assert_eq!(
+        monthly_rank_changes(&mut db.exe(), 0).await.unwrap(),
+        vec![
+            MonthlyRankChanges { month: "2022-10".to_string(), promotions: 4, demotions: 0 },
+            MonthlyRankChanges { month: "2022-11".to_string(), promotions: 0, demotions: 1 },
+        ]
+    );
+    assert!(monthly_rank_changes(&mut db.exe(), nov + 3 * day).await.unwrap().is_empty());
+}
@@ -0,0 +1,32 @@
+use memberdb::model::discord::DiscordId;
+use memberdb::model::member::MemberRank;
+use memberdb::testing::TestDB;
+
+#[tokio::test]
+async fn reactions_are_counted_for_both_users() {
+    let (db, _events) = TestDB::new()
+        .discord_partial(1, MemberRank::Six)
+        .discord_partial(2, MemberRank::Six)
+        .build()
+        .await
+        .unwrap();
+    let (reactor, author) = (DiscordId(1), DiscordId(2));
+
+    let mut tx = db.begin().await.unwrap();
+    for _ in 0..3 {
+        reactor.update_reaction_given(&mut tx, 1).await.unwrap();
+        author.update_reaction_received(&mut tx, 1).await.unwrap();
+    }
+    // Removed reactions are uncounted, without going below 0
+    reactor.update_reaction_given(&mut tx, -1).await.unwrap();
+    author.update_reaction_received(&mut tx, -1).await.unwrap();
+    reactor.update_reaction_received(&mut tx, -1).await.unwrap();
+    // Users without a discord profile are skipped
+    DiscordId(3).update_reaction_given(&mut tx, 1).await.unwrap();
+    tx.commit().await.unwrap();
+
+    let reactor = reactor.get(&mut db.exe()).await.unwrap().unwrap();
+    assert_eq!((reactor.reaction_given, reactor.reaction_received), (2, 0));
+    let author = author.get(&mut db.exe()).await.unwrap().unwrap();
+    assert_eq!((author.reaction_given, author.reaction_received), (0, 2));
+}
@@ -0,0 +1,33 @@
+use memberdb::model::discord::DiscordId;
+use memberdb::reminder;
+use memberdb::testing::TestDB;
+
+#[tokio::test]
+async fn reminders_are_due_in_order_and_only_cancellable_by_their_user() {
+    let (db, _events) = TestDB::new().build().await.unwrap();
+    let alice = DiscordId(1);
+    let bob = DiscordId(2);
+
+    let mut tx = db.begin().await.unwrap();
+    let later = reminder::add_reminder(&mut tx, alice, 10, "Check wars", 1000, 5000).await.unwrap();
+    let sooner = reminder::add_reminder(&mut tx, alice, 10, "Join voice", 1000, 2000).await.unwrap();
+    let other = reminder::add_reminder(&mut tx, bob, 20, "Weekly reset", 1000, 3000).await.unwrap();
+    tx.commit().await.unwrap();
+
+    let reminders = reminder::user_reminders(&mut db.exe(), alice).await.unwrap();
+    assert_eq!(reminders.iter().map(|r| r.id).collect::<Vec<i64>>(), vec![sooner, later]);
+    assert_eq!(reminders[0].text, "Join voice");
+
+    let due = reminder::due_reminders(&db, 3000).await.unwrap();
+    assert_eq!(due.iter().map(|r| r.id).collect::<Vec<i64>>(), vec![sooner, other]);
+
+    let mut tx = db.begin().await.unwrap();
+    assert!(!reminder::remove_reminder(&mut tx, other, Some(alice)).await.unwrap());
+    assert!(reminder::remove_reminder(&mut tx, later, Some(alice)).await.unwrap());
+    assert!(reminder::remove_reminder(&mut tx, other, None).await.unwrap());
+    tx.commit().await.unwrap();
+
+    let reminders = reminder::user_reminders(&mut db.exe(), alice).await.unwrap();
+    assert_eq!(reminders.iter().map(|r| r.id).collect::<Vec<i64>>(), vec![sooner]);
+    assert!(reminder::user_reminders(&mut db.exe(), bob).await.unwrap().is_empty());
+}
@@ -0,0 +1,65 @@
+use memberdb::model::discord::DiscordId;
+use memberdb::model::guild::GuildRank;
+use memberdb::model::member::MemberRank;
+use memberdb::model::wynn::McId;
+use memberdb::role_connection::{self, RoleConnection};
+use memberdb::testing::TestDB;
+
+fn conn(user: i64, token: &str) -> RoleConnection {
+    RoleConnection {
+        user: DiscordId(user),
+        access_token: format!("{}-access", token),
+        refresh_token: format!("{}-refresh", token),
+        expires: 1000,
+    }
+}
+
+#[tokio::test]
+async fn role_connections_are_replaced_and_removed() {
+    let (db, _events) = TestDB::new().build().await.unwrap();
+
+    let mut tx = db.begin().await.unwrap();
+    role_connection::set_role_connection(&mut tx, &conn(2, "a")).await.unwrap();
+    role_connection::set_role_connection(&mut tx, &conn(1, "b")).await.unwrap();
+    role_connection::set_role_connection(&mut tx, &conn(2, "c")).await.unwrap();
+    tx.commit().await.unwrap();
+    assert_eq!(role_connection::role_connections(&db).await.unwrap(), vec![conn(1, "b"), conn(2, "c")]);
+
+    let mut tx = db.begin().await.unwrap();
+    assert!(role_connection::remove_role_connection(&mut tx, DiscordId(1)).await.unwrap());
+    assert!(!role_connection::remove_role_connection(&mut tx, DiscordId(1)).await.unwrap());
+    tx.commit().await.unwrap();
+    assert_eq!(role_connection::role_connections(&db).await.unwrap(), vec![conn(2, "c")]);
+}
+
+#[tokio::test]
+async fn role_metadata_reflects_guild_profile() {
+    let (db, _events) = TestDB::new()
+        .full_member(1, "mc1", "Alice", MemberRank::Five)
+        .guild_member("mc1", "Alice", GuildRank::Captain)
+        .full_member(2, "mc2", "Bob", MemberRank::Six)
+        .discord_partial(3, MemberRank::Six)
+        .build()
+        .await
+        .unwrap();
+    let mut tx = db.begin().await.unwrap();
+    McId("mc1".to_string()).update_xp(&mut tx, 1500).await.unwrap();
+    tx.commit().await.unwrap();
+
+    let alice = role_connection::role_metadata(&mut db.exe(), DiscordId(1)).await.unwrap();
+    assert_eq!(alice.ign.as_deref(), Some("Alice"));
+    assert!(alice.in_guild());
+    assert_eq!(alice.rank_level(), 3);
+    assert_eq!(alice.weekly_xp, 1500);
+
+    let bob = role_connection::role_metadata(&mut db.exe(), DiscordId(2)).await.unwrap();
+    assert_eq!(bob.ign.as_deref(), Some("Bob"));
+    assert!(!bob.in_guild());
+    assert_eq!(bob.weekly_xp, 0);
+
+    for id in [3, 4] {
+        let metadata = role_connection::role_metadata(&mut db.exe(), DiscordId(id)).await.unwrap();
+        assert_eq!(metadata.ign, None);
+        assert!(!metadata.in_guild());
+    }
+}
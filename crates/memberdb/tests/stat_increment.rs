@@ -0,0 +1,61 @@
+use memberdb::model::discord::DiscordId;
+use memberdb::model::wynn::McId;
+use memberdb::stat_increment::{self, AuditedStat};
+use memberdb::testing::TestDB;
+
+#[tokio::test]
+async fn increments_are_only_recorded_while_audited() {
+    let (db, _events) = TestDB::new().build().await.unwrap();
+    let pucaet = McId("0a1b".to_string());
+    let discord = DiscordId(1);
+
+    let mut tx = db.begin().await.unwrap();
+    stat_increment::record_wynn(&mut tx, &pucaet, AuditedStat::Xp, 100, "MemberContribute", 10)
+        .await
+        .unwrap();
+    tx.commit().await.unwrap();
+    assert!(stat_increment::increments(&mut db.exe(), None, Some(&pucaet), None, 10)
+        .await
+        .unwrap()
+        .is_empty());
+
+    db.set_stat_audit(true);
+    let mut tx = db.begin().await.unwrap();
+    stat_increment::record_wynn(&mut tx, &pucaet, AuditedStat::Xp, 100, "MemberContribute", 10)
+        .await
+        .unwrap();
+    stat_increment::record_wynn(&mut tx, &pucaet, AuditedStat::Online, 60, "PlayerStay", 20).await.unwrap();
+    stat_increment::record_discord(&mut tx, discord, AuditedStat::Message, 1, "Message", 30).await.unwrap();
+    stat_increment::record_discord(&mut tx, DiscordId(2), AuditedStat::Voice, 60, "VoiceTracker", 40)
+        .await
+        .unwrap();
+    tx.commit().await.unwrap();
+
+    // Newest first, both profiles of the member are included
+    let increments =
+        stat_increment::increments(&mut db.exe(), Some(discord), Some(&pucaet), None, 10).await.unwrap();
+    let summary = increments.iter().map(|i| (i.stat, i.amount, i.source.as_str())).collect::<Vec<_>>();
+    assert_eq!(
+        summary,
+        vec![
+            (AuditedStat::Message, 1, "Message"),
+            (AuditedStat::Online, 60, "PlayerStay"),
+            (AuditedStat::Xp, 100, "MemberContribute")
+        ]
+    );
+    assert_eq!(increments[0].discord, Some(discord));
+    assert_eq!(increments[1].mcid, Some(pucaet.clone()));
+
+    let xp =
+        stat_increment::increments(&mut db.exe(), Some(discord), Some(&pucaet), Some(AuditedStat::Xp), 10)
+            .await
+            .unwrap();
+    assert_eq!(xp.iter().map(|i| i.time).collect::<Vec<_>>(), vec![10]);
+    let latest = stat_increment::increments(&mut db.exe(), None, Some(&pucaet), None, 1).await.unwrap();
+    assert_eq!(latest.iter().map(|i| i.time).collect::<Vec<_>>(), vec![20]);
+
+    assert_eq!(stat_increment::prune_increments(&db, 25).await.unwrap(), 2);
+    let increments =
+        stat_increment::increments(&mut db.exe(), Some(discord), Some(&pucaet), None, 10).await.unwrap();
+    assert_eq!(increments.iter().map(|i| i.time).collect::<Vec<_>>(), vec![30]);
+}
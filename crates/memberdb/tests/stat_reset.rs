@@ -0,0 +1,51 @@
+use memberdb::events::DBEvent;
+use memberdb::model::db::Stat;
+use memberdb::model::discord::DiscordId;
+use memberdb::model::guild::GuildRank;
+use memberdb::model::wynn::McId;
+use memberdb::testing::TestDB;
+
+const STAFF: i64 = 658478931682394134;
+
+#[tokio::test]
+async fn stat_reset_for_member_and_everyone() {
+    let (db, mut events) = TestDB::new()
+        .guild_member("0a1b", "Pucaet", GuildRank::Recruit)
+        .guild_member("2c3d", "Jeron", GuildRank::Recruit)
+        .build()
+        .await
+        .unwrap();
+    let pucaet = McId("0a1b".to_string());
+    let jeron = McId("2c3d".to_string());
+    let mut tx = db.begin().await.unwrap();
+    pucaet.update_xp(&mut tx, 100).await.unwrap();
+    jeron.update_xp(&mut tx, 200).await.unwrap();
+    tx.commit().await.unwrap();
+    while events.try_recv().is_ok() {}
+
+    let mid = pucaet.mid(&mut db.exe()).await.unwrap().unwrap();
+    let mut tx = db.begin().await.unwrap();
+    let count = memberdb::reset_stat(&mut tx, &Stat::WeeklyXp, Some(mid), DiscordId(STAFF), 1).await.unwrap();
+    tx.commit().await.unwrap();
+    assert_eq!(count, 1);
+    assert_eq!(pucaet.weekly_xp(&mut db.exe()).await.unwrap(), 0);
+    assert_eq!(pucaet.xp(&mut db.exe()).await.unwrap(), 100);
+    assert_eq!(jeron.weekly_xp(&mut db.exe()).await.unwrap(), 200);
+    match events.recv().await.unwrap().as_ref() {
+        DBEvent::StatReset { stat, mid: Some(reset_mid), caller } => {
+            assert_eq!(*stat, Stat::WeeklyXp);
+            assert_eq!(*reset_mid, mid);
+            assert_eq!(*caller, DiscordId(STAFF));
+        }
+        event => panic!("Unexpected event {:?}", event),
+    }
+
+    let mut tx = db.begin().await.unwrap();
+    let count = memberdb::reset_stat(&mut tx, &Stat::Xp, None, DiscordId(STAFF), 2).await.unwrap();
+    tx.commit().await.unwrap();
+    assert_eq!(count, 2);
+    assert_eq!(pucaet.xp(&mut db.exe()).await.unwrap(), 0);
+    assert_eq!(jeron.xp(&mut db.exe()).await.unwrap(), 0);
+    assert_eq!(jeron.weekly_xp(&mut db.exe()).await.unwrap(), 200);
+    assert!(matches!(events.recv().await.unwrap().as_ref(), DBEvent::StatReset { mid: None, .. }));
+}
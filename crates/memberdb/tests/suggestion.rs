@@ -0,0 +1,38 @@
+use memberdb::model::discord::DiscordId;
+use memberdb::suggestion::{self, SuggestionStatus};
+use memberdb::testing::TestDB;
+
+#[tokio::test]
+async fn suggestion_votes_toggle_and_are_listed_by_score() {
+    let (db, _events) = TestDB::new().build().await.unwrap();
+
+    let mut tx = db.begin().await.unwrap();
+    let events = suggestion::add_suggestion(&mut tx, DiscordId(1), "Weekly events", 1000).await.unwrap();
+    let wars = suggestion::add_suggestion(&mut tx, DiscordId(2), "More wars", 1000).await.unwrap();
+    suggestion::set_suggestion_message(&mut tx, wars, 10, 20).await.unwrap();
+    suggestion::vote_suggestion(&mut tx, events, DiscordId(3), true).await.unwrap();
+    suggestion::vote_suggestion(&mut tx, wars, DiscordId(3), true).await.unwrap();
+    suggestion::vote_suggestion(&mut tx, wars, DiscordId(4), true).await.unwrap();
+    // Voting the other way replaces the vote, and voting the same way again removes it
+    suggestion::vote_suggestion(&mut tx, events, DiscordId(4), true).await.unwrap();
+    suggestion::vote_suggestion(&mut tx, events, DiscordId(4), false).await.unwrap();
+    suggestion::vote_suggestion(&mut tx, events, DiscordId(3), true).await.unwrap();
+    tx.commit().await.unwrap();
+
+    let got = suggestion::get_suggestion(&mut db.exe(), events).await.unwrap().unwrap();
+    assert_eq!((got.upvotes, got.downvotes), (0, 1));
+    assert_eq!(got.status, SuggestionStatus::Open);
+    assert_eq!(suggestion::suggestion_by_message(&mut db.exe(), 20).await.unwrap(), Some(wars));
+
+    let listed = suggestion::list_suggestions(&mut db.exe(), None).await.unwrap();
+    assert_eq!(listed.iter().map(|s| s.id).collect::<Vec<i64>>(), vec![wars, events]);
+
+    let mut tx = db.begin().await.unwrap();
+    assert!(suggestion::set_suggestion_status(&mut tx, wars, SuggestionStatus::Accepted).await.unwrap());
+    assert!(!suggestion::set_suggestion_status(&mut tx, 99, SuggestionStatus::Accepted).await.unwrap());
+    tx.commit().await.unwrap();
+
+    let accepted =
+        suggestion::list_suggestions(&mut db.exe(), Some(SuggestionStatus::Accepted)).await.unwrap();
+    assert_eq!(accepted.iter().map(|s| s.id).collect::<Vec<i64>>(), vec![wars]);
+}
@@ -0,0 +1,60 @@
+#![cfg(feature = "discord")]
+
+use serenity::client::Cache;
+
+use memberdb::model::db::{Column, Stat};
+use memberdb::model::discord::DiscordId;
+use memberdb::model::guild::GuildRank;
+use memberdb::model::member::MemberRank;
+use memberdb::model::wynn::McId;
+use memberdb::query_builder::{Filter, QueryMod, Selectables};
+use memberdb::testing::TestDB;
+use util::string::StatFormat;
+
+#[tokio::test]
+async fn summary_row_totals_filtered_rows() {
+    let (db, _events) = TestDB::new()
+        .guild_member("0a1b", "Pucaet", GuildRank::Recruit)
+        .guild_member("2c3d", "Jeron", GuildRank::Captain)
+        .discord_partial(1, MemberRank::Six)
+        .build()
+        .await
+        .unwrap();
+    let mut tx = db.begin().await.unwrap();
+    McId("0a1b".to_string()).update_xp(&mut tx, 1000).await.unwrap();
+    McId("2c3d".to_string()).update_xp(&mut tx, 3000).await.unwrap();
+    McId("0a1b".to_string()).update_activity(&mut tx, 3600).await.unwrap();
+    DiscordId(1).update_message(&mut tx, 5).await.unwrap();
+    tx.commit().await.unwrap();
+    let cache = Cache::default();
+
+    let summary = memberdb::table::list_members_summary(&cache, &StatFormat::default(), &db, &Vec::new())
+        .await
+        .unwrap();
+    assert_eq!(summary, vec!["Σ 3", "", ""]);
+
+    let summary = memberdb::table::stat_leaderboard_summary(
+        &cache,
+        &StatFormat::default(),
+        &db,
+        &Stat::Xp,
+        &Vec::new(),
+    )
+    .await
+    .unwrap();
+    assert_eq!(summary, vec!["Σ 2", "", "4,000"]);
+
+    let cols = vec![Selectables::Column(Column::WIgn), Selectables::Column(Column::GXp)];
+    let actions = vec![QueryMod::Filter(Filter::GuildRank(GuildRank::Recruit, std::cmp::Ordering::Equal))];
+    let summary = memberdb::table::make_table_summary(&cache, &StatFormat::default(), &db, &cols, &actions)
+        .await
+        .unwrap();
+    assert_eq!(summary, vec!["Σ 1", "", "1,000"]);
+
+    let cols = vec![Column::WOnline, Column::WAvgOnline];
+    let actions = vec![Filter::InGuild];
+    let summary = memberdb::table::make_table_summary(&cache, &StatFormat::default(), &db, &cols, &actions)
+        .await
+        .unwrap();
+    assert_eq!(summary, vec!["Σ 2", "1h ", "0s"]);
+}
@@ -0,0 +1,34 @@
+use memberdb::model::discord::DiscordId;
+use memberdb::testing::TestDB;
+use memberdb::ticket;
+
+#[tokio::test]
+async fn tickets_are_found_until_closed_and_kept_in_history() {
+    let (db, _events) = TestDB::new().build().await.unwrap();
+
+    let mut tx = db.begin().await.unwrap();
+    let first = ticket::open_ticket(&mut tx, DiscordId(1), 10, 1000).await.unwrap().unwrap();
+    let other = ticket::open_ticket(&mut tx, DiscordId(2), 20, 1500).await.unwrap().unwrap();
+    // Applicants can only have one open ticket
+    assert_eq!(ticket::open_ticket(&mut tx, DiscordId(1), 15, 1200).await.unwrap(), None);
+    tx.commit().await.unwrap();
+
+    let open = ticket::open_ticket_of(&mut db.exe(), DiscordId(1)).await.unwrap().unwrap();
+    assert_eq!((open.id, open.channel, open.closed), (first, 10, None));
+    assert_eq!(ticket::ticket_by_channel(&mut db.exe(), 20).await.unwrap().unwrap().id, other);
+    let listed = ticket::open_tickets(&mut db.exe()).await.unwrap();
+    assert_eq!(listed.iter().map(|t| t.id).collect::<Vec<i64>>(), vec![first, other]);
+
+    let mut tx = db.begin().await.unwrap();
+    assert!(ticket::close_ticket(&mut tx, first, DiscordId(3), 2000).await.unwrap());
+    // Already closed
+    assert!(!ticket::close_ticket(&mut tx, first, DiscordId(3), 3000).await.unwrap());
+    let second = ticket::open_ticket(&mut tx, DiscordId(1), 30, 4000).await.unwrap().unwrap();
+    tx.commit().await.unwrap();
+
+    let closed = ticket::ticket_by_channel(&mut db.exe(), 10).await.unwrap().unwrap();
+    assert_eq!((closed.closed, closed.closed_by), (Some(2000), Some(DiscordId(3))));
+    assert_eq!(ticket::open_ticket_of(&mut db.exe(), DiscordId(1)).await.unwrap().unwrap().id, second);
+    let history = ticket::tickets_of(&mut db.exe(), DiscordId(1)).await.unwrap();
+    assert_eq!(history.iter().map(|t| t.id).collect::<Vec<i64>>(), vec![second, first]);
+}
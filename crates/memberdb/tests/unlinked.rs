@@ -0,0 +1,40 @@
+use memberdb::model::guild::GuildRank;
+use memberdb::model::member::MemberRank;
+use memberdb::model::wynn::McId;
+use memberdb::testing::TestDB;
+use util::string::StatFormat;
+
+#[tokio::test]
+async fn unlinked_profiles_by_activity() {
+    let (db, _events) = TestDB::new()
+        .guild_member("0a1b", "Pucaet", GuildRank::Captain)
+        .full_member(1, "2c3d", "Jeron", MemberRank::Five)
+        .guild_member("2c3d", "Jeron", GuildRank::Recruit)
+        .guild_member("4e5f", "Idle", GuildRank::Recruit)
+        .wynn_partial("6a7b", "Offline", MemberRank::Five)
+        .build()
+        .await
+        .unwrap();
+
+    let mut tx = db.begin().await.unwrap();
+    McId("0a1b".to_string()).update_xp(&mut tx, 2_000_000).await.unwrap();
+    // Linked to a discord profile, so never listed
+    McId("2c3d".to_string()).update_xp(&mut tx, 5_000_000).await.unwrap();
+    McId("2c3d".to_string()).update_activity(&mut tx, 90_000).await.unwrap();
+    // Not active enough
+    McId("4e5f".to_string()).update_xp(&mut tx, 1000).await.unwrap();
+    McId("4e5f".to_string()).update_activity(&mut tx, 3600).await.unwrap();
+    McId("6a7b".to_string()).update_activity(&mut tx, 40_000).await.unwrap();
+    tx.commit().await.unwrap();
+
+    let (table, header) =
+        memberdb::table::unlinked_profiles(&StatFormat::default(), &db, 1_000_000, 36_000).await.unwrap();
+    assert_eq!(header, vec!["#", "ign", "rank", "online", "xp"]);
+    assert_eq!(
+        table,
+        vec![vec!["1", "Offline", "", "11h 6m 40s", "0"], vec!["2", "Pucaet", "Captain", "0s", "2,000,000"]]
+    );
+
+    let (table, _) = memberdb::table::unlinked_profiles(&StatFormat::default(), &db, 0, 0).await.unwrap();
+    assert_eq!(table.len(), 3);
+}
@@ -0,0 +1,30 @@
+use memberdb::model::discord::{DiscordId, UserIds};
+use memberdb::model::member::MemberRank;
+use memberdb::testing::TestDB;
+use util::string::StatFormat;
+
+#[tokio::test]
+async fn leaderboard_names_users_by_id_without_cache() {
+    let (db, _events) = TestDB::new()
+        .full_member(1, "0a1b", "Pucaet", MemberRank::Five)
+        .discord_partial(2, MemberRank::Six)
+        .build()
+        .await
+        .unwrap();
+
+    let mut tx = db.begin().await.unwrap();
+    DiscordId(1).update_message(&mut tx, 5).await.unwrap();
+    DiscordId(2).update_message(&mut tx, 2).await.unwrap();
+    tx.commit().await.unwrap();
+
+    let (table, _) = memberdb::table::stat_leaderboard(
+        &UserIds,
+        &StatFormat::default(),
+        &db,
+        &memberdb::model::db::Stat::WeeklyMessage,
+        &Vec::new(),
+    )
+    .await
+    .unwrap();
+    assert_eq!(table, vec![vec!["1", "Pucaet", "5"], vec!["2", "2", "2"]]);
+}
@@ -0,0 +1,40 @@
+use std::thread::sleep;
+use std::time::Duration;
+
+use memberdb::voice_tracker::VoiceTracker;
+
+const ELAPSED: Duration = Duration::from_millis(200);
+
+#[test]
+fn weight_is_applied_to_durations() {
+    let mut vt = VoiceTracker::new();
+    assert_eq!(vt.track_voice(&1, 10, 100), None);
+    assert_eq!(vt.track_voice(&2, 10, 50), None);
+    assert_eq!(vt.track_voice(&3, 10, 0), None);
+    assert_eq!(vt.track_voice(&4, 10, 200), None);
+    sleep(ELAPSED);
+
+    let mut durs = vt.track_all_voice().map(|(id, _, dur)| (*id, dur)).collect::<Vec<_>>();
+    durs.sort_by_key(|(id, _)| *id);
+    assert!(durs[0].1 >= ELAPSED);
+    assert!(durs[1].1 >= ELAPSED / 2 && durs[1].1 < ELAPSED);
+    assert_eq!(durs[2].1, Duration::ZERO);
+    assert!(durs[3].1 >= ELAPSED * 2);
+}
+
+#[test]
+fn weight_change_flushes_old_weight() {
+    let mut vt = VoiceTracker::new();
+    vt.track_voice(&1, 10, 50);
+    sleep(ELAPSED);
+
+    let (channel, dur) = vt.track_voice(&1, 20, 100).unwrap();
+    assert_eq!(channel, 10);
+    assert!(dur >= ELAPSED / 2 && dur < ELAPSED);
+    sleep(ELAPSED);
+
+    let (channel, dur) = vt.untrack_voice(&1).unwrap();
+    assert_eq!(channel, 20);
+    assert!(dur >= ELAPSED);
+    assert_eq!(vt.untrack_voice(&1), None);
+}
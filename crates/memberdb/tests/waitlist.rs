@@ -0,0 +1,32 @@
+use memberdb::model::discord::DiscordId;
+use memberdb::testing::TestDB;
+use memberdb::waitlist;
+
+#[tokio::test]
+async fn recruits_are_listed_until_removed_or_joined() {
+    let (db, _events) = TestDB::new().build().await.unwrap();
+
+    let mut tx = db.begin().await.unwrap();
+    let first =
+        waitlist::add_recruit(&mut tx, "Pucaet", Some(DiscordId(1)), DiscordId(3), 1000).await.unwrap();
+    let second = waitlist::add_recruit(&mut tx, "Jeron", None, DiscordId(3), 2000).await.unwrap();
+    let third = waitlist::add_recruit(&mut tx, "SephDark18", None, DiscordId(4), 1500).await.unwrap();
+    tx.commit().await.unwrap();
+
+    let recruits = waitlist::waitlist(&mut db.exe()).await.unwrap();
+    assert_eq!(recruits.iter().map(|r| r.id).collect::<Vec<i64>>(), vec![first, third, second]);
+    let recruit = waitlist::recruit_by_ign(&mut db.exe(), "pucaet").await.unwrap().unwrap();
+    assert_eq!((recruit.id, recruit.discord, recruit.added_by), (first, Some(DiscordId(1)), DiscordId(3)));
+
+    let mut tx = db.begin().await.unwrap();
+    assert!(waitlist::remove_recruit(&mut tx, third).await.unwrap());
+    assert!(!waitlist::remove_recruit(&mut tx, third).await.unwrap());
+    // Ign is case insensitive
+    assert_eq!(waitlist::remove_ign(&mut tx, "PUCAET").await.unwrap(), 1);
+    assert_eq!(waitlist::remove_ign(&mut tx, "Notch").await.unwrap(), 0);
+    tx.commit().await.unwrap();
+
+    let recruits = waitlist::waitlist(&mut db.exe()).await.unwrap();
+    assert_eq!(recruits.iter().map(|r| r.ign.as_str()).collect::<Vec<_>>(), vec!["Jeron"]);
+    assert!(waitlist::recruit_by_ign(&mut db.exe(), "Pucaet").await.unwrap().is_none());
+}
@@ -0,0 +1,53 @@
+use memberdb::model::discord::{DiscordId, UserIds};
+use memberdb::model::guild::GuildRank;
+use memberdb::model::member::MemberRank;
+use memberdb::model::wynn::McId;
+use memberdb::testing::TestDB;
+use memberdb::weekly_backup::{backup_time, undo_weekly_reset, UNDO_WINDOW};
+use util::string::StatFormat;
+
+#[tokio::test]
+async fn weekly_reset_is_undone_keeping_new_stats() {
+    let (db, _events) = TestDB::new()
+        .full_member(1, "0a1b", "Pucaet", MemberRank::Five)
+        .guild_member("0a1b", "Pucaet", GuildRank::Recruit)
+        .build()
+        .await
+        .unwrap();
+    let mcid = McId("0a1b".to_string());
+    let mut tx = db.begin().await.unwrap();
+    DiscordId(1).update_message(&mut tx, 5).await.unwrap();
+    mcid.update_activity(&mut tx, 60).await.unwrap();
+    mcid.update_xp(&mut tx, 1000).await.unwrap();
+    tx.commit().await.unwrap();
+
+    let mut tx = db.begin().await.unwrap();
+    assert!(undo_weekly_reset(&mut tx, 0).await.is_err());
+    drop(tx);
+
+    memberdb::weekly_reset(&db, &UserIds, &StatFormat::default()).await.unwrap();
+    let time = backup_time(&mut db.exe()).await.unwrap().unwrap();
+    assert_eq!(DiscordId(1).weekly_message(&mut db.exe()).await.unwrap(), 0);
+    assert_eq!(mcid.average_online_time_range(&mut db.exe()).await.unwrap(), 1);
+
+    let mut tx = db.begin().await.unwrap();
+    DiscordId(1).update_message(&mut tx, 2).await.unwrap();
+    tx.commit().await.unwrap();
+
+    // The backup is too old
+    let mut tx = db.begin().await.unwrap();
+    assert!(undo_weekly_reset(&mut tx, time + UNDO_WINDOW + 1).await.is_err());
+    drop(tx);
+
+    let mut tx = db.begin().await.unwrap();
+    assert_eq!(undo_weekly_reset(&mut tx, time + 60).await.unwrap(), time);
+    tx.commit().await.unwrap();
+    assert_eq!(DiscordId(1).weekly_message(&mut db.exe()).await.unwrap(), 7);
+    assert_eq!(mcid.weekly_online_time(&mut db.exe()).await.unwrap(), 60);
+    assert_eq!(mcid.weekly_xp(&mut db.exe()).await.unwrap(), 1000);
+    assert_eq!(mcid.average_online_time_range(&mut db.exe()).await.unwrap(), 0);
+    assert_eq!(mcid.average_online_time(&mut db.exe()).await.unwrap(), 0);
+
+    // The backup is removed, so the reset can't be undone twice
+    assert_eq!(backup_time(&mut db.exe()).await.unwrap(), None);
+}
@@ -0,0 +1,60 @@
+use std::collections::HashMap;
+
+use memberdb::model::db::Stat;
+use memberdb::model::discord::{DiscordId, UserIds};
+use memberdb::model::guild::GuildRank;
+use memberdb::model::member::MemberRank;
+use memberdb::model::wynn::McId;
+use memberdb::testing::TestDB;
+use memberdb::weekly_report::{
+    clear_pending_reset, latest_report, make_report, pending_reset, set_pending_reset, store_report,
+};
+use util::string::StatFormat;
+
+#[tokio::test]
+async fn report_is_made_without_resetting_and_stored() {
+    let (db, _events) = TestDB::new()
+        .full_member(1, "0a1b", "Pucaet", MemberRank::Five)
+        .guild_member("0a1b", "Pucaet", GuildRank::Recruit)
+        .guild_member("2c3d", "Jeron", GuildRank::Recruit)
+        .guild_member("4e5f", "SephDark18", GuildRank::Chief)
+        .build()
+        .await
+        .unwrap();
+    let mut tx = db.begin().await.unwrap();
+    DiscordId(1).update_message(&mut tx, 5).await.unwrap();
+    McId("0a1b".to_string()).update_xp(&mut tx, 1000).await.unwrap();
+    McId("2c3d".to_string()).update_xp(&mut tx, 10).await.unwrap();
+    tx.commit().await.unwrap();
+
+    let requirements = HashMap::from([(GuildRank::Recruit, 100)]);
+    assert_eq!(latest_report(&mut db.exe()).await.unwrap(), None);
+    let report = make_report(&db, &UserIds, &StatFormat::default(), &requirements, 1000).await.unwrap();
+    assert_eq!(report.met_requirement, vec!["Pucaet"]);
+    // Chief has no requirement, so isn't in the report
+    assert_eq!(report.missed_requirement, vec!["Jeron"]);
+    let (stat, messages) = &report.top[0];
+    assert_eq!(stat, &Stat::WeeklyMessage);
+    assert_eq!(messages, &vec![vec!["1", "Pucaet", "5"]]);
+    assert!(report.summary().contains("**1** met, **1** missed"));
+    // Making the report doesn't change anything
+    assert_eq!(DiscordId(1).weekly_message(&mut db.exe()).await.unwrap(), 5);
+
+    store_report(&db, &report).await.unwrap();
+    let older = make_report(&db, &UserIds, &StatFormat::default(), &requirements, 500).await.unwrap();
+    store_report(&db, &older).await.unwrap();
+    assert_eq!(latest_report(&mut db.exe()).await.unwrap(), Some(report));
+}
+
+#[tokio::test]
+async fn pending_reset_is_replaced_and_cleared() {
+    let (db, _events) = TestDB::new().build().await.unwrap();
+    assert_eq!(pending_reset(&mut db.exe()).await.unwrap(), None);
+
+    set_pending_reset(&db, 1000, 4600).await.unwrap();
+    set_pending_reset(&db, 2000, 5600).await.unwrap();
+    assert_eq!(pending_reset(&mut db.exe()).await.unwrap(), Some((2000, 5600)));
+
+    clear_pending_reset(&db).await.unwrap();
+    assert_eq!(pending_reset(&mut db.exe()).await.unwrap(), None);
+}
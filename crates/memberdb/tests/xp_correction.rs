@@ -0,0 +1,36 @@
+use memberdb::model::guild::GuildRank;
+use memberdb::model::wynn::McId;
+use memberdb::testing::TestDB;
+use memberdb::xp_correction;
+
+#[tokio::test]
+async fn held_back_xp_is_only_counted_once_approved() {
+    let (db, _events) =
+        TestDB::new().guild_member("0a1b", "Pucaet", GuildRank::Recruit).build().await.unwrap();
+    let pucaet = McId("0a1b".to_string());
+
+    let mut tx = db.begin().await.unwrap();
+    let big = xp_correction::add_correction(&mut tx, &pucaet, "Pucaet", 5_000_000, 1000).await.unwrap();
+    let negative = xp_correction::add_correction(&mut tx, &pucaet, "Pucaet", -300, 2000).await.unwrap();
+    tx.commit().await.unwrap();
+    assert_eq!(
+        xp_correction::pending_corrections(&mut db.exe()).await.unwrap(),
+        vec![big.clone(), negative.clone()]
+    );
+    assert_eq!(pucaet.xp(&mut db.exe()).await.unwrap(), 0);
+
+    let mut tx = db.begin().await.unwrap();
+    assert_eq!(xp_correction::approve_correction(&mut tx, big.id).await.unwrap(), Some(big.clone()));
+    assert_eq!(
+        xp_correction::discard_correction(&mut tx, negative.id).await.unwrap(),
+        Some(negative.clone())
+    );
+    // Already reviewed
+    assert_eq!(xp_correction::approve_correction(&mut tx, negative.id).await.unwrap(), None);
+    tx.commit().await.unwrap();
+
+    assert!(xp_correction::pending_corrections(&mut db.exe()).await.unwrap().is_empty());
+    assert_eq!(pucaet.xp(&mut db.exe()).await.unwrap(), 5_000_000);
+    assert_eq!(pucaet.weekly_xp(&mut db.exe()).await.unwrap(), 5_000_000);
+    assert_eq!(memberdb::daily_summary(&db).await.unwrap().xp, 5_000_000);
+}
@@ -0,0 +1,87 @@
+#![cfg(feature = "discord")]
+
+use std::collections::HashMap;
+
+use serenity::client::Cache;
+
+use memberdb::model::guild::GuildRank;
+use memberdb::model::wynn::McId;
+use memberdb::testing::TestDB;
+use memberdb::xp_requirement;
+use util::string::StatFormat;
+
+#[tokio::test]
+async fn weekly_xp_misses_are_tracked_as_streaks() {
+    let (db, _events) = TestDB::new()
+        .guild_member("0a1b", "Pucaet", GuildRank::Recruit)
+        .guild_member("2c3d", "Jeron", GuildRank::Recruit)
+        .guild_member("4e5f", "Owner", GuildRank::Owner)
+        .build()
+        .await
+        .unwrap();
+    let requirements = xp_requirement::parse_requirements(&HashMap::from([
+        ("Recruit".to_string(), 1000),
+        ("NotARank".to_string(), 10),
+    ]));
+    assert_eq!(requirements, HashMap::from([(GuildRank::Recruit, 1000)]));
+
+    let mut tx = db.begin().await.unwrap();
+    McId("2c3d".to_string()).update_xp(&mut tx, 1000).await.unwrap();
+    tx.commit().await.unwrap();
+
+    let misses = xp_requirement::record_weekly_xp(&db, &requirements, 1).await.unwrap();
+    assert_eq!(misses.len(), 1);
+    assert_eq!(misses[0].ign, "Pucaet");
+    assert_eq!(misses[0].streak, 1);
+    memberdb::weekly_reset(&db, &Cache::default(), &StatFormat::default()).await.unwrap();
+
+    let misses = xp_requirement::record_weekly_xp(&db, &requirements, 2).await.unwrap();
+    assert_eq!(
+        misses.iter().map(|miss| (miss.ign.as_str(), miss.streak)).collect::<Vec<_>>(),
+        vec![("Pucaet", 2), ("Jeron", 1)]
+    );
+    assert_eq!(McId("0a1b".to_string()).xp_miss_streak(&mut db.exe()).await.unwrap(), 2);
+    assert_eq!(McId("4e5f".to_string()).xp_miss_streak(&mut db.exe()).await.unwrap(), 0);
+}
+
+#[tokio::test]
+async fn requirements_are_simulated_without_recording() {
+    let (db, _events) = TestDB::new()
+        .guild_member("0a1b", "Pucaet", GuildRank::Recruit)
+        .guild_member("2c3d", "Jeron", GuildRank::Recruit)
+        .guild_member("4e5f", "Owner", GuildRank::Owner)
+        .build()
+        .await
+        .unwrap();
+    let mut tx = db.begin().await.unwrap();
+    McId("0a1b".to_string()).update_xp(&mut tx, 600).await.unwrap();
+    McId("2c3d".to_string()).update_xp(&mut tx, 2000).await.unwrap();
+    tx.commit().await.unwrap();
+
+    let requirements = HashMap::from([(GuildRank::Recruit, 1000), (GuildRank::Owner, 10)]);
+    let results = xp_requirement::simulate_requirements(&mut db.exe(), &requirements, false).await.unwrap();
+    assert_eq!(
+        results.iter().map(|result| (result.rank, result.members, result.failed)).collect::<Vec<_>>(),
+        vec![(GuildRank::Owner, 1, 1), (GuildRank::Recruit, 2, 1)]
+    );
+    assert_eq!(results[1].buckets, [0, 0, 1, 0]);
+    assert_eq!(results[1].median_xp, 2000);
+    assert_eq!(McId("0a1b".to_string()).xp_miss_streak(&mut db.exe()).await.unwrap(), 0);
+
+    // The weekly xp backed up at the last reset is evaluated, even for ranks without a requirement
+    // back then
+    let recorded = HashMap::from([(GuildRank::Recruit, 500)]);
+    xp_requirement::record_weekly_xp(&db, &recorded, 1).await.unwrap();
+    memberdb::weekly_reset(&db, &Cache::default(), &StatFormat::default()).await.unwrap();
+    let results = xp_requirement::simulate_requirements(&mut db.exe(), &requirements, true).await.unwrap();
+    assert_eq!(
+        results.iter().map(|result| (result.rank, result.members, result.failed)).collect::<Vec<_>>(),
+        vec![(GuildRank::Owner, 1, 1), (GuildRank::Recruit, 2, 1)]
+    );
+    assert_eq!(results[1].median_xp, 2000);
+
+    // A requirement of 0 is met by everyone
+    let requirements = HashMap::from([(GuildRank::Recruit, 0)]);
+    let results = xp_requirement::simulate_requirements(&mut db.exe(), &requirements, false).await.unwrap();
+    assert_eq!((results[0].members, results[0].failed), (2, 0));
+}
@@ -0,0 +1,71 @@
+//! RGB pixel buffer for drawing images, which are encoded as PNG
+use crate::font::{self, GLYPH_HEIGHT, GLYPH_WIDTH};
+
+/// RGB pixel buffer for drawing images
+pub struct Canvas {
+    width: usize,
+    height: usize,
+    pixels: Vec<u8>,
+}
+
+impl Canvas {
+    /// Create a canvas filled with a background color
+    pub fn new(width: usize, height: usize, background: [u8; 3]) -> Self {
+        let pixels = background.repeat(width * height);
+        Self { width, height, pixels }
+    }
+
+    /// Width of `text` drawn at `scale`, in pixels
+    pub fn text_width(text: &str, scale: usize) -> usize {
+        text.chars().count() * (GLYPH_WIDTH + 1) * scale
+    }
+
+    pub fn fill_rect(&mut self, x: usize, y: usize, w: usize, h: usize, color: [u8; 3]) {
+        for py in y..std::cmp::min(y + h, self.height) {
+            for px in x..std::cmp::min(x + w, self.width) {
+                let i = (py * self.width + px) * 3;
+                self.pixels[i..i + 3].copy_from_slice(&color);
+            }
+        }
+    }
+
+    /// Draw RGB pixels of a `w` by `h` image with its top left corner at `(x, y)`
+    pub fn draw_pixels(&mut self, x: usize, y: usize, w: usize, h: usize, pixels: &[u8]) {
+        for py in 0..std::cmp::min(h, self.height.saturating_sub(y)) {
+            for px in 0..std::cmp::min(w, self.width.saturating_sub(x)) {
+                let src = (py * w + px) * 3;
+                let dst = ((y + py) * self.width + x + px) * 3;
+                if let Some(color) = pixels.get(src..src + 3) {
+                    self.pixels[dst..dst + 3].copy_from_slice(color);
+                }
+            }
+        }
+    }
+
+    /// Draw text with its top left corner at `(x, y)`, each font pixel is `scale` pixels wide
+    pub fn draw_text(&mut self, x: usize, y: usize, text: &str, color: [u8; 3], scale: usize) {
+        for (char_i, c) in text.chars().enumerate() {
+            let char_x = x + char_i * (GLYPH_WIDTH + 1) * scale;
+            for (col_i, col) in font::glyph(c).iter().enumerate() {
+                for row_i in 0..GLYPH_HEIGHT {
+                    if col & (1 << row_i) != 0 {
+                        self.fill_rect(char_x + col_i * scale, y + row_i * scale, scale, scale, color);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Encode the canvas as PNG
+    pub fn encode(self) -> Vec<u8> {
+        let mut data = Vec::new();
+        let mut encoder = png::Encoder::new(&mut data, self.width as u32, self.height as u32);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        // Writing into a vector only fails if the image is empty, which can't happen here
+        let mut writer = encoder.write_header().expect("Failed to write PNG header");
+        writer.write_image_data(&self.pixels).expect("Failed to write PNG data");
+        writer.finish().expect("Failed to finish PNG");
+        data
+    }
+}
@@ -0,0 +1,247 @@
+//! Rendering of member profile cards
+//!
+//! A profile card is an image showing a member's avatar, name, rank emblem, key stats and progress
+//! bars, which is more compact than the profile embed and can be shared around.
+//! ```
+//! use memberdb::model::guild::GuildRank;
+//! use msgtool::card::{render_card, Card, Emblem, ProgressBar};
+//!
+//! let card = Card {
+//!     avatar: None,
+//!     title: "Captain Pucaet".to_string(),
+//!     subtitle: "pucaet".to_string(),
+//!     emblem: Some(Emblem::guild_rank(GuildRank::Captain)),
+//!     stats: vec![("Wars".to_string(), "12".to_string())],
+//!     bars: vec![ProgressBar {
+//!         label: "Weekly XP".to_string(),
+//!         value: 500,
+//!         max: 1000,
+//!         text: "500 / 1,000".to_string(),
+//!     }],
+//! };
+//! let png = render_card(&card);
+//! assert!(png.starts_with(b"\x89PNG"));
+//! ```
+use anyhow::{bail, Context, Result};
+
+use memberdb::model::guild::{GuildRank, GUILD_RANKS};
+
+use crate::canvas::Canvas;
+use crate::font::GLYPH_HEIGHT;
+
+/// Width of a card in pixels
+const WIDTH: usize = 560;
+/// Width and height of the avatar in pixels
+pub const AVATAR_SIZE: usize = 96;
+const PADDING: usize = 16;
+/// Size of a font pixel of the title
+const TITLE_SCALE: usize = 3;
+/// Size of a font pixel of the other texts
+const TEXT_SCALE: usize = 2;
+/// Height of a stat or a progress bar
+const ROW_HEIGHT: usize = 46;
+const BAR_HEIGHT: usize = 12;
+const PIP_SIZE: usize = 8;
+
+const BACKGROUND: [u8; 3] = [0x2F, 0x31, 0x36];
+const PANEL: [u8; 3] = [0x20, 0x22, 0x25];
+const TEXT: [u8; 3] = [0xDC, 0xDD, 0xDE];
+const TEXT_DIM: [u8; 3] = [0x96, 0x98, 0x9D];
+const ACCENT: [u8; 3] = [0x58, 0x65, 0xF2];
+const COMPLETE: [u8; 3] = [0x57, 0xF2, 0x87];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// Avatar of a card, which is a square of [`AVATAR_SIZE`] RGB pixels
+pub struct Avatar {
+    pixels: Vec<u8>,
+}
+
+impl Avatar {
+    /// Decode a PNG image into an avatar.
+    ///
+    /// The image is cropped into a square and scaled to [`AVATAR_SIZE`], transparent pixels are
+    /// blended into the card background.
+    /// ```
+    /// use msgtool::card::{render_card, Avatar, Card};
+    ///
+    /// let card = Card { title: "Pucaet".to_string(), ..Default::default() };
+    /// assert!(Avatar::from_png(&render_card(&card)).is_ok());
+    /// assert!(Avatar::from_png(b"not a png").is_err());
+    /// ```
+    pub fn from_png(data: &[u8]) -> Result<Self> {
+        let mut decoder = png::Decoder::new(data);
+        decoder.set_transformations(png::Transformations::normalize_to_color8());
+        let mut reader = decoder.read_info().context("Failed to read PNG header")?;
+        let mut buf = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut buf).context("Failed to decode PNG")?;
+        let channels = match info.color_type {
+            png::ColorType::Grayscale => 1,
+            png::ColorType::GrayscaleAlpha => 2,
+            png::ColorType::Rgb => 3,
+            png::ColorType::Rgba => 4,
+            png::ColorType::Indexed => bail!("PNG palette wasn't expanded"),
+        };
+        let (width, height) = (info.width as usize, info.height as usize);
+        if width == 0 || height == 0 {
+            bail!("PNG is empty");
+        }
+
+        // Crop the center square, and sample it with nearest neighbor
+        let side = std::cmp::min(width, height);
+        let (left, top) = ((width - side) / 2, (height - side) / 2);
+        let mut pixels = Vec::with_capacity(AVATAR_SIZE * AVATAR_SIZE * 3);
+        for y in 0..AVATAR_SIZE {
+            for x in 0..AVATAR_SIZE {
+                let src_x = left + x * side / AVATAR_SIZE;
+                let src_y = top + y * side / AVATAR_SIZE;
+                let i = (src_y * width + src_x) * channels;
+                let px = &buf[i..i + channels];
+                let (rgb, alpha) = match channels {
+                    1 => ([px[0]; 3], 255),
+                    2 => ([px[0]; 3], px[1]),
+                    3 => ([px[0], px[1], px[2]], 255),
+                    _ => ([px[0], px[1], px[2]], px[3]),
+                };
+                pixels.extend(rgb.iter().zip(BACKGROUND).map(|(fg, bg)| blend(*fg, bg, alpha)));
+            }
+        }
+        Ok(Self { pixels })
+    }
+}
+
+/// Blend a color channel over a background channel
+fn blend(fg: u8, bg: u8, alpha: u8) -> u8 {
+    ((fg as u32 * alpha as u32 + bg as u32 * (255 - alpha as u32)) / 255) as u8
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// Rank emblem of a card, which is a colored label followed by `level` pips
+pub struct Emblem {
+    pub label: String,
+    pub level: usize,
+    pub color: [u8; 3],
+}
+
+impl Emblem {
+    /// Emblem of a guild rank, with one pip for recruits up to six pips for the owner
+    /// ```
+    /// use memberdb::model::guild::GuildRank;
+    /// use msgtool::card::Emblem;
+    ///
+    /// assert_eq!(Emblem::guild_rank(GuildRank::Recruit).level, 1);
+    /// assert_eq!(Emblem::guild_rank(GuildRank::Owner).level, 6);
+    /// ```
+    pub fn guild_rank(rank: GuildRank) -> Self {
+        let level = GUILD_RANKS.len() - GUILD_RANKS.iter().position(|r| *r == rank).unwrap_or(0);
+        let color = match rank {
+            GuildRank::Owner => [0xF1, 0xC4, 0x0F],
+            GuildRank::Chief => [0xE6, 0x7E, 0x22],
+            GuildRank::Strategist => [0x9B, 0x59, 0xB6],
+            GuildRank::Captain => [0x34, 0x98, 0xDB],
+            GuildRank::Recruiter => [0x2E, 0xCC, 0x71],
+            GuildRank::Recruit => [0x95, 0xA5, 0xA6],
+        };
+        Self { label: rank.to_string(), level, color }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// Progress bar of a card, which is full once `value` reaches `max`
+pub struct ProgressBar {
+    pub label: String,
+    pub value: i64,
+    pub max: i64,
+    /// Text shown next to the label, ex: "500 / 1,000"
+    pub text: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+/// Content of a profile card
+pub struct Card {
+    /// Avatar of the member, a placeholder with the title's initial is drawn if there is none
+    pub avatar: Option<Avatar>,
+    pub title: String,
+    pub subtitle: String,
+    pub emblem: Option<Emblem>,
+    /// Stats as (stat name, formatted stat), drawn in two columns
+    pub stats: Vec<(String, String)>,
+    pub bars: Vec<ProgressBar>,
+}
+
+/// Render a profile card into an image encoded as PNG.
+///
+/// Characters that aren't printable ascii are drawn as boxes, and texts that don't fit are cut off.
+pub fn render_card(card: &Card) -> Vec<u8> {
+    let text_h = GLYPH_HEIGHT * TEXT_SCALE;
+    let title_h = GLYPH_HEIGHT * TITLE_SCALE;
+    let header_h = AVATAR_SIZE + 2 * PADDING;
+    let stats_h = card.stats.len().div_ceil(2) * ROW_HEIGHT;
+    let height = header_h + stats_h + card.bars.len() * ROW_HEIGHT + PADDING;
+    let mut canvas = Canvas::new(WIDTH, height, BACKGROUND);
+    canvas.fill_rect(0, 0, WIDTH, header_h, PANEL);
+
+    // Header
+    match &card.avatar {
+        Some(avatar) => canvas.draw_pixels(PADDING, PADDING, AVATAR_SIZE, AVATAR_SIZE, &avatar.pixels),
+        None => {
+            canvas.fill_rect(PADDING, PADDING, AVATAR_SIZE, AVATAR_SIZE, ACCENT);
+            // Initial of the last word, so the rank in front of a name is skipped
+            let initial: String =
+                card.title.rsplit(' ').next().and_then(|word| word.chars().next()).into_iter().collect();
+            let scale = 6;
+            let x = PADDING + (AVATAR_SIZE - Canvas::text_width(&initial, scale)) / 2;
+            let y = PADDING + (AVATAR_SIZE - GLYPH_HEIGHT * scale) / 2;
+            canvas.draw_text(x, y, &initial, TEXT, scale);
+        }
+    }
+    let x = AVATAR_SIZE + 2 * PADDING;
+    let chars = (WIDTH - x - PADDING) / Canvas::text_width(" ", TITLE_SCALE);
+    canvas.draw_text(x, PADDING, &truncate(&card.title, chars), TEXT, TITLE_SCALE);
+    let chars = (WIDTH - x - PADDING) / Canvas::text_width(" ", TEXT_SCALE);
+    let y = PADDING + title_h + 8;
+    canvas.draw_text(x, y, &truncate(&card.subtitle, chars), TEXT_DIM, TEXT_SCALE);
+    if let Some(emblem) = &card.emblem {
+        let y = y + text_h + 12;
+        let label_w = Canvas::text_width(&emblem.label, TEXT_SCALE) + 8;
+        canvas.fill_rect(x, y, label_w, text_h + 8, emblem.color);
+        canvas.draw_text(x + 4, y + 4, &emblem.label, PANEL, TEXT_SCALE);
+        for i in 0..emblem.level {
+            let pip_x = x + label_w + 8 + i * (PIP_SIZE + 4);
+            canvas.fill_rect(pip_x, y + (text_h + 8 - PIP_SIZE) / 2, PIP_SIZE, PIP_SIZE, emblem.color);
+        }
+    }
+
+    // Stats, in two columns
+    let col_w = (WIDTH - 2 * PADDING) / 2;
+    let chars = col_w / Canvas::text_width(" ", TEXT_SCALE);
+    for (i, (name, value)) in card.stats.iter().enumerate() {
+        let x = PADDING + i % 2 * col_w;
+        let y = header_h + PADDING / 2 + i / 2 * ROW_HEIGHT;
+        canvas.draw_text(x, y, &truncate(name, chars), TEXT_DIM, TEXT_SCALE);
+        canvas.draw_text(x, y + text_h + 4, &truncate(value, chars), TEXT, TEXT_SCALE);
+    }
+
+    // Progress bars
+    let bar_w = WIDTH - 2 * PADDING;
+    for (i, bar) in card.bars.iter().enumerate() {
+        let y = header_h + stats_h + PADDING / 2 + i * ROW_HEIGHT;
+        canvas.draw_text(PADDING, y, &bar.label, TEXT_DIM, TEXT_SCALE);
+        let text_w = Canvas::text_width(&bar.text, TEXT_SCALE);
+        canvas.draw_text(PADDING + bar_w.saturating_sub(text_w), y, &bar.text, TEXT, TEXT_SCALE);
+        let y = y + text_h + 6;
+        canvas.fill_rect(PADDING, y, bar_w, BAR_HEIGHT, PANEL);
+        let (filled, color) = if bar.max <= 0 || bar.value >= bar.max {
+            (bar_w, COMPLETE)
+        } else {
+            ((bar_w as i64 * bar.value.max(0) / bar.max) as usize, ACCENT)
+        };
+        canvas.fill_rect(PADDING, y, filled, BAR_HEIGHT, color);
+    }
+
+    canvas.encode()
+}
+
+/// Cut off a text to at most `chars` characters
+fn truncate(text: &str, chars: usize) -> String {
+    text.chars().take(chars).collect()
+}
@@ -1,15 +1,21 @@
 //! Tools for live interactions with users via discord messages, mostly through message components
+use std::borrow::Cow;
 use std::sync::Arc;
 use std::time::Duration;
 
 use anyhow::Result;
-use serenity::builder::{CreateActionRow, CreateButton, CreateComponents};
+use serenity::builder::{
+    CreateActionRow, CreateButton, CreateComponents, CreateInteractionResponseData, CreateMessage,
+};
 use serenity::client::bridge::gateway::ShardMessenger;
 use serenity::futures::StreamExt;
 use serenity::http::{CacheHttp, Http};
-use serenity::model::application::component::ButtonStyle;
+use serenity::json::Value;
+use serenity::model::application::component::{ActionRowComponent, ButtonStyle, InputTextStyle};
 use serenity::model::application::interaction::message_component::MessageComponentInteraction;
+use serenity::model::application::interaction::modal::ModalSubmitInteraction;
 use serenity::model::application::interaction::InteractionResponseType;
+use serenity::model::channel::AttachmentType;
 use serenity::model::id::{ChannelId, UserId};
 
 use crate::pager::{Pager, ToPage};
@@ -93,28 +99,136 @@ where
     Ok(Some((choice, ci)))
 }
 
+/// Ask user to choose one of the options via message.
+///
+/// Send a message with a button for each option, and return the index of the chosen one.
+/// Only the first 25 options are shown, as that is the limit of buttons in a message.
+/// The message is stop being observed after `timeout` (in seconds) is elapsed.
+pub async fn choose<C>(
+    ctx: &C, channel_id: &ChannelId, content: &str, options: &[String], timeout: u64, user_id: UserId,
+) -> Result<Option<usize>>
+where
+    C: AsRef<Http> + AsRef<ShardMessenger> + CacheHttp,
+{
+    let m = channel_id
+        .send_message(ctx, |m| {
+            m.content(content).components(|c| {
+                for (row, chunk) in options.chunks(5).take(5).enumerate() {
+                    c.create_action_row(|ar| {
+                        for (i, option) in chunk.iter().enumerate() {
+                            let mut button = CreateButton::default();
+                            button.custom_id(row * 5 + i).label(option).style(ButtonStyle::Primary);
+                            ar.add_button(button);
+                        }
+                        ar
+                    });
+                }
+                c
+            })
+        })
+        .await?;
+
+    let ci = match m
+        .await_component_interaction(ctx)
+        .timeout(Duration::from_secs(timeout))
+        .author_id(user_id)
+        .await
+    {
+        Some(ci) => {
+            ci.create_interaction_response(ctx, |r| {
+                r.kind(InteractionResponseType::UpdateMessage)
+                    .interaction_response_data(|d| d.set_components(CreateComponents::default()))
+            })
+            .await?;
+            ci
+        }
+        None => {
+            m.reply(ctx, "Timed out").await?;
+            return Ok(None);
+        }
+    };
+
+    Ok(ci.data.custom_id.parse().ok())
+}
+
+/// Page that can be displayed in a paged message
+pub trait MessagePage {
+    /// Add the page to a new message
+    fn build_message<'a>(&'a self, m: &mut CreateMessage<'a>);
+
+    /// Replace the displayed page of an existing message with this page
+    fn build_update<'a>(&'a self, d: &mut CreateInteractionResponseData<'a>);
+}
+
+impl MessagePage for String {
+    fn build_message<'a>(&'a self, m: &mut CreateMessage<'a>) {
+        m.content(self);
+    }
+
+    fn build_update<'a>(&'a self, d: &mut CreateInteractionResponseData<'a>) {
+        d.content(self);
+    }
+}
+
+/// Image page, which is displayed as an attachment
+#[derive(Debug)]
+pub struct Image {
+    /// File name of the image
+    pub name: String,
+    /// Encoded image data
+    pub data: Vec<u8>,
+}
+
+impl Image {
+    fn attachment(&self) -> AttachmentType<'_> {
+        AttachmentType::Bytes { data: Cow::Borrowed(&self.data), filename: self.name.clone() }
+    }
+}
+
+impl MessagePage for Image {
+    fn build_message<'a>(&'a self, m: &mut CreateMessage<'a>) {
+        m.add_file(self.attachment());
+    }
+
+    fn build_update<'a>(&'a self, d: &mut CreateInteractionResponseData<'a>) {
+        // Without this the previous image is kept alongside the new one
+        d.0.insert("attachments", Value::Array(Vec::new()));
+        d.add_file(self.attachment());
+    }
+}
+
+/// Amount of seconds a user has to fill in the jump modal of a paged message
+const JUMP_TIMEOUT: u64 = 60;
+
 /// Send a paged message.
 ///
 /// Send a navigable paged message using [`Pager`].
+/// If the pager has a search index, the message also has a "Jump" button, which asks for a search
+/// term in a modal and jumps to the page containing it, see [`Pager.jump`].
 /// The message is stop being observed after `timeout` (in seconds) is elapsed.
 ///
 /// [`Pager`]: crate::pager::Pager
-pub async fn page<C, D>(
-    ctx: &C, channel_id: &ChannelId, pager: &mut Pager<D, String>, timeout: u64,
-) -> Result<()>
+/// [`Pager.jump`]: crate::pager::Pager::jump
+pub async fn page<C, D, P>(ctx: &C, channel_id: &ChannelId, pager: &mut Pager<D, P>, timeout: u64) -> Result<()>
 where
     C: AsRef<Http> + AsRef<ShardMessenger> + CacheHttp,
-    D: ToPage<Page = String>,
+    D: ToPage<Page = P>,
+    P: MessagePage,
 {
-    let content = pager.get_page();
+    let page = pager.get_page();
     if pager.len() == 1 {
-        channel_id.say(ctx, content).await?;
+        channel_id
+            .send_message(ctx, |m| {
+                page.build_message(m);
+                m
+            })
+            .await?;
         return Ok(());
     }
     let msg = channel_id
         .send_message(ctx, |m| {
-            m.content(content)
-                .components(|c| c.create_action_row(|ar| create_page_buttons(ar, 0, 2)))
+            page.build_message(m);
+            m.components(|c| c.create_action_row(|ar| create_page_buttons(ar, 0, 2, pager.is_searchable())))
         })
         .await?;
 
@@ -140,6 +254,31 @@ where
                 pager.last();
                 update_page_message(mci, ctx, pager).await?;
             }
+            "JUMP" => {
+                mci.create_interaction_response(ctx, |r| {
+                    r.kind(InteractionResponseType::Modal).interaction_response_data(|d| {
+                        d.custom_id("JUMP").title("Jump to page").components(|c| {
+                            c.create_action_row(|ar| {
+                                ar.create_input_text(|t| {
+                                    t.custom_id("TERM")
+                                        .label("Ign or search term")
+                                        .style(InputTextStyle::Short)
+                                        .required(true)
+                                })
+                            })
+                        })
+                    })
+                })
+                .await?;
+                let modal = msg
+                    .await_modal_interaction(ctx)
+                    .author_id(mci.user.id)
+                    .timeout(Duration::from_secs(JUMP_TIMEOUT))
+                    .await;
+                if let Some(modal) = modal {
+                    jump_page_message(modal, ctx, pager).await?;
+                }
+            }
             _ => {}
         }
     }
@@ -147,28 +286,78 @@ where
     Ok(())
 }
 
+/// Jumps paged message to the page containing the search term submitted in the jump modal
+async fn jump_page_message<D, P>(
+    modal: Arc<ModalSubmitInteraction>, http: &impl AsRef<Http>, pager: &mut Pager<D, P>,
+) -> Result<()>
+where
+    D: ToPage<Page = P>,
+    P: MessagePage,
+{
+    let term = modal
+        .data
+        .components
+        .iter()
+        .flat_map(|row| row.components.iter())
+        .find_map(|component| match component {
+            ActionRowComponent::InputText(input) if input.custom_id == "TERM" => Some(input.value.clone()),
+            _ => None,
+        })
+        .unwrap_or_default();
+
+    if !pager.jump(&term) {
+        return Ok(modal
+            .create_interaction_response(http, |r| {
+                r.kind(InteractionResponseType::ChannelMessageWithSource).interaction_response_data(|d| {
+                    d.content(format!("No page contains \"{}\"", term.trim())).ephemeral(true)
+                })
+            })
+            .await?);
+    }
+    Ok(modal
+        .create_interaction_response(http, |r| {
+            r.kind(InteractionResponseType::UpdateMessage)
+                .interaction_response_data(|d| build_page_update(d, pager))
+        })
+        .await?)
+}
+
 /// Updates paged message
-async fn update_page_message<D>(
-    mci: Arc<MessageComponentInteraction>, http: &impl AsRef<Http>, pager: &Pager<D, String>,
+async fn update_page_message<D, P>(
+    mci: Arc<MessageComponentInteraction>, http: &impl AsRef<Http>, pager: &Pager<D, P>,
 ) -> Result<()>
 where
-    D: ToPage<Page = String>,
+    D: ToPage<Page = P>,
+    P: MessagePage,
 {
     Ok(mci
         .create_interaction_response(http, |r| {
-            r.kind(InteractionResponseType::UpdateMessage).interaction_response_data(|d| {
-                d.content(pager.get_page()).components(|c| {
-                    let mut ar = CreateActionRow::default();
-                    create_page_buttons(&mut ar, pager.index(), pager.len());
-                    c.set_action_row(ar)
-                })
-            })
+            r.kind(InteractionResponseType::UpdateMessage)
+                .interaction_response_data(|d| build_page_update(d, pager))
         })
         .await?)
 }
 
-/// Create paged message buttons
-fn create_page_buttons(ar: &mut CreateActionRow, index: usize, len: usize) -> &mut CreateActionRow {
+/// Replace the displayed page and buttons of a paged message with the pager's current page
+fn build_page_update<'a, 'b, D, P>(
+    d: &'b mut CreateInteractionResponseData<'a>, pager: &'a Pager<D, P>,
+) -> &'b mut CreateInteractionResponseData<'a>
+where
+    D: ToPage<Page = P>,
+    P: MessagePage,
+{
+    pager.get_page().build_update(d);
+    d.components(|c| {
+        let mut ar = CreateActionRow::default();
+        create_page_buttons(&mut ar, pager.index(), pager.len(), pager.is_searchable());
+        c.set_action_row(ar)
+    })
+}
+
+/// Create paged message buttons, `searchable` adds the "Jump" button
+fn create_page_buttons(
+    ar: &mut CreateActionRow, index: usize, len: usize, searchable: bool,
+) -> &mut CreateActionRow {
     let mut first = CreateButton::default();
     first
         .custom_id("FIRST")
@@ -192,5 +381,11 @@ fn create_page_buttons(ar: &mut CreateActionRow, index: usize, len: usize) -> &m
         .disabled(index == len - 1);
     ar.add_button(last);
 
+    if searchable {
+        let mut jump = CreateButton::default();
+        jump.custom_id("JUMP").style(ButtonStyle::Primary).label("Jump");
+        ar.add_button(jump);
+    }
+
     ar
 }
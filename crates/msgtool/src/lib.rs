@@ -1,4 +1,7 @@
 //! Utilities related to discord messages
+mod canvas;
+pub mod card;
+mod font;
 #[warn(missing_docs, missing_debug_implementations)]
 pub mod interact;
 pub mod pager;
@@ -57,6 +57,22 @@
 //! pager.next();
 //! assert!(pager.get_page() == &String::from("FOO(1)"));
 //! ```
+//! A [`Pager`] can also carry a search index with [`Pager.with_index`], which lists the search keys
+//! of each page, so it can jump to the page containing a key using [`Pager.jump`].
+//! ```
+//! use msgtool::pager::Pager;
+//! use msgtool::table::TableData;
+//!
+//! let table = vec![vec!["Pucaet", "10M"], vec!["SephDark18", "15B"], vec!["Jeron", "123"]];
+//! let pages = TableData::paginate(table, vec!["ign", "xp"], 2);
+//! let index = pages.iter().map(TableData::search_keys).collect();
+//! let mut pager = Pager::new(pages).with_index(index);
+//!
+//! assert!(pager.jump("jeron"));
+//! assert!(pager.index() == 1);
+//! assert!(!pager.jump("Notch"));
+//! assert!(pager.index() == 1);
+//! ```
 
 /// Trait for page data that can be converted to page of type [`ToPage::Page`]
 pub trait ToPage {
@@ -77,6 +93,8 @@ where
     index: usize,
     /// Became true of all page data are generated
     is_full: bool,
+    /// Search keys of each page, see [`Pager.with_index`]
+    search_index: Option<Vec<Vec<String>>>,
 }
 
 impl<D, P> Pager<D, P>
@@ -94,11 +112,62 @@ where
             panic!("Empty pager data")
         }
         let pages = Vec::with_capacity(data.len());
-        let mut pager = Self { data, index: 0, pages, is_full: false };
+        let mut pager = Self { data, index: 0, pages, is_full: false, search_index: None };
         pager.pages.push(Some(pager.make_page()));
         pager
     }
 
+    /// Add a search index to the pager, which is the search keys of each page in order, so
+    /// [`Pager.jump`] can find the page containing a key.
+    ///
+    /// # Panic
+    /// Panics if the index doesn't have the same amount of pages as the page data.
+    pub fn with_index(mut self, index: Vec<Vec<String>>) -> Self {
+        if index.len() != self.data.len() {
+            panic!("Search index doesn't match the page data")
+        }
+        self.search_index = Some(index);
+        self
+    }
+
+    /// Check if the pager has a search index
+    pub fn is_searchable(&self) -> bool {
+        self.search_index.is_some()
+    }
+
+    /// Find the index of the page containing a search key that matches `term`, ignoring case.
+    ///
+    /// Pages with a key equal to `term` are preferred over pages with a key that only contains it.
+    /// Returns `None` if no key matches or the pager has no search index.
+    pub fn find(&self, term: &str) -> Option<usize> {
+        let index = self.search_index.as_ref()?;
+        let term = term.trim().to_lowercase();
+        if term.is_empty() {
+            return None;
+        }
+        index
+            .iter()
+            .position(|keys| keys.iter().any(|key| key.to_lowercase() == term))
+            .or_else(|| index.iter().position(|keys| keys.iter().any(|key| key.to_lowercase().contains(&term))))
+    }
+
+    /// Set the page index to the page containing a search key that matches `term`, see
+    /// [`Pager.find`].
+    ///
+    /// Returns if a matching page is found, otherwise the page index is unchanged.
+    pub fn jump(&mut self, term: &str) -> bool {
+        match self.find(term) {
+            Some(index) => {
+                self.index = index;
+                if !self.is_full {
+                    self.try_add_page();
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
     /// Generate the current page according to the index
     fn make_page(&self) -> P {
         self.data
@@ -16,7 +16,7 @@ use tokio::sync::RwLock;
 use memberdb::model::wynn::McId;
 use memberdb::DB;
 use util::discord::PublicChannel;
-use util::ok;
+use util::some;
 
 /// A target is a generalization of an object which the bot can act upon
 ///
@@ -32,7 +32,8 @@ use util::ok;
 /// This is referred to as hinted target.
 ///
 /// - Mc account: `m:(ign)`
-/// - Discord account: `d:(username)`
+/// - Discord account: `d:(username)`, the username can also be a nickname or a legacy
+///   `name#discriminator` tag
 /// - Discord role: `r:(name)`
 /// - Discord channel: `c:(name)`
 ///
@@ -80,27 +81,7 @@ impl<'a> TargetObject<'a> {
         name: &'a str,
     ) -> Result<TargetObject<'a>> {
         match prefix {
-            "m" => {
-                if !wynn::utils::is_valid_ign(name) {
-                    bail!("Invalid mc ign")
-                }
-
-                // Tries to get mcid from database first, if fails, then mojang api is used
-                let id = {
-                    let db = db.read().await;
-                    McId::from_ign(&mut db.exe(), name).await?
-                };
-                match id {
-                    Some(McId(id)) => Ok(Self::Mc(id)),
-                    None => {
-                        let id = ok!(
-                            wynn::get_id(client, name).await,
-                            bail!("Failed to find player with given ign")
-                        );
-                        Ok(Self::Mc(id))
-                    }
-                }
-            }
+            "m" => Ok(Self::Mc(parse_ign(db, client, name).await?.0)),
             _ => {
                 let d_obj = DiscordObject::parse(cache_http, guild, prefix, name).await?;
                 Ok(Self::Discord(Box::new(d_obj)))
@@ -109,6 +90,61 @@ impl<'a> TargetObject<'a> {
     }
 }
 
+/// Get the mcid of an ign, which is the parsing used by the mc account target (`m:(ign)`).
+///
+/// Unlike the other target parsing, this doesn't need a discord guild.
+///
+/// If no one currently uses the ign, players in the database that previously used it are checked,
+/// so a player can still be found after a name change.
+/// Fails if more than one player previously used it, see [`parse_old_ign`].
+pub async fn parse_ign(db: &RwLock<DB>, client: &Client, ign: &str) -> Result<McId> {
+    if !wynn::utils::is_valid_ign(ign) {
+        bail!("Invalid mc ign")
+    }
+
+    // Tries to get mcid from database first, if fails, then mojang api is used, if both fail, then
+    // previously used igns are checked
+    let id = {
+        let db = db.read().await;
+        McId::from_ign(&mut db.exe(), ign).await?
+    };
+    if let Some(id) = id {
+        return Ok(id);
+    }
+    if let Ok(id) = wynn::get_id(client, ign).await {
+        return Ok(McId(id));
+    }
+
+    let mut owners = {
+        let db = db.read().await;
+        memberdb::ign_history::previous_owners(&mut db.exe(), ign).await?
+    };
+    if owners.len() > 1 {
+        bail!("Multiple players previously used this ign")
+    }
+    Ok(some!(owners.pop(), bail!("Failed to find player with given ign")))
+}
+
+/// Get the mcids of players in the database that previously used `ign` and no longer do, with the
+/// one that stopped using it most recently first.
+///
+/// Returns an empty list if someone currently uses the ign, according to the database or the
+/// mojang api.
+pub async fn parse_old_ign(db: &RwLock<DB>, client: &Client, ign: &str) -> Result<Vec<McId>> {
+    let owners = {
+        let db = db.read().await;
+        if McId::from_ign(&mut db.exe(), ign).await?.is_some() {
+            return Ok(Vec::new());
+        }
+        memberdb::ign_history::previous_owners(&mut db.exe(), ign).await?
+    };
+    // Only ask mojang api if needed, as the ign is likely not used by anyone
+    if !owners.is_empty() && wynn::get_id(client, ign).await.is_ok() {
+        return Ok(Vec::new());
+    }
+    Ok(owners)
+}
+
 /// Types of discord objects, directly corresponds to [`DiscordObject`]
 #[derive(Debug, PartialEq, Eq)]
 pub enum DiscordObjectType {
@@ -133,7 +169,8 @@ pub enum DiscordObjectType {
 /// General form: `(hint-prefix):(name)`
 /// This is referred to as hinted target.
 ///
-/// - Discord account: `d:(username)`
+/// - Discord account: `d:(username)`, the username can also be a nickname or a legacy
+///   `name#discriminator` tag
 /// - Discord role: `r:(name)`
 /// - Discord channel: `c:(name)`
 ///
@@ -1,11 +1,13 @@
 //! Utilities for formatting string presentation of database profiles
-use memberdb::model::db::Profiles;
+use memberdb::goal::Goal;
+use memberdb::model::db::{Profiles, Stat};
 use memberdb::model::discord::DiscordProfile;
 use memberdb::model::guild::GuildProfile;
 use memberdb::model::wynn::WynnProfile;
 use serenity::client::Cache;
 
 use util::some;
+use util::string::StatFormat;
 
 /// Get the name of a member given its profiles.
 ///
@@ -13,7 +15,7 @@ use util::some;
 ///
 /// The first string is the name, and is formatted as:
 /// "(member or guild rank) (ign or discord username)"
-/// With discord username formatted as "username#discriminator".
+/// With discord username formatted by [`util::discord::user_tag`].
 ///
 /// Member rank is prioritized over guild rank, and if both doesn't exists, then the rank portion
 /// of the name is empty. Ign is prioritized over discord username.
@@ -38,7 +40,7 @@ pub async fn get_names(cache: &Cache, profiles: &Profiles) -> (String, String) {
         Some(discord) => discord
             .id
             .to_user(cache)
-            .map(|user| format!("{}#{}", user.name, user.discriminator)),
+            .map(|user| util::discord::user_tag(&user.name, user.discriminator)),
         None => None,
     };
 
@@ -64,11 +66,12 @@ pub async fn get_names(cache: &Cache, profiles: &Profiles) -> (String, String) {
 
 /// Format guild stats.
 ///
-/// Given a guild profile, return all its stats as list of tuples in the form of:
+/// Given a guild profile, return all its stats formatted with `fmt` as list of tuples in the form of:
 /// (stat name, formatted stat).
 /// ```
 /// use memberdb::model::guild::{GuildProfile, GuildRank};
 /// use msgtool::profile::format_guild_stat_fields;
+/// use util::string::StatFormat;
 ///
 /// let profile = GuildProfile {
 ///     id: "3f6dc89b-444d-4f28-b1dd-c3cac33ea152".to_string(),
@@ -76,63 +79,92 @@ pub async fn get_names(cache: &Cache, profiles: &Profiles) -> (String, String) {
 ///     rank: GuildRank::Chief,
 ///     xp: 1234567,
 ///     xp_week: 123,
+///     joined: Some("2020-05-04T13:37:48.000Z".to_string()),
+///     wars: 12,
 /// };
 ///
-/// assert!(format_guild_stat_fields(&Some(profile)) == vec! [
+/// assert!(format_guild_stat_fields(&Some(profile), &StatFormat::default()) == vec! [
 ///     ("Guild Rank", "Chief".to_string()),
 ///     ("Total XP Contributed", "1,234,567".to_string()),
 ///     ("Weekly XP Contributed", "123".to_string()),
+///     ("Joined", "2020-05-04".to_string()),
+///     ("Wars", "12".to_string()),
 /// ]);
-/// assert!(format_guild_stat_fields(&None).is_empty());
+/// assert!(format_guild_stat_fields(&None, &StatFormat::default()).is_empty());
 /// ```
-pub fn format_guild_stat_fields(guild: &Option<GuildProfile>) -> Vec<(&str, String)> {
+pub fn format_guild_stat_fields(
+    guild: &Option<GuildProfile>, fmt: &StatFormat,
+) -> Vec<(&'static str, String)> {
     match guild {
-        Some(guild) => vec![
-            ("Guild Rank", guild.rank.to_string()),
-            ("Total XP Contributed", util::string::fmt_num(guild.xp, false)),
-            ("Weekly XP Contributed", util::string::fmt_num(guild.xp_week, false)),
-        ],
+        Some(guild) => {
+            let mut fields = vec![
+                ("Guild Rank", guild.rank.to_string()),
+                ("Total XP Contributed", fmt.num(guild.xp, false)),
+                ("Weekly XP Contributed", fmt.num(guild.xp_week, false)),
+            ];
+            if let Some(joined) = &guild.joined {
+                // Only the date part of the datetime is displayed
+                fields.push(("Joined", joined.get(..10).unwrap_or(joined).to_string()));
+            }
+            fields.push(("Wars", fmt.num(guild.wars, false)));
+            fields
+        }
         None => Vec::new(),
     }
 }
 
 /// Format discord stats.
 ///
-/// Given a discord profile, return all its stats as list of tuples in the form of:
+/// Given a discord profile, return all its stats formatted with `fmt` as list of tuples in the form of:
 /// (stat name, formatted stat).
 ///
 /// Note that only the used stats are formatted.
 /// ```
-/// use memberdb::model::discord::DiscordProfile;
+/// use memberdb::model::discord::{DiscordId, DiscordProfile};
+/// use memberdb::model::member::MemberId;
 /// use msgtool::profile::format_discord_stat_fields;
+/// use util::string::StatFormat;
 ///
 /// let profile = DiscordProfile {
-///     id: 658478931682394134,
-///     mid: Some(24),
+///     id: DiscordId(658478931682394134),
+///     mid: Some(MemberId(24)),
 ///     message: 1234567,
 ///     message_week: 123,
 ///     image: 0,
-///     reaction: 0,
+///     reaction_given: 42,
+///     reaction_received: 1500,
 ///     voice: 70,
 ///     voice_week: 12,
 ///     activity: 0,
+///     stream: 3661,
+///     stream_week: 0,
 /// };
 ///
-/// assert!(format_discord_stat_fields(&Some(profile)) == vec! [
+/// assert!(format_discord_stat_fields(&Some(profile), &StatFormat::default()) == vec! [
 ///     ("Total Messages", "1,234,567".to_string()),
 ///     ("Weekly Messages", "123".to_string()),
 ///     ("Total Voice Time", "1m 10s".to_string()),
 ///     ("Weekly Voice Time", "12s".to_string()),
+///     ("Total Stream Time", "1h 1m 1s".to_string()),
+///     ("Weekly Stream Time", "0s".to_string()),
+///     ("Reactions Given", "42".to_string()),
+///     ("Reactions Received", "1,500".to_string()),
 /// ]);
-/// assert!(format_discord_stat_fields(&None).is_empty());
+/// assert!(format_discord_stat_fields(&None, &StatFormat::default()).is_empty());
 /// ```
-pub fn format_discord_stat_fields(discord: &Option<DiscordProfile>) -> Vec<(&str, String)> {
+pub fn format_discord_stat_fields(
+    discord: &Option<DiscordProfile>, fmt: &StatFormat,
+) -> Vec<(&'static str, String)> {
     match discord {
         Some(discord) => vec![
-            ("Total Messages", util::string::fmt_num(discord.message, false)),
-            ("Weekly Messages", util::string::fmt_num(discord.message_week, false)),
-            ("Total Voice Time", util::string::fmt_second(discord.voice)),
-            ("Weekly Voice Time", util::string::fmt_second(discord.voice_week)),
+            ("Total Messages", fmt.num(discord.message, false)),
+            ("Weekly Messages", fmt.num(discord.message_week, false)),
+            ("Total Voice Time", fmt.second(discord.voice)),
+            ("Weekly Voice Time", fmt.second(discord.voice_week)),
+            ("Total Stream Time", fmt.second(discord.stream)),
+            ("Weekly Stream Time", fmt.second(discord.stream_week)),
+            ("Reactions Given", fmt.num(discord.reaction_given, false)),
+            ("Reactions Received", fmt.num(discord.reaction_received, false)),
         ],
         None => Vec::new(),
     }
@@ -140,11 +172,12 @@ pub fn format_discord_stat_fields(discord: &Option<DiscordProfile>) -> Vec<(&str
 
 /// Format wynn stats.
 ///
-/// Given a wynn profile, return all its stats as list of tuples in the form of:
+/// Given a wynn profile, return all its stats formatted with `fmt` as list of tuples in the form of:
 /// (stat name, formatted stat).
 /// ```
 /// use memberdb::model::wynn::WynnProfile;
 /// use msgtool::profile::format_wynn_stat_fields;
+/// use util::string::StatFormat;
 ///
 /// let profile = WynnProfile {
 ///     id: "3f6dc89b-444d-4f28-b1dd-c3cac33ea152".to_string(),
@@ -159,20 +192,136 @@ pub fn format_discord_stat_fields(discord: &Option<DiscordProfile>) -> Vec<(&str
 ///     activity_avg_range: 1,
 /// };
 ///
-/// assert!(format_wynn_stat_fields(&Some(profile)) == vec! [
+/// assert!(format_wynn_stat_fields(&Some(profile), &StatFormat::default()) == vec! [
 ///     ("Total Online Time", "1m 10s".to_string()),
 ///     ("Weekly Online Time", "12s".to_string()),
 ///     ("Average Online Time", "5s".to_string())
 /// ]);
-/// assert!(format_wynn_stat_fields(&None).is_empty());
+/// assert!(format_wynn_stat_fields(&None, &StatFormat::default()).is_empty());
 /// ```
-pub fn format_wynn_stat_fields(wynn: &Option<WynnProfile>) -> Vec<(&str, String)> {
+pub fn format_wynn_stat_fields(
+    wynn: &Option<WynnProfile>, fmt: &StatFormat,
+) -> Vec<(&'static str, String)> {
     match wynn {
         Some(wynn) => vec![
-            ("Total Online Time", util::string::fmt_second(wynn.activity)),
-            ("Weekly Online Time", util::string::fmt_second(wynn.activity_week)),
-            ("Average Online Time", util::string::fmt_second(wynn.activity_avg)),
+            ("Total Online Time", fmt.second(wynn.activity)),
+            ("Weekly Online Time", fmt.second(wynn.activity_week)),
+            ("Average Online Time", fmt.second(wynn.activity_avg)),
         ],
         None => Vec::new(),
     }
 }
+
+/// Format a stat value with `fmt`, durations are formatted as time and other stats as numbers in
+/// shorthand.
+/// ```
+/// use memberdb::model::db::Stat;
+/// use msgtool::profile::format_stat_val;
+/// use util::string::StatFormat;
+///
+/// let fmt = StatFormat::default();
+/// assert!(format_stat_val(&Stat::WeeklyXp, 2_500_000, &fmt) == "2.5M");
+/// assert!(format_stat_val(&Stat::WeeklyVoice, 3661, &fmt) == "1h 1m 1s");
+/// let fmt = StatFormat { shorthand: false, ..fmt };
+/// assert!(format_stat_val(&Stat::WeeklyXp, 2_500_000, &fmt) == "2,500,000");
+/// ```
+pub fn format_stat_val(stat: &Stat, val: i64, fmt: &StatFormat) -> String {
+    if stat.to_column().is_duration() {
+        fmt.second(val)
+    } else {
+        fmt.num(val, true)
+    }
+}
+
+/// Format the progress of a goal, with a progress bar and percentage.
+/// ```
+/// use memberdb::goal::Goal;
+/// use memberdb::model::db::Stat;
+/// use memberdb::model::member::MemberId;
+/// use msgtool::profile::format_goal_progress;
+/// use util::string::StatFormat;
+///
+/// let goal = Goal { mid: MemberId(1), stat: Stat::WeeklyXp, target: 2_000_000, progress: 1_500_000 };
+/// assert!(format_goal_progress(&goal, &StatFormat::default()) == "1.5M / 2M `████████░░` 75%");
+/// ```
+pub fn format_goal_progress(goal: &Goal, fmt: &StatFormat) -> String {
+    let ratio = if goal.target > 0 { goal.progress as f64 / goal.target as f64 } else { 1.0 };
+    format!(
+        "{} / {} `{}` {}%",
+        format_stat_val(&goal.stat, goal.progress, fmt),
+        format_stat_val(&goal.stat, goal.target, fmt),
+        util::string::progress_bar(ratio, 10),
+        (ratio.min(1.0) * 100.0).round() as i64
+    )
+}
+
+/// Get the comparable stats of a member's profiles, as list of tuples in the form of:
+/// (stat name, value, whether the value is a duration).
+fn comparable_stats(profiles: &Profiles) -> Vec<(&'static str, i64, bool)> {
+    let mut stats = Vec::new();
+    if let Some(guild) = &profiles.guild {
+        stats.push(("Total XP Contributed", guild.xp, false));
+        stats.push(("Weekly XP Contributed", guild.xp_week, false));
+        stats.push(("Wars", guild.wars, false));
+    }
+    if let Some(wynn) = &profiles.wynn {
+        stats.push(("Total Online Time", wynn.activity, true));
+        stats.push(("Weekly Online Time", wynn.activity_week, true));
+        stats.push(("Average Online Time", wynn.activity_avg, true));
+    }
+    if let Some(discord) = &profiles.discord {
+        stats.push(("Total Messages", discord.message, false));
+        stats.push(("Weekly Messages", discord.message_week, false));
+        stats.push(("Total Voice Time", discord.voice, true));
+        stats.push(("Weekly Voice Time", discord.voice_week, true));
+        stats.push(("Total Stream Time", discord.stream, true));
+        stats.push(("Reactions Given", discord.reaction_given, false));
+        stats.push(("Reactions Received", discord.reaction_received, false));
+    }
+    stats
+}
+
+/// Format a stat of two members side by side, with the difference between them and who leads.
+/// `names` are the names of the two members.
+/// ```
+/// use msgtool::profile::format_stat_diff;
+/// use util::string::StatFormat;
+///
+/// let names = ("Pucaet", "SephDark18");
+/// let fmt = StatFormat::default();
+/// assert!(format_stat_diff(names, 1_500, 1_200, false, &fmt) == "1,500 | 1,200\n**Pucaet** leads by 300");
+/// assert!(format_stat_diff(names, 70, 3671, true, &fmt) == "1m 10s | 1h 1m 11s\n**SephDark18** leads by 1h 1s");
+/// assert!(format_stat_diff(names, 7, 7, false, &fmt) == "7 | 7\nTied");
+/// ```
+pub fn format_stat_diff(names: (&str, &str), a: i64, b: i64, duration: bool, fmt: &StatFormat) -> String {
+    let format = |val: i64| {
+        if duration {
+            fmt.second(val)
+        } else {
+            fmt.num(val, false)
+        }
+    };
+    let lead = match a.cmp(&b) {
+        std::cmp::Ordering::Greater => format!("**{}** leads by {}", names.0, format(a - b)),
+        std::cmp::Ordering::Less => format!("**{}** leads by {}", names.1, format(b - a)),
+        std::cmp::Ordering::Equal => "Tied".to_string(),
+    };
+    format!("{} | {}\n{}", format(a), format(b), lead)
+}
+
+/// Compare the stats of two members.
+///
+/// Given their profiles and names, return the stats both of them have as list of tuples in the
+/// form of: (stat name, formatted comparison), see [`format_stat_diff`].
+pub fn format_stat_comparison(
+    a: &Profiles, b: &Profiles, names: (&str, &str), fmt: &StatFormat,
+) -> Vec<(&'static str, String)> {
+    let b_stats = comparable_stats(b);
+    comparable_stats(a)
+        .into_iter()
+        .filter_map(|(name, a_val, duration)| {
+            let (_, b_val, _) = b_stats.iter().find(|(b_name, _, _)| *b_name == name)?;
+            Some((name, format_stat_diff(names, a_val, *b_val, duration, fmt)))
+        })
+        .collect()
+}
@@ -6,6 +6,10 @@
 //! The provided struct [`TableData`] allows for table pagination, and implements [`ToPage`] which
 //! uses [`format_table`], so it can also be used with [`Pager`].
 //!
+//! Tables can also be rendered into PNG images with [`render_table`], which don't break on small
+//! screens like the monospace tables do. [`TableImage`] is the image counterpart of
+//! [`TableData`].
+//!
 //! This module uses `Vec<Vec<&str>>` to represent table, and provides [`borrow_table`] and
 //! [`borrow_row`] for conversion from `Vec<Vec<String>>` to `Vec<Vec<&str>>`.
 //!
@@ -13,6 +17,9 @@
 //! [`Pager`]: crate::pager::Pager
 use util::some;
 
+use crate::canvas::Canvas;
+use crate::font::{GLYPH_HEIGHT, GLYPH_WIDTH};
+use crate::interact::Image;
 use crate::pager::ToPage;
 
 const BOX_CORNER_TL: char = '╭';
@@ -30,6 +37,15 @@ const BOX_CROSS: char = '╪';
 const BULLET_EMPTY: char = '◌';
 const BULLET_FULL: char = '●';
 
+/// Size of a font pixel in a table image
+const IMAGE_SCALE: usize = 2;
+/// Space between the content of a cell and its borders in a table image, in font pixels
+const IMAGE_PADDING: usize = 3;
+const IMAGE_BACKGROUND: [u8; 3] = [0x2F, 0x31, 0x36];
+const IMAGE_HEADER_BACKGROUND: [u8; 3] = [0x20, 0x22, 0x25];
+const IMAGE_TEXT: [u8; 3] = [0xDC, 0xDD, 0xDE];
+const IMAGE_LINE: [u8; 3] = [0x4F, 0x54, 0x5C];
+
 /// Format a 2d string vector into a fancy table.
 ///
 /// If `page_info` is provided then a page index indicator is included.
@@ -229,6 +245,80 @@ pub fn make_page_indicator(page_index: usize, page_num: usize) -> String {
     s
 }
 
+/// Render a 2d string vector into a table image encoded as PNG.
+///
+/// If `page_info` is provided then a page index indicator is included under the table.
+/// `page_info` is a tuple of current page index and total number of pages in that order.
+///
+/// Characters that aren't printable ascii are drawn as boxes.
+/// ```
+/// use msgtool::table::render_table;
+///
+/// let table = vec![vec!["name", "rank", "xp"], vec!["foo", "Owner", "10M"]];
+/// let png = render_table(&table, Some((1, 3)));
+/// assert!(png.starts_with(b"\x89PNG"));
+/// ```
+///
+/// # Panic
+/// Panics if the table is empty.
+pub fn render_table(table: &[Vec<&str>], page_info: Option<(usize, usize)>) -> Vec<u8> {
+    if table.is_empty() {
+        panic!("Can't render empty table");
+    }
+
+    let char_w = (GLYPH_WIDTH + 1) * IMAGE_SCALE;
+    let char_h = GLYPH_HEIGHT * IMAGE_SCALE;
+    let pad = IMAGE_PADDING * IMAGE_SCALE;
+    let line = IMAGE_SCALE;
+    let row_h = char_h + 2 * pad;
+
+    let col_widths: Vec<usize> = (0..table[0].len())
+        .map(|col_i| {
+            let chars = table.iter().map(|row| row[col_i].chars().count()).max().unwrap_or(0);
+            chars * char_w + 2 * pad
+        })
+        .collect();
+    let table_w = col_widths.iter().sum::<usize>() + (col_widths.len() + 1) * line;
+    let table_h = table.len() * row_h + (table.len() + 1) * line;
+    let height = if page_info.is_some() { table_h + row_h } else { table_h };
+
+    let mut canvas = Canvas::new(table_w, height, IMAGE_BACKGROUND);
+    canvas.fill_rect(0, line, table_w, row_h, IMAGE_HEADER_BACKGROUND);
+
+    // Horizontal borders
+    for row_i in 0..=table.len() {
+        canvas.fill_rect(0, row_i * (row_h + line), table_w, line, IMAGE_LINE);
+    }
+    // Vertical borders
+    let mut x = 0;
+    canvas.fill_rect(x, 0, line, table_h, IMAGE_LINE);
+    for w in &col_widths {
+        x += w + line;
+        canvas.fill_rect(x, 0, line, table_h, IMAGE_LINE);
+    }
+
+    for (row_i, row) in table.iter().enumerate() {
+        let y = row_i * (row_h + line) + line + pad;
+        let mut x = line;
+        for (item, w) in row.iter().zip(&col_widths) {
+            canvas.draw_text(x + pad, y, item, IMAGE_TEXT, IMAGE_SCALE);
+            x += w + line;
+        }
+    }
+
+    if let Some((page_index, page_num)) = page_info {
+        canvas.draw_text(
+            pad,
+            table_h + pad,
+            &format!("{}/{}", page_index, page_num),
+            IMAGE_TEXT,
+            IMAGE_SCALE,
+        );
+    }
+
+    canvas.encode()
+}
+
 /// Data needed to create a table.
 ///
 /// This struct is designed to be used with [`Pager`] for creating paged table.
@@ -303,6 +393,20 @@ impl<'a> TableData<'a> {
 
         pages
     }
+
+    /// Get the search keys of the table for [`Pager.with_index`], which are all the cells except
+    /// the header's.
+    /// ```
+    /// use msgtool::table::TableData;
+    ///
+    /// let table = TableData(vec![vec!["name", "rank"], vec!["foo", "Owner"], vec!["bar", "Chief"]]);
+    /// assert!(table.search_keys() == vec!["foo", "Owner", "bar", "Chief"]);
+    /// ```
+    ///
+    /// [`Pager.with_index`]: crate::pager::Pager::with_index
+    pub fn search_keys(&self) -> Vec<String> {
+        self.0.iter().skip(1).flatten().map(|cell| cell.to_string()).collect()
+    }
 }
 
 impl<'a> ToPage for TableData<'a> {
@@ -323,3 +427,26 @@ impl<'a> ToPage for TableData<'a> {
         s
     }
 }
+
+/// Data needed to create a table image.
+///
+/// This is the same as [`TableData`], except that it is rendered using [`render_table`].
+#[derive(Debug, PartialEq, Eq, Default)]
+pub struct TableImage<'a>(pub Vec<Vec<&'a str>>);
+
+impl<'a> ToPage for TableImage<'a> {
+    type Page = Image;
+
+    /// Renders into table image using [`render_table`].
+    fn to_page(&self, page_info: Option<(usize, usize)>) -> Self::Page {
+        let data = render_table(
+            &self.0,
+            // No page index indicator if there is only one page
+            match page_info {
+                Some((_, 1)) => None,
+                _ => page_info,
+            },
+        );
+        Image { name: "table.png".to_string(), data }
+    }
+}
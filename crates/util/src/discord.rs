@@ -9,33 +9,139 @@ use serenity::model::guild::{Guild, Member, Role};
 use serenity::model::id::ChannelId;
 use serenity::model::permissions::Permissions;
 
-/// Search member in cache using [`Guild::member_named`], but if it isn't cached,
-/// [`Guild::search_members`] is used to search over API.
+/// Search member by name in cache, but if it isn't cached, [`Guild::search_members`] is used to
+/// search over API.
+///
+/// The name is matched with [`match_user_name`], so it can be a unique username, a legacy
+/// "name#discriminator" tag or a nickname, optionally prefixed with "@".
 ///
 /// # Errors
 /// Returns [`Error::Http`] if API returns an error.
 ///
-/// [`Guild::member_named`]: serenity::model::guild::Guild::member_named
 /// [`Guild::search_members`]: serenity::model::guild::Guild::search_members
 /// [`Error::Http`]: serenity::Error::Http
 pub async fn get_member_named<'a>(
     http: &'a Http, guild: &'a Guild, name: &'a str,
 ) -> Result<Option<Cow<'a, Member>>> {
-    match guild.member_named(name) {
-        Some(member) => Ok(Some(Cow::Borrowed(member))),
-        None => {
-            let mut members = guild
-                .search_members(http, name, Some(1))
-                .await
-                .context("Failed to search guild members")?;
-            if members.is_empty() {
-                Ok(None)
-            } else {
-                let member = members.remove(0);
-                Ok(Some(Cow::Owned(member)))
-            }
+    if let Some(member) = find_member_named(guild.members.values(), name) {
+        return Ok(Some(Cow::Borrowed(member)));
+    }
+
+    // The api searches by prefix of usernames and nicknames, so the discriminator is left out
+    let query = name.strip_prefix('@').unwrap_or(name);
+    let query = split_legacy_tag(query).map_or(query, |(name, _)| name);
+    let members =
+        guild.search_members(http, query, Some(10)).await.context("Failed to search guild members")?;
+    let member = match find_member_named(members.iter(), name) {
+        Some(member) => Some(member.clone()),
+        None => members.into_iter().next(),
+    };
+    Ok(member.map(Cow::Owned))
+}
+
+/// Find the member whose names best match `name`, see [`match_user_name`]
+fn find_member_named<'a>(members: impl Iterator<Item = &'a Member>, name: &str) -> Option<&'a Member> {
+    members
+        .filter_map(|member| {
+            let quality =
+                match_user_name(name, &member.user.name, member.user.discriminator, member.nick.as_deref())?;
+            Some((quality, member))
+        })
+        .min_by_key(|(quality, _)| *quality)
+        .map(|(_, member)| member)
+}
+
+/// Split a legacy "name#discriminator" tag
+fn split_legacy_tag(tag: &str) -> Option<(&str, u16)> {
+    let (name, discriminator) = tag.rsplit_once('#')?;
+    if discriminator.is_empty() || discriminator.len() > 4 {
+        return None;
+    }
+    Some((name, discriminator.parse().ok()?))
+}
+
+/// Check how well the names of a discord user match `query`, a lower value is a better match.
+/// Returns `None` if they don't match.
+///
+/// `query` can be a unique username, a legacy "name#discriminator" tag or a nickname, and may be
+/// prefixed with "@". Usernames and tags are preferred over nicknames, and unique usernames also
+/// match case-insensitively.
+/// ```
+/// use util::discord::match_user_name;
+///
+/// assert_eq!(match_user_name("pucaet", "pucaet", 0, None), Some(0));
+/// assert_eq!(match_user_name("@Pucaet", "pucaet", 0, Some("Puca")), Some(1));
+/// assert_eq!(match_user_name("Puca", "pucaet", 0, Some("Puca")), Some(2));
+/// assert_eq!(match_user_name("Pucaet#0042", "Pucaet", 42, None), Some(0));
+/// assert_eq!(match_user_name("Pucaet#42", "Pucaet", 42, None), Some(0));
+/// assert_eq!(match_user_name("Pucaet#0043", "Pucaet", 42, None), None);
+/// assert_eq!(match_user_name("pucaet", "Pucaet", 42, None), None);
+/// ```
+pub fn match_user_name(query: &str, name: &str, discriminator: u16, nick: Option<&str>) -> Option<u8> {
+    let query = query.strip_prefix('@').unwrap_or(query);
+    match split_legacy_tag(query) {
+        Some((tag_name, tag_discriminator)) if tag_name == name && tag_discriminator == discriminator => {
+            return Some(0)
         }
+        Some(_) => {}
+        None if query == name => return Some(0),
+        // Unique usernames are always lowercase
+        None if discriminator == 0 && query.eq_ignore_ascii_case(name) => return Some(1),
+        None => {}
+    }
+    if nick == Some(query) {
+        return Some(2);
     }
+    None
+}
+
+/// Format the name of a discord user.
+///
+/// Users that migrated to unique usernames have a discriminator of 0 and are named by their
+/// username alone, the others are named by their legacy "name#discriminator" tag.
+/// ```
+/// use util::discord::user_tag;
+///
+/// assert_eq!(user_tag("pucaet", 0), "pucaet");
+/// assert_eq!(user_tag("Pucaet", 42), "Pucaet#0042");
+/// ```
+pub fn user_tag(name: &str, discriminator: u16) -> String {
+    if discriminator == 0 {
+        name.to_string()
+    } else {
+        format!("{}#{:04}", name, discriminator)
+    }
+}
+
+/// Format the display name of a discord user, which is their nickname if they have one, otherwise
+/// it is the same as [`user_tag`].
+/// ```
+/// use util::discord::display_name;
+///
+/// assert_eq!(display_name(Some("Puca"), "pucaet", 0), "Puca");
+/// assert_eq!(display_name(None, "Pucaet", 42), "Pucaet#0042");
+/// ```
+pub fn display_name(nick: Option<&str>, name: &str, discriminator: u16) -> String {
+    match nick {
+        Some(nick) => nick.to_string(),
+        None => user_tag(name, discriminator),
+    }
+}
+
+/// Format the display name of a guild member, see [`display_name`]
+pub fn member_display_name(member: &Member) -> String {
+    display_name(member.nick.as_deref(), &member.user.name, member.user.discriminator)
+}
+
+/// Get the id of the shard that receives the events of a guild, out of `shard_count` shards
+/// ```
+/// use util::discord::guild_shard_id;
+///
+/// assert_eq!(guild_shard_id(81384788765712384, 1), 0);
+/// assert_eq!(guild_shard_id(81384788765712384, 16), 2);
+/// ```
+pub fn guild_shard_id(guild_id: u64, shard_count: u64) -> u64 {
+    (guild_id >> 22) % shard_count.max(1)
 }
 
 /// Return a channel's category and parent channel (if it is a thread) in a tuple of that order.
@@ -1,8 +1,10 @@
 //! General utilities
 #[warn(missing_docs, missing_debug_implementations)]
+#[cfg(feature = "discord")]
 pub mod discord;
 pub mod imp;
 pub mod string;
+pub mod task;
 pub mod tri;
 
 /// Given numbers a and b, return (a / b, a % b)
@@ -1,8 +1,12 @@
 //! String related functions
+use std::fmt;
+use std::str::FromStr;
+
 use anyhow::{bail, Result};
 use num_format::{Locale, ToFormattedString};
+use serde::{Deserialize, Serialize};
 
-use crate::{div_rem, ok};
+use crate::{div_rem, ioerr, ok};
 
 /// Join an iterator over [`&Display`] into string with ", "
 /// ```
@@ -79,62 +83,148 @@ pub fn fmt_second(seconds: i64) -> String {
 
 /// Parse a string into seconds.
 ///
-/// The string need to be in the format of `(whole integer)(time unit)`.
-/// Time unit can be the following:
-/// - `s`: seconds
-/// - `m`: minutes
-/// - `h`: hours
-/// - `d`: days
-/// - `w`: weeks
+/// The string need to be in the format of `(number)(time unit)`, where the number can have a
+/// fractional part, ex: "1.5h".
+/// Time unit can be the following, and can also be written in full, in english or french:
+/// - `s`, `sec`, `second`, `seconde`: seconds
+/// - `m`, `min`, `minute`: minutes
+/// - `h`, `hr`, `hour`, `heure`: hours
+/// - `d`, `j`, `day`, `jour`: days
+/// - `w`, `wk`, `week`, `semaine`: weeks
+///
+/// The number can contain `,` separators, ex: "12,000h".
+/// Multiple expressions can be chained together, with or without spaces in between: "10w2d21h",
+/// "1 hour and 30 minutes".
+/// ISO-8601 durations with weeks, days, hours, minutes and seconds are also accepted: "PT2H30M".
 ///
-/// The integer can contain `,` separators, ex: "12,000h".
-/// Multiple expressions can be chained together: "10w2d21h".
+/// The result is rounded to the nearest second.
 /// ```
 /// # use util::string::parse_second;
 /// assert!(parse_second("12s").unwrap() == 12);
 /// assert!(parse_second("12,000h").unwrap() == 43200000);
 /// assert!(parse_second("10w2d21h").unwrap() == 6296400);
+/// assert!(parse_second("1.5h").unwrap() == 5400);
+/// assert!(parse_second("90min").unwrap() == 5400);
+/// assert!(parse_second("1 hour and 30 minutes").unwrap() == 5400);
+/// assert!(parse_second("2 jours et 3 heures").unwrap() == 183600);
+/// assert!(parse_second("1d, 2h").unwrap() == 93600);
+/// assert!(parse_second("PT2H30M").unwrap() == 9000);
+/// assert!(parse_second("P1W2DT0.5S").unwrap() == 777601);
+/// assert!(parse_second("5 parsecs").is_err());
+/// assert!(parse_second("P1M").is_err());
+/// ```
+/// Output of [`fmt_second`] can be parsed back:
+/// ```
+/// # use util::string::{fmt_second, parse_second};
+/// for seconds in [1, 70, 3600, 90061, 1000000000] {
+///     assert!(parse_second(&fmt_second(seconds)).unwrap() == seconds as u64);
+/// }
 /// ```
 ///
 /// # Errors
 /// Returns [`Result::Err`] is the given string is empty or isn't in a valid format
 pub fn parse_second(s: &str) -> Result<u64> {
+    let s = s.trim().to_lowercase();
     if s.is_empty() {
         bail!("Empty")
     }
+    if let Some(iso) = s.strip_prefix('p') {
+        return parse_iso_duration(iso);
+    }
 
-    let mut seconds = 0;
-    let mut search_buffer = String::new();
-    for c in s.chars() {
-        // ignore ','
-        if c == ',' {
-            continue;
+    let mut seconds = 0.0;
+    let mut chars = s.chars().peekable();
+    loop {
+        while chars.next_if(|c| c.is_whitespace() || *c == ',').is_some() {}
+        if chars.peek().is_none() {
+            break;
         }
 
-        if "0123456789".contains(c) {
-            search_buffer.push(c);
-        } else {
-            if search_buffer.is_empty() {
-                bail!("Invalid format")
+        // Parse number, ignoring ','
+        let mut num = String::new();
+        while let Some(c) = chars.next_if(|c| c.is_ascii_digit() || *c == '.' || *c == ',') {
+            if c != ',' {
+                num.push(c);
+            }
+        }
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+
+        // Parse time unit
+        let mut unit = String::new();
+        while let Some(c) = chars.next_if(|c| c.is_alphabetic()) {
+            unit.push(c);
+        }
+        if num.is_empty() {
+            // Words joining the expressions
+            if unit == "and" || unit == "et" {
+                continue;
             }
-            // Parse collected number
-            let searched_num: u64 = ok!(search_buffer.parse(), bail!("Invalid number '{}'", search_buffer));
-            search_buffer.clear();
-
-            // Parse time unit
-            let multiplier = match c {
-                's' => 1,
-                'm' => 60,
-                'h' => 3600,
-                'd' => 86400,
-                'w' => 604800,
-                _ => bail!("Unknown time unit '{}'", c),
-            };
-            seconds += searched_num * multiplier;
+            bail!("Invalid format")
+        }
+        let num: f64 = ok!(num.parse(), bail!("Invalid number '{}'", num));
+        if unit.is_empty() {
+            bail!("Missing time unit after '{}'", num)
+        }
+        seconds += num * time_unit_seconds(&unit)?;
+    }
+
+    Ok(seconds.round() as u64)
+}
+
+/// Get the amount of seconds in a time unit
+fn time_unit_seconds(unit: &str) -> Result<f64> {
+    Ok(match unit {
+        "s" | "sec" | "secs" | "second" | "seconds" | "seconde" | "secondes" => 1.0,
+        "m" | "min" | "mins" | "minute" | "minutes" => 60.0,
+        "h" | "hr" | "hrs" | "hour" | "hours" | "heure" | "heures" => 3600.0,
+        "d" | "j" | "day" | "days" | "jour" | "jours" => 86400.0,
+        "w" | "wk" | "wks" | "week" | "weeks" | "semaine" | "semaines" => 604800.0,
+        _ => bail!("Unknown time unit '{}'", unit),
+    })
+}
+
+/// Parse an ISO-8601 duration without the leading `P` into seconds.
+///
+/// Years and months are rejected, as their length varies.
+fn parse_iso_duration(s: &str) -> Result<u64> {
+    if s.is_empty() || s == "t" {
+        bail!("Empty duration")
+    }
+
+    let mut seconds = 0.0;
+    let mut num = String::new();
+    let mut in_time = false;
+    for c in s.chars() {
+        if c.is_ascii_digit() || c == '.' || c == ',' {
+            // ISO-8601 allows ',' as decimal separator
+            num.push(if c == ',' { '.' } else { c });
+            continue;
         }
+        if c == 't' && !in_time && num.is_empty() {
+            in_time = true;
+            continue;
+        }
+        if num.is_empty() {
+            bail!("Invalid format")
+        }
+        let multiplier = match (in_time, c) {
+            (false, 'w') => 604800.0,
+            (false, 'd') => 86400.0,
+            (false, 'y' | 'm') => bail!("Years and months are not supported"),
+            (true, 'h') => 3600.0,
+            (true, 'm') => 60.0,
+            (true, 's') => 1.0,
+            _ => bail!("Unknown time unit '{}'", c),
+        };
+        let n: f64 = ok!(num.parse(), bail!("Invalid number '{}'", num));
+        num.clear();
+        seconds += n * multiplier;
+    }
+    if !num.is_empty() {
+        bail!("Missing time unit after '{}'", num)
     }
 
-    Ok(seconds)
+    Ok(seconds.round() as u64)
 }
 
 /// Format a number into String.
@@ -162,6 +252,122 @@ pub fn fmt_num(num: i64, shorthand: bool) -> String {
     num.to_formatted_string(&Locale::en)
 }
 
+/// All variants of [`DurationStyle`]
+pub const DURATION_STYLES: [DurationStyle; 2] = [DurationStyle::Units, DurationStyle::Hours];
+
+/// How durations are displayed
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DurationStyle {
+    /// Broken down into units of time, ex: "12h 30m", see [`fmt_second`]
+    #[default]
+    Units,
+    /// In hours with a fractional part, ex: "12.5h"
+    Hours,
+}
+
+impl FromStr for DurationStyle {
+    type Err = std::io::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s.to_lowercase().as_str() {
+            "units" => Self::Units,
+            "hours" => Self::Hours,
+            _ => return ioerr!("Failed to parse '{}' as DurationStyle", s),
+        })
+    }
+}
+
+impl fmt::Display for DurationStyle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Units => write!(f, "units"),
+            Self::Hours => write!(f, "hours"),
+        }
+    }
+}
+
+/// Preferences of how stats are displayed, the default formats like [`fmt_second`] and
+/// [`fmt_num`].
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(default)]
+pub struct StatFormat {
+    /// How durations are displayed
+    pub duration: DurationStyle,
+    /// Amount of decimals of durations displayed in hours
+    pub hour_decimals: usize,
+    /// If large numbers can be displayed in shorthand, ex: "12.34M"
+    pub shorthand: bool,
+}
+
+impl Default for StatFormat {
+    fn default() -> Self {
+        Self { duration: DurationStyle::Units, hour_decimals: 1, shorthand: true }
+    }
+}
+
+impl StatFormat {
+    /// Format seconds into user friendly string.
+    /// ```
+    /// # use util::string::{DurationStyle, StatFormat};
+    /// let hours = StatFormat { duration: DurationStyle::Hours, ..StatFormat::default() };
+    /// assert!(StatFormat::default().second(45030) == "12h 30m 30s");
+    /// assert!(hours.second(45030) == "12.5h");
+    /// assert!(StatFormat { hour_decimals: 2, ..hours }.second(70) == "0.02h");
+    /// ```
+    pub fn second(&self, seconds: i64) -> String {
+        match self.duration {
+            DurationStyle::Units => fmt_second(seconds),
+            DurationStyle::Hours => format!("{:.*}h", self.hour_decimals, seconds as f64 / 3600.0),
+        }
+    }
+
+    /// Format a number into String, `shorthand` is ignored if shorthand is disabled.
+    /// ```
+    /// # use util::string::StatFormat;
+    /// let full = StatFormat { shorthand: false, ..StatFormat::default() };
+    /// assert!(StatFormat::default().num(12_345_000, true) == "12.34M");
+    /// assert!(full.num(12_345_000, true) == "12,345,000");
+    /// ```
+    pub fn num(&self, num: i64, shorthand: bool) -> String {
+        fmt_num(num, shorthand && self.shorthand)
+    }
+}
+
+impl fmt::Display for StatFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.duration {
+            DurationStyle::Units => write!(f, "durations in units")?,
+            DurationStyle::Hours => write!(f, "durations in hours with {} decimals", self.hour_decimals)?,
+        }
+        if self.shorthand {
+            write!(f, ", large numbers in shorthand")
+        } else {
+            write!(f, ", numbers in full")
+        }
+    }
+}
+
+/// Format an amount of bytes into String, in the largest unit that keeps the number above 1.
+/// ```
+/// # use util::string::fmt_bytes;
+/// assert!(fmt_bytes(512) == "512 B");
+/// assert!(fmt_bytes(1536) == "1.50 KiB");
+/// assert!(fmt_bytes(3 * 1024 * 1024 * 1024) == "3.00 GiB");
+/// ```
+pub fn fmt_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.2} {}", size, UNITS[unit])
+}
+
 /// Parse a string into an integer.
 ///
 /// The number can contain `,` separators, ex: "12,000".
@@ -192,6 +398,154 @@ pub fn parse_num(s: &str) -> Result<i64> {
     Ok(num * multiplier)
 }
 
+/// Format a list of values into a sparkline, where each value is represented by a bar character
+/// scaled between the smallest and largest value.
+/// ```
+/// # use util::string::sparkline;
+/// assert!(sparkline(&[0.0, 1.0, 2.0, 7.0]) == "▁▂▃█");
+/// assert!(sparkline(&[3.0, 3.0]) == "▄▄");
+/// assert!(sparkline(&[]).is_empty());
+/// ```
+pub fn sparkline(values: &[f64]) -> String {
+    const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+    let min = values.iter().copied().fold(f64::INFINITY, f64::min);
+    let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    values
+        .iter()
+        .map(|v| {
+            if max - min <= f64::EPSILON {
+                BARS[3]
+            } else {
+                BARS[((v - min) / (max - min) * 7.0).round() as usize]
+            }
+        })
+        .collect()
+}
+
+/// Format a ratio into a progress bar of `width` characters, the ratio is clamped between 0 and 1.
+/// ```
+/// # use util::string::progress_bar;
+/// assert!(progress_bar(0.5, 4) == "██░░");
+/// assert!(progress_bar(0.0, 3) == "░░░");
+/// assert!(progress_bar(1.7, 3) == "███");
+/// ```
+pub fn progress_bar(ratio: f64, width: usize) -> String {
+    let filled = (ratio.clamp(0.0, 1.0) * width as f64).round() as usize;
+    "█".repeat(filled) + &"░".repeat(width - filled)
+}
+
+/// Max amount of characters in a discord message
+pub const MESSAGE_LEN: usize = 2000;
+
+/// Split content into as few messages as possible, each at most `max_len` characters.
+///
+/// Content is split between lines, lines that are too long are split after a whitespace when
+/// possible. A code block that is split is closed at the end of a message and reopened at the
+/// start of the next one, so its formatting is kept.
+/// ```
+/// # use util::string::split_message;
+/// assert!(split_message("ab\ncd\nef", 5) == vec!["ab\ncd", "ef"]);
+/// assert!(split_message("one two three", 8) == vec!["one two ", "three"]);
+/// assert!(split_message("ééééé", 2) == vec!["éé", "éé", "é"]);
+/// assert!(split_message("```sql\na\nb\n```", 12) == vec!["```sql\na\n```", "```sql\nb\n```"]);
+/// assert!(split_message("", 5).is_empty());
+/// ```
+pub fn split_message(content: &str, max_len: usize) -> Vec<String> {
+    const FENCE: &str = "```";
+
+    let mut messages = Vec::new();
+    let mut current = String::new();
+    let mut current_len = 0;
+    // Opening line of the code block the current line is in, ex: "```sql"
+    let mut code_block: Option<String> = None;
+    for line in content.split('\n') {
+        let is_fence = line.trim_start().starts_with(FENCE);
+        let inside = code_block.is_some();
+        // Room for closing the code block at the end of the message
+        let reserve = if inside != is_fence { FENCE.len() + 1 } else { 0 };
+        let reopen_len = code_block.as_ref().map_or(0, |fence| fence.chars().count() + 1);
+        let piece_len = max_len.saturating_sub(reserve + reopen_len).max(1);
+
+        for piece in split_line(line, piece_len) {
+            let len = piece.chars().count();
+            let sep = usize::from(!current.is_empty());
+            if !current.is_empty() && current_len + sep + len + reserve > max_len {
+                if let Some(fence) = &code_block {
+                    current.push('\n');
+                    current.push_str(FENCE);
+                    messages.push(std::mem::replace(&mut current, fence.clone()));
+                    current_len = fence.chars().count();
+                } else {
+                    messages.push(std::mem::take(&mut current));
+                    current_len = 0;
+                }
+            }
+            if !current.is_empty() {
+                current.push('\n');
+                current_len += 1;
+            }
+            current.push_str(piece);
+            current_len += len;
+        }
+
+        if is_fence {
+            code_block = if inside { None } else { Some(line.trim().to_string()) };
+        }
+    }
+    if !current.is_empty() {
+        messages.push(current);
+    }
+    messages
+}
+
+/// Split a line into pieces of at most `max_len` characters, preferring to split after a
+/// whitespace
+fn split_line(line: &str, max_len: usize) -> Vec<&str> {
+    let mut pieces = Vec::new();
+    let mut rest = line;
+    while let Some((end, _)) = rest.char_indices().nth(max_len) {
+        let split = match rest[..end].rfind(char::is_whitespace) {
+            Some(i) => i + rest[i..].chars().next().map_or(1, char::len_utf8),
+            None => end,
+        };
+        pieces.push(&rest[..split]);
+        rest = &rest[split..];
+    }
+    pieces.push(rest);
+    pieces
+}
+
+/// Checks if a string matches a pattern, where `*` in the pattern matches any characters.
+/// ```
+/// # use util::string::glob_match;
+/// assert!(glob_match("HB*", "HB12"));
+/// assert!(glob_match("*lobby*", "lobby3"));
+/// assert!(glob_match("WC1", "WC1"));
+/// assert!(!glob_match("WC1", "WC10"));
+/// assert!(!glob_match("HB*", "WC1"));
+/// ```
+pub fn glob_match(pattern: &str, s: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let mut rest = match s.strip_prefix(parts.next().unwrap_or("")) {
+        Some(rest) => rest,
+        None => return false,
+    };
+    let parts: Vec<&str> = parts.collect();
+    let (last, middle) = match parts.split_last() {
+        Some(split) => split,
+        // There is no `*` in the pattern
+        None => return rest.is_empty(),
+    };
+    for part in middle {
+        match rest.find(part) {
+            Some(i) => rest = &rest[i + part.len()..],
+            None => return false,
+        }
+    }
+    rest.ends_with(last)
+}
+
 /// Deserialize content of file into `Option<...>`.
 ///
 /// Takes the path to the json file, and an optional default value.
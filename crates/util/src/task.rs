@@ -0,0 +1,27 @@
+//! Spawning of long running tasks.
+//!
+//! Crates start their loops through a [`Spawner`] instead of spawning them directly, so the bot
+//! decides how they are supervised (ex: restarted when they panic).
+use std::future::Future;
+
+/// What to do when a task stops
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RestartPolicy {
+    /// Restart the task whenever it stops, this is for tasks that should run forever
+    Always,
+    /// Only restart the task if it panicked
+    OnPanic,
+    /// Never restart the task
+    Never,
+}
+
+/// Spawner of named long running tasks
+pub trait Spawner {
+    /// Spawn a task named `name`.
+    ///
+    /// `factory` creates the task's future, it is called again every time the task is restarted.
+    fn spawn<F, Fut>(&self, name: &str, policy: RestartPolicy, factory: F)
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static;
+}
@@ -0,0 +1,145 @@
+//! Abstraction over the APIs used by the wynn loops.
+//!
+//! [`WynnApi`] is implemented by [`HttpApi`], which requests the actual Wynncraft and Mojang API,
+//! and by [`MockApi`], which replays responses given to it beforehand.
+//! Other implementations can be used to add caching or proxying in front of the APIs.
+use std::collections::{HashMap, VecDeque};
+
+use anyhow::{bail, Context, Result};
+use reqwest::Client;
+use serenity::async_trait;
+use tokio::sync::Mutex;
+
+use util::some;
+
+use crate::diagnostics;
+use crate::model::{Guild, GuildLeaderboard, ServerList};
+
+/// Trait for requesting the Wynncraft API.
+#[async_trait]
+pub trait WynnApi: Send + Sync + 'static {
+    /// Get a guild's statistic.
+    async fn get_guild(&self, name: &str) -> Result<Guild>;
+    /// Get the list of online players in each server.
+    async fn get_online_players(&self) -> Result<ServerList>;
+    /// Get the global guild leaderboard.
+    async fn get_guild_leaderboard(&self) -> Result<GuildLeaderboard>;
+    /// Get a player's current ign from their mcid.
+    async fn get_player(&self, mcid: &str) -> Result<String>;
+}
+
+/// [`WynnApi`] that sends actual requests to the APIs.
+#[derive(Debug, Clone)]
+pub struct HttpApi(pub Client);
+
+#[async_trait]
+impl WynnApi for HttpApi {
+    async fn get_guild(&self, name: &str) -> Result<Guild> {
+        crate::get_guild(&self.0, name).await
+    }
+
+    async fn get_online_players(&self) -> Result<ServerList> {
+        let url = "https://api.wynncraft.com/public_api.php?action=onlinePlayers";
+
+        let resp = self.0.get(url).send().await.context("failed to request wynncraft api for server list")?;
+        diagnostics::parse_response(resp, "wynncraft server list").await
+    }
+
+    async fn get_guild_leaderboard(&self) -> Result<GuildLeaderboard> {
+        let url = "https://api.wynncraft.com/public_api.php?action=statsLeaderboard&type=guild&timeframe=alltime";
+
+        let resp =
+            self.0.get(url).send().await.context("failed to request wynncraft api for guild leaderboard")?;
+        diagnostics::parse_response(resp, "wynncraft guild leaderboard").await
+    }
+
+    async fn get_player(&self, mcid: &str) -> Result<String> {
+        crate::get_ign(&self.0, mcid).await
+    }
+}
+
+#[derive(Debug, Default)]
+/// [`WynnApi`] that replays recorded responses, useful for testing.
+///
+/// Responses are returned in the same order they are recorded, and the last response is repeated
+/// once the rest are used up.
+/// Requesting without any recorded response results in an error.
+/// ```
+/// use wynn::api::{MockApi, WynnApi};
+///
+/// # #[tokio::main]
+/// # async fn main() {
+/// let api = MockApi::new();
+/// api.record_player("0a1b", "Pucaet").await;
+///
+/// assert!(api.get_player("0a1b").await.unwrap() == "Pucaet");
+/// assert!(api.get_online_players().await.is_err());
+/// # }
+/// ```
+pub struct MockApi {
+    guilds: Mutex<HashMap<String, VecDeque<Guild>>>,
+    online_players: Mutex<VecDeque<ServerList>>,
+    leaderboards: Mutex<VecDeque<GuildLeaderboard>>,
+    players: Mutex<HashMap<String, String>>,
+}
+
+impl MockApi {
+    /// Create a mock without any recorded responses
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a guild stats response
+    pub async fn record_guild(&self, guild: Guild) {
+        let mut guilds = self.guilds.lock().await;
+        guilds.entry(guild.name.clone()).or_default().push_back(guild);
+    }
+
+    /// Record a server list response
+    pub async fn record_online_players(&self, list: ServerList) {
+        self.online_players.lock().await.push_back(list);
+    }
+
+    /// Record a guild leaderboard response
+    pub async fn record_guild_leaderboard(&self, leaderboard: GuildLeaderboard) {
+        self.leaderboards.lock().await.push_back(leaderboard);
+    }
+
+    /// Record the ign of a player
+    pub async fn record_player(&self, mcid: &str, ign: &str) {
+        self.players.lock().await.insert(mcid.to_string(), ign.to_string());
+    }
+}
+
+/// Get the next recorded response, the last one is kept so it can be repeated.
+fn replay<T: Clone>(responses: &mut VecDeque<T>) -> Option<T> {
+    if responses.len() > 1 {
+        responses.pop_front()
+    } else {
+        responses.front().cloned()
+    }
+}
+
+#[async_trait]
+impl WynnApi for MockApi {
+    async fn get_guild(&self, name: &str) -> Result<Guild> {
+        let mut guilds = self.guilds.lock().await;
+        let responses = some!(guilds.get_mut(name), bail!("No recorded guild stats for {}", name));
+        replay(responses).context("No recorded guild stats")
+    }
+
+    async fn get_online_players(&self) -> Result<ServerList> {
+        let mut lists = self.online_players.lock().await;
+        replay(&mut lists).context("No recorded server list")
+    }
+
+    async fn get_guild_leaderboard(&self) -> Result<GuildLeaderboard> {
+        let mut leaderboards = self.leaderboards.lock().await;
+        replay(&mut leaderboards).context("No recorded guild leaderboard")
+    }
+
+    async fn get_player(&self, mcid: &str) -> Result<String> {
+        let players = self.players.lock().await;
+        players.get(mcid).cloned().context("No recorded player")
+    }
+}
@@ -42,7 +42,7 @@ use anyhow::Result;
 use serenity::prelude::TypeMapKey;
 use tokio::sync::RwLock;
 
-use crate::model::{Guild, GuildMember};
+use crate::model::{Guild, GuildMember, LeaderboardGuild};
 use util::{read_json, write_json};
 
 /// Container for the event loops' persistent data
@@ -61,6 +61,8 @@ pub struct Cache {
     /// Note that this map only contains the names of players who has a linked wynn profile in the
     /// member database.
     pub online: RwLock<OnlineMap>,
+    /// The guild's entry on the global guild leaderboard, `None` if it isn't on the leaderboard.
+    pub leaderboard: RwLock<Option<LeaderboardGuild>>,
 }
 
 impl Cache {
@@ -70,6 +72,7 @@ impl Cache {
             guild: RwLock::new(read_json!("cache/guild.json")),
             members: RwLock::new(read_json!("cache/members.json")),
             online: RwLock::new(OnlineMap(HashMap::new())),
+            leaderboard: RwLock::new(None),
         })
     }
 
@@ -0,0 +1,85 @@
+//! Diagnostics of API responses that fail to parse.
+//!
+//! When an API changes its response format, [`parse`] logs where in the response the parsing
+//! failed (ex: missing field `contributed` at `members[3]`) instead of just that it failed, and
+//! counts the failure.
+//! If a payload directory is set via [`set_payload_dir`], the offending responses are also stored
+//! in it for inspection.
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{anyhow, bail, Context, Result};
+use reqwest::Response;
+use serde::de::DeserializeOwned;
+use tracing::{info, warn};
+
+/// Amount of API responses that failed to parse
+static PARSE_FAILURES: AtomicU64 = AtomicU64::new(0);
+/// Directory responses that failed to parse are stored in
+static PAYLOAD_DIR: Mutex<Option<PathBuf>> = Mutex::new(None);
+
+/// Set the directory responses that failed to parse are stored in, `None` to not store them
+pub fn set_payload_dir(dir: Option<PathBuf>) {
+    *PAYLOAD_DIR.lock().expect("Payload dir is poisoned") = dir;
+}
+
+/// Get the amount of API responses that failed to parse
+pub fn parse_failures() -> u64 {
+    PARSE_FAILURES.load(Ordering::Relaxed)
+}
+
+/// Read the body of an API response and parse it via [`parse`].
+///
+/// Responses with an unsuccessful status fail without being parsed, so error pages aren't counted
+/// as parse failures.
+pub async fn parse_response<T: DeserializeOwned>(resp: Response, what: &str) -> Result<T> {
+    let status = resp.status();
+    if !status.is_success() {
+        bail!("{} request failed with status {}", what, status);
+    }
+    let body = resp.text().await.with_context(|| format!("failed to read {} response", what))?;
+    parse(&body, what)
+}
+
+/// Parse the json body of an API response, `what` describes the response (ex: "guild stats").
+///
+/// On failure, the returned error contains the json path the parsing failed at.
+/// ```
+/// use wynn::diagnostics::{parse, parse_failures};
+///
+/// assert_eq!(parse::<Vec<u8>>("[1, 2]", "numbers").unwrap(), vec![1, 2]);
+/// let why = parse::<Vec<u8>>("[1, -2]", "numbers").unwrap_err();
+/// assert!(why.to_string().starts_with("failed to parse numbers response from json at [1]"));
+/// assert_eq!(parse_failures(), 1);
+/// ```
+pub fn parse<T: DeserializeOwned>(body: &str, what: &str) -> Result<T> {
+    let why = match serde_path_to_error::deserialize(&mut serde_json::Deserializer::from_str(body)) {
+        Ok(value) => return Ok(value),
+        Err(why) => why,
+    };
+    PARSE_FAILURES.fetch_add(1, Ordering::Relaxed);
+    let path = why.path().to_string();
+    let why = why.into_inner();
+    warn!(what, path, "Failed to parse api response: {}", why);
+    store_payload(body, what);
+    Err(anyhow!("failed to parse {} response from json at {}: {}", what, path, why))
+}
+
+/// Store a response into the payload directory, if there is one
+fn store_payload(body: &str, what: &str) {
+    let dir = match PAYLOAD_DIR.lock().expect("Payload dir is poisoned").clone() {
+        Some(dir) => dir,
+        None => return,
+    };
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or_default();
+    let file = dir.join(format!("{}-{}.json", what.replace(' ', "_"), now));
+
+    let result = fs::create_dir_all(&dir).and_then(|_| fs::write(&file, body));
+    match result {
+        Ok(_) => info!(file = %file.display(), "Stored api response that failed to parse"),
+        Err(why) => warn!(file = %file.display(), "Failed to store api response: {:#}", why),
+    }
+}
@@ -8,9 +8,13 @@
 //!
 //! [`start_loops`]: crate::loops::start_loops
 use event::signal;
+use serde::Serialize;
+
+use crate::model::GuildBanner;
 
 /// Wynncraft/Mojang events
-#[derive(Debug, Clone)]
+#[derive(Serialize, Debug, Clone)]
+#[serde(tag = "type")]
 pub enum WynnEvent {
     /// New guild member joined
     MemberJoin {
@@ -18,6 +22,9 @@ pub enum WynnEvent {
         rank: String,
         ign: String,
         xp: i64,
+        /// The datetime of when they joined the guild, as appeared in the api
+        joined: String,
+        wars: Option<i64>,
     },
     /// Guild member left the guild
     MemberLeave { id: String, rank: String, ign: String },
@@ -35,6 +42,13 @@ pub enum WynnEvent {
         old_contrib: i64,
         new_contrib: i64,
     },
+    /// Guild member participated in wars
+    MemberWar {
+        id: String,
+        ign: String,
+        old_wars: i64,
+        new_wars: i64,
+    },
     /// Guild member's ign changed
     ///
     /// Note that this event won't emit for players that aren't in the in-game guild.
@@ -47,6 +61,10 @@ pub enum WynnEvent {
     ///
     /// This event only includes the guild's new level.
     GuildLevelUp { level: u8 },
+    /// Guild's tag (prefix) changed
+    GuildTagChange { old_tag: String, new_tag: String },
+    /// Guild's banner changed
+    GuildBannerChange { old_banner: GuildBanner, new_banner: GuildBanner },
     /// Player joins the server
     ///
     /// Note that this is only emitted for when a player logs on.
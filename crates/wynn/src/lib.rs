@@ -1,19 +1,23 @@
 //! Functions for communication with Mojang/Wynncraft API
+pub mod api;
 #[warn(missing_docs, missing_debug_implementations)]
 pub mod cache;
+pub mod diagnostics;
 pub mod error;
 pub mod events;
 pub mod loops;
 pub mod model;
 pub mod utils;
 
+use std::collections::HashMap;
+
 use anyhow::{bail, Context, Result};
 use reqwest::Client;
 
 use util::some;
 
 use crate::error::IdDashingError;
-use crate::model::{MojangIdResponse, MojangIgnResponse};
+use crate::model::{Guild, MojangIdResponse, MojangIgnResponse};
 
 /// Get an ign's corresponding mcid via Mojang API.
 ///
@@ -34,15 +38,43 @@ pub async fn get_id(client: &Client, ign: &str) -> Result<String> {
         .await
         .context("failed to request mojang api for ign id")?;
 
-    let resp = resp
-        .json::<MojangIdResponse>()
-        .await
-        .context("failed to parse mojang ign id response from json")?;
+    let resp = diagnostics::parse_response::<MojangIdResponse>(resp, "mojang ign id").await?;
 
     let id = crate::utils::id_dashed(&resp.id).ok_or(IdDashingError)?;
     Ok(id)
 }
 
+/// Get the corresponding mcids of multiple igns via Mojang API.
+///
+/// Returns a map from lowercased ign to mcid, igns that don't exist are excluded from the map.
+///
+/// # Errors
+/// Returns [`reqwest::Error`] if something went wrong while sending request or failing to parse
+/// the API response.
+/// Returns [`IdDashingError`] if unable to convert the received id into its dashed form.
+pub async fn get_ids(client: &Client, igns: &[String]) -> Result<HashMap<String, String>> {
+    let url = "https://api.mojang.com/profiles/minecraft";
+    let mut ids = HashMap::with_capacity(igns.len());
+
+    // The API only accepts 10 igns per request
+    for chunk in igns.chunks(10) {
+        let resp = client
+            .post(url)
+            .json(chunk)
+            .send()
+            .await
+            .context("failed to request mojang api for bulk ign ids")?;
+
+        let resp = diagnostics::parse_response::<Vec<MojangIdResponse>>(resp, "mojang bulk ign ids").await?;
+
+        for player in resp {
+            let id = crate::utils::id_dashed(&player.id).ok_or(IdDashingError)?;
+            ids.insert(player.name.to_lowercase(), id);
+        }
+    }
+    Ok(ids)
+}
+
 /// Get a player's ign via Mojang api.
 ///
 /// # Errors
@@ -56,11 +88,24 @@ pub async fn get_ign(client: &Client, mcid: &str) -> Result<String> {
         .await
         .context("failed to request mojang api for ign")?;
 
-    let mut resp = resp
-        .json::<MojangIgnResponse>()
-        .await
-        .context("failed to parse mojang ign response from json")?;
+    let mut resp = diagnostics::parse_response::<MojangIgnResponse>(resp, "mojang ign").await?;
 
     let name = some!(resp.pop(), bail!("name history is empty"));
     Ok(name.name)
 }
+
+/// Get a guild's statistic via Wynncraft api.
+///
+/// # Errors
+/// Returns [`reqwest::Error`] if something went wrong while sending request or failing to parse
+/// the API response.
+pub async fn get_guild(client: &Client, name: &str) -> Result<Guild> {
+    let mut url = "https://api.wynncraft.com/public_api.php?action=guildStats&command=".to_string();
+    url.push_str(name);
+
+    let resp = crate::utils::request(client, 2, &url, "wynncraft api for guild stats")
+        .await
+        .context("failed to request wynncraft api for guild stats")?;
+
+    diagnostics::parse_response(resp, "wynncraft guild stats").await
+}
@@ -4,13 +4,15 @@ use std::thread;
 use std::time::Duration as StdDuration;
 
 use anyhow::Result;
-use reqwest::Client;
 use serenity::async_trait;
 use tokio::time::{self, Duration};
 use tracing::{error, info};
 
 use util::ok;
+use util::string::glob_match;
+use util::task::{RestartPolicy, Spawner};
 
+use crate::api::WynnApi;
 use crate::cache::Cache;
 use crate::events::{WynnEvent, WynnSignal};
 use crate::model::{Guild, GuildMember, ServerList};
@@ -18,23 +20,52 @@ use crate::model::{Guild, GuildMember, ServerList};
 /// Start loops for fetching and analyzing of wynncraft api and broadcasting [`WynnEvent`]
 ///
 /// This function need to be called for [`Cache`] and [`WynnEvent`] to work.
+/// The loops are spawned with `spawner`, and all API requests are made through `api`.
+/// Players on worlds that match one of the `ignored_worlds` patterns (see [`glob_match`]) are
+/// treated as offline.
 ///
 /// [`WynnEvent`]: event::WynnEvent
 /// [`Cache`]: crate::cache::Cache
+/// [`glob_match`]: util::string::glob_match
 pub async fn start_loops(
-    signal: WynnSignal, client: Client, cache: Arc<Cache>, tracked_ign: impl TrackedIgn,
+    spawner: &impl Spawner, signal: WynnSignal, api: impl WynnApi, cache: Arc<Cache>,
+    tracked_ign: impl TrackedIgn, ignored_worlds: Vec<String>,
 ) {
+    let api = Arc::new(api);
+    let tracked_ign = Arc::new(tracked_ign);
     let shared_signal = signal.clone();
-    let shared_client = client.clone();
+    let shared_api = Arc::clone(&api);
     let shared_cache = Arc::clone(&cache);
-    tokio::spawn(async move {
-        // This is to make sure wynn events are sent after receivers are created
-        thread::sleep(StdDuration::from_secs(5));
-        main_guild_api_loop(shared_signal, &shared_client, &shared_cache).await;
+    spawner.spawn("wynn guild api", RestartPolicy::Always, move || {
+        let shared_signal = shared_signal.clone();
+        let shared_api = shared_api.clone();
+        let shared_cache = shared_cache.clone();
+        async move {
+            // This is to make sure wynn events are sent after receivers are created
+            thread::sleep(StdDuration::from_secs(5));
+            main_guild_api_loop(shared_signal, shared_api.as_ref(), &shared_cache).await;
+        }
     });
 
-    tokio::spawn(async move {
-        server_api_loop(signal, &client, tracked_ign, &cache).await;
+    let shared_api = Arc::clone(&api);
+    let shared_cache = Arc::clone(&cache);
+    spawner.spawn("wynn leaderboard api", RestartPolicy::Always, move || {
+        let shared_api = shared_api.clone();
+        let shared_cache = shared_cache.clone();
+        async move {
+            leaderboard_api_loop(shared_api.as_ref(), &shared_cache).await;
+        }
+    });
+
+    spawner.spawn("wynn server api", RestartPolicy::Always, move || {
+        let signal = signal.clone();
+        let api = api.clone();
+        let tracked_ign = tracked_ign.clone();
+        let ignored_worlds = ignored_worlds.clone();
+        let cache = cache.clone();
+        async move {
+            server_api_loop(signal, api.as_ref(), tracked_ign.as_ref(), &ignored_worlds, &cache).await;
+        }
     });
 }
 
@@ -45,31 +76,34 @@ pub trait TrackedIgn: Send + Sync + 'static {
     async fn tracked_ign(&self) -> Result<HashSet<String>>;
 }
 
+/// A fixed set of tracked igns, useful when there is no database to get them from (ex: testing).
+#[async_trait]
+impl TrackedIgn for HashSet<String> {
+    async fn tracked_ign(&self) -> Result<HashSet<String>> {
+        Ok(self.clone())
+    }
+}
+
 /// Starts a loop to analyze main guild statistics and broadcast [`WynnEvent`]
 ///
 /// [`WynnEvent`]: event::WynnEvent
-async fn main_guild_api_loop(signal: WynnSignal, client: &Client, cache: &Cache) {
+async fn main_guild_api_loop(signal: WynnSignal, api: &impl WynnApi, cache: &Cache) {
     let mut interval = time::interval(Duration::from_millis(10000));
     let mut prev_timestamp = 0;
 
-    let mut url = "https://api.wynncraft.com/public_api.php?action=guildStats&command=".to_string();
-    url.push_str(&std::env::var("GUILD_NAME").expect("Expected guild name in environment"));
+    let guild_name = std::env::var("GUILD_NAME").expect("Expected guild name in environment");
 
     info!("Starting main guild loop");
     loop {
         interval.tick().await;
 
-        let resp = client.get(&url).send().await;
-        if let Err(why) = &resp {
-            crate::utils::request_error_log(why, "main guild stats");
-            continue;
-        }
-        let resp = resp.unwrap().json::<Guild>().await;
-        if let Err(why) = resp {
-            error!("Failed to parse responce to json when requesting main guild stats: {}", why);
-            continue;
-        }
-        let mut resp = resp.unwrap();
+        let mut resp = match api.get_guild(&guild_name).await {
+            Ok(resp) => resp,
+            Err(why) => {
+                error!("Failed to get main guild stats: {:#}", why);
+                continue;
+            }
+        };
 
         // Checks if the response is outdated
         if resp.request.timestamp > prev_timestamp {
@@ -86,11 +120,12 @@ async fn main_guild_api_loop(signal: WynnSignal, client: &Client, cache: &Cache)
             let cache_resp = cache.guild.read().await;
             match cache_resp.as_ref() {
                 Some(cache_resp) => {
-                    // Checking for guild global events
-                    if cache_resp.level < resp.level {
-                        info!(level = resp.level, "Guild level up");
-                        events.push(WynnEvent::GuildLevelUp { level: resp.level });
+                    // Not all responses includes the banner, so the cached one is kept
+                    if resp.banner.is_none() {
+                        resp.banner = cache_resp.banner.clone();
                     }
+                    // Checking for guild global events
+                    events.append(&mut get_guild_events(cache_resp, &resp));
                 }
                 None => {
                     // This is needed so database can be populated during the bot's initial run
@@ -101,6 +136,8 @@ async fn main_guild_api_loop(signal: WynnSignal, client: &Client, cache: &Cache)
                             rank: member.rank.clone(),
                             ign: member.name.clone(),
                             xp: member.contributed,
+                            joined: member.joined.clone(),
+                            wars: member.wars,
                         })
                     }
                 }
@@ -118,6 +155,8 @@ async fn main_guild_api_loop(signal: WynnSignal, client: &Client, cache: &Cache)
                             rank: member.rank.clone(),
                             ign: member.name.clone(),
                             xp: member.contributed,
+                            joined: member.joined.clone(),
+                            wars: member.wars,
                         });
                     }
                 }
@@ -154,6 +193,35 @@ async fn main_guild_api_loop(signal: WynnSignal, client: &Client, cache: &Cache)
     }
 }
 
+/// Analyzes old & new guild statistics for [`WynnEvent`]
+///
+/// [`WynnEvent`]: event::WynnEvent
+fn get_guild_events(old: &Guild, new: &Guild) -> Vec<WynnEvent> {
+    let mut events = Vec::new();
+
+    if old.level < new.level {
+        info!(level = new.level, "Guild level up");
+        events.push(WynnEvent::GuildLevelUp { level: new.level });
+    }
+
+    if old.prefix != new.prefix {
+        info!(%old.prefix, %new.prefix, "Guild tag change");
+        events.push(WynnEvent::GuildTagChange { old_tag: old.prefix.clone(), new_tag: new.prefix.clone() });
+    }
+
+    if let (Some(old_banner), Some(new_banner)) = (&old.banner, &new.banner) {
+        if old_banner != new_banner {
+            info!("Guild banner change");
+            events.push(WynnEvent::GuildBannerChange {
+                old_banner: old_banner.clone(),
+                new_banner: new_banner.clone(),
+            });
+        }
+    }
+
+    events
+}
+
 /// Analyzes old & new guild member statistics for [`WynnEvent`]
 ///
 /// [`WynnEvent`]: event::WynnEvent
@@ -181,6 +249,19 @@ fn get_member_events(old: &GuildMember, new: &GuildMember) -> Vec<WynnEvent> {
         });
     }
 
+    // checks for war count change
+    if let (Some(old_wars), Some(new_wars)) = (old.wars, new.wars) {
+        if old_wars < new_wars {
+            info!(name=%new.name, old_wars, new_wars, "Guild member war");
+            events.push(WynnEvent::MemberWar {
+                id: old.uuid.clone(),
+                ign: new.name.clone(),
+                old_wars,
+                new_wars,
+            });
+        }
+    }
+
     // checks for contribution change
     if old.contributed < new.contributed {
         info!(name=%new.name, old.contributed, new.contributed, "Guild member contribution");
@@ -196,31 +277,50 @@ fn get_member_events(old: &GuildMember, new: &GuildMember) -> Vec<WynnEvent> {
     events
 }
 
+/// Starts a loop to update the guild's entry on the global guild leaderboard in [`Cache`]
+async fn leaderboard_api_loop(api: &impl WynnApi, cache: &Cache) {
+    let mut interval = time::interval(Duration::from_secs(3600));
+
+    let guild_name = std::env::var("GUILD_NAME").expect("Expected guild name in environment");
+
+    info!("Starting guild leaderboard loop");
+    loop {
+        interval.tick().await;
+
+        let resp = match api.get_guild_leaderboard().await {
+            Ok(resp) => resp,
+            Err(why) => {
+                error!("Failed to get guild leaderboard: {:#}", why);
+                continue;
+            }
+        };
+        let entry = resp.data.into_iter().find(|guild| guild.name == guild_name);
+        *cache.leaderboard.write().await = entry;
+    }
+}
+
 /// Starts a loop to analyze server online players and broadcast [`WynnEvent`]
 ///
 /// [`WynnEvent`]: event::WynnEvent
-async fn server_api_loop(signal: WynnSignal, client: &Client, tracked_ign: impl TrackedIgn, cache: &Cache) {
+async fn server_api_loop(
+    signal: WynnSignal, api: &impl WynnApi, tracked_ign: &impl TrackedIgn, ignored_worlds: &[String],
+    cache: &Cache,
+) {
     let mut interval = time::interval(Duration::from_secs(60));
     let mut prev_timestamp: u64 = 0;
     let mut first_loop = true;
 
-    let url = "https://api.wynncraft.com/public_api.php?action=onlinePlayers";
-
     info!("Starting server list loop");
     loop {
         interval.tick().await;
 
-        let resp = client.get(url).send().await;
-        if let Err(why) = &resp {
-            crate::utils::request_error_log(why, "server list");
-            continue;
-        }
-        let resp = resp.unwrap().json::<ServerList>().await;
-        if let Err(why) = resp {
-            error!("Failed to parse responce to json when requesting server list: {}", why);
-            continue;
-        }
-        let mut resp = resp.unwrap();
+        let mut resp = match api.get_online_players().await {
+            Ok(resp) => resp,
+            Err(why) => {
+                error!("Failed to get server list: {:#}", why);
+                continue;
+            }
+        };
 
         let mut found_meta = false;
         let mut elapsed = 0;
@@ -258,7 +358,7 @@ async fn server_api_loop(signal: WynnSignal, client: &Client, tracked_ign: impl
         if first_loop {
             // initialize `tracked_ign`
             let mut tracked_ign = cache.online.write().await;
-            for (world, igns) in iter_ign(&resp) {
+            for (world, igns) in iter_ign(&resp, ignored_worlds) {
                 for ign in igns {
                     if all_igns.contains(ign) {
                         tracked_ign.insert(world.clone(), ign.to_string());
@@ -271,7 +371,7 @@ async fn server_api_loop(signal: WynnSignal, client: &Client, tracked_ign: impl
 
         {
             let mut tracked_ign = cache.online.write().await;
-            for (world, igns) in iter_ign(&resp) {
+            for (world, igns) in iter_ign(&resp, ignored_worlds) {
                 for ign in igns {
                     // Filter out igns that can't be tracked, aka not in database
                     if all_igns.contains(ign) {
@@ -336,11 +436,13 @@ async fn server_api_loop(signal: WynnSignal, client: &Client, tracked_ign: impl
 }
 
 /// Help function for constructing a map from server to its online players, excluding lobby
-/// servers.
-fn iter_ign<'a>(resp: &'a ServerList) -> HashMap<&'_ String, impl Iterator<Item = &'_ str>> {
+/// servers and servers that match one of the `ignored` patterns.
+fn iter_ign<'a>(
+    resp: &'a ServerList, ignored: &[String],
+) -> HashMap<&'a String, impl Iterator<Item = &'a str>> {
     let mut ign_map = HashMap::new();
     for (world, players) in resp.iter() {
-        if !world.starts_with("WC") {
+        if !world.starts_with("WC") || ignored.iter().any(|pattern| glob_match(pattern, world)) {
             continue;
         }
 
@@ -42,9 +42,52 @@ pub struct Guild {
     #[serde(rename = "createdFriendly")]
     pub created_friendly: String,
     pub territories: u16,
+    /// The guild's banner, not all api responses includes it
+    #[serde(default)]
+    pub banner: Option<GuildBanner>,
     pub request: RequestInfo,
 }
 
+/// Guild banner object from wynncraft API response
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct GuildBanner {
+    /// Color of the banner's base, ex: "WHITE"
+    pub base: String,
+    pub tier: u8,
+    /// Name of the banner's structure, ex: "Banner Tower"
+    #[serde(default)]
+    pub structure: String,
+    /// Patterns drawn on the base, from the bottom
+    #[serde(default)]
+    pub layers: Vec<BannerLayer>,
+}
+
+impl GuildBanner {
+    /// Describe the banner's colors and patterns
+    /// ```
+    /// use wynn::model::{BannerLayer, GuildBanner};
+    ///
+    /// let layer = BannerLayer { colour: "BLACK".to_string(), pattern: "CROSS".to_string() };
+    /// let banner =
+    ///     GuildBanner { base: "WHITE".to_string(), tier: 3, structure: String::new(), layers: vec![layer] };
+    /// assert_eq!(banner.describe(), "WHITE base (tier 3), BLACK CROSS");
+    /// ```
+    pub fn describe(&self) -> String {
+        let mut s = format!("{} base (tier {})", self.base, self.tier);
+        for layer in &self.layers {
+            s.push_str(&format!(", {} {}", layer.colour, layer.pattern));
+        }
+        s
+    }
+}
+
+/// Pattern layer of a [`GuildBanner`]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct BannerLayer {
+    pub colour: String,
+    pub pattern: String,
+}
+
 /// Guild member object from wynncraft API response
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct GuildMember {
@@ -55,6 +98,32 @@ pub struct GuildMember {
     pub joined: String,
     #[serde(rename = "joinedFriendly")]
     pub joined_friendly: String,
+    /// Amount of wars participated, not all api responses includes it
+    #[serde(default)]
+    pub wars: Option<i64>,
+}
+
+/// API response of
+/// "api.wynncraft.com/public_api.php?action=statsLeaderboard&type=guild&timeframe=alltime"
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GuildLeaderboard {
+    pub data: Vec<LeaderboardGuild>,
+    pub request: RequestInfo,
+}
+
+/// Guild object from the guild leaderboard
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct LeaderboardGuild {
+    pub name: String,
+    pub prefix: String,
+    pub xp: i64,
+    pub level: u8,
+    pub territories: u16,
+    #[serde(rename = "membersCount", default)]
+    pub members_count: u16,
+    /// Position on the leaderboard, starting from 1
+    #[serde(rename = "num")]
+    pub rank: u32,
 }
 
 /// API response of "api.wynncraft.com/public_api.php?action=onlinePlayers".
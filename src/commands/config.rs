@@ -1,4 +1,5 @@
 //! Configuration commands
+use std::collections::HashSet;
 use std::fmt::Write as _;
 
 use anyhow::Context as AHContext;
@@ -8,22 +9,51 @@ use serenity::framework::standard::{Args, CommandResult};
 use serenity::model::channel::{ChannelType, GuildChannel, Message};
 use tokio::sync::RwLock;
 
-use config::tag::{Tag, CHANNEL_TAGS, TEXT_CHANNEL_TAGS, USER_TAGS};
+use config::audit::TagOrphans;
+use config::locale::{Locale, LOCALES};
+use config::log_subscription::{LogEvent, LOG_EVENTS, LOG_EVENT_GROUPS};
+use config::rank_channel::{PermissionTemplate, RankChannel, PERMISSION_TEMPLATES};
+use config::tag::{CustomTag, Tag, TagTarget, CHANNEL_TAGS, TEXT_CHANNEL_TAGS, USER_TAGS};
 use config::utils::Tags;
 use config::Config;
+use memberdb::model::member::MemberRank;
+use msgtool::interact::ConfirmStyle;
 use msgtool::parser::DiscordObject;
 use util::discord::PublicChannel;
-use util::string;
-use util::{ok, some};
+use util::string::{self, DurationStyle, StatFormat};
+use util::{ctx, ok, some};
 
 use crate::checks::STAFF_CHECK;
-use crate::{arg, cmd_bail, data, finish, send_embed};
+use crate::util::rank_channel;
+use crate::{arg, cmd_bail, data, finish, send, send_embed, tr};
+
+/// Parse the next argument as a tag, which can be a custom tag.
+/// Finishes the command if the tag doesn't exist.
+macro_rules! parse_tag {
+    ($ctx:ident, $msg:ident, $args:ident, $config:ident) => {{
+        let name = arg!($ctx, $msg, $args, "tag");
+        let tag = {
+            let config = $config.read().await;
+            config.parse_tag(&name)
+        };
+        some!(tag, finish!($ctx, $msg, "Unknown tag '{}', use `tag` to see the available tags", name))
+    }};
+}
 
 #[command("tag")]
-#[sub_commands(describe_tag, add_tag, remove_tag, show_tags, list_tagged)]
-/// Display possible tags an object can have.
+#[sub_commands(describe_tag, add_tag, remove_tag, show_tags, list_tagged, create_tag, delete_tag)]
+/// Display possible tags an object can have, including the custom tags.
 /// For operations on the tags, use subcommands.
 async fn list_tags(ctx: &Context, msg: &Message, _: Args) -> CommandResult {
+    let config = data!(ctx, "config");
+    let custom = {
+        let config = config.read().await;
+        let mut tags: Vec<String> =
+            config.custom_tags.iter().map(|(name, def)| format!("{} ({})", name, def.target)).collect();
+        tags.sort();
+        tags
+    };
+    let custom = if custom.is_empty() { "None".to_string() } else { custom.join(", ") };
     send_embed!(ctx, msg, |e| {
         e.title("Tags")
             .description(
@@ -33,23 +63,558 @@ for operations on those tags, like adding/removing tags, see `help tag` for more
             .field("Channel", string::str_join_iter(CHANNEL_TAGS.iter()), true)
             .field("Text Channel", string::str_join_iter(TEXT_CHANNEL_TAGS.iter()), true)
             .field("User", string::str_join_iter(USER_TAGS.iter()), true)
+            .field("Custom", custom, false)
+    });
+    Ok(())
+}
+
+#[command("config")]
+#[only_in(guild)]
+#[checks(STAFF)]
+#[sub_commands(audit_config, log_subscriptions, rank_channels, set_prefix)]
+/// Display how many objects are tagged, and how many log channels there are.
+/// For maintenance of the config and managing log channels, use subcommands.
+async fn show_config(ctx: &Context, msg: &Message, _: Args) -> CommandResult {
+    let config = data!(ctx, "config");
+    let (version, channels, categories, text_channels, roles, users, log_channels) = {
+        let config = config.read().await;
+        (
+            config.version,
+            config.channel_tags.objects().count(),
+            config.category_tags.objects().count(),
+            config.text_channel_tags.objects().count(),
+            config.user_role_tags.objects().count(),
+            config.user_tags.objects().count(),
+            config.log_subscriptions.channels().count(),
+        )
+    };
+    send_embed!(ctx, msg, |e| {
+        e.title("Config")
+            .description("Use `config audit` to find tagged objects that no longer exist.")
+            .field("Tagged channels", channels, true)
+            .field("Tagged categories", categories, true)
+            .field("Tagged text channels", text_channels, true)
+            .field("Tagged roles", roles, true)
+            .field("Tagged users", users, true)
+            .field("Log channels", log_channels, true)
+            .footer(|f| f.text(format!("Config version {}", version)))
     });
     Ok(())
 }
 
+#[command("audit")]
+#[only_in(guild)]
+#[checks(STAFF)]
+/// Find tagged channels, roles and users that no longer exist in this server, and offer to remove
+/// their tags.
+///
+/// This is needed if they are deleted while the bot is offline, or if a tagged user left the
+/// server.
+async fn audit_config(ctx: &Context, msg: &Message) -> CommandResult {
+    let guild = some!(msg.guild(ctx), cmd_bail!("Failed to get message's guild"));
+    let config = data!(ctx, "config");
+
+    let orphans = ctx!(TagOrphans::find(&config, &ctx.http, &guild).await, "Failed to audit config")?;
+    if orphans.is_empty() {
+        finish!(ctx, msg, "All tagged objects still exist");
+    }
+
+    let mut content = format!("Found {} tagged objects that no longer exist:", orphans.len());
+    let kinds = [("Channels", &orphans.channels), ("Roles", &orphans.roles), ("Users", &orphans.users)];
+    for (kind, ids) in kinds {
+        if !ids.is_empty() {
+            write!(content, "\n**{}**: {}", kind, string::str_join_iter(ids.iter()))?;
+        }
+    }
+    content.push_str("\nRemove their tags?");
+
+    let answer = ctx!(
+        msgtool::interact::confirm(
+            ctx,
+            &msg.channel_id,
+            &content,
+            &ConfirmStyle::Important,
+            30,
+            msg.author.id
+        )
+        .await
+    )?;
+    match answer {
+        Some((true, _)) => {
+            {
+                let mut config = config.write().await;
+                orphans.remove_from(&mut config);
+            }
+            finish!(ctx, msg, "Removed the tags of {} objects", orphans.len())
+        }
+        Some((false, _)) => finish!(ctx, msg, "Cancelled"),
+        // Timeout is already responded to
+        None => Ok(()),
+    }
+}
+
+#[command("logs")]
+#[only_in(guild)]
+#[checks(STAFF)]
+#[usage("[channel] [add | remove] [events]")]
+#[example("")]
+#[example("#guild-log")]
+#[example("#guild-log add MemberJoin MemberLeave")]
+#[example("#guild-log add online")]
+#[example("#guild-log remove Xp")]
+/// Manage which log events each log channel receives.
+/// Without arguments, list the log channels and their events. With only a channel, list the
+/// events it receives.
+///
+/// > **Log events**
+/// Events are given by their names, or by groups of them:
+/// - `member`: MemberJoin, MemberLeave, RankChange, NameChange, RankExpire
+/// - `guild`: LevelUp, GuildChange
+/// - `level`: LevelUp
+/// - `xp`: Xp
+/// - `online`: PlayerJoin, PlayerLeave, PlayerMove
+/// - `all`: all of the above
+async fn log_subscriptions(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let config = data!(ctx, "config");
+    let channel = match arg!(ctx, msg, args, ?"channel") {
+        Some(channel) => channel,
+        None => {
+            let config = config.read().await;
+            let mut channels: Vec<(&u64, &HashSet<LogEvent>)> = config.log_subscriptions.iter().collect();
+            if channels.is_empty() {
+                finish!(
+                    ctx,
+                    msg,
+                    "There are no log channels, use `config logs <channel> add <events>` to add one"
+                );
+            }
+            channels.sort_by_key(|(channel_id, _)| **channel_id);
+            let mut content = String::from("> **Log channels**");
+            for (channel_id, events) in channels {
+                write!(content, "\n<#{}>: {}", channel_id, log_event_list(events))?;
+            }
+            finish!(ctx, msg, content);
+        }
+    };
+    let guild = some!(msg.guild(ctx), cmd_bail!("Failed to get message's guild"));
+    let channel_id = match DiscordObject::from_str(&ctx, &guild, &channel).await {
+        Ok(DiscordObject::Channel(PublicChannel::Guild(channel))) if channel.kind == ChannelType::Text => {
+            channel.id.0
+        }
+        Ok(_) => finish!(ctx, msg, "Log channels can only be text channels"),
+        Err(why) => finish!(ctx, msg, "Invalid channel: {}", why),
+    };
+
+    let subscribe = match arg!(ctx, msg, args, ?"action").as_deref() {
+        Some("add") => true,
+        Some("remove") => false,
+        Some(action) => finish!(ctx, msg, "Unknown action '{}', it can be `add` or `remove`", action),
+        None => {
+            let config = config.read().await;
+            match config.log_subscriptions.events(channel_id) {
+                Some(events) => finish!(ctx, msg, "<#{}> receives {}", channel_id, log_event_list(events)),
+                None => finish!(ctx, msg, "<#{}> isn't a log channel", channel_id),
+            }
+        }
+    };
+    let mut events = Vec::new();
+    for name in args.rest().split_whitespace() {
+        match LogEvent::parse_many(name) {
+            Some(parsed) => events.extend(parsed),
+            None => finish!(
+                ctx,
+                msg,
+                "Unknown log event '{}', available events: {}, or the groups {}",
+                name,
+                string::str_join_iter(LOG_EVENTS.iter()),
+                LOG_EVENT_GROUPS.join(", ")
+            ),
+        }
+    }
+    if events.is_empty() {
+        finish!(ctx, msg, "Log events not provided");
+    }
+
+    let mut changed = 0;
+    {
+        let mut config = config.write().await;
+        for event in &events {
+            let is_changed = if subscribe {
+                config.log_subscriptions.subscribe(channel_id, *event)
+            } else {
+                config.log_subscriptions.unsubscribe(channel_id, event)
+            };
+            if is_changed {
+                changed += 1;
+            }
+        }
+    }
+    if subscribe {
+        finish!(ctx, msg, "<#{}> now receives {} more log events", channel_id, changed);
+    }
+    finish!(ctx, msg, "<#{}> no longer receives {} log events", channel_id, changed);
+}
+
+/// Format log events into a list, in the order of [`LOG_EVENTS`]
+fn log_event_list(events: &HashSet<LogEvent>) -> String {
+    string::str_join_iter(LOG_EVENTS.iter().filter(|event| events.contains(event)))
+}
+
+/// Max amount of users listed as missing or extra per rank channel
+const RANK_CHANNEL_USER_LIST_LEN: usize = 10;
+
+#[command("ranks")]
+#[only_in(guild)]
+#[checks(STAFF)]
+#[usage("[sync | <channel> <min rank | remove> [template]]")]
+#[example("")]
+#[example("sync")]
+#[example("#strategy Pilot")]
+#[example("#war-vc Rocketeer Voice")]
+#[example("#strategy remove")]
+/// Manage the channels that are only visible to members of some ranks.
+/// Members of the minimum rank and above are granted access through their own permission
+/// overwrite, which is kept in sync when their rank changes, so the channel should be hidden from
+/// everyone else.
+///
+/// Without arguments, audit the rank channels, showing who is granted access and who is missing
+/// or shouldn't have it. Use `sync` to fix them.
+///
+/// > **Templates**
+/// - `Read`: view the channel and its history
+/// - `Chat`: read, send messages and react (default)
+/// - `Voice`: view, connect and speak
+async fn rank_channels(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let (db, config) = data!(ctx, "db", "config");
+    let guild = some!(msg.guild(ctx), cmd_bail!("Failed to get message's guild"));
+    let channel = match arg!(ctx, msg, args, ?"channel") {
+        Some(channel) if channel == "sync" => {
+            let changed = ctx!(rank_channel::sync_all(&ctx.cache, &ctx.http, &db, &config, &guild).await)?;
+            finish!(ctx, msg, "Rank channels are synced, {} overwrites changed", changed);
+        }
+        Some(channel) => channel,
+        None => {
+            let audits = ctx!(rank_channel::audit(&ctx.cache, &db, &config, &guild).await)?;
+            if audits.is_empty() {
+                finish!(
+                    ctx,
+                    msg,
+                    "There are no rank channels, use `config ranks <channel> <min rank>` to add one"
+                );
+            }
+            let mut content = String::from("> **Rank channels**");
+            for audit in audits {
+                write!(
+                    content,
+                    "\n<#{}>: {}+, {} users granted",
+                    audit.access.channel,
+                    audit.access.min_rank,
+                    audit.granted.len()
+                )?;
+                for (label, users) in [("Missing", &audit.missing), ("Shouldn't have access", &audit.extra)] {
+                    if users.is_empty() {
+                        continue;
+                    }
+                    let mentions: Vec<String> = users
+                        .iter()
+                        .take(RANK_CHANNEL_USER_LIST_LEN)
+                        .map(|user| format!("<@{}>", user))
+                        .collect();
+                    write!(content, "\n- {} ({}): {}", label, users.len(), mentions.join(" "))?;
+                }
+            }
+            for part in string::split_message(&content, string::MESSAGE_LEN) {
+                send!(ctx, msg, part);
+            }
+            return Ok(());
+        }
+    };
+    let channel_id = match DiscordObject::from_str(&ctx, &guild, &channel).await {
+        Ok(DiscordObject::Channel(PublicChannel::Guild(channel))) => channel.id.0,
+        Ok(_) => finish!(ctx, msg, "Rank channels can only be server channels"),
+        Err(why) => finish!(ctx, msg, "Invalid channel: {}", why),
+    };
+
+    let rank = arg!(ctx, msg, args, "min rank");
+    if rank == "remove" {
+        let removed = {
+            let mut config = config.write().await;
+            config.rank_channels.remove(&channel_id).is_some()
+        };
+        if !removed {
+            finish!(ctx, msg, "<#{}> isn't a rank channel", channel_id);
+        }
+        finish!(ctx, msg, "<#{}> is no longer a rank channel, its overwrites are kept", channel_id);
+    }
+    let min_rank = some!(rank.parse::<MemberRank>().ok(), finish!(ctx, msg, "Unknown rank '{}'", rank));
+    let template = match arg!(ctx, msg, args, ?"template") {
+        Some(template) => some!(
+            template.parse::<PermissionTemplate>().ok(),
+            finish!(
+                ctx,
+                msg,
+                "Unknown template '{}', available templates: {}",
+                template,
+                string::str_join_iter(PERMISSION_TEMPLATES.iter())
+            )
+        ),
+        None => PermissionTemplate::default(),
+    };
+
+    {
+        let mut config = config.write().await;
+        let channel = RankChannel { min_rank: min_rank.to_string(), template };
+        config.rank_channels.insert(channel_id, channel);
+    }
+    finish!(
+        ctx,
+        msg,
+        "<#{}> is now visible to {}+ with the `{}` template, use `config ranks sync` to apply it",
+        channel_id,
+        min_rank,
+        template
+    );
+}
+
+/// Max length of a guild's command prefix
+const MAX_PREFIX_LEN: usize = 10;
+
+#[command("prefix")]
+#[only_in(guild)]
+#[checks(STAFF)]
+#[usage("[prefix | reset]")]
+#[example("")]
+#[example("!")]
+#[example("reset")]
+/// Set the command prefix of this server, or `reset` it back to the bot's default prefix.
+/// Without arguments, display the current prefix.
+/// Mentioning the bot always works as a prefix, in case the prefix is forgotten.
+async fn set_prefix(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let guild_id = some!(msg.guild_id, cmd_bail!("Failed to get message's guild"));
+    let config = data!(ctx, "config");
+    let prefix = match args.single::<String>() {
+        Ok(prefix) => prefix,
+        Err(_) => {
+            let prefix = crate::command_prefix(&config, Some(guild_id.0)).await;
+            finish!(ctx, msg, "The command prefix is `{}`", prefix);
+        }
+    };
+
+    if prefix == "reset" {
+        let removed = {
+            let mut config = config.write().await;
+            config.prefixes.remove(&guild_id.0).is_some()
+        };
+        if !removed {
+            finish!(ctx, msg, "This server is already using the default prefix");
+        }
+        let prefix = crate::command_prefix(&config, Some(guild_id.0)).await;
+        finish!(ctx, msg, "Command prefix reset to `{}`", prefix);
+    }
+    if prefix.chars().count() > MAX_PREFIX_LEN {
+        finish!(ctx, msg, "Prefix can't be longer than {} characters", MAX_PREFIX_LEN);
+    }
+
+    {
+        let mut config = config.write().await;
+        config.prefixes.insert(guild_id.0, prefix.clone());
+    }
+    finish!(ctx, msg, "Command prefix set to `{}`", prefix);
+}
+
+#[command("locale")]
+#[only_in(guild)]
+#[checks(STAFF)]
+#[usage("<locale>")]
+#[example("fr")]
+/// Set the language the bot responds in for this server.
+/// Currently only the member management and statistics commands are translated.
+///
+/// > **Available locales**
+/// `en` (English), `fr` (French)
+async fn set_locale(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let locale = match args.single::<Locale>() {
+        Ok(locale) => locale,
+        Err(_) => finish!(ctx, msg, "Locale not provided or invalid, available locales: {}", string::str_join_iter(LOCALES.iter())),
+    };
+    let guild_id = some!(msg.guild_id, cmd_bail!("Failed to get message's guild"));
+
+    let config = data!(ctx, "config");
+    {
+        let mut config = config.write().await;
+        config.locales.insert(guild_id.0, locale);
+    }
+
+    finish!(ctx, msg, tr!(locale, LocaleSet));
+}
+
+/// Max amount of decimals of durations displayed in hours
+const MAX_HOUR_DECIMALS: usize = 3;
+
+/// Parse the remaining arguments as changes to a stat format.
+/// Returns `None` to reset the format, or the argument that failed to parse.
+fn parse_stat_format(mut format: StatFormat, args: &mut Args) -> Result<Option<StatFormat>, String> {
+    for arg in args.iter::<String>().flatten() {
+        match arg.to_lowercase().as_str() {
+            "reset" => return Ok(None),
+            "short" => format.shorthand = true,
+            "full" => format.shorthand = false,
+            s => match (s.parse::<DurationStyle>(), s.parse::<usize>()) {
+                (Ok(style), _) => format.duration = style,
+                (_, Ok(decimals)) if decimals <= MAX_HOUR_DECIMALS => format.hour_decimals = decimals,
+                _ => return Err(arg),
+            },
+        }
+    }
+    Ok(Some(format))
+}
+
+#[command("format")]
+#[sub_commands(server_stat_format)]
+#[usage("[units | hours] [decimals] [short | full] | reset")]
+#[example("")]
+#[example("hours 1 full")]
+#[example("reset")]
+/// Set how stats are displayed to you, overriding the format of the server.
+/// Without arguments, display the format currently used for you.
+///
+/// > **Durations**
+/// `units`: broken down into units of time, ex: "12h 30m"
+/// `hours`: in hours, ex: "12.5h", followed by the amount of decimals (0 to 3)
+/// > **Numbers**
+/// `short`: large numbers are shortened when space is limited, ex: "12.34M"
+/// `full`: always in full, ex: "12,345,000"
+///
+/// Use `reset` to follow the format of the server again.
+async fn stat_format(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let config = data!(ctx, "config");
+    let guild_id = msg.guild_id.map(|id| id.0);
+    let current = {
+        let config = config.read().await;
+        config.stat_format(guild_id, msg.author.id.0)
+    };
+    if args.is_empty() {
+        finish!(ctx, msg, "Stats are displayed to you with {}", current);
+    }
+
+    let format = match parse_stat_format(current, &mut args) {
+        Ok(format) => format,
+        Err(arg) => finish!(ctx, msg, "Invalid format `{}`, see `help format`", arg),
+    };
+    let current = {
+        let mut config = config.write().await;
+        match format {
+            Some(format) => config.user_stat_formats.insert(msg.author.id.0, format),
+            None => config.user_stat_formats.remove(&msg.author.id.0),
+        };
+        config.stat_format(guild_id, msg.author.id.0)
+    };
+    finish!(ctx, msg, "Stats are now displayed to you with {}", current);
+}
+
+#[command("server")]
+#[only_in(guild)]
+#[checks(STAFF)]
+#[usage("[units | hours] [decimals] [short | full] | reset")]
+#[example("hours 1")]
+/// Set how stats are displayed in this server, for users that didn't set their own format.
+/// Without arguments, display the format of this server.
+///
+/// See `help format` for the available formats.
+async fn server_stat_format(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let guild_id = some!(msg.guild_id, cmd_bail!("Failed to get message's guild"));
+    let config = data!(ctx, "config");
+    let current = {
+        let config = config.read().await;
+        config.stat_formats.get(&guild_id.0).copied().unwrap_or_default()
+    };
+    if args.is_empty() {
+        finish!(ctx, msg, "Stats are displayed in this server with {}", current);
+    }
+
+    let format = match parse_stat_format(current, &mut args) {
+        Ok(format) => format,
+        Err(arg) => finish!(ctx, msg, "Invalid format `{}`, see `help format`", arg),
+    };
+    {
+        let mut config = config.write().await;
+        match format {
+            Some(format) => config.stat_formats.insert(guild_id.0, format),
+            None => config.stat_formats.remove(&guild_id.0),
+        };
+    }
+    finish!(ctx, msg, "Stats are now displayed in this server with {}", format.unwrap_or_default());
+}
+
 #[command("info")]
 #[usage("<tag>")]
 #[example("NoTrack")]
 /// Describe what object the given tag can be attached to and what it means.
 async fn describe_tag(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
-    let content = match arg!(ctx, msg, args, "tag": Tags) {
+    let config = data!(ctx, "config");
+    let tag = parse_tag!(ctx, msg, args, config);
+    let config = config.read().await;
+    let content = match &tag {
         Tags::User(t) => format!("User/role tag: {}", t.describe()),
         Tags::Channel(t) => format!("Channel/category tag: {}", t.describe()),
         Tags::TextChannel(t) => format!("Text channel tag: {}", t.describe()),
+        Tags::Custom(t) => match config.custom_tags.get(&t.0) {
+            Some(def) => match def.target {
+                TagTarget::User => format!("Custom user/role tag: {}", def.description),
+                TagTarget::Channel => format!("Custom channel/category tag: {}", def.description),
+            },
+            None => cmd_bail!("Failed to get custom tag definition"),
+        },
     };
     finish!(ctx, msg, content);
 }
 
+#[command("create")]
+#[only_in(guild)]
+#[checks(STAFF)]
+#[usage("<name> <user|channel> <description>")]
+#[example("EventHost user Members who host guild events")]
+#[example("Announcement channel Channels guild announcements are posted in")]
+/// Create a custom tag, which can be attached to users and roles if the target is `user`, or
+/// channels and categories if the target is `channel`.
+/// The name can only contain letters, digits, `_` and `-`.
+///
+/// Custom tags don't do anything by themselves, they group objects so features can refer to them.
+async fn create_tag(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let (tag, target) = arg!(ctx, msg, args, "name": CustomTag, "target": TagTarget);
+    let description = args.rest().trim();
+    if description.is_empty() {
+        finish!(ctx, msg, "Description not provided");
+    }
+
+    let config = data!(ctx, "config");
+    {
+        let mut config = config.write().await;
+        if let Err(why) = config.create_custom_tag(&tag, description.to_string(), target) {
+            finish!(ctx, msg, "Failed to create tag: {}", why);
+        }
+    }
+    finish!(ctx, msg, "Created custom tag '{}'", tag);
+}
+
+#[command("delete")]
+#[only_in(guild)]
+#[checks(STAFF)]
+#[usage("<name>")]
+#[example("EventHost")]
+/// Delete a custom tag, which also removes it from all objects.
+async fn delete_tag(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let tag = arg!(ctx, msg, args, "name": CustomTag);
+    let config = data!(ctx, "config");
+    {
+        let mut config = config.write().await;
+        if let Err(why) = config.delete_custom_tag(&tag) {
+            finish!(ctx, msg, "Failed to delete tag: {}", why);
+        }
+    }
+    finish!(ctx, msg, "Deleted custom tag '{}'", tag);
+}
+
 #[command("add")]
 #[only_in(guild)]
 #[checks(STAFF)]
@@ -69,14 +634,14 @@ async fn describe_tag(ctx: &Context, msg: &Message, mut args: Args) -> CommandRe
 async fn add_tag(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
     let guild = some!(msg.guild(&ctx), cmd_bail!("Failed to get message's guild"));
 
-    let tag = arg!(ctx, msg, args, "tag": Tags);
+    let config = data!(ctx, "config");
+    let tag = parse_tag!(ctx, msg, args, config);
     let target_arg = args.rest();
 
     let target = match DiscordObject::from_str(&ctx, &guild, target_arg).await {
         Ok(v) => v,
         Err(why) => finish!(ctx, msg, format!("Invalid target: {}", why)),
     };
-    let config = data!(ctx, "config");
 
     match target {
         DiscordObject::Member(member) => {
@@ -86,6 +651,10 @@ async fn add_tag(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult
                     let mut config = config.write().await;
                     config.user_tags.add(&member.user.id.0, tag);
                 }
+                Tags::Custom(tag) if custom_target(&config, &tag).await == Some(TagTarget::User) => {
+                    let mut config = config.write().await;
+                    config.custom_user_tags.add(&member.user.id.0, tag);
+                }
                 _ => finish!(ctx, msg, "This tag can't be added to a discord user"),
             }
             finish!(ctx, msg, "Successfully added tag to user");
@@ -96,6 +665,10 @@ async fn add_tag(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult
                     let mut config = config.write().await;
                     config.user_role_tags.add(&role.id.0, tag);
                 }
+                Tags::Custom(tag) if custom_target(&config, &tag).await == Some(TagTarget::User) => {
+                    let mut config = config.write().await;
+                    config.custom_user_role_tags.add(&role.id.0, tag);
+                }
                 _ => finish!(ctx, msg, "This tag can't be added to a role"),
             }
             finish!(ctx, msg, "Successfully added tag to role");
@@ -117,6 +690,10 @@ async fn add_tag(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult
                     let mut config = config.write().await;
                     config.text_channel_tags.add(id, tag);
                 }
+                Tags::Custom(tag) if custom_target(&config, &tag).await == Some(TagTarget::Channel) => {
+                    let mut config = config.write().await;
+                    config.custom_channel_tags.add(&channel.id().0, tag);
+                }
                 _ => finish!(ctx, msg, "This tag can't be added to a channel/category"),
             }
             finish!(ctx, msg, "Successfully added tag to channel/category");
@@ -143,14 +720,14 @@ async fn add_tag(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult
 async fn remove_tag(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
     let guild = some!(msg.guild(&ctx), cmd_bail!("Failed to get message's guild"));
 
-    let tag = arg!(ctx, msg, args, "tag": Tags);
+    let config = data!(ctx, "config");
+    let tag = parse_tag!(ctx, msg, args, config);
     let target_arg = args.rest();
 
     let target = match DiscordObject::from_str(&ctx, &guild, target_arg).await {
         Ok(v) => v,
         Err(why) => finish!(ctx, msg, format!("Invalid target: {}", why)),
     };
-    let config = data!(ctx, "config");
 
     match target {
         DiscordObject::Member(member) => {
@@ -160,6 +737,10 @@ async fn remove_tag(ctx: &Context, msg: &Message, mut args: Args) -> CommandResu
                     let mut config = config.write().await;
                     config.user_tags.remove(&member.user.id.0, &tag);
                 }
+                Tags::Custom(tag) => {
+                    let mut config = config.write().await;
+                    config.custom_user_tags.remove(&member.user.id.0, &tag);
+                }
                 _ => finish!(ctx, msg, "An user can't possibly have this tag as it is incompatible"),
             }
             finish!(ctx, msg, "Successfully removed tag from user");
@@ -170,6 +751,10 @@ async fn remove_tag(ctx: &Context, msg: &Message, mut args: Args) -> CommandResu
                     let mut config = config.write().await;
                     config.user_role_tags.remove(&role.id.0, &tag);
                 }
+                Tags::Custom(tag) => {
+                    let mut config = config.write().await;
+                    config.custom_user_role_tags.remove(&role.id.0, &tag);
+                }
                 _ => finish!(ctx, msg, "A role can't possibly have this tag as it is incompatible"),
             }
             finish!(ctx, msg, "Successfully removed tag from role");
@@ -184,6 +769,10 @@ async fn remove_tag(ctx: &Context, msg: &Message, mut args: Args) -> CommandResu
                     let mut config = config.write().await;
                     config.text_channel_tags.remove(&channel.id().0, &tag);
                 }
+                Tags::Custom(tag) => {
+                    let mut config = config.write().await;
+                    config.custom_channel_tags.remove(&channel.id().0, &tag);
+                }
                 _ => {
                     finish!(ctx, msg, "A channel/category can't possibly have this tag as it is incompatible")
                 }
@@ -224,10 +813,10 @@ async fn show_tags(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
 
             let user_tags = {
                 let config = config.read().await;
-                config
-                    .user_tags
-                    .get(&member.user.id.0)
-                    .map(|tags| string::str_join_iter(tags.iter()))
+                join_tag_lists([
+                    tag_list(config.user_tags.get(&member.user.id.0)),
+                    tag_list(config.custom_user_tags.get(&member.user.id.0)),
+                ])
             };
 
             let role_tags: Vec<(String, String)> = {
@@ -236,12 +825,16 @@ async fn show_tags(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
                     .roles
                     .iter()
                     .filter_map(|id| {
-                        config.user_role_tags.get(&id.0).map(|tags| {
+                        let tags = join_tag_lists([
+                            tag_list(config.user_role_tags.get(&id.0)),
+                            tag_list(config.custom_user_role_tags.get(&id.0)),
+                        ]);
+                        tags.map(|tags| {
                             let role_name = match id.to_role_cached(&ctx) {
                                 Some(role) => role.name,
                                 None => "UNKNOWN".to_string(),
                             };
-                            (role_name, string::str_join_iter(tags.iter()))
+                            (role_name, tags)
                         })
                     })
                     .collect()
@@ -265,10 +858,10 @@ async fn show_tags(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
         DiscordObject::Role(role) => {
             let list = {
                 let config = config.read().await;
-                config
-                    .user_role_tags
-                    .get(&role.id.0)
-                    .map(|tags| string::str_join_iter(tags.iter()))
+                join_tag_lists([
+                    tag_list(config.user_role_tags.get(&role.id.0)),
+                    tag_list(config.custom_user_role_tags.get(&role.id.0)),
+                ])
             };
             send_embed!(ctx, msg, |e| {
                 if let Some(list) = list {
@@ -323,8 +916,8 @@ async fn show_tags(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
 #[example("NoNickUpdate")]
 /// List all objects with this tag attached.
 async fn list_tagged(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
-    let tag = arg!(ctx, msg, args, "tag": Tags);
     let config = data!(ctx, "config");
+    let tag = parse_tag!(ctx, msg, args, config);
 
     let mut content = String::new();
     match tag {
@@ -354,6 +947,18 @@ async fn list_tagged(ctx: &Context, msg: &Message, mut args: Args) -> CommandRes
                 write!(content, "<#{}> ", channel_id)?;
             }
         }
+        Tags::Custom(tag) => {
+            let config = config.read().await;
+            for user_id in config.custom_user_tags.tagged_objects(&tag) {
+                write!(content, "<@!{}> ", user_id)?;
+            }
+            for role_id in config.custom_user_role_tags.tagged_objects(&tag) {
+                write!(content, "<@&{}> ", role_id)?;
+            }
+            for channel_id in config.custom_channel_tags.tagged_objects(&tag) {
+                write!(content, "<#{}> ", channel_id)?;
+            }
+        }
     }
     finish!(ctx, msg, if content.is_empty() { "Empty" } else { &content });
 }
@@ -366,7 +971,10 @@ async fn get_channel_parent_tag_lists(
     let category_tags = match category_id {
         Some(id) => {
             let config = config.read().await;
-            config.channel_tags.get(&id.0).map(|tags| string::str_join_iter(tags.iter()))
+            join_tag_lists([
+                tag_list(config.channel_tags.get(&id.0)),
+                tag_list(config.custom_channel_tags.get(&id.0)),
+            ])
         }
         None => None,
     };
@@ -379,26 +987,31 @@ async fn get_channel_parent_tag_lists(
 
 /// Get a channel's tag list
 async fn get_channel_tag_list(config: &RwLock<Config>, channel_id: &u64) -> Option<String> {
-    let channel_tags = {
-        let config = config.read().await;
-        config
-            .channel_tags
-            .get(channel_id)
-            .map(|tags| string::str_join_iter(tags.iter()))
-    };
-    let text_channel_tags = {
-        let config = config.read().await;
-        config
-            .text_channel_tags
-            .get(channel_id)
-            .map(|tags| string::str_join_iter(tags.iter()))
-    };
+    let config = config.read().await;
+    join_tag_lists([
+        tag_list(config.channel_tags.get(channel_id)),
+        tag_list(config.text_channel_tags.get(channel_id)),
+        tag_list(config.custom_channel_tags.get(channel_id)),
+    ])
+}
+
+/// Format tags into a list, `None` if there are no tags
+fn tag_list<T: Tag>(tags: Option<&HashSet<T>>) -> Option<String> {
+    tags.map(|tags| string::str_join_iter(tags.iter()))
+}
 
-    if channel_tags.is_some() && text_channel_tags.is_some() {
-        let mut channel_tags = channel_tags.unwrap();
-        channel_tags.push_str(", ");
-        channel_tags.push_str(&text_channel_tags.unwrap());
-        return Some(channel_tags);
+/// Join tag lists with ", ", `None` if all of them are `None`
+fn join_tag_lists<const N: usize>(lists: [Option<String>; N]) -> Option<String> {
+    let lists: Vec<String> = lists.into_iter().flatten().collect();
+    if lists.is_empty() {
+        None
+    } else {
+        Some(lists.join(", "))
     }
-    channel_tags.or(text_channel_tags)
+}
+
+/// Get what kind of objects a custom tag can be attached to
+async fn custom_target(config: &RwLock<Config>, tag: &CustomTag) -> Option<TagTarget> {
+    let config = config.read().await;
+    config.custom_tags.get(&tag.0).map(|def| def.target)
 }
@@ -0,0 +1,207 @@
+//! Personal weekly goal and guild goal commands
+use std::fmt::Write as _;
+
+use chrono::offset::Utc;
+use serenity::client::Context;
+use serenity::framework::standard::macros::command;
+use serenity::framework::standard::{Args, CommandResult};
+use serenity::model::channel::Message;
+
+use memberdb::model::discord::DiscordId;
+use memberdb::query_builder::Selectable;
+use memberdb::{goal, guild_goal};
+use util::{ctx, some};
+
+use crate::checks::STAFF_CHECK;
+use crate::i18n;
+use crate::util::guild_goal::format_goal;
+use crate::{arg, data, finish};
+
+/// Max amount of guild goals listed at once
+const GUILD_GOAL_LIST_LEN: usize = 10;
+
+#[command("goal")]
+#[sub_commands(set_goal, clear_goal)]
+/// Show your weekly goals and your progress on them.
+///
+/// Goals are checked at the weekly reset, and you are congratulated for each of them that you
+/// reached. They are kept between weeks until you clear them.
+/// To set or clear a goal, use subcommands.
+async fn show_goals(ctx: &Context, msg: &Message, _: Args) -> CommandResult {
+    let fmt = i18n::stat_format(ctx, msg).await;
+    let discord_id = ctx!(DiscordId::try_from(msg.author.id.0))?;
+    let db = data!(ctx, "db");
+    let goals = {
+        let db = db.read().await;
+        let mid = some!(ctx!(discord_id.mid(&mut db.exe()).await)?, finish!(ctx, msg, "You aren't a member"));
+        ctx!(mid.goals(&mut db.exe()).await)?
+    };
+    if goals.is_empty() {
+        finish!(ctx, msg, "You have no goals, set one with `goal set`");
+    }
+
+    let mut content = String::new();
+    for goal in goals {
+        let progress = msgtool::profile::format_goal_progress(&goal, &fmt);
+        writeln!(content, "`{}` {}", goal.stat.table_name(), progress)?;
+    }
+    finish!(ctx, msg, content)
+}
+
+#[command("set")]
+#[usage("<stat> <target>")]
+#[example("weekly_xp 2m")]
+#[example("weekly_voice 5h")]
+/// Set your goal of a weekly stat, replacing the existing one.
+///
+/// Available stats are: `weekly_message`, `weekly_voice`, `weekly_stream`, `weekly_online` and
+/// `weekly_xp`.
+/// Time based stats take a duration as `target`, ex: "5h" or "1 day", and others take a number,
+/// ex: "500" or "2m".
+async fn set_goal(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let fmt = i18n::stat_format(ctx, msg).await;
+    let (stat, target) = arg!(ctx, msg, args, "stat", "target");
+    let stat = some!(goal::parse_goal_stat(&stat).ok(), finish!(ctx, msg, "Invalid stat `{}`", stat));
+    let target = match stat.parse_val(&target) {
+        Ok(target) if target > 0 => i64::try_from(target)?,
+        _ => finish!(ctx, msg, "Invalid goal `{}`", target),
+    };
+    let discord_id = ctx!(DiscordId::try_from(msg.author.id.0))?;
+
+    let db = data!(ctx, "db");
+    {
+        let db = db.write().await;
+        let mid = some!(ctx!(discord_id.mid(&mut db.exe()).await)?, finish!(ctx, msg, "You aren't a member"));
+        let mut tx = ctx!(db.begin().await)?;
+        ctx!(goal::set_goal(&mut tx, mid, &stat, target).await, "Failed to set goal")?;
+        ctx!(tx.commit().await)?;
+    }
+    finish!(
+        ctx,
+        msg,
+        "Your `{}` goal is set to **{}**",
+        stat.table_name(),
+        msgtool::profile::format_stat_val(&stat, target, &fmt)
+    );
+}
+
+#[command("clear")]
+#[usage("<stat>")]
+#[example("weekly_xp")]
+/// Clear your goal of a weekly stat.
+async fn clear_goal(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let stat = arg!(ctx, msg, args, "stat");
+    let stat = some!(goal::parse_goal_stat(&stat).ok(), finish!(ctx, msg, "Invalid stat `{}`", stat));
+    let discord_id = ctx!(DiscordId::try_from(msg.author.id.0))?;
+
+    let db = data!(ctx, "db");
+    let removed = {
+        let db = db.write().await;
+        let mid = some!(ctx!(discord_id.mid(&mut db.exe()).await)?, finish!(ctx, msg, "You aren't a member"));
+        let mut tx = ctx!(db.begin().await)?;
+        let removed = ctx!(goal::remove_goal(&mut tx, mid, &stat).await)?;
+        ctx!(tx.commit().await)?;
+        removed
+    };
+    if removed {
+        finish!(ctx, msg, "Cleared your `{}` goal", stat.table_name());
+    }
+    finish!(ctx, msg, "You don't have a `{}` goal", stat.table_name());
+}
+
+#[command("guildgoal")]
+#[sub_commands(add_guild_goal, remove_guild_goal)]
+/// Show the guild goals and the guild's progress on them.
+///
+/// Every guild xp contribution made while a goal is running counts toward it. The progress is
+/// posted daily to the channels tagged with `GuildGoal`, where goals are also celebrated once
+/// they are completed.
+async fn show_guild_goals(ctx: &Context, msg: &Message, _: Args) -> CommandResult {
+    let fmt = i18n::stat_format(ctx, msg).await;
+    let db = data!(ctx, "db");
+    let goals = {
+        let db = db.read().await;
+        ctx!(guild_goal::list_goals(&mut db.exe()).await)?
+    };
+    if goals.is_empty() {
+        finish!(ctx, msg, "There are no guild goals");
+    }
+
+    let mut content = String::new();
+    for goal in goals.iter().take(GUILD_GOAL_LIST_LEN) {
+        let status = match goal.ended {
+            Some(_) if goal.completed() => " (completed)",
+            Some(_) => " (ran out of time)",
+            None => "",
+        };
+        writeln!(content, "{}{}", format_goal(goal, &fmt), status)?;
+    }
+    finish!(ctx, msg, content)
+}
+
+#[command("add")]
+#[checks(Staff)]
+#[usage("<target> <duration> <name>")]
+#[example("1b 90d Season xp")]
+#[example("500m never Road to level 100")]
+/// Set a guild goal of contributing `target` guild xp within `duration`, or without a deadline
+/// if `duration` is "never".
+///
+/// `target` is a number, ex: "500m" or "1b".
+/// If `duration` contains spaces, wrap it in quotes.
+async fn add_guild_goal(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let (target, duration) = arg!(ctx, msg, args, "target", "duration");
+    let target = match util::string::parse_num(&target) {
+        Ok(target) if target > 0 => target,
+        _ => finish!(ctx, msg, "Invalid target `{}`", target),
+    };
+    let now = Utc::now().timestamp();
+    let deadline = if duration == "never" {
+        None
+    } else {
+        match util::string::parse_second(&duration) {
+            Ok(duration) if duration > 0 => {
+                Some(now.saturating_add(i64::try_from(duration).unwrap_or(i64::MAX)))
+            }
+            _ => finish!(ctx, msg, "Invalid duration `{}`", duration),
+        }
+    };
+    let name = args.rest().trim();
+    if name.is_empty() {
+        finish!(ctx, msg, "What is the goal called?");
+    }
+
+    let db = data!(ctx, "db");
+    let id = {
+        let db = db.write().await;
+        let mut tx = ctx!(db.begin().await)?;
+        let id = ctx!(
+            guild_goal::add_goal(&mut tx, name, target, deadline, now).await,
+            "Failed to add guild goal"
+        )?;
+        ctx!(tx.commit().await)?;
+        id
+    };
+    finish!(ctx, msg, "Guild goal `#{}` is set", id);
+}
+
+#[command("remove")]
+#[checks(Staff)]
+#[usage("<id>")]
+#[example("1")]
+/// Remove a guild goal, use `guildgoal` to find its id.
+async fn remove_guild_goal(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let id = arg!(ctx, msg, args, "id": i64);
+    let db = data!(ctx, "db");
+    let removed = {
+        let db = db.write().await;
+        let mut tx = ctx!(db.begin().await)?;
+        let removed = ctx!(guild_goal::remove_goal(&mut tx, id).await)?;
+        ctx!(tx.commit().await)?;
+        removed
+    };
+    if removed {
+        finish!(ctx, msg, "Removed guild goal `#{}`", id);
+    }
+    finish!(ctx, msg, "There is no guild goal `#{}`", id);
+}
@@ -1,20 +1,28 @@
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
 use serenity::client::Context;
 use serenity::framework::standard::macros::command;
 use serenity::framework::standard::{Args, CommandResult};
 use serenity::model::channel::Message;
+use serenity::model::id::{ChannelId, UserId};
+use serenity::model::mention::Mentionable;
 use tokio::sync::RwLock;
 
+use config::locale::Locale;
+use config::tag::TextChannelTag;
 use memberdb::events::DBEvent;
 use memberdb::model::db::ProfileType;
 use memberdb::model::discord::DiscordId;
 use memberdb::model::member::{MemberId, MemberRank, MemberType};
 use memberdb::model::wynn::McId;
-use memberdb::DB;
+use memberdb::{ConcurrentChange, DB};
 use util::{ctx, ok, some};
 
 use crate::checks::{MAINSERVER_CHECK, STAFF_CHECK};
-use crate::util::db;
-use crate::{arg, cmd_bail, data, finish, send, t};
+use crate::i18n;
+use crate::util::{db, promotion_vote, reply, Terminator};
+use crate::{arg, cmd_bail, data, finish, send, t, tfinish, tr, ttry};
 
 #[command("addMember")]
 #[bucket("mojang")]
@@ -23,13 +31,14 @@ use crate::{arg, cmd_bail, data, finish, send, t};
 #[usage("<discord_user> <ign>")]
 #[example("Pucaet#9528 Pucaet\n")]
 /// Add a new member with provided discord and mc accounts.
-/// `discord_user` is a discord username, ex: `Pucaet` or `Pucaet#9528`.
+/// `discord_user` is a discord username or nickname, ex: `pucaet` or the legacy `Pucaet#9528`.
 ///
 /// > **How the initial rank is determined**
 /// if `ign` is in guild, their guild rank is used,
 /// otherwise the bot attempts to find a rank role on `discord_user` and use that.
 /// If all fails, the lowest rank is used.
 pub async fn add_member(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let lc = i18n::locale(ctx, msg).await;
     let (discord_name, ign) = arg!(ctx, msg, args, "discord_user", "ign");
 
     let (db, client) = data!(ctx, "db", "reqwest");
@@ -41,11 +50,10 @@ pub async fn add_member(ctx: &Context, msg: &Message, mut args: Args) -> Command
     // Check for precondition. Both profiles has to be unlinked
     let (wynn_mid, discord_mid) = db::get_profile_mids(&db, discord_id, &mcid).await;
     if discord_mid.is_some() && wynn_mid.is_some() && discord_mid == wynn_mid {
-        finish!(ctx, msg, "Both profiles are already linked to the same member");
+        finish!(ctx, msg, tr!(lc, ProfilesSameMember));
     }
     if wynn_mid.is_some() || discord_mid.is_some() {
-        finish!(ctx, msg, "At least one of the provided profiles is already linked to a member. If you want to update / add \
-profiles on an existing member, use the command `link` instead");
+        finish!(ctx, msg, tr!(lc, ProfileAlreadyLinked));
     }
 
     // Getting initial member rank
@@ -78,8 +86,8 @@ profiles on an existing member, use the command `link` instead");
         ctx,
         msg,
         match result {
-            Ok(_) => "Successfully added member",
-            Err(_) => "Failed to add member",
+            Ok(_) => tr!(lc, MemberAdded),
+            Err(_) => tr!(lc, MemberAddFailed),
         }
     )
 }
@@ -91,7 +99,7 @@ profiles on an existing member, use the command `link` instead");
 #[usage("<discord_user> <ign>")]
 #[example("Pucaet#9528 Pucaet")]
 /// Update a profile link of an existing member.
-/// `discord_user` is a discord username, ex: `Pucaet` or `Pucaet#9528`.
+/// `discord_user` is a discord username or nickname, ex: `pucaet` or the legacy `Pucaet#9528`.
 ///
 /// This command only accepts one linked account representing an existing member,
 /// and an unlinked account to be linked to that member.
@@ -105,6 +113,7 @@ profiles on an existing member, use the command `link` instead");
 /// If a discord partial member (discord: "my_account") wants to link up their mc account "myIgn",
 /// to do that, use `link my_account myIgn`.
 pub async fn link_profile(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let lc = i18n::locale(ctx, msg).await;
     let (discord_name, ign) = arg!(ctx, msg, args, "discord_user", "ign");
 
     let (db, client) = data!(ctx, "db", "reqwest");
@@ -115,17 +124,16 @@ pub async fn link_profile(ctx: &Context, msg: &Message, mut args: Args) -> Comma
 
     let (wynn_mid, discord_mid) = crate::util::db::get_profile_mids(&db, discord_id, &mcid).await;
     if discord_mid.and(wynn_mid).is_some() && discord_mid == wynn_mid {
-        finish!(ctx, msg, "Both profiles are already linked to the same member");
+        finish!(ctx, msg, tr!(lc, ProfilesSameMember));
     }
     if discord_mid.or(wynn_mid).is_none() {
-        finish!(ctx, msg, "Both profiles are unlinked. If you want to add a new member, use the command `addMember` instead");
+        finish!(ctx, msg, tr!(lc, ProfilesBothUnlinked));
     }
     if discord_mid.and(wynn_mid).is_some() {
         finish!(
             ctx,
             msg,
-            "Both profiles are linked. If you want to link both profiles to the same member, \
-unlink one of them first, then call this command again"
+            tr!(lc, ProfilesBothLinked)
         )
     }
 
@@ -134,10 +142,16 @@ unlink one of them first, then call this command again"
         let result = {
             let db = db.write().await;
             let mut tx = ctx!(db.begin().await)?;
-            let r = ctx!(
-                mid.bind_discord(&mut tx, Some(discord_id)).await,
-                "Failed to link discord profile to member"
-            );
+            let r = async {
+                // The profiles can be changed by someone else since the checks
+                discord_id.expect_mid(&mut tx.exe(), None).await?;
+                mcid.expect_mid(&mut tx.exe(), Some(mid)).await?;
+                ctx!(
+                    mid.bind_discord(&mut tx, Some(discord_id)).await,
+                    "Failed to link discord profile to member"
+                )
+            }
+            .await;
             if r.is_ok() {
                 ctx!(tx.commit().await)?;
             }
@@ -148,8 +162,9 @@ unlink one of them first, then call this command again"
             ctx,
             msg,
             match result {
-                Ok(_) => "Successfully linked discord user to member",
-                Err(_) => "Failed to link discord user to member",
+                Ok(_) => tr!(lc, DiscordLinked),
+                Err(why) if why.is::<ConcurrentChange>() => tr!(lc, MemberChangedConcurrently),
+                Err(_) => tr!(lc, DiscordLinkFailed),
             }
         )
     // Updating wynn profile linke
@@ -157,20 +172,24 @@ unlink one of them first, then call this command again"
         {
             // Checking if member already have a wynn profile
             let db = db.read().await;
+            if ctx!(mid.kind(&mut db.exe()).await)?.is_guest() {
+                finish!(ctx, msg, tr!(lc, GuestWynnLink));
+            }
             if let Some(ign) = existing_wynn_link_check(&db, mid).await {
-                finish!(ctx, msg,
-                    format!("The discord user `{}` already has a mc account `{}` linked to them, which can't be changed",
-                            discord_name, ign));
+                finish!(ctx, msg, tr!(lc, WynnLinkExists, discord_name, ign));
             }
         }
 
         let result = {
             let db = db.write().await;
             let mut tx = ctx!(db.begin().await)?;
-            let r = ctx!(
-                mid.bind_wynn(&mut tx, Some(&mcid), &ign).await,
-                "Failed to link wynn profile to member"
-            );
+            let r = async {
+                // The profiles can be changed by someone else since the checks
+                mcid.expect_mid(&mut tx.exe(), None).await?;
+                discord_id.expect_mid(&mut tx.exe(), Some(mid)).await?;
+                ctx!(mid.bind_wynn(&mut tx, Some(&mcid), &ign).await, "Failed to link wynn profile to member")
+            }
+            .await;
             if r.is_ok() {
                 ctx!(tx.commit().await)?;
             }
@@ -181,8 +200,9 @@ unlink one of them first, then call this command again"
             ctx,
             msg,
             match result {
-                Ok(_) => "Successfully linked mc user to member",
-                Err(_) => "Failed to link mc user to member",
+                Ok(_) => tr!(lc, WynnLinked),
+                Err(why) if why.is::<ConcurrentChange>() => tr!(lc, MemberChangedConcurrently),
+                Err(_) => tr!(lc, WynnLinkFailed),
             }
         )
     }
@@ -198,7 +218,8 @@ unlink one of them first, then call this command again"
 #[example("discord Pucaet#9528")]
 #[example("wynn Pucaet")]
 /// Add a discord or wynn partial member with corresponding discord user or mc account.
-/// A discord user is specified with their username, ex: "Pucaet" or "Pucaet#9528".
+/// A discord user is specified with their username or nickname, ex: "pucaet" or the legacy
+/// "Pucaet#9528".
 ///
 /// > **How initial member rank is determined**
 /// For discord partial member, the bot attempts to find a rank role from the user and use that,
@@ -206,11 +227,12 @@ unlink one of them first, then call this command again"
 /// For wynn partial member, if they're in the guild, their guild rank is used,
 /// otherwise the lowest rank is used.
 async fn add_partial(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let lc = i18n::locale(ctx, msg).await;
     let profile_type = arg!(ctx, msg, args, "partial member type": ProfileType);
     let target_arg = args.rest();
 
     if let ProfileType::Guild = profile_type {
-        finish!(ctx, msg, "Invalid partial member type (need to be discord or wynn)");
+        finish!(ctx, msg, tr!(lc, InvalidPartialType));
     }
 
     let guild = some!(msg.guild(&ctx), cmd_bail!("Failed to get message guild"));
@@ -220,7 +242,7 @@ async fn add_partial(ctx: &Context, msg: &Message, mut args: Args) -> CommandRes
         ProfileType::Discord => {
             let discord_member = some!(
                 ctx!(util::discord::get_member_named(&ctx.http, &guild, target_arg).await)?,
-                finish!(ctx, msg, "Failed to find discord user of given name")
+                finish!(ctx, msg, tr!(lc, DiscordUserNotFound))
             );
             let discord_id = DiscordId::try_from(discord_member.as_ref().user.id.0)?;
 
@@ -228,7 +250,7 @@ async fn add_partial(ctx: &Context, msg: &Message, mut args: Args) -> CommandRes
                 // checking if there is already a discord profile linked
                 let db = db.read().await;
                 if let Ok(Some(_)) = discord_id.mid(&mut db.exe()).await {
-                    finish!(ctx, msg, "discord user already linked with a member");
+                    finish!(ctx, msg, tr!(lc, DiscordAlreadyMember));
                 }
             }
 
@@ -257,20 +279,20 @@ async fn add_partial(ctx: &Context, msg: &Message, mut args: Args) -> CommandRes
                 ctx,
                 msg,
                 match result {
-                    Ok(_) => "Successfully added discord partial member",
-                    Err(_) => "Failed to add discord partial member",
+                    Ok(_) => tr!(lc, DiscordPartialAdded),
+                    Err(_) => tr!(lc, DiscordPartialAddFailed),
                 }
             );
         }
         ProfileType::Wynn => {
             let mcid =
-                ok!(wynn::get_id(&client, target_arg).await, finish!(ctx, msg, "Provided ign doesn't exist"));
+                ok!(wynn::get_id(&client, target_arg).await, finish!(ctx, msg, tr!(lc, IgnNotFound)));
             let mcid = McId(mcid);
 
             {
                 let db = db.read().await;
                 if let Ok(Some(_)) = mcid.mid(&mut db.exe()).await {
-                    finish!(ctx, msg, "mc account already linked with a member");
+                    finish!(ctx, msg, tr!(lc, McAlreadyMember));
                 }
             }
 
@@ -295,8 +317,8 @@ async fn add_partial(ctx: &Context, msg: &Message, mut args: Args) -> CommandRes
                 ctx,
                 msg,
                 match result {
-                    Ok(_) => "Successfully added wynn partial member",
-                    Err(_) => "Failed to add wynn partial member",
+                    Ok(_) => tr!(lc, WynnPartialAdded),
+                    Err(_) => tr!(lc, WynnPartialAddFailed),
                 }
             );
         }
@@ -304,6 +326,55 @@ async fn add_partial(ctx: &Context, msg: &Message, mut args: Args) -> CommandRes
     }
 }
 
+#[command("addGuest")]
+#[only_in(guild)]
+#[checks(MainServer, Staff)]
+#[usage("<discord_user>")]
+#[example("Pucaet#9528")]
+/// Add a visiting discord user as a guest, ex: someone from an allied guild.
+/// `discord_user` is a discord username or nickname, ex: `pucaet` or the legacy `Pucaet#9528`.
+///
+/// Guests have their message and voice activity tracked like members, but they don't get any
+/// rank roles, their rank can't be changed, and they aren't in guild requirement reports.
+/// Use `removeMember` to stop tracking a guest.
+async fn add_guest(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let lc = i18n::locale(ctx, msg).await;
+    let guild = some!(msg.guild(&ctx), cmd_bail!("Failed to get message guild"));
+    let db = data!(ctx, "db");
+
+    let discord_member = some!(
+        ctx!(util::discord::get_member_named(&ctx.http, &guild, args.rest()).await)?,
+        finish!(ctx, msg, tr!(lc, DiscordUserNotFound))
+    );
+    let discord_id = DiscordId::try_from(discord_member.as_ref().user.id.0)?;
+
+    {
+        let db = db.read().await;
+        if let Ok(Some(_)) = discord_id.mid(&mut db.exe()).await {
+            finish!(ctx, msg, tr!(lc, DiscordAlreadyMember));
+        }
+    }
+
+    let result = {
+        let db = db.write().await;
+        let mut tx = ctx!(db.begin().await)?;
+        let r = ctx!(MemberId::add_guest(&mut tx, discord_id).await, "Failed to add guest");
+        if r.is_ok() {
+            ctx!(tx.commit().await)?;
+        }
+        r
+    };
+
+    finish!(
+        ctx,
+        msg,
+        match result {
+            Ok(_) => tr!(lc, GuestAdded),
+            Err(_) => tr!(lc, GuestAddFailed),
+        }
+    )
+}
+
 #[command("unlink")]
 #[bucket("mojang")]
 #[only_in(guild)]
@@ -314,20 +385,21 @@ async fn add_partial(ctx: &Context, msg: &Message, mut args: Args) -> CommandRes
 /// Unlink discord or wynn account from a member which is specified by `target`.
 ///
 /// > **How do I specify different targets**
-/// - __Discord user__: "d:<username>", ex: "d:Pucaet" or "d:Pucaet#9528"
+/// - __Discord user__: "d:<username>", ex: "d:pucaet" or the legacy "d:Pucaet#9528"
 /// - __Mc account__: "m:<ign>", ex: "m:SephDark18"
 async fn unlink_profile(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let lc = i18n::locale(ctx, msg).await;
     let profile_type = arg!(ctx, msg, args, "partial member type": ProfileType);
     let target_arg = args.rest();
 
     if let ProfileType::Guild = profile_type {
-        finish!(ctx, msg, "Invalid profile type (need to be discord or wynn)");
+        finish!(ctx, msg, tr!(lc, InvalidProfileType));
     }
 
     let guild = some!(msg.guild(&ctx), cmd_bail!("Failed to get message guild"));
     let (db, client) = data!(ctx, "db", "reqwest");
 
-    let mid = t!(db::parse_user_target_mid(ctx, msg, &db, &client, &guild, target_arg).await);
+    let mid = t!(db::parse_user_target_mid(ctx, msg, &db, &client, Some(&guild), target_arg).await);
 
     let (old_discord, old_mcid) = {
         let db = db.read().await;
@@ -336,17 +408,20 @@ async fn unlink_profile(ctx: &Context, msg: &Message, mut args: Args) -> Command
 
     match profile_type {
         ProfileType::Discord => {
-            if old_discord.is_none() {
-                finish!(ctx, msg, "There is no linked discord profile for the command to unlink");
-            }
+            let old_discord = some!(old_discord, finish!(ctx, msg, tr!(lc, NoDiscordLink)));
 
             let result = {
                 let db = db.write().await;
                 let mut tx = ctx!(db.begin().await)?;
-                let r = ctx!(
-                    mid.bind_discord(&mut tx, None).await,
-                    "Failed to unbind discord profile from member"
-                );
+                let r = async {
+                    // The profile can be changed by someone else since the checks
+                    old_discord.expect_mid(&mut tx.exe(), Some(mid)).await?;
+                    ctx!(
+                        mid.bind_discord(&mut tx, None).await,
+                        "Failed to unbind discord profile from member"
+                    )
+                }
+                .await;
                 if r.is_ok() {
                     ctx!(tx.commit().await)?;
                 }
@@ -354,27 +429,32 @@ async fn unlink_profile(ctx: &Context, msg: &Message, mut args: Args) -> Command
             };
 
             match result {
-                Ok(_) => send!(ctx, msg, "Successfully unlinked discord profile"),
-                Err(_) => finish!(ctx, msg, "Failed to unlink discord profile from member"),
+                Ok(_) => send!(ctx, msg, tr!(lc, DiscordUnlinked)),
+                Err(why) if why.is::<ConcurrentChange>() => {
+                    finish!(ctx, msg, tr!(lc, MemberChangedConcurrently))
+                }
+                Err(_) => finish!(ctx, msg, tr!(lc, DiscordUnlinkFailed)),
             }
         }
         ProfileType::Wynn => {
-            if old_mcid.is_none() {
-                finish!(ctx, msg, "There is no linked wynn profile for the command to unlink");
-            }
+            let old_mcid = some!(old_mcid, finish!(ctx, msg, tr!(lc, NoWynnLink)));
             let member_type = {
                 let db = db.read().await;
                 ctx!(mid.kind(&mut db.exe()).await)?
             };
             if let MemberType::GuildPartial = member_type {
-                finish!(ctx, msg, "You can't unlink wynn profile of a guild partial member")
+                finish!(ctx, msg, tr!(lc, GuildPartialUnlink))
             }
 
             let result = {
                 let db = db.write().await;
                 let mut tx = ctx!(db.begin().await)?;
-                let r =
-                    ctx!(mid.bind_wynn(&mut tx, None, "").await, "Failed to unbind wynn profile from member");
+                let r = async {
+                    // The profile can be changed by someone else since the checks
+                    old_mcid.expect_mid(&mut tx.exe(), Some(mid)).await?;
+                    ctx!(mid.bind_wynn(&mut tx, None, "").await, "Failed to unbind wynn profile from member")
+                }
+                .await;
                 if r.is_ok() {
                     ctx!(tx.commit().await)?;
                 }
@@ -382,8 +462,11 @@ async fn unlink_profile(ctx: &Context, msg: &Message, mut args: Args) -> Command
             };
 
             match result {
-                Ok(_) => send!(ctx, msg, "Successfully unlinked wynn profile"),
-                Err(_) => finish!(ctx, msg, "Failed to unlink wynn profile from member"),
+                Ok(_) => send!(ctx, msg, tr!(lc, WynnUnlinked)),
+                Err(why) if why.is::<ConcurrentChange>() => {
+                    finish!(ctx, msg, tr!(lc, MemberChangedConcurrently))
+                }
+                Err(_) => finish!(ctx, msg, tr!(lc, WynnUnlinkFailed)),
             }
         }
         ProfileType::Guild => {
@@ -404,20 +487,21 @@ async fn unlink_profile(ctx: &Context, msg: &Message, mut args: Args) -> Command
 /// Note that you can't remove a guild partial member with this command.
 ///
 /// > **How do I specify different targets**
-/// - __Discord user__: "d:<username>", ex: "d:Pucaet" or "d:Pucaet#9528"
+/// - __Discord user__: "d:<username>", ex: "d:pucaet" or the legacy "d:Pucaet#9528"
 /// - __Mc account__: "m:<ign>", ex: "m:SephDark18"
 pub async fn remove_member(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let lc = i18n::locale(ctx, msg).await;
     let guild = some!(msg.guild(&ctx), cmd_bail!("Failed to get message's guild"));
     let (db, client) = data!(ctx, "db", "reqwest");
 
-    let mid = t!(db::parse_user_target_mid(ctx, msg, &db, &client, &guild, args.rest()).await);
+    let mid = t!(db::parse_user_target_mid(ctx, msg, &db, &client, Some(&guild), args.rest()).await);
 
     let member_type = {
         let db = db.read().await;
         mid.kind(&mut db.exe()).await?
     };
     if let MemberType::GuildPartial = member_type {
-        finish!(ctx, msg, "You can't remove a guild partial with this command")
+        finish!(ctx, msg, tr!(lc, GuildPartialRemove))
     }
 
     let result = {
@@ -434,92 +518,141 @@ pub async fn remove_member(ctx: &Context, msg: &Message, args: Args) -> CommandR
         ctx,
         msg,
         match result {
-            Ok(_) => "Successfully removed member",
-            Err(_) => "Failed to remove member",
+            Ok(_) => tr!(lc, MemberRemoved),
+            Err(_) => tr!(lc, MemberRemoveFailed),
         }
     )
 }
 
-/// Set a member's rank
-async fn set_rank(
-    ctx: &Context, msg: &Message, db: &RwLock<DB>, mid: MemberId, old_rank: MemberRank, rank: MemberRank,
-) -> CommandResult {
+/// Check if `caller` is allowed to change member `mid`'s rank from `old_rank` to `rank`.
+/// Guests are excluded from rank changes.
+#[allow(clippy::too_many_arguments)]
+async fn check_rank_change(
+    ctx: &Context, sender: &impl reply::Respond, lc: Locale, caller: UserId, db: &RwLock<DB>, mid: MemberId,
+    old_rank: MemberRank, rank: MemberRank,
+) -> Terminator<()> {
+    {
+        let db = db.read().await;
+        if ttry!(mid.kind(&mut db.exe()).await).is_guest() {
+            tfinish!(ctx, sender, tr!(lc, GuestRankChange));
+        }
+    }
     if old_rank == rank {
-        finish!(ctx, msg, "Member is already specified rank");
+        tfinish!(ctx, sender, tr!(lc, SameRank));
     }
 
     let caller_rank = {
         let db = db.read().await;
-        let discord_id = DiscordId::try_from(msg.author.id.0)?;
-        let mid = some!(
-            ctx!(discord_id.mid(&mut db.exe()).await)?,
-            finish!(ctx, msg, "Only a member can use this command")
-        );
-        ctx!(mid.rank(&mut db.exe()).await)?
+        let discord_id = ttry!(DiscordId::try_from(caller.0));
+        let mid =
+            some!(ttry!(discord_id.mid(&mut db.exe()).await), tfinish!(ctx, sender, tr!(lc, MemberOnly)));
+        ttry!(mid.rank(&mut db.exe()).await)
     };
     if caller_rank <= old_rank {
-        finish!(ctx, msg, "You can't change the rank of someone with a higher or equal rank to yours")
+        tfinish!(ctx, sender, tr!(lc, TargetRankTooHigh))
     }
     if caller_rank <= rank {
-        finish!(ctx, msg, "You can't set someone else to a rank that is higher or equal to yours");
+        tfinish!(ctx, sender, tr!(lc, RankTooHigh));
     }
+    Terminator::Proceed(())
+}
+
+/// Set a member's rank on behalf of `caller`, and send the result to `sender`.
+/// If `duration` is given, the rank is temporary and is reverted after `duration` seconds.
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn set_rank(
+    ctx: &Context, sender: &impl reply::Respond, lc: Locale, caller: UserId, db: &RwLock<DB>, mid: MemberId,
+    old_rank: MemberRank, rank: MemberRank, duration: Option<u64>,
+) -> CommandResult {
+    t!(check_rank_change(ctx, sender, lc, caller, db, mid, old_rank, rank).await);
+
+    let expire = match duration {
+        Some(duration) => {
+            let now = ctx!(SystemTime::now().duration_since(UNIX_EPOCH), "Failed to get current unix timestamp")?;
+            Some(i64::try_from(now.as_secs() + duration)?)
+        }
+        None => None,
+    };
 
     let result = {
         let db = db.write().await;
         let mut tx = ctx!(db.begin().await)?;
-        let r = mid.set_rank(&mut tx, rank).await;
+        // Fails if the rank is changed by someone else since the checks
+        let r = mid.change_rank(&mut tx, old_rank, rank, expire).await;
         if r.is_ok() {
             ctx!(tx.commit().await)?;
         }
         r
     };
-    finish!(
-        ctx,
-        msg,
-        match result {
-            Ok(_) => {
-                {
-                    let db = db.read().await;
-                    db.signal(DBEvent::MemberRankChange { mid, old: old_rank, new: rank });
-                }
-                "Successfully changed member's rank"
+    match result {
+        Ok(_) => {
+            {
+                let db = db.read().await;
+                db.signal(DBEvent::MemberRankChange { mid, old: old_rank, new: rank });
+            }
+            match duration {
+                Some(duration) => finish!(
+                    ctx,
+                    sender,
+                    tr!(lc, TempRankChanged, util::string::fmt_second(i64::try_from(duration)?))
+                ),
+                None => finish!(ctx, sender, tr!(lc, RankChanged)),
             }
-            Err(_) => "Failed to change member's rank",
         }
-    )
+        Err(why) if why.is::<ConcurrentChange>() => finish!(ctx, sender, tr!(lc, MemberChangedConcurrently)),
+        Err(_) => finish!(ctx, sender, tr!(lc, RankChangeFailed)),
+    }
 }
 
 #[command("setRank")]
 #[bucket("mojang")]
 #[only_in(guild)]
 #[checks(MainServer, Staff)]
-#[usage("<rank> <target>")]
+#[usage("<rank> <target> [--for <duration>]")]
 #[example("Comonaut m:Pucaet")]
 #[example("Cadet d:Pucaet#9528")]
+#[example("Pilot m:Pucaet --for 14d")]
 /// Set a member's rank.
 /// Member is specified by `target`, which can be discord user or ign.
 /// `rank` can't be higher or equal to your own rank.
 /// The target can't be in a rank that higher or equal to yours.
 ///
+/// If `--for <duration>` is given, the rank is temporary, and the member is reverted to their
+/// previous rank once the duration elapsed. The expiry is checked daily.
+/// `duration` is written as `(number)(time unit)`, ex: `14d` is 14 days, or as an ISO-8601
+/// duration, ex: `P2W`.
+/// Following time units are allows: `s` (second), `m` (minute), `h` (hour), `d` (day), and `w`
+/// (week).
+///
 /// There are also shortcut commands: `promote` and `demote`
 ///
 /// > **How do I specify different targets**
-/// - __Discord user__: "d:<username>", ex: "d:Pucaet" or "d:Pucaet#9528"
+/// - __Discord user__: "d:<username>", ex: "d:pucaet" or the legacy "d:Pucaet#9528"
 /// - __Mc account__: "m:<ign>", ex: "m:SephDark18"
 pub async fn set_member_rank(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let lc = i18n::locale(ctx, msg).await;
     let guild = some!(msg.guild(&ctx), cmd_bail!("Failed to get message's guild"));
     let (db, client) = data!(ctx, "db", "reqwest");
 
     let rank = arg!(ctx, msg, args, "rank": MemberRank);
 
-    let mid = t!(db::parse_user_target_mid(ctx, msg, &db, &client, &guild, args.rest()).await);
+    // Splits the optional duration from the target, as the target can contain spaces
+    let (target, duration) = match args.rest().rsplit_once(" --for ") {
+        Some((target, duration)) => match util::string::parse_second(duration.trim()) {
+            Ok(duration) => (target, Some(duration)),
+            Err(_) => finish!(ctx, msg, tr!(lc, InvalidDuration, duration.trim())),
+        },
+        None => (args.rest(), None),
+    };
+
+    let mid = t!(db::parse_user_target_mid(ctx, msg, &db, &client, Some(&guild), target).await);
 
     let old_rank = {
         let db = db.read().await;
         ctx!(mid.rank(&mut db.exe()).await)?
     };
 
-    set_rank(ctx, msg, &db, mid, old_rank, rank).await
+    set_rank(ctx, msg, lc, msg.author.id, &db, mid, old_rank, rank, duration).await
 }
 
 #[command("promote")]
@@ -537,21 +670,22 @@ pub async fn set_member_rank(ctx: &Context, msg: &Message, mut args: Args) -> Co
 /// There are also the command `setRank` to set a member's rank directly.
 ///
 /// > **How do I specify different targets**
-/// - __Discord user__: "d:<username>", ex: "d:Pucaet" or "d:Pucaet#9528"
+/// - __Discord user__: "d:<username>", ex: "d:pucaet" or the legacy "d:Pucaet#9528"
 /// - __Mc account__: "m:<ign>", ex: "m:SephDark18"
 pub async fn promote_member(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let lc = i18n::locale(ctx, msg).await;
     let guild = some!(msg.guild(&ctx), cmd_bail!("Failed to get message's guild"));
     let (db, client) = data!(ctx, "db", "reqwest");
 
-    let mid = t!(db::parse_user_target_mid(ctx, msg, &db, &client, &guild, args.rest()).await);
+    let mid = t!(db::parse_user_target_mid(ctx, msg, &db, &client, Some(&guild), args.rest()).await);
 
     let old_rank = {
         let db = db.read().await;
         ctx!(mid.rank(&mut db.exe()).await)?
     };
-    let rank = some!(old_rank.promote(), finish!(ctx, msg, "Member is already the highest rank"));
+    let rank = some!(old_rank.promote(), finish!(ctx, msg, tr!(lc, HighestRank)));
 
-    set_rank(ctx, msg, &db, mid, old_rank, rank).await
+    set_rank(ctx, msg, lc, msg.author.id, &db, mid, old_rank, rank, None).await
 }
 
 #[command("demote")]
@@ -569,21 +703,111 @@ pub async fn promote_member(ctx: &Context, msg: &Message, args: Args) -> Command
 /// There are also the command `setRank` to set a member's rank directly.
 ///
 /// > **How do I specify different targets**
-/// - __Discord user__: "d:<username>", ex: "d:Pucaet" or "d:Pucaet#9528"
+/// - __Discord user__: "d:<username>", ex: "d:pucaet" or the legacy "d:Pucaet#9528"
 /// - __Mc account__: "m:<ign>", ex: "m:SephDark18"
 pub async fn demote_member(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let lc = i18n::locale(ctx, msg).await;
     let guild = some!(msg.guild(&ctx), cmd_bail!("Failed to get message's guild"));
     let (db, client) = data!(ctx, "db", "reqwest");
 
-    let mid = t!(db::parse_user_target_mid(ctx, msg, &db, &client, &guild, args.rest()).await);
+    let mid = t!(db::parse_user_target_mid(ctx, msg, &db, &client, Some(&guild), args.rest()).await);
+
+    let old_rank = {
+        let db = db.read().await;
+        ctx!(mid.rank(&mut db.exe()).await)?
+    };
+    let rank = some!(old_rank.demote(), finish!(ctx, msg, tr!(lc, LowestRank)));
+
+    set_rank(ctx, msg, lc, msg.author.id, &db, mid, old_rank, rank, None).await
+}
+
+#[command("promotionvote")]
+#[bucket("mojang")]
+#[only_in(guild)]
+#[checks(MainServer, Staff)]
+#[usage("<target> <rank>")]
+#[example("m:Pucaet Pilot")]
+#[example("d:Pucaet#9528 Cosmonaut")]
+/// Open a vote among staff members on changing a member's rank.
+/// Member is specified by `target`, which can be discord user or ign.
+/// `rank` can't be higher or equal to your own rank.
+/// The target can't be in a rank that higher or equal to yours.
+///
+/// The vote is held in the channel tagged with `PromotionVote`, where staff members vote by
+/// reacting with ✅ or ❌. Once the voting window is over, the rank change is applied if enough
+/// staff members approved it, and there are more approvals than rejections.
+/// The voting window and the amount of approvals needed are set in the bot's config.
+///
+/// > **How do I specify different targets**
+/// - __Discord user__: "d:<username>", ex: "d:pucaet" or the legacy "d:Pucaet#9528"
+/// - __Mc account__: "m:<ign>", ex: "m:SephDark18"
+pub async fn vote_member_rank(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let lc = i18n::locale(ctx, msg).await;
+    let guild = some!(msg.guild(ctx), cmd_bail!("Failed to get message's guild"));
+    let (db, client, config) = data!(ctx, "db", "reqwest", "config");
 
+    // The rank is the last argument, as the target can contain spaces
+    let (target, rank) = some!(args.rest().rsplit_once(' '), finish!(ctx, msg, "rank not provided"));
+    let rank = ok!(MemberRank::from_str(rank), finish!(ctx, msg, "'{}' isn't a valid rank", rank));
+
+    let mid = t!(db::parse_user_target_mid(ctx, msg, &db, &client, Some(&guild), target).await);
     let old_rank = {
         let db = db.read().await;
+        if ctx!(mid.in_promotion_vote(&mut db.exe()).await)? {
+            finish!(ctx, msg, tr!(lc, PromotionVoteExists));
+        }
         ctx!(mid.rank(&mut db.exe()).await)?
     };
-    let rank = some!(old_rank.demote(), finish!(ctx, msg, "Member is already the lowest rank"));
+    t!(check_rank_change(ctx, msg, lc, msg.author.id, &db, mid, old_rank, rank).await);
+
+    let (channel_id, window) = {
+        let config = config.read().await;
+        let channel_id = some!(
+            config.text_channel_tags.tagged_objects(&TextChannelTag::PromotionVote).next(),
+            finish!(ctx, msg, tr!(lc, NoPromotionVoteChannel))
+        );
+        (ChannelId(*channel_id), config.promotion_votes.window)
+    };
+
+    let name = {
+        let db = db.read().await;
+        match ctx!(mid.links(&mut db.exe()).await)? {
+            (_, Some(mcid)) => ctx!(mcid.ign(&mut db.exe()).await)?,
+            (Some(id), None) => {
+                let user = some!(id.to_user(&ctx.cache), cmd_bail!("Failed to get discord user"));
+                util::discord::user_tag(&user.name, user.discriminator)
+            }
+            (None, None) => cmd_bail!("Member has no profiles"),
+        }
+    };
+    let now = ctx!(SystemTime::now().duration_since(UNIX_EPOCH), "Failed to get current unix timestamp")?;
+    let close = i64::try_from(now.as_secs() + window)?;
+
+    let poll = promotion_vote::make_poll(&name, old_rank, rank, msg.author.id, close);
+    let poll = ctx!(channel_id.say(&ctx, poll).await, "Failed to send promotion vote poll")?;
+    ctx!(poll.react(&ctx, promotion_vote::APPROVE).await)?;
+    ctx!(poll.react(&ctx, promotion_vote::REJECT).await)?;
+
+    {
+        let db = db.write().await;
+        let mut tx = ctx!(db.begin().await)?;
+        ctx!(
+            memberdb::open_promotion_vote(
+                &mut tx,
+                mid,
+                DiscordId::try_from(msg.author.id.0)?,
+                old_rank,
+                rank,
+                i64::try_from(channel_id.0)?,
+                i64::try_from(poll.id.0)?,
+                close
+            )
+            .await
+        )?;
+        ctx!(tx.commit().await)?;
+    }
 
-    set_rank(ctx, msg, &db, mid, old_rank, rank).await
+    finish!(ctx, msg, tr!(lc, PromotionVoteOpened, channel_id.mention()))
 }
 
 /// Checks if a member has a linked wynn profile, if so, return Some(ign).
@@ -1,27 +1,43 @@
 //! Commands for displaying member statistics
+use std::borrow::Cow;
 use std::fmt::Write as _;
 
 use anyhow::Context as AHContext;
+use chrono::{DateTime, Utc};
+use serenity::builder::CreateEmbed;
 use serenity::client::Context;
 use serenity::framework::standard::macros::command;
 use serenity::framework::standard::{Args, CommandResult};
-use serenity::model::channel::Message;
+use serenity::model::channel::{AttachmentType, Message};
+use serenity::model::user::User;
+use tokio::sync::RwLock;
+use tracing::warn;
 
-use memberdb::model::db::{Profiles, Stat};
+use config::locale::Locale;
+use memberdb::model::db::{Column, Profiles, Stat};
 use memberdb::model::discord::DiscordId;
-use memberdb::query_builder::{Filter, QueryMod, Selectables, Sort};
+use memberdb::query_builder::{Filter, GroupBy, QueryMod, Selectable, Selectables, Sort};
+use memberdb::{global_rank, message_log, rank_history, waitlist, DB};
+use msgtool::card::{render_card, Avatar, Card, Emblem, ProgressBar};
 use msgtool::pager::Pager;
-use msgtool::table::{self, TableData};
+use msgtool::parser::DiscordObject;
+use msgtool::table::{self, TableData, TableImage};
+use util::string::StatFormat;
 use util::{ctx, some};
 
+use crate::checks::STAFF_CHECK;
+use crate::i18n;
 use crate::util::arg;
 use crate::util::db::{self, TargetId};
 use crate::util::discord::{MinimalLB, MinimalMembers};
-use crate::{arg, cmd_bail, data, finish, flag, send_embed, t};
+use crate::util::waitlist::Slots;
+use crate::{arg, cmd_bail, data, finish, flag, send_embed, t, tr};
+
+/// Amount of days of the guild's global rank history shown in `guildinfo`
+const RANK_HISTORY_DAYS: i64 = 14;
 
 #[command("profile")]
 #[bucket("mojang")]
-#[only_in(guild)]
 #[usage("[target]")]
 #[example("m:Pucaet")]
 #[example("d:Pucaet")]
@@ -30,10 +46,14 @@ use crate::{arg, cmd_bail, data, finish, flag, send_embed, t};
 /// If `target` is not specified, then the discord user who called the command is used.
 ///
 /// > **How do I specify different targets**
-/// - __Discord user__: "d:<username>", ex: "d:Pucaet" or "d:Pucaet#9528"
+/// - __Discord user__: "d:<username>", ex: "d:pucaet" or the legacy "d:Pucaet#9528"
 /// - __Mc account__: "m:<ign>", ex: "m:SephDark18"
+///
+/// In DMs, only mc accounts and user pings can be used as `target`.
 async fn display_profile(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
-    let guild = some!(msg.guild(&ctx), cmd_bail!("Failed to get the associated guild"));
+    let lc = i18n::locale(ctx, msg).await;
+    let fmt = i18n::stat_format(ctx, msg).await;
+    let guild = msg.guild(&ctx);
     let (db, client) = data!(ctx, "db", "reqwest");
 
     let target = {
@@ -41,7 +61,7 @@ async fn display_profile(ctx: &Context, msg: &Message, args: Args) -> CommandRes
         if arg.is_empty() {
             TargetId::Discord(msg.author.id)
         } else {
-            t!(db::parse_user_target(ctx, msg, &db, &client, &guild, args.rest()).await)
+            t!(db::parse_user_target(ctx, msg, &db, &client, guild.as_ref(), args.rest()).await)
         }
     };
     let profiles = {
@@ -56,54 +76,266 @@ async fn display_profile(ctx: &Context, msg: &Message, args: Args) -> CommandRes
     };
 
     if profiles.is_none() {
-        finish!(ctx, msg, "No profiles found");
+        finish!(ctx, msg, tr!(lc, NoProfiles));
     }
 
-    let names = msgtool::profile::get_names(&ctx.cache, &profiles).await;
+    let embed = profile_embed(ctx, &db, &profiles, lc, &fmt).await?;
+    ctx!(msg.channel_id.send_message(&ctx, |m| m.set_embed(embed)).await, "Failed to send embed")?;
 
-    send_embed!(ctx, msg, |e| {
-        e.author(|a| a.name(names.1)).title(names.0);
+    Ok(())
+}
 
-        if let Some(discord) = &profiles.discord {
-            if let Some(user) = discord.id.to_user(&ctx.cache) {
-                if let Some(url) = user.avatar_url() {
-                    e.thumbnail(url);
-                }
+/// Make the embed that displays `profiles`, which is shared by the `profile` prefix and slash
+/// commands
+pub(crate) async fn profile_embed(
+    ctx: &Context, db: &RwLock<DB>, profiles: &Profiles, lc: Locale, fmt: &StatFormat,
+) -> anyhow::Result<CreateEmbed> {
+    let names = msgtool::profile::get_names(&ctx.cache, profiles).await;
+    let last_message = match (&profiles.member, &profiles.discord) {
+        (Some(member), Some(_)) => {
+            let db = db.read().await;
+            message_log::last_message_at(&mut db.exe(), member.id).await?
+        }
+        _ => None,
+    };
+    let goals = match &profiles.member {
+        Some(member) => {
+            let db = db.read().await;
+            ctx!(member.id.goals(&mut db.exe()).await)?
+        }
+        None => Vec::new(),
+    };
+
+    let mut e = CreateEmbed::default();
+    e.author(|a| a.name(names.1)).title(names.0);
+
+    if let Some(discord) = &profiles.discord {
+        if let Some(user) = discord.id.to_user(&ctx.cache) {
+            if let Some(url) = user.avatar_url() {
+                e.thumbnail(url);
             }
         }
+    }
+
+    for (name, value) in msgtool::profile::format_guild_stat_fields(&profiles.guild, fmt) {
+        e.field(name, value, true);
+    }
+    for (name, value) in msgtool::profile::format_wynn_stat_fields(&profiles.wynn, fmt) {
+        e.field(name, value, true);
+    }
+    for (name, value) in msgtool::profile::format_discord_stat_fields(&profiles.discord, fmt) {
+        e.field(name, value, true);
+    }
+    if let Some(time) = last_message {
+        e.field("Last Message", format!("<t:{}:R>", time), true);
+    }
+    if !goals.is_empty() {
+        let goals = goals
+            .iter()
+            .map(|goal| {
+                format!("`{}` {}", goal.stat.table_name(), msgtool::profile::format_goal_progress(goal, fmt))
+            })
+            .collect::<Vec<_>>();
+        e.field("Weekly Goals", goals.join("\n"), false);
+    }
+
+    if profiles.member.is_none() {
+        e.footer(|f| f.text(tr!(lc, UnlinkedProfile)));
+    }
+
+    Ok(e)
+}
+
+#[command("compare")]
+#[bucket("mojang")]
+#[usage("<target-a> <target-b>")]
+#[example("m:Pucaet m:SephDark18")]
+#[example("d:pucaet d:sephdark18")]
+/// Compare the stats of two members side by side, with the difference between them and who leads
+/// in each stat.
+/// Only the stats both of them have are compared.
+///
+/// See `profile` for how to specify the targets.
+async fn compare_profiles(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let lc = i18n::locale(ctx, msg).await;
+    let fmt = i18n::stat_format(ctx, msg).await;
+    let guild = msg.guild(ctx);
+    let (db, client) = data!(ctx, "db", "reqwest");
 
-        for (name, value) in msgtool::profile::format_guild_stat_fields(&profiles.guild) {
+    let (a, b) = arg!(ctx, msg, args, "target-a", "target-b");
+    let a = t!(db::parse_user_target(ctx, msg, &db, &client, guild.as_ref(), &a).await);
+    let b = t!(db::parse_user_target(ctx, msg, &db, &client, guild.as_ref(), &b).await);
+    let (a, b) = {
+        let db = db.read().await;
+        (target_profiles(&db, a).await?, target_profiles(&db, b).await?)
+    };
+    if a.is_none() || b.is_none() {
+        finish!(ctx, msg, tr!(lc, NoProfiles));
+    }
+
+    let (a_name, _) = msgtool::profile::get_names(&ctx.cache, &a).await;
+    let (b_name, _) = msgtool::profile::get_names(&ctx.cache, &b).await;
+    let fields = msgtool::profile::format_stat_comparison(&a, &b, (&a_name, &b_name), &fmt);
+    if fields.is_empty() {
+        finish!(ctx, msg, tr!(lc, NoCommonStats));
+    }
+
+    send_embed!(ctx, msg, |e| {
+        e.title(format!("{} vs {}", a_name, b_name));
+        for (name, value) in fields {
             e.field(name, value, true);
         }
-        for (name, value) in msgtool::profile::format_wynn_stat_fields(&profiles.wynn) {
-            e.field(name, value, true);
+        e
+    });
+
+    Ok(())
+}
+
+/// Get the profiles of a target
+async fn target_profiles(db: &DB, target: TargetId) -> anyhow::Result<Profiles> {
+    Ok(match target {
+        TargetId::Discord(id) => Profiles::from_discord(db, DiscordId::try_from(id.0)?).await,
+        TargetId::Wynn(id) => Profiles::from_mc(db, &id).await,
+    })
+}
+
+#[command("card")]
+#[bucket("mojang")]
+#[usage("[target]")]
+#[example("m:Pucaet")]
+#[example("d:pucaet")]
+/// Display the profile card of `target`, an image with their avatar, rank and key stats.
+/// If `target` is not specified, then the discord user who called the command is used.
+///
+/// > **How do I specify different targets**
+/// - __Discord user__: "d:<username>", ex: "d:pucaet" or the legacy "d:Pucaet#9528"
+/// - __Mc account__: "m:<ign>", ex: "m:SephDark18"
+///
+/// In DMs, only mc accounts and user pings can be used as `target`.
+async fn display_card(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let lc = i18n::locale(ctx, msg).await;
+    let fmt = i18n::stat_format(ctx, msg).await;
+    let guild = msg.guild(ctx);
+    let (db, client, config) = data!(ctx, "db", "reqwest", "config");
+
+    let target = {
+        let arg = args.rest();
+        if arg.is_empty() {
+            TargetId::Discord(msg.author.id)
+        } else {
+            t!(db::parse_user_target(ctx, msg, &db, &client, guild.as_ref(), args.rest()).await)
         }
-        for (name, value) in msgtool::profile::format_discord_stat_fields(&profiles.discord) {
-            e.field(name, value, true);
+    };
+    let profiles = {
+        let db = db.read().await;
+        match target {
+            TargetId::Discord(id) => {
+                let id = DiscordId::try_from(id.0)?;
+                Profiles::from_discord(&db, id).await
+            }
+            TargetId::Wynn(id) => Profiles::from_mc(&db, &id).await,
         }
+    };
 
-        if profiles.member.is_none() {
-            e.footer(|f| f.text("This is an unlinked profile"));
+    if profiles.is_none() {
+        finish!(ctx, msg, tr!(lc, NoProfiles));
+    }
+
+    let (title, subtitle) = msgtool::profile::get_names(&ctx.cache, &profiles).await;
+    let avatar = match profiles.discord.as_ref().and_then(|discord| discord.id.to_user(&ctx.cache)) {
+        Some(user) => match fetch_avatar(&client, &user).await {
+            Ok(avatar) => Some(avatar),
+            Err(why) => {
+                warn!("Failed to get avatar of {}: {:#}", user.id, why);
+                None
+            }
+        },
+        None => None,
+    };
+
+    let mut stats = Vec::new();
+    // The guild rank is shown by the emblem instead
+    for (name, value) in msgtool::profile::format_guild_stat_fields(&profiles.guild, &fmt).into_iter().skip(1) {
+        stats.push((name.to_string(), value));
+    }
+    for (name, value) in msgtool::profile::format_wynn_stat_fields(&profiles.wynn, &fmt) {
+        stats.push((name.to_string(), value));
+    }
+    for (name, value) in msgtool::profile::format_discord_stat_fields(&profiles.discord, &fmt) {
+        stats.push((name.to_string(), value));
+    }
+
+    let mut bars = Vec::new();
+    if let Some(guild) = &profiles.guild {
+        let requirement = {
+            let config = config.read().await;
+            config.xp_requirements.get(&guild.rank.to_string()).copied()
+        };
+        if let Some(requirement) = requirement {
+            bars.push(ProgressBar {
+                label: "Weekly XP Requirement".to_string(),
+                value: guild.xp_week,
+                max: requirement,
+                text: format!(
+                    "{} / {}",
+                    fmt.num(guild.xp_week, false),
+                    fmt.num(requirement, false)
+                ),
+            });
         }
+    }
 
-        e
-    });
+    let card = Card {
+        avatar,
+        title,
+        subtitle,
+        emblem: profiles.guild.as_ref().map(|guild| Emblem::guild_rank(guild.rank)),
+        stats,
+        bars,
+    };
+    let image =
+        AttachmentType::Bytes { data: Cow::Owned(render_card(&card)), filename: "card.png".to_string() };
+    ctx!(msg.channel_id.send_files(&ctx, [image], |m| m.reference_message(msg)).await)?;
 
     Ok(())
 }
 
+/// Get the avatar of a discord user as a PNG, and decode it
+async fn fetch_avatar(client: &reqwest::Client, user: &User) -> anyhow::Result<Avatar> {
+    let url = match &user.avatar {
+        Some(hash) => format!("https://cdn.discordapp.com/avatars/{}/{}.png?size=128", user.id, hash),
+        None => user.default_avatar_url(),
+    };
+    let data = client
+        .get(url)
+        .send()
+        .await
+        .context("Failed to request avatar")?
+        .error_for_status()
+        .context("Failed to get avatar")?
+        .bytes()
+        .await
+        .context("Failed to read avatar")?;
+    Avatar::from_png(&data)
+}
+
 #[command("members")]
-#[usage("[filters] [minimal]")]
+#[usage("[filters] [minimal | image] [--totals]")]
 #[example("")]
 #[example("minimal")]
 #[example("Chief")]
 #[example("guild >weekly_voice:1h")]
 #[example("<Pilot xp")]
 #[example(">Strategist <online:1w3d >xp:12m minimal")]
+#[example("guild image")]
+#[example("in_guild --totals")]
 /// List members with optional filters.
 ///
 /// If you use this command with "minimal" as an argument, then the table is displayed without any
 /// styling. Useful if you are viewing it on a small screen.
+/// With "image" as an argument, the table is sent as images instead, which displays correctly on
+/// all screen sizes.
+/// With "--totals" as an argument, a row with the amount of listed members is added to the end.
 ///
 /// > **"filters" can be any numbers of the following values separated by space**
 /// `full`, `partial`, `guild`, `discord`, `wynn` (member type),
@@ -115,8 +347,9 @@ async fn display_profile(ctx: &Context, msg: &Message, args: Args) -> CommandRes
 /// or `<Cosmonaut` to filter out all member ranks above cosmonaut.
 ///
 /// > **"filters" can also contains stat filters**
-/// Following stats can be filtered: `message`, `weekly_message`, `voice`, `weekly_voice`, `online`,
-/// `weekly_online`, `avg_online`, `xp`, `weekly_xp`.
+/// Following stats can be filtered: `message`, `weekly_message`, `voice`, `weekly_voice`, `stream`,
+/// `weekly_stream`, `reactions_given`, `reactions_received`, `online`, `weekly_online`,
+/// `avg_online`, `xp`, `weekly_xp`.
 ///
 /// With just the stat name, it filters out anyone with that stat as 0. Ex `online` filters out
 /// anyone with no online time.
@@ -128,51 +361,80 @@ async fn display_profile(ctx: &Context, msg: &Message, args: Args) -> CommandRes
 /// `<weekly_voice:5m` filters out anyone with weekly voice time greater than 5 minutes. (Note that
 /// the stat value has to be specified for it to work)
 ///
+/// A range of stat value can be specified with `..`, ex: `xp:1m..5m` filters out anyone whose xp
+/// is below 1 million or above 5 millions.
+///
+/// A stat can also be compared to another stat with `>`, `<` or `=`, ex: `voice>online` filters
+/// out anyone whose voice time isn't greater than their online time.
+/// `avg(stat)` is the average of a stat across all members, ex: `weekly_xp>avg(weekly_xp)` filters
+/// out anyone whose weekly xp isn't above average.
+///
 /// > **How to specify stat value**
 /// For stats that is just a plain number (xp and message), you can just specify a number (`1000`).
 /// You can also write `5,000,000` as `5m`, or `10,000,000,000` as `10b`.
 /// Only whole integer is allows, and you can use commas to section up the number (`10,000,000`).
 ///
-/// For stats that is a duration of time (voice and online), it can be specified in the format of
-/// `(whole integer)(time unit)`, ex: `10h` is 10 hours.
+/// For stats that is a duration of time (voice, stream and online), it can be specified in the format of
+/// `(number)(time unit)`, ex: `10h` is 10 hours, and `1.5h` is 1 hour and 30 minutes.
 /// Following time units are allows: `s` (second), `m` (minute), `h` (hour), `d` (day), and `w`
-/// (week).
+/// (week). They can also be written out, ex: `90min` or `2jours`.
 /// Multiple expressions can be chained together, ex: `1w5h20m` is 1 week 5 hours and 20 minutes.
+/// ISO-8601 durations are also accepted, ex: `PT2H30M` is 2 hours and 30 minutes.
 async fn list_member(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let lc = i18n::locale(ctx, msg).await;
+    let fmt = i18n::stat_format(ctx, msg).await;
     let filters = arg::any::<Filter>(&mut args);
-    let is_minimal = flag!(ctx, msg, args, "minimal");
+    let (is_minimal, is_image, is_totals) = flag!(ctx, msg, args, "minimal", "image", "--totals");
 
     let db = data!(ctx, "db");
 
-    let table = {
+    let mut table = {
         let db = db.read().await;
-        ctx!(memberdb::table::list_members(&ctx.cache, &db, &filters).await, "Failed to get members list")?
+        ctx!(memberdb::table::list_members(&ctx.cache, &fmt, &db, &filters).await, "Failed to get members list")?
     };
     if table.is_empty() {
-        finish!(ctx, msg, "Found 0 member");
+        finish!(ctx, msg, tr!(lc, NoMembers));
+    }
+    if is_totals {
+        let db = db.read().await;
+        table.push(ctx!(
+            memberdb::table::list_members_summary(&ctx.cache, &fmt, &db, &filters).await,
+            "Failed to get members list summary"
+        )?);
     }
 
     let header = vec!["IGN".to_string(), "DISCORD".to_string(), "RANK".to_string()];
-    crate::display_table_pages!(ctx, &msg.channel_id, table, header, 10, is_minimal, MinimalMembers);
+    crate::display_table_pages!(ctx, &msg.channel_id, table, header, 10, is_minimal, is_image, MinimalMembers);
 
     Ok(())
 }
 
 #[command("lb")]
-#[usage("<stat> [filters] [minimal]")]
+#[usage("<stat> [filters] [minimal | image] [--totals]")]
 #[example("weekly_xp")]
 #[example("xp minimal")]
 #[example("message full")]
 #[example("weekly_voice >Pilot <online:1w")]
 #[example("online Recruiter >xp:10,000 voice:1d5h minimal")]
+#[example("voice channel:#war-voice")]
+#[example("weekly_xp image")]
+#[example("weekly_xp in_guild --totals")]
 /// Display leaderboard on specified statistic with optional filters.
 ///
 /// If you use this command with "minimal" as an argument, then the leaderboard is displayed without
 /// any styling. Useful if you are viewing it on a small screen.
+/// With "image" as an argument, the leaderboard is sent as images instead, which displays
+/// correctly on all screen sizes.
+/// With "--totals" as an argument, a row with the amount of listed members and the total of the
+/// stat is added to the end.
 ///
 /// > **"stat" can be following values:**
-/// `message`, `weekly_message`, `voice`, `weekly_voice`, `online`, `weekly_online`, `avg_online`,
-/// `xp`, `weekly_xp`.
+/// `message`, `weekly_message`, `voice`, `weekly_voice`, `stream`, `weekly_stream`,
+/// `reactions_given`, `reactions_received`, `online`, `weekly_online`, `avg_online`, `xp`,
+/// `weekly_xp`.
+///
+/// The `voice` stat can be followed by `channel:<channel>` to only count voice time spent in that
+/// voice channel, where `<channel>` is either a channel ping or the name of the channel.
 ///
 /// > **"filters" can be any numbers of the following values separated by space**
 /// `full`, `partial`, `guild`, `discord`, `wynn` (member type),
@@ -194,42 +456,90 @@ async fn list_member(ctx: &Context, msg: &Message, mut args: Args) -> CommandRes
 /// `<weekly_voice:5m` filters out anyone with weekly voice time greater than 5 minutes. (Note that
 /// the stat value has to be specified for it to work)
 ///
+/// A range of stat value can be specified with `..`, ex: `xp:1m..5m` filters out anyone whose xp
+/// is below 1 million or above 5 millions.
+///
+/// A stat can also be compared to another stat with `>`, `<` or `=`, ex: `voice>online` filters
+/// out anyone whose voice time isn't greater than their online time.
+/// `avg(stat)` is the average of a stat across all members, ex: `weekly_xp>avg(weekly_xp)` filters
+/// out anyone whose weekly xp isn't above average.
+///
 /// > **How to specify stat value**
 /// For stats that is just a plain number (xp and message), you can just specify a number (`1000`).
 /// You can also write `5,000,000` as `5m`, or `10,000,000,000` as `10b`.
 /// Only whole integer is allows, and you can use commas to section up the number (`10,000,000`).
 ///
-/// For stats that is a duration of time (voice and online), it can be specified in the format of
-/// `(whole integer)(time unit)`, ex: `10h` is 10 hours.
+/// For stats that is a duration of time (voice, stream and online), it can be specified in the format of
+/// `(number)(time unit)`, ex: `10h` is 10 hours, and `1.5h` is 1 hour and 30 minutes.
 /// Following time units are allows: `s` (second), `m` (minute), `h` (hour), `d` (day), and `w`
-/// (week).
+/// (week). They can also be written out, ex: `90min` or `2jours`.
 /// Multiple expressions can be chained together, ex: `1w5h20m` is 1 week 5 hours and 20 minutes.
+/// ISO-8601 durations are also accepted, ex: `PT2H30M` is 2 hours and 30 minutes.
 async fn stat_leaderboard(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let lc = i18n::locale(ctx, msg).await;
+    let fmt = i18n::stat_format(ctx, msg).await;
     let stat = arg!(ctx, msg, args, "stat": Stat);
+    let channel = match args.current().and_then(|arg| arg.strip_prefix("channel:")) {
+        Some(channel) => {
+            if stat != Stat::Voice {
+                finish!(ctx, msg, tr!(lc, ChannelVoiceOnly));
+            }
+            let guild = some!(msg.guild(&ctx), cmd_bail!("Failed to get message's guild"));
+            let channel_id = match DiscordObject::from_ping(ctx, &guild, channel).await {
+                Ok(DiscordObject::Channel(channel)) => channel.id(),
+                _ => some!(
+                    util::discord::get_channel_named(&guild, channel.trim_start_matches('#')),
+                    finish!(ctx, msg, tr!(lc, ChannelNotFound))
+                )
+                .id(),
+            };
+            args.advance();
+            Some(i64::try_from(channel_id.0)?)
+        }
+        None => None,
+    };
     let filters = arg::any::<Filter>(&mut args);
-    let is_minimal = flag!(ctx, msg, args, "minimal");
+    let (is_minimal, is_image, is_totals) = flag!(ctx, msg, args, "minimal", "image", "--totals");
 
     let db = data!(ctx, "db");
 
-    let (table, header) = {
+    let (mut table, header) = {
         let db = db.read().await;
-        ctx!(
-            memberdb::table::stat_leaderboard(&ctx.cache, &db, &stat, &filters).await,
-            "Failed to get stat leaderboard"
-        )?
+        match channel {
+            Some(channel) => ctx!(
+                memberdb::table::channel_voice_leaderboard(&ctx.cache, &fmt, &db, channel, &filters).await,
+                "Failed to get channel voice leaderboard"
+            )?,
+            None => ctx!(
+                memberdb::table::stat_leaderboard(&ctx.cache, &fmt, &db, &stat, &filters).await,
+                "Failed to get stat leaderboard"
+            )?,
+        }
     };
     if table.is_empty() {
-        finish!(ctx, msg, "leaderboard empty");
+        finish!(ctx, msg, tr!(lc, EmptyLeaderboard));
+    }
+    if is_totals {
+        let db = db.read().await;
+        table.push(match channel {
+            Some(channel) => ctx!(
+                memberdb::table::channel_voice_leaderboard_summary(&ctx.cache, &fmt, &db, channel, &filters).await,
+                "Failed to get channel voice leaderboard summary"
+            )?,
+            None => ctx!(
+                memberdb::table::stat_leaderboard_summary(&ctx.cache, &fmt, &db, &stat, &filters).await,
+                "Failed to get stat leaderboard summary"
+            )?,
+        });
     }
 
-    crate::display_table_pages!(ctx, &msg.channel_id, table, header, 10, is_minimal, MinimalLB);
+    crate::display_table_pages!(ctx, &msg.channel_id, table, header, 10, is_minimal, is_image, MinimalLB);
 
     Ok(())
 }
 
 #[command("member")]
 #[bucket("mojang")]
-#[only_in(guild)]
 #[usage("<target>")]
 #[example("m:Pucaet")]
 #[example("d:Pucaet")]
@@ -239,13 +549,15 @@ async fn stat_leaderboard(ctx: &Context, msg: &Message, mut args: Args) -> Comma
 /// If what you want are statistics, use the command `profile` instead.
 ///
 /// > **How do I specify different targets**
-/// - __Discord user__: "d:<username>", ex: "d:Pucaet" or "d:Pucaet#9528"
+/// - __Discord user__: "d:<username>", ex: "d:pucaet" or the legacy "d:Pucaet#9528"
 /// - __Mc account__: "m:<ign>", ex: "m:SephDark18"
+///
+/// In DMs, only mc accounts and user pings can be used as `target`.
 async fn display_member_info(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
-    let guild = some!(msg.guild(&ctx), cmd_bail!("Failed to get message's guild"));
+    let guild = msg.guild(&ctx);
     let (db, client) = data!(ctx, "db", "reqwest");
 
-    let mid = t!(db::parse_user_target_mid(ctx, msg, &db, &client, &guild, args.rest()).await);
+    let mid = t!(db::parse_user_target_mid(ctx, msg, &db, &client, guild.as_ref(), args.rest()).await);
     let member = {
         let db = db.read().await;
         some!(
@@ -268,24 +580,261 @@ async fn display_member_info(ctx: &Context, msg: &Message, args: Args) -> Comman
     }
     if let Some(id) = member.discord {
         let user = some!(id.to_user(&ctx.cache), cmd_bail!("Failed to get discord user"));
-        write!(content, "\n**Discord** {}#{} `{}`", user.name, user.discriminator, id)?;
+        let tag = util::discord::user_tag(&user.name, user.discriminator);
+        let nick = msg.guild_id.and_then(|guild_id| {
+            ctx.cache.member_field(guild_id, user.id, |member| member.nick.clone()).flatten()
+        });
+        match nick {
+            Some(nick) => write!(content, "\n**Discord** {} ({}) `{}`", nick, tag, id)?,
+            None => write!(content, "\n**Discord** {} `{}`", tag, id)?,
+        }
+
+        let inviter = {
+            let db = db.read().await;
+            ctx!(id.inviter(&mut db.exe()).await, "Failed to get discord_invite.inviter")?
+        };
+        if let Some(inviter) = inviter {
+            match inviter.to_user(&ctx.cache) {
+                Some(user) => {
+                    let tag = util::discord::user_tag(&user.name, user.discriminator);
+                    write!(content, "\n**Invited by** {} `{}`", tag, inviter)?
+                }
+                None => write!(content, "\n**Invited by** `{}`", inviter)?,
+            }
+        }
+    }
+
+    finish!(ctx, msg, content)
+}
+
+#[command("rankhistory")]
+#[usage("<target>")]
+#[example("m:Pucaet")]
+#[example("d:Pucaet#9528")]
+/// Display the guild rank changes of member specified by `target`, and how long they have been at
+/// their current guild rank.
+///
+/// > **How do I specify different targets**
+/// - __Discord user__: "d:<username>", ex: "d:pucaet" or the legacy "d:Pucaet#9528"
+/// - __Mc account__: "m:<ign>", ex: "m:SephDark18"
+///
+/// In DMs, only mc accounts and user pings can be used as `target`.
+async fn display_rank_history(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let guild = msg.guild(ctx);
+    let (db, client) = data!(ctx, "db", "reqwest");
+
+    let mid = t!(db::parse_user_target_mid(ctx, msg, &db, &client, guild.as_ref(), args.rest()).await);
+    let db = db.read().await;
+    let mcid = match ctx!(mid.links(&mut db.exe()).await)?.1 {
+        Some(mcid) => mcid,
+        None => finish!(ctx, msg, "Member doesn't have a linked mc account"),
+    };
+    let rank = match mcid.rank(&mut db.exe()).await {
+        Ok(rank) => rank,
+        Err(_) => finish!(ctx, msg, "Member was never in the guild"),
+    };
+    let ign = ctx!(mcid.ign(&mut db.exe()).await, "Failed to get wynn.ign")?;
+    let history = ctx!(rank_history::rank_history(&mut db.exe(), &mcid).await)?;
+
+    let mut content = format!("**{}** is {}", ign, rank);
+    let since = match history.last() {
+        Some(change) => Some(change.time),
+        None => ctx!(mcid.get_guild(&mut db.exe()).await)?
+            .and_then(|profile| profile.joined)
+            .and_then(|joined| DateTime::parse_from_rfc3339(&joined).ok())
+            .map(|joined| joined.timestamp()),
+    };
+    if let Some(since) = since {
+        write!(content, " since <t:{}:d>", since)?;
+    }
+    if history.is_empty() {
+        content.push_str("\nNo guild rank changes are recorded");
+    }
+    for change in history {
+        write!(content, "\n<t:{}:d> {} → {}", change.time, change.old, change.new)?;
+    }
+    finish!(ctx, msg, content)
+}
+
+#[command("rankstats")]
+#[checks(Staff)]
+#[usage("[months]")]
+#[example("")]
+#[example("12")]
+/// Display how fast guild members move between guild ranks over the last `months` months
+/// (default 6): the average time spent at each guild rank before it changed, and the amount of
+/// promotions and demotions of each month.
+///
+/// Only rank changes recorded by the bot are counted, so the time between joining the guild and
+/// the first rank change isn't included.
+async fn display_rank_stats(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let months = arg!(ctx, msg, args, ?"months": i64).unwrap_or(6);
+    if months <= 0 {
+        finish!(ctx, msg, "The amount of months has to be positive");
     }
+    let since = Utc::now().timestamp() - months.saturating_mul(30 * 86400);
 
+    let db = data!(ctx, "db");
+    let (tenures, changes) = {
+        let db = db.read().await;
+        (
+            ctx!(rank_history::rank_tenures(&mut db.exe(), since).await)?,
+            ctx!(rank_history::monthly_rank_changes(&mut db.exe(), since).await)?,
+        )
+    };
+    if tenures.is_empty() && changes.is_empty() {
+        finish!(ctx, msg, "No guild rank changes are recorded in the last {} months", months);
+    }
+
+    let mut content = format!("**Average time at rank** (last {} months)", months);
+    if tenures.is_empty() {
+        content.push_str("\nNot enough rank changes are recorded");
+    }
+    for tenure in tenures {
+        write!(
+            content,
+            "\n{}: {:.1} days ({} stays)",
+            tenure.rank,
+            tenure.average as f64 / 86400.0,
+            tenure.samples
+        )?;
+    }
+    content.push_str("\n\n**Rank changes per month**");
+    for month in changes {
+        write!(
+            content,
+            "\n`{}` {} promotions, {} demotions",
+            month.month, month.promotions, month.demotions
+        )?;
+    }
     finish!(ctx, msg, content)
 }
 
+#[command("recruiters")]
+#[usage("[minimal | image]")]
+#[example("")]
+#[example("minimal")]
+/// Display leaderboard of who invited the most members to the discord server.
+///
+/// Only invited users that are currently members are counted, and invites used before the bot
+/// started tracking them aren't known.
+///
+/// If you use this command with "minimal" as an argument, then the leaderboard is displayed without
+/// any styling. Useful if you are viewing it on a small screen.
+/// With "image" as an argument, the leaderboard is sent as images instead, which displays
+/// correctly on all screen sizes.
+async fn recruiter_leaderboard(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let lc = i18n::locale(ctx, msg).await;
+    let (is_minimal, is_image) = flag!(ctx, msg, args, "minimal", "image");
+
+    let db = data!(ctx, "db");
+    let (table, header) = {
+        let db = db.read().await;
+        ctx!(
+            memberdb::table::recruiter_leaderboard(&ctx.cache, &db).await,
+            "Failed to get recruiter leaderboard"
+        )?
+    };
+    if table.is_empty() {
+        finish!(ctx, msg, tr!(lc, EmptyLeaderboard));
+    }
+
+    crate::display_table_pages!(ctx, &msg.channel_id, table, header, 10, is_minimal, is_image, MinimalLB);
+
+    Ok(())
+}
+
+#[command("guildinfo")]
+#[usage("[weekly] [minimal | image]")]
+#[example("")]
+#[example("weekly")]
+#[example("weekly minimal")]
+/// Display the number of members and their total message, voice, online and xp, split by member
+/// type, along with the totals across all members.
+/// The guild's slots, which are its member count and member cap, and the guild's rank on the
+/// global guild leaderboard and its history are shown after the table.
+///
+/// With "weekly" as an argument, the weekly stats are totaled instead.
+///
+/// If you use this command with "minimal" as an argument, then the table is displayed without
+/// any styling. Useful if you are viewing it on a small screen.
+/// With "image" as an argument, the table is sent as images instead, which displays
+/// correctly on all screen sizes.
+async fn display_guild_info(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let (is_weekly, is_minimal, is_image) = flag!(ctx, msg, args, "weekly", "minimal", "image");
+    let fmt = i18n::stat_format(ctx, msg).await;
+
+    let (db, config, cache) = data!(ctx, "db", "config", "cache");
+    let (table, header) = {
+        let db = db.read().await;
+        ctx!(
+            memberdb::table::member_type_summary(&ctx.cache, &fmt, &db, is_weekly).await,
+            "Failed to get member type summary"
+        )?
+    };
+
+    crate::display_table_pages!(ctx, &msg.channel_id, table, header, 10, is_minimal, is_image, MinimalLB);
+
+    let slots = Slots::get(&cache, &config).await;
+    let waitlisted = {
+        let db = db.read().await;
+        ctx!(waitlist::waitlist(&mut db.exe()).await)?.len()
+    };
+    let mut content = format!("Guild slots: {}", slots.describe());
+    if waitlisted > 0 {
+        write!(content, ", {} recruits waitlisted", waitlisted)?;
+    }
+
+    let now = Utc::now().timestamp();
+    let samples = {
+        let db = db.read().await;
+        ctx!(global_rank::rank_samples(&db, now - RANK_HISTORY_DAYS * 86400).await)?
+    };
+    if let Some(last) = samples.last() {
+        write!(content, "\nGlobal rank: **#{}**", last.rank)?;
+        let week = samples.iter().find(|sample| sample.time >= now - 7 * 86400).unwrap_or(last);
+        let climbed = week.rank - last.rank;
+        if climbed != 0 {
+            let arrow = if climbed > 0 { "▲" } else { "▼" };
+            write!(content, " ({}{} this week)", arrow, climbed.abs())?;
+        }
+        let ranks = global_rank::daily_ranks(&samples, now, RANK_HISTORY_DAYS);
+        if ranks.len() > 1 {
+            // Ranks are negated so a better rank is a taller bar
+            let values = ranks.iter().map(|rank| -(*rank as f64)).collect::<Vec<_>>();
+            write!(
+                content,
+                "\nRank history: `{}` #{} → #{}",
+                util::string::sparkline(&values),
+                ranks[0],
+                ranks[ranks.len() - 1]
+            )?;
+        }
+    }
+    msg.channel_id.say(&ctx, content).await?;
+
+    Ok(())
+}
+
 #[command("table")]
-#[usage("<columns> | [filters] | [sorts] [minimal]")]
+#[usage("<columns> | [filters] | [sorts] [group by <column>] [minimal | image] [--totals]")]
 #[example("weekly_xp")]
 #[example("xp minimal")]
 #[example("name message | full")]
 #[example("weekly_voice | >Pilot <online:1w")]
 #[example("ign guild_rank || ^online")]
 #[example("name xp rank | >xp:10,000 voice:1d5h | rank ^xp minimal")]
+#[example("name xp | guild image")]
+#[example("name xp weekly_online avg_online | in_guild --totals")]
+#[example("count sum(xp) avg(online) | in_guild group by guild_rank")]
 /// Display a custom leaderboard.
 ///
 /// If you use this command with "minimal" as an argument, then the leaderboard is displayed without
 /// any styling. Useful if you are viewing it on a small screen.
+/// With "image" as an argument, the leaderboard is sent as images instead, which displays
+/// correctly on all screen sizes.
+/// With "--totals" as an argument, a row summarizing the stat columns is added to the end, which
+/// has the total of each stat, or the average for `avg_online` and `rank_time`.
 ///
 /// This command has 3 separate argument lists separated by `|`, in order they are:
 /// - __columns__ List of columns in the leaderboard
@@ -297,10 +846,23 @@ async fn display_member_info(ctx: &Context, msg: &Message, args: Args) -> Comman
 /// If you have `sorts` and `filters` is empty, `|` still needs to be included, ex: "table name xp || ^xp".
 ///
 /// > **"columns" can be any numbers of the following values separated by space**
-/// `message`, `weekly_message`, `voice`, `weekly_voice`, `online`, `weekly_online`, `xp`,
-/// `weekly_xp` (stats)
-/// `mc_id`, `in_guild` (status on if member is in in-game guild), `ign`, `guild_rank`, `id`, `rank`,
-/// `type`, `name` (member ign or discord username if ign not exist)
+/// `message`, `weekly_message`, `voice`, `weekly_voice`, `stream`, `weekly_stream`,
+/// `reactions_given`, `reactions_received` (reactions in tracked channels, excluding reactions to
+/// your own messages), `online`, `weekly_online`, `xp`, `weekly_xp` (stats)
+/// `last_message` (time of the member's last message in a tracked channel, in UTC)
+/// `mc_id`, `in_guild` (status on if member is in in-game guild), `ign`, `guild_rank`,
+/// `guild_joined` (date of joining the in-game guild), `wars`,
+/// `rank_time` (time since the last guild rank change, or since joining), `id`, `rank`, `type`,
+/// `name` (member ign or discord username if ign not exist)
+///
+/// > **Grouping**
+/// With `group by <column>` at the end, members with the same value in the column are grouped
+/// into a single row, ex: `group by guild_rank` gives a row for each guild rank.
+/// Rows of groups can only contain the grouped column, which is always included, and the
+/// following columns that are computed across the group:
+/// `count` (amount of members), `sum(<stat>)` (total of a stat), `avg(<stat>)` (average of a stat)
+/// These columns can also be used without grouping, which gives a single row computed across all
+/// members.
 ///
 /// > **"filters" can be any numbers of the following values separated by space**
 /// `full`, `partial`, `guild`, `discord`, `wynn` (member type),
@@ -322,16 +884,25 @@ async fn display_member_info(ctx: &Context, msg: &Message, args: Args) -> Comman
 /// `<weekly_voice:5m` filters out anyone with weekly voice time greater than 5 minutes. (Note that
 /// the stat value has to be specified for it to work)
 ///
+/// A range of stat value can be specified with `..`, ex: `xp:1m..5m` filters out anyone whose xp
+/// is below 1 million or above 5 millions.
+///
+/// A stat can also be compared to another stat with `>`, `<` or `=`, ex: `voice>online` filters
+/// out anyone whose voice time isn't greater than their online time.
+/// `avg(stat)` is the average of a stat across all members, ex: `weekly_xp>avg(weekly_xp)` filters
+/// out anyone whose weekly xp isn't above average.
+///
 /// > **How to specify stat value**
 /// For stats that is just a plain number (xp and message), you can just specify a number (`1000`).
 /// You can also write `5,000,000` as `5m`, or `10,000,000,000` as `10b`.
 /// Only whole integer is allows, and you can use commas to section up the number (`10,000,000`).
 ///
-/// For stats that is a duration of time (voice and online), it can be specified in the format of
-/// `(whole integer)(time unit)`, ex: `10h` is 10 hours.
+/// For stats that is a duration of time (voice, stream and online), it can be specified in the format of
+/// `(number)(time unit)`, ex: `10h` is 10 hours, and `1.5h` is 1 hour and 30 minutes.
 /// Following time units are allows: `s` (second), `m` (minute), `h` (hour), `d` (day), and `w`
-/// (week).
+/// (week). They can also be written out, ex: `90min` or `2jours`.
 /// Multiple expressions can be chained together, ex: `1w5h20m` is 1 week 5 hours and 20 minutes.
+/// ISO-8601 durations are also accepted, ex: `PT2H30M` is 2 hours and 30 minutes.
 ///
 /// > **"sorts" can be any number of column names separated by space**
 /// With just the column name, that column is ordered in descent order. If `^` is added to the
@@ -339,57 +910,108 @@ async fn display_member_info(ctx: &Context, msg: &Message, args: Args) -> Comman
 /// Sorts are applied in the order they are specified in.
 /// Note that the column `name` is special and can't be sorted.
 async fn display_table(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
-    let columns = arg::any::<Selectables>(&mut args);
+    let lc = i18n::locale(ctx, msg).await;
+    let fmt = i18n::stat_format(ctx, msg).await;
+    let mut columns = arg::any::<Selectables>(&mut args);
     arg::consume_raw(&mut args, "|");
     let filters = arg::any::<Filter>(&mut args);
     arg::consume_raw(&mut args, "|");
     let sorts = arg::any::<Sort>(&mut args);
-    let is_minimal = flag!(ctx, msg, args, "minimal");
+    let group = if arg::consume_raw(&mut args, "group") {
+        if !arg::consume_raw(&mut args, "by") {
+            finish!(ctx, msg, tr!(lc, GroupBySyntax));
+        }
+        Some(arg!(ctx, msg, args, "group column": Column))
+    } else {
+        None
+    };
+    let (is_minimal, is_image, is_totals) = flag!(ctx, msg, args, "minimal", "image", "--totals");
 
     if columns.is_empty() {
-        finish!(ctx, msg, "No columns specified");
+        finish!(ctx, msg, tr!(lc, NoColumns));
+    }
+    // Grouped rows only have values computed across the group, and the grouped column itself
+    if group.is_some() || columns.iter().any(|col| matches!(col, Selectables::Aggregate(_))) {
+        let is_grouped = |col: &Column| group.as_ref() == Some(col);
+        let is_valid_col = |col: &Selectables| match col {
+            Selectables::Aggregate(_) => true,
+            Selectables::Column(col) => is_grouped(col),
+            Selectables::MemberName(_) => false,
+        };
+        let is_valid_sort = |sort: &Sort| match sort {
+            Sort::Asc(col) | Sort::Desc(col) => is_grouped(col),
+        };
+        if !columns.iter().all(is_valid_col) || !sorts.iter().all(is_valid_sort) {
+            finish!(ctx, msg, tr!(lc, GroupedColumns));
+        }
+        if let Some(group) = &group {
+            if !columns.iter().any(|col| matches!(col, Selectables::Column(col) if col == group)) {
+                columns.insert(0, Selectables::Column(group.clone()));
+            }
+        }
     }
-    let mut actions = Vec::with_capacity(filters.len() + sorts.len());
+    let mut actions = Vec::with_capacity(filters.len() + sorts.len() + 1);
     actions.append(&mut filters.into_iter().map(QueryMod::Filter).collect());
     actions.append(&mut sorts.into_iter().map(QueryMod::Sort).collect());
+    if let Some(group) = group {
+        actions.push(QueryMod::GroupBy(GroupBy(group)));
+    }
 
     let db = data!(ctx, "db");
 
-    let (table, header) = {
+    let (mut table, header) = {
         let db = db.read().await;
         ctx!(
-            memberdb::table::make_table(&ctx.cache, &db, &columns, &actions).await,
+            memberdb::table::make_table(&ctx.cache, &fmt, &db, &columns, &actions).await,
             "Failed to get stat leaderboard"
         )?
     };
     if table.is_empty() {
-        finish!(ctx, msg, "leaderboard empty");
+        finish!(ctx, msg, tr!(lc, EmptyLeaderboard));
+    }
+    if is_totals {
+        let db = db.read().await;
+        table.push(ctx!(
+            memberdb::table::make_table_summary(&ctx.cache, &fmt, &db, &columns, &actions).await,
+            "Failed to get table summary"
+        )?);
     }
 
-    crate::display_table_pages!(ctx, &msg.channel_id, table, header, 10, is_minimal, MinimalLB);
+    crate::display_table_pages!(ctx, &msg.channel_id, table, header, 10, is_minimal, is_image, MinimalLB);
 
     Ok(())
 }
 
 #[macro_export]
-/// Display a table as paged message.
+/// Display a table as paged message, which can jump to the page containing a member.
 macro_rules! display_table_pages {
-    ($ctx:ident, $channel_id:expr, $data:ident, $header:ident, $page_len:literal, $is_minimal:ident, $minimal_wrap:ident) => {{
+    ($ctx:ident, $channel_id:expr, $data:ident, $header:ident, $page_len:literal, $is_minimal:ident, $is_image:ident, $minimal_wrap:ident) => {{
         let data = table::borrow_table(&$data);
         let header = table::borrow_row(&$header);
         let table_data = TableData::paginate(data, header, $page_len);
+        let index = table_data.iter().map(TableData::search_keys).collect::<Vec<_>>();
         if $is_minimal {
             let table_data = table_data
                 .into_iter()
                 .map(|data| $minimal_wrap(data.0))
                 .collect::<Vec<$minimal_wrap>>();
-            let mut pager = Pager::new(table_data);
+            let mut pager = Pager::new(table_data).with_index(index);
+            ctx!(
+                msgtool::interact::page(&$ctx, $channel_id, &mut pager, 120).await,
+                "Error when displaying leaderboard pages"
+            )?;
+        } else if $is_image {
+            let table_data = table_data
+                .into_iter()
+                .map(|data| TableImage(data.0))
+                .collect::<Vec<TableImage>>();
+            let mut pager = Pager::new(table_data).with_index(index);
             ctx!(
                 msgtool::interact::page(&$ctx, $channel_id, &mut pager, 120).await,
                 "Error when displaying leaderboard pages"
             )?;
         } else {
-            let mut pager = Pager::new(table_data);
+            let mut pager = Pager::new(table_data).with_index(index);
             ctx!(
                 msgtool::interact::page(&$ctx, $channel_id, &mut pager, 120).await,
                 "Error when displaying leaderboard pages"
@@ -1,15 +1,23 @@
 //! Commands that displays bot info
 use std::collections::HashSet;
 
+use anyhow::Context as AHContext;
 use chrono::offset::Utc;
 use serenity::client::bridge::gateway::ShardId;
 use serenity::framework::standard::macros::{command, help};
 use serenity::framework::standard::{help_commands, Args, CommandGroup, CommandResult, HelpOptions};
-use serenity::model::channel::Message;
+use serenity::model::channel::{Channel, ChannelType, Message};
 use serenity::model::id::UserId;
 use serenity::prelude::*;
 
-use crate::{data, finish};
+use memberdb::model::member::MemberId;
+use memberdb::model::wynn::McId;
+use msgtool::table;
+use util::{ctx, string};
+
+use crate::checks::STAFF_CHECK;
+use crate::data::MEMBER_DB_FILE;
+use crate::{data, finish, send_embed};
 
 #[command]
 /// ping
@@ -33,6 +41,67 @@ async fn ping(ctx: &Context, msg: &Message) -> CommandResult {
     finish!(ctx, msg, content);
 }
 
+#[command("botinfo")]
+/// Display the bot's version and uptime, along with how much it is tracking and caching.
+async fn bot_info(ctx: &Context, msg: &Message) -> CommandResult {
+    let (db, config, cache, start_time) = data!(ctx, "db", "config", "cache", "start");
+
+    let (members, linked, igns) = {
+        let db = db.read().await;
+        (
+            ctx!(MemberId::count(&mut db.exe()).await)?,
+            ctx!(MemberId::with_discord(&mut db.exe()).await)?.len(),
+            ctx!(McId::igns(&mut db.exe()).await)?.len(),
+        )
+    };
+    let guild_members = cache.members.read().await.as_ref().map_or(0, |members| members.len());
+    let online = cache.online.read().await.0.values().map(|igns| igns.len()).sum::<usize>();
+
+    let (discord_members, tracked_channels) = match msg.guild(ctx) {
+        Some(guild) => {
+            let config = config.read().await;
+            let tracked = guild
+                .channels
+                .values()
+                .filter(|channel| match channel {
+                    Channel::Guild(channel) => {
+                        channel.kind == ChannelType::Text && config.is_channel_tracked(&ctx.cache, channel)
+                    }
+                    _ => false,
+                })
+                .count();
+            (guild.member_count.to_string(), tracked.to_string())
+        }
+        None => ("-".to_string(), "-".to_string()),
+    };
+
+    // The write-ahead log holds changes that aren't yet merged into the database file
+    let db_size: u64 = [MEMBER_DB_FILE.to_string(), format!("{}-wal", MEMBER_DB_FILE)]
+        .iter()
+        .filter_map(|file| std::fs::metadata(file).ok())
+        .map(|metadata| metadata.len())
+        .sum();
+    let uptime = (Utc::now() - start_time).num_seconds();
+
+    send_embed!(ctx, msg, |e| {
+        e.title("Bot info")
+            .field("Version", env!("CARGO_PKG_VERSION"), true)
+            .field("Uptime", string::fmt_second(uptime), true)
+            .field("Database size", string::fmt_bytes(db_size), true)
+            .field("Members", members, true)
+            .field("Guild members", guild_members, true)
+            .field("Discord members", discord_members, true)
+            .field("Linked discord", linked, true)
+            .field("Tracked igns", igns, true)
+            .field("Tracked channels", tracked_channels, true)
+            .field("Cached guilds", ctx.cache.guild_count(), true)
+            .field("Cached channels", ctx.cache.guild_channel_count(), true)
+            .field("Cached users", ctx.cache.user_count(), true)
+            .field("Online players", online, true)
+    });
+    Ok(())
+}
+
 #[command("utc")]
 /// Display current utc time, which is used by the bot's internal timer.
 async fn utc_now(ctx: &Context, msg: &Message) -> CommandResult {
@@ -40,6 +109,98 @@ async fn utc_now(ctx: &Context, msg: &Message) -> CommandResult {
     finish!(ctx, msg, "{}", now.format("%Y %b %d (%a) %T UTC"));
 }
 
+#[command("nextreset")]
+/// Display when the next weekly reset happens, which resets the weekly stats.
+async fn next_reset(ctx: &Context, msg: &Message) -> CommandResult {
+    let config = data!(ctx, "config");
+    let reset = {
+        let config = config.read().await;
+        ctx!(config.weekly_reset.time())?
+    };
+    let next = reset.next_after(Utc::now()).timestamp();
+    finish!(ctx, msg, "The next weekly reset is at <t:{}:F> (<t:{}:R>)", next, next);
+}
+
+/// Max amount of query timings displayed by the `status` command
+const MAX_QUERY_TIMINGS: usize = 5;
+
+#[command("status")]
+#[checks(Staff)]
+/// Display the status of the bot's background tasks and why they last stopped, along with the
+/// connection stage and latency of each shard.
+/// The database queries that took the most time on average are also displayed, along with their
+/// timings in milliseconds, and the amount of API responses that failed to parse.
+async fn task_status(ctx: &Context, msg: &Message) -> CommandResult {
+    let (tasks, db, shard_manager) = data!(ctx, "tasks", "db", "shard");
+    let statuses = tasks.statuses();
+    let timings = {
+        let db = db.read().await;
+        db.query_stats().timings()
+    };
+
+    let mut rows =
+        vec![vec!["Task".to_string(), "State".to_string(), "Restarts".to_string(), "Panics".to_string()]];
+    let mut errors = String::new();
+    for (name, status) in &statuses {
+        rows.push(vec![
+            name.clone(),
+            status.state.to_string(),
+            status.restarts.to_string(),
+            status.panics.to_string(),
+        ]);
+        if let Some(why) = &status.last_error {
+            let why: String = why.chars().take(100).collect();
+            errors.push_str(&format!("\n**{}**: {}", name, why));
+        }
+    }
+    let table = table::format_table(&table::borrow_table(&rows), None);
+
+    let mut shard_rows = vec![vec!["Shard".to_string(), "Stage".to_string(), "Latency".to_string()]];
+    {
+        let manager = shard_manager.lock().await;
+        let runners = manager.runners.lock().await;
+        let mut shards: Vec<_> = runners.iter().collect();
+        shards.sort_by_key(|(id, _)| id.0);
+        for (id, runner) in shards {
+            let latency = match runner.latency {
+                Some(latency) => format!("{}ms", latency.as_millis()),
+                None => "-".to_string(),
+            };
+            shard_rows.push(vec![id.0.to_string(), runner.stage.to_string(), latency]);
+        }
+    }
+    let shard_table = table::format_table(&table::borrow_table(&shard_rows), None);
+
+    let mut query_rows = vec![vec![
+        "Query".to_string(),
+        "Count".to_string(),
+        "Avg".to_string(),
+        "Max".to_string(),
+        "Total".to_string(),
+    ]];
+    for (sql, timing) in timings.iter().take(MAX_QUERY_TIMINGS) {
+        query_rows.push(vec![
+            sql.chars().take(40).collect(),
+            timing.count.to_string(),
+            timing.avg().as_millis().to_string(),
+            timing.max.as_millis().to_string(),
+            timing.total.as_millis().to_string(),
+        ]);
+    }
+    let query_table = table::format_table(&table::borrow_table(&query_rows), None);
+    let parse_failures = wynn::diagnostics::parse_failures();
+
+    let mut content = format!("```{}```", table);
+    if !errors.is_empty() {
+        content.push_str(&format!("\nLast stopped:{}", errors));
+    }
+    content.push_str(&format!(
+        "\nShards:```{}```\nSlowest queries:```{}```\nAPI parse failures: {}",
+        shard_table, query_table, parse_failures
+    ));
+    finish!(ctx, msg, content);
+}
+
 #[help]
 #[individual_command_tip = "If you want more information about a specific command, \
 just pass the command as argument."]
@@ -1,17 +1,29 @@
 mod config;
+mod goal;
 mod member_manage;
 mod member_stat;
 mod meta;
 mod nick;
 mod owner;
+mod poll;
+mod reminder;
 mod staff_util;
+mod suggestion;
+mod ticket;
+mod waitlist;
 mod wynn;
 
 pub use crate::commands::config::*;
+pub use crate::commands::goal::*;
 pub use crate::commands::member_manage::*;
 pub use crate::commands::member_stat::*;
 pub use crate::commands::meta::*;
 pub use crate::commands::nick::*;
 pub use crate::commands::owner::*;
+pub use crate::commands::poll::*;
+pub use crate::commands::reminder::*;
 pub use crate::commands::staff_util::*;
+pub use crate::commands::suggestion::*;
+pub use crate::commands::ticket::*;
+pub use crate::commands::waitlist::*;
 pub use crate::commands::wynn::*;
@@ -8,7 +8,8 @@ use tracing::error;
 use memberdb::model::discord::DiscordId;
 use util::{ctx, some};
 
-use crate::{data, finish};
+use crate::util::sync_state::DiscordSyncState;
+use crate::{cmd_bail, data, finish};
 
 #[command("nick")]
 #[only_in(guild)]
@@ -24,15 +25,19 @@ async fn set_custom_nick(ctx: &Context, msg: &Message, args: Args) -> CommandRes
 
     let db = data!(ctx, "db");
 
-    let discord_member = ctx!(msg.member(&ctx).await, "Failed to get member who sent the message")?;
+    let guild = some!(msg.guild(ctx), cmd_bail!("Failed to get message's guild"));
+    let mut discord_member = ctx!(msg.member(&ctx).await, "Failed to get member who sent the message")?;
     let discord_id = DiscordId::try_from(discord_member.user.id.0)?;
     let mid = {
         let db = db.read().await;
         some!(ctx!(discord_id.mid(&mut db.exe()).await)?, finish!(ctx, msg, "You aren't a member"))
     };
 
-    let result =
-        crate::util::discord::fix_member_nick(&ctx.http, &db, mid, &discord_member, Some(custom_nick)).await;
+    let state = DiscordSyncState::of_member(&db, mid, &guild, &discord_member, Some(custom_nick)).await;
+    let result = match state {
+        Ok(state) => state.diff(&guild, &discord_member).apply_nick(&ctx.http, &mut discord_member).await,
+        Err(why) => Err(why),
+    };
 
     finish!(
         ctx,
@@ -1,17 +1,26 @@
 //! Dev util commands
+use std::borrow::Cow;
+use std::fmt::Write;
 use std::process::Command;
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
 
+use anyhow::Context as AHContext;
 use serenity::client::Context;
 use serenity::framework::standard::macros::command;
 use serenity::framework::standard::{Args, CommandResult};
-use serenity::model::channel::Message;
+use serenity::model::channel::{AttachmentType, Message};
+use tracing::info;
+use tracing_subscriber::filter::LevelFilter;
 
-use util::ctx;
+use msgtool::interact::ConfirmStyle;
+use util::{ctx, string};
 
-use crate::{arg, data, finish, send};
+use crate::secrets::{self, Secrets};
+use crate::{arg, data, finish, flag, log_level, redact, send};
 
 #[command]
-/// Run sql query and send its output as message.
+/// Run sql query and send its output as message, long outputs are split over multiple messages.
 async fn sql(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
     let (db_name, mut query) = arg!(ctx, msg, args, "database name", "query");
 
@@ -21,13 +30,13 @@ async fn sql(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
         .output()?;
 
     let stdout = String::from_utf8(output.stdout)?;
-    if !stdout.is_empty() {
-        finish!(ctx, msg, stdout);
-    }
-
     let stderr = String::from_utf8(output.stderr)?;
-    if !stderr.is_empty() {
-        finish!(ctx, msg, stderr);
+    let output = if stdout.is_empty() { stderr } else { stdout };
+    if !output.is_empty() {
+        for part in string::split_message(&output, string::MESSAGE_LEN) {
+            send!(ctx, msg, part);
+        }
+        return Ok(());
     }
 
     finish!(ctx, msg, "No output");
@@ -43,8 +52,8 @@ async fn check_db_integrity(ctx: &Context, msg: &Message, _: Args) -> CommandRes
     {
         let db = db.read().await;
         let issues = memberdb::check_integrity(&db).await?;
-        if !issues.is_empty() {
-            send!(ctx, msg, issues.join("\n"));
+        for part in string::split_message(&issues.join("\n"), string::MESSAGE_LEN) {
+            send!(ctx, msg, part);
         }
     }
 
@@ -52,3 +61,260 @@ async fn check_db_integrity(ctx: &Context, msg: &Message, _: Args) -> CommandRes
 
     Ok(())
 }
+
+#[command("migrate")]
+/// Import members and their stats from the legacy bot's json export, the file is read from the
+/// host.
+/// By default only a report of what would be imported is sent, add the `apply` flag to actually
+/// import the members.
+#[usage("legacy <file> [apply]")]
+#[example("legacy export/haxbot.json")]
+#[example("legacy export/haxbot.json apply")]
+async fn migrate(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let (source, file) = arg!(ctx, msg, args, "source", "file");
+    if source != "legacy" {
+        finish!(ctx, msg, "Unknown migration source, only `legacy` is supported");
+    }
+    let apply = flag!(ctx, msg, args, "apply");
+
+    let content = std::fs::read_to_string(&file).with_context(|| format!("Failed to read {}", file))?;
+    let members = memberdb::migrate::parse_export(&content)?;
+
+    let (db, client) = data!(ctx, "db", "reqwest");
+    let igns: Vec<String> = members.iter().filter_map(|m| m.ign.clone()).collect();
+    let mcids = wynn::get_ids(&client, &igns).await?;
+
+    let db = db.write().await;
+    let plan = memberdb::migrate::plan_import(&db, members, &mcids).await?;
+
+    let mut report = String::new();
+    for member in &plan.members {
+        writeln!(report, "+ {} ({})", member.name, member.rank)?;
+    }
+    for (name, reason) in &plan.skipped {
+        writeln!(report, "- {}: {}", name, reason)?;
+    }
+
+    let summary = if apply {
+        let count = memberdb::migrate::import(&db, &plan).await?;
+        format!("Imported {} members, skipped {} entries", count, plan.skipped.len())
+    } else {
+        format!(
+            "Dry run: {} members can be imported, {} entries would be skipped",
+            plan.members.len(),
+            plan.skipped.len()
+        )
+    };
+
+    let report = AttachmentType::Bytes {
+        data: Cow::Owned(report.into_bytes()),
+        filename: "report.txt".to_string(),
+    };
+    ctx!(
+        msg.channel_id
+            .send_files(&ctx, [report], |m| m.content(&summary).reference_message(msg))
+            .await
+    )?;
+
+    Ok(())
+}
+
+#[command("loglevel")]
+/// Change the log level of a module at runtime, ex: raise `wynn::loops` to `debug` during an
+/// incident. The change is lost on restart.
+///
+/// Without arguments, the current level overrides are displayed.
+/// The level can be `trace`, `debug`, `info`, `warn`, `error` or `off`, use `reset` to remove the
+/// module's override, or call `loglevel reset` to remove all overrides.
+#[usage("[module] [level | reset]")]
+#[example("wynn::loops debug")]
+#[example("wynn::loops reset")]
+#[example("reset")]
+async fn log_level(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let levels = data!(ctx, "log");
+    let mut levels = levels.lock().await;
+
+    let target = match arg!(ctx, msg, args, ?"module") {
+        Some(target) => target,
+        None => {
+            let mut content = format!("Default level: `{}`", log_level::DEFAULT_LEVEL);
+            for (target, level) in levels.overrides() {
+                write!(content, "\n`{}`: `{}`", target, level)?;
+            }
+            finish!(ctx, msg, content);
+        }
+    };
+    if target == "reset" {
+        levels.reset()?;
+        finish!(ctx, msg, "Removed all log level overrides");
+    }
+
+    let level = arg!(ctx, msg, args, "level");
+    if level == "reset" {
+        if !levels.unset(&target)? {
+            finish!(ctx, msg, "`{}` doesn't have a log level override", target);
+        }
+        finish!(ctx, msg, "Removed log level override of `{}`", target);
+    }
+    let level = match LevelFilter::from_str(&level) {
+        Ok(level) => level,
+        Err(_) => finish!(
+            ctx,
+            msg,
+            "Invalid log level, it can be `trace`, `debug`, `info`, `warn`, `error` or `off`"
+        ),
+    };
+    levels.set(&target, level)?;
+    finish!(ctx, msg, "Log level of `{}` is set to `{}`", target, level);
+}
+
+#[command("redactlogs")]
+/// Display or set if discord ids, igns and names in the log files are replaced with hashes, and
+/// message metadata is omitted from them, so they can be shared without leaking member data.
+#[usage("[on | off]")]
+#[example("")]
+#[example("on")]
+async fn redact_logs(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let config = data!(ctx, "config");
+    let enabled = match args.rest().trim() {
+        "" => {
+            let state = if config.read().await.redact_logs { "redacted" } else { "not redacted" };
+            finish!(ctx, msg, "Log files are {}", state);
+        }
+        "on" => true,
+        "off" => false,
+        _ => finish!(ctx, msg, "Expected `on` or `off`"),
+    };
+    config.write().await.redact_logs = enabled;
+    redact::set_enabled(enabled);
+    info!(enabled, "Changed log redaction");
+    if enabled {
+        finish!(ctx, msg, "Log files are now redacted");
+    }
+    finish!(ctx, msg, "Log files are no longer redacted");
+}
+
+#[command("weeklyreset")]
+/// Undo the last weekly reset if it fired erroneously, ex: triggered by mistake.
+///
+/// The weekly stats are backed up right before each weekly reset, and can be restored within 24
+/// hours of it.
+/// Stats gained since the reset are kept.
+#[usage("undo")]
+#[example("undo")]
+async fn weekly_reset(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let action = arg!(ctx, msg, args, "action");
+    if action != "undo" {
+        finish!(ctx, msg, "Unknown action, only `undo` is supported");
+    }
+
+    let db = data!(ctx, "db");
+    let now = ctx!(SystemTime::now().duration_since(UNIX_EPOCH), "Failed to get current unix timestamp")?;
+    let now = i64::try_from(now.as_secs())?;
+    let time = {
+        let db = db.read().await;
+        let time = memberdb::weekly_backup::backup_time(&mut db.exe()).await?;
+        match time {
+            Some(time) if now - time <= memberdb::weekly_backup::UNDO_WINDOW => time,
+            Some(_) => finish!(ctx, msg, "The last weekly reset is more than 24 hours ago"),
+            None => finish!(ctx, msg, "There is no weekly reset to undo"),
+        }
+    };
+
+    let answer = ctx!(
+        msgtool::interact::confirm(
+            ctx,
+            &msg.channel_id,
+            &format!("Undo the weekly reset at <t:{}:f>?", time),
+            &ConfirmStyle::Important,
+            30,
+            msg.author.id,
+        )
+        .await
+    )?;
+    match answer {
+        Some((true, _)) => {
+            info!(caller = %msg.author.id, time, "Undoing weekly reset");
+            let db = db.write().await;
+            let mut tx = db.begin().await?;
+            ctx!(memberdb::weekly_backup::undo_weekly_reset(&mut tx, now).await)?;
+            ctx!(tx.commit().await)?;
+            finish!(ctx, msg, "Weekly stats are restored")
+        }
+        Some((false, _)) => finish!(ctx, msg, "Cancelled"),
+        // Timeout is already responded to
+        None => Ok(()),
+    }
+}
+
+#[command("secrets")]
+#[sub_commands(reload_secrets)]
+/// Display where the secrets are read from, and the masked secrets in use.
+/// To re-read them from the secrets file, use `secrets reload`.
+async fn show_secrets(ctx: &Context, msg: &Message, _: Args) -> CommandResult {
+    let secrets = data!(ctx, "secrets");
+    let secrets = secrets.read().await;
+    let client_secret = secrets.client_secret.as_deref().map(secrets::mask);
+    finish!(
+        ctx,
+        msg,
+        "Secrets are read from the {}\nDiscord token: `{}`\nClient secret: `{}`",
+        secrets.source(),
+        secrets::mask(&secrets.discord_token),
+        client_secret.as_deref().unwrap_or("not set")
+    );
+}
+
+#[command("reload")]
+/// Re-read the secrets from the secrets file set by `SECRETS_FILE`, and check if the Wynncraft and
+/// Mojang APIs can be requested.
+///
+/// A new discord token has to belong to this bot, otherwise the reload is cancelled.
+/// If the discord token or the client secret changed, the bot saves its state and restarts in
+/// place to reconnect with them.
+async fn reload_secrets(ctx: &Context, msg: &Message, _: Args) -> CommandResult {
+    let (secrets, client) = data!(ctx, "secrets", "reqwest");
+    let new = match Secrets::load() {
+        Ok(new) => new,
+        Err(why) => finish!(ctx, msg, "Failed to load secrets: {:#}", why),
+    };
+
+    let mut content = String::from("> **Secrets reload**");
+    for (name, result) in secrets::check_apis(&client).await {
+        match result {
+            Ok(_) => write!(content, "\n{} API: ok", name)?,
+            Err(why) => write!(content, "\n{} API: failed, {:#}", name, why)?,
+        }
+    }
+
+    let old = secrets.read().await.clone();
+    if new == old {
+        content.push_str("\nThe secrets didn't change");
+        finish!(ctx, msg, content);
+    }
+    if new.discord_token != old.discord_token {
+        let user = match secrets::validate_token(&new.discord_token).await {
+            Ok(user) => user,
+            Err(why) => {
+                write!(content, "\nThe new discord token is invalid, reload cancelled: {:#}", why)?;
+                finish!(ctx, msg, content);
+            }
+        };
+        if user.id != ctx.cache.current_user_id() {
+            write!(content, "\nThe new discord token belongs to {}, reload cancelled", user.tag())?;
+            finish!(ctx, msg, content);
+        }
+    }
+
+    info!(caller = %msg.author.id, "Reloaded secrets");
+    *secrets.write().await = new;
+    content.push_str("\nSecrets reloaded, restarting to reconnect with them");
+    send!(ctx, msg, content);
+
+    let (config, cache, shard) = data!(ctx, "config", "cache", "shard");
+    cache.write().await;
+    config.read().await.write("./config.json");
+    secrets::request_restart();
+    shard.lock().await.shutdown_all().await;
+    Ok(())
+}
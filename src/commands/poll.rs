@@ -0,0 +1,119 @@
+//! Poll commands
+use anyhow::Context as AHContext;
+use chrono::offset::Utc;
+use serenity::client::Context;
+use serenity::framework::standard::macros::command;
+use serenity::framework::standard::{Args, CommandResult};
+use serenity::model::channel::{Message, ReactionType};
+use tracing::warn;
+
+use memberdb::model::discord::DiscordId;
+use memberdb::poll;
+use util::{ctx, some};
+
+use crate::i18n;
+use crate::util::poll::{make_poll, make_results_embed, OPTION_EMOJIS};
+use crate::{arg, data, finish, send_embed, tr};
+
+#[command("poll")]
+#[only_in(guild)]
+#[sub_commands(create_poll, show_poll_results)]
+/// Polls where members vote on an option by reacting to the poll message.
+/// For creating polls and viewing their results, use subcommands.
+async fn poll_info(ctx: &Context, msg: &Message, _: Args) -> CommandResult {
+    finish!(ctx, msg, "Use `poll create` to create a poll, and `poll results` to view the results of one")
+}
+
+#[command("create")]
+#[only_in(guild)]
+#[usage("<duration> <question> | <option> | <option> [| <option> ...]")]
+#[example("1d Which day should we hold the event on? | Saturday | Sunday")]
+#[example("\"12 hours\" Next war target? | Corkus | Gavel | Molten Heights")]
+/// Create a poll in this channel that closes after `duration`, members vote by reacting with the
+/// emoji of an option.
+/// Members that react with several options don't have their vote counted.
+/// Once the poll closes, its results are posted, and can be viewed with `poll results`.
+///
+/// A poll has between 2 and 10 options.
+/// If `duration` contains spaces, wrap it in quotes.
+async fn create_poll(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let lc = i18n::locale(ctx, msg).await;
+    let duration = arg!(ctx, msg, args, "duration");
+    let duration = match util::string::parse_second(&duration) {
+        Ok(duration) if duration > 0 => duration,
+        _ => finish!(ctx, msg, tr!(lc, InvalidDuration, duration)),
+    };
+    let mut parts = args.rest().split('|').map(str::trim);
+    let question = some!(parts.next().filter(|s| !s.is_empty()), finish!(ctx, msg, "Question not provided"));
+    let options: Vec<String> = parts.filter(|s| !s.is_empty()).map(String::from).collect();
+    if options.len() < 2 || options.len() > OPTION_EMOJIS.len() {
+        finish!(ctx, msg, "A poll has between 2 and {} options, separated by `|`", OPTION_EMOJIS.len());
+    }
+    let close = Utc::now().timestamp().saturating_add(i64::try_from(duration).unwrap_or(i64::MAX));
+
+    let creator = DiscordId::try_from(msg.author.id.0)?;
+    let channel = i64::try_from(msg.channel_id.0)?;
+    let db = data!(ctx, "db");
+    // The poll is sent in the transaction that stores it, so a poll message that is sent is always
+    // stored and closed
+    let (id, message) = {
+        let db = db.write().await;
+        let mut tx = ctx!(db.begin().await)?;
+        let message = ctx!(
+            msg.channel_id.say(&ctx, make_poll(question, &options, close)).await,
+            "Failed to send poll"
+        )?;
+        let stored = async {
+            let id = poll::open_poll(
+                &mut tx,
+                creator,
+                channel,
+                i64::try_from(message.id.0)?,
+                question,
+                &options,
+                close,
+            )
+            .await?;
+            ctx!(tx.commit().await)?;
+            anyhow::Ok(id)
+        }
+        .await;
+        match stored {
+            Ok(id) => (id, message),
+            Err(why) => {
+                if let Err(why) = message.delete(&ctx).await {
+                    warn!("Failed to delete poll that failed to be stored: {:#}", why);
+                }
+                return Err(why.into());
+            }
+        }
+    };
+    for emoji in OPTION_EMOJIS.iter().take(options.len()) {
+        ctx!(message.react(&ctx, ReactionType::Unicode(emoji.to_string())).await)?;
+    }
+    finish!(ctx, msg, "Created poll `{}`", id)
+}
+
+#[command("results")]
+#[only_in(guild)]
+#[usage("<id>")]
+#[example("3")]
+/// Display the results of a poll, the id of a poll is shown when it is created.
+async fn show_poll_results(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let id = arg!(ctx, msg, args, "id": i64);
+    let db = data!(ctx, "db");
+    let (poll, results) = {
+        let db = db.read().await;
+        let poll = some!(
+            ctx!(poll::get_poll(&mut db.exe(), id).await)?,
+            finish!(ctx, msg, "Poll `{}` doesn't exist", id)
+        );
+        let results = ctx!(poll::poll_results(&mut db.exe(), id).await)?;
+        (poll, results)
+    };
+    if results.iter().all(|(_, votes)| votes.is_none()) {
+        finish!(ctx, msg, "Poll `{}` is still open, it closes <t:{}:R>", id, poll.close);
+    }
+    send_embed!(ctx, msg, |e| make_results_embed(e, &poll.question, &results));
+    Ok(())
+}
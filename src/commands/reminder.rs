@@ -0,0 +1,112 @@
+//! Reminder commands
+use std::fmt::Write as _;
+
+use chrono::offset::Utc;
+use serenity::client::Context;
+use serenity::framework::standard::macros::command;
+use serenity::framework::standard::{Args, CommandResult};
+use serenity::model::channel::Message;
+
+use memberdb::model::discord::DiscordId;
+use memberdb::reminder;
+use util::{ctx, string};
+
+use crate::i18n;
+use crate::{arg, data, finish, tr};
+
+/// Max amount of reminders a user can have at once
+const MAX_REMINDERS: usize = 25;
+
+#[command("remindme")]
+#[sub_commands(list_reminders, cancel_reminder)]
+#[usage("<duration> <text>")]
+#[example("2h Join the war")]
+#[example("\"1 day and 6 hours\" Check the weekly leaderboard")]
+/// Remind you of `text` after `duration`, the reminder is sent to your DMs, or to the channel the
+/// command is used in if you can't be DMed.
+///
+/// If `duration` contains spaces, wrap it in quotes.
+/// Units can be written as letters or words, ex: "1d5h", "1.5h" or "1 hour and 30 minutes".
+///
+/// To view or cancel your reminders, use subcommands.
+async fn remind_me(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let lc = i18n::locale(ctx, msg).await;
+    let duration = arg!(ctx, msg, args, "duration");
+    let duration = match util::string::parse_second(&duration) {
+        Ok(duration) if duration > 0 => duration,
+        _ => finish!(ctx, msg, tr!(lc, InvalidDuration, duration)),
+    };
+    let text = args.rest().trim();
+    if text.is_empty() {
+        finish!(ctx, msg, "What should I remind you of?");
+    }
+    let user = ctx!(DiscordId::try_from(msg.author.id.0))?;
+    let now = Utc::now().timestamp();
+    let due = now.saturating_add(i64::try_from(duration).unwrap_or(i64::MAX));
+
+    let db = data!(ctx, "db");
+    let id = {
+        let db = db.write().await;
+        if ctx!(reminder::user_reminders(&mut db.exe(), user).await)?.len() >= MAX_REMINDERS {
+            finish!(ctx, msg, "You can't have more than {} reminders", MAX_REMINDERS);
+        }
+        let mut tx = ctx!(db.begin().await)?;
+        let id = ctx!(
+            reminder::add_reminder(&mut tx, user, i64::try_from(msg.channel_id.0)?, text, now, due).await,
+            "Failed to add reminder"
+        )?;
+        ctx!(tx.commit().await)?;
+        id
+    };
+    finish!(ctx, msg, "I'll remind you <t:{}:R> (reminder `{}`)", due, id);
+}
+
+#[command("list")]
+/// List your reminders that aren't sent yet, split over several messages if they don't fit in one.
+async fn list_reminders(ctx: &Context, msg: &Message, _: Args) -> CommandResult {
+    let user = ctx!(DiscordId::try_from(msg.author.id.0))?;
+    let db = data!(ctx, "db");
+    let reminders = {
+        let db = db.read().await;
+        ctx!(reminder::user_reminders(&mut db.exe(), user).await)?
+    };
+    if reminders.is_empty() {
+        finish!(ctx, msg, "You have no reminders");
+    }
+
+    let mut content = String::new();
+    for reminder in reminders {
+        writeln!(content, "`{}` <t:{}:R> {}", reminder.id, reminder.due, reminder.text)?;
+    }
+    // Reminder texts are written by users, so they can't ping anyone
+    for part in string::split_message(&content, string::MESSAGE_LEN) {
+        ctx!(
+            msg.channel_id
+                .send_message(ctx, |m| m.content(part).allowed_mentions(|am| am.empty_parse()))
+                .await,
+            "Failed to send reminders"
+        )?;
+    }
+    Ok(())
+}
+
+#[command("cancel")]
+#[usage("<id>")]
+#[example("12")]
+/// Cancel one of your reminders, use `remindme list` to find its id.
+async fn cancel_reminder(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let id = arg!(ctx, msg, args, "id": i64);
+    let user = ctx!(DiscordId::try_from(msg.author.id.0))?;
+    let db = data!(ctx, "db");
+    let removed = {
+        let db = db.write().await;
+        let mut tx = ctx!(db.begin().await)?;
+        let removed = ctx!(reminder::remove_reminder(&mut tx, id, Some(user)).await)?;
+        ctx!(tx.commit().await)?;
+        removed
+    };
+    if removed {
+        finish!(ctx, msg, "Cancelled reminder `{}`", id);
+    }
+    finish!(ctx, msg, "You don't have a reminder with id `{}`", id);
+}
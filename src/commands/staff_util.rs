@@ -1,19 +1,38 @@
 //! Staf util commands
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::fmt::Write as _;
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
 
+use chrono::NaiveDateTime;
 use serenity::client::Context;
 use serenity::framework::standard::macros::command;
 use serenity::framework::standard::{Args, CommandResult};
 use serenity::model::channel::Message;
-use tracing::error;
+use tracing::{error, info};
 
-use memberdb::model::discord::DiscordId;
+use event::timer::TimerEvent;
+use memberdb::ign_sync;
+use memberdb::member_exit::{self, ExitReason, MemberExit};
+use memberdb::model::db::Stat;
+use memberdb::model::discord::{DiscordId, UserNames};
+use memberdb::model::guild::GuildRank;
 use memberdb::model::wynn::McId;
-use util::{ctx, some};
+use memberdb::stat_increment::{self, AuditedStat};
+use msgtool::interact::ConfirmStyle;
+use msgtool::pager::Pager;
+use msgtool::table::{self, TableData, TableImage};
+use util::{ctx, ok, some};
+use wynn::api::{HttpApi, WynnApi};
 
 use crate::checks::STAFF_CHECK;
-use crate::{cmd_bail, data, finish};
+use crate::i18n;
+use crate::util::bulk_fix::{self, FixKind, FixProgress};
+use crate::util::db::{self, TargetId};
+use crate::util::discord::MinimalLB;
+use crate::util::outbox;
+use crate::util::sync_state::DiscordSyncState;
+use crate::{arg, cmd_bail, data, finish, flag, send, t};
 
 #[command("fixNick")]
 #[only_in(guild)]
@@ -22,7 +41,7 @@ use crate::{cmd_bail, data, finish};
 #[example("Pucaet")]
 #[example("Pucaet#9528")]
 /// Fix `discord_user`'s nickname.
-/// `discord_user` is a discord username, ex: "Pucaet" or "Pucaet#9528".
+/// `discord_user` is a discord username or nickname, ex: "pucaet" or the legacy "Pucaet#9528".
 ///
 /// Note that this command works even if target user has the `NoNickUpdate` tag.
 async fn fix_nick(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
@@ -48,8 +67,12 @@ async fn fix_nick(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
         )
     };
 
-    let result =
-        crate::util::discord::fix_member_nick(&ctx.http, &db, mid, discord_member.as_ref(), None).await;
+    let mut discord_member = discord_member.into_owned();
+    let state = DiscordSyncState::of_member(&db, mid, &guild, &discord_member, None).await;
+    let result = match state {
+        Ok(state) => state.diff(&guild, &discord_member).apply_nick(&ctx.http, &mut discord_member).await,
+        Err(why) => Err(why),
+    };
 
     finish!(
         ctx,
@@ -71,7 +94,7 @@ async fn fix_nick(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
 #[example("Pucaet")]
 #[example("Pucaet#9528")]
 /// Fix `discord_user`'s role.
-/// `discord_user` is a discord username, ex: "Pucaet" or "Pucaet#9528".
+/// `discord_user` is a discord username or nickname, ex: "pucaet" or the legacy "Pucaet#9528".
 ///
 /// Note that this command works even if target user has the `NoRoleUpdate` tag.
 async fn fix_role(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
@@ -85,18 +108,17 @@ async fn fix_role(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
 
     let discord_id = DiscordId::try_from(discord_member.as_ref().user.id.0)?;
 
-    let rank = {
+    let mid = {
         let db = db.read().await;
-        let mid = some!(
+        some!(
             ctx!(discord_id.mid(&mut db.exe()).await)?,
             finish!(ctx, msg, "The provided discord user isn't a member")
-        );
-        ctx!(mid.rank(&mut db.exe()).await)?
+        )
     };
 
     let mut discord_member = discord_member.into_owned();
-    let result =
-        ctx!(crate::util::discord::fix_discord_roles(&ctx.http, rank, &guild, &mut discord_member).await);
+    let state = ctx!(DiscordSyncState::of_member(&db, mid, &guild, &discord_member, None).await)?;
+    let result = ctx!(state.diff(&guild, &discord_member).apply_roles(&ctx.http, &mut discord_member).await);
 
     finish!(
         ctx,
@@ -108,14 +130,63 @@ async fn fix_role(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
     )
 }
 
+#[command("fixAll")]
+#[only_in(guild)]
+#[checks(Staff)]
+#[usage("<nick | role> [resume]")]
+#[example("nick")]
+#[example("role resume")]
+/// Fix the nickname or roles of all members.
+/// The progress is shown in a status message that is updated every few members, and a summary is
+/// sent once it is done.
+///
+/// Members with the `NoNickUpdate` / `NoRoleUpdate` tag are skipped.
+///
+/// If the fix is interrupted (ex: by rate limits or a restart), call this command again with
+/// `resume` to continue from where it stopped.
+async fn fix_all(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let kind = arg!(ctx, msg, args, "kind": FixKind);
+    let resume = flag!(ctx, msg, args, "resume");
+    let (db, config) = data!(ctx, "db", "config");
+    let guild = some!(msg.guild(ctx), cmd_bail!("Failed to get message's guild"));
+
+    let mut progress = if resume {
+        match FixProgress::load() {
+            Some(progress) if progress.kind == kind => progress,
+            _ => finish!(ctx, msg, "There is no unfinished {} fix to resume", kind),
+        }
+    } else {
+        FixProgress::new(kind)
+    };
+
+    let mut status = send!(ctx, msg, "Fixing {} of all members", kind);
+    let finished = bulk_fix::fix_all(ctx, &db, &config, &guild, &mut progress, &mut status).await?;
+
+    let summary = format!(
+        "Changed {}, skipped {}, failed {}",
+        progress.changed, progress.skipped, progress.failed
+    );
+    if finished {
+        finish!(ctx, msg, "Finished fixing {} of all members\n{}", kind, summary);
+    }
+    finish!(
+        ctx,
+        msg,
+        "Interrupted by rate limits, use `fixAll {} resume` to continue later\n{}",
+        kind,
+        summary
+    );
+}
+
 #[command("syncIgn")]
 #[bucket("mojang")]
 #[only_in(guild)]
 #[checks(Staff)]
 #[usage("<ign>")]
 #[example("Pucaet")]
-/// Because the bot only updates ign of in-game guild members,
-/// this command is need to update ign of in-game non-guild members.
+/// Because the bot only updates ign of in-game guild members, and only re-syncs the igns of
+/// other players once a week, this command is used to update ign of in-game non-guild members
+/// right away.
 ///
 /// Note that the `ign` is the ign that is currently stored in database.
 /// For example is a player named "old_name" (as stored in the database) changed their name, then
@@ -138,13 +209,629 @@ async fn sync_member_ign(ctx: &Context, msg: &Message, arg: Args) -> CommandResu
     {
         let db = db.write().await;
         let mut tx = ctx!(db.begin().await)?;
-        ctx!(mcid.set_ign(&mut tx, &ign).await)?;
+        ctx!(ign_sync::sync_ign(&mut tx, &mcid, old_ign, &ign, chrono::Utc::now().timestamp()).await)?;
         ctx!(tx.commit().await)?;
     }
 
     finish!(ctx, msg, "Ign updated to {}", ign)
 }
 
+#[command("refresh")]
+#[bucket("mojang")]
+#[only_in(guild)]
+#[checks(Staff)]
+#[usage("<target>")]
+#[example("m:Pucaet")]
+#[example("d:Pucaet#9528")]
+/// Force fetch a player's ign, guild membership and guild rank, and correct the database if they
+/// are outdated.
+/// This is useful for when the bot missed some changes.
+///
+/// > **How do I specify different targets**
+/// - __Discord user__: "d:<username>", ex: "d:pucaet" or the legacy "d:Pucaet#9528"
+/// - __Mc account__: "m:<ign>", ex: "m:SephDark18"
+async fn refresh_member(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let guild = some!(msg.guild(&ctx), cmd_bail!("Failed to get message's guild"));
+    let (db, client) = data!(ctx, "db", "reqwest");
+
+    let target = t!(db::parse_user_target(ctx, msg, &db, &client, Some(&guild), args.rest()).await);
+    let mcid = match target {
+        TargetId::Wynn(mcid) => mcid,
+        TargetId::Discord(_) => {
+            let db = db.read().await;
+            let mid = some!(target.get_mid(&db).await, finish!(ctx, msg, "Target isn't a member"));
+            some!(
+                ctx!(mid.mcid(&mut db.exe()).await)?,
+                finish!(ctx, msg, "Target doesn't have a linked mc account")
+            )
+        }
+    };
+
+    let ign = ctx!(wynn::get_ign(&client, &mcid.0).await, "Failed to get ign")?;
+    let guild_name = ctx!(std::env::var("GUILD_NAME"), "Failed to get guild name")?;
+    let guild_stat = ctx!(wynn::get_guild(&client, &guild_name).await, "Failed to get guild stats")?;
+    let guild_member = guild_stat.members.into_iter().find(|m| m.uuid == mcid.0);
+
+    let mut changes = Vec::new();
+    {
+        let db = db.write().await;
+        let mut tx = ctx!(db.begin().await)?;
+
+        if ctx!(mcid.wynn_exist(&mut tx.exe()).await)? {
+            let old_ign = ctx!(mcid.ign(&mut tx.exe()).await)?;
+            if old_ign != ign {
+                ctx!(mcid.set_ign(&mut tx, &ign).await)?;
+                changes.push(format!("Ign updated from {} to {}", old_ign, ign));
+            }
+        }
+
+        let in_guild = ctx!(mcid.in_guild(&mut tx.exe()).await)?;
+        match guild_member {
+            Some(guild_member) => {
+                let rank = ctx!(GuildRank::from_api(&guild_member.rank))?;
+                if !in_guild {
+                    ctx!(mcid.bind_guild(&mut tx, &ign, true, rank).await)?;
+                    ctx!(mcid.update_xp(&mut tx, guild_member.contributed).await)?;
+                    let now = chrono::Utc::now().timestamp();
+                    ctx!(
+                        stat_increment::record_wynn(
+                            &mut tx,
+                            &mcid,
+                            AuditedStat::Xp,
+                            guild_member.contributed,
+                            "Refresh",
+                            now
+                        )
+                        .await
+                    )?;
+                    changes.push(format!("Joined the guild as {}", rank));
+                } else {
+                    let old_rank = ctx!(mcid.rank(&mut tx.exe()).await)?;
+                    if old_rank != rank {
+                        ctx!(mcid.set_rank(&mut tx, rank).await)?;
+                        changes.push(format!("Guild rank updated from {} to {}", old_rank, rank));
+                    }
+                }
+                ctx!(mcid.set_joined(&mut tx, &guild_member.joined).await)?;
+                if let Some(wars) = guild_member.wars {
+                    ctx!(mcid.set_wars(&mut tx, wars).await)?;
+                }
+            }
+            None => {
+                if in_guild {
+                    let rank = ctx!(mcid.rank(&mut tx.exe()).await)?;
+                    ctx!(mcid.bind_guild(&mut tx, &ign, false, rank).await)?;
+                    changes.push("Left the guild".to_string());
+                }
+            }
+        }
+
+        ctx!(tx.commit().await)?;
+    }
+
+    if changes.is_empty() {
+        finish!(ctx, msg, "Already up to date");
+    }
+    finish!(ctx, msg, changes.join("\n"))
+}
+
+/// Max amount of stat increments listed by `statAudit`
+const STAT_AUDIT_LEN: i64 = 20;
+
+#[command("statAudit")]
+#[bucket("mojang")]
+#[only_in(guild)]
+#[checks(Staff)]
+#[sub_commands(stat_audit_enable, stat_audit_disable)]
+#[usage("<target> [stat]")]
+#[example("m:Pucaet")]
+#[example("d:pucaet message")]
+/// List the latest recorded increments of a member's stats, along with what they came from, so
+/// disputed stats can be looked into.
+/// `stat` is one of "message", "voice", "stream", "online" or "xp", all of them are listed if it
+/// isn't given.
+///
+/// Increments are only recorded while the stat audit is enabled with `statAudit on`, and they are
+/// kept for the amount of days set in the config.
+///
+/// > **How do I specify different targets**
+/// - __Discord user__: "d:<username>", ex: "d:pucaet" or the legacy "d:Pucaet#9528"
+/// - __Mc account__: "m:<ign>", ex: "m:SephDark18"
+async fn stat_audit(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let guild = some!(msg.guild(ctx), cmd_bail!("Failed to get message's guild"));
+    let (db, client) = data!(ctx, "db", "reqwest");
+
+    let rest = args.rest().trim();
+    let (target, stat) = match rest.rsplit_once(' ') {
+        Some((target, stat)) => match stat.parse::<AuditedStat>() {
+            Ok(stat) => (target, Some(stat)),
+            Err(_) => (rest, None),
+        },
+        None => (rest, None),
+    };
+    let target = t!(db::parse_user_target(ctx, msg, &db, &client, Some(&guild), target).await);
+
+    let (increments, audited) = {
+        let db = db.read().await;
+        let (discord, mcid) = match target.get_mid(&db).await {
+            Some(mid) => ctx!(mid.links(&mut db.exe()).await)?,
+            None => match &target {
+                TargetId::Discord(id) => (Some(DiscordId::try_from(id.0)?), None),
+                TargetId::Wynn(mcid) => (None, Some(mcid.clone())),
+            },
+        };
+        let increments = ctx!(
+            stat_increment::increments(&mut db.exe(), discord, mcid.as_ref(), stat, STAT_AUDIT_LEN).await
+        )?;
+        (increments, db.stat_audit())
+    };
+
+    let mut content = "> **Latest stat increments**".to_string();
+    if !audited {
+        content.push_str("\nThe stat audit is disabled, enable it with `statAudit on`");
+    }
+    if increments.is_empty() {
+        content.push_str("\nNo stat increments are recorded");
+    }
+    for increment in increments {
+        let amount = match increment.stat {
+            AuditedStat::Voice | AuditedStat::Stream | AuditedStat::Online => {
+                util::string::fmt_second(increment.amount)
+            }
+            AuditedStat::Message | AuditedStat::Xp => util::string::fmt_num(increment.amount, false),
+        };
+        write!(
+            content,
+            "\n<t:{}:f> **{}** +{} from `{}`",
+            increment.time, increment.stat, amount, increment.source
+        )?;
+    }
+    finish!(ctx, msg, content)
+}
+
+#[command("on")]
+#[only_in(guild)]
+#[checks(Staff)]
+/// Start recording stat increments. This is meant for debugging, as it grows the database fast.
+async fn stat_audit_enable(ctx: &Context, msg: &Message, _: Args) -> CommandResult {
+    let (db, config) = data!(ctx, "db", "config");
+    config.write().await.stat_audit.enabled = true;
+    db.read().await.set_stat_audit(true);
+    info!(user = msg.author.id.0, "Enabled stat audit");
+    finish!(ctx, msg, "Stat increments are now recorded")
+}
+
+#[command("off")]
+#[only_in(guild)]
+#[checks(Staff)]
+/// Stop recording stat increments, the recorded increments are kept until they are pruned.
+async fn stat_audit_disable(ctx: &Context, msg: &Message, _: Args) -> CommandResult {
+    let (db, config) = data!(ctx, "db", "config");
+    config.write().await.stat_audit.enabled = false;
+    db.read().await.set_stat_audit(false);
+    info!(user = msg.author.id.0, "Disabled stat audit");
+    finish!(ctx, msg, "Stat increments are no longer recorded")
+}
+
+#[command("resetnow")]
+#[only_in(guild)]
+#[checks(Staff)]
+/// Trigger the weekly reset now, which records the weekly xp requirements and resets the weekly
+/// stats, same as the scheduled weekly reset.
+///
+/// Note that the next scheduled weekly reset still happens as usual.
+async fn reset_now(ctx: &Context, msg: &Message) -> CommandResult {
+    let timer = data!(ctx, "timer");
+
+    let answer = ctx!(
+        msgtool::interact::confirm(
+            ctx,
+            &msg.channel_id,
+            "Reset the weekly stats now? Only the bot owner can undo it, within 24 hours.",
+            &ConfirmStyle::Important,
+            30,
+            msg.author.id,
+        )
+        .await
+    )?;
+    match answer {
+        Some((true, _)) => {
+            info!(caller = %msg.author.id, "Triggering weekly reset manually");
+            timer.signal(TimerEvent::Weekly);
+            finish!(ctx, msg, "Weekly reset triggered")
+        }
+        Some((false, _)) => finish!(ctx, msg, "Cancelled"),
+        // Timeout is already responded to
+        None => Ok(()),
+    }
+}
+
+#[command("weeklyReport")]
+#[checks(Staff)]
+#[usage("[latest]")]
+#[example("")]
+#[example("latest")]
+/// Make a report of the current week without resetting anything, same as the report made before
+/// the weekly reset.
+/// If `latest` is given, the report stored at the last weekly reset is displayed instead.
+async fn weekly_report(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let latest = flag!(ctx, msg, args, "latest");
+    let (db, config) = data!(ctx, "db", "config");
+
+    let fmt = i18n::stat_format(ctx, msg).await;
+    let report = if latest {
+        let db = db.read().await;
+        let report = ctx!(memberdb::weekly_report::latest_report(&mut db.exe()).await)?;
+        some!(report, finish!(ctx, msg, "No weekly report is stored yet"))
+    } else {
+        let requirements = {
+            let config = config.read().await;
+            memberdb::xp_requirement::parse_requirements(&config.xp_requirements)
+        };
+        let now = ctx!(SystemTime::now().duration_since(UNIX_EPOCH), "Failed to get current unix timestamp")?;
+        let now = i64::try_from(now.as_secs())?;
+        let db = db.read().await;
+        ctx!(memberdb::weekly_report::make_report(&db, &*ctx.cache, &fmt, &requirements, now).await)?
+    };
+    finish!(ctx, msg, report.summary())
+}
+
+#[command("simulateReq")]
+#[checks(Staff)]
+#[usage("<rank>=<xp> ... [previous]")]
+#[example("Recruit=500k Recruiter=1m")]
+#[example("Captain=2m previous")]
+/// Show how many guild members would miss hypothetical weekly xp requirements, so they can be
+/// tuned before being adopted. The configured requirements aren't changed.
+/// `rank` is a guild rank name, ex: "Recruit", and `xp` can be abbreviated, ex: "1.5m".
+/// The current week's xp is evaluated, unless `previous` is given, in which case the xp of the
+/// week before the last weekly reset is evaluated instead.
+async fn simulate_requirements(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let mut previous = false;
+    let mut requirements = HashMap::new();
+    for arg in crate::util::arg::rest(&mut args) {
+        if arg == "previous" {
+            previous = true;
+            continue;
+        }
+        let (rank, xp) = some!(arg.split_once('='), finish!(ctx, msg, "Invalid requirement '{}'", arg));
+        let rank = ok!(GuildRank::from_str(rank), finish!(ctx, msg, "'{}' isn't a valid guild rank", rank));
+        let xp = ok!(util::string::parse_num(xp), finish!(ctx, msg, "'{}' isn't a valid xp amount", xp));
+        if xp < 0 {
+            finish!(ctx, msg, "Xp requirement can't be negative");
+        }
+        requirements.insert(rank, xp);
+    }
+    if requirements.is_empty() {
+        finish!(ctx, msg, "No requirement provided");
+    }
+
+    let db = data!(ctx, "db");
+    let results = {
+        let db = db.read().await;
+        ctx!(memberdb::xp_requirement::simulate_requirements(&mut db.exe(), &requirements, previous).await)?
+    };
+
+    let week = if previous { "previous week" } else { "current week" };
+    let mut content = format!("> **Simulated weekly xp requirements** ({})", week);
+    let (mut members, mut failed) = (0, 0);
+    for result in results {
+        members += result.members;
+        failed += result.failed;
+        let _ = write!(
+            content,
+            "\n**{}** requires {} xp: {}/{} would fail, median {} xp",
+            result.rank,
+            util::string::fmt_num(result.required, true),
+            result.failed,
+            result.members,
+            util::string::fmt_num(result.median_xp, true)
+        );
+        if result.failed > 0 {
+            let [a, b, c, d] = result.buckets;
+            let _ = write!(content, "\n    met <25%: {}, 25-50%: {}, 50-75%: {}, 75-100%: {}", a, b, c, d);
+        }
+    }
+    let _ = write!(content, "\nTotal: {}/{} would fail", failed, members);
+    finish!(ctx, msg, content)
+}
+
+/// Stats that can be reset with `resetstat`, they all have both a weekly and a total version
+const RESETTABLE_STATS: [&str; 5] = ["message", "voice", "stream", "online", "xp"];
+
+#[command("resetstat")]
+#[bucket("mojang")]
+#[only_in(guild)]
+#[checks(Staff)]
+#[usage("<stat> [weekly|total] [target|all]")]
+#[example("xp weekly m:Pucaet")]
+#[example("voice total all")]
+/// Reset a stat to 0 for a member, or for everyone.
+/// `stat` is one of "message", "voice", "stream", "online", or "xp".
+/// The weekly version of the stat is reset unless `total` is given.
+/// Member is specified by `target`, which can be discord user or ign. If it is `all` or not
+/// given, the stat is reset for everyone.
+///
+/// You are asked for confirmation first, as a reset can't be undone.
+/// All resets are recorded in the database.
+///
+/// > **How do I specify different targets**
+/// - __Discord user__: "d:<username>", ex: "d:pucaet" or the legacy "d:Pucaet#9528"
+/// - __Mc account__: "m:<ign>", ex: "m:SephDark18"
+async fn reset_member_stat(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let guild = some!(msg.guild(ctx), cmd_bail!("Failed to get message's guild"));
+    let (db, client) = data!(ctx, "db", "reqwest");
+
+    let name = some!(args.single::<String>().ok(), finish!(ctx, msg, "Stat not provided"));
+    if !RESETTABLE_STATS.contains(&name.as_str()) {
+        finish!(ctx, msg, "'{}' isn't a stat that can be reset", name);
+    }
+    let weekly = match args.current() {
+        Some("total") => {
+            args.advance();
+            false
+        }
+        Some("weekly") => {
+            args.advance();
+            true
+        }
+        _ => true,
+    };
+    let stat_name = if weekly { format!("weekly_{}", name) } else { name };
+    let stat = ctx!(Stat::from_str(&stat_name))?;
+
+    let target = args.rest();
+    let (mid, target) = if target.is_empty() || target == "all" {
+        (None, "everyone")
+    } else {
+        let mid = t!(db::parse_user_target_mid(ctx, msg, &db, &client, Some(&guild), target).await);
+        (Some(mid), target)
+    };
+
+    let answer = ctx!(
+        msgtool::interact::confirm(
+            ctx,
+            &msg.channel_id,
+            &format!("Reset `{}` of {}? This can't be undone.", stat_name, target),
+            &ConfirmStyle::Important,
+            30,
+            msg.author.id,
+        )
+        .await
+    )?;
+    match answer {
+        Some((true, _)) => {}
+        Some((false, _)) => finish!(ctx, msg, "Cancelled"),
+        // Timeout is already responded to
+        None => return Ok(()),
+    }
+
+    let now = ctx!(SystemTime::now().duration_since(UNIX_EPOCH), "Failed to get current unix timestamp")?;
+    let now = i64::try_from(now.as_secs())?;
+    let caller = DiscordId::try_from(msg.author.id.0)?;
+    let count = {
+        let db = db.write().await;
+        let mut tx = ctx!(db.begin().await)?;
+        let count = ctx!(memberdb::reset_stat(&mut tx, &stat, mid, caller, now).await)?;
+        ctx!(tx.commit().await)?;
+        count
+    };
+    finish!(ctx, msg, "Reset `{}` of {} profiles", stat_name, count)
+}
+
+/// Default min guild xp of the profiles listed by `unlinked`
+const UNLINKED_MIN_XP: i64 = 1_000_000;
+/// Default min online time in seconds of the profiles listed by `unlinked`
+const UNLINKED_MIN_ONLINE: i64 = 10 * 3600;
+
+#[command("unlinked")]
+#[checks(Staff)]
+#[usage("[min_xp] [min_online]")]
+#[example("")]
+#[example("500k")]
+#[example("0 1d")]
+/// List mc accounts that aren't linked to a discord user, but have at least `min_xp` guild xp
+/// (default 1m) or `min_online` online time (default 10h), from the most online.
+/// Useful for finding members that never linked their discord.
+///
+/// `min_online` is a duration like `1d5h`, and `min_xp` can be written like `500k` or `1m`.
+async fn list_unlinked(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let min_xp = match arg!(ctx, msg, args, ?"min_xp") {
+        Some(min_xp) => match util::string::parse_num(&min_xp) {
+            Ok(min_xp) => min_xp,
+            Err(_) => finish!(ctx, msg, "Invalid xp amount `{}`", min_xp),
+        },
+        None => UNLINKED_MIN_XP,
+    };
+    let min_online = match arg!(ctx, msg, args, ?"min_online") {
+        Some(min_online) => match util::string::parse_second(&min_online).map(i64::try_from) {
+            Ok(Ok(min_online)) => min_online,
+            _ => finish!(ctx, msg, "Invalid duration `{}`", min_online),
+        },
+        None => UNLINKED_MIN_ONLINE,
+    };
+
+    let fmt = i18n::stat_format(ctx, msg).await;
+    let db = data!(ctx, "db");
+    let (table, header) = {
+        let db = db.read().await;
+        ctx!(memberdb::table::unlinked_profiles(&fmt, &db, min_xp, min_online).await)?
+    };
+    if table.is_empty() {
+        finish!(ctx, msg, "No unlinked mc accounts are active enough");
+    }
+
+    let table_data = TableData::paginate(table::borrow_table(&table), table::borrow_row(&header), 10);
+    let index = table_data.iter().map(TableData::search_keys).collect();
+    let mut pager = Pager::new(table_data).with_index(index);
+    ctx!(
+        msgtool::interact::page(ctx, &msg.channel_id, &mut pager, 120).await,
+        "Error when displaying unlinked profile pages"
+    )?;
+    Ok(())
+}
+
+/// Max amount of characters of a response displayed in the `exitlog` table
+const MAX_RESPONSE_PREVIEW_LEN: usize = 40;
+
+#[command("exitlog")]
+#[checks(Staff)]
+#[usage("[kicked | left | purged | #] [minimal | image]")]
+#[example("")]
+#[example("kicked")]
+#[example("12")]
+/// Display the members that left the guild or the discord server, from the newest, optionally
+/// only the ones that left for a reason.
+/// If the number of a record is given, it is displayed in full instead, including the exit
+/// question response.
+///
+/// Leaving the in-game guild is always recorded as `left`, as the API doesn't tell if the player
+/// is kicked. Discord users removed in a prune are recorded as `purged`.
+///
+/// If you use this command with "minimal" as an argument, then the table is displayed without
+/// any styling. Useful if you are viewing it on a small screen.
+/// With "image" as an argument, the table is sent as images instead, which displays
+/// correctly on all screen sizes.
+async fn exit_log(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let filter = arg!(ctx, msg, args, ?"filter");
+    let (is_minimal, is_image) = flag!(ctx, msg, args, "minimal", "image");
+    let db = data!(ctx, "db");
+
+    if let Some(id) = filter.as_deref().and_then(|filter| filter.parse::<i64>().ok()) {
+        let exit = {
+            let db = db.read().await;
+            ctx!(member_exit::get_exit(&mut db.exe(), id).await)?
+        };
+        let exit = some!(exit, finish!(ctx, msg, "Exit record #{} not found", id));
+        let mut content = format!(
+            "> **Exit #{}**\n**{}** ({}) {} the {} <t:{}:R>",
+            exit.id,
+            exit_name(ctx, &exit),
+            exit.rank,
+            exit.reason,
+            exit.source,
+            exit.time
+        );
+        if let Some(mcid) = &exit.mcid {
+            write!(content, "\nMc account: `{}`", mcid.0)?;
+        }
+        if let Some(discord) = exit.discord {
+            write!(content, "\nDiscord user: <@{}>", discord.0)?;
+        }
+        match (&exit.question, &exit.response) {
+            (Some(question), Some(response)) => write!(content, "\n\n__{}__\n{}", question, response)?,
+            (Some(question), None) => write!(content, "\n\n__{}__\nNo response", question)?,
+            _ => content.push_str("\n\nExit question wasn't sent"),
+        }
+        finish!(ctx, msg, content);
+    }
+    let reason = match filter {
+        Some(filter) => match ExitReason::from_str(&filter) {
+            Ok(reason) => Some(reason),
+            Err(_) => finish!(ctx, msg, "Invalid reason `{}`", filter),
+        },
+        None => None,
+    };
+
+    let exits = {
+        let db = db.read().await;
+        ctx!(member_exit::list_exits(&mut db.exe(), reason).await)?
+    };
+    if exits.is_empty() {
+        finish!(ctx, msg, "No members left");
+    }
+
+    let header = vec![
+        "#".to_string(),
+        "name".to_string(),
+        "date".to_string(),
+        "left".to_string(),
+        "reason".to_string(),
+        "response".to_string(),
+    ];
+    let table: Vec<Vec<String>> = exits
+        .iter()
+        .map(|exit| {
+            let date = NaiveDateTime::from_timestamp_opt(exit.time, 0)
+                .map(|time| time.format("%Y-%m-%d").to_string())
+                .unwrap_or_default();
+            let response = exit.response.as_deref().unwrap_or_default();
+            let mut preview: String = response.chars().take(MAX_RESPONSE_PREVIEW_LEN).collect();
+            if response.chars().count() > MAX_RESPONSE_PREVIEW_LEN {
+                preview.push('…');
+            }
+            vec![
+                exit.id.to_string(),
+                exit_name(ctx, exit),
+                date,
+                exit.source.to_string(),
+                exit.reason.to_string(),
+                preview,
+            ]
+        })
+        .collect();
+
+    crate::display_table_pages!(ctx, &msg.channel_id, table, header, 10, is_minimal, is_image, MinimalLB);
+
+    Ok(())
+}
+
+/// Get the name of the member that left, which is their ign if they had a mc account, otherwise
+/// their discord name
+fn exit_name(ctx: &Context, exit: &MemberExit) -> String {
+    if let Some(ign) = &exit.ign {
+        return ign.clone();
+    }
+    match exit.discord {
+        Some(discord) => ctx.cache.user_name(discord).unwrap_or_else(|| discord.to_string()),
+        None => String::new(),
+    }
+}
+
+#[command("logs")]
+#[sub_commands(flush_logs)]
+#[checks(Staff)]
+/// Display the amount of logs and summaries waiting to be delivered.
+///
+/// Messages to tagged channels and log channels that didn't reach any of them, because no channel
+/// has the tag or the bot lacks permissions, are kept and retried every few minutes.
+/// Use `logs flush` to retry right away after tagging a channel or fixing its permissions.
+async fn pending_logs(ctx: &Context, msg: &Message, _: Args) -> CommandResult {
+    let outbox = data!(ctx, "outbox");
+    let counts = outbox.counts();
+    if counts.is_empty() {
+        finish!(ctx, msg, "All logs and summaries are delivered");
+    }
+
+    let mut content = String::from("Messages waiting to be delivered:");
+    for (destination, count) in counts {
+        write!(content, "\n{}: **{}**", destination, count)?;
+    }
+    finish!(ctx, msg, content)
+}
+
+#[command("flush")]
+#[checks(Staff)]
+/// Try to deliver the logs and summaries waiting to be delivered.
+async fn flush_logs(ctx: &Context, msg: &Message, _: Args) -> CommandResult {
+    let (config, outbox) = data!(ctx, "config", "outbox");
+    if outbox.is_empty() {
+        finish!(ctx, msg, "There are no logs or summaries to deliver");
+    }
+    let delivered = outbox::flush(&outbox, &config, ctx).await;
+    let remaining = outbox.len();
+    if remaining == 0 {
+        finish!(ctx, msg, "Delivered {} messages", delivered);
+    }
+    finish!(
+        ctx,
+        msg,
+        "Delivered {} messages, {} are still undelivered, check the channel tags and permissions",
+        delivered,
+        remaining
+    )
+}
+
 #[command("rankSymbol")]
 /// Display all rank symbols
 async fn get_rank_symbols(ctx: &Context, msg: &Message, _: Args) -> CommandResult {
@@ -191,3 +878,56 @@ async fn list_igns(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
 
     finish!(ctx, msg, content)
 }
+
+#[command("observe")]
+#[checks(Staff)]
+#[usage("[guild name]")]
+#[example("")]
+#[example("Avicia")]
+/// Start observing another guild, its level and member count changes are then posted to the
+/// channels tagged with `Intel`. If the guild is already observed, stop observing it instead.
+///
+/// Without a guild name, the observed guilds are listed.
+async fn observe_guild(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let (config, client) = data!(ctx, "config", "reqwest");
+    let name = args.rest().trim();
+
+    if name.is_empty() {
+        let observed = config.read().await.observed_guilds.join(", ");
+        if observed.is_empty() {
+            finish!(ctx, msg, "No guilds are observed");
+        }
+        finish!(ctx, msg, "Observed guilds: {}", observed);
+    }
+
+    let removed = {
+        let mut config = config.write().await;
+        let observed = config.observed_guilds.iter().position(|guild| guild.eq_ignore_ascii_case(name));
+        observed.map(|i| config.observed_guilds.remove(i))
+    };
+    if let Some(name) = removed {
+        info!(caller = %msg.author.id, name, "Stopped observing guild");
+        finish!(ctx, msg, "Stopped observing **{}**", name);
+    }
+
+    let guild = match HttpApi(client).get_guild(name).await {
+        Ok(guild) => guild,
+        Err(why) => {
+            info!(name, "Failed to get guild to observe: {:#}", why);
+            finish!(ctx, msg, "Guild `{}` not found", name);
+        }
+    };
+    {
+        let mut config = config.write().await;
+        config.observed_guilds.push(guild.name.clone());
+    }
+    info!(caller = %msg.author.id, name = guild.name, "Started observing guild");
+    finish!(
+        ctx,
+        msg,
+        "Observing **{}** (level {}, {} members), changes are posted to channels tagged with `Intel`",
+        guild.name,
+        guild.level,
+        guild.members.len()
+    )
+}
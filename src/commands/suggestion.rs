@@ -0,0 +1,112 @@
+//! Suggestion box commands
+use chrono::offset::Utc;
+use serenity::client::Context;
+use serenity::framework::standard::macros::command;
+use serenity::framework::standard::{Args, CommandResult};
+use serenity::model::channel::Message;
+use serenity::model::id::ChannelId;
+use serenity::model::mention::Mentionable;
+
+use config::tag::TextChannelTag;
+use memberdb::model::discord::DiscordId;
+use memberdb::suggestion::{self, SuggestionStatus};
+use msgtool::pager::Pager;
+use msgtool::table::{self, TableData, TableImage};
+use util::{ctx, some};
+
+use crate::checks::STAFF_CHECK;
+use crate::util::discord::MinimalLB;
+use crate::util::suggestion::{submit, update_message};
+use crate::{arg, data, finish, flag};
+
+/// Max amount of characters of a suggestion displayed by the `suggestions` command
+const MAX_PREVIEW_LEN: usize = 40;
+
+#[command("suggest")]
+#[only_in(guild)]
+#[usage("<suggestion>")]
+#[example("Hold a weekly guild event on Saturdays")]
+/// Submit a suggestion, which is posted to the suggestion channel where members vote on it.
+/// You can also submit one by sending it in the suggestion channel.
+async fn suggest(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let text = args.rest().trim();
+    if text.is_empty() {
+        finish!(ctx, msg, "Suggestion not provided");
+    }
+    let (db, config) = data!(ctx, "db", "config");
+    let channel_id = {
+        let config = config.read().await;
+        let channel_id = config.text_channel_tags.tagged_objects(&TextChannelTag::Suggestion).next();
+        ChannelId(*some!(channel_id, finish!(ctx, msg, "There is no suggestion channel")))
+    };
+    let author = ctx!(DiscordId::try_from(msg.author.id.0))?;
+    let id = ctx!(submit(&ctx.http, &db, channel_id, author, text, Utc::now().timestamp()).await)?;
+    finish!(ctx, msg, "Submitted suggestion #{} to {}", id, channel_id.mention())
+}
+
+#[command("suggestions")]
+#[sub_commands(mark_suggestion)]
+#[usage("[open | accepted | rejected | implemented] [minimal | image]")]
+#[example("")]
+#[example("open")]
+#[example("accepted minimal")]
+/// Display the suggestions from the highest voted, optionally only the ones with a status.
+///
+/// If you use this command with "minimal" as an argument, then the table is displayed without
+/// any styling. Useful if you are viewing it on a small screen.
+/// With "image" as an argument, the table is sent as images instead, which displays
+/// correctly on all screen sizes.
+async fn list_suggestions(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let status = arg!(ctx, msg, args, ?"status": SuggestionStatus);
+    let (is_minimal, is_image) = flag!(ctx, msg, args, "minimal", "image");
+
+    let db = data!(ctx, "db");
+    let suggestions = {
+        let db = db.read().await;
+        ctx!(suggestion::list_suggestions(&mut db.exe(), status).await)?
+    };
+    if suggestions.is_empty() {
+        finish!(ctx, msg, "There are no suggestions");
+    }
+
+    let header = vec!["#".to_string(), "status".to_string(), "votes".to_string(), "suggestion".to_string()];
+    let table: Vec<Vec<String>> = suggestions
+        .into_iter()
+        .map(|s| {
+            let mut text: String = s.text.chars().take(MAX_PREVIEW_LEN).collect();
+            if s.text.chars().count() > MAX_PREVIEW_LEN {
+                text.push('…');
+            }
+            vec![s.id.to_string(), s.status.to_string(), format!("+{} -{}", s.upvotes, s.downvotes), text]
+        })
+        .collect();
+
+    crate::display_table_pages!(ctx, &msg.channel_id, table, header, 10, is_minimal, is_image, MinimalLB);
+
+    Ok(())
+}
+
+#[command("mark")]
+#[only_in(guild)]
+#[checks(STAFF)]
+#[usage("<id> <open | accepted | rejected | implemented>")]
+#[example("12 accepted")]
+/// Set the status of a suggestion, which is shown on its message.
+async fn mark_suggestion(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let (id, status) = arg!(ctx, msg, args, "id": i64, "status": SuggestionStatus);
+    let db = data!(ctx, "db");
+    let suggestion = {
+        let db = db.write().await;
+        let mut tx = ctx!(db.begin().await)?;
+        if !ctx!(suggestion::set_suggestion_status(&mut tx, id, status).await)? {
+            finish!(ctx, msg, "Suggestion #{} doesn't exist", id);
+        }
+        let suggestion = ctx!(suggestion::get_suggestion(&mut tx.exe(), id).await)?;
+        ctx!(tx.commit().await)?;
+        suggestion
+    };
+    if let Some(suggestion) = suggestion {
+        ctx!(update_message(&ctx.http, &suggestion).await)?;
+    }
+    finish!(ctx, msg, "Marked suggestion #{} as {}", id, status)
+}
@@ -0,0 +1,121 @@
+//! Recruitment ticket commands
+use std::fmt::Write as _;
+
+use serenity::client::Context;
+use serenity::framework::standard::macros::command;
+use serenity::framework::standard::{Args, CommandResult};
+use serenity::model::channel::Message;
+
+use config::tag::TextChannelTag;
+use memberdb::model::discord::DiscordId;
+use memberdb::ticket;
+use util::{ctx, some};
+
+use crate::checks::STAFF_CHECK;
+use crate::util::ticket::{close, describe_applicant, post_panel};
+use crate::{cmd_bail, data, finish};
+
+/// Max amount of tickets listed at once
+const TICKET_LIST_LEN: usize = 15;
+
+#[command("tickets")]
+#[only_in(guild)]
+#[checks(Staff)]
+#[sub_commands(ticket_panel, close_ticket)]
+#[usage("[discord_user]")]
+#[example("")]
+#[example("Pucaet")]
+/// List the open recruitment tickets, or the tickets of `discord_user` along with their member
+/// record.
+/// `discord_user` is a discord username or nickname.
+///
+/// Tickets are private threads of the channels tagged with `Recruitment`, opened by applicants
+/// with the button posted by `tickets panel`. Transcripts of closed tickets are posted to the
+/// channels tagged with `TicketLog`.
+async fn list_tickets(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let db = data!(ctx, "db");
+    let name = args.rest().trim();
+
+    if name.is_empty() {
+        let tickets = {
+            let db = db.read().await;
+            ctx!(ticket::open_tickets(&mut db.exe()).await)?
+        };
+        if tickets.is_empty() {
+            finish!(ctx, msg, "There are no open tickets");
+        }
+        let mut content = String::from("> **Open tickets**");
+        for ticket in tickets.iter().take(TICKET_LIST_LEN) {
+            write!(
+                content,
+                "\n#{} <#{}> by <@{}>, opened <t:{}:R>",
+                ticket.id, ticket.channel, ticket.applicant, ticket.opened
+            )?;
+        }
+        finish!(ctx, msg, content);
+    }
+
+    let guild = some!(msg.guild(ctx), cmd_bail!("Failed to get message's guild"));
+    let member = some!(
+        ctx!(util::discord::get_member_named(&ctx.http, &guild, name).await)?,
+        finish!(ctx, msg, "Can't find specified discord user")
+    );
+    let applicant = DiscordId::try_from(member.user.id.0)?;
+    let tickets = {
+        let db = db.read().await;
+        ctx!(ticket::tickets_of(&mut db.exe(), applicant).await)?
+    };
+    let record = ctx!(describe_applicant(&db, applicant).await)?;
+    let mut content = format!("> **Tickets of <@{}>**\nApplicant: {}", applicant, record);
+    if tickets.is_empty() {
+        content.push_str("\nThey have never opened a ticket");
+    }
+    for ticket in tickets.iter().take(TICKET_LIST_LEN) {
+        write!(content, "\n#{} <#{}>, opened <t:{}:R>", ticket.id, ticket.channel, ticket.opened)?;
+        if let (Some(closed), Some(closed_by)) = (ticket.closed, ticket.closed_by) {
+            write!(content, ", closed <t:{}:R> by <@{}>", closed, closed_by)?;
+        }
+    }
+    finish!(ctx, msg, content)
+}
+
+#[command("panel")]
+#[only_in(guild)]
+#[checks(Staff)]
+/// Post the button applicants open tickets with in this channel, which has to be tagged with
+/// `Recruitment`.
+async fn ticket_panel(ctx: &Context, msg: &Message, _: Args) -> CommandResult {
+    let config = data!(ctx, "config");
+    let is_recruitment = {
+        let config = config.read().await;
+        config.text_channel_tags.tagged(&msg.channel_id.0, &TextChannelTag::Recruitment)
+    };
+    if !is_recruitment {
+        finish!(ctx, msg, "This channel isn't tagged with `Recruitment`");
+    }
+    ctx!(post_panel(&ctx.http, msg.channel_id).await)?;
+    Ok(())
+}
+
+#[command("close")]
+#[only_in(guild)]
+#[checks(Staff)]
+/// Close the ticket this command is used in, its transcript is posted to the channels tagged with
+/// `TicketLog`.
+/// Applicants can also close their ticket with its button.
+async fn close_ticket(ctx: &Context, msg: &Message, _: Args) -> CommandResult {
+    let (db, config) = data!(ctx, "db", "config");
+    let ticket = {
+        let db = db.read().await;
+        ctx!(ticket::ticket_by_channel(&mut db.exe(), i64::try_from(msg.channel_id.0)?).await)?
+    };
+    let ticket = match ticket {
+        Some(ticket) if ticket.closed.is_none() => ticket,
+        Some(_) => finish!(ctx, msg, "This ticket is already closed"),
+        None => finish!(ctx, msg, "This channel isn't a ticket"),
+    };
+    let closer = DiscordId::try_from(msg.author.id.0)?;
+    ctx!(msg.reply(ctx, format!("Ticket closed by <@{}>", closer)).await)?;
+    ctx!(close(ctx, &db, &config, &ticket, closer).await, "Failed to close ticket")?;
+    Ok(())
+}
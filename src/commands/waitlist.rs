@@ -0,0 +1,152 @@
+//! Guild waitlist commands
+use std::fmt::Write as _;
+
+use serenity::client::Context;
+use serenity::framework::standard::macros::command;
+use serenity::framework::standard::{Args, CommandResult};
+use serenity::model::channel::Message;
+
+use memberdb::model::discord::DiscordId;
+use memberdb::{ticket, waitlist};
+use util::{ctx, some};
+
+use crate::checks::STAFF_CHECK;
+use crate::util::waitlist::{format_recruit, Slots};
+use crate::{cmd_bail, data, finish};
+
+/// Max amount of recruits listed at once
+const WAITLIST_LEN: usize = 15;
+
+#[command("waitlist")]
+#[only_in(guild)]
+#[checks(Staff)]
+#[sub_commands(waitlist_add, waitlist_remove, guild_member_cap)]
+/// List the recruits accepted while the in-game guild is full, who wait for a guild slot to open,
+/// along with the guild slots.
+///
+/// When a guild member leaves while there are waitlisted recruits, staff are notified in the
+/// channels tagged with `Waitlist`, if the guild's member cap is set with `waitlist cap`.
+/// Recruits are taken off the waitlist once they join the guild.
+async fn list_waitlist(ctx: &Context, msg: &Message, _: Args) -> CommandResult {
+    let (db, config, cache) = data!(ctx, "db", "config", "cache");
+    let slots = Slots::get(&cache, &config).await;
+    let recruits = {
+        let db = db.read().await;
+        ctx!(waitlist::waitlist(&mut db.exe()).await)?
+    };
+
+    let mut content = format!("> **Guild waitlist**\nGuild slots: {}", slots.describe());
+    if recruits.is_empty() {
+        content.push_str("\nThere are no waitlisted recruits");
+    }
+    for recruit in recruits.iter().take(WAITLIST_LEN) {
+        write!(content, "\n{}", format_recruit(recruit))?;
+    }
+    if recruits.len() > WAITLIST_LEN {
+        write!(content, "\n...and {} more", recruits.len() - WAITLIST_LEN)?;
+    }
+    finish!(ctx, msg, content)
+}
+
+#[command("add")]
+#[only_in(guild)]
+#[checks(Staff)]
+#[usage("<ign> [discord_user]")]
+#[example("Pucaet")]
+#[example("Pucaet pucaet")]
+/// Waitlist an accepted recruit by their ign.
+/// `discord_user` is the recruit's discord username or nickname. If it isn't given and this
+/// command is used in a recruitment ticket, the ticket's applicant is used.
+async fn waitlist_add(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let ign = some!(args.single::<String>().ok(), finish!(ctx, msg, "Ign not provided"));
+    let name = args.rest().trim();
+    let (db, config, cache) = data!(ctx, "db", "config", "cache");
+
+    let discord = if name.is_empty() {
+        let db = db.read().await;
+        let ticket = ctx!(ticket::ticket_by_channel(&mut db.exe(), i64::try_from(msg.channel_id.0)?).await)?;
+        ticket.map(|ticket| ticket.applicant)
+    } else {
+        let guild = some!(msg.guild(ctx), cmd_bail!("Failed to get message's guild"));
+        let member = some!(
+            ctx!(util::discord::get_member_named(&ctx.http, &guild, name).await)?,
+            finish!(ctx, msg, "Can't find specified discord user")
+        );
+        Some(DiscordId::try_from(member.user.id.0)?)
+    };
+
+    let id = {
+        let db = db.write().await;
+        if let Some(recruit) = ctx!(waitlist::recruit_by_ign(&mut db.exe(), &ign).await)? {
+            finish!(ctx, msg, "`{}` is already waitlisted as #{}", recruit.ign, recruit.id);
+        }
+        let mut tx = ctx!(db.begin().await)?;
+        let added_by = DiscordId::try_from(msg.author.id.0)?;
+        let now = chrono::Utc::now().timestamp();
+        let id = ctx!(waitlist::add_recruit(&mut tx, &ign, discord, added_by, now).await)?;
+        ctx!(tx.commit().await)?;
+        id
+    };
+
+    let slots = Slots::get(&cache, &config).await;
+    let mut content = format!("Waitlisted `{}` as #{}", ign, id);
+    if let Some(open) = slots.open().filter(|open| *open > 0) {
+        write!(content, ", the guild has {} open slots so they can be invited right away", open)?;
+    }
+    finish!(ctx, msg, content)
+}
+
+#[command("remove")]
+#[only_in(guild)]
+#[checks(Staff)]
+#[usage("<id | ign>")]
+#[example("3")]
+#[example("Pucaet")]
+/// Take a recruit off the waitlist by their waitlist id or ign.
+async fn waitlist_remove(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let target = args.rest().trim();
+    if target.is_empty() {
+        finish!(ctx, msg, "Recruit not provided");
+    }
+    let db = data!(ctx, "db");
+    let db = db.write().await;
+    let id = match target.trim_start_matches('#').parse::<i64>() {
+        Ok(id) => id,
+        Err(_) => match ctx!(waitlist::recruit_by_ign(&mut db.exe(), target).await)? {
+            Some(recruit) => recruit.id,
+            None => finish!(ctx, msg, "`{}` isn't waitlisted", target),
+        },
+    };
+    let mut tx = ctx!(db.begin().await)?;
+    let removed = ctx!(waitlist::remove_recruit(&mut tx, id).await)?;
+    ctx!(tx.commit().await)?;
+    if removed {
+        finish!(ctx, msg, "Took #{} off the waitlist", id)
+    } else {
+        finish!(ctx, msg, "There is no waitlisted recruit #{}", id)
+    }
+}
+
+#[command("cap")]
+#[only_in(guild)]
+#[checks(Staff)]
+#[usage("[cap | none]")]
+#[example("")]
+#[example("50")]
+#[example("none")]
+/// Display or set the max amount of members of the in-game guild, which the guild api doesn't
+/// report.
+/// With `none`, the cap is unset, and staff are no longer notified of opened guild slots.
+async fn guild_member_cap(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let (config, cache) = data!(ctx, "config", "cache");
+    let cap = match args.rest().trim() {
+        "" => finish!(ctx, msg, "Guild slots: {}", Slots::get(&cache, &config).await.describe()),
+        "none" => None,
+        s => match s.parse::<u64>() {
+            Ok(cap) => Some(cap),
+            Err(_) => finish!(ctx, msg, "'{}' isn't a valid member cap", s),
+        },
+    };
+    config.write().await.guild_member_cap = cap;
+    finish!(ctx, msg, "Guild slots: {}", Slots::get(&cache, &config).await.describe())
+}
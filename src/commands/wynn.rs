@@ -1,11 +1,22 @@
 //! Commands that displays Wynncraft info
 use anyhow::Context as AHContext;
+use chrono::offset::Utc;
 use serenity::client::Context;
 use serenity::framework::standard::macros::command;
 use serenity::framework::standard::{Args, CommandResult};
 use serenity::model::channel::Message;
 
-use crate::{data, finish, send_embed};
+use memberdb::level::{self, XpSample};
+use memberdb::online_history;
+use util::some;
+use util::string::{fmt_second, sparkline};
+
+use crate::{cmd_bail, data, finish, send_embed};
+
+/// Amount of days the guild level progress is measured over
+const LEVEL_PROGRESS_DAYS: i64 = 7;
+/// Amount of weeks the guild activity trend is displayed for
+const ACTIVITY_TREND_WEEKS: i64 = 8;
 
 #[command("online")]
 /// Display online members
@@ -46,3 +57,86 @@ async fn display_online_players(ctx: &Context, msg: &Message, _: Args) -> Comman
 
     Ok(())
 }
+
+#[command("levelprogress")]
+/// Display the guild's level progress, and estimate when it will reach the next level.
+///
+/// The estimate is based on the average progress made in the last 7 days, and assumes that the
+/// xp required per level stays the same.
+async fn display_level_progress(ctx: &Context, msg: &Message, _: Args) -> CommandResult {
+    let (db, cache) = data!(ctx, "db", "cache");
+
+    let (level, xp) = {
+        let guild = cache.guild.read().await;
+        match guild.as_ref() {
+            Some(guild) => (guild.level, guild.xp),
+            None => finish!(ctx, msg, "No guild data cached"),
+        }
+    };
+
+    let now = Utc::now().timestamp();
+    let mut samples = {
+        let db = db.read().await;
+        level::xp_samples(&db, now - LEVEL_PROGRESS_DAYS * 86400).await?
+    };
+    samples.push(XpSample { time: now, level: level.into(), xp: xp.into() });
+
+    let mut content = format!("Guild level **{}**, **{:.1}%** to level {}\n", level, xp, level + 1);
+    match level::progress_rate(&samples) {
+        Some(rate) => {
+            let daily = level::daily_progress(&samples, now, LEVEL_PROGRESS_DAYS);
+            let (older, recent) = daily.split_at(daily.len() / 2);
+            let older = older.iter().sum::<f64>() / older.len() as f64;
+            let recent = recent.iter().sum::<f64>() / recent.len() as f64;
+            let trend = if recent > older * 1.1 {
+                "↗"
+            } else if recent < older * 0.9 {
+                "↘"
+            } else {
+                "→"
+            };
+
+            content.push_str(&format!("Progress: **{:.1}%** per day {}\n", rate, trend));
+            content.push_str(&format!("Last {} days: `{}`\n", LEVEL_PROGRESS_DAYS, sparkline(&daily)));
+            match level::level_eta(&samples) {
+                Some(eta) => content.push_str(&format!("Estimated time to next level: **{}**", fmt_second(eta))),
+                None => content.push_str("The guild isn't making any progress"),
+            }
+        }
+        None => content.push_str("Not enough data to estimate the progress yet"),
+    }
+
+    finish!(ctx, msg, content);
+}
+
+#[command("guildactivity")]
+/// Display how many guild members are online, and its trend over the last 8 weeks.
+///
+/// The stats only include complete days, so today isn't counted.
+async fn display_guild_activity(ctx: &Context, msg: &Message, _: Args) -> CommandResult {
+    let db = data!(ctx, "db");
+
+    let now = Utc::now().timestamp();
+    let trend = {
+        let db = db.read().await;
+        online_history::weekly_online_trend(&db, now, ACTIVITY_TREND_WEEKS).await?
+    };
+    let week = some!(trend.last(), cmd_bail!("Online trend is empty"));
+    if week.peak_online == 0 {
+        finish!(ctx, msg, "Not enough data to display the guild activity yet");
+    }
+
+    let ratios: Vec<f64> = trend.iter().map(|stats| stats.avg_online_ratio * 100.0).collect();
+    let content = format!(
+        "__Last 7 days__\n\
+        Peak members online: **{}**\n\
+        Average members online: **{:.1}** ({:.1}% of the guild)\n\
+        Last {} weeks: `{}`",
+        week.peak_online,
+        week.avg_online_members,
+        week.avg_online_ratio * 100.0,
+        ACTIVITY_TREND_WEEKS,
+        sparkline(&ratios),
+    );
+    finish!(ctx, msg, content);
+}
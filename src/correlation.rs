@@ -0,0 +1,53 @@
+//! Correlation ids of command invocations.
+//!
+//! Every message is dispatched to the framework within a "command" span, and the `before` hook
+//! generates a correlation id for the invoked command and records it into the span.
+//! Everything logged while the command runs, including database operations and the database
+//! events it broadcasts, is within the span, so they can be tied back to the invocation.
+use std::cell::RefCell;
+use std::collections::hash_map::RandomState;
+use std::hash::BuildHasher;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use serenity::async_trait;
+use serenity::client::Context;
+use serenity::framework::{Framework, StandardFramework};
+use serenity::model::channel::Message;
+use tracing::{field, info_span, Instrument, Span};
+
+tokio::task_local! {
+    /// Correlation id of the command being dispatched
+    static CORRELATION_ID: RefCell<Option<String>>;
+}
+
+/// Wrapper of [`StandardFramework`] that dispatches each message within a "command" span
+pub struct TracedFramework(pub StandardFramework);
+
+#[async_trait]
+impl Framework for TracedFramework {
+    async fn dispatch(&self, ctx: Context, msg: Message) {
+        let span = info_span!("command", cid = field::Empty);
+        let dispatch = self.0.dispatch(ctx, msg).instrument(span);
+        CORRELATION_ID.scope(RefCell::new(None), crate::report::isolate(dispatch)).await;
+    }
+}
+
+/// Generate a correlation id for the command being dispatched, and record it into its span
+pub fn start_command() -> String {
+    let id = new_id();
+    Span::current().record("cid", id.as_str());
+    let _ = CORRELATION_ID.try_with(|cid| *cid.borrow_mut() = Some(id.clone()));
+    id
+}
+
+/// Get the correlation id of the command being dispatched
+pub fn current() -> Option<String> {
+    CORRELATION_ID.try_with(|cid| cid.borrow().clone()).ok().flatten()
+}
+
+/// Generate a short id that is unlikely to collide with recent ones
+fn new_id() -> String {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let hash = RandomState::new().hash_one(COUNTER.fetch_add(1, Ordering::Relaxed));
+    format!("{:08x}", hash as u32)
+}
@@ -2,6 +2,9 @@
 use std::sync::Arc;
 use std::time::Duration;
 
+use chrono::{DateTime, Utc};
+
+use memberdb::reset_gate::ResetGate;
 use memberdb::voice_tracker::VoiceTracker;
 use serenity::client::bridge::gateway::ShardManager;
 use serenity::prelude::{Mutex as SMutex, TypeMapKey};
@@ -15,6 +18,13 @@ use memberdb::DB;
 use wynn::cache::Cache;
 use wynn::events::WynnSignal;
 
+use crate::tasks::TaskRegistry;
+use crate::util::autocomplete::IgnIndex;
+use crate::util::outbox::Outbox;
+
+/// Path of the member database file
+pub const MEMBER_DB_FILE: &str = "./database/member.db";
+
 #[derive(Debug, Clone)]
 /// Container for all bot data, so they can all be cloned at once.
 pub struct BotData {
@@ -26,6 +36,12 @@ pub struct BotData {
     pub timer_signal: TimerSignal,
     pub wynn_cache: Arc<Cache>,
     pub voice_tracker: Arc<Mutex<VoiceTracker>>,
+    pub reset_gate: Arc<ResetGate>,
+    pub ign_index: Arc<RwLock<IgnIndex>>,
+    pub tasks: TaskRegistry,
+    pub outbox: Arc<Outbox>,
+    /// When the bot was started
+    pub start_time: DateTime<Utc>,
 }
 
 impl BotData {
@@ -34,7 +50,10 @@ impl BotData {
         let wynn_cache = Arc::new(Cache::new().await.expect("Failed to read wynn cache files"));
         let config = Config::new(config_file).expect("Failed to read config file");
         let config = Arc::new(RwLock::new(config));
-        let db = DB::new(member_db_file, 5).await;
+        let db = {
+            let config = config.read().await;
+            DB::new(member_db_file, &config.database).await
+        };
         let db = Arc::new(RwLock::new(db));
         let voice_tracker = Arc::new(Mutex::new(VoiceTracker::new()));
         Self {
@@ -43,9 +62,14 @@ impl BotData {
             reqwest_client: make_reqwest_clinet(),
             wynn_signal: WynnSignal::new(64),
             discord_signal: DiscordSignal::new(64),
-            timer_signal: TimerSignal::new(1),
+            timer_signal: TimerSignal::new(4),
             wynn_cache,
             voice_tracker,
+            reset_gate: Arc::new(ResetGate::new()),
+            ign_index: Arc::new(RwLock::new(IgnIndex::new())),
+            tasks: TaskRegistry::new(),
+            outbox: Arc::new(Outbox::load()),
+            start_time: Utc::now(),
         }
     }
 
@@ -58,6 +82,12 @@ impl BotData {
         data.insert::<Cache>(self.wynn_cache.clone());
         data.insert::<ShardManagerContainer>(client.shard_manager.clone());
         data.insert::<VoiceTracker>(self.voice_tracker.clone());
+        data.insert::<ResetGate>(self.reset_gate.clone());
+        data.insert::<IgnIndex>(self.ign_index.clone());
+        data.insert::<TimerSignalContainer>(self.timer_signal.clone());
+        data.insert::<TaskRegistry>(self.tasks.clone());
+        data.insert::<Outbox>(self.outbox.clone());
+        data.insert::<StartTimeContainer>(self.start_time);
     }
 }
 
@@ -75,6 +105,20 @@ impl TypeMapKey for ReqClientContainer {
     type Value = reqwest::Client;
 }
 
+/// Bot data key for `TimerSignal`
+pub struct TimerSignalContainer;
+
+impl TypeMapKey for TimerSignalContainer {
+    type Value = TimerSignal;
+}
+
+/// Bot data key for the time the bot was started
+pub struct StartTimeContainer;
+
+impl TypeMapKey for StartTimeContainer {
+    type Value = DateTime<Utc>;
+}
+
 fn make_reqwest_clinet() -> reqwest::Client {
     reqwest::ClientBuilder::new()
         .timeout(Duration::from_secs(5))
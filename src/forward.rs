@@ -0,0 +1,99 @@
+//! Forwarding of database and wynn events to a webhook, configured by
+//! [`EventForwarding`](config::forward::EventForwarding).
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use reqwest::Client;
+use serde::Serialize;
+use serde_json::{json, Value};
+use tokio::sync::RwLock;
+use tracing::{info, warn};
+
+use config::Config;
+use memberdb::DB;
+use util::task::{RestartPolicy, Spawner};
+use util::{ctx, ok};
+use wynn::events::WynnSignal;
+
+/// Timeout of posting events to the webhook
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Start the loops that forward events to the configured webhook
+pub async fn start_forward_loop(
+    spawner: &impl Spawner, client: Client, config: Arc<RwLock<Config>>, db: Arc<RwLock<DB>>,
+    signal: WynnSignal,
+) {
+    let shared_client = client.clone();
+    let shared_config = Arc::clone(&config);
+    spawner.spawn("event forwarding (db event)", RestartPolicy::Always, move || {
+        let shared_client = shared_client.clone();
+        let shared_config = shared_config.clone();
+        let db = db.clone();
+        async move {
+            info!("Starting event forwarding loop (db event)");
+            let mut recv = {
+                let db = db.read().await;
+                db.connect()
+            };
+            loop {
+                let event =
+                    ok!(ctx!(recv.recv().await, "Failed to receive db event in forwarding loop"), continue);
+                forward(&shared_client, &shared_config, "db", &[event.as_ref()]).await;
+            }
+        }
+    });
+
+    spawner.spawn("event forwarding (wynn event)", RestartPolicy::Always, move || {
+        let signal = signal.clone();
+        let client = client.clone();
+        let config = config.clone();
+        async move {
+            info!("Starting event forwarding loop (wynn event)");
+            let mut recv = signal.connect();
+            loop {
+                let events =
+                    ok!(ctx!(recv.recv().await, "Failed to receive wynn event in forwarding loop"), continue);
+                let enabled = {
+                    let config = config.read().await;
+                    config.event_forwarding.wynn_events
+                };
+                if enabled {
+                    forward(&client, &config, "wynn", events.as_ref()).await;
+                }
+            }
+        }
+    });
+}
+
+/// Post events to the webhook in the background, if there is one, so a slow webhook doesn't hold up
+/// the receiving loops
+async fn forward(client: &Client, config: &RwLock<Config>, source: &str, events: &(impl Serialize + ?Sized)) {
+    let webhook = {
+        let config = config.read().await;
+        config.event_forwarding.webhook.clone()
+    };
+    if let Some(webhook) = webhook {
+        let body = json!({ "source": source, "events": events });
+        let client = client.clone();
+        let source = source.to_string();
+        tokio::spawn(async move {
+            if let Err(why) = post(&client, &webhook, &body).await {
+                warn!(source, "Failed to forward events: {:#}", why);
+            }
+        });
+    }
+}
+
+async fn post(client: &Client, url: &str, body: &Value) -> Result<()> {
+    client
+        .post(url)
+        .timeout(TIMEOUT)
+        .json(body)
+        .send()
+        .await
+        .context("Failed to post events to webhook")?
+        .error_for_status()
+        .context("Webhook responded with an error")?;
+    Ok(())
+}
@@ -1,45 +1,64 @@
 //! Bot event handling
 use std::env;
+use std::sync::Arc;
 
 use event::{DiscordContext, DiscordEvent, DiscordSignal};
 use serenity::async_trait;
-use serenity::model::channel::{GuildChannel, Message};
-use serenity::model::event::ResumedEvent;
+use serenity::model::application::interaction::Interaction;
+use serenity::model::channel::{GuildChannel, Message, Reaction};
+use serenity::model::event::{InviteCreateEvent, InviteDeleteEvent, ResumedEvent};
 use serenity::model::gateway::Ready;
 use serenity::model::guild::{Member, Role};
 use serenity::model::id::{GuildId, RoleId};
 use serenity::model::user::User;
 use serenity::model::voice::VoiceState;
 use serenity::prelude::*;
-use tracing::info;
+use tracing::{debug, info, warn};
+
+use crate::modules::BotModule;
 
 /// Bot event handler
 pub struct Handler {
     discord_signal: DiscordSignal,
     main_guild_id: u64,
+    /// Enabled feature modules, which message component interactions are dispatched to
+    modules: Arc<Vec<Box<dyn BotModule>>>,
 }
 
 impl Handler {
     /// Create a new handler
-    pub fn new(discord_signal: DiscordSignal) -> Self {
+    pub fn new(discord_signal: DiscordSignal, modules: Arc<Vec<Box<dyn BotModule>>>) -> Self {
         let main_guild_id: u64 = env::var("MAIN_GUILD")
             .expect("Expected main guild id in the environment")
             .parse()
             .expect("Invalid main guild id");
-        Self { discord_signal, main_guild_id }
+        Self { discord_signal, main_guild_id, modules }
     }
 
-    /// Broadcast a `DiscordEvent`
+    /// Broadcast a `DiscordEvent`, it is dropped if the main guild isn't cached yet
     fn send_event(&self, ctx: &Context, event: DiscordEvent) {
-        let ctx = DiscordContext::new(ctx, self.main_guild_id);
-        self.discord_signal.signal((ctx, event));
+        match DiscordContext::new(ctx, self.main_guild_id) {
+            Some(ctx) => {
+                self.discord_signal.signal((ctx, event));
+            }
+            None => debug!(shard = ctx.shard_id, "Dropped event received before the main guild is cached"),
+        }
     }
 }
 
 #[async_trait]
 impl EventHandler for Handler {
     async fn ready(&self, ctx: Context, ready: Ready) {
-        info!("Connected as {}", ready.user.name);
+        info!(shard = ?ready.shard, "Connected as {}", ready.user.name);
+        // Only the shard the main guild is on signals that the bot is ready
+        if let Some([shard_id, shard_count]) = ready.shard {
+            if shard_id != util::discord::guild_shard_id(self.main_guild_id, shard_count) {
+                return;
+            }
+        }
+        if let Err(why) = crate::util::slash::register(&ctx, GuildId(self.main_guild_id)).await {
+            warn!("{:#}", why);
+        }
         // Ensures main guild is cached when event is sent
         std::thread::sleep(std::time::Duration::from_secs(2));
         self.send_event(&ctx, DiscordEvent::Ready);
@@ -53,6 +72,14 @@ impl EventHandler for Handler {
         self.send_event(&ctx, DiscordEvent::Message { message: Box::new(msg) });
     }
 
+    async fn reaction_add(&self, ctx: Context, reaction: Reaction) {
+        self.send_event(&ctx, DiscordEvent::ReactionAdd { reaction });
+    }
+
+    async fn reaction_remove(&self, ctx: Context, reaction: Reaction) {
+        self.send_event(&ctx, DiscordEvent::ReactionRemove { reaction });
+    }
+
     async fn voice_state_update(&self, ctx: Context, old: Option<VoiceState>, new: VoiceState) {
         let event = match old {
             Some(old) => {
@@ -91,4 +118,43 @@ impl EventHandler for Handler {
     ) {
         self.send_event(&ctx, DiscordEvent::MemberLeave { user, guild_id, member });
     }
+
+    async fn invite_create(&self, ctx: Context, data: InviteCreateEvent) {
+        self.send_event(&ctx, DiscordEvent::InviteCreate { invite: data });
+    }
+
+    async fn invite_delete(&self, ctx: Context, data: InviteDeleteEvent) {
+        self.send_event(&ctx, DiscordEvent::InviteDelete { invite: data });
+    }
+
+    async fn interaction_create(&self, ctx: Context, interaction: Interaction) {
+        match interaction {
+            Interaction::Autocomplete(interaction) => {
+                if let Err(why) = crate::util::autocomplete::respond(&ctx, &interaction).await {
+                    warn!("Failed to autocomplete: {:#}", why);
+                }
+            }
+            Interaction::ApplicationCommand(interaction) => {
+                if let Err(why) = crate::util::slash::respond(&ctx, &interaction).await {
+                    warn!("Failed to respond to slash command: {:#}", why);
+                }
+            }
+            Interaction::MessageComponent(interaction) => {
+                if let Err(why) = crate::util::weekly_reset::respond(&ctx, &interaction).await {
+                    warn!("Failed to respond to weekly reset button: {:#}", why);
+                }
+                if let Err(why) = crate::util::suggestion::respond(&ctx, &interaction).await {
+                    warn!("Failed to respond to suggestion vote button: {:#}", why);
+                }
+                if let Err(why) = crate::util::xp_correction::respond(&ctx, &interaction).await {
+                    warn!("Failed to respond to xp correction button: {:#}", why);
+                }
+                if let Err(why) = crate::util::link_conflict::respond(&ctx, &interaction).await {
+                    warn!("Failed to respond to link conflict button: {:#}", why);
+                }
+                crate::modules::dispatch_interaction(&self.modules, &ctx, &interaction).await;
+            }
+            _ => {}
+        }
+    }
 }
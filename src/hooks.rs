@@ -9,7 +9,9 @@ use tracing::{error, info};
 use util::{ok, some};
 
 #[hook]
-pub async fn before(ctx: &Context, msg: &Message, _: &str) -> bool {
+pub async fn before(ctx: &Context, msg: &Message, command_name: &str) -> bool {
+    let cid = crate::correlation::start_command();
+    crate::report::command_breadcrumb(command_name, &cid);
     let channel = ok!(msg.channel(&ctx).await, return false);
 
     // If a command is called in a guild channel, then this check is performed to determine if that
@@ -21,12 +23,24 @@ pub async fn before(ctx: &Context, msg: &Message, _: &str) -> bool {
         let kind = PermissionOverwriteType::Member(ctx.cache.current_user_id());
         let allow = util::discord::check_channel_allow(&guild, &channel, kind, Permissions::SEND_MESSAGES);
         if allow {
-            info!("Invoking command '{}' by user '{}' in '{}'", msg.content, msg.author.name, channel);
+            info!(
+                content = msg.content,
+                author = msg.author.name,
+                %channel,
+                "Invoking command '{}'",
+                command_name
+            );
         }
         return allow;
     }
 
-    info!("Invoking command '{}' by user '{}' in '{}'", msg.content, msg.author.name, channel);
+    info!(
+        content = msg.content,
+        author = msg.author.name,
+        %channel,
+        "Invoking command '{}'",
+        command_name
+    );
     true
 }
 
@@ -34,10 +48,20 @@ pub async fn before(ctx: &Context, msg: &Message, _: &str) -> bool {
 pub async fn after(ctx: &Context, msg: &Message, command_name: &str, command_result: CommandResult) {
     // Report unhandled error
     if let Err(why) = command_result {
-        error!("Command '{}' returned error: {}", command_name, why);
-        let _ = msg
-            .reply(&ctx, "**Encountered an unexpected error when running command**")
-            .await;
+        let cid = crate::correlation::current();
+        // The tags are attached to the reported error
+        error!(
+            tags.command = command_name,
+            tags.cid = cid.as_deref().unwrap_or("none"),
+            "Command '{}' returned error: {}",
+            command_name,
+            why
+        );
+        let content = match cid {
+            Some(cid) => format!("**Encountered an unexpected error when running command** (id: `{}`)", cid),
+            None => "**Encountered an unexpected error when running command**".to_string(),
+        };
+        let _ = msg.reply(&ctx, content).await;
     }
 }
 
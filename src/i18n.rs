@@ -0,0 +1,366 @@
+//! Translations of user-facing command responses
+//!
+//! Each response is a variant of [`Msg`], which has a template for every [`Locale`].
+//! The locale is selected per guild through [`Config::locales`], and [`tr`] is used to get the
+//! response in that locale.
+//! ```
+//! # use haxbotjr::tr;
+//! use config::locale::Locale;
+//!
+//! assert_eq!(tr!(Locale::En, MemberRemoved), "Successfully removed member");
+//! assert_eq!(tr!(Locale::Fr, InvalidDuration, "2x"), "'2x' n'est pas une durée valide");
+//! ```
+//!
+//! [`Config::locales`]: config::Config::locales
+use std::env;
+use std::fmt::Display;
+use std::sync::Arc;
+
+use serenity::client::Context;
+use serenity::model::channel::Message;
+
+use config::locale::Locale;
+use config::Config;
+use util::string::StatFormat;
+
+/// Get the response template of a [`Msg`], with its `{}` placeholders filled in by the arguments.
+/// ```
+/// # use haxbotjr::tr;
+/// use config::locale::Locale;
+///
+/// let response: &str = tr!(Locale::En, NoProfiles);
+/// let response: String = tr!(Locale::En, TempRankChanged, "1 day");
+/// ```
+#[macro_export]
+macro_rules! tr {
+    ($locale:expr, $msg:ident) => {
+        $crate::i18n::Msg::$msg.get($locale)
+    };
+    ($locale:expr, $msg:ident, $($arg:expr),+) => {{
+        let s: String = $crate::i18n::Msg::$msg.fmt($locale, &[$(&$arg),+]);
+        s
+    }};
+}
+
+macro_rules! messages {
+    ($($name:ident { en: $en:literal, fr: $fr:literal $(,)? })*) => {
+        /// User-facing command responses
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub enum Msg {
+            $($name,)*
+        }
+
+        impl Msg {
+            /// Get the response template in given locale
+            pub fn get(self, locale: Locale) -> &'static str {
+                match (self, locale) {
+                    $(
+                        (Self::$name, Locale::En) => $en,
+                        (Self::$name, Locale::Fr) => $fr,
+                    )*
+                }
+            }
+        }
+    };
+}
+
+impl Msg {
+    /// Get the response in given locale, with its `{}` placeholders replaced by `args` in order
+    pub fn fmt(self, locale: Locale, args: &[&dyn Display]) -> String {
+        let mut parts = self.get(locale).split("{}");
+        let mut s = parts.next().unwrap_or_default().to_string();
+        for (i, part) in parts.enumerate() {
+            if let Some(arg) = args.get(i) {
+                s.push_str(&arg.to_string());
+            }
+            s.push_str(part);
+        }
+        s
+    }
+}
+
+/// Get the locale of the guild a message is sent in
+pub async fn locale(ctx: &Context, msg: &Message) -> Locale {
+    let config = {
+        let data = ctx.data.read().await;
+        data.get::<Config>().map(Arc::clone)
+    };
+    match config {
+        Some(config) => config.read().await.locale(msg.guild_id.map(|id| id.0)),
+        None => Locale::default(),
+    }
+}
+
+/// Get how stats are displayed to the author of a message, see [`Config::stat_format`]
+pub async fn stat_format(ctx: &Context, msg: &Message) -> StatFormat {
+    let config = {
+        let data = ctx.data.read().await;
+        data.get::<Config>().map(Arc::clone)
+    };
+    match config {
+        Some(config) => config.read().await.stat_format(msg.guild_id.map(|id| id.0), msg.author.id.0),
+        None => StatFormat::default(),
+    }
+}
+
+/// Get how stats are displayed in the logs and reports the bot posts on its own, which is the
+/// format of the main guild, see [`Config::guild_stat_format`]
+pub fn main_stat_format(config: &Config) -> StatFormat {
+    let main_guild = env::var("MAIN_GUILD").ok().and_then(|id| id.parse().ok());
+    config.guild_stat_format(main_guild)
+}
+
+messages! {
+    // Member management
+    ProfilesSameMember {
+        en: "Both profiles are already linked to the same member",
+        fr: "Les deux profils sont déjà liés au même membre",
+    }
+    ProfileAlreadyLinked {
+        en: "At least one of the provided profiles is already linked to a member. If you want to update / add \
+profiles on an existing member, use the command `link` instead",
+        fr: "Au moins un des profils donnés est déjà lié à un membre. Pour modifier / ajouter des profils \
+à un membre existant, utilisez plutôt la commande `link`",
+    }
+    MemberAdded {
+        en: "Successfully added member",
+        fr: "Membre ajouté avec succès",
+    }
+    MemberAddFailed {
+        en: "Failed to add member",
+        fr: "Échec de l'ajout du membre",
+    }
+    ProfilesBothUnlinked {
+        en: "Both profiles are unlinked. If you want to add a new member, use the command `addMember` instead",
+        fr: "Aucun des deux profils n'est lié. Pour ajouter un nouveau membre, utilisez plutôt la commande \
+`addMember`",
+    }
+    ProfilesBothLinked {
+        en: "Both profiles are linked. If you want to link both profiles to the same member, \
+unlink one of them first, then call this command again",
+        fr: "Les deux profils sont liés. Pour lier les deux profils au même membre, \
+déliez d'abord l'un d'eux, puis relancez cette commande",
+    }
+    DiscordLinked {
+        en: "Successfully linked discord user to member",
+        fr: "Utilisateur discord lié au membre avec succès",
+    }
+    DiscordLinkFailed {
+        en: "Failed to link discord user to member",
+        fr: "Échec de la liaison de l'utilisateur discord au membre",
+    }
+    WynnLinkExists {
+        en: "The discord user `{}` already has a mc account `{}` linked to them, which can't be changed",
+        fr: "L'utilisateur discord `{}` a déjà un compte mc `{}` lié, qui ne peut pas être changé",
+    }
+    WynnLinked {
+        en: "Successfully linked mc user to member",
+        fr: "Compte mc lié au membre avec succès",
+    }
+    WynnLinkFailed {
+        en: "Failed to link mc user to member",
+        fr: "Échec de la liaison du compte mc au membre",
+    }
+    InvalidPartialType {
+        en: "Invalid partial member type (need to be discord or wynn)",
+        fr: "Type de membre partiel invalide (doit être discord ou wynn)",
+    }
+    DiscordUserNotFound {
+        en: "Failed to find discord user of given name",
+        fr: "Aucun utilisateur discord trouvé avec ce nom",
+    }
+    DiscordAlreadyMember {
+        en: "discord user already linked with a member",
+        fr: "l'utilisateur discord est déjà lié à un membre",
+    }
+    DiscordPartialAdded {
+        en: "Successfully added discord partial member",
+        fr: "Membre partiel discord ajouté avec succès",
+    }
+    DiscordPartialAddFailed {
+        en: "Failed to add discord partial member",
+        fr: "Échec de l'ajout du membre partiel discord",
+    }
+    GuestAdded {
+        en: "Successfully added guest",
+        fr: "Invité ajouté avec succès",
+    }
+    GuestAddFailed {
+        en: "Failed to add guest",
+        fr: "Échec de l'ajout de l'invité",
+    }
+    GuestWynnLink {
+        en: "You can't link a mc account to a guest, remove them and add them as a member instead",
+        fr: "Vous ne pouvez pas lier un compte mc à un invité, retirez-le et ajoutez-le en tant que membre",
+    }
+    IgnNotFound {
+        en: "Provided ign doesn't exist",
+        fr: "L'ign donné n'existe pas",
+    }
+    McAlreadyMember {
+        en: "mc account already linked with a member",
+        fr: "le compte mc est déjà lié à un membre",
+    }
+    WynnPartialAdded {
+        en: "Successfully added wynn partial member",
+        fr: "Membre partiel wynn ajouté avec succès",
+    }
+    WynnPartialAddFailed {
+        en: "Failed to add wynn partial member",
+        fr: "Échec de l'ajout du membre partiel wynn",
+    }
+    InvalidProfileType {
+        en: "Invalid profile type (need to be discord or wynn)",
+        fr: "Type de profil invalide (doit être discord ou wynn)",
+    }
+    NoDiscordLink {
+        en: "There is no linked discord profile for the command to unlink",
+        fr: "Il n'y a aucun profil discord lié à délier",
+    }
+    DiscordUnlinked {
+        en: "Successfully unlinked discord profile",
+        fr: "Profil discord délié avec succès",
+    }
+    DiscordUnlinkFailed {
+        en: "Failed to unlink discord profile from member",
+        fr: "Échec de la déliaison du profil discord du membre",
+    }
+    NoWynnLink {
+        en: "There is no linked wynn profile for the command to unlink",
+        fr: "Il n'y a aucun profil wynn lié à délier",
+    }
+    GuildPartialUnlink {
+        en: "You can't unlink wynn profile of a guild partial member",
+        fr: "Vous ne pouvez pas délier le profil wynn d'un membre partiel de guilde",
+    }
+    WynnUnlinked {
+        en: "Successfully unlinked wynn profile",
+        fr: "Profil wynn délié avec succès",
+    }
+    WynnUnlinkFailed {
+        en: "Failed to unlink wynn profile from member",
+        fr: "Échec de la déliaison du profil wynn du membre",
+    }
+    GuildPartialRemove {
+        en: "You can't remove a guild partial with this command",
+        fr: "Vous ne pouvez pas retirer un membre partiel de guilde avec cette commande",
+    }
+    MemberRemoved {
+        en: "Successfully removed member",
+        fr: "Membre retiré avec succès",
+    }
+    MemberRemoveFailed {
+        en: "Failed to remove member",
+        fr: "Échec du retrait du membre",
+    }
+    SameRank {
+        en: "Member is already specified rank",
+        fr: "Le membre a déjà ce rang",
+    }
+    GuestRankChange {
+        en: "You can't change the rank of a guest",
+        fr: "Vous ne pouvez pas changer le rang d'un invité",
+    }
+    MemberOnly {
+        en: "Only a member can use this command",
+        fr: "Seul un membre peut utiliser cette commande",
+    }
+    TargetRankTooHigh {
+        en: "You can't change the rank of someone with a higher or equal rank to yours",
+        fr: "Vous ne pouvez pas changer le rang de quelqu'un ayant un rang supérieur ou égal au vôtre",
+    }
+    RankTooHigh {
+        en: "You can't set someone else to a rank that is higher or equal to yours",
+        fr: "Vous ne pouvez pas donner à quelqu'un un rang supérieur ou égal au vôtre",
+    }
+    TempRankChanged {
+        en: "Successfully changed member's rank for {}",
+        fr: "Rang du membre changé avec succès pour {}",
+    }
+    RankChanged {
+        en: "Successfully changed member's rank",
+        fr: "Rang du membre changé avec succès",
+    }
+    RankChangeFailed {
+        en: "Failed to change member's rank",
+        fr: "Échec du changement de rang du membre",
+    }
+    MemberChangedConcurrently {
+        en: "The member was changed by someone else in the meantime, please retry",
+        fr: "Le membre a été modifié par quelqu'un d'autre entre-temps, veuillez réessayer",
+    }
+    InvalidDuration {
+        en: "'{}' isn't a valid duration",
+        fr: "'{}' n'est pas une durée valide",
+    }
+    HighestRank {
+        en: "Member is already the highest rank",
+        fr: "Le membre a déjà le rang le plus élevé",
+    }
+    LowestRank {
+        en: "Member is already the lowest rank",
+        fr: "Le membre a déjà le rang le plus bas",
+    }
+    PromotionVoteExists {
+        en: "There is already an open promotion vote for this member",
+        fr: "Il y a déjà un vote de promotion en cours pour ce membre",
+    }
+    NoPromotionVoteChannel {
+        en: "There is no channel tagged with `PromotionVote` to hold the vote in",
+        fr: "Aucun salon n'a le tag `PromotionVote` pour y tenir le vote",
+    }
+    PromotionVoteOpened {
+        en: "Promotion vote opened in {}",
+        fr: "Vote de promotion ouvert dans {}",
+    }
+
+    // Member statistics
+    NoProfiles {
+        en: "No profiles found",
+        fr: "Aucun profil trouvé",
+    }
+    UnlinkedProfile {
+        en: "This is an unlinked profile",
+        fr: "Ce profil n'est lié à aucun membre",
+    }
+    NoCommonStats {
+        en: "They have no stats in common",
+        fr: "Ils n'ont aucune statistique en commun",
+    }
+    NoMembers {
+        en: "Found 0 member",
+        fr: "Aucun membre trouvé",
+    }
+    ChannelVoiceOnly {
+        en: "Only the `voice` stat can be limited to a channel",
+        fr: "Seule la statistique `voice` peut être limitée à un salon",
+    }
+    ChannelNotFound {
+        en: "Failed to find the channel",
+        fr: "Salon introuvable",
+    }
+    EmptyLeaderboard {
+        en: "leaderboard empty",
+        fr: "classement vide",
+    }
+    NoColumns {
+        en: "No columns specified",
+        fr: "Aucune colonne spécifiée",
+    }
+
+    GroupBySyntax {
+        en: "Grouping is written as `group by <column>`",
+        fr: "Le regroupement s'écrit `group by <colonne>`",
+    }
+
+    GroupedColumns {
+        en: "When grouping or using `count`, `sum(...)` or `avg(...)`, the other columns and sorts can only be the grouped column",
+        fr: "Lors d'un regroupement ou de l'utilisation de `count`, `sum(...)` ou `avg(...)`, les autres colonnes et tris ne peuvent être que la colonne regroupée",
+    }
+
+    // Configuration
+    LocaleSet {
+        en: "Bot responses in this server are now in English",
+        fr: "Les réponses du bot sur ce serveur sont maintenant en français",
+    }
+}
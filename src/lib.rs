@@ -1,27 +1,44 @@
 //! Bot utilities
 pub mod checks;
 pub mod commands;
+pub mod correlation;
 pub mod data;
+pub mod forward;
 pub mod handler;
 pub mod hooks;
+pub mod i18n;
+pub mod linked_roles;
+pub mod log_level;
 pub mod logging;
 pub mod loops;
+pub mod modules;
+pub mod observer;
+pub mod redact;
+pub mod report;
+pub mod secrets;
+pub mod tasks;
 pub mod util;
 
 use std::collections::HashSet;
 use std::env;
 use std::sync::Arc;
 
+use config::Config;
 use event::DiscordSignal;
+use serenity::cache::Cache;
 use serenity::client::ClientBuilder;
 use serenity::framework::StandardFramework;
+use serenity::futures::future::BoxFuture;
 use serenity::http::Http;
+use serenity::model::channel::Message;
 use serenity::model::guild::Guild;
 use serenity::model::id::UserId;
 use serenity::prelude::*;
 use tracing::info;
 
+use crate::correlation::TracedFramework;
 use crate::handler::Handler;
+use crate::modules::BotModule;
 
 /// Get the owners of this bot
 pub async fn get_owners(http: &Http) -> HashSet<UserId> {
@@ -40,30 +57,73 @@ pub async fn get_owners(http: &Http) -> HashSet<UserId> {
     }
 }
 
+/// Get the command prefix of a guild, which is the one set in the config, or the
+/// `COMMAND_PREFIX` env var if the guild doesn't have one
+pub async fn command_prefix(config: &RwLock<Config>, guild_id: Option<u64>) -> String {
+    let config = config.read().await;
+    match config.prefix(guild_id) {
+        Some(prefix) => prefix.to_string(),
+        None => env::var("COMMAND_PREFIX").unwrap_or_default(),
+    }
+}
+
+/// Check if a message calls a command, which is when it starts with the command prefix of its
+/// guild or a mention of the bot
+pub async fn is_command(cache: &Cache, config: &RwLock<Config>, message: &Message) -> bool {
+    let bot_id = cache.current_user_id();
+    let content = message.content.trim_start();
+    if content.starts_with(&format!("<@{}>", bot_id)) || content.starts_with(&format!("<@!{}>", bot_id)) {
+        return true;
+    }
+    let prefix = command_prefix(config, message.guild_id.map(|id| id.0)).await;
+    !prefix.is_empty() && content.starts_with(&prefix)
+}
+
+/// Dynamic prefix hook of the framework, resolves the prefix with [`command_prefix`]
+fn dynamic_prefix<'fut>(ctx: &'fut Context, msg: &'fut Message) -> BoxFuture<'fut, Option<String>> {
+    Box::pin(async move {
+        let config = ctx.data.read().await.get::<Config>()?.clone();
+        Some(command_prefix(&config, msg.guild_id.map(|id| id.0)).await)
+    })
+}
+
 /// Build a framework with hooks, prefix and owners already configured
+///
+/// Commands are called with the guild's prefix (see [`command_prefix`]) or by mentioning the bot.
 pub async fn my_framework(http: &Http) -> StandardFramework {
     let owners = get_owners(http).await;
-    let prefix = env::var("COMMAND_PREFIX").expect("Expected command prefix in the environment");
+    env::var("COMMAND_PREFIX").expect("Expected command prefix in the environment");
+    let bot_id = match http.get_current_user().await {
+        Ok(user) => user.id,
+        Err(why) => panic!("Could not access bot user: {:?}", why),
+    };
     StandardFramework::new()
-        .configure(|c| c.owners(owners).prefix(prefix))
+        // The static prefix is cleared, so it doesn't match in guilds with their own prefix
+        .configure(|c| c.owners(owners).prefix("").on_mention(Some(bot_id)).dynamic_prefix(dynamic_prefix))
         .before(crate::hooks::before)
         .after(crate::hooks::after)
         .unrecognised_command(crate::hooks::unknown_command)
         .on_dispatch_error(crate::hooks::dispatch_error)
 }
 
-/// Build a client with intents and event handler already configured
-pub fn my_client(token: &str, framework: StandardFramework, discord_signal: DiscordSignal) -> ClientBuilder {
+/// Build a client with intents and event handler already configured, message component
+/// interactions are dispatched to `modules`
+pub fn my_client(
+    token: &str, framework: StandardFramework, discord_signal: DiscordSignal,
+    modules: Arc<Vec<Box<dyn BotModule>>>,
+) -> ClientBuilder {
     let intents = GatewayIntents::GUILDS
         | GatewayIntents::GUILD_MESSAGES
+        | GatewayIntents::GUILD_MESSAGE_REACTIONS
         | GatewayIntents::GUILD_MEMBERS
         | GatewayIntents::DIRECT_MESSAGES
         | GatewayIntents::GUILD_PRESENCES
         | GatewayIntents::MESSAGE_CONTENT
-        | GatewayIntents::GUILD_VOICE_STATES;
+        | GatewayIntents::GUILD_VOICE_STATES
+        | GatewayIntents::GUILD_INVITES;
     Client::builder(token, intents)
-        .framework(framework)
-        .event_handler(Handler::new(discord_signal))
+        .framework(TracedFramework(framework))
+        .event_handler(Handler::new(discord_signal, modules))
 }
 
 /// This function waits for the bot to be ready, and return the main guild object (as specified by
@@ -0,0 +1,531 @@
+//! Exposing member data through discord's linked roles, configured by
+//! [`LinkedRoles`](config::linked_roles::LinkedRoles).
+//!
+//! Users link their account by opening `/linked-role` of the OAuth2 server, which redirects them
+//! to discord's authorization page and back to `/callback`. Their tokens are then stored in the
+//! database, and their role connection metadata is pushed to discord periodically.
+use std::env;
+use std::fmt;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anyhow::{bail, Context, Result};
+use rand::rngs::OsRng;
+use rand::RngCore;
+use reqwest::{Client, Response, StatusCode, Url};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use serenity::http::Http;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::RwLock;
+use tokio::time;
+use tracing::{info, warn};
+
+use config::Config;
+use memberdb::model::discord::DiscordId;
+use memberdb::role_connection::{self, RoleConnection, RoleMetadata};
+use memberdb::DB;
+use util::task::{RestartPolicy, Spawner};
+use util::{ctx, ok, some};
+
+const API: &str = "https://discord.com/api/v10";
+/// Timeout of requests to the discord api
+const TIMEOUT: Duration = Duration::from_secs(10);
+/// Timeout of reading a request sent to the OAuth2 server
+const READ_TIMEOUT: Duration = Duration::from_secs(10);
+/// Max size of a request sent to the OAuth2 server
+const MAX_REQUEST_SIZE: usize = 8192;
+/// Access tokens expiring within this amount of seconds are refreshed before being used
+const REFRESH_MARGIN: i64 = 300;
+
+#[derive(Debug, Serialize)]
+/// A role connection metadata record, see discord's documentation for the types
+struct MetadataRecord {
+    key: &'static str,
+    name: &'static str,
+    description: &'static str,
+    #[serde(rename = "type")]
+    kind: u8,
+}
+
+/// Metadata records of the application
+const METADATA: [MetadataRecord; 3] = [
+    MetadataRecord {
+        key: "weekly_xp",
+        name: "Weekly XP",
+        description: "Guild xp contributed this week greater than",
+        // INTEGER_GREATER_THAN_OR_EQUAL
+        kind: 2,
+    },
+    MetadataRecord {
+        key: "guild_rank",
+        name: "Guild Rank",
+        description: "Guild rank at least (1 = recruit, 6 = owner)",
+        kind: 2,
+    },
+    MetadataRecord {
+        key: "in_guild",
+        name: "In Guild",
+        description: "Is in the guild",
+        // BOOLEAN_EQUAL
+        kind: 7,
+    },
+];
+
+#[derive(Debug, Clone)]
+/// The bot's application and its OAuth2 credentials
+struct Application {
+    id: u64,
+    secret: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Error of a request authorized by a user, the user revoked their authorization
+struct Revoked;
+
+impl fmt::Display for Revoked {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("User revoked their authorization")
+    }
+}
+
+impl std::error::Error for Revoked {}
+
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    refresh_token: String,
+    expires_in: i64,
+}
+
+/// Register the metadata records, and start the OAuth2 server and the metadata pushing loop if
+/// linked roles are enabled.
+pub async fn start_linked_roles(
+    spawner: &impl Spawner, client: Client, http: &Http, bot_token: &str, client_secret: Option<String>,
+    config: Arc<RwLock<Config>>, db: Arc<RwLock<DB>>,
+) {
+    let (enabled, address) = {
+        let config = config.read().await;
+        (config.linked_roles.enabled, config.linked_roles.address.clone())
+    };
+    if !enabled {
+        return;
+    }
+    let secret = some!(client_secret, {
+        warn!("Linked roles require DISCORD_CLIENT_SECRET");
+        return;
+    });
+    let info = ok!(http.get_current_application_info().await, "Failed to get application info", return);
+    let app = Application { id: info.id.0, secret };
+    if let Err(why) = register_metadata(&client, app.id, bot_token).await {
+        warn!("Failed to register role connection metadata: {:#}", why);
+        return;
+    }
+
+    let shared_client = client.clone();
+    let shared_config = Arc::clone(&config);
+    let shared_db = Arc::clone(&db);
+    let shared_app = app.clone();
+    spawner.spawn("linked roles server", RestartPolicy::Always, move || {
+        let client = shared_client.clone();
+        let config = shared_config.clone();
+        let db = shared_db.clone();
+        let app = shared_app.clone();
+        let address = address.clone();
+        async move {
+            info!(address, "Starting linked roles OAuth2 server");
+            let listener =
+                ok!(TcpListener::bind(&address).await, "Failed to bind linked roles server", return);
+            loop {
+                let (stream, _) = ok!(listener.accept().await, "Failed to accept connection", continue);
+                let client = client.clone();
+                let config = config.clone();
+                let db = db.clone();
+                let app = app.clone();
+                tokio::spawn(async move {
+                    if let Err(why) = handle_connection(stream, &client, &config, &db, &app).await {
+                        warn!("Failed to handle linked roles request: {:#}", why);
+                    }
+                });
+            }
+        }
+    });
+
+    spawner.spawn("linked roles metadata pushing", RestartPolicy::Always, move || {
+        let client = client.clone();
+        let config = config.clone();
+        let db = db.clone();
+        let app = app.clone();
+        async move {
+            info!("Starting linked roles metadata pushing loop");
+            let push_interval = {
+                let config = config.read().await;
+                config.linked_roles.push_interval.max(60)
+            };
+            let mut interval = time::interval(Duration::from_secs(push_interval));
+            loop {
+                interval.tick().await;
+                let conns = {
+                    let db = db.read().await;
+                    ok!(
+                        role_connection::role_connections(&db).await,
+                        "Failed to get role connections",
+                        continue
+                    )
+                };
+                for conn in conns {
+                    if let Err(why) = update_connection(&client, &db, &app, conn).await {
+                        warn!("Failed to push role connection metadata: {:#}", why);
+                    }
+                }
+            }
+        }
+    });
+}
+
+/// Replace the application's metadata records with [`METADATA`]
+async fn register_metadata(client: &Client, app_id: u64, bot_token: &str) -> Result<()> {
+    client
+        .put(format!("{}/applications/{}/role-connections/metadata", API, app_id))
+        .timeout(TIMEOUT)
+        .header("Authorization", format!("Bot {}", bot_token))
+        .json(&METADATA)
+        .send()
+        .await
+        .context("Failed to send metadata records")?
+        .error_for_status()
+        .context("Discord rejected metadata records")?;
+    Ok(())
+}
+
+/// Get the url of discord's authorization page
+/// ```
+/// use haxbotjr::linked_roles::authorize_url;
+///
+/// let url = authorize_url(123, "https://bot.example.com/callback", "abc");
+/// assert!(url.starts_with("https://discord.com/oauth2/authorize?client_id=123&"));
+/// assert!(url.contains("redirect_uri=https%3A%2F%2Fbot.example.com%2Fcallback"));
+/// assert!(url.contains("scope=role_connections.write+identify"));
+/// assert!(url.ends_with("&state=abc"));
+/// ```
+pub fn authorize_url(client_id: u64, redirect_uri: &str, state: &str) -> String {
+    let client_id = client_id.to_string();
+    Url::parse_with_params(
+        "https://discord.com/oauth2/authorize",
+        &[
+            ("client_id", client_id.as_str()),
+            ("redirect_uri", redirect_uri),
+            ("response_type", "code"),
+            ("scope", "role_connections.write identify"),
+            ("prompt", "consent"),
+            ("state", state),
+        ],
+    )
+    .map(String::from)
+    .unwrap_or_default()
+}
+
+/// Respond to a request sent to the OAuth2 server
+async fn handle_connection(
+    mut stream: TcpStream, client: &Client, config: &RwLock<Config>, db: &RwLock<DB>, app: &Application,
+) -> Result<()> {
+    let request = ctx!(time::timeout(READ_TIMEOUT, read_request(&mut stream)).await, "Request timed out")??;
+    let request_line = request.lines().next().unwrap_or_default();
+    let target = match request_line.split(' ').collect::<Vec<&str>>()[..] {
+        ["GET", target, _] => target,
+        _ => return respond(&mut stream, "405 Method Not Allowed", "", "Method not allowed").await,
+    };
+    let url = match parse_target(target) {
+        Some(url) => url,
+        None => return respond(&mut stream, "400 Bad Request", "", "Invalid request target").await,
+    };
+
+    match url.path() {
+        "/linked-role" => {
+            let state = random_state();
+            let redirect_uri = {
+                let config = config.read().await;
+                config.linked_roles.redirect_uri()
+            };
+            let headers = format!(
+                "Location: {}\r\nSet-Cookie: state={}; Max-Age=600; HttpOnly; Secure; SameSite=Lax\r\n",
+                authorize_url(app.id, &redirect_uri, &state),
+                state
+            );
+            respond(&mut stream, "302 Found", &headers, "Redirecting to discord").await
+        }
+        "/callback" => {
+            let code = query_param(&url, "code");
+            let state = query_param(&url, "state");
+            let cookie = request
+                .lines()
+                .filter_map(|line| line.split_once(':'))
+                .filter(|(name, _)| name.eq_ignore_ascii_case("cookie"))
+                .flat_map(|(_, value)| value.split(';'))
+                .find_map(|cookie| cookie.trim().strip_prefix("state="));
+            let code = match (code, state, cookie) {
+                (Some(code), Some(state), Some(cookie)) if !state.is_empty() && state == cookie => code,
+                _ => return respond(&mut stream, "403 Forbidden", "", "Invalid OAuth2 state").await,
+            };
+            match link_user(client, config, db, app, &code).await {
+                Ok(()) => {
+                    respond(&mut stream, "200 OK", "", "Your account is linked, you can close this page")
+                        .await
+                }
+                Err(why) => {
+                    warn!("Failed to link user: {:#}", why);
+                    respond(&mut stream, "500 Internal Server Error", "", "Failed to link your account").await
+                }
+            }
+        }
+        _ => respond(&mut stream, "404 Not Found", "", "Not found").await,
+    }
+}
+
+/// Read the request line and headers of a request
+async fn read_request(stream: &mut TcpStream) -> Result<String> {
+    let mut buf = Vec::new();
+    let mut chunk = [0; 1024];
+    while !buf.windows(4).any(|w| w == b"\r\n\r\n") {
+        if buf.len() >= MAX_REQUEST_SIZE {
+            bail!("Request is too large");
+        }
+        let n = ctx!(stream.read(&mut chunk).await, "Failed to read request")?;
+        if n == 0 {
+            bail!("Connection closed before the request ended");
+        }
+        buf.extend_from_slice(&chunk[..n]);
+    }
+    Ok(String::from_utf8_lossy(&buf).into_owned())
+}
+
+async fn respond(stream: &mut TcpStream, status: &str, headers: &str, body: &str) -> Result<()> {
+    let response = format!(
+        "HTTP/1.1 {}\r\n{}Content-Type: text/plain; charset=utf-8\r\nContent-Length: {}\r\n\
+        Connection: close\r\n\r\n{}",
+        status,
+        headers,
+        body.len(),
+        body
+    );
+    ctx!(stream.write_all(response.as_bytes()).await, "Failed to write response")?;
+    Ok(())
+}
+
+/// Parse the target of a request, which is its path and query string
+/// ```
+/// use haxbotjr::linked_roles::{parse_target, query_param};
+///
+/// let url = parse_target("/callback?code=a%2Fb+c&state=d").unwrap();
+/// assert_eq!(url.path(), "/callback");
+/// assert_eq!(query_param(&url, "code").as_deref(), Some("a/b c"));
+/// assert_eq!(query_param(&url, "state").as_deref(), Some("d"));
+/// assert_eq!(query_param(&url, "stat"), None);
+/// ```
+pub fn parse_target(target: &str) -> Option<Url> {
+    if !target.starts_with('/') {
+        return None;
+    }
+    Url::parse("http://localhost").ok()?.join(target).ok()
+}
+
+/// Get a percent-decoded parameter from the query string of a url
+pub fn query_param(url: &Url, name: &str) -> Option<String> {
+    url.query_pairs().find(|(key, _)| key == name).map(|(_, value)| value.into_owned())
+}
+
+/// Generate an unguessable OAuth2 state from the OS's random source
+fn random_state() -> String {
+    let mut bytes = [0; 16];
+    OsRng.fill_bytes(&mut bytes);
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn now() -> i64 {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+    i64::try_from(now.as_secs()).unwrap_or(i64::MAX)
+}
+
+/// Exchange an OAuth2 code for tokens, store them and push the user's metadata
+async fn link_user(
+    client: &Client, config: &RwLock<Config>, db: &RwLock<DB>, app: &Application, code: &str,
+) -> Result<()> {
+    let redirect_uri = {
+        let config = config.read().await;
+        config.linked_roles.redirect_uri()
+    };
+    let token = request_token(
+        client,
+        app,
+        &[("grant_type", "authorization_code"), ("code", code), ("redirect_uri", &redirect_uri)],
+    )
+    .await?;
+
+    #[derive(Deserialize)]
+    struct User {
+        id: String,
+    }
+    let user: User = client
+        .get(format!("{}/users/@me", API))
+        .timeout(TIMEOUT)
+        .bearer_auth(&token.access_token)
+        .send()
+        .await
+        .context("Failed to get current user")?
+        .error_for_status()
+        .context("Discord rejected current user request")?
+        .json()
+        .await
+        .context("Failed to parse current user")?;
+    let user = DiscordId(ctx!(user.id.parse(), "Invalid user id")?);
+
+    let conn = RoleConnection {
+        user,
+        access_token: token.access_token,
+        refresh_token: token.refresh_token,
+        expires: now().saturating_add(token.expires_in),
+    };
+    {
+        let db = db.write().await;
+        let mut tx = ctx!(db.begin().await)?;
+        ctx!(role_connection::set_role_connection(&mut tx, &conn).await)?;
+        ctx!(tx.commit().await)?;
+    }
+    info!(?user, "User linked their role connection");
+    update_connection(client, db, app, conn).await
+}
+
+async fn request_token(client: &Client, app: &Application, params: &[(&str, &str)]) -> Result<TokenResponse> {
+    let client_id = app.id.to_string();
+    let mut form = vec![("client_id", client_id.as_str()), ("client_secret", app.secret.as_str())];
+    form.extend_from_slice(params);
+    let response = client
+        .post(format!("{}/oauth2/token", API))
+        .timeout(TIMEOUT)
+        .form(&form)
+        .send()
+        .await
+        .context("Failed to request OAuth2 token")?;
+    check_user_response(response)
+        .await
+        .context("Discord rejected OAuth2 token request")?
+        .json()
+        .await
+        .context("Failed to parse OAuth2 token")
+}
+
+/// Push a user's metadata, refreshing their access token first if it's about to expire.
+///
+/// If the user revoked their authorization, their tokens are removed.
+async fn update_connection(
+    client: &Client, db: &RwLock<DB>, app: &Application, conn: RoleConnection,
+) -> Result<()> {
+    let conn = if conn.expires - REFRESH_MARGIN <= now() {
+        let token = request_token(
+            client,
+            app,
+            &[("grant_type", "refresh_token"), ("refresh_token", &conn.refresh_token)],
+        )
+        .await;
+        let token = match token {
+            Ok(token) => token,
+            Err(why) if why.is::<Revoked>() => return unlink_user(db, conn.user).await,
+            Err(why) => return Err(why),
+        };
+        let conn = RoleConnection {
+            user: conn.user,
+            access_token: token.access_token,
+            refresh_token: token.refresh_token,
+            expires: now().saturating_add(token.expires_in),
+        };
+        let db = db.write().await;
+        let mut tx = ctx!(db.begin().await)?;
+        ctx!(role_connection::set_role_connection(&mut tx, &conn).await)?;
+        ctx!(tx.commit().await)?;
+        conn
+    } else {
+        conn
+    };
+
+    let metadata = {
+        let db = db.read().await;
+        ctx!(role_connection::role_metadata(&mut db.exe(), conn.user).await)?
+    };
+    match push_metadata(client, app, &conn, &metadata).await {
+        Err(why) if why.is::<Revoked>() => unlink_user(db, conn.user).await,
+        result => result,
+    }
+}
+
+async fn push_metadata(
+    client: &Client, app: &Application, conn: &RoleConnection, metadata: &RoleMetadata,
+) -> Result<()> {
+    let platform_name = env::var("GUILD_NAME").unwrap_or_default();
+    let response = client
+        .put(format!("{}/users/@me/applications/{}/role-connection", API, app.id))
+        .timeout(TIMEOUT)
+        .bearer_auth(&conn.access_token)
+        .json(&json!({
+            "platform_name": platform_name,
+            "platform_username": metadata.ign,
+            "metadata": {
+                "weekly_xp": metadata.weekly_xp,
+                "guild_rank": metadata.rank_level(),
+                "in_guild": u8::from(metadata.in_guild()),
+            },
+        }))
+        .send()
+        .await
+        .context("Failed to push role connection metadata")?;
+    check_user_response(response).await.context("Discord rejected role connection metadata")?;
+    Ok(())
+}
+
+/// Fail if the response to a request authorized by a user isn't successful, with [`Revoked`] if
+/// the user revoked their authorization
+async fn check_user_response(response: Response) -> Result<Response> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+    let body = response.text().await.unwrap_or_default();
+    if is_revoked(status, &body) {
+        return Err(Revoked.into());
+    }
+    bail!("Discord responded with {}: {}", status, body)
+}
+
+/// Check if a failed response means the user revoked their authorization, which is when it is
+/// unauthorized, or its error is `invalid_grant`, ex: the refresh token is revoked
+/// ```
+/// use haxbotjr::linked_roles::is_revoked;
+/// use reqwest::StatusCode;
+///
+/// assert!(is_revoked(StatusCode::UNAUTHORIZED, ""));
+/// assert!(is_revoked(StatusCode::BAD_REQUEST, r#"{"error": "invalid_grant"}"#));
+/// assert!(!is_revoked(StatusCode::BAD_REQUEST, r#"{"error": "invalid_request"}"#));
+/// assert!(!is_revoked(StatusCode::BAD_REQUEST, r#"{"message": "Invalid Form Body", "code": 50035}"#));
+/// ```
+pub fn is_revoked(status: StatusCode, body: &str) -> bool {
+    #[derive(Deserialize)]
+    struct ErrorBody {
+        error: String,
+    }
+    match status {
+        StatusCode::UNAUTHORIZED => true,
+        StatusCode::BAD_REQUEST => {
+            serde_json::from_str::<ErrorBody>(body).is_ok_and(|body| body.error == "invalid_grant")
+        }
+        _ => false,
+    }
+}
+
+async fn unlink_user(db: &RwLock<DB>, user: DiscordId) -> Result<()> {
+    info!(?user, "User revoked their role connection");
+    let db = db.write().await;
+    let mut tx = ctx!(db.begin().await)?;
+    ctx!(role_connection::remove_role_connection(&mut tx, user).await)?;
+    ctx!(tx.commit().await)?;
+    Ok(())
+}
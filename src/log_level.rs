@@ -0,0 +1,75 @@
+//! Runtime control of the log levels.
+//!
+//! The log filter is installed as a reloadable layer, so the level of specific modules can be
+//! raised (ex: `wynn::loops=debug`) during an incident without restarting the bot.
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use serenity::prelude::TypeMapKey;
+use tokio::sync::Mutex;
+use tracing::info;
+use tracing_subscriber::filter::{EnvFilter, LevelFilter};
+use tracing_subscriber::{reload, Registry};
+
+/// Level of modules without an override
+pub const DEFAULT_LEVEL: LevelFilter = LevelFilter::INFO;
+
+/// Reloadable log filter, and the per-module level overrides applied to it
+pub struct LogLevels {
+    handle: reload::Handle<EnvFilter, Registry>,
+    overrides: BTreeMap<String, LevelFilter>,
+}
+
+impl LogLevels {
+    /// Create the log filter layer, and the [`LogLevels`] that controls it
+    pub fn layer() -> (reload::Layer<EnvFilter, Registry>, Self) {
+        let (layer, handle) = reload::Layer::new(EnvFilter::default().add_directive(DEFAULT_LEVEL.into()));
+        (layer, Self { handle, overrides: BTreeMap::new() })
+    }
+
+    /// Get the per-module level overrides
+    pub fn overrides(&self) -> &BTreeMap<String, LevelFilter> {
+        &self.overrides
+    }
+
+    /// Set the level of a module, ex: "wynn::loops"
+    pub fn set(&mut self, target: &str, level: LevelFilter) -> Result<()> {
+        let mut overrides = self.overrides.clone();
+        overrides.insert(target.to_string(), level);
+        self.apply(overrides)
+    }
+
+    /// Remove the level override of a module, returns false if it doesn't have one
+    pub fn unset(&mut self, target: &str) -> Result<bool> {
+        let mut overrides = self.overrides.clone();
+        if overrides.remove(target).is_none() {
+            return Ok(false);
+        }
+        self.apply(overrides)?;
+        Ok(true)
+    }
+
+    /// Remove all level overrides
+    pub fn reset(&mut self) -> Result<()> {
+        self.apply(BTreeMap::new())
+    }
+
+    /// Reload the log filter with `overrides`, and keep them if they are valid
+    fn apply(&mut self, overrides: BTreeMap<String, LevelFilter>) -> Result<()> {
+        let directives = std::iter::once(DEFAULT_LEVEL.to_string())
+            .chain(overrides.iter().map(|(target, level)| format!("{}={}", target, level)))
+            .collect::<Vec<String>>()
+            .join(",");
+        let filter = EnvFilter::try_new(&directives).context("Invalid log filter")?;
+        self.handle.reload(filter).context("Failed to reload log filter")?;
+        info!(directives, "Log filter reloaded");
+        self.overrides = overrides;
+        Ok(())
+    }
+}
+
+/// Bot data key for [`LogLevels`]
+impl TypeMapKey for LogLevels {
+    type Value = Arc<Mutex<LogLevels>>;
+}
@@ -1,24 +1,36 @@
 //! Loops for handling channel loggings.
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::sync::{Arc, Mutex};
 
 use anyhow::Result;
 use chrono::offset::Utc;
 use serenity::CacheAndHttp;
+use tokio::sync::broadcast::error::RecvError;
 use tokio::sync::RwLock;
-use tokio::time::{self, Duration};
-use tracing::info;
+use tokio::time::{self, Duration, Instant};
+use tracing::{info, warn};
 
+use config::log_subscription::LogEvent;
+use config::milestone;
 use config::tag::TextChannelTag;
 use config::Config;
 use memberdb::events::DBEvent;
+use memberdb::model::member::MemberId;
+use memberdb::query_builder::Selectable;
 use memberdb::DB;
 use msgtool::table;
+use util::string::{self, StatFormat};
+use util::task::{RestartPolicy, Spawner};
 use util::{ctx, ok, some};
+use wynn::cache::Cache;
 use wynn::events::{WynnEvent, WynnSignal};
 
-/// Make a log message from `WynnEvent`
-fn make_wynn_log(event: &WynnEvent) -> Option<String> {
+use crate::i18n;
+use crate::util::outbox::{self, Outbox};
+use crate::util::{link_conflict, weekly_reset, xp_correction};
+
+/// Make a log message from `WynnEvent`, with stats displayed in `fmt`
+fn make_wynn_log(event: &WynnEvent, fmt: &StatFormat) -> Option<String> {
     Some(match event {
         WynnEvent::MemberJoin { ign, .. } => format!("**{}** joined the guild", ign),
         WynnEvent::MemberLeave { ign, rank, .. } => format!("**{}** ({}) left the guild", ign, rank),
@@ -26,14 +38,22 @@ fn make_wynn_log(event: &WynnEvent) -> Option<String> {
             format!("**{}** guild rank changed, from __{}__ to __{}__", ign, old_rank, new_rank)
         }
         WynnEvent::MemberContribute { ign, old_contrib, new_contrib, .. } => {
-            let delta = util::string::fmt_num(new_contrib - old_contrib, false);
-            let new_contrib = util::string::fmt_num(*new_contrib, true);
+            let delta = fmt.num(new_contrib - old_contrib, false);
+            let new_contrib = fmt.num(*new_contrib, true);
             format!("**{}** contributed __{}__ xp, total *{}* xp", ign, delta, new_contrib)
         }
         WynnEvent::MemberNameChange { old_name, new_name, .. } => {
             format!("**{}** changed their name to **{}**", old_name, new_name)
         }
         WynnEvent::GuildLevelUp { level } => format!("**Guild leveled up to** __{}__", level),
+        WynnEvent::GuildTagChange { old_tag, new_tag } => {
+            format!("**Guild tag changed** from __{}__ to __{}__", old_tag, new_tag)
+        }
+        WynnEvent::GuildBannerChange { old_banner, new_banner } => format!(
+            "**Guild banner changed** from __{}__ to __{}__",
+            old_banner.describe(),
+            new_banner.describe()
+        ),
         WynnEvent::PlayerJoin { ign, world } => format!("**{}** logged in at __{}__", ign, world),
         WynnEvent::PlayerMove { ign, old_world, new_world } => {
             format!("**{}** moved from __{}__ to __{}__", ign, old_world, new_world)
@@ -43,38 +63,95 @@ fn make_wynn_log(event: &WynnEvent) -> Option<String> {
     })
 }
 
-/// All channel tags for logging
-pub const LOG_CHANNEL_TAGS: [TextChannelTag; 4] = [
-    TextChannelTag::GuildMemberLog,
-    TextChannelTag::GuildLevelLog,
-    TextChannelTag::XpLog,
-    TextChannelTag::OnlineLog,
-];
-
-/// Get a `WynnEvent`'s corresponding channel tag
-fn get_log_channel_tag(event: &WynnEvent) -> Option<TextChannelTag> {
+/// Get a `WynnEvent`'s corresponding log event
+fn get_log_event(event: &WynnEvent) -> Option<LogEvent> {
     Some(match event {
-        WynnEvent::MemberJoin { .. }
-        | WynnEvent::MemberLeave { .. }
-        | WynnEvent::MemberRankChange { .. }
-        | WynnEvent::MemberNameChange { .. } => TextChannelTag::GuildMemberLog,
-        WynnEvent::GuildLevelUp { .. } => TextChannelTag::GuildLevelLog,
-        WynnEvent::MemberContribute { .. } => TextChannelTag::XpLog,
-        WynnEvent::PlayerJoin { .. } | WynnEvent::PlayerLeave { .. } | WynnEvent::PlayerMove { .. } => {
-            TextChannelTag::OnlineLog
-        }
+        WynnEvent::MemberJoin { .. } => LogEvent::MemberJoin,
+        WynnEvent::MemberLeave { .. } => LogEvent::MemberLeave,
+        WynnEvent::MemberRankChange { .. } => LogEvent::RankChange,
+        WynnEvent::MemberNameChange { .. } => LogEvent::NameChange,
+        WynnEvent::GuildLevelUp { .. } => LogEvent::LevelUp,
+        WynnEvent::GuildTagChange { .. } | WynnEvent::GuildBannerChange { .. } => LogEvent::GuildChange,
+        WynnEvent::MemberContribute { .. } => LogEvent::Xp,
+        WynnEvent::PlayerJoin { .. } => LogEvent::PlayerJoin,
+        WynnEvent::PlayerLeave { .. } => LogEvent::PlayerLeave,
+        WynnEvent::PlayerMove { .. } => LogEvent::PlayerMove,
         _ => return None,
     })
 }
 
-/// Start loop for collecting & sending of channel logs.
-pub async fn start_log_loop(cache_http: Arc<CacheAndHttp>, config: Arc<RwLock<Config>>, signal: WynnSignal) {
-    // Logs are collected in a buffer waiting to be send.
-    let mut buffers: HashMap<&TextChannelTag, String> = HashMap::with_capacity(LOG_CHANNEL_TAGS.len());
-    for k in &LOG_CHANNEL_TAGS {
-        buffers.insert(k, String::new());
+/// Max amount of log messages sent to a channel in a burst
+const CHANNEL_BURST: f64 = 3.0;
+/// Seconds it takes for a channel to regain one message of its burst
+const CHANNEL_REFILL_SECS: f64 = 2.0;
+
+/// Paces the log messages sent to each channel with a token bucket, so a burst of logs doesn't hit
+/// discord's rate limits and delay the other bot actions.
+#[derive(Debug, Default)]
+struct ChannelPacer {
+    /// Tokens of each channel, and when they were last refilled
+    buckets: HashMap<u64, (f64, Instant)>,
+}
+
+impl ChannelPacer {
+    /// Take a token of a channel if it has one, otherwise return how long until it has one
+    fn try_take(&mut self, channel_id: u64) -> Result<(), Duration> {
+        let now = Instant::now();
+        let (tokens, refilled) = self.buckets.entry(channel_id).or_insert((CHANNEL_BURST, now));
+        let elapsed = now.duration_since(*refilled).as_secs_f64();
+        *tokens = (*tokens + elapsed / CHANNEL_REFILL_SECS).min(CHANNEL_BURST);
+        *refilled = now;
+        if *tokens >= 1.0 {
+            *tokens -= 1.0;
+            return Ok(());
+        }
+        Err(Duration::from_secs_f64((1.0 - *tokens) * CHANNEL_REFILL_SECS))
     }
-    let buffers = Arc::new(Mutex::new(buffers));
+}
+
+/// Send the queued log messages of each channel as their pacing allows.
+/// Channels take turns, so a channel waiting for its tokens doesn't hold back the others.
+async fn send_paced(
+    pacer: &mut ChannelPacer, outbox: &Outbox, config: &RwLock<Config>, cache_http: &CacheAndHttp,
+    mut queues: Vec<(u64, VecDeque<String>)>,
+) {
+    loop {
+        queues.retain(|(_, queue)| !queue.is_empty());
+        if queues.is_empty() {
+            return;
+        }
+        let mut wait: Option<Duration> = None;
+        let mut sent = false;
+        for (channel_id, queue) in &mut queues {
+            match pacer.try_take(*channel_id) {
+                Ok(()) => {
+                    let log = some!(queue.pop_front(), continue);
+                    let _ = ctx!(outbox::send_log(outbox, config, cache_http, *channel_id, &log).await);
+                    sent = true;
+                }
+                Err(until) => wait = Some(wait.map_or(until, |wait| wait.min(until))),
+            }
+        }
+        if let (false, Some(wait)) = (sent, wait) {
+            time::sleep(wait).await;
+        }
+    }
+}
+
+/// Start loop for collecting & sending of channel logs.
+///
+/// Each log channel is sent the logs of the events it subscribed to, see
+/// [`Config::log_subscriptions`].
+/// The logs of a channel are packed into as few messages as possible, which are paced by
+/// [`ChannelPacer`].
+/// Logs that fail to be delivered are buffered in the outbox.
+pub async fn start_log_loop(
+    spawner: &impl Spawner, cache_http: Arc<CacheAndHttp>, config: Arc<RwLock<Config>>, outbox: Arc<Outbox>,
+    signal: WynnSignal,
+) {
+    // Logs are collected in a buffer waiting to be send, along with the event they are of.
+    let buffer: Vec<(LogEvent, String)> = Vec::new();
+    let buffer = Arc::new(Mutex::new(buffer));
 
     // Xp logs are treated differently from other.
     // Each player's xp contribution info is tracked in the form of `(ign, contributed, total)`
@@ -84,102 +161,114 @@ pub async fn start_log_loop(cache_http: Arc<CacheAndHttp>, config: Arc<RwLock<Co
     let xp_buffer: HashMap<String, (String, i64, i64)> = HashMap::new();
     let xp_buffer = Arc::new(Mutex::new(xp_buffer));
 
-    let shared_buffers = Arc::clone(&buffers);
+    let shared_buffer = Arc::clone(&buffer);
     let shared_xp_buffer = Arc::clone(&xp_buffer);
     let shared_config = Arc::clone(&config);
     // Start loop that sends log messages
-    tokio::spawn(async move {
-        info!("Starting discord log channel loop");
-        let mut interval = time::interval(Duration::from_secs(60));
-        loop {
-            interval.tick().await;
-            // Send logs in log buffer, and empty it
-            for tag in &LOG_CHANNEL_TAGS {
-                let log = {
-                    let mut buffers = shared_buffers.lock().unwrap();
-                    // Early return if no logs
-                    let buffer = buffers.get(tag).unwrap();
-                    if buffer.is_empty() {
-                        continue;
-                    }
-                    // Get buffer and replace it with an empty one
-                    let buffer = buffers.remove(tag).unwrap();
-                    buffers.insert(tag, String::new());
-                    buffer
-                };
+    spawner.spawn("channel log sending", RestartPolicy::Always, move || {
+        let shared_buffer = shared_buffer.clone();
+        let shared_xp_buffer = shared_xp_buffer.clone();
+        let shared_config = shared_config.clone();
+        let cache_http = cache_http.clone();
+        let outbox = outbox.clone();
+        async move {
+            info!("Starting discord log channel loop");
+            let mut pacer = ChannelPacer::default();
+            let mut interval = time::interval(Duration::from_secs(60));
+            loop {
+                interval.tick().await;
+                // Take the logs in log buffer, and empty it
+                let mut logs = std::mem::take(&mut *shared_buffer.lock().unwrap());
+
+                // Format xp logs, they are sent after the other logs
+                let fmt = i18n::main_stat_format(&*shared_config.read().await);
                 {
-                    let config = shared_config.read().await;
-                    let _ = ctx!(config.send(&cache_http, tag, &log).await);
+                    // Add each player's own xp log message to the logs
+                    let mut xp_buffer = shared_xp_buffer.lock().unwrap();
+                    for (ign, diff, xp) in xp_buffer.values() {
+                        let diff = fmt.num(*diff, false);
+                        let xp = fmt.num(*xp, true);
+                        let log = format!("**{}** contributed __{}__ xp, total *{}* xp", ign, diff, xp);
+                        logs.push((LogEvent::Xp, log));
+                    }
+                    xp_buffer.clear();
                 }
-            }
-
-            // Format xp logs
-            let mut log_buffer = String::new();
-            {
-                // Add each player's own xp log message to the total buffer
-                let mut xp_buffer = shared_xp_buffer.lock().unwrap();
-                for (ign, diff, xp) in xp_buffer.values() {
-                    let diff = util::string::fmt_num(*diff, false);
-                    let xp = util::string::fmt_num(*xp, true);
-                    let log = format!("**{}** contributed __{}__ xp, total *{}* xp", ign, diff, xp);
-                    log_buffer.push('\n');
-                    log_buffer.push_str(&log);
+                if logs.is_empty() {
+                    continue;
                 }
-                xp_buffer.clear();
-            }
-            // Send the logs if not empty
-            if !log_buffer.is_empty() {
-                let config = shared_config.read().await;
-                let _ = ctx!(config.send(&cache_http, &TextChannelTag::XpLog, &log_buffer).await);
+
+                // Send each log channel the logs of the events it subscribed to
+                let subscriptions: Vec<(u64, HashSet<LogEvent>)> = {
+                    let config = shared_config.read().await;
+                    config
+                        .log_subscriptions
+                        .iter()
+                        .map(|(channel, events)| (*channel, events.clone()))
+                        .collect()
+                };
+                let queues: Vec<(u64, VecDeque<String>)> = subscriptions
+                    .into_iter()
+                    .map(|(channel_id, events)| {
+                        let log: Vec<&str> = logs
+                            .iter()
+                            .filter(|(event, _)| events.contains(event))
+                            .map(|(_, log)| log.as_str())
+                            .collect();
+                        (channel_id, string::split_message(&log.join("\n"), string::MESSAGE_LEN).into())
+                    })
+                    .collect();
+                send_paced(&mut pacer, &outbox, &shared_config, &cache_http, queues).await;
             }
         }
     });
 
     // Start loop that adds to log buffer and track player xp contribution info
-    tokio::spawn(async move {
-        info!("Starting wynn event logging loop");
-        let mut receiver = signal.connect();
-        loop {
-            let events =
-                ok!(ctx!(receiver.recv().await, "Failed to receive wynn events in log loop"), continue);
-
-            for event in events.as_ref() {
-                let tag = some!(get_log_channel_tag(event), continue);
-                // Do not log if there are no log channels
-                {
-                    let config = config.read().await;
-                    if config.text_channel_tags.tagged_objects(&tag).next().is_none() {
-                        continue;
-                    }
-                }
+    spawner.spawn("channel log collecting", RestartPolicy::Always, move || {
+        let signal = signal.clone();
+        let config = config.clone();
+        let xp_buffer = xp_buffer.clone();
+        let buffer = buffer.clone();
+        async move {
+            info!("Starting wynn event logging loop");
+            let mut receiver = signal.connect();
+            loop {
+                let events =
+                    ok!(ctx!(receiver.recv().await, "Failed to receive wynn events in log loop"), continue);
+
+                for event in events.as_ref() {
+                    let log_event = some!(get_log_event(event), continue);
+                    // Do not log if no log channels subscribed to the event
+                    let fmt = {
+                        let config = config.read().await;
+                        if config.log_subscriptions.subscribers(&log_event).next().is_none() {
+                            continue;
+                        }
+                        i18n::main_stat_format(&config)
+                    };
 
-                match event {
-                    WynnEvent::MemberContribute { id, ign, old_contrib, new_contrib } => {
-                        // Updates xp contribution info tracking
-                        let mut xp_buffer = xp_buffer.lock().unwrap();
-                        let diff = new_contrib - old_contrib;
-                        match xp_buffer.get_mut(id) {
-                            Some(contrib) => {
-                                // Updates the ign if it is changed
-                                if *ign != contrib.0 {
-                                    contrib.0 = ign.clone();
+                    match event {
+                        WynnEvent::MemberContribute { id, ign, old_contrib, new_contrib } => {
+                            // Updates xp contribution info tracking
+                            let mut xp_buffer = xp_buffer.lock().unwrap();
+                            let diff = new_contrib - old_contrib;
+                            match xp_buffer.get_mut(id) {
+                                Some(contrib) => {
+                                    // Updates the ign if it is changed
+                                    if *ign != contrib.0 {
+                                        contrib.0 = ign.clone();
+                                    }
+                                    contrib.1 += diff;
+                                    contrib.2 = *new_contrib;
+                                }
+                                None => {
+                                    xp_buffer.insert(id.clone(), (ign.clone(), diff, *new_contrib));
                                 }
-                                contrib.1 += diff;
-                                contrib.2 = *new_contrib;
-                            }
-                            None => {
-                                xp_buffer.insert(id.clone(), (ign.clone(), diff, *new_contrib));
                             }
                         }
-                    }
-                    _ => {
-                        // Make the log message and add it to buffer
-                        let log = some!(make_wynn_log(event), continue);
-                        {
-                            let mut buffers = buffers.lock().unwrap();
-                            let buffer = buffers.get_mut(&tag).unwrap();
-                            buffer.push('\n');
-                            buffer.push_str(&log);
+                        _ => {
+                            // Make the log message and add it to buffer
+                            let log = some!(make_wynn_log(event, &fmt), continue);
+                            buffer.lock().unwrap().push((log_event, log));
                         }
                     }
                 }
@@ -191,88 +280,392 @@ pub async fn start_log_loop(cache_http: Arc<CacheAndHttp>, config: Arc<RwLock<Co
 /// The max amount of rows within a summary message
 const SUMMARY_TABLE_LEN: usize = 30;
 
-/// Send a message to summary channels
+/// The max amount of reached goals within a message
+const GOAL_REPORT_CHUNK: usize = 20;
+
+/// Send a message to summary channels, it is buffered in the outbox if it isn't delivered
 macro_rules! send_to_summary {
-    ($cache_http:expr, $config:ident, $msg:expr) => {
-        let config = $config.read().await;
-        ok!(ctx!(config.send($cache_http, &TextChannelTag::Summary, $msg).await), continue);
+    ($cache_http:expr, $config:ident, $outbox:ident, $msg:expr) => {
+        ok!(
+            ctx!(outbox::send(&$outbox, &$config, $cache_http, &TextChannelTag::Summary, $msg).await),
+            continue
+        );
     };
 }
 
-/// Start the loops for sending weekly summaries
+/// Start the loops for sending daily and weekly summaries.
+///
+/// Summaries that fail to be delivered are buffered in the outbox.
 pub async fn start_summary_loop(
-    cache_http: Arc<CacheAndHttp>, config: Arc<RwLock<Config>>, db: Arc<RwLock<DB>>,
+    spawner: &impl Spawner, cache_http: Arc<CacheAndHttp>, config: Arc<RwLock<Config>>, db: Arc<RwLock<DB>>,
+    outbox: Arc<Outbox>,
 ) {
-    tokio::spawn(async move {
-        info!("Starting weekly summary loop");
-        let mut receiver = {
-            let db = db.read().await;
-            db.connect()
-        };
-        loop {
-            let event =
-                ok!(ctx!(receiver.recv().await, "Failed to receive db event in summary loop"), continue);
-
-            if let DBEvent::WeeklyReset { message_lb, voice_lb, online_lb, xp_lb } = event.as_ref() {
-                // Do not send summary if there are no channels to send
+    spawner.spawn("summary", RestartPolicy::Always, move || {
+        let cache_http = cache_http.clone();
+        let config = config.clone();
+        let db = db.clone();
+        let outbox = outbox.clone();
+        async move {
+            info!("Starting summary loop");
+            let mut receiver = {
+                let db = db.read().await;
+                db.connect()
+            };
+            loop {
+                let event =
+                    ok!(ctx!(receiver.recv().await, "Failed to receive db event in summary loop"), continue);
+                let fmt = i18n::main_stat_format(&*config.read().await);
+
+                if let DBEvent::WeeklyReset { message_lb, voice_lb, online_lb, xp_lb, online, global_rank } =
+                    event.as_ref()
                 {
-                    let config = config.read().await;
-                    if config
-                        .text_channel_tags
-                        .tagged_objects(&TextChannelTag::Summary)
-                        .next()
-                        .is_none()
-                    {
+                    // Send header
+                    let now = Utc::now().format("%Y %b %d");
+                    let msg = format!("> **Weekly summary for {}**\n\n__Weekly message__", now);
+                    send_to_summary!(&cache_http, config, outbox, &msg);
+
+                    // Send each summaries
+                    let lb = table::borrow_table(&message_lb.0);
+                    ok!(send_summary(&cache_http, &config, &outbox, &lb).await, continue);
+                    send_to_summary!(&cache_http, config, outbox, "__Weekly voice time__");
+                    let lb = table::borrow_table(&voice_lb.0);
+                    ok!(send_summary(&cache_http, &config, &outbox, &lb).await, continue);
+                    send_to_summary!(&cache_http, config, outbox, "__Weekly online time__");
+                    let lb = table::borrow_table(&online_lb.0);
+                    ok!(send_summary(&cache_http, &config, &outbox, &lb).await, continue);
+                    send_to_summary!(&cache_http, config, outbox, "__Weekly xp contribution__");
+                    let lb = table::borrow_table(&xp_lb.0);
+                    ok!(send_summary(&cache_http, &config, &outbox, &lb).await, continue);
+                    let msg = format!(
+                        "__Weekly guild activity__\n\
+                        Peak members online: **{}**\n\
+                        Average members online: **{:.1}** ({:.1}% of the guild)",
+                        online.peak_online,
+                        online.avg_online_members,
+                        online.avg_online_ratio * 100.0,
+                    );
+                    send_to_summary!(&cache_http, config, outbox, &msg);
+                    if let Some(change) = global_rank {
+                        let msg = if change.from == change.to {
+                            format!("__Global guild rank__\nStayed at **#{}** this week", change.to)
+                        } else {
+                            format!(
+                                "__Global guild rank__\nMoved from **#{}** to **#{}** this week",
+                                change.from, change.to
+                            )
+                        };
+                        send_to_summary!(&cache_http, config, outbox, &msg);
+                    }
+                }
+
+                if let DBEvent::DailyReset { summary } = event.as_ref() {
+                    // The reset happens at midnight, so the stats are from the day before
+                    let yesterday = (Utc::now() - chrono::Duration::days(1)).format("%Y %b %d");
+                    let msg = format!(
+                        "> **Daily summary for {}**\n\
+                        Xp contributed: **{}**\n\
+                        Unique members online: **{}**\n\
+                        Peak members online: **{}**\n\
+                        Average members online: **{:.1}** ({:.1}% of the guild)\n\
+                        Messages sent: **{}**\n\
+                        Guild joins: **{}**\n\
+                        Guild leaves: **{}**",
+                        yesterday,
+                        fmt.num(summary.xp, false),
+                        fmt.num(summary.online, false),
+                        fmt.num(summary.online_peak, false),
+                        summary.avg_online_members,
+                        summary.avg_online_ratio * 100.0,
+                        fmt.num(summary.message, false),
+                        fmt.num(summary.joins, false),
+                        fmt.num(summary.leaves, false),
+                    );
+                    send_to_summary!(&cache_http, config, outbox, &msg);
+                }
+
+                if let DBEvent::WeeklyReport { report, deadline } = event.as_ref() {
+                    ok!(weekly_reset::post_report(&cache_http, &config, report, *deadline).await, continue);
+                }
+
+                if let DBEvent::XpCorrection { correction } = event.as_ref() {
+                    ok!(xp_correction::post_correction(&cache_http, &config, correction, &fmt).await, continue);
+                }
+
+                if let DBEvent::LinkConflict { conflict } = event.as_ref() {
+                    ok!(link_conflict::post_conflict(&cache_http, &config, conflict).await, continue);
+                }
+
+                if let DBEvent::WeeklyResetSkip = event.as_ref() {
+                    let msg = "Weekly reset is skipped, the weekly stats are kept until the next reset";
+                    ok!(
+                        ctx!(config::send(&config, &cache_http, &TextChannelTag::WeeklyReport, msg).await),
+                        continue
+                    );
+                }
+
+                if let DBEvent::WeeklyResetUndo { time } = event.as_ref() {
+                    let msg = format!("Weekly reset at <t:{}:f> is undone, the weekly stats are restored", time);
+                    ok!(
+                        ctx!(config::send(&config, &cache_http, &TextChannelTag::WeeklyReport, &msg).await),
+                        continue
+                    );
+                }
+
+                if let DBEvent::XpRequirementReport { misses } = event.as_ref() {
+                    let now = Utc::now().format("%Y %b %d");
+                    let msg = if misses.is_empty() {
+                        format!(
+                            "> **Weekly xp requirement report for {}**\nAll guild members met the requirement",
+                            now
+                        )
+                    } else {
+                        format!(
+                            "> **Weekly xp requirement report for {}**\n{} guild members are below the requirement",
+                            now,
+                            misses.len()
+                        )
+                    };
+                    ok!(
+                        ctx!(config::send(&config, &cache_http, &TextChannelTag::XpReport, &msg).await),
+                        continue
+                    );
+                    if misses.is_empty() {
                         continue;
                     }
+
+                    let mut report =
+                        vec![["IGN", "RANK", "XP", "REQUIRED", "MISSED WEEKS"].map(String::from).to_vec()];
+                    for miss in misses {
+                        report.push(vec![
+                            miss.ign.clone(),
+                            miss.rank.to_string(),
+                            fmt.num(miss.xp, false),
+                            fmt.num(miss.required, false),
+                            miss.streak.to_string(),
+                        ]);
+                    }
+                    let report = table::borrow_table(&report);
+                    ok!(send_table(&cache_http, &config, &TextChannelTag::XpReport, &report).await, continue);
                 }
 
-                // Send header
-                let now = Utc::now().format("%Y %b %d");
-                let msg = format!("> **Weekly summary for {}**\n\n__Weekly message__", now);
-                send_to_summary!(&cache_http, config, &msg);
-
-                // Send each summaries
-                ok!(send_summary(&cache_http, &config, &table::borrow_table(&message_lb.0)).await, continue);
-                send_to_summary!(&cache_http, config, "__Weekly voice time__");
-                ok!(send_summary(&cache_http, &config, &table::borrow_table(&voice_lb.0)).await, continue);
-                send_to_summary!(&cache_http, config, "__Weekly online time__");
-                ok!(send_summary(&cache_http, &config, &table::borrow_table(&online_lb.0)).await, continue);
-                send_to_summary!(&cache_http, config, "__Weekly xp contribution__");
-                ok!(send_summary(&cache_http, &config, &table::borrow_table(&xp_lb.0)).await, continue);
+                if let DBEvent::GoalReport { achieved } = event.as_ref() {
+                    if achieved.is_empty() {
+                        continue;
+                    }
+                    let lines = achieved
+                        .iter()
+                        .map(|achieved| {
+                            let name = match (&achieved.discord, &achieved.ign) {
+                                (Some(discord), _) => format!("<@{}>", discord),
+                                (None, Some(ign)) => format!("**{}**", ign),
+                                (None, None) => format!("Member {}", achieved.goal.mid),
+                            };
+                            format!(
+                                "{} reached their `{}` goal of **{}**",
+                                name,
+                                achieved.goal.stat.table_name(),
+                                msgtool::profile::format_stat_val(&achieved.goal.stat, achieved.goal.target, &fmt)
+                            )
+                        })
+                        .collect::<Vec<_>>();
+                    for (i, chunk) in lines.chunks(GOAL_REPORT_CHUNK).enumerate() {
+                        let mut msg = chunk.join("\n");
+                        if i == 0 {
+                            msg = format!("> **Congratulations on reaching your weekly goals!**\n{}", msg);
+                        }
+                        send_to_summary!(&cache_http, config, outbox, &msg);
+                    }
+                }
             }
         }
     });
 }
 
-/// Build summary messages from stat leaderboard and send them
-async fn send_summary(cache_http: &CacheAndHttp, config: &RwLock<Config>, lb: &Vec<Vec<&str>>) -> Result<()> {
+/// Build summary messages from stat leaderboard and send them, they are buffered in the outbox if
+/// they aren't delivered
+async fn send_summary(
+    cache_http: &CacheAndHttp, config: &RwLock<Config>, outbox: &Outbox, lb: &Vec<Vec<&str>>,
+) -> Result<()> {
     // If leaderboard is empty
     if lb.is_empty() {
-        let config = config.read().await;
-        ctx!(
-            config
-                .send(&cache_http, &TextChannelTag::Summary, "```\nEmpty leaderboard\n```",)
-                .await
-        )?;
+        ctx!(outbox::send(outbox, config, &cache_http, &TextChannelTag::Summary, EMPTY_TABLE).await)?;
+    }
+    for table in format_tables(lb) {
+        ctx!(outbox::send(outbox, config, &cache_http, &TextChannelTag::Summary, &table).await)?;
     }
+    Ok(())
+}
 
+/// Build messages from a table and send them to channels with `tag`
+async fn send_table(
+    cache_http: &CacheAndHttp, config: &RwLock<Config>, tag: &TextChannelTag, lb: &Vec<Vec<&str>>,
+) -> Result<()> {
+    // If leaderboard is empty
+    if lb.is_empty() {
+        ctx!(config::send(config, &cache_http, tag, EMPTY_TABLE).await)?;
+    }
+    for table in format_tables(lb) {
+        ctx!(config::send(config, &cache_http, tag, &table).await)?;
+    }
+    Ok(())
+}
+
+/// Message sent in place of an empty table
+const EMPTY_TABLE: &str = "```\nEmpty leaderboard\n```";
+
+/// Format a table into messages of at most [`SUMMARY_TABLE_LEN`] rows
+fn format_tables(lb: &Vec<Vec<&str>>) -> Vec<String> {
     let max_widths = msgtool::table::calc_cols_max_width(lb);
     // Split lb into chunks according to `SUMMARY_TABLE_LEN
-    let tables = lb.chunks(SUMMARY_TABLE_LEN).map(|chunk| {
-        // Format rows into string
-        let mut table = String::from("```\n");
-        for row in chunk.iter().map(|row| msgtool::table::format_row(row, &max_widths)) {
-            table.push_str(&row);
+    lb.chunks(SUMMARY_TABLE_LEN)
+        .map(|chunk| {
+            // Format rows into string
+            let mut table = String::from("```\n");
+            for row in chunk.iter().map(|row| msgtool::table::format_row(row, &max_widths)) {
+                table.push_str(&row);
+            }
+            table.push_str("```");
+            table
+        })
+        .collect()
+}
+
+/// Start the loops for announcing guild milestones.
+///
+/// Only milestones higher than the previously announced one are announced, so a count going back
+/// and forth around a milestone doesn't get announced repeatedly.
+/// The announced milestones aren't persisted, so they are reset when the bot restarts.
+pub async fn start_milestone_loop(
+    spawner: &impl Spawner, cache_http: Arc<CacheAndHttp>, config: Arc<RwLock<Config>>, db: Arc<RwLock<DB>>,
+    wynn_cache: Arc<Cache>, signal: WynnSignal,
+) {
+    let shared_cache_http = Arc::clone(&cache_http);
+    let shared_config = Arc::clone(&config);
+    spawner.spawn("guild milestone (wynn event)", RestartPolicy::Always, move || {
+        let wynn_cache = wynn_cache.clone();
+        let signal = signal.clone();
+        let shared_cache_http = shared_cache_http.clone();
+        let shared_config = shared_config.clone();
+        async move {
+            info!("Starting guild milestone loop (wynn event)");
+            // The cache is only updated after the events are sent, so it contains the guild members
+            // before the first batch of events.
+            // If there isn't one, the first batch adds all guild members, which isn't a milestone.
+            let mut guild_members = {
+                let members = wynn_cache.members.read().await;
+                members.as_ref().map(|members| members.len() as u64)
+            };
+            let mut announced_members = 0;
+            let mut announced_level = 0;
+            let mut recv = signal.connect();
+            loop {
+                let events = match recv.recv().await {
+                    Ok(events) => events,
+                    Err(RecvError::Lagged(skipped)) => {
+                        warn!(skipped, "Guild milestone loop lagged behind wynn events");
+                        // Joins and leaves were missed, count the guild members again
+                        let members = wynn_cache.members.read().await;
+                        guild_members = members.as_ref().map(|members| members.len() as u64);
+                        continue;
+                    }
+                    Err(RecvError::Closed) => break,
+                };
+
+                let (mut joins, mut leaves, mut level) = (0, 0, None);
+                for event in events.as_ref() {
+                    match event {
+                        WynnEvent::MemberJoin { .. } => joins += 1,
+                        WynnEvent::MemberLeave { .. } => leaves += 1,
+                        WynnEvent::GuildLevelUp { level: new } => level = Some(u64::from(*new)),
+                        _ => {}
+                    }
+                }
+
+                let (members_step, level_step) = {
+                    let config = shared_config.read().await;
+                    (config.milestones.guild_members, config.milestones.guild_level)
+                };
+                if joins > 0 || leaves > 0 {
+                    let old = some!(guild_members, {
+                        guild_members = Some(joins);
+                        continue;
+                    });
+                    let new = (old + joins).saturating_sub(leaves);
+                    guild_members = Some(new);
+                    if let Some(milestone) = milestone::reached(members_step, old.max(announced_members), new)
+                    {
+                        announced_members = milestone;
+                        let msg = format!("> **The guild has reached {} members!**", milestone);
+                        send_milestone(&shared_cache_http, &shared_config, &msg).await;
+                    }
+                }
+                if let Some(level) = level {
+                    if let Some(milestone) =
+                        milestone::reached(level_step, (level - 1).max(announced_level), level)
+                    {
+                        announced_level = milestone;
+                        let msg = format!("> **The guild has reached level {}!**", milestone);
+                        send_milestone(&shared_cache_http, &shared_config, &msg).await;
+                    }
+                }
+            }
         }
-        table.push_str("```");
-        table
     });
-    {
-        let config = config.read().await;
-        for table in tables {
-            ctx!(config.send(&cache_http, &TextChannelTag::Summary, &table,).await)?;
+
+    spawner.spawn("member milestone (db event)", RestartPolicy::Always, move || {
+        let db = db.clone();
+        let config = config.clone();
+        let cache_http = cache_http.clone();
+        async move {
+            info!("Starting member milestone loop (db event)");
+            let (mut recv, count) = {
+                let db = db.read().await;
+                (db.connect(), MemberId::count(&mut db.exe()).await)
+            };
+            let mut members = ok!(ctx!(count, "Failed to count members"), return) as u64;
+            let mut announced = 0;
+            loop {
+                let event = match recv.recv().await {
+                    Ok(event) => event,
+                    Err(RecvError::Lagged(skipped)) => {
+                        warn!(skipped, "Member milestone loop lagged behind db events");
+                        // Member additions and removals were missed, count the members again
+                        let count = {
+                            let db = db.read().await;
+                            MemberId::count(&mut db.exe()).await
+                        };
+                        members = ok!(ctx!(count, "Failed to count members"), continue) as u64;
+                        continue;
+                    }
+                    Err(RecvError::Closed) => break,
+                };
+
+                match event.as_ref() {
+                    DBEvent::MemberAdd { .. } => {
+                        let old = members;
+                        members += 1;
+                        let step = {
+                            let config = config.read().await;
+                            config.milestones.members
+                        };
+                        if let Some(milestone) = milestone::reached(step, old.max(announced), members) {
+                            announced = milestone;
+                            let msg = format!("> **We now have {} members!**", milestone);
+                            send_milestone(&cache_http, &config, &msg).await;
+                        }
+                    }
+                    DBEvent::MemberRemove { .. } => members = members.saturating_sub(1),
+                    _ => {}
+                }
+            }
         }
-    }
-    Ok(())
+    });
+}
+
+/// Send a message to milestone channels
+async fn send_milestone(cache_http: &CacheAndHttp, config: &RwLock<Config>, msg: &str) {
+    let _ = ctx!(
+        config::send(config, cache_http, &TextChannelTag::Milestone, msg).await,
+        "Failed to announce milestone"
+    );
 }
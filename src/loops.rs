@@ -1,12 +1,20 @@
 //! Loops that listens to events and updates the bot / discord accordingly
 use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use serenity::http::Http;
 use serenity::model::guild::{Guild, Member};
+use serenity::model::id::{ChannelId, GuildId, UserId};
+use serenity::model::mention::Mentionable;
+use serenity::model::voice::VoiceState;
 use serenity::CacheAndHttp;
 use tokio::sync::RwLock;
+use tokio::time;
 use tracing::{info, instrument, warn};
 
+use config::log_subscription::LogEvent;
+use config::tag::{ChannelTag, TextChannelTag};
+use config::war_voice::Cooldowns;
 use config::Config;
 use event::{DiscordContext, DiscordEvent, DiscordSignal};
 use memberdb::events::DBEvent;
@@ -14,29 +22,56 @@ use memberdb::model::discord::DiscordId;
 use memberdb::model::member::{MemberId, MemberRank};
 use memberdb::model::wynn::McId;
 use memberdb::DB;
-use util::discord;
+use util::task::{RestartPolicy, Spawner};
 use util::{ctxw, ok, some};
 use wynn::events::{WynnEvent, WynnSignal};
 
+use crate::util::exit_interview;
+use crate::util::invites::InviteCache;
+use crate::util::mutation::{Mutation, RetryQueue};
+use crate::util::poll;
+use crate::util::promotion_vote;
+use crate::util::rank_channel;
+use crate::util::reminder;
+use crate::util::suggestion;
+use crate::util::sync_state::DiscordSyncState;
+
 /// Start event listening loops
 pub async fn start_loops(
-    cache_http: Arc<CacheAndHttp>, db: Arc<RwLock<DB>>, config: Arc<RwLock<Config>>, wynn_sig: WynnSignal,
-    dc_sig: DiscordSignal,
+    spawner: &impl Spawner, cache_http: Arc<CacheAndHttp>, db: Arc<RwLock<DB>>, config: Arc<RwLock<Config>>,
+    wynn_sig: WynnSignal, dc_sig: DiscordSignal,
 ) {
     let shared_cache_http = cache_http.clone();
     let shared_db = db.clone();
     let shared_config = config.clone();
     let shared_dc_sig = dc_sig.clone();
-    tokio::spawn(async move {
-        let guild = crate::wait_main_guild(shared_dc_sig).await;
-        info!("Starting discord event listening loop (db event)");
-        let mut recv = {
-            let db = shared_db.read().await;
-            db.connect()
-        };
-        loop {
-            let event = recv.recv().await.unwrap();
-            process_db_event(&shared_cache_http, &shared_db, &shared_config, &guild, &event).await;
+    spawner.spawn("discord sync (db event)", RestartPolicy::Always, move || {
+        let shared_dc_sig = shared_dc_sig.clone();
+        let shared_cache_http = shared_cache_http.clone();
+        let shared_db = shared_db.clone();
+        let shared_config = shared_config.clone();
+        async move {
+            let guild = crate::wait_main_guild(shared_dc_sig).await;
+            info!("Starting discord event listening loop (db event)");
+            let mut recv = {
+                let db = shared_db.read().await;
+                db.connect()
+            };
+            // Retries are done in this loop, so they can't override the mutations of newer events
+            let mut retries = RetryQueue::new();
+            let mut interval = time::interval(Duration::from_secs(5));
+            loop {
+                tokio::select! {
+                    event = recv.recv() => {
+                        let event = event.unwrap();
+                        process_db_event(&shared_cache_http, &shared_db, &shared_config, &guild, &mut retries, &event)
+                            .await;
+                    }
+                    _ = interval.tick() => {
+                        retries.retry_due(&shared_cache_http, &shared_db, &shared_config, &guild).await;
+                    }
+                }
+            }
         }
     });
 
@@ -44,107 +79,175 @@ pub async fn start_loops(
     let shared_db = db.clone();
     let shared_config = config.clone();
     let shared_dc_sig = dc_sig.clone();
-    tokio::spawn(async move {
-        let guild = crate::wait_main_guild(shared_dc_sig).await;
-        info!("Starting discord event listening loop (wynn event)");
-        let mut recv = wynn_sig.connect();
-        loop {
-            let events = recv.recv().await.unwrap();
-            for event in events.as_ref() {
-                process_wynn_event(&shared_cache_http, &shared_db, &shared_config, &guild, event).await;
+    spawner.spawn("discord sync (wynn event)", RestartPolicy::Always, move || {
+        let shared_dc_sig = shared_dc_sig.clone();
+        let wynn_sig = wynn_sig.clone();
+        let shared_cache_http = shared_cache_http.clone();
+        let shared_db = shared_db.clone();
+        let shared_config = shared_config.clone();
+        async move {
+            let guild = crate::wait_main_guild(shared_dc_sig).await;
+            info!("Starting discord event listening loop (wynn event)");
+            let mut recv = wynn_sig.connect();
+            loop {
+                let events = recv.recv().await.unwrap();
+                for event in events.as_ref() {
+                    process_wynn_event(&shared_cache_http, &shared_db, &shared_config, &guild, event).await;
+                }
+            }
+        }
+    });
+
+    let shared_cache_http = cache_http.clone();
+    let shared_db = db.clone();
+    let shared_config = config.clone();
+    let shared_dc_sig = dc_sig.clone();
+    spawner.spawn("promotion vote closing", RestartPolicy::Always, move || {
+        let shared_dc_sig = shared_dc_sig.clone();
+        let shared_cache_http = shared_cache_http.clone();
+        let shared_db = shared_db.clone();
+        let shared_config = shared_config.clone();
+        async move {
+            let guild = crate::wait_main_guild(shared_dc_sig).await;
+            info!("Starting promotion vote closing loop");
+            let mut interval = time::interval(Duration::from_secs(60));
+            loop {
+                interval.tick().await;
+                let now = ok!(
+                    SystemTime::now().duration_since(UNIX_EPOCH),
+                    "Failed to get current unix timestamp",
+                    continue
+                );
+                let now = ok!(i64::try_from(now.as_secs()), "Failed to convert u64 to i64", continue);
+                promotion_vote::close_due_votes(&shared_cache_http, &shared_db, &shared_config, &guild, now)
+                    .await;
+            }
+        }
+    });
+
+    let shared_cache_http = cache_http.clone();
+    let shared_db = db.clone();
+    spawner.spawn("poll closing", RestartPolicy::Always, move || {
+        let shared_cache_http = shared_cache_http.clone();
+        let shared_db = shared_db.clone();
+        async move {
+            info!("Starting poll closing loop");
+            let mut interval = time::interval(Duration::from_secs(60));
+            loop {
+                interval.tick().await;
+                let now = ok!(
+                    SystemTime::now().duration_since(UNIX_EPOCH),
+                    "Failed to get current unix timestamp",
+                    continue
+                );
+                let now = ok!(i64::try_from(now.as_secs()), "Failed to convert u64 to i64", continue);
+                poll::close_due_polls(&shared_cache_http, &shared_db, now).await;
             }
         }
     });
 
-    tokio::spawn(async move {
-        info!("Starting discord event listening loop (discord event)");
-        let mut recv = dc_sig.connect();
-        loop {
-            let event = recv.recv().await.unwrap();
-            let (ctx, event) = event.as_ref();
-            process_discord_event(&cache_http, &db, &config, event, ctx).await;
+    let shared_cache_http = cache_http.clone();
+    let shared_db = db.clone();
+    spawner.spawn("reminder sending", RestartPolicy::Always, move || {
+        let shared_cache_http = shared_cache_http.clone();
+        let shared_db = shared_db.clone();
+        async move {
+            info!("Starting reminder sending loop");
+            let mut interval = time::interval(Duration::from_secs(30));
+            loop {
+                interval.tick().await;
+                let now = ok!(
+                    SystemTime::now().duration_since(UNIX_EPOCH),
+                    "Failed to get current unix timestamp",
+                    continue
+                );
+                let now = ok!(i64::try_from(now.as_secs()), "Failed to convert u64 to i64", continue);
+                reminder::send_due_reminders(&shared_cache_http, &shared_db, now).await;
+            }
+        }
+    });
+
+    spawner.spawn("discord sync (discord event)", RestartPolicy::Always, move || {
+        let dc_sig = dc_sig.clone();
+        let cache_http = cache_http.clone();
+        let db = db.clone();
+        let config = config.clone();
+        async move {
+            info!("Starting discord event listening loop (discord event)");
+            let mut recv = dc_sig.connect();
+            let mut invites = InviteCache::new();
+            let mut cooldowns = Cooldowns::new();
+            loop {
+                let event = recv.recv().await.unwrap();
+                let (ctx, event) = event.as_ref();
+                process_discord_event(&cache_http, &db, &config, &mut invites, &mut cooldowns, event, ctx)
+                    .await;
+            }
         }
     });
 }
 
-#[instrument(skip(cache_http, guild, db))]
+#[instrument(skip(cache_http, guild, db, retries))]
 async fn process_db_event(
-    cache_http: &CacheAndHttp, db: &RwLock<DB>, config: &RwLock<Config>, guild: &Guild, event: &DBEvent,
+    cache_http: &CacheAndHttp, db: &RwLock<DB>, config: &RwLock<Config>, guild: &Guild,
+    retries: &mut RetryQueue, event: &DBEvent,
 ) {
+    let http = &cache_http.http;
     match event {
-        DBEvent::MemberAdd {
-            discord_id: Some(discord_id), rank, mid, ..
-        } => {
+        DBEvent::MemberAdd { discord_id: Some(discord_id), rank, .. } => {
+            sync_rank_channels(cache_http, config, *discord_id, Some(*rank)).await;
             let mut member = some!(get_discord_member(cache_http, guild, *discord_id).await, return);
-            add_init_role_nick(&cache_http.http, db, config, *mid, *rank, guild, &mut member).await;
+            let mutations = Mutation::all(Some(String::new()));
+            retries.apply(http, db, config, guild, &mut member, mutations).await;
         }
         DBEvent::MemberRemove { discord_id: Some(discord_id), .. }
         | DBEvent::DiscordProfileUnbind { before: discord_id, removed: false, .. } => {
+            // The discord user is no longer a member, so their roles, nick & rank channel access
+            // are removed
+            sync_rank_channels(cache_http, config, *discord_id, None).await;
             let mut member = some!(get_discord_member(cache_http, guild, *discord_id).await, return);
-            remove_all_role_nick(&cache_http.http, config, guild, &mut member).await;
+            retries.apply(http, db, config, guild, &mut member, Mutation::all(None)).await;
         }
-        DBEvent::MemberRankChange { mid, new: rank, .. } => {
-            let mut member = some!(get_discord_member_db(cache_http, db, *mid, guild).await, return);
-
-            let update_role = {
-                let config = config.read().await;
-                config.should_update_role(&member)
-            };
-            if update_role {
-                info!("Updating discord roles");
-                let _ = ctxw!(
-                    crate::util::discord::fix_discord_roles(&cache_http.http, *rank, guild, &mut member)
-                        .await
-                );
+        DBEvent::MemberRankChange { mid, new: rank, .. } | DBEvent::MemberRankExpire { mid, new: rank, .. } => {
+            if let DBEvent::MemberRankExpire { old, .. } = event {
+                announce_rank_expire(cache_http, db, config, guild, *mid, *old, *rank).await;
             }
-
-            let update_nick = {
-                let config = config.read().await;
-                config.should_update_nick(&member)
+            let discord_id = {
+                let db = db.read().await;
+                ok!(mid.links(&mut db.exe()).await, return).0
             };
-            if update_nick {
-                info!("Updating discord nickname");
-                if let Err(why) =
-                    crate::util::discord::fix_member_nick(&cache_http.http, db, *mid, &member, None).await
-                {
-                    warn!("Failed to update discord member's nickname: {:#}", why);
-                }
+            if let Some(discord_id) = discord_id {
+                sync_rank_channels(cache_http, config, discord_id, Some(*rank)).await;
             }
+
+            let mut member = some!(get_discord_member_db(cache_http, db, *mid, guild).await, return);
+            retries.apply(http, db, config, guild, &mut member, Mutation::all(None)).await;
         }
         DBEvent::WynnProfileBind { mid, .. } | DBEvent::WynnProfileUnbind { mid, removed: false, .. } => {
             // Because discord nickname prioritize ign, so when a member's wynn profile is
             // binded / unbinded, their discord nickname need to be updated.
-            let member = some!(get_discord_member_db(cache_http, db, *mid, guild).await, return);
-
-            {
-                let config = config.read().await;
-                if !config.should_update_nick(&member) {
-                    return;
-                }
-            }
-
-            info!("Updating discord nickname");
-            if let Err(why) =
-                crate::util::discord::fix_member_nick(&cache_http.http, db, *mid, &member, None).await
-            {
-                warn!("Failed to update discord member's nickname: {:#}", why);
-            }
+            let mut member = some!(get_discord_member_db(cache_http, db, *mid, guild).await, return);
+            let mutation = Mutation::Nick { custom_nick: None };
+            retries.apply(http, db, config, guild, &mut member, [mutation]).await;
         }
-        DBEvent::DiscordProfileBind { mid, old, new } => {
-            // Remove roles & nick from the old discord user
+        DBEvent::DiscordProfileBind { old, new, .. } => {
+            // Remove roles & nick from the old discord user, as it is no longer a member
             if let Some(old_discord) = old {
+                sync_rank_channels(cache_http, config, *old_discord, None).await;
                 if let Some(mut old_member) = get_discord_member(cache_http, guild, *old_discord).await {
-                    remove_all_role_nick(&cache_http.http, config, guild, &mut old_member).await;
+                    retries.apply(http, db, config, guild, &mut old_member, Mutation::all(None)).await;
                 }
             }
 
-            // Add roles & nick to the new discord user
+            // Add roles, nick & rank channel access to the new discord user
+            let rank = ok!(rank_channel::member_rank(db, *new).await, return);
+            sync_rank_channels(cache_http, config, *new, rank).await;
             let mut member = some!(get_discord_member(cache_http, guild, *new).await, return);
-            let rank = {
-                let db = db.read().await;
-                ok!(mid.rank(&mut db.exe()).await, return)
-            };
-            add_init_role_nick(&cache_http.http, db, config, *mid, rank, guild, &mut member).await;
+            let mutations = Mutation::all(Some(String::new()));
+            retries.apply(http, db, config, guild, &mut member, mutations).await;
+        }
+        DBEvent::MemberExit { exit } => {
+            exit_interview::ask(http, db, config, exit).await;
         }
         _ => {}
     }
@@ -159,7 +262,7 @@ pub async fn process_wynn_event(
         WynnEvent::MemberNameChange { id, new_name, .. } => {
             let mcid = McId(id.clone());
             // Update discord nick due to ign change.
-            let (member, mid) = some!(get_discord_member_mc(cache_http, db, &mcid, guild).await, return);
+            let (mut member, mid) = some!(get_discord_member_mc(cache_http, db, &mcid, guild).await, return);
 
             {
                 let config = config.read().await;
@@ -172,13 +275,9 @@ pub async fn process_wynn_event(
                 let db = db.read().await;
                 ok!(mid.rank(&mut db.exe()).await, return)
             };
-            let custom_nick = match &member.nick {
-                Some(nick) => crate::util::discord::extract_custom_nick(nick),
-                None => "",
-            };
-            let new_nick = format!("{} {} {}", rank.get_symbol(), new_name, custom_nick);
-            let result = member.edit(&cache_http.http, |e| e.nickname(new_nick)).await;
-            if let Err(why) = result {
+            // The new ign is used directly, as the database may not be updated yet
+            let state = DiscordSyncState::member(rank, Some(new_name), guild, &member, None);
+            if let Err(why) = state.diff(guild, &member).apply_nick(&cache_http.http, &mut member).await {
                 warn!("Failed to update discord member's nickname: {:#}", why);
             }
         }
@@ -186,12 +285,23 @@ pub async fn process_wynn_event(
     }
 }
 
-#[instrument(skip(cache_http, db))]
+#[instrument(skip(cache_http, db, invites, cooldowns))]
 pub async fn process_discord_event(
-    cache_http: &CacheAndHttp, db: &RwLock<DB>, config: &RwLock<Config>, event: &DiscordEvent,
-    ctx: &DiscordContext,
+    cache_http: &CacheAndHttp, db: &RwLock<DB>, config: &RwLock<Config>, invites: &mut InviteCache,
+    cooldowns: &mut Cooldowns, event: &DiscordEvent, ctx: &DiscordContext,
 ) {
     match event {
+        DiscordEvent::Ready => {
+            info!("Caching discord invites");
+            let _ = ctxw!(invites.refresh(&cache_http.http, ctx.main_guild.id).await);
+        }
+        DiscordEvent::InviteCreate { invite } if invite.guild_id == Some(ctx.main_guild.id) => {
+            let inviter = invite.inviter.as_ref().map(|user| user.id);
+            invites.insert(invite.code.clone(), inviter, invite.max_uses);
+        }
+        DiscordEvent::InviteDelete { invite } if invite.guild_id == Some(ctx.main_guild.id) => {
+            invites.remove(&invite.code);
+        }
         DiscordEvent::MemberUpdate { old: Some(old), new, .. } => {
             // Update discord nick due to discord username change
             if old.user.name != new.user.name {
@@ -207,17 +317,16 @@ pub async fn process_discord_event(
                     let db = db.read().await;
                     some!(ok!(id.mid(&mut db.exe()).await, return), return)
                 };
-                // Only updates it if the discord nick is using discord username instead of ign
-                let has_wynn = {
-                    let db = db.read().await;
-                    ok!(mid.links(&mut db.exe()).await, return).1.is_some()
-                };
-                if !has_wynn {
-                    if let Err(why) =
-                        crate::util::discord::fix_member_nick(&cache_http.http, db, mid, new, None).await
-                    {
-                        warn!("Failed to update discord member's nickname: {:#}", why);
-                    }
+                // The nick only changes if it is using discord username instead of ign
+                let state = ok!(
+                    ctxw!(DiscordSyncState::of_member(db, mid, &ctx.main_guild, new, None).await),
+                    return
+                );
+                let mut member = new.clone();
+                if let Err(why) =
+                    state.diff(&ctx.main_guild, new).apply_nick(&cache_http.http, &mut member).await
+                {
+                    warn!("Failed to update discord member's nickname: {:#}", why);
                 }
             }
         }
@@ -228,11 +337,134 @@ pub async fn process_discord_event(
                     warn!("Failed to send welcome message: {:#}", why);
                 }
             }
+
+            if member.guild_id == ctx.main_guild.id {
+                record_invite(&cache_http.http, db, invites, member, ctx.main_guild.id).await;
+            }
+        }
+        DiscordEvent::VoiceJoin { state } => {
+            announce_war_voice_join(cache_http, config, cooldowns, state).await;
+        }
+        DiscordEvent::Message { message } => {
+            let is_suggestion = {
+                let config = config.read().await;
+                config.text_channel_tags.tagged(&message.channel_id.0, &TextChannelTag::Suggestion)
+            };
+            if is_suggestion {
+                let _ = ctxw!(suggestion::repost(cache_http, db, config, message).await);
+            }
+            if message.guild_id.is_none() {
+                let _ = ctxw!(exit_interview::record_response(cache_http, db, config, message).await);
+            }
         }
         _ => {}
     }
 }
 
+/// Find the invite a discord user joined with, and record it in the database.
+async fn record_invite(
+    http: &Http, db: &RwLock<DB>, invites: &mut InviteCache, member: &Member, guild_id: GuildId,
+) {
+    let (code, inviter) = match ctxw!(invites.find_used(http, guild_id).await) {
+        Ok(Some(used)) => used,
+        _ => {
+            info!(user = member.user.id.0, "Unable to determine the invite used to join");
+            return;
+        }
+    };
+    let id = ok!(DiscordId::try_from(member.user.id.0), return);
+    let inviter = match inviter {
+        Some(inviter) => Some(ok!(DiscordId::try_from(inviter.0), return)),
+        None => None,
+    };
+
+    let db = db.write().await;
+    let mut tx = ok!(ctxw!(db.begin().await), return);
+    ok!(ctxw!(id.set_inviter(&mut tx, inviter, &code).await), return);
+    let _ = ctxw!(tx.commit().await);
+}
+
+/// Announce a user joining a [`ChannelTag::WarVoice`] channel in its linked text channel, along
+/// with how many users are in the voice channel, unless the channel is on cooldown.
+///
+/// [`ChannelTag::WarVoice`]: config::tag::ChannelTag::WarVoice
+async fn announce_war_voice_join(
+    cache_http: &CacheAndHttp, config: &RwLock<Config>, cooldowns: &mut Cooldowns, state: &VoiceState,
+) {
+    let channel_id = some!(state.channel_id, return);
+    let guild_id = some!(state.guild_id, return);
+    let (text_channel, cooldown) = {
+        let config = config.read().await;
+        if !config.channel_tags.tagged(&channel_id.0, &ChannelTag::WarVoice) {
+            return;
+        }
+        (config.war_voice.text_channel(channel_id.0), config.war_voice.cooldown)
+    };
+
+    let now =
+        ok!(SystemTime::now().duration_since(UNIX_EPOCH), "Failed to get current unix timestamp", return);
+    let now = ok!(i64::try_from(now.as_secs()), "Failed to convert u64 to i64", return);
+    if !cooldowns.try_start(channel_id.0, now, cooldown) {
+        return;
+    }
+
+    let assembled = some!(
+        cache_http.cache.guild_field(guild_id, |guild| {
+            guild.voice_states.values().filter(|s| s.channel_id == Some(channel_id)).count()
+        }),
+        return
+    );
+    let name = match &state.member {
+        Some(member) => member.display_name().into_owned(),
+        None => state.user_id.mention().to_string(),
+    };
+    let msg = format!("**{}** joined war voice, {} members assembled", name, assembled);
+    if let Err(why) = ChannelId(text_channel).say(&cache_http.http, msg).await {
+        warn!(text_channel, "Failed to announce war voice join: {:#}", why);
+    }
+}
+
+/// Sync the access of a discord user to the rank channels, `rank` is their member rank, `None` if
+/// they aren't a member
+async fn sync_rank_channels(
+    cache_http: &CacheAndHttp, config: &RwLock<Config>, discord_id: DiscordId, rank: Option<MemberRank>,
+) {
+    let user_id = ok!(u64::try_from(discord_id.0), "Failed to convert DiscordId to UserId", return);
+    let result =
+        rank_channel::sync_member(&cache_http.cache, &cache_http.http, config, UserId(user_id), rank).await;
+    if let Err(why) = result {
+        warn!(user_id, "Failed to sync rank channel access: {:#}", why);
+    }
+}
+
+/// Announce a member's temporary rank expiring in the log channels subscribed to it.
+async fn announce_rank_expire(
+    cache_http: &CacheAndHttp, db: &RwLock<DB>, config: &RwLock<Config>, guild: &Guild, mid: MemberId,
+    old: MemberRank, new: MemberRank,
+) {
+    let mcid = {
+        let db = db.read().await;
+        ok!(mid.mcid(&mut db.exe()).await, return)
+    };
+    let name = match mcid {
+        Some(mcid) => {
+            let db = db.read().await;
+            ok!(mcid.ign(&mut db.exe()).await, return)
+        }
+        None => match get_discord_member_db(cache_http, db, mid, guild).await {
+            Some(member) => util::discord::member_display_name(&member),
+            None => return,
+        },
+    };
+
+    let msg = format!("**{}**'s temporary rank __{}__ has expired, reverted to __{}__", name, old, new);
+    let channels: Vec<u64> = {
+        let config = config.read().await;
+        config.log_subscriptions.subscribers(&LogEvent::RankExpire).copied().collect()
+    };
+    let _ = ctxw!(config::send_log(config, cache_http, &channels, &msg).await);
+}
+
 /// Get a mcid's associated discord user and id.
 pub async fn get_discord_member_mc(
     cache_http: &CacheAndHttp, db: &RwLock<DB>, mcid: &McId, guild: &Guild,
@@ -277,56 +509,3 @@ pub async fn get_discord_member(cache_http: &CacheAndHttp, guild: &Guild, id: Di
         ok!(ctxw!(guild.member(&cache_http, user_id).await, "Failed to get discord member"), return None);
     Some(member)
 }
-
-/// Update a discord user's nick and add rank roles.
-pub async fn add_init_role_nick(
-    http: &Http, db: &RwLock<DB>, config: &RwLock<Config>, mid: MemberId, rank: MemberRank, guild: &Guild,
-    member: &mut Member,
-) {
-    let update_role = {
-        let config = config.read().await;
-        config.should_update_role(member)
-    };
-    if update_role {
-        info!("Updating discord roles");
-        let _ = ctxw!(crate::util::discord::fix_discord_roles(http, rank, guild, member).await);
-    }
-
-    let update_nick = {
-        let config = config.read().await;
-        config.should_update_nick(member)
-    };
-    if update_nick {
-        info!("Updating discord nickname");
-        if let Err(why) = crate::util::discord::fix_member_nick(http, db, mid, member, Some("")).await {
-            warn!("Failed to set discord member's initial nickname: {:#}", why);
-        }
-    }
-}
-
-/// Remove a discord user's nick and all rank roles.
-pub async fn remove_all_role_nick(http: &Http, config: &RwLock<Config>, guild: &Guild, member: &mut Member) {
-    let update_role = {
-        let config = config.read().await;
-        config.should_update_role(member)
-    };
-    if update_role {
-        info!("Removing discord rank roles");
-        for rank in memberdb::model::member::MANAGED_MEMBER_RANKS {
-            let _ = ctxw!(discord::remove_role_maybe(http, rank.get_role(guild), member).await);
-            let _ = ctxw!(discord::remove_role_maybe(http, rank.get_group_role(guild), member).await);
-        }
-    }
-
-    let update_nick = {
-        let config = config.read().await;
-        config.should_update_nick(member)
-    };
-    if update_nick {
-        info!("Removing discord nickname");
-        let result = member.edit(&http, |e| e.nickname("")).await;
-        if let Err(why) = result {
-            warn!("Failed to remove discord member's nickname: {:#}", why);
-        }
-    }
-}
@@ -1,28 +1,43 @@
 use std::env;
+use std::path::PathBuf;
+use std::sync::Arc;
 
 use serenity::framework::standard::macros::group;
 use serenity::http::Http;
+use tokio::sync::{Mutex, RwLock};
 use tokio::time::{self, Duration};
 use tracing::{error, info};
-use tracing_subscriber::filter::LevelFilter;
+use tracing_subscriber::fmt;
 use tracing_subscriber::layer::SubscriberExt;
-use tracing_subscriber::{fmt, Layer};
 
 use memberdb::TrackedIgnGetter;
+use util::task::{RestartPolicy, Spawner};
+use wynn::api::HttpApi;
 
 use haxbotjr::commands::*;
-use haxbotjr::data::BotData;
+use haxbotjr::data::{BotData, MEMBER_DB_FILE};
+use haxbotjr::log_level::LogLevels;
+use haxbotjr::redact::RedactFields;
+use haxbotjr::secrets::Secrets;
 
 #[group]
-#[commands(ping, set_custom_nick, display_online_players)]
+#[commands(ping, bot_info, set_custom_nick, display_online_players, display_level_progress, display_guild_activity)]
 struct General;
 
 #[group]
-#[commands(display_profile, stat_leaderboard, display_table)]
+#[commands(
+    display_profile,
+    compare_profiles,
+    display_card,
+    stat_leaderboard,
+    recruiter_leaderboard,
+    display_guild_info,
+    display_table
+)]
 struct Statistics;
 
 #[group]
-#[commands(list_member, display_member_info)]
+#[commands(list_member, display_member_info, display_rank_history, display_rank_stats)]
 struct Members;
 
 #[group("Member Management")]
@@ -31,27 +46,49 @@ struct Members;
     unlink_profile,
     add_partial,
     add_member,
+    add_guest,
     remove_member,
     set_member_rank,
     promote_member,
     demote_member,
+    vote_member_rank,
     fix_nick,
     fix_role,
-    sync_member_ign
+    fix_all,
+    sync_member_ign,
+    refresh_member,
+    stat_audit,
+    reset_now,
+    weekly_report,
+    simulate_requirements,
+    reset_member_stat,
+    list_unlinked,
+    exit_log,
+    pending_logs
 )]
 struct MemberManagement;
 
 #[group]
-#[commands(get_rank_symbols, utc_now, list_igns)]
+#[commands(
+    get_rank_symbols,
+    utc_now,
+    next_reset,
+    task_status,
+    list_igns,
+    remind_me,
+    poll_info,
+    suggest,
+    list_suggestions
+)]
 struct Utilities;
 
 #[group]
-#[commands(list_tags)]
+#[commands(list_tags, show_config, set_locale, stat_format)]
 struct Configuration;
 
 #[group]
 #[owners_only]
-#[commands(sql, check_db_integrity)]
+#[commands(sql, check_db_integrity, migrate, log_level, redact_logs, weekly_reset, show_secrets)]
 struct Owner;
 
 #[tokio::main]
@@ -62,31 +99,39 @@ async fn main() {
     // Initialize logging
     let file_appender = tracing_appender::rolling::daily("./log", "log");
     let (file_writer, _guard) = tracing_appender::non_blocking(file_appender);
+    let (log_filter, log_levels) = LogLevels::layer();
     tracing::subscriber::set_global_default(
         tracing_subscriber::registry()
+            .with(log_filter)
+            .with(haxbotjr::report::layer())
             .with(
                 fmt::Layer::default()
                     .with_ansi(false)
                     .with_timer(fmt::time::UtcTime::rfc_3339())
-                    .with_writer(file_writer)
-                    .with_filter(LevelFilter::INFO),
+                    .fmt_fields(RedactFields)
+                    .with_writer(file_writer),
             )
             .with(
                 fmt::Layer::default()
                     .with_ansi(true)
                     .with_timer(fmt::time::UtcTime::rfc_3339())
-                    .with_writer(std::io::stdout)
-                    .with_filter(LevelFilter::INFO),
+                    .with_writer(std::io::stdout),
             ),
     )
     .expect("Failed to set global log subscriber");
+    haxbotjr::tasks::log_panics();
+    let _report_guard = haxbotjr::report::init();
 
     // Get global variables
-    let token = env::var("DISCORD_TOKEN").expect("Expected a token in the environment");
+    let secrets = Secrets::load().expect("Failed to load secrets");
+    let token = secrets.discord_token.clone();
     let http = Http::new(&token);
 
     // Creating client
-    let bot_data = BotData::new("./database/member.db", "./config.json").await;
+    let bot_data = BotData::new(MEMBER_DB_FILE, "./config.json").await;
+    haxbotjr::redact::set_enabled(bot_data.config.read().await.redact_logs);
+    bot_data.db.read().await.set_stat_audit(bot_data.config.read().await.stat_audit.enabled);
+    let modules = Arc::new(haxbotjr::modules::enabled(&bot_data).await);
     let framework = haxbotjr::my_framework(&http)
         .await
         .help(&MY_HELP)
@@ -96,35 +141,75 @@ async fn main() {
         .group(&MEMBERMANAGEMENT_GROUP)
         .group(&CONFIGURATION_GROUP)
         .group(&UTILITIES_GROUP)
-        .group(&OWNER_GROUP)
+        .group(&OWNER_GROUP);
+    let framework = haxbotjr::modules::register(framework, &modules)
         // Rate limit for mojang api, 1 request lower just in case
         .bucket("mojang", |b| b.time_span(600).limit(599))
         .await;
-    let mut client = haxbotjr::my_client(&token, framework, bot_data.discord_signal.clone())
+    let mut client = haxbotjr::my_client(&token, framework, bot_data.discord_signal.clone(), modules.clone())
         .await
         .expect("Failed to create client");
     bot_data.add_to_client(&client).await;
+    {
+        let mut data = client.data.write().await;
+        data.insert::<LogLevels>(Arc::new(Mutex::new(log_levels)));
+        data.insert::<Secrets>(Arc::new(RwLock::new(secrets.clone())));
+    }
 
     // Start loops
     let data = bot_data.clone();
-    event::timer::start_loop(data.timer_signal).await;
+    let tasks = &bot_data.tasks;
+    {
+        let config = data.config.read().await;
+        config.weekly_reset.time().expect("Invalid weekly reset time in config");
+    }
+    let config = data.config.clone();
+    let weekly_reset = move || {
+        let config = config.clone();
+        async move { config.read().await.weekly_reset.time() }
+    };
+    event::timer::start_loop(tasks, data.timer_signal, weekly_reset).await;
 
     let data = bot_data.clone();
     let cache_http = client.cache_and_http.clone();
-    haxbotjr::logging::start_log_loop(cache_http, data.config, data.wynn_signal).await;
+    haxbotjr::logging::start_log_loop(tasks, cache_http, data.config, data.outbox, data.wynn_signal).await;
 
     let data = bot_data.clone();
     let cache_http = client.cache_and_http.clone();
-    haxbotjr::logging::start_summary_loop(cache_http, data.config, data.db).await;
+    haxbotjr::logging::start_summary_loop(tasks, cache_http, data.config, data.db, data.outbox).await;
 
     let data = bot_data.clone();
     let cache_http = client.cache_and_http.clone();
-    haxbotjr::loops::start_loops(cache_http, data.db, data.config, data.wynn_signal, data.discord_signal)
-        .await;
+    haxbotjr::util::outbox::start_flush_loop(tasks, cache_http, data.config, data.outbox).await;
+
+    let data = bot_data.clone();
+    let cache_http = client.cache_and_http.clone();
+    haxbotjr::logging::start_milestone_loop(
+        tasks,
+        cache_http,
+        data.config,
+        data.db,
+        data.wynn_cache,
+        data.wynn_signal,
+    )
+    .await;
+
+    let data = bot_data.clone();
+    let cache_http = client.cache_and_http.clone();
+    haxbotjr::loops::start_loops(
+        tasks,
+        cache_http,
+        data.db,
+        data.config,
+        data.wynn_signal,
+        data.discord_signal,
+    )
+    .await;
 
     let data = bot_data.clone();
     let cache = client.cache_and_http.cache.clone();
     memberdb::loops::start_loops(
+        tasks,
         data.db,
         data.config,
         cache,
@@ -133,47 +218,97 @@ async fn main() {
         data.wynn_signal,
         data.discord_signal,
         data.timer_signal,
+        data.reset_gate,
     )
     .await;
 
     let data = bot_data.clone();
-    config::start_loop(data.config, data.discord_signal).await;
+    memberdb::loops::start_ign_sync_loop(tasks, data.db, HttpApi(data.reqwest_client)).await;
 
     let data = bot_data.clone();
+    config::start_loop(tasks, data.config, data.discord_signal).await;
+
+    let data = bot_data.clone();
+    haxbotjr::util::autocomplete::start_invalidation_loop(tasks, data.ign_index, data.db, data.wynn_signal)
+        .await;
+
+    let data = bot_data.clone();
+    haxbotjr::forward::start_forward_loop(tasks, data.reqwest_client, data.config, data.db, data.wynn_signal)
+        .await;
+
+    let data = bot_data.clone();
+    haxbotjr::linked_roles::start_linked_roles(
+        tasks,
+        data.reqwest_client,
+        &http,
+        &token,
+        secrets.client_secret,
+        data.config,
+        data.db,
+    )
+    .await;
+
+    let data = bot_data.clone();
+    let (ignored_worlds, payload_dir) = {
+        let config = data.config.read().await;
+        (config.ignored_worlds.clone(), config.api_payload_dir.clone())
+    };
+    wynn::diagnostics::set_payload_dir(payload_dir.map(PathBuf::from));
     wynn::loops::start_loops(
+        tasks,
         data.wynn_signal,
-        data.reqwest_client,
+        HttpApi(data.reqwest_client),
         data.wynn_cache,
         TrackedIgnGetter(data.db),
+        ignored_worlds,
     )
     .await;
 
+    let cache_http = client.cache_and_http.clone();
+    let module_ctx = haxbotjr::modules::ModuleContext { tasks, data: &bot_data, cache_http };
+    haxbotjr::modules::start(&modules, &module_ctx).await;
+
     let data = bot_data.clone();
-    tokio::spawn(async move {
-        info!("Starting periodic state saving loop");
-        let mut interval = time::interval(Duration::from_secs(60));
-        loop {
-            interval.tick().await;
-            data.wynn_cache.write().await;
-            data.config.read().await.write("./config.json");
+    tasks.spawn("state saving", RestartPolicy::Always, move || {
+        let data = data.clone();
+        async move {
+            info!("Starting periodic state saving loop");
+            let mut interval = time::interval(Duration::from_secs(60));
+            loop {
+                interval.tick().await;
+                data.wynn_cache.write().await;
+                data.config.read().await.write("./config.json");
+            }
         }
     });
 
+    let data = bot_data.clone();
     let shard_manager = client.shard_manager.clone();
-    tokio::spawn(async move {
-        info!("Starting ctrl+c handler");
-        tokio::signal::ctrl_c().await.expect("Could not register ctrl+c handler");
-
-        // shutdown codes
-        info!("Saving api cache files");
-        bot_data.wynn_cache.write().await;
-        info!("Saving config file");
-        bot_data.config.read().await.write("./config.json");
-        shard_manager.lock().await.shutdown_all().await;
+    tasks.spawn("ctrl+c handler", RestartPolicy::Never, move || {
+        let data = data.clone();
+        let shard_manager = shard_manager.clone();
+        async move {
+            info!("Starting ctrl+c handler");
+            tokio::signal::ctrl_c().await.expect("Could not register ctrl+c handler");
+
+            // shutdown codes
+            info!("Saving api cache files");
+            data.wynn_cache.write().await;
+            info!("Saving config file");
+            data.config.read().await.write("./config.json");
+            info!("Saving outbox file");
+            data.outbox.save();
+            shard_manager.lock().await.shutdown_all().await;
+        }
     });
 
-    // Start client
-    if let Err(why) = client.start().await {
+    // Start client, with the amount of shards recommended by discord unless it is set
+    let result = match env::var("SHARDS") {
+        Ok(shards) => client.start_shards(shards.parse().expect("Invalid shard count")).await,
+        Err(_) => client.start_autosharded().await,
+    };
+    if let Err(why) = result {
         error!("Client error: {:?}", why);
     }
+    haxbotjr::secrets::restart_if_requested();
 }
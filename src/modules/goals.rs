@@ -0,0 +1,37 @@
+//! Personal stat goals of members, and the guild goals staff set for the whole guild.
+//!
+//! Its commands are listed under the `Goals` group of the help, they used to be listed under
+//! `Statistics`. The module also posts the daily progress and the completion of guild goals.
+//! Guild goals are ended by the daily reset of the member database, which isn't part of this
+//! module, so goals still end while it is disabled.
+use serenity::framework::standard::macros::group;
+use serenity::framework::standard::CommandGroup;
+use serenity::futures::future::BoxFuture;
+
+use crate::commands::*;
+use crate::modules::{BotModule, ModuleContext};
+
+#[group]
+#[commands(show_goals, show_guild_goals)]
+struct Goals;
+
+/// Goals module
+pub struct GoalsModule;
+
+impl BotModule for GoalsModule {
+    fn name(&self) -> &'static str {
+        "goals"
+    }
+
+    fn groups(&self) -> Vec<&'static CommandGroup> {
+        vec![&GOALS_GROUP]
+    }
+
+    fn start<'a>(&'a self, ctx: &'a ModuleContext<'a>) -> BoxFuture<'a, ()> {
+        Box::pin(async move {
+            let data = ctx.data.clone();
+            crate::util::guild_goal::start_post_loop(ctx.tasks, ctx.cache_http.clone(), data.config, data.db)
+                .await;
+        })
+    }
+}
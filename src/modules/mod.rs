@@ -0,0 +1,129 @@
+//! Feature modules, which are subsystems of the bot that can be enabled per deployment.
+//!
+//! A module registers its command groups, starts the loops that listen to its events, and responds
+//! to the message components it [handles](BotModule::handles), through [`BotModule`].
+//! All modules are listed in [`all`], the ones named in [`Config::disabled_modules`] aren't
+//! registered at startup.
+//!
+//! The member database tables of modules are created by the member database's migrations
+//! whether the modules are enabled or not, so a module can be enabled again without losing data.
+//!
+//! [`Config::disabled_modules`]: config::Config::disabled_modules
+pub mod goals;
+pub mod observer;
+pub mod recruitment;
+
+use std::sync::Arc;
+
+use anyhow::Result;
+use serenity::client::Context;
+use serenity::framework::standard::CommandGroup;
+use serenity::framework::StandardFramework;
+use serenity::futures::future::BoxFuture;
+use serenity::model::application::interaction::message_component::MessageComponentInteraction;
+use serenity::CacheAndHttp;
+use tracing::{info, warn};
+
+use crate::data::BotData;
+use crate::tasks::TaskRegistry;
+
+/// What a module is started with
+pub struct ModuleContext<'a> {
+    pub tasks: &'a TaskRegistry,
+    pub data: &'a BotData,
+    pub cache_http: Arc<CacheAndHttp>,
+}
+
+/// A subsystem of the bot
+pub trait BotModule: Send + Sync {
+    /// Name of the module, which is how it is disabled in the config
+    fn name(&self) -> &'static str;
+
+    /// Command groups of the module
+    fn groups(&self) -> Vec<&'static CommandGroup> {
+        Vec::new()
+    }
+
+    /// Start the loops of the module
+    fn start<'a>(&'a self, _ctx: &'a ModuleContext<'a>) -> BoxFuture<'a, ()> {
+        Box::pin(async {})
+    }
+
+    /// Whether the module responds to the message components with `custom_id`, such as its buttons
+    fn handles(&self, _custom_id: &str) -> bool {
+        false
+    }
+
+    /// Respond to a message component the module [handles](BotModule::handles)
+    fn on_interaction<'a>(
+        &'a self, _ctx: &'a Context, _interaction: &'a MessageComponentInteraction,
+    ) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async { Ok(()) })
+    }
+}
+
+/// Get all modules
+/// ```
+/// let modules = haxbotjr::modules::all();
+/// let mut names: Vec<_> = modules.iter().map(|module| module.name()).collect();
+/// names.sort();
+/// names.dedup();
+/// assert_eq!(names.len(), modules.len());
+/// ```
+pub fn all() -> Vec<Box<dyn BotModule>> {
+    vec![
+        Box::new(recruitment::RecruitmentModule),
+        Box::new(goals::GoalsModule),
+        Box::new(observer::ObserverModule),
+    ]
+}
+
+/// Get the modules that aren't disabled in the config
+pub async fn enabled(data: &BotData) -> Vec<Box<dyn BotModule>> {
+    let modules = all();
+    let disabled = data.config.read().await.disabled_modules.clone();
+    for name in &disabled {
+        if !modules.iter().any(|module| module.name() == name) {
+            warn!(name, "Unknown module is disabled in the config");
+        }
+    }
+
+    let mut enabled = Vec::new();
+    for module in modules {
+        if disabled.iter().any(|name| name == module.name()) {
+            info!(name = module.name(), "Module is disabled");
+            continue;
+        }
+        enabled.push(module);
+    }
+    enabled
+}
+
+/// Add the command groups of modules to a framework
+pub fn register(mut framework: StandardFramework, modules: &[Box<dyn BotModule>]) -> StandardFramework {
+    for module in modules {
+        for group in module.groups() {
+            framework = framework.group(group);
+        }
+    }
+    framework
+}
+
+/// Start the loops of modules
+pub async fn start(modules: &[Box<dyn BotModule>], ctx: &ModuleContext<'_>) {
+    for module in modules {
+        info!(name = module.name(), "Starting module");
+        module.start(ctx).await;
+    }
+}
+
+/// Pass a message component interaction to the modules that handle it
+pub async fn dispatch_interaction(
+    modules: &[Box<dyn BotModule>], ctx: &Context, interaction: &MessageComponentInteraction,
+) {
+    for module in modules.iter().filter(|module| module.handles(&interaction.data.custom_id)) {
+        if let Err(why) = module.on_interaction(ctx, interaction).await {
+            warn!(name = module.name(), "Failed to respond to interaction: {:#}", why);
+        }
+    }
+}
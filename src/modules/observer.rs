@@ -0,0 +1,42 @@
+//! Observing the level and member count of other guilds, see [`crate::observer`].
+//!
+//! Its `observe` command is listed under the new `Intel` group of the help, it used to be listed
+//! under `Utilities`.
+use serenity::framework::standard::macros::group;
+use serenity::framework::standard::CommandGroup;
+use serenity::futures::future::BoxFuture;
+
+use wynn::api::HttpApi;
+
+use crate::commands::*;
+use crate::modules::{BotModule, ModuleContext};
+
+#[group]
+#[commands(observe_guild)]
+struct Intel;
+
+/// Guild observer module
+pub struct ObserverModule;
+
+impl BotModule for ObserverModule {
+    fn name(&self) -> &'static str {
+        "observer"
+    }
+
+    fn groups(&self) -> Vec<&'static CommandGroup> {
+        vec![&INTEL_GROUP]
+    }
+
+    fn start<'a>(&'a self, ctx: &'a ModuleContext<'a>) -> BoxFuture<'a, ()> {
+        Box::pin(async move {
+            let data = ctx.data.clone();
+            crate::observer::start_observer_loop(
+                ctx.tasks,
+                HttpApi(data.reqwest_client),
+                ctx.cache_http.clone(),
+                data.config,
+            )
+            .await;
+        })
+    }
+}
@@ -0,0 +1,76 @@
+//! Recruitment tickets, and the waitlist of recruits accepted while the in-game guild is full.
+//!
+//! Its commands are listed under the `Recruitment` group of the help, `tickets` along with its
+//! `panel` and `close` subcommands, and `waitlist` along with its `add`, `remove` and `cap`
+//! subcommands. The module also answers the buttons of ticket panels and tickets.
+use anyhow::Result;
+use serenity::client::Context;
+use serenity::framework::standard::macros::group;
+use serenity::framework::standard::CommandGroup;
+use serenity::futures::future::BoxFuture;
+use serenity::model::application::interaction::message_component::MessageComponentInteraction;
+
+use crate::commands::*;
+use crate::modules::{BotModule, ModuleContext};
+
+#[group]
+#[commands(list_tickets, list_waitlist)]
+struct Recruitment;
+
+/// Recruitment module
+/// ```
+/// use haxbotjr::modules::recruitment::RecruitmentModule;
+/// use haxbotjr::modules::BotModule;
+///
+/// let module = RecruitmentModule;
+/// let names: Vec<_> = module.groups()[0]
+///     .options
+///     .commands
+///     .iter()
+///     .flat_map(|command| {
+///         let subcommands = command.options.sub_commands.iter();
+///         command.options.names.iter().chain(subcommands.flat_map(|sub| sub.options.names))
+///     })
+///     .copied()
+///     .collect();
+/// assert_eq!(names, ["tickets", "panel", "close", "waitlist", "add", "remove", "cap"]);
+///
+/// assert!(module.handles("ticket_close:3"));
+/// assert!(!module.handles("weekly_reset_proceed"));
+/// ```
+pub struct RecruitmentModule;
+
+impl BotModule for RecruitmentModule {
+    fn name(&self) -> &'static str {
+        "recruitment"
+    }
+
+    fn groups(&self) -> Vec<&'static CommandGroup> {
+        vec![&RECRUITMENT_GROUP]
+    }
+
+    fn start<'a>(&'a self, ctx: &'a ModuleContext<'a>) -> BoxFuture<'a, ()> {
+        Box::pin(async move {
+            let data = ctx.data.clone();
+            crate::util::waitlist::start_slot_loop(
+                ctx.tasks,
+                ctx.cache_http.clone(),
+                data.config,
+                data.db,
+                data.wynn_cache,
+                data.wynn_signal,
+            )
+            .await;
+        })
+    }
+
+    fn handles(&self, custom_id: &str) -> bool {
+        crate::util::ticket::is_ticket_button(custom_id)
+    }
+
+    fn on_interaction<'a>(
+        &'a self, ctx: &'a Context, interaction: &'a MessageComponentInteraction,
+    ) -> BoxFuture<'a, Result<()>> {
+        Box::pin(crate::util::ticket::respond(ctx, interaction))
+    }
+}
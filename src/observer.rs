@@ -0,0 +1,124 @@
+//! Observing of other guilds listed in [`Config::observed_guilds`].
+//!
+//! Only their level and member count are polled, nothing is written to the member database, and
+//! the changes are posted to [`TextChannelTag::Intel`] channels.
+//!
+//! [`Config::observed_guilds`]: config::Config::observed_guilds
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use serenity::CacheAndHttp;
+use tokio::sync::RwLock;
+use tokio::time::{self, Duration};
+use tracing::{info, warn};
+
+use config::tag::TextChannelTag;
+use config::Config;
+use util::ctx;
+use util::task::{RestartPolicy, Spawner};
+use wynn::api::WynnApi;
+use wynn::model::Guild;
+
+/// Interval between polls of the observed guilds
+const POLL_INTERVAL: Duration = Duration::from_secs(600);
+
+/// Public stats of an observed guild
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GuildSnapshot {
+    pub level: u8,
+    pub members: usize,
+}
+
+impl GuildSnapshot {
+    pub fn of(guild: &Guild) -> Self {
+        Self { level: guild.level, members: guild.members.len() }
+    }
+
+    /// Describe the changes since the `old` snapshot of guild `name`, `None` if nothing changed
+    ///
+    /// ```
+    /// use haxbotjr::observer::GuildSnapshot;
+    ///
+    /// let old = GuildSnapshot { level: 70, members: 80 };
+    /// assert_eq!(old.describe_changes("Avicia", &old), None);
+    ///
+    /// let new = GuildSnapshot { level: 71, members: 77 };
+    /// assert_eq!(
+    ///     new.describe_changes("Avicia", &old).unwrap(),
+    ///     "**Avicia**: level 70 → 71, members 80 → 77 (-3)"
+    /// );
+    /// ```
+    pub fn describe_changes(&self, name: &str, old: &Self) -> Option<String> {
+        let mut changes = Vec::new();
+        if self.level != old.level {
+            changes.push(format!("level {} → {}", old.level, self.level));
+        }
+        if self.members != old.members {
+            let diff = self.members as i64 - old.members as i64;
+            changes.push(format!("members {} → {} ({:+})", old.members, self.members, diff));
+        }
+
+        if changes.is_empty() {
+            None
+        } else {
+            Some(format!("**{}**: {}", name, changes.join(", ")))
+        }
+    }
+}
+
+/// Start the loop that polls the observed guilds and posts their changes
+pub async fn start_observer_loop(
+    spawner: &impl Spawner, api: impl WynnApi, cache_http: Arc<CacheAndHttp>, config: Arc<RwLock<Config>>,
+) {
+    let api = Arc::new(api);
+    spawner.spawn("guild observer", RestartPolicy::Always, move || {
+        let api = api.clone();
+        let cache_http = cache_http.clone();
+        let config = config.clone();
+        async move {
+            info!("Starting guild observer loop");
+            let mut snapshots = HashMap::new();
+            let mut interval = time::interval(POLL_INTERVAL);
+            loop {
+                interval.tick().await;
+                let changes = poll_guilds(api.as_ref(), &config, &mut snapshots).await;
+                if !changes.is_empty() {
+                    let msg = changes.join("\n");
+                    let _ = ctx!(config::send(&config, &cache_http, &TextChannelTag::Intel, &msg).await);
+                }
+            }
+        }
+    });
+}
+
+/// Poll the observed guilds and update their snapshots, returns the descriptions of the changes.
+///
+/// Guilds that were just added to the observed list are only snapshotted.
+async fn poll_guilds(
+    api: &impl WynnApi, config: &RwLock<Config>, snapshots: &mut HashMap<String, GuildSnapshot>,
+) -> Vec<String> {
+    let names = {
+        let config = config.read().await;
+        config.observed_guilds.clone()
+    };
+    snapshots.retain(|name, _| names.contains(name));
+
+    let mut changes = Vec::new();
+    for name in names {
+        let guild = match api.get_guild(&name).await {
+            Ok(guild) => guild,
+            Err(why) => {
+                warn!(name, "Failed to get observed guild stats: {:#}", why);
+                continue;
+            }
+        };
+        let snapshot = GuildSnapshot::of(&guild);
+        if let Some(old) = snapshots.insert(name.clone(), snapshot) {
+            if let Some(change) = snapshot.describe_changes(&name, &old) {
+                info!(name, ?old, new = ?snapshot, "Observed guild changed");
+                changes.push(change);
+            }
+        }
+    }
+    changes
+}
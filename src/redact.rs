@@ -0,0 +1,174 @@
+//! Redaction of member data in the log files, so they can be shared for debugging.
+//!
+//! When enabled with [`Config::redact_logs`], [`RedactFields`] replaces the log fields that
+//! identify a user (discord ids, igns, names) with a hash, and omits the fields that describe
+//! messages. Discord ids in the log messages themselves are hashed too.
+//! Hashes are consistent within a run of the bot, so the same user can still be followed across
+//! the logs, but they differ between runs.
+//!
+//! [`Config::redact_logs`]: config::Config::redact_logs
+use std::borrow::Cow;
+use std::collections::hash_map::RandomState;
+use std::fmt;
+use std::hash::BuildHasher;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::OnceLock;
+
+use tracing::field::{Field, Visit};
+use tracing_subscriber::field::{MakeVisitor, VisitFmt, VisitOutput};
+use tracing_subscriber::fmt::format::{DefaultVisitor, Writer};
+
+/// Names of the fields that identify a user, their values are hashed
+const PERSONAL_FIELDS: [&str; 20] = [
+    "id",
+    "mid",
+    "ign",
+    "old_ign",
+    "name",
+    "old_name",
+    "new_name",
+    "member.name",
+    "old.name",
+    "new.name",
+    "user",
+    "user_id",
+    "discord",
+    "discord_id",
+    "mcid",
+    "caller",
+    "applicant",
+    "closer",
+    "author",
+    "nick",
+];
+/// Names of the fields that describe messages, they are omitted
+const MESSAGE_FIELDS: [&str; 6] = ["content", "channel", "channel_id", "text_channel", "thread", "ctx"];
+/// Names of the fields of events, only the event's variant name is kept, ex: "MemberJoin"
+const EVENT_FIELDS: [&str; 1] = ["event"];
+/// Range of the amount of digits of a discord id
+const ID_DIGITS: std::ops::RangeInclusive<usize> = 17..=20;
+
+/// If the log files are redacted
+static ENABLED: AtomicBool = AtomicBool::new(false);
+/// Keys of the hashes, which are random for each run
+static HASH_KEYS: OnceLock<RandomState> = OnceLock::new();
+
+/// Set if the log files are redacted
+pub fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+/// Check if the log files are redacted
+pub fn enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Hash a value into a short string that doesn't reveal it
+/// ```
+/// use haxbotjr::redact::hash;
+///
+/// assert_eq!(hash("Pucaet"), hash("Pucaet"));
+/// assert_ne!(hash("Pucaet"), hash("SephDark18"));
+/// assert!(hash("Pucaet").starts_with('#'));
+/// ```
+pub fn hash(value: &str) -> String {
+    format!("#{:08x}", HASH_KEYS.get_or_init(RandomState::new).hash_one(value) as u32)
+}
+
+/// Hash the discord ids in a text, which are numbers of 17 to 20 digits
+/// ```
+/// use haxbotjr::redact::{hash, redact_ids};
+///
+/// let text = "Failed to fix nick of 658478931682394134 (member 24)";
+/// assert_eq!(redact_ids(text), format!("Failed to fix nick of {} (member 24)", hash("658478931682394134")));
+/// assert_eq!(redact_ids("Gained 1000000 xp"), "Gained 1000000 xp");
+/// ```
+pub fn redact_ids(text: &str) -> Cow<'_, str> {
+    let mut redacted = String::new();
+    let mut last = 0;
+    let mut digits_start = None;
+    for (i, c) in text.char_indices().chain(std::iter::once((text.len(), ' '))) {
+        match (c.is_ascii_digit(), digits_start) {
+            (true, None) => digits_start = Some(i),
+            (false, Some(start)) => {
+                if ID_DIGITS.contains(&(i - start)) {
+                    redacted.push_str(&text[last..start]);
+                    redacted.push_str(&hash(&text[start..i]));
+                    last = i;
+                }
+                digits_start = None;
+            }
+            _ => {}
+        }
+    }
+    if last == 0 {
+        return Cow::Borrowed(text);
+    }
+    redacted.push_str(&text[last..]);
+    Cow::Owned(redacted)
+}
+
+/// Formats the log fields like the default format, but with member data redacted if enabled
+#[derive(Debug, Default)]
+pub struct RedactFields;
+
+impl<'a> MakeVisitor<Writer<'a>> for RedactFields {
+    type Visitor = RedactVisitor<'a>;
+
+    fn make_visitor(&self, target: Writer<'a>) -> Self::Visitor {
+        RedactVisitor { inner: DefaultVisitor::new(target, true), enabled: enabled() }
+    }
+}
+
+/// Visitor of [`RedactFields`]
+#[derive(Debug)]
+pub struct RedactVisitor<'a> {
+    inner: DefaultVisitor<'a>,
+    enabled: bool,
+}
+
+impl<'a> Visit for RedactVisitor<'a> {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.record_debug(field, &format_args!("{}", value))
+        } else {
+            self.record_debug(field, &value)
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if !self.enabled {
+            return self.inner.record_debug(field, value);
+        }
+        let name = field.name();
+        if MESSAGE_FIELDS.contains(&name) {
+            return;
+        }
+        if name == "message" {
+            let message = format!("{:?}", value);
+            return self.inner.record_debug(field, &format_args!("{}", redact_ids(&message)));
+        }
+        if EVENT_FIELDS.contains(&name) {
+            let event = format!("{:?}", value);
+            let variant = event.split(|c: char| !c.is_alphanumeric() && c != '_').next().unwrap_or_default();
+            return self.inner.record_debug(field, &format_args!("{}", variant));
+        }
+        if PERSONAL_FIELDS.contains(&name) {
+            let value = format!("{:?}", value);
+            return self.inner.record_debug(field, &format_args!("{}", hash(value.trim_matches('"'))));
+        }
+        self.inner.record_debug(field, &format_args!("{}", redact_ids(&format!("{:?}", value))))
+    }
+}
+
+impl<'a> VisitOutput<fmt::Result> for RedactVisitor<'a> {
+    fn finish(self) -> fmt::Result {
+        self.inner.finish()
+    }
+}
+
+impl<'a> VisitFmt for RedactVisitor<'a> {
+    fn writer(&mut self) -> &mut dyn fmt::Write {
+        self.inner.writer()
+    }
+}
@@ -0,0 +1,132 @@
+//! Optional error reporting to Sentry.
+//!
+//! Reporting is compiled in with the `sentry` feature, and enabled at runtime by setting the
+//! `SENTRY_DSN` environment variable. Without either, everything here does nothing.
+//!
+//! Every error logged through tracing, which includes the errors returned by commands and the
+//! errors of the loops, is reported as an event, and warnings are kept as breadcrumbs of the next
+//! event. Each command is dispatched with its own breadcrumbs, starting with its invocation and
+//! correlation id, and the errors it returns are tagged with the command name and correlation id.
+//! Events are tagged with the release, which is `haxbotjr@<version>` unless `SENTRY_RELEASE` is
+//! set.
+//!
+//! When [`Config::redact_logs`] is enabled, discord ids in reported messages are hashed like in
+//! the log files, and the fields of the events are omitted.
+//!
+//! [`Config::redact_logs`]: config::Config::redact_logs
+use std::future::Future;
+
+use tracing::Subscriber;
+use tracing_subscriber::layer::Layer;
+use tracing_subscriber::registry::LookupSpan;
+
+/// Environment variable of the Sentry DSN
+pub const DSN_VAR: &str = "SENTRY_DSN";
+/// Environment variable that overrides the release events are tagged with
+pub const RELEASE_VAR: &str = "SENTRY_RELEASE";
+
+/// Keeps reporting enabled until it is dropped, which flushes the pending events
+#[must_use]
+pub struct Guard {
+    #[cfg(feature = "sentry")]
+    _guard: Option<sentry::ClientInitGuard>,
+}
+
+/// Enable reporting if `SENTRY_DSN` is set
+#[cfg(feature = "sentry")]
+pub fn init() -> Guard {
+    let dsn = match std::env::var(DSN_VAR) {
+        Ok(dsn) if !dsn.is_empty() => dsn,
+        _ => return Guard { _guard: None },
+    };
+    let release = std::env::var(RELEASE_VAR).ok().map(Into::into).or_else(|| sentry::release_name!());
+    let guard = sentry::init((
+        dsn,
+        sentry::ClientOptions {
+            release,
+            attach_stacktrace: true,
+            before_send: Some(std::sync::Arc::new(|mut event| {
+                if crate::redact::enabled() {
+                    event.message =
+                        event.message.map(|message| crate::redact::redact_ids(&message).into_owned());
+                    event.extra.clear();
+                }
+                Some(event)
+            })),
+            before_breadcrumb: Some(std::sync::Arc::new(|mut breadcrumb| {
+                if crate::redact::enabled() {
+                    breadcrumb.message =
+                        breadcrumb.message.map(|message| crate::redact::redact_ids(&message).into_owned());
+                    breadcrumb.data.clear();
+                }
+                Some(breadcrumb)
+            })),
+            ..Default::default()
+        },
+    ));
+    tracing::info!(release = ?guard.options().release, "Enabled error reporting");
+    Guard { _guard: Some(guard) }
+}
+
+/// Enable reporting if `SENTRY_DSN` is set
+#[cfg(not(feature = "sentry"))]
+pub fn init() -> Guard {
+    if std::env::var(DSN_VAR).is_ok() {
+        tracing::warn!("{} is set, but the bot is built without the sentry feature", DSN_VAR);
+    }
+    Guard {}
+}
+
+/// Tracing layer that reports errors as events, and warnings as breadcrumbs
+#[cfg(feature = "sentry")]
+pub fn layer<S>() -> impl Layer<S>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    use sentry::integrations::tracing::EventFilter;
+    use tracing::Level;
+
+    sentry::integrations::tracing::layer().event_filter(|metadata| match *metadata.level() {
+        Level::ERROR => EventFilter::Event,
+        Level::WARN => EventFilter::Breadcrumb,
+        _ => EventFilter::Ignore,
+    })
+}
+
+/// Tracing layer that reports errors as events, and warnings as breadcrumbs
+#[cfg(not(feature = "sentry"))]
+pub fn layer<S>() -> impl Layer<S>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    tracing_subscriber::layer::Identity::new()
+}
+
+/// Run a future with its own breadcrumbs, so concurrent commands don't mix theirs
+#[cfg(feature = "sentry")]
+pub fn isolate<F: Future>(future: F) -> impl Future<Output = F::Output> {
+    use sentry::{Hub, SentryFutureExt};
+
+    future.bind_hub(Hub::new_from_top(Hub::current()))
+}
+
+/// Run a future with its own breadcrumbs, so concurrent commands don't mix theirs
+#[cfg(not(feature = "sentry"))]
+pub fn isolate<F: Future>(future: F) -> impl Future<Output = F::Output> {
+    future
+}
+
+/// Leave a breadcrumb of a command invocation
+#[cfg(feature = "sentry")]
+pub fn command_breadcrumb(command_name: &str, cid: &str) {
+    sentry::add_breadcrumb(sentry::Breadcrumb {
+        category: Some("command".to_string()),
+        message: Some(format!("Invoking command '{}'", command_name)),
+        data: std::iter::once(("cid".to_string(), cid.into())).collect(),
+        ..Default::default()
+    });
+}
+
+/// Leave a breadcrumb of a command invocation
+#[cfg(not(feature = "sentry"))]
+pub fn command_breadcrumb(_command_name: &str, _cid: &str) {}
@@ -0,0 +1,180 @@
+//! Secrets the bot authenticates with, and rotating them at runtime.
+//!
+//! Secrets are read from the file at `SECRETS_FILE` if it is set, which is in the same `KEY=value`
+//! format as ".env", otherwise from the environment. Values in the file take precedence over the
+//! environment.
+//!
+//! The file can be reloaded with the `secrets reload` owner command, which validates the new
+//! secrets before using them. A rotated discord token can't be swapped into the running client, so
+//! the bot restarts itself in place to reconnect with it, see [`request_restart`].
+use std::collections::HashMap;
+use std::env;
+use std::fmt;
+use std::fs;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use anyhow::{bail, Context, Result};
+use reqwest::Client;
+use serenity::http::Http;
+use serenity::model::user::CurrentUser;
+use serenity::prelude::TypeMapKey;
+use tokio::sync::RwLock;
+use tracing::{error, info};
+
+use wynn::api::{HttpApi, WynnApi};
+
+/// Environment variable of the secrets file path
+pub const SECRETS_FILE_VAR: &str = "SECRETS_FILE";
+/// Mcid used to check the Mojang API, which is of the account "Notch"
+const PROBE_MCID: &str = "069a79f444e94726a5befca90e38aaf5";
+
+/// Set when the bot should restart after its client shuts down
+static RESTART: AtomicBool = AtomicBool::new(false);
+
+/// Secrets the bot authenticates with
+#[derive(Clone, PartialEq, Eq)]
+pub struct Secrets {
+    /// Token of the discord bot
+    pub discord_token: String,
+    /// OAuth2 client secret of the discord application, used by linked roles
+    pub client_secret: Option<String>,
+    /// Path of the file the secrets are read from, `None` if they are read from the environment
+    pub file: Option<String>,
+}
+
+impl Secrets {
+    /// Read the secrets from the file at `SECRETS_FILE`, or from the environment
+    pub fn load() -> Result<Self> {
+        let file = env::var(SECRETS_FILE_VAR).ok();
+        let values = match &file {
+            Some(file) => parse_env(
+                &fs::read_to_string(file)
+                    .with_context(|| format!("Failed to read secrets file '{}'", file))?,
+            ),
+            None => HashMap::new(),
+        };
+        let get = |key: &str| values.get(key).cloned().or_else(|| env::var(key).ok());
+
+        let discord_token = match get("DISCORD_TOKEN") {
+            Some(token) => token,
+            None => bail!("Expected DISCORD_TOKEN in the secrets file or the environment"),
+        };
+        Ok(Self { discord_token, client_secret: get("DISCORD_CLIENT_SECRET"), file })
+    }
+
+    /// Get where the secrets are read from
+    pub fn source(&self) -> String {
+        match &self.file {
+            Some(file) => format!("file `{}`", file),
+            None => "environment".to_string(),
+        }
+    }
+}
+
+impl fmt::Debug for Secrets {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Secrets")
+            .field("discord_token", &mask(&self.discord_token))
+            .field("client_secret", &self.client_secret.as_deref().map(mask))
+            .field("file", &self.file)
+            .finish()
+    }
+}
+
+/// Bot data key for [`Secrets`]
+impl TypeMapKey for Secrets {
+    type Value = Arc<RwLock<Secrets>>;
+}
+
+/// Parse the `KEY=value` lines of an env file, comments and invalid lines are ignored
+/// ```
+/// use haxbotjr::secrets::parse_env;
+///
+/// let values = parse_env("# Bot\nDISCORD_TOKEN=abc\nexport SECRET = \"d=e\"\ninvalid");
+/// assert_eq!(values.len(), 2);
+/// assert_eq!(values["DISCORD_TOKEN"], "abc");
+/// assert_eq!(values["SECRET"], "d=e");
+/// ```
+pub fn parse_env(content: &str) -> HashMap<String, String> {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.starts_with('#'))
+        .filter_map(|line| {
+            let line = line.strip_prefix("export ").unwrap_or(line);
+            let (key, value) = line.split_once('=')?;
+            let value = value.trim();
+            let value = ['"', '\'']
+                .iter()
+                .find_map(|quote| value.strip_prefix(*quote)?.strip_suffix(*quote))
+                .unwrap_or(value);
+            Some((key.trim().to_string(), value.to_string()))
+        })
+        .collect()
+}
+
+/// Hide a secret except its last 4 characters
+/// ```
+/// use haxbotjr::secrets::mask;
+///
+/// assert_eq!(mask("abcdefgh"), "****efgh");
+/// assert_eq!(mask("abc"), "****");
+/// ```
+pub fn mask(secret: &str) -> String {
+    let chars: Vec<char> = secret.chars().collect();
+    if chars.len() <= 4 {
+        return "****".to_string();
+    }
+    let tail: String = chars[chars.len() - 4..].iter().collect();
+    format!("****{}", tail)
+}
+
+/// Check if a discord token is valid, and get the bot user it belongs to
+pub async fn validate_token(token: &str) -> Result<CurrentUser> {
+    Http::new(token).get_current_user().await.context("Discord rejected the token")
+}
+
+/// Check if the Wynncraft and Mojang APIs can be requested, returns the result of each API
+pub async fn check_apis(client: &Client) -> Vec<(&'static str, Result<()>)> {
+    let api = HttpApi(client.clone());
+    vec![
+        ("Wynncraft", api.get_online_players().await.map(|_| ())),
+        ("Mojang", api.get_player(PROBE_MCID).await.map(|_| ())),
+    ]
+}
+
+/// Make the bot restart in place once its client shuts down, so it reconnects with the secrets
+/// reloaded from the file
+pub fn request_restart() {
+    RESTART.store(true, Ordering::SeqCst);
+}
+
+/// Restart the bot if [`request_restart`] is called, by replacing the process with a new one of
+/// the same executable and arguments
+pub fn restart_if_requested() {
+    if !RESTART.load(Ordering::SeqCst) {
+        return;
+    }
+    info!("Restarting to reconnect with the reloaded secrets");
+    let result = env::current_exe().context("Failed to get current executable").and_then(|exe| {
+        let mut command = std::process::Command::new(exe);
+        command.args(env::args_os().skip(1));
+        exec(command)
+    });
+    if let Err(why) = result {
+        error!("Failed to restart: {:#}", why);
+    }
+}
+
+#[cfg(unix)]
+fn exec(mut command: std::process::Command) -> Result<()> {
+    use std::os::unix::process::CommandExt;
+    // `exec` only returns on failure
+    Err(command.exec()).context("Failed to replace process")
+}
+
+#[cfg(not(unix))]
+fn exec(_: std::process::Command) -> Result<()> {
+    bail!("Restarting in place is only supported on unix")
+}
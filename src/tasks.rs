@@ -0,0 +1,190 @@
+//! Supervision of the bot's long running tasks.
+//!
+//! Every loop is spawned through the [`TaskRegistry`], which restarts it with backoff according
+//! to its [`RestartPolicy`] when it panics or exits, and keeps track of its status so it can be
+//! displayed by the `status` command.
+//!
+//! A panicking task only takes down itself, as every task runs in its own tokio task. The panic
+//! is caught through its `JoinHandle`, and logged along with where it happened by [`log_panics`].
+use std::any::Any;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::future::Future;
+use std::panic;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use serenity::prelude::TypeMapKey;
+use tracing::{error, info, warn};
+
+use util::task::{RestartPolicy, Spawner};
+
+/// Delay before the first restart of a task, it is doubled on each consecutive restart
+const RESTART_DELAY: Duration = Duration::from_secs(1);
+/// Max delay before restarting a task
+const MAX_RESTART_DELAY: Duration = Duration::from_secs(300);
+/// If a task ran for at least this long before stopping, its restart delay is reset
+const STABLE_AFTER: Duration = Duration::from_secs(600);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// State of a supervised task
+pub enum TaskState {
+    Running,
+    /// Stopped and waiting to be restarted
+    Restarting,
+    /// Returned and won't be restarted
+    Exited,
+    /// Panicked and won't be restarted
+    Panicked,
+}
+
+impl fmt::Display for TaskState {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            Self::Running => "running",
+            Self::Restarting => "restarting",
+            Self::Exited => "exited",
+            Self::Panicked => "panicked",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+#[derive(Debug, Clone)]
+/// Status of a supervised task
+pub struct TaskStatus {
+    pub state: TaskState,
+    pub policy: RestartPolicy,
+    /// Amount of times the task has been restarted
+    pub restarts: u32,
+    /// Amount of times the task has panicked
+    pub panics: u32,
+    /// Why the task last stopped
+    pub last_error: Option<String>,
+}
+
+#[derive(Debug, Clone, Default)]
+/// Registry of supervised tasks
+pub struct TaskRegistry {
+    tasks: Arc<Mutex<BTreeMap<String, TaskStatus>>>,
+}
+
+impl TaskRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the name and status of all tasks, sorted by name
+    pub fn statuses(&self) -> Vec<(String, TaskStatus)> {
+        let tasks = self.tasks.lock().expect("Task registry is poisoned");
+        tasks.iter().map(|(name, status)| (name.clone(), status.clone())).collect()
+    }
+
+    fn update(&self, name: &str, f: impl FnOnce(&mut TaskStatus)) {
+        let mut tasks = self.tasks.lock().expect("Task registry is poisoned");
+        if let Some(status) = tasks.get_mut(name) {
+            f(status);
+        }
+    }
+}
+
+impl Spawner for TaskRegistry {
+    fn spawn<F, Fut>(&self, name: &str, policy: RestartPolicy, factory: F)
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let status =
+            TaskStatus { state: TaskState::Running, policy, restarts: 0, panics: 0, last_error: None };
+        {
+            let mut tasks = self.tasks.lock().expect("Task registry is poisoned");
+            if tasks.insert(name.to_string(), status).is_some() {
+                warn!(task = name, "Task is registered more than once");
+            }
+        }
+
+        let registry = self.clone();
+        let name = name.to_string();
+        tokio::spawn(async move {
+            let mut delay = RESTART_DELAY;
+            loop {
+                let started = Instant::now();
+                registry.update(&name, |status| status.state = TaskState::Running);
+
+                let result = tokio::spawn(factory()).await;
+                let uptime =
+                    util::string::fmt_second(started.elapsed().as_secs().try_into().unwrap_or(i64::MAX));
+                let (panicked, why) = match result {
+                    Ok(()) => {
+                        warn!(task = name, uptime, "Task exited");
+                        (false, "exited".to_string())
+                    }
+                    Err(why) if why.is_panic() => {
+                        let why = panic_message(why.into_panic().as_ref());
+                        error!(task = name, uptime, "Task panicked: {}", why);
+                        registry.update(&name, |status| status.panics += 1);
+                        (true, why)
+                    }
+                    Err(why) => {
+                        warn!(task = name, uptime, "Task was cancelled: {}", why);
+                        (false, why.to_string())
+                    }
+                };
+
+                let restart = match policy {
+                    RestartPolicy::Always => true,
+                    RestartPolicy::OnPanic => panicked,
+                    RestartPolicy::Never => false,
+                };
+                if !restart {
+                    registry.update(&name, |status| {
+                        status.state = if panicked { TaskState::Panicked } else { TaskState::Exited };
+                        status.last_error = Some(why);
+                    });
+                    return;
+                }
+
+                if started.elapsed() >= STABLE_AFTER {
+                    delay = RESTART_DELAY;
+                }
+                registry.update(&name, |status| {
+                    status.state = TaskState::Restarting;
+                    status.restarts += 1;
+                    status.last_error = Some(why);
+                });
+                info!(task = name, "Restarting task in {}s", delay.as_secs());
+                tokio::time::sleep(delay).await;
+                delay = (delay * 2).min(MAX_RESTART_DELAY);
+            }
+        });
+    }
+}
+
+/// Bot data key for [`TaskRegistry`]
+impl TypeMapKey for TaskRegistry {
+    type Value = TaskRegistry;
+}
+
+/// Log panics with tracing, so they end up in the log files along with where they happened.
+///
+/// The default panic hook only prints to stderr.
+pub fn log_panics() {
+    panic::set_hook(Box::new(|info| {
+        let location = info.location().map(|l| format!("{}:{}:{}", l.file(), l.line(), l.column()));
+        let thread = std::thread::current();
+        let thread = thread.name().unwrap_or("<unnamed>");
+        let why = panic_message(info.payload());
+        error!(thread, location = location.as_deref().unwrap_or("unknown"), "Panicked: {}", why);
+    }));
+}
+
+/// Get the message of a panic payload
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        s.to_string()
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s.clone()
+    } else {
+        "unknown panic".to_string()
+    }
+}
@@ -118,7 +118,7 @@ pub async fn arg_check(ctx: &Context, msg: &Message, args: &mut Args) -> Termina
 }
 
 /// Same as `arg_check` buts accepts `Vec<String>` instead of `Args`
-async fn arg_check_list(ctx: &Context, msg: &Message, arg_list: Vec<String>) -> Terminator<()> {
+pub async fn arg_check_list(ctx: &Context, msg: &Message, arg_list: Vec<String>) -> Terminator<()> {
     match arg_list.len() {
         0 => Terminator::Proceed(()),
         1 => {
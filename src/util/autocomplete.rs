@@ -0,0 +1,156 @@
+//! Autocompletion of slash command arguments.
+//!
+//! Member igns are suggested from [`IgnIndex`], an in-memory prefix index over the igns of
+//! members, which is rebuilt lazily after database events that change them.
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use anyhow::Result;
+use serenity::client::Context;
+use serenity::model::application::interaction::autocomplete::AutocompleteInteraction;
+use serenity::prelude::TypeMapKey;
+use tokio::sync::RwLock;
+use tracing::info;
+
+use memberdb::events::DBEvent;
+use memberdb::model::wynn::McId;
+use memberdb::DB;
+use util::task::{RestartPolicy, Spawner};
+use util::{ctx, ok};
+use wynn::events::{WynnEvent, WynnSignal};
+
+/// Max amount of suggestions, which is the limit discord allows
+const MAX_SUGGESTIONS: usize = 25;
+
+#[derive(Debug, Default)]
+/// Prefix index over the igns of members
+pub struct IgnIndex {
+    /// Igns keyed by their lowercase form, `None` if the index needs to be rebuilt
+    igns: Option<BTreeMap<String, String>>,
+}
+
+impl IgnIndex {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Mark the index as outdated, so it is rebuilt on the next lookup
+    pub fn invalidate(&mut self) {
+        self.igns = None;
+    }
+
+    /// Get up to `limit` igns that start with `prefix`, case insensitive
+    pub async fn suggest(&mut self, db: &RwLock<DB>, prefix: &str, limit: usize) -> Result<Vec<String>> {
+        let igns = match &mut self.igns {
+            Some(igns) => igns,
+            igns => {
+                let db = db.read().await;
+                let list = ctx!(McId::igns(&mut db.exe()).await)?;
+                info!(count = list.len(), "Rebuilt ign index");
+                igns.insert(list.into_iter().map(|ign| (ign.to_lowercase(), ign)).collect())
+            }
+        };
+        let prefix = prefix.to_lowercase();
+        Ok(igns
+            .range(prefix.clone()..)
+            .take_while(|(key, _)| key.starts_with(&prefix))
+            .take(limit)
+            .map(|(_, ign)| ign.clone())
+            .collect())
+    }
+}
+
+/// Bot data key for [`IgnIndex`]
+impl TypeMapKey for IgnIndex {
+    type Value = Arc<RwLock<IgnIndex>>;
+}
+
+/// Start the loops that invalidate the [`IgnIndex`] when member igns change
+pub async fn start_invalidation_loop(
+    spawner: &impl Spawner, index: Arc<RwLock<IgnIndex>>, db: Arc<RwLock<DB>>, signal: WynnSignal,
+) {
+    let shared_index = Arc::clone(&index);
+    spawner.spawn("ign index invalidation (db event)", RestartPolicy::Always, move || {
+        let shared_index = shared_index.clone();
+        let db = db.clone();
+        async move {
+            info!("Starting ign index invalidation loop (db event)");
+            let mut recv = {
+                let db = db.read().await;
+                db.connect()
+            };
+            loop {
+                let event =
+                    ok!(ctx!(recv.recv().await, "Failed to receive db event in ign index loop"), continue);
+                match event.as_ref() {
+                    DBEvent::MemberAdd { .. }
+                    | DBEvent::MemberRemove { .. }
+                    | DBEvent::WynnProfileBind { .. }
+                    | DBEvent::WynnProfileUnbind { .. } => shared_index.write().await.invalidate(),
+                    _ => {}
+                }
+            }
+        }
+    });
+
+    spawner.spawn("ign index invalidation (wynn event)", RestartPolicy::Always, move || {
+        let signal = signal.clone();
+        let index = index.clone();
+        async move {
+            info!("Starting ign index invalidation loop (wynn event)");
+            let mut recv = signal.connect();
+            loop {
+                let events =
+                    ok!(ctx!(recv.recv().await, "Failed to receive wynn event in ign index loop"), continue);
+                if events.iter().any(|event| matches!(event, WynnEvent::MemberNameChange { .. })) {
+                    index.write().await.invalidate();
+                }
+            }
+        }
+    });
+}
+
+/// Respond to an autocomplete interaction.
+///
+/// The `ign` argument is completed with igns, and the `target` argument is completed with member
+/// targets (ex: "m:Pucaet"), of the `profile` and `setrank` commands.
+pub async fn respond(ctx: &Context, interaction: &AutocompleteInteraction) -> Result<()> {
+    if !matches!(interaction.data.name.as_str(), "profile" | "setrank") {
+        return Ok(());
+    }
+    let option = match interaction.data.options.iter().find(|option| option.focused) {
+        Some(option) => option,
+        None => return Ok(()),
+    };
+    let input = option.value.as_ref().and_then(|value| value.as_str()).unwrap_or("");
+    let (prefix, input) = match option.name.as_str() {
+        "ign" => ("", input),
+        "target" => ("m:", input.strip_prefix("m:").unwrap_or(input)),
+        _ => return Ok(()),
+    };
+
+    let (db, index) = {
+        let data = ctx.data.read().await;
+        let db = Arc::clone(data.get::<DB>().expect("Failed to get db"));
+        let index = Arc::clone(data.get::<IgnIndex>().expect("Failed to get ign index"));
+        (db, index)
+    };
+    let igns = {
+        let mut index = index.write().await;
+        index.suggest(&db, input, MAX_SUGGESTIONS).await?
+    };
+
+    ctx!(
+        interaction
+            .create_autocomplete_response(&ctx.http, |r| {
+                for ign in igns {
+                    let value = format!("{}{}", prefix, ign);
+                    r.add_string_choice(&value, &value);
+                }
+                r
+            })
+            .await,
+        "Failed to respond to autocomplete"
+    )?;
+    Ok(())
+}
@@ -0,0 +1,188 @@
+//! Fixing the nicknames or roles of all members at once.
+//!
+//! As it takes a while to go through every member, the fix reports its progress by editing a
+//! status message, and saves its progress to [`PROGRESS_FILE`] so it can be resumed if it gets
+//! interrupted by rate limits or a restart.
+use std::fmt;
+use std::str::FromStr;
+
+use anyhow::Result;
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use serenity::client::Context;
+use serenity::model::channel::Message;
+use serenity::model::guild::{Guild, Member};
+use tokio::sync::RwLock;
+use tracing::{error, info, warn};
+
+use config::Config;
+use memberdb::model::member::MemberId;
+use memberdb::DB;
+use util::{ctx, ioerr, read_json, write_json};
+
+use super::sync_state::DiscordSyncState;
+
+/// File the progress of an unfinished bulk fix is saved to
+pub const PROGRESS_FILE: &str = "cache/bulk_fix.json";
+/// The status message is updated every time this amount of members are processed
+const STATUS_INTERVAL: usize = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+/// What a bulk fix corrects
+pub enum FixKind {
+    Nick,
+    Role,
+}
+
+impl FromStr for FixKind {
+    type Err = std::io::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "nick" => Self::Nick,
+            "role" => Self::Role,
+            _ => return ioerr!("Failed to parse '{}' as FixKind", s),
+        })
+    }
+}
+
+impl fmt::Display for FixKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Nick => write!(f, "nick"),
+            Self::Role => write!(f, "role"),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+/// Progress of a bulk fix
+pub struct FixProgress {
+    pub kind: FixKind,
+    /// Id of the last processed member, members are processed in the order of their ids
+    pub last: Option<i64>,
+    /// Amount of members whose nickname / roles are corrected
+    pub changed: usize,
+    /// Amount of members that are already correct, or can't be updated
+    pub skipped: usize,
+    /// Amount of members that failed to update
+    pub failed: usize,
+}
+
+impl FixProgress {
+    pub fn new(kind: FixKind) -> Self {
+        Self { kind, last: None, changed: 0, skipped: 0, failed: 0 }
+    }
+
+    /// Load the progress of an unfinished bulk fix
+    pub fn load() -> Option<Self> {
+        read_json!(PROGRESS_FILE, None).flatten()
+    }
+
+    /// Save the progress so it can be resumed later
+    pub fn save(&self) {
+        write_json!(PROGRESS_FILE, self, "bulk fix progress");
+    }
+
+    /// Remove the saved progress
+    pub fn clear() {
+        if let Err(why) = std::fs::remove_file(PROGRESS_FILE) {
+            if why.kind() != std::io::ErrorKind::NotFound {
+                error!("Failed to remove {}: {:#}", PROGRESS_FILE, why);
+            }
+        }
+    }
+
+    /// Format the progress into a status message
+    pub fn status(&self, done: usize, total: usize) -> String {
+        format!(
+            "Fixing {} of all members: {}/{}\nChanged {}, skipped {}, failed {}",
+            self.kind, done, total, self.changed, self.skipped, self.failed
+        )
+    }
+}
+
+/// Fix all members that haven't been processed according to `progress`.
+///
+/// `status` is edited periodically to show the progress.
+/// Returns `false` if the fix is interrupted by rate limits, in which case its progress is saved.
+pub async fn fix_all(
+    ctx: &Context, db: &RwLock<DB>, config: &RwLock<Config>, guild: &Guild, progress: &mut FixProgress,
+    status: &mut Message,
+) -> Result<bool> {
+    let members = {
+        let db = db.read().await;
+        ctx!(MemberId::with_discord(&mut db.exe()).await)?
+    };
+    let members: Vec<_> = match progress.last {
+        Some(last) => members.into_iter().filter(|(mid, _)| mid.0 > last).collect(),
+        None => members,
+    };
+    let total = members.len();
+    info!(kind = %progress.kind, total, "Starting bulk fix");
+
+    for (i, (mid, discord_id)) in members.into_iter().enumerate() {
+        let member = match u64::try_from(discord_id.0) {
+            Ok(id) => guild.member(ctx, id).await.ok(),
+            Err(_) => None,
+        };
+        match member {
+            Some(mut member) => match fix_member(ctx, db, config, guild, progress.kind, mid, &mut member).await {
+                Ok(true) => progress.changed += 1,
+                Ok(false) => progress.skipped += 1,
+                Err(why) => {
+                    if is_rate_limited(&why) {
+                        warn!(?mid, "Bulk fix interrupted by rate limit");
+                        progress.save();
+                        return Ok(false);
+                    }
+                    error!(?mid, "Failed to fix member: {:#}", why);
+                    progress.failed += 1;
+                }
+            },
+            // Not in the discord server
+            None => progress.skipped += 1,
+        }
+        progress.last = Some(mid.0);
+
+        if (i + 1) % STATUS_INTERVAL == 0 {
+            progress.save();
+            let content = progress.status(i + 1, total);
+            let _ = ctx!(status.edit(ctx, |m| m.content(content)).await, "Failed to edit bulk fix status");
+        }
+    }
+
+    FixProgress::clear();
+    Ok(true)
+}
+
+/// Fix a member, returns `false` if the member is skipped
+async fn fix_member(
+    ctx: &Context, db: &RwLock<DB>, config: &RwLock<Config>, guild: &Guild, kind: FixKind, mid: MemberId,
+    member: &mut Member,
+) -> Result<bool> {
+    let diff = DiscordSyncState::of_member(db, mid, guild, member, None).await?.diff(guild, member);
+    match kind {
+        FixKind::Nick => {
+            if diff.nick.is_none() || !config.read().await.should_update_nick(member) {
+                return Ok(false);
+            }
+            diff.apply_nick(&ctx.http, member).await?;
+        }
+        FixKind::Role => {
+            if !diff.changes_roles() || !config.read().await.should_update_role(member) {
+                return Ok(false);
+            }
+            diff.apply_roles(&ctx.http, member).await?;
+        }
+    }
+    Ok(true)
+}
+
+/// Checks if an error is caused by discord rate limiting
+fn is_rate_limited(why: &anyhow::Error) -> bool {
+    why.chain().any(|e| match e.downcast_ref::<serenity::Error>() {
+        Some(serenity::Error::Http(e)) => e.status_code() == Some(StatusCode::TOO_MANY_REQUESTS),
+        _ => false,
+    })
+}
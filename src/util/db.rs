@@ -12,7 +12,10 @@ use memberdb::model::discord::DiscordId;
 use memberdb::model::member::MemberId;
 use memberdb::model::wynn::McId;
 use memberdb::DB;
-use msgtool::parser::{DiscordObject, TargetObject};
+use msgtool::interact;
+use msgtool::parser::{
+    extract_id_from_ping, parse_ign, parse_old_ign, DiscordObject, DiscordObjectType, TargetObject,
+};
 use util::{ctx, ok, ok_some, some};
 
 use crate::util::Terminator::{self, *};
@@ -45,10 +48,20 @@ pub async fn get_profile_ids<'a>(
     Proceed((discord_member, discord_id, McId(mcid)))
 }
 
-/// Parse a target expression into `TargetId`
+/// Parse a target expression into `TargetId`.
+///
+/// If `guild` is `None` (ex: the command is called in DMs), only mc targets and user pings are
+/// accepted, as the other targets are searched for in the guild.
 pub async fn parse_user_target(
-    ctx: &Context, msg: &Message, db: &RwLock<DB>, client: &Client, guild: &Guild, s: &str,
+    ctx: &Context, msg: &Message, db: &RwLock<DB>, client: &Client, guild: Option<&Guild>, s: &str,
 ) -> Terminator<TargetId> {
+    if let Some(mcid) = t!(?choose_old_ign_owner(ctx, msg, db, client, s).await) {
+        return Proceed(TargetId::Wynn(mcid));
+    }
+    let guild = match guild {
+        Some(guild) => guild,
+        None => return parse_guildless_user_target(ctx, msg, db, client, s).await,
+    };
     let target = match TargetObject::from_str(ctx, db, client, guild, s).await {
         Ok(v) => v,
         Err(why) => tfinish!(ctx, msg, format!("invalid target: {}", why)),
@@ -62,9 +75,52 @@ pub async fn parse_user_target(
     })
 }
 
+/// Parse a target expression into `TargetId` without a guild
+async fn parse_guildless_user_target(
+    ctx: &Context, msg: &Message, db: &RwLock<DB>, client: &Client, s: &str,
+) -> Terminator<TargetId> {
+    if let Some(ign) = s.strip_prefix("m:") {
+        return match parse_ign(db, client, ign).await {
+            Ok(id) => Proceed(TargetId::Wynn(id)),
+            Err(why) => tfinish!(ctx, msg, format!("invalid target: {}", why)),
+        };
+    }
+    if let Some((DiscordObjectType::Member, id)) = extract_id_from_ping(s) {
+        return Proceed(TargetId::Discord(UserId(id)));
+    }
+    tfinish!(ctx, msg, "Only mc users (m:<ign>) and user pings can be used as target outside of a server")
+}
+
+/// If `s` is a mc target of an ign that is not currently used by anyone, but was previously used by
+/// multiple players in the database, ask the user to choose one of them.
+///
+/// Returns `None` if there is no need for choosing, in which case the target is parsed normally.
+async fn choose_old_ign_owner(
+    ctx: &Context, msg: &Message, db: &RwLock<DB>, client: &Client, s: &str,
+) -> Terminator<Option<McId>> {
+    let ign = some!(s.strip_prefix("m:"), return Proceed(None));
+    let mut owners = ok!(parse_old_ign(db, client, ign).await, return Proceed(None));
+    if owners.len() < 2 {
+        return Proceed(None);
+    }
+    owners.truncate(25);
+
+    let mut igns = Vec::with_capacity(owners.len());
+    {
+        let db = db.read().await;
+        for mcid in &owners {
+            igns.push(ttry!(mcid.ign(&mut db.exe()).await));
+        }
+    }
+    let content = format!("Multiple players previously used the ign `{}`, which one do you mean?", ign);
+    let choice = ttry!(interact::choose(ctx, &msg.channel_id, &content, &igns, 30, msg.author.id).await);
+    let i = some!(choice, return Terminate);
+    Proceed(Some(some!(owners.into_iter().nth(i), return Terminate)))
+}
+
 /// Parse a target expression into member id
 pub async fn parse_user_target_mid(
-    ctx: &Context, msg: &Message, db: &RwLock<DB>, client: &Client, guild: &Guild, s: &str,
+    ctx: &Context, msg: &Message, db: &RwLock<DB>, client: &Client, guild: Option<&Guild>, s: &str,
 ) -> Terminator<MemberId> {
     let target = t!(?parse_user_target(ctx, msg, db, client, guild, s).await);
     Proceed(some!(
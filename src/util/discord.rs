@@ -1,37 +1,18 @@
 //! Discord related utilties
-use anyhow::Result;
+use anyhow::{Context as AHContext, Result};
+use serenity::client::Context;
 use serenity::http::Http;
-use serenity::model::guild::{Guild, Member};
-use tokio::sync::RwLock;
-
-use memberdb::model::member::{MemberId, MemberRank};
-use memberdb::DB;
+use serenity::model::application::interaction::application_command::ApplicationCommandInteraction;
+use serenity::model::application::interaction::message_component::MessageComponentInteraction;
+use serenity::model::application::interaction::{InteractionResponseType, MessageFlags};
+use serenity::model::channel::ReactionType;
+use serenity::model::guild::Member;
+use serenity::model::id::{ChannelId, GuildId, MessageId};
+use serenity::model::user::User;
+
+use memberdb::model::member::MemberRank;
 use msgtool::pager::ToPage;
-use util::ctx;
-use util::discord;
-
-/// Update discord member's role to match up with their rank
-pub async fn fix_discord_roles(
-    http: &Http, rank: MemberRank, guild: &Guild, member: &mut Member,
-) -> Result<()> {
-    if memberdb::model::member::MANAGED_MEMBER_RANKS.contains(&rank) {
-        discord::add_role_maybe(http, rank.get_role(guild), member).await?;
-        discord::add_role_maybe(http, rank.get_group_role(guild), member).await?;
-    }
-
-    for other_rank in memberdb::model::member::MANAGED_MEMBER_RANKS {
-        if rank == other_rank {
-            continue;
-        }
-
-        discord::remove_role_maybe(http, other_rank.get_role(guild), member).await?;
-        if !rank.is_same_group(other_rank) {
-            discord::remove_role_maybe(http, other_rank.get_group_role(guild), member).await?;
-        }
-    }
-
-    Ok(())
-}
+use util::some;
 
 /// Given discord nick, return custom nick within it
 pub fn extract_custom_nick(nick: &str) -> &str {
@@ -44,52 +25,64 @@ pub fn extract_custom_nick(nick: &str) -> &str {
     }
 }
 
-/// Fix a member's discord nick.
-/// If `custom_nick` is none, attempts to preserve their original custom nick.
-pub async fn fix_member_nick(
-    http: &Http, db: &RwLock<DB>, mid: MemberId, discord_member: &Member, custom_nick: Option<&str>,
-) -> Result<Member> {
-    let rank = {
-        let db = db.read().await;
-        ctx!(mid.rank(&mut db.exe()).await)?
-    };
-    let ign = {
-        let db = db.read().await;
-        let (_, mcid) = ctx!(mid.links(&mut db.exe()).await)?;
-        match mcid {
-            Some(mcid) => mcid.ign(&mut db.exe()).await.ok(),
-            None => None,
-        }
-    };
-    // convert Option<String> to Option<&String>
-    let ign = match &ign {
-        Some(ign) => Some(ign),
-        None => None,
-    };
-
-    fix_discord_nick(http, &rank, ign, discord_member, custom_nick).await
+/// Check if the user that pressed a button is a staff
+pub fn is_staff_interaction(ctx: &Context, interaction: &MessageComponentInteraction) -> bool {
+    is_staff_member(ctx, interaction.guild_id, interaction.member.as_ref())
 }
 
-/// Fix a discord member's nick.
-/// If `custom_nick` is none, attempts to preserve their original custom nick.
-pub async fn fix_discord_nick(
-    http: &Http, rank: &MemberRank, ign: Option<&String>, discord_member: &Member, custom_nick: Option<&str>,
-) -> Result<Member> {
-    let name = match ign {
-        Some(ign) => ign,
-        None => &discord_member.user.name,
-    };
-    let custom_nick = match custom_nick {
-        Some(s) => s,
-        None => match &discord_member.nick {
-            Some(nick) => extract_custom_nick(nick),
-            None => "",
-        },
-    };
+/// Check if the user that called a slash command is a staff
+pub fn is_staff_command(ctx: &Context, interaction: &ApplicationCommandInteraction) -> bool {
+    is_staff_member(ctx, interaction.guild_id, interaction.member.as_ref())
+}
+
+/// Check if `member` of guild `guild_id` has the staff group role
+fn is_staff_member(ctx: &Context, guild_id: Option<GuildId>, member: Option<&Member>) -> bool {
+    let guild = some!(guild_id.and_then(|id| id.to_guild_cached(ctx)), return false);
+    let member = some!(member, return false);
+    match MemberRank::Zero.get_group_role(&guild) {
+        Some(role) => member.roles.contains(&role.id),
+        None => false,
+    }
+}
 
-    let nick = format!("{} {} {}", rank.get_symbol(), name, custom_nick);
-    let discord_member = discord_member.edit(&http, |e| e.nickname(nick)).await?;
-    Ok(discord_member)
+/// Respond to a button press with a message only visible to the user that pressed it
+pub async fn reply_ephemeral(
+    ctx: &Context, interaction: &MessageComponentInteraction, content: &str,
+) -> Result<()> {
+    interaction
+        .create_interaction_response(ctx, |r| {
+            r.kind(InteractionResponseType::ChannelMessageWithSource)
+                .interaction_response_data(|d| d.content(content).flags(MessageFlags::EPHEMERAL))
+        })
+        .await
+        .context("Failed to respond to button")
+}
+
+/// Check if a request failed because what it targets doesn't exist, such as a deleted message
+pub fn is_not_found(why: &serenity::Error) -> bool {
+    match why {
+        serenity::Error::Http(why) => why.status_code().map(|code| code.as_u16()) == Some(404),
+        _ => false,
+    }
+}
+
+/// Get all users that reacted to a message with `reaction`
+pub async fn reaction_users(
+    http: &Http, channel_id: ChannelId, message_id: MessageId, reaction: ReactionType,
+) -> serenity::Result<Vec<User>> {
+    let mut users = Vec::new();
+    let mut after = None;
+    loop {
+        let page = channel_id.reaction_users(http, message_id, reaction.clone(), Some(100), after).await?;
+        after = match page.last() {
+            Some(user) if page.len() == 100 => Some(user.id),
+            _ => None,
+        };
+        users.extend(page);
+        if after.is_none() {
+            return Ok(users);
+        }
+    }
 }
 
 /// A 2d vector that can be formatted into a minimal lb table via `ToPage`
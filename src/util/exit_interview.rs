@@ -0,0 +1,76 @@
+//! Exit interviews, asking members that left why they did.
+//!
+//! When a member leaving is recorded, [`ask`] sends them the configured exit question via DM.
+//! Their next DM to the bot is passed to [`record_response`], which stores it as the answer if it
+//! is sent within the response window.
+use anyhow::{Context as AHContext, Result};
+use serenity::http::Http;
+use serenity::CacheAndHttp;
+use serenity::model::channel::Message;
+use serenity::model::id::UserId;
+use tokio::sync::RwLock;
+use tracing::{info, warn};
+
+use config::Config;
+use memberdb::member_exit::{self, MemberExit};
+use memberdb::model::discord::DiscordId;
+use memberdb::DB;
+use util::{ctxw, ok, some};
+
+/// DM the exit question to a member that left, if there is one and they had a discord profile
+pub async fn ask(http: &Http, db: &RwLock<DB>, config: &RwLock<Config>, exit: &MemberExit) {
+    let question = some!(config.read().await.exit_interview.question.clone(), return);
+    let discord = some!(exit.discord, return);
+    let user = UserId(ok!(u64::try_from(discord.0), return));
+    let content = format!(
+        "You left the {}, mind telling us why? Just reply to this message.\n> {}",
+        exit.source, question
+    );
+    let dm = match user.create_dm_channel(http).await {
+        Ok(channel) => channel.say(http, content).await,
+        Err(why) => Err(why),
+    };
+    if let Err(why) = dm {
+        warn!(id = exit.id, "Failed to DM exit question: {:#}", why);
+        return;
+    }
+    info!(id = exit.id, "Sent exit question");
+
+    let db = db.write().await;
+    let mut tx = ok!(ctxw!(db.begin().await), return);
+    ok!(ctxw!(member_exit::set_exit_question(&mut tx, exit.id, &question).await), return);
+    let _ = ctxw!(tx.commit().await);
+}
+
+/// Record a DM sent to the bot as the response to the exit question sent to its author, if there
+/// is one waiting for a response
+pub async fn record_response(
+    cache_http: &CacheAndHttp, db: &RwLock<DB>, config: &RwLock<Config>, message: &Message,
+) -> Result<()> {
+    if message.guild_id.is_some() || message.author.bot || message.content.trim().is_empty() {
+        return Ok(());
+    }
+    // Commands used in DMs aren't responses
+    if crate::is_command(&cache_http.cache, config, message).await {
+        return Ok(());
+    }
+    let http = &cache_http.http;
+    let window = config.read().await.exit_interview.response_window();
+    let since = message.timestamp.unix_timestamp().saturating_sub(window);
+    let author = DiscordId::try_from(message.author.id.0)?;
+
+    let id = {
+        let db = db.write().await;
+        let mut tx = db.begin().await?;
+        let id = member_exit::record_exit_response(&mut tx, author, message.content.trim(), since).await?;
+        tx.commit().await?;
+        id
+    };
+    if id.is_some() {
+        message
+            .reply(http, "Thank you for your response!")
+            .await
+            .context("Failed to acknowledge exit question response")?;
+    }
+    Ok(())
+}
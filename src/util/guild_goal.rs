@@ -0,0 +1,117 @@
+//! Announcements of guild goals.
+//!
+//! The progress of running guild goals is posted daily to [`TextChannelTag::GuildGoal`]
+//! channels, along with the goals whose deadline has passed, and goals are celebrated there once
+//! they are completed.
+use std::sync::Arc;
+
+use anyhow::Result;
+use serenity::CacheAndHttp;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::RwLock;
+use tracing::{info, warn};
+
+use config::tag::TextChannelTag;
+use config::Config;
+use memberdb::events::DBEvent;
+use memberdb::guild_goal::GuildGoal;
+use memberdb::DB;
+use util::string::StatFormat;
+use util::task::{RestartPolicy, Spawner};
+use util::{ctx, ok};
+
+use crate::i18n;
+
+/// Format a guild goal with its progress bar, and its deadline if it is still running
+pub fn format_goal(goal: &GuildGoal, fmt: &StatFormat) -> String {
+    let mut s = format!(
+        "**{}** `#{}`\n{} / {} xp `{}` {}%",
+        goal.name,
+        goal.id,
+        fmt.num(goal.progress, true),
+        fmt.num(goal.target, true),
+        util::string::progress_bar(goal.ratio(), 15),
+        (goal.ratio().min(1.0) * 100.0).floor() as i64
+    );
+    if let (Some(deadline), None) = (goal.deadline, goal.ended) {
+        s.push_str(&format!(", ends <t:{}:R>", deadline));
+    }
+    s
+}
+
+/// Post the daily progress of the running goals and the goals whose deadline has passed
+pub async fn post_update(
+    cache_http: &CacheAndHttp, config: &RwLock<Config>, running: &[GuildGoal], expired: &[GuildGoal],
+    fmt: &StatFormat,
+) -> Result<()> {
+    let mut msg = String::new();
+    if !running.is_empty() {
+        msg.push_str("> **Guild goal progress**");
+        for goal in running {
+            msg.push('\n');
+            msg.push_str(&format_goal(goal, fmt));
+        }
+    }
+    if !expired.is_empty() {
+        if !msg.is_empty() {
+            msg.push_str("\n\n");
+        }
+        msg.push_str("> **Guild goals that ran out of time**");
+        for goal in expired {
+            msg.push('\n');
+            msg.push_str(&format_goal(goal, fmt));
+        }
+    }
+    ctx!(config::send(config, cache_http, &TextChannelTag::GuildGoal, &msg).await)?;
+    Ok(())
+}
+
+/// Celebrate the completion of a goal
+pub async fn post_completion(
+    cache_http: &CacheAndHttp, config: &RwLock<Config>, goal: &GuildGoal, fmt: &StatFormat,
+) -> Result<()> {
+    let days = (goal.ended.unwrap_or(goal.created) - goal.created) / 86400;
+    let msg = format!(
+        ":tada: **The guild reached its goal!** :tada:\n{}\nCompleted in {} days",
+        format_goal(goal, fmt),
+        days
+    );
+    ctx!(config::send(config, cache_http, &TextChannelTag::GuildGoal, &msg).await)?;
+    Ok(())
+}
+
+/// Start the loop that posts the updates and completions of guild goals signaled by the member
+/// database
+pub async fn start_post_loop(
+    spawner: &impl Spawner, cache_http: Arc<CacheAndHttp>, config: Arc<RwLock<Config>>, db: Arc<RwLock<DB>>,
+) {
+    spawner.spawn("guild goal posting", RestartPolicy::Always, move || {
+        let cache_http = cache_http.clone();
+        let config = config.clone();
+        let db = db.clone();
+        async move {
+            info!("Starting guild goal posting loop");
+            let mut receiver = db.read().await.connect();
+            loop {
+                let event = match receiver.recv().await {
+                    Ok(event) => event,
+                    Err(RecvError::Lagged(skipped)) => {
+                        warn!(skipped, "Guild goal posting loop lagged behind db events");
+                        continue;
+                    }
+                    Err(RecvError::Closed) => break,
+                };
+                let fmt = i18n::main_stat_format(&*config.read().await);
+                match event.as_ref() {
+                    DBEvent::GuildGoalUpdate { running, expired } => {
+                        ok!(post_update(&cache_http, &config, running, expired, &fmt).await, continue);
+                    }
+                    DBEvent::GuildGoalComplete { goal } => {
+                        ok!(post_completion(&cache_http, &config, goal, &fmt).await, continue);
+                    }
+                    _ => {}
+                }
+            }
+        }
+    });
+}
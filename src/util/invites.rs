@@ -0,0 +1,107 @@
+//! Tracking of discord invite uses, to find out which invite a user joined the server with.
+//!
+//! Discord doesn't tell which invite is used when a user joins, so instead the uses of all invites
+//! are cached, and compared against the server's current invites when someone joins.
+use std::collections::HashMap;
+
+use anyhow::{Context, Result};
+use serenity::http::Http;
+use serenity::model::id::{GuildId, UserId};
+use serenity::model::invite::RichInvite;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// Cached state of an invite
+pub struct CachedInvite {
+    pub inviter: Option<UserId>,
+    pub uses: u64,
+    /// Maximum amount of uses, 0 if unlimited
+    pub max_uses: u64,
+}
+
+impl CachedInvite {
+    /// Check if the invite gets used up on its next use, in which case it is deleted by discord
+    fn is_last_use(&self) -> bool {
+        self.max_uses != 0 && self.uses + 1 >= self.max_uses
+    }
+}
+
+impl From<&RichInvite> for CachedInvite {
+    fn from(invite: &RichInvite) -> Self {
+        Self {
+            inviter: invite.inviter.as_ref().map(|user| user.id),
+            uses: invite.uses,
+            max_uses: invite.max_uses,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+/// Cache of a discord server's invites, mapped by invite code
+pub struct InviteCache {
+    invites: HashMap<String, CachedInvite>,
+}
+
+impl InviteCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replace the cache with the server's current invites.
+    pub async fn refresh(&mut self, http: &Http, guild_id: GuildId) -> Result<()> {
+        let invites = guild_id.invites(http).await.context("Failed to fetch guild invites")?;
+        self.invites = invites.iter().map(|invite| (invite.code.clone(), invite.into())).collect();
+        Ok(())
+    }
+
+    /// Add a newly created invite
+    pub fn insert(&mut self, code: String, inviter: Option<UserId>, max_uses: u64) {
+        self.invites.insert(code, CachedInvite { inviter, uses: 0, max_uses });
+    }
+
+    /// Remove a deleted invite.
+    ///
+    /// Invites that are deleted because they got used up are kept until the next
+    /// [`find_used`](Self::find_used), as the invite can be deleted before the join is received.
+    pub fn remove(&mut self, code: &str) {
+        if let Some(invite) = self.invites.get(code) {
+            if !invite.is_last_use() {
+                self.invites.remove(code);
+            }
+        }
+    }
+
+    /// Fetch the server's invites to find the invite used since the cache was last updated, and
+    /// update the cache.
+    ///
+    /// Returns the code and inviter of that invite, or `None` if it can't be determined, which
+    /// happens when multiple invites are used since the last update.
+    pub async fn find_used(
+        &mut self, http: &Http, guild_id: GuildId,
+    ) -> Result<Option<(String, Option<UserId>)>> {
+        let invites = guild_id.invites(http).await.context("Failed to fetch guild invites")?;
+        let invites: HashMap<String, CachedInvite> =
+            invites.iter().map(|invite| (invite.code.clone(), invite.into())).collect();
+
+        let mut used = invites
+            .iter()
+            .filter(|(code, invite)| invite.uses > self.invites.get(*code).map_or(0, |cached| cached.uses));
+        let found = match (used.next(), used.next()) {
+            (Some((code, invite)), None) => Some((code.clone(), invite.inviter)),
+            (Some(_), Some(_)) => None,
+            // The invite may have been deleted after being used up
+            (None, _) => {
+                let mut used_up = self
+                    .invites
+                    .iter()
+                    .filter(|(code, invite)| invite.is_last_use() && !invites.contains_key(*code));
+                match (used_up.next(), used_up.next()) {
+                    (Some((code, invite)), None) => Some((code.clone(), invite.inviter)),
+                    _ => None,
+                }
+            }
+        };
+
+        self.invites = invites;
+        Ok(found)
+    }
+}
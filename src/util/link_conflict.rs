@@ -0,0 +1,113 @@
+//! Staff review of guild joins whose member has a discord link that looks stale.
+//!
+//! Joins held back by the database are posted to [`TextChannelTag::LinkConflict`] channels with
+//! buttons to keep or unlink the member's discord profile.
+//! Button presses are answered by [`respond`], which applies the join either way.
+use std::sync::Arc;
+
+use anyhow::{Context as AHContext, Result};
+use serenity::client::Context;
+use serenity::model::application::component::ButtonStyle;
+use serenity::model::application::interaction::message_component::MessageComponentInteraction;
+use serenity::model::application::interaction::InteractionResponseType;
+use serenity::model::id::ChannelId;
+use serenity::CacheAndHttp;
+use tokio::sync::RwLock;
+use tracing::{info, warn};
+
+use config::tag::TextChannelTag;
+use config::Config;
+use memberdb::link_conflict::{self, LinkConflict};
+use memberdb::DB;
+use util::some;
+
+use crate::util::discord::{is_staff_interaction, reply_ephemeral};
+
+/// Prefix of the custom id of the button that keeps the discord link, followed by the join's id
+const KEEP_ID: &str = "link_conflict_keep";
+/// Prefix of the custom id of the button that unlinks the discord profile, followed by the join's id
+const UNLINK_ID: &str = "link_conflict_unlink";
+
+/// Post a held back join to [`TextChannelTag::LinkConflict`] channels
+pub async fn post_conflict(
+    cache_http: &CacheAndHttp, config: &RwLock<Config>, conflict: &LinkConflict,
+) -> Result<()> {
+    let content = format!(
+        "> **Suspicious guild join #{}**\n`{}` joined the guild while linked to <@{}>, but {}.\n\
+        They aren't bound to the guild until a staff keeps or unlinks the discord profile.",
+        conflict.id, conflict.join.ign, conflict.discord, conflict.conflict
+    );
+    let channels: Vec<u64> = {
+        let config = config.read().await;
+        config.text_channel_tags.tagged_objects(&TextChannelTag::LinkConflict).copied().collect()
+    };
+    for channel_id in channels {
+        let result = ChannelId(channel_id)
+            .send_message(&cache_http.http, |m| {
+                m.content(&content).components(|c| {
+                    c.create_action_row(|ar| {
+                        ar.create_button(|b| {
+                            b.custom_id(format!("{}:{}", KEEP_ID, conflict.id))
+                                .label("Keep link")
+                                .style(ButtonStyle::Success)
+                        })
+                        .create_button(|b| {
+                            b.custom_id(format!("{}:{}", UNLINK_ID, conflict.id))
+                                .label("Unlink discord")
+                                .style(ButtonStyle::Danger)
+                        })
+                    })
+                })
+            })
+            .await;
+        if let Err(why) = result {
+            warn!(channel_id, "Failed to post link conflict: {:#}", why);
+        }
+    }
+    Ok(())
+}
+
+/// Respond to a button press on a held back join, other interactions are ignored
+pub async fn respond(ctx: &Context, interaction: &MessageComponentInteraction) -> Result<()> {
+    let (action, id) = some!(interaction.data.custom_id.split_once(':'), return Ok(()));
+    let unlink = match action {
+        KEEP_ID => false,
+        UNLINK_ID => true,
+        _ => return Ok(()),
+    };
+    let id: i64 = id.parse().context("Invalid link conflict id")?;
+
+    if !is_staff_interaction(ctx, interaction) {
+        return reply_ephemeral(ctx, interaction, "Only staff can review guild joins").await;
+    }
+
+    let db = {
+        let data = ctx.data.read().await;
+        Arc::clone(data.get::<DB>().context("Failed to get db")?)
+    };
+    let conflict = {
+        let db = db.write().await;
+        let mut tx = db.begin().await?;
+        let conflict = if unlink {
+            link_conflict::unlink_discord(&mut tx, id).await?
+        } else {
+            link_conflict::keep_link(&mut tx, id).await?
+        };
+        tx.commit().await?;
+        conflict
+    };
+    if conflict.is_none() {
+        return reply_ephemeral(ctx, interaction, "This guild join is already reviewed").await;
+    }
+    info!(id, unlink, user = interaction.user.id.0, "Staff reviewed guild join");
+
+    let result = if unlink { "unlinked the discord profile" } else { "kept the discord link" };
+    let content = format!("{}\n\n<@{}> {}.", interaction.message.content, interaction.user.id, result);
+    interaction
+        .create_interaction_response(ctx, |r| {
+            r.kind(InteractionResponseType::UpdateMessage)
+                .interaction_response_data(|d| d.content(content).components(|c| c))
+        })
+        .await
+        .context("Failed to respond to link conflict button")
+}
@@ -2,8 +2,8 @@
 ///
 /// This the same as using [`send`] and then exit the command.
 ///
-/// This macro takes a variable name that of [`Context`], and a variable name that of [`Message`],
-/// which are used to send the reply.
+/// This macro takes a variable name that of [`Context`], and a variable name that of [`Message`]
+/// (or anything that implements [`Respond`]), which are used to send the reply.
 /// It then takes the content of the reply, which can be a string, or a format string with
 /// arguments.
 /// ```
@@ -27,6 +27,7 @@
 /// a function that returns [`Result`].
 ///
 /// [`send`]: crate::send
+/// [`Respond`]: crate::util::reply::Respond
 /// [`Result`]: std::result::Result
 /// [`Context`]: serenity::client::Context
 /// [`Message`]: serenity::model::channel::Message
@@ -46,8 +47,8 @@ macro_rules! finish {
 ///
 /// This is the same as [`finish`] but it won't exit the command.
 ///
-/// This macro takes a variable name that of [`Context`], and a variable name that of [`Message`],
-/// which are used to send the reply.
+/// This macro takes a variable name that of [`Context`], and a variable name that of [`Message`]
+/// (or anything that implements [`Respond`]), which are used to send the reply.
 /// It then takes the content of the reply, which can be a string, or a format string with
 /// arguments.
 /// ```
@@ -67,19 +68,32 @@ macro_rules! finish {
 /// a function that returns [`Result`].
 ///
 /// [`finish`]: crate::finish
+/// [`Respond`]: crate::util::reply::Respond
 /// [`Result`]: std::result::Result
 /// [`Context`]: serenity::client::Context
 /// [`Message`]: serenity::model::channel::Message
 #[macro_export]
 macro_rules! send {
     ($ctx:ident, $sender:expr, $content:expr) => {
-        $sender.reply(&$ctx, $content).await.map_err(|why| {
+        {
+            // Unused when the sender is generic over `Respond`
+            #[allow(unused_imports)]
+            use $crate::util::reply::Respond as _;
+            $sender.respond(&$ctx, $content.to_string()).await
+        }
+        .map_err(|why| {
             tracing::error!("Failed to reply to message: {:#}", why);
             why
         })?
     };
     ($ctx:ident, $sender:expr, $($content:tt)+) => {
-        $sender.reply(&$ctx, format!($($content)+)).await.map_err(|why| {
+        {
+            // Unused when the sender is generic over `Respond`
+            #[allow(unused_imports)]
+            use $crate::util::reply::Respond as _;
+            $sender.respond(&$ctx, format!($($content)+)).await
+        }
+        .map_err(|why| {
             tracing::error!("Failed to reply to message: {:#}", why);
             why
         })?
@@ -107,8 +121,14 @@ macro_rules! cmd_bail {
 /// - "db": [`Arc<RwLock<DB>>`]
 /// - "shard": [`Arc<Mutex<ShardManager>>`]
 /// - "reqwest": [`reqwest::Client`]
+/// - "timer": [`TimerSignal`]
+/// - "tasks": [`TaskRegistry`]
+/// - "log": [`Arc<Mutex<LogLevels>>`]
+/// - "secrets": [`Arc<RwLock<Secrets>>`]
 /// - "vc": [`Arc<Mutex<VoiceTracker>>`]
 /// - "cache": [`Arc<Cache>`]
+/// - "outbox": [`Arc<Outbox>`]
+/// - "start": [`DateTime<Utc>`] of when the bot was started
 /// ```
 /// # use haxbotjr::data;
 /// use anyhow::Result;
@@ -131,6 +151,12 @@ macro_rules! cmd_bail {
 /// [`Arc<Mutex<ShardManager>>`]: serenity::client::bridge::gateway::ShardManager
 /// [`Arc<Mutex<VoiceTracker>>`]: memberdb::voice_tracker::VoiceTracker
 /// [`Arc<Cache>`]: wynn::cache::Cache
+/// [`TimerSignal`]: event::timer::TimerSignal
+/// [`TaskRegistry`]: crate::tasks::TaskRegistry
+/// [`Arc<Mutex<LogLevels>>`]: crate::log_level::LogLevels
+/// [`Arc<RwLock<Secrets>>`]: crate::secrets::Secrets
+/// [`Arc<Outbox>`]: crate::util::outbox::Outbox
+/// [`DateTime<Utc>`]: chrono::DateTime
 #[macro_export]
 macro_rules! data {
     ($ctx:ident, $name:tt) => {{
@@ -165,6 +191,30 @@ macro_rules! data {
             None => $crate::cmd_bail!("Failed to access reqwest client"),
         }
     };
+    (INTERNAL; "timer", $data:ident) => {
+        match $data.get::<$crate::data::TimerSignalContainer>() {
+            Some(v) => v.clone(),
+            None => $crate::cmd_bail!("Failed to access timer signal"),
+        }
+    };
+    (INTERNAL; "tasks", $data:ident) => {
+        match $data.get::<$crate::tasks::TaskRegistry>() {
+            Some(v) => v.clone(),
+            None => $crate::cmd_bail!("Failed to access task registry"),
+        }
+    };
+    (INTERNAL; "log", $data:ident) => {
+        match $data.get::<$crate::log_level::LogLevels>() {
+            Some(v) => v.clone(),
+            None => $crate::cmd_bail!("Failed to access log levels"),
+        }
+    };
+    (INTERNAL; "secrets", $data:ident) => {
+        match $data.get::<$crate::secrets::Secrets>() {
+            Some(v) => v.clone(),
+            None => $crate::cmd_bail!("Failed to access secrets"),
+        }
+    };
     (INTERNAL; "vc", $data:ident) => {
         match $data.get::<memberdb::voice_tracker::VoiceTracker>() {
             Some(v) => v.clone(),
@@ -177,6 +227,18 @@ macro_rules! data {
             None => $crate::cmd_bail!("Failed to access wynn cache"),
         }
     };
+    (INTERNAL; "outbox", $data:ident) => {
+        match $data.get::<$crate::util::outbox::Outbox>() {
+            Some(v) => v.clone(),
+            None => $crate::cmd_bail!("Failed to access outbox"),
+        }
+    };
+    (INTERNAL; "start", $data:ident) => {
+        match $data.get::<$crate::data::StartTimeContainer>() {
+            Some(v) => *v,
+            None => $crate::cmd_bail!("Failed to access start time"),
+        }
+    };
 }
 
 /// Send an embed.
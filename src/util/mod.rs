@@ -1,8 +1,28 @@
 //! Utility functions for commands
 pub mod arg;
+pub mod autocomplete;
+pub mod bulk_fix;
 pub mod db;
 pub mod discord;
+pub mod exit_interview;
+pub mod guild_goal;
+pub mod invites;
+pub mod link_conflict;
 pub mod macros;
+pub mod mutation;
+pub mod outbox;
+pub mod poll;
+pub mod promotion_vote;
+pub mod rank_channel;
+pub mod reminder;
+pub mod reply;
+pub mod slash;
+pub mod suggestion;
+pub mod sync_state;
+pub mod ticket;
+pub mod waitlist;
+pub mod weekly_reset;
+pub mod xp_correction;
 
 /// Wraps `T`, the `Terminate` variant signals the calling command that it should terminate.
 pub enum Terminator<T> {
@@ -54,14 +74,26 @@ macro_rules! ttry {
 /// Same as `finish` but returns `Terminator::Terminate`
 macro_rules! tfinish {
     ($ctx:ident, $sender:expr, $content:expr) => {{
-        let _ = $sender.reply(&$ctx, $content).await.map_err(|why| {
+        let _ = {
+            // Unused when the sender is generic over `Respond`
+            #[allow(unused_imports)]
+            use $crate::util::reply::Respond as _;
+            $sender.respond(&$ctx, $content.to_string()).await
+        }
+        .map_err(|why| {
             tracing::error!("Failed to reply to message: {:#}", why);
             why
         });
         return $crate::util::Terminator::Terminate;
     }};
     ($ctx:ident, $sender:expr, $($content:tt)+) => {{
-        let _ = $sender.reply(&$ctx, format!($($content)+)).await.map_err(|why| {
+        let _ = {
+            // Unused when the sender is generic over `Respond`
+            #[allow(unused_imports)]
+            use $crate::util::reply::Respond as _;
+            $sender.respond(&$ctx, format!($($content)+)).await
+        }
+        .map_err(|why| {
             tracing::error!("Failed to reply to message: {:#}", why);
             why
         });
@@ -0,0 +1,170 @@
+//! Changes made to discord members to keep their roles and nicknames in sync with the database,
+//! and a queue for retrying the ones that failed.
+//!
+//! Mutations fail under rate limits or transient errors, so instead of only logging the failure,
+//! they are retried with exponential backoff.
+//! Only the latest mutation of a member's roles / nickname is retried.
+use std::collections::HashMap;
+use std::mem::{self, Discriminant};
+use std::time::Duration;
+
+use anyhow::Result;
+use serenity::http::Http;
+use serenity::model::guild::{Guild, Member};
+use serenity::model::id::UserId;
+use serenity::CacheAndHttp;
+use tokio::sync::RwLock;
+use tokio::time::Instant;
+use tracing::{info, warn};
+
+use config::Config;
+use memberdb::DB;
+
+use super::sync_state::DiscordSyncState;
+
+/// Delay before the first retry of a failed mutation, it is doubled on each failed retry
+const RETRY_DELAY: Duration = Duration::from_secs(5);
+/// Max delay between retries
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(600);
+/// Amount of retries before a mutation is given up
+const MAX_RETRIES: u32 = 8;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// A change that brings part of a discord member to its [`DiscordSyncState`].
+///
+/// The desired state is computed when the mutation is applied, so a retried mutation is never
+/// outdated.
+pub enum Mutation {
+    /// Sync the rank roles
+    Roles,
+    /// Sync the nickname, `custom_nick` replaces their current custom nick if given
+    Nick { custom_nick: Option<String> },
+}
+
+impl Mutation {
+    /// Mutations that sync both the roles and nickname
+    pub fn all(custom_nick: Option<String>) -> [Self; 2] {
+        [Self::Roles, Self::Nick { custom_nick }]
+    }
+
+    /// Apply the mutation, unless the member is tagged to not have that part updated
+    pub async fn apply(
+        &self, http: &Http, db: &RwLock<DB>, config: &RwLock<Config>, guild: &Guild, member: &mut Member,
+    ) -> Result<()> {
+        let allowed = {
+            let config = config.read().await;
+            match self {
+                Self::Roles => config.should_update_role(member),
+                Self::Nick { .. } => config.should_update_nick(member),
+            }
+        };
+        if !allowed {
+            return Ok(());
+        }
+
+        let custom_nick = match self {
+            Self::Nick { custom_nick } => custom_nick.as_deref(),
+            Self::Roles => None,
+        };
+        let diff = DiscordSyncState::fetch(db, guild, member, custom_nick).await?.diff(guild, member);
+        match self {
+            Self::Roles if diff.changes_roles() => {
+                info!("Updating discord roles");
+                diff.apply_roles(http, member).await
+            }
+            Self::Nick { .. } if diff.nick.is_some() => {
+                info!("Updating discord nickname");
+                diff.apply_nick(http, member).await
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+#[derive(Debug)]
+/// A mutation waiting to be retried
+struct Retry {
+    mutation: Mutation,
+    /// Amount of failed retries
+    attempts: u32,
+    /// When the mutation is retried
+    due: Instant,
+}
+
+#[derive(Debug, Default)]
+/// Queue of failed mutations, only the latest mutation of each part of a discord member is kept
+pub struct RetryQueue {
+    retries: HashMap<(UserId, Discriminant<Mutation>), Retry>,
+}
+
+impl RetryQueue {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Apply mutations to a discord member, and queue the failed ones for retry.
+    ///
+    /// Queued mutations that change the same part of the member are dropped, as they are
+    /// superseded.
+    pub async fn apply(
+        &mut self, http: &Http, db: &RwLock<DB>, config: &RwLock<Config>, guild: &Guild, member: &mut Member,
+        mutations: impl IntoIterator<Item = Mutation>,
+    ) {
+        for mutation in mutations {
+            let key = (member.user.id, mem::discriminant(&mutation));
+            self.retries.remove(&key);
+            if let Err(why) = mutation.apply(http, db, config, guild, member).await {
+                warn!(?mutation, "Failed to mutate discord member: {:#}", why);
+                self.schedule(key, mutation, 0, &why);
+            }
+        }
+    }
+
+    /// Retry the mutations that are due
+    pub async fn retry_due(
+        &mut self, cache_http: &CacheAndHttp, db: &RwLock<DB>, config: &RwLock<Config>, guild: &Guild,
+    ) {
+        let now = Instant::now();
+        let due: Vec<_> =
+            self.retries.iter().filter(|(_, retry)| retry.due <= now).map(|(key, _)| *key).collect();
+        for key in due {
+            let retry = match self.retries.remove(&key) {
+                Some(retry) => retry,
+                None => continue,
+            };
+            let attempts = retry.attempts + 1;
+            let mut member = match guild.member(cache_http, key.0).await {
+                Ok(member) => member,
+                Err(why) => {
+                    let why = anyhow::Error::from(why).context("Failed to get discord member");
+                    warn!(mutation = ?retry.mutation, attempts, "Failed to retry discord mutation: {:#}", why);
+                    self.schedule(key, retry.mutation, attempts, &why);
+                    continue;
+                }
+            };
+            match retry.mutation.apply(&cache_http.http, db, config, guild, &mut member).await {
+                Ok(()) => info!(mutation = ?retry.mutation, attempts, "Retried discord mutation"),
+                Err(why) => {
+                    warn!(mutation = ?retry.mutation, attempts, "Failed to retry discord mutation: {:#}", why);
+                    self.schedule(key, retry.mutation, attempts, &why);
+                }
+            }
+        }
+    }
+
+    /// Queue a failed mutation for retry, unless retrying won't fix the failure
+    fn schedule(
+        &mut self, key: (UserId, Discriminant<Mutation>), mutation: Mutation, attempts: u32,
+        why: &anyhow::Error,
+    ) {
+        if why.downcast_ref::<serenity::Error>().is_some_and(config::report::is_permanent) {
+            return;
+        }
+        if attempts >= MAX_RETRIES {
+            warn!(?mutation, "Giving up on discord mutation");
+            return;
+        }
+        let delay = RETRY_DELAY.saturating_mul(2u32.saturating_pow(attempts)).min(MAX_RETRY_DELAY);
+        self.retries.insert(key, Retry { mutation, attempts, due: Instant::now() + delay });
+    }
+}
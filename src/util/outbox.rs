@@ -0,0 +1,263 @@
+//! Messages to tagged channels and log channels that couldn't be delivered, kept until they can be.
+//!
+//! Summaries are sent with [`send`] and logs with [`send_log`], which buffer a message in the
+//! [`Outbox`] if it doesn't reach any channel, ex: no channel has the tag, or the bot lacks
+//! permissions.
+//! Buffered messages are saved to [`OUTBOX_FILE`] periodically so they survive restarts, and are
+//! retried by [`flush`], which is called periodically and by the `logs flush` command.
+//! Retrying a message doesn't count towards untagging its channels, so channels aren't untagged
+//! while they are unavailable for a while.
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+use anyhow::Result;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use serenity::http::CacheHttp;
+use serenity::prelude::TypeMapKey;
+use serenity::CacheAndHttp;
+use tokio::sync::RwLock;
+use tokio::time::{self, Duration};
+use tracing::{info, warn};
+
+use config::report::SendReport;
+use config::tag::TextChannelTag;
+use config::Config;
+use util::task::{RestartPolicy, Spawner};
+use util::{read_json, write_json};
+
+/// File the undelivered messages are saved to
+pub const OUTBOX_FILE: &str = "cache/outbox.json";
+/// Max amount of undelivered messages kept, the oldest ones are dropped when it is exceeded
+const MAX_UNDELIVERED: usize = 200;
+/// How often delivering the undelivered messages is retried
+const FLUSH_INTERVAL: Duration = Duration::from_secs(300);
+/// How often the undelivered messages are saved to [`OUTBOX_FILE`] if they changed
+const SAVE_INTERVAL: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+/// Where an undelivered message is sent to
+pub enum Destination {
+    /// All the channels with the tag
+    Tag(TextChannelTag),
+    /// A log channel, the message is dropped if it no longer subscribes to any log event
+    LogChannel(u64),
+}
+
+impl fmt::Display for Destination {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Tag(tag) => write!(f, "{}", tag),
+            Self::LogChannel(channel_id) => write!(f, "<#{}>", channel_id),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+/// A message that wasn't delivered
+pub struct Undelivered {
+    pub destination: Destination,
+    pub content: String,
+    /// Unix timestamp of when the message was first sent
+    pub time: i64,
+}
+
+#[derive(Debug, Default)]
+/// Undelivered messages, from the oldest to the newest
+pub struct Outbox {
+    messages: Mutex<VecDeque<Undelivered>>,
+    /// If the messages changed since they were last saved
+    dirty: AtomicBool,
+}
+
+impl Outbox {
+    /// Load the undelivered messages saved to [`OUTBOX_FILE`]
+    pub fn load() -> Self {
+        let messages = read_json!(OUTBOX_FILE, VecDeque::new()).unwrap_or_default();
+        Self { messages: Mutex::new(messages), dirty: AtomicBool::new(false) }
+    }
+
+    /// Save the undelivered messages to [`OUTBOX_FILE`] if they changed since the last save
+    pub fn save(&self) {
+        if self.dirty.swap(false, Ordering::Relaxed) {
+            let messages = self.messages.lock().unwrap();
+            write_json!(OUTBOX_FILE, &*messages, "outbox");
+        }
+    }
+
+    /// Buffer a message to be delivered later
+    pub fn push(&self, destination: Destination, content: String) {
+        let mut messages = self.messages.lock().unwrap();
+        if messages.len() >= MAX_UNDELIVERED {
+            if let Some(dropped) = messages.pop_front() {
+                let destination = dropped.destination;
+                warn!(%destination, time = dropped.time, "Dropping oldest undelivered message");
+            }
+        }
+        messages.push_back(Undelivered { destination, content, time: Utc::now().timestamp() });
+        self.dirty.store(true, Ordering::Relaxed);
+    }
+
+    /// Remove the oldest message equal to the given one, if it's still buffered
+    fn remove(&self, message: &Undelivered) {
+        let mut messages = self.messages.lock().unwrap();
+        if let Some(index) = messages.iter().position(|buffered| buffered == message) {
+            messages.remove(index);
+            self.dirty.store(true, Ordering::Relaxed);
+        }
+    }
+
+    /// Check if there are undelivered messages to a destination
+    pub fn has_pending(&self, destination: &Destination) -> bool {
+        self.messages.lock().unwrap().iter().any(|message| &message.destination == destination)
+    }
+
+    /// Get the amount of undelivered messages to each destination
+    pub fn counts(&self) -> Vec<(Destination, usize)> {
+        let mut counts: Vec<(Destination, usize)> = Vec::new();
+        for message in self.messages.lock().unwrap().iter() {
+            match counts.iter_mut().find(|(destination, _)| destination == &message.destination) {
+                Some((_, count)) => *count += 1,
+                None => counts.push((message.destination.clone(), 1)),
+            }
+        }
+        counts
+    }
+
+    /// Get the amount of undelivered messages
+    pub fn len(&self) -> usize {
+        self.messages.lock().unwrap().len()
+    }
+
+    /// Check if there are no undelivered messages
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl TypeMapKey for Outbox {
+    type Value = Arc<Outbox>;
+}
+
+/// Send a message to all the channels with a tag via [`config::send`], it is buffered if it
+/// doesn't reach any of them.
+///
+/// If there are already undelivered messages with the tag, the message is buffered right away so
+/// the messages are delivered in order.
+pub async fn send(
+    outbox: &Outbox, config: &RwLock<Config>, cache_http: &impl CacheHttp, tag: &TextChannelTag,
+    content: &str,
+) -> Result<SendReport> {
+    let destination = Destination::Tag(tag.clone());
+    if outbox.has_pending(&destination) {
+        outbox.push(destination, content.to_string());
+        return Ok(SendReport::default());
+    }
+    let result = config::send(config, cache_http, tag, content).await;
+    buffer_if_undelivered(outbox, destination, content, &result);
+    result
+}
+
+/// Send a log message to a log channel via [`config::send_log`], it is buffered if it isn't
+/// delivered.
+///
+/// If there are already undelivered messages to the channel, the message is buffered right away
+/// so the messages are delivered in order.
+pub async fn send_log(
+    outbox: &Outbox, config: &RwLock<Config>, cache_http: &impl CacheHttp, channel_id: u64, content: &str,
+) -> Result<SendReport> {
+    let destination = Destination::LogChannel(channel_id);
+    if outbox.has_pending(&destination) {
+        outbox.push(destination, content.to_string());
+        return Ok(SendReport::default());
+    }
+    let result = config::send_log(config, cache_http, &[channel_id], content).await;
+    buffer_if_undelivered(outbox, destination, content, &result);
+    result
+}
+
+/// Buffer a message if the result of sending it shows it didn't reach any channel
+fn buffer_if_undelivered(
+    outbox: &Outbox, destination: Destination, content: &str, result: &Result<SendReport>,
+) {
+    if !matches!(result, Ok(report) if !report.sent.is_empty()) {
+        info!(%destination, "Buffering message that wasn't delivered to any channel");
+        outbox.push(destination, content.to_string());
+    }
+}
+
+/// Try to deliver the undelivered messages in order, returns the amount of messages delivered.
+///
+/// Once a message fails to be delivered, the later messages to the same destination aren't tried.
+/// A message is only taken out of the outbox once it is delivered, so the messages stay in order
+/// if delivering them fails partway.
+/// Messages to log channels that no longer subscribe to any log event are dropped.
+pub async fn flush(outbox: &Outbox, config: &RwLock<Config>, cache_http: &impl CacheHttp) -> usize {
+    let messages: Vec<Undelivered> = outbox.messages.lock().unwrap().iter().cloned().collect();
+    if messages.is_empty() {
+        return 0;
+    }
+
+    let mut delivered = 0;
+    let mut failed: Vec<Destination> = Vec::new();
+    for message in messages {
+        if failed.contains(&message.destination) {
+            continue;
+        }
+        let config = config.read().await;
+        let channels: Vec<u64> = match &message.destination {
+            Destination::Tag(tag) => config.text_channel_tags.tagged_objects(tag).copied().collect(),
+            Destination::LogChannel(channel_id) => {
+                if config.log_subscriptions.events(*channel_id).is_none() {
+                    info!(
+                        channel_id,
+                        "Dropping undelivered message to a channel that is no longer a log channel"
+                    );
+                    outbox.remove(&message);
+                    continue;
+                }
+                vec![*channel_id]
+            }
+        };
+        match config.resend_to(cache_http, &channels, &message.content).await {
+            Ok(report) if !report.sent.is_empty() => {
+                outbox.remove(&message);
+                delivered += 1;
+            }
+            _ => failed.push(message.destination.clone()),
+        }
+    }
+    if delivered > 0 {
+        info!(delivered, remaining = outbox.len(), "Delivered buffered messages");
+    }
+    outbox.save();
+    delivered
+}
+
+/// Start the loop that periodically tries to deliver the undelivered messages, and saves them
+pub async fn start_flush_loop(
+    spawner: &impl Spawner, cache_http: Arc<CacheAndHttp>, config: Arc<RwLock<Config>>, outbox: Arc<Outbox>,
+) {
+    spawner.spawn("outbox flush", RestartPolicy::Always, move || {
+        let cache_http = cache_http.clone();
+        let config = config.clone();
+        let outbox = outbox.clone();
+        async move {
+            info!("Starting outbox flush loop");
+            let mut flush_interval = time::interval(FLUSH_INTERVAL);
+            let mut save_interval = time::interval(SAVE_INTERVAL);
+            loop {
+                tokio::select! {
+                    _ = flush_interval.tick() => {
+                        if !outbox.is_empty() {
+                            flush(&outbox, &config, &cache_http).await;
+                        }
+                    }
+                    _ = save_interval.tick() => outbox.save(),
+                }
+            }
+        }
+    });
+}
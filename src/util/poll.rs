@@ -0,0 +1,132 @@
+//! Polls, where discord users vote on an option by reacting to a poll message with its emoji.
+//!
+//! Polls are stored in the database, and [`close_due_polls`] is called periodically to tally the
+//! polls whose voting window is over, so polls are still closed after a restart.
+//! Each user has one vote, reacting with several options doesn't count.
+use std::collections::HashMap;
+
+use anyhow::{Context as AHContext, Result};
+use serenity::builder::CreateEmbed;
+use serenity::model::channel::ReactionType;
+use serenity::model::id::{ChannelId, MessageId, UserId};
+use serenity::CacheAndHttp;
+use tokio::sync::RwLock;
+use tracing::{info, warn};
+
+use memberdb::poll::{self, Poll};
+use memberdb::DB;
+use util::{ctx, ctxw, ok};
+
+use crate::util::discord::{is_not_found, reaction_users};
+
+/// Reactions used to vote on the options, in the same order as the options
+pub const OPTION_EMOJIS: [&str; 10] = ["1️⃣", "2️⃣", "3️⃣", "4️⃣", "5️⃣", "6️⃣", "7️⃣", "8️⃣", "9️⃣", "🔟"];
+
+/// Make the content of a poll message
+pub fn make_poll(question: &str, options: &[String], close: i64) -> String {
+    let mut content = format!("> **Poll**\n{}\n", question);
+    for (emoji, option) in OPTION_EMOJIS.iter().zip(options) {
+        content.push_str(&format!("{} {}\n", emoji, option));
+    }
+    content.push_str(&format!("React with one option to vote, the poll closes <t:{}:R>.", close));
+    content
+}
+
+/// Build the embed displaying the results of a poll, `results` is its options along with their
+/// votes.
+pub fn make_results_embed<'a>(
+    e: &'a mut CreateEmbed, question: &str, results: &[(String, Option<i64>)],
+) -> &'a mut CreateEmbed {
+    let total: i64 = results.iter().filter_map(|(_, votes)| *votes).sum();
+    let mut description = String::new();
+    for ((option, votes), emoji) in results.iter().zip(OPTION_EMOJIS) {
+        let line = match votes {
+            Some(votes) if total > 0 => {
+                format!("{} {}: **{}** ({}%)\n", emoji, option, votes, votes * 100 / total)
+            }
+            Some(votes) => format!("{} {}: **{}**\n", emoji, option, votes),
+            None => format!("{} {}\n", emoji, option),
+        };
+        description.push_str(&line);
+    }
+    e.title(question).description(description).footer(|f| f.text(format!("{} votes", total)))
+}
+
+/// Close all polls whose voting window is over at unix timestamp `now`
+pub async fn close_due_polls(cache_http: &CacheAndHttp, db: &RwLock<DB>, now: i64) {
+    let polls = {
+        let db = db.read().await;
+        ok!(ctxw!(poll::due_polls(&db, now).await), return)
+    };
+    for poll in polls {
+        if let Err(why) = close_poll(cache_http, db, &poll).await {
+            warn!(poll.id, "Failed to close poll: {:#}", why);
+        }
+    }
+}
+
+/// Tally a poll's reactions, store the votes and post the results.
+///
+/// If the poll message is deleted, the poll is closed without votes.
+async fn close_poll(cache_http: &CacheAndHttp, db: &RwLock<DB>, poll: &Poll) -> Result<()> {
+    let votes = tally_votes(cache_http, poll).await?;
+    {
+        let db = db.write().await;
+        let mut tx = db.begin().await?;
+        let stored = votes.clone().unwrap_or_else(|| vec![0; poll.options.len()]);
+        poll::close_poll(&mut tx, poll.id, &stored).await?;
+        tx.commit().await?;
+    }
+    let votes = match votes {
+        Some(votes) => votes,
+        None => {
+            info!(poll.id, "Closed poll whose message is deleted");
+            return Ok(());
+        }
+    };
+
+    let results: Vec<(String, Option<i64>)> =
+        poll.options.iter().cloned().zip(votes.into_iter().map(Some)).collect();
+    let channel_id = ChannelId(u64::try_from(poll.channel)?);
+    let message_id = MessageId(u64::try_from(poll.message)?);
+    ctx!(
+        channel_id
+            .send_message(&cache_http.http, |m| {
+                m.reference_message((channel_id, message_id))
+                    .content("Poll closed")
+                    .embed(|e| make_results_embed(e, &poll.question, &results))
+            })
+            .await,
+        "Failed to send poll results"
+    )?;
+    Ok(())
+}
+
+/// Count the votes of each option of a poll, `None` if the poll message is deleted.
+///
+/// Each user has one vote, so users that reacted with several options aren't counted. Bots are
+/// excluded.
+async fn tally_votes(cache_http: &CacheAndHttp, poll: &Poll) -> Result<Option<Vec<i64>>> {
+    let channel_id = ChannelId(u64::try_from(poll.channel)?);
+    let message_id = MessageId(u64::try_from(poll.message)?);
+
+    // The option each user voted on, `None` if they voted on several
+    let mut ballots: HashMap<UserId, Option<usize>> = HashMap::new();
+    for (option, emoji) in OPTION_EMOJIS.iter().take(poll.options.len()).enumerate() {
+        let reaction = ReactionType::Unicode(emoji.to_string());
+        let users = match reaction_users(&cache_http.http, channel_id, message_id, reaction).await {
+            Ok(users) => users,
+            Err(why) if is_not_found(&why) => return Ok(None),
+            Err(why) => return Err(why).context("Failed to get reaction users"),
+        };
+        for user in users.into_iter().filter(|user| !user.bot) {
+            ballots.entry(user.id).and_modify(|ballot| *ballot = None).or_insert(Some(option));
+        }
+    }
+
+    let mut votes = vec![0; poll.options.len()];
+    for option in ballots.into_values().flatten() {
+        votes[option] += 1;
+    }
+    Ok(Some(votes))
+}
@@ -0,0 +1,160 @@
+//! Promotion votes, where staff members vote on changing a member's rank by reacting to a poll
+//! message.
+//!
+//! Votes are stored in the database, and [`close_due_votes`] is called periodically to tally the
+//! votes whose voting window is over, so votes are still closed after a restart.
+//! A vote whose poll message is deleted is closed without applying the rank change.
+use std::collections::HashSet;
+
+use anyhow::{Context as AHContext, Result};
+use serenity::model::channel::ReactionType;
+use serenity::model::guild::Guild;
+use serenity::model::id::{ChannelId, MessageId, UserId};
+use serenity::CacheAndHttp;
+use tokio::sync::RwLock;
+use tracing::{info, warn};
+
+use config::Config;
+use memberdb::events::DBEvent;
+use memberdb::model::member::MemberRank;
+use memberdb::{ConcurrentChange, PromotionVote, DB};
+use util::{ctx, ctxw, ok, some};
+
+use crate::util::discord::{is_not_found, reaction_users};
+
+/// Reaction used to approve a rank change
+pub const APPROVE: char = '✅';
+/// Reaction used to reject a rank change
+pub const REJECT: char = '❌';
+
+/// Make the content of a vote's poll message
+pub fn make_poll(name: &str, old_rank: MemberRank, rank: MemberRank, caller: UserId, close: i64) -> String {
+    format!(
+        "> **Promotion vote**\n<@{}> proposes changing **{}**'s rank from __{}__ to __{}__.\n\
+        React with {} to approve or {} to reject, the vote closes <t:{}:R>.",
+        caller, name, old_rank, rank, APPROVE, REJECT, close
+    )
+}
+
+/// Close all votes whose voting window is over at unix timestamp `now`
+pub async fn close_due_votes(
+    cache_http: &CacheAndHttp, db: &RwLock<DB>, config: &RwLock<Config>, guild: &Guild, now: i64,
+) {
+    let votes = {
+        let db = db.read().await;
+        ok!(ctxw!(memberdb::due_promotion_votes(&db, now).await), return)
+    };
+    for vote in votes {
+        if let Err(why) = close_vote(cache_http, db, config, guild, &vote).await {
+            warn!(vote.id, "Failed to close promotion vote: {:#}", why);
+        }
+    }
+}
+
+/// Tally a vote's reactions, and apply the rank change if it passed
+async fn close_vote(
+    cache_http: &CacheAndHttp, db: &RwLock<DB>, config: &RwLock<Config>, guild: &Guild, vote: &PromotionVote,
+) -> Result<()> {
+    let min_approvals = {
+        let config = config.read().await;
+        config.promotion_votes.min_approvals
+    };
+    let (approvals, rejections) = match tally_votes(cache_http, guild, vote).await? {
+        Some(tally) => tally,
+        None => {
+            let db = db.write().await;
+            let mut tx = db.begin().await?;
+            memberdb::close_promotion_vote(&mut tx, vote.id, 0, 0, false).await?;
+            tx.commit().await?;
+            info!(vote.id, "Closed promotion vote whose poll message is deleted");
+            return Ok(());
+        }
+    };
+    let passed = approvals >= min_approvals && approvals > rejections;
+    info!(vote.id, approvals, rejections, passed, "Tallied promotion vote");
+
+    let apply = {
+        let db = db.write().await;
+        let mut tx = db.begin().await?;
+        // The rank change isn't applied if the member's rank has changed while the vote is open
+        let apply = passed
+            && match vote.mid.change_rank(&mut tx, vote.old_rank, vote.rank, None).await {
+                Ok(()) => true,
+                Err(why) if why.is::<ConcurrentChange>() => false,
+                Err(why) => return Err(why),
+            };
+        memberdb::close_promotion_vote(
+            &mut tx,
+            vote.id,
+            i64::try_from(approvals)?,
+            i64::try_from(rejections)?,
+            passed,
+        )
+        .await?;
+        tx.commit().await?;
+        if apply {
+            db.signal(DBEvent::MemberRankChange { mid: vote.mid, old: vote.old_rank, new: vote.rank });
+        }
+        apply
+    };
+
+    let result = if apply {
+        format!("the member's rank is changed to __{}__", vote.rank)
+    } else if passed {
+        "but the member's rank has changed since the vote is opened, so it isn't applied".to_string()
+    } else {
+        "the rank change is rejected".to_string()
+    };
+    let channel_id = ChannelId(u64::try_from(vote.channel)?);
+    let message_id = MessageId(u64::try_from(vote.message)?);
+    ctx!(
+        channel_id
+            .send_message(&cache_http.http, |m| {
+                m.reference_message((channel_id, message_id)).content(format!(
+                    "Vote closed with {} approvals and {} rejections, {}",
+                    approvals, rejections, result
+                ))
+            })
+            .await,
+        "Failed to send promotion vote result"
+    )?;
+    Ok(())
+}
+
+/// Count the approvals and rejections of a vote, `None` if its poll message is deleted.
+///
+/// Each staff member has one vote, so staff members that reacted with both [`APPROVE`] and
+/// [`REJECT`] aren't counted.
+async fn tally_votes(
+    cache_http: &CacheAndHttp, guild: &Guild, vote: &PromotionVote,
+) -> Result<Option<(u64, u64)>> {
+    let approvers = some!(staff_reactions(cache_http, guild, vote, APPROVE).await?, return Ok(None));
+    let rejecters = some!(staff_reactions(cache_http, guild, vote, REJECT).await?, return Ok(None));
+    let approvals = u64::try_from(approvers.difference(&rejecters).count())?;
+    let rejections = u64::try_from(rejecters.difference(&approvers).count())?;
+    Ok(Some((approvals, rejections)))
+}
+
+/// Get the staff members that reacted to a vote's poll message with `emoji`, `None` if the message
+/// is deleted
+async fn staff_reactions(
+    cache_http: &CacheAndHttp, guild: &Guild, vote: &PromotionVote, emoji: char,
+) -> Result<Option<HashSet<UserId>>> {
+    let role = MemberRank::Zero.get_group_role(guild).context("Failed to find staff role")?;
+    let channel_id = ChannelId(u64::try_from(vote.channel)?);
+    let message_id = MessageId(u64::try_from(vote.message)?);
+
+    let users =
+        match reaction_users(&cache_http.http, channel_id, message_id, ReactionType::from(emoji)).await {
+            Ok(users) => users,
+            Err(why) if is_not_found(&why) => return Ok(None),
+            Err(why) => return Err(why).context("Failed to get reaction users"),
+        };
+    let mut staff = HashSet::new();
+    for user in users {
+        if !user.bot && user.has_role(cache_http, guild.id, role.id).await.unwrap_or(false) {
+            staff.insert(user.id);
+        }
+    }
+    Ok(Some(staff))
+}
@@ -0,0 +1,253 @@
+//! Channels only visible to members of some ranks, configured by [`Config::rank_channels`].
+//!
+//! Access is granted with permission overwrites of the members, which [`sync_member`] adds or
+//! removes according to the member's rank. Overwrites are only added to users without one, and
+//! only overwrites granting exactly the channel's template are removed, so overwrites set by hand
+//! are left alone. Users whose overwrite set by hand doesn't grant access are listed as missing by
+//! [`audit`], so staff can fix them.
+use std::collections::{BTreeSet, HashMap};
+use std::str::FromStr;
+
+use anyhow::{Context, Result};
+use serenity::cache::Cache;
+use serenity::http::Http;
+use serenity::model::channel::{GuildChannel, PermissionOverwrite, PermissionOverwriteType};
+use serenity::model::guild::Guild;
+use serenity::model::id::{ChannelId, UserId};
+use serenity::model::permissions::Permissions;
+use tokio::sync::RwLock;
+use tracing::{info, warn};
+
+use config::Config;
+use memberdb::model::discord::DiscordId;
+use memberdb::model::member::{MemberId, MemberRank};
+use memberdb::DB;
+use util::{ok, some};
+
+/// Change [`sync_member`] makes to the overwrite of a user in a rank channel
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccessChange {
+    /// Add an overwrite granting access
+    Grant,
+    /// Remove the overwrite granting access
+    Revoke,
+}
+
+/// A rank channel of [`Config::rank_channels`], with its minimum rank parsed
+#[derive(Debug, Clone, Copy)]
+pub struct RankAccess {
+    pub channel: ChannelId,
+    pub min_rank: MemberRank,
+    /// Permissions granted to the members that can access the channel
+    pub allow: Permissions,
+}
+
+impl RankAccess {
+    /// Check if a member of `rank` can access the channel, `None` being someone that isn't a
+    /// member
+    pub fn allows(&self, rank: Option<MemberRank>) -> bool {
+        rank.is_some_and(|rank| rank >= self.min_rank)
+    }
+
+    /// Check if an overwrite grants access to the channel
+    fn grants(&self, overwrite: &PermissionOverwrite) -> bool {
+        overwrite.allow.contains(self.allow)
+    }
+
+    /// Get the change to make to a user's overwrite, `overwrite` being their current one and
+    /// `rank` their member rank
+    /// ```
+    /// use haxbotjr::util::rank_channel::{AccessChange, RankAccess};
+    /// use memberdb::model::member::MemberRank;
+    /// use serenity::model::channel::{PermissionOverwrite, PermissionOverwriteType};
+    /// use serenity::model::id::{ChannelId, UserId};
+    /// use serenity::model::permissions::Permissions;
+    ///
+    /// let allow = Permissions::VIEW_CHANNEL | Permissions::SEND_MESSAGES;
+    /// let access = RankAccess { channel: ChannelId(1), min_rank: MemberRank::Four, allow };
+    /// let kind = PermissionOverwriteType::Member(UserId(2));
+    /// let synced = PermissionOverwrite { allow, deny: Permissions::empty(), kind };
+    /// let deny = Permissions::SEND_MESSAGES;
+    /// let muted = PermissionOverwrite { allow: Permissions::empty(), deny, kind };
+    ///
+    /// assert_eq!(access.change(None, Some(MemberRank::Three)), Some(AccessChange::Grant));
+    /// assert_eq!(access.change(None, Some(MemberRank::Five)), None);
+    /// assert_eq!(access.change(Some(&synced), Some(MemberRank::Three)), None);
+    /// assert_eq!(access.change(Some(&synced), None), Some(AccessChange::Revoke));
+    /// // Overwrites set by hand are left alone
+    /// assert_eq!(access.change(Some(&muted), Some(MemberRank::Three)), None);
+    /// assert_eq!(access.change(Some(&muted), None), None);
+    /// ```
+    pub fn change(
+        &self, overwrite: Option<&PermissionOverwrite>, rank: Option<MemberRank>,
+    ) -> Option<AccessChange> {
+        match (overwrite, self.allows(rank)) {
+            (None, true) => Some(AccessChange::Grant),
+            (Some(overwrite), false) if self.is_synced(overwrite) => Some(AccessChange::Revoke),
+            _ => None,
+        }
+    }
+
+    /// Check if an overwrite is one added by the sync
+    fn is_synced(&self, overwrite: &PermissionOverwrite) -> bool {
+        overwrite.allow == self.allow && overwrite.deny.is_empty()
+    }
+
+    /// Get the discord users that are granted access to the channel by an overwrite
+    pub fn granted_users(&self, channel: &GuildChannel) -> BTreeSet<UserId> {
+        channel
+            .permission_overwrites
+            .iter()
+            .filter_map(|overwrite| match overwrite.kind {
+                PermissionOverwriteType::Member(user) if self.grants(overwrite) => Some(user),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+/// Access state of a rank channel
+#[derive(Debug)]
+pub struct ChannelAudit {
+    pub access: RankAccess,
+    /// Users granted access to the channel
+    pub granted: BTreeSet<UserId>,
+    /// Users in the server that should be granted access but aren't
+    pub missing: BTreeSet<UserId>,
+    /// Users that are granted access but shouldn't be
+    pub extra: BTreeSet<UserId>,
+}
+
+/// Get the rank channels, channels with an invalid minimum rank are skipped
+pub async fn rank_channels(config: &RwLock<Config>) -> Vec<RankAccess> {
+    let config = config.read().await;
+    let mut channels: Vec<RankAccess> = config
+        .rank_channels
+        .iter()
+        .filter_map(|(id, channel)| {
+            let min_rank = ok!(
+                MemberRank::from_str(&channel.min_rank),
+                "Invalid minimum rank of rank channel",
+                return None
+            );
+            Some(RankAccess { channel: ChannelId(*id), min_rank, allow: channel.template.permissions() })
+        })
+        .collect();
+    channels.sort_by_key(|access| access.channel);
+    channels
+}
+
+/// Get the rank of every member with a discord profile, keyed by their discord user.
+/// Guests are left out, as they don't get rank access.
+pub async fn member_ranks(db: &RwLock<DB>) -> Result<HashMap<UserId, MemberRank>> {
+    let db = db.read().await;
+    let mut ranks = HashMap::new();
+    for (mid, discord) in MemberId::with_discord(&mut db.exe()).await? {
+        if mid.kind(&mut db.exe()).await?.is_guest() {
+            continue;
+        }
+        let user = UserId(u64::try_from(discord.0).context("Failed to convert DiscordId to UserId")?);
+        ranks.insert(user, mid.rank(&mut db.exe()).await?);
+    }
+    Ok(ranks)
+}
+
+/// Get the rank of the member linked to a discord user, `None` if they aren't a member or are a
+/// guest
+pub async fn member_rank(db: &RwLock<DB>, discord: DiscordId) -> Result<Option<MemberRank>> {
+    let db = db.read().await;
+    let mid = match discord.mid(&mut db.exe()).await? {
+        Some(mid) => mid,
+        None => return Ok(None),
+    };
+    if mid.kind(&mut db.exe()).await?.is_guest() {
+        return Ok(None);
+    }
+    Ok(Some(mid.rank(&mut db.exe()).await?))
+}
+
+/// Sync the access of a discord user to all rank channels, `rank` is their member rank, `None` if
+/// they aren't a member.
+///
+/// Returns the amount of overwrites that are added or removed.
+pub async fn sync_member(
+    cache: &Cache, http: &Http, config: &RwLock<Config>, user: UserId, rank: Option<MemberRank>,
+) -> Result<usize> {
+    let mut changed = 0;
+    for access in rank_channels(config).await {
+        let channel = some!(cache.guild_channel(access.channel), {
+            warn!(channel = access.channel.0, "Rank channel doesn't exist");
+            continue;
+        });
+        let overwrite =
+            channel.permission_overwrites.iter().find(|o| o.kind == PermissionOverwriteType::Member(user));
+        match access.change(overwrite, rank) {
+            Some(AccessChange::Revoke) => {
+                info!(channel = channel.id.0, user = user.0, "Removing rank channel access");
+                channel
+                    .delete_permission(http, PermissionOverwriteType::Member(user))
+                    .await
+                    .context("Failed to remove rank channel overwrite")?;
+                changed += 1;
+            }
+            None => {}
+            Some(AccessChange::Grant) => {
+                info!(channel = channel.id.0, user = user.0, "Granting rank channel access");
+                let overwrite = PermissionOverwrite {
+                    allow: access.allow,
+                    deny: Permissions::empty(),
+                    kind: PermissionOverwriteType::Member(user),
+                };
+                channel
+                    .create_permission(http, &overwrite)
+                    .await
+                    .context("Failed to add rank channel overwrite")?;
+                changed += 1;
+            }
+        }
+    }
+    Ok(changed)
+}
+
+/// Sync the access of every user in the server and every user with an overwrite to all rank
+/// channels, returns the amount of overwrites that are added or removed.
+pub async fn sync_all(
+    cache: &Cache, http: &Http, db: &RwLock<DB>, config: &RwLock<Config>, guild: &Guild,
+) -> Result<usize> {
+    let ranks = member_ranks(db).await?;
+    let mut users: BTreeSet<UserId> = guild.members.keys().copied().collect();
+    for access in rank_channels(config).await {
+        if let Some(channel) = cache.guild_channel(access.channel) {
+            users.extend(access.granted_users(&channel));
+        }
+    }
+
+    let mut changed = 0;
+    for user in users {
+        changed += sync_member(cache, http, config, user, ranks.get(&user).copied()).await?;
+    }
+    Ok(changed)
+}
+
+/// Compare who is granted access to each rank channel with who should be.
+/// Channels that don't exist are left out.
+pub async fn audit(
+    cache: &Cache, db: &RwLock<DB>, config: &RwLock<Config>, guild: &Guild,
+) -> Result<Vec<ChannelAudit>> {
+    let ranks = member_ranks(db).await?;
+    let mut audits = Vec::new();
+    for access in rank_channels(config).await {
+        let channel = some!(cache.guild_channel(access.channel), continue);
+        let granted = access.granted_users(&channel);
+        let missing = guild
+            .members
+            .keys()
+            .filter(|user| access.allows(ranks.get(user).copied()) && !granted.contains(user))
+            .copied()
+            .collect();
+        let extra =
+            granted.iter().filter(|user| !access.allows(ranks.get(user).copied())).copied().collect();
+        audits.push(ChannelAudit { access, granted, missing, extra });
+    }
+    Ok(audits)
+}
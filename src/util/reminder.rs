@@ -0,0 +1,61 @@
+//! Sending reminders set with the `remindme` command.
+//!
+//! Reminders are stored in the database, and [`send_due_reminders`] is called periodically to send
+//! the ones that are due, so reminders are still sent after a restart.
+use anyhow::{Context as AHContext, Result};
+use serenity::model::id::{ChannelId, UserId};
+use serenity::CacheAndHttp;
+use tokio::sync::RwLock;
+use tracing::{info, warn};
+
+use memberdb::reminder::{self, Reminder};
+use memberdb::DB;
+use util::{ctxw, ok};
+
+/// Send all reminders that are due at unix timestamp `now`, and remove them
+pub async fn send_due_reminders(cache_http: &CacheAndHttp, db: &RwLock<DB>, now: i64) {
+    let reminders = {
+        let db = db.read().await;
+        ok!(ctxw!(reminder::due_reminders(&db, now).await), return)
+    };
+    for reminder in reminders {
+        if let Err(why) = send_reminder(cache_http, &reminder).await {
+            warn!(reminder.id, "Failed to send reminder: {:#}", why);
+        }
+        // Reminders that failed to send are also removed, so they aren't retried forever
+        let db = db.write().await;
+        let mut tx = ok!(ctxw!(db.begin().await), continue);
+        ok!(ctxw!(reminder::remove_reminder(&mut tx, reminder.id, None).await), continue);
+        let _ = ctxw!(tx.commit().await);
+    }
+}
+
+/// Send a reminder to its user's DMs, or to the channel it is set in if they can't be DMed.
+///
+/// The text of a reminder is written by its user, so it can't ping anyone, only the user is pinged
+/// when the reminder is sent to a channel.
+async fn send_reminder(cache_http: &CacheAndHttp, reminder: &Reminder) -> Result<()> {
+    let user = UserId(u64::try_from(reminder.user.0)?);
+    let content = format!("⏰ Reminder from <t:{}:R>: {}", reminder.created, reminder.text);
+    let dm = match user.create_dm_channel(&cache_http.http).await {
+        Ok(channel) => {
+            channel
+                .send_message(&cache_http.http, |m| {
+                    m.content(&content).allowed_mentions(|am| am.empty_parse())
+                })
+                .await
+        }
+        Err(why) => Err(why),
+    };
+    if let Err(why) = dm {
+        info!(reminder.id, "Failed to DM reminder, sending it to its channel instead: {:#}", why);
+        ChannelId(u64::try_from(reminder.channel)?)
+            .send_message(&cache_http.http, |m| {
+                m.content(format!("<@{}> {}", user, content))
+                    .allowed_mentions(|am| am.empty_parse().users([user]))
+            })
+            .await
+            .context("Failed to send reminder to its channel")?;
+    }
+    Ok(())
+}
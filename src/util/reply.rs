@@ -0,0 +1,71 @@
+//! Abstraction over the ways a command can respond to its caller.
+//!
+//! Commands reply through [`Respond`], which is implemented for [`Message`] for prefix commands,
+//! and for [`InteractionResponder`] for slash commands, so the response macros ([`send`],
+//! [`finish`], [`tfinish`]) can be used by both.
+//!
+//! [`send`]: crate::send
+//! [`finish`]: crate::finish
+//! [`tfinish`]: crate::tfinish
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use serenity::async_trait;
+use serenity::client::Context;
+use serenity::model::application::interaction::application_command::ApplicationCommandInteraction;
+use serenity::model::application::interaction::InteractionResponseType;
+use serenity::model::channel::Message;
+use serenity::Result;
+
+/// Something a command can send its responses to
+#[async_trait]
+pub trait Respond: Sync {
+    /// Send a response, and return the message it is sent as
+    async fn respond(&self, ctx: &Context, content: String) -> Result<Message>;
+}
+
+#[async_trait]
+impl Respond for Message {
+    async fn respond(&self, ctx: &Context, content: String) -> Result<Message> {
+        self.reply(ctx, content).await
+    }
+}
+
+#[derive(Debug)]
+/// Responds to a slash command.
+///
+/// The first response is sent as the interaction response, and the rest are sent as followup
+/// messages.
+/// If `ephemeral` is set, the responses are only visible to the caller, which management commands
+/// should take from [`Config::ephemeral_responses`].
+///
+/// [`Config::ephemeral_responses`]: config::Config::ephemeral_responses
+pub struct InteractionResponder<'a> {
+    interaction: &'a ApplicationCommandInteraction,
+    ephemeral: bool,
+    responded: AtomicBool,
+}
+
+impl<'a> InteractionResponder<'a> {
+    pub fn new(interaction: &'a ApplicationCommandInteraction, ephemeral: bool) -> Self {
+        Self { interaction, ephemeral, responded: AtomicBool::new(false) }
+    }
+}
+
+#[async_trait]
+impl Respond for InteractionResponder<'_> {
+    async fn respond(&self, ctx: &Context, content: String) -> Result<Message> {
+        if self.responded.swap(true, Ordering::SeqCst) {
+            return self
+                .interaction
+                .create_followup_message(ctx, |m| m.content(content).ephemeral(self.ephemeral))
+                .await;
+        }
+        self.interaction
+            .create_interaction_response(ctx, |r| {
+                r.kind(InteractionResponseType::ChannelMessageWithSource)
+                    .interaction_response_data(|d| d.content(content).ephemeral(self.ephemeral))
+            })
+            .await?;
+        self.interaction.get_interaction_response(ctx).await
+    }
+}
@@ -0,0 +1,176 @@
+//! Slash commands.
+//!
+//! The `profile` and `setrank` slash commands are registered in the main guild once the bot is
+//! ready, and their `ign` / `target` arguments are completed by [`autocomplete`].
+//! They respond through [`InteractionResponder`], management commands such as `setrank` respond
+//! with messages only visible to the caller if [`Config::ephemeral_responses`] is set.
+//!
+//! [`autocomplete`]: crate::util::autocomplete
+//! [`Config::ephemeral_responses`]: config::Config::ephemeral_responses
+use std::str::FromStr;
+
+use anyhow::{anyhow, Context as AHContext, Result};
+use serenity::client::Context;
+use serenity::model::application::command::CommandOptionType;
+use serenity::model::application::interaction::application_command::ApplicationCommandInteraction;
+use serenity::model::application::interaction::InteractionResponseType;
+use serenity::model::id::GuildId;
+
+use memberdb::model::db::Profiles;
+use memberdb::model::discord::DiscordId;
+use memberdb::model::member::{MemberRank, MEMBER_RANKS};
+use msgtool::parser::parse_ign;
+use util::{ctx, ok, some};
+
+use crate::commands::{profile_embed, set_rank};
+use crate::util::discord::is_staff_command;
+use crate::util::reply::InteractionResponder;
+use crate::{data, finish, tr};
+
+/// Register the slash commands in guild `guild_id`, replacing the ones registered before
+pub async fn register(ctx: &Context, guild_id: GuildId) -> Result<()> {
+    ctx!(
+        guild_id
+            .set_application_commands(&ctx.http, |commands| {
+                commands
+                    .create_application_command(|c| {
+                        c.name("profile")
+                            .description("Display the profiles / statistics of a player")
+                            .create_option(|o| {
+                                o.name("ign")
+                                    .description("Ign of the player, yourself if not given")
+                                    .kind(CommandOptionType::String)
+                                    .set_autocomplete(true)
+                            })
+                    })
+                    .create_application_command(|c| {
+                        c.name("setrank")
+                            .description("Set a member's rank")
+                            .create_option(|o| {
+                                o.name("rank")
+                                    .description("New rank")
+                                    .kind(CommandOptionType::String)
+                                    .required(true);
+                                for rank in MEMBER_RANKS {
+                                    o.add_string_choice(rank, rank);
+                                }
+                                o
+                            })
+                            .create_option(|o| {
+                                o.name("target")
+                                    .description("Member, as \"m:<ign>\"")
+                                    .kind(CommandOptionType::String)
+                                    .required(true)
+                                    .set_autocomplete(true)
+                            })
+                            .create_option(|o| {
+                                o.name("for")
+                                    .description("Duration of a temporary rank, ex: 14d")
+                                    .kind(CommandOptionType::String)
+                            })
+                    })
+            })
+            .await,
+        "Failed to register slash commands"
+    )?;
+    Ok(())
+}
+
+/// Respond to a slash command
+pub async fn respond(ctx: &Context, interaction: &ApplicationCommandInteraction) -> Result<()> {
+    match interaction.data.name.as_str() {
+        "profile" => display_profile(ctx, interaction).await,
+        "setrank" => set_member_rank(ctx, interaction).await,
+        _ => Ok(()),
+    }
+}
+
+/// Get the value of a string argument
+fn string_arg<'a>(interaction: &'a ApplicationCommandInteraction, name: &str) -> Option<&'a str> {
+    let option = interaction.data.options.iter().find(|option| option.name == name)?;
+    option.value.as_ref().and_then(|value| value.as_str())
+}
+
+/// Display the profiles of the player with the `ign` argument, or of the caller
+async fn display_profile(ctx: &Context, interaction: &ApplicationCommandInteraction) -> Result<()> {
+    let (db, client, config) = data!(ctx, "db", "reqwest", "config");
+    let (lc, fmt) = {
+        let config = config.read().await;
+        let guild_id = interaction.guild_id.map(|id| id.0);
+        (config.locale(guild_id), config.stat_format(guild_id, interaction.user.id.0))
+    };
+    let responder = InteractionResponder::new(interaction, false);
+
+    let profiles = match string_arg(interaction, "ign") {
+        Some(ign) => {
+            let mcid = match parse_ign(&db, &client, ign).await {
+                Ok(mcid) => mcid,
+                Err(why) => finish!(ctx, responder, "invalid target: {}", why),
+            };
+            let db = db.read().await;
+            Profiles::from_mc(&db, &mcid).await
+        }
+        None => {
+            let id = DiscordId::try_from(interaction.user.id.0)?;
+            let db = db.read().await;
+            Profiles::from_discord(&db, id).await
+        }
+    };
+    if profiles.is_none() {
+        finish!(ctx, responder, tr!(lc, NoProfiles));
+    }
+
+    let embed = profile_embed(ctx, &db, &profiles, lc, &fmt).await?;
+    interaction
+        .create_interaction_response(ctx, |r| {
+            r.kind(InteractionResponseType::ChannelMessageWithSource)
+                .interaction_response_data(|d| d.set_embed(embed))
+        })
+        .await
+        .context("Failed to respond to slash command")
+}
+
+/// Set the rank of the member with the `target` argument, which is the slash command version of
+/// `setRank`
+async fn set_member_rank(ctx: &Context, interaction: &ApplicationCommandInteraction) -> Result<()> {
+    let (db, client, config) = data!(ctx, "db", "reqwest", "config");
+    let (lc, ephemeral) = {
+        let config = config.read().await;
+        (config.locale(interaction.guild_id.map(|id| id.0)), config.ephemeral_responses)
+    };
+    let responder = InteractionResponder::new(interaction, ephemeral);
+
+    // Slash commands are only registered in the main guild, so only the caller has to be checked
+    if !is_staff_command(ctx, interaction) {
+        finish!(ctx, responder, "This command can only be used by a staff");
+    }
+
+    let rank = some!(string_arg(interaction, "rank"), finish!(ctx, responder, "rank not provided"));
+    let rank = ok!(MemberRank::from_str(rank), finish!(ctx, responder, "'{}' isn't a valid rank", rank));
+    let duration = match string_arg(interaction, "for") {
+        Some(duration) => match util::string::parse_second(duration.trim()) {
+            Ok(duration) => Some(duration),
+            Err(_) => finish!(ctx, responder, tr!(lc, InvalidDuration, duration.trim())),
+        },
+        None => None,
+    };
+
+    let target = some!(string_arg(interaction, "target"), finish!(ctx, responder, "target not provided"));
+    let ign = target.strip_prefix("m:").unwrap_or(target);
+    let mcid = match parse_ign(&db, &client, ign).await {
+        Ok(mcid) => mcid,
+        Err(why) => finish!(ctx, responder, "invalid target: {}", why),
+    };
+    let (mid, old_rank) = {
+        let db = db.read().await;
+        let mid = some!(
+            ctx!(mcid.mid(&mut db.exe()).await)?,
+            finish!(ctx, responder, "Failed to find target member in database")
+        );
+        (mid, ctx!(mid.rank(&mut db.exe()).await)?)
+    };
+
+    set_rank(ctx, &responder, lc, interaction.user.id, &db, mid, old_rank, rank, duration)
+        .await
+        .map_err(|why| anyhow!(why))
+}
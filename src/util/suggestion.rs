@@ -0,0 +1,157 @@
+//! Suggestion box, where members submit suggestions that others vote on with buttons, and staff
+//! review.
+//!
+//! Suggestions are posted as messages with vote buttons, button presses are answered by
+//! [`respond`], which records the vote and updates the message.
+use std::sync::Arc;
+
+use anyhow::{Context as AHContext, Result};
+use serenity::client::Context;
+use serenity::http::Http;
+use serenity::CacheAndHttp;
+use serenity::model::application::component::ButtonStyle;
+use serenity::model::application::interaction::message_component::MessageComponentInteraction;
+use serenity::model::application::interaction::InteractionResponseType;
+use serenity::model::channel::Message;
+use serenity::model::id::{ChannelId, MessageId};
+use tokio::sync::RwLock;
+use tracing::info;
+
+use config::Config;
+use memberdb::model::discord::DiscordId;
+use memberdb::suggestion::{self, Suggestion};
+use memberdb::DB;
+use util::some;
+
+/// Custom id of the upvote button
+const UP_ID: &str = "suggestion_up";
+/// Custom id of the downvote button
+const DOWN_ID: &str = "suggestion_down";
+
+/// Make the content of a suggestion's message
+pub fn make_content(suggestion: &Suggestion) -> String {
+    format!(
+        "> **Suggestion #{}** by <@{}>\n{}\n\nStatus: __{}__ · 👍 {} · 👎 {}",
+        suggestion.id,
+        suggestion.author.0,
+        suggestion.text,
+        suggestion.status,
+        suggestion.upvotes,
+        suggestion.downvotes
+    )
+}
+
+/// Submit a suggestion and post it to `channel_id`, returns its id
+pub async fn submit(
+    http: &Http, db: &RwLock<DB>, channel_id: ChannelId, author: DiscordId, text: &str, created: i64,
+) -> Result<i64> {
+    let suggestion = {
+        let db = db.write().await;
+        let mut tx = db.begin().await?;
+        let id = suggestion::add_suggestion(&mut tx, author, text, created).await?;
+        let suggestion =
+            suggestion::get_suggestion(&mut tx.exe(), id).await?.context("Missing suggestion")?;
+        tx.commit().await?;
+        suggestion
+    };
+
+    let message = channel_id
+        .send_message(http, |m| {
+            m.content(make_content(&suggestion)).components(|c| {
+                c.create_action_row(|ar| {
+                    ar.create_button(|b| b.custom_id(UP_ID).emoji('👍').style(ButtonStyle::Success))
+                        .create_button(|b| b.custom_id(DOWN_ID).emoji('👎').style(ButtonStyle::Danger))
+                })
+            })
+        })
+        .await
+        .context("Failed to post suggestion")?;
+
+    let db = db.write().await;
+    let mut tx = db.begin().await?;
+    suggestion::set_suggestion_message(
+        &mut tx,
+        suggestion.id,
+        i64::try_from(channel_id.0)?,
+        i64::try_from(message.id.0)?,
+    )
+    .await?;
+    tx.commit().await?;
+    Ok(suggestion.id)
+}
+
+/// Repost a message sent in a [`TextChannelTag::Suggestion`] channel as a suggestion, and delete
+/// the original message.
+///
+/// [`TextChannelTag::Suggestion`]: config::tag::TextChannelTag::Suggestion
+pub async fn repost(
+    cache_http: &CacheAndHttp, db: &RwLock<DB>, config: &RwLock<Config>, message: &Message,
+) -> Result<()> {
+    if message.author.bot || message.content.trim().is_empty() {
+        return Ok(());
+    }
+    // Commands used in the channel aren't suggestions
+    if crate::is_command(&cache_http.cache, config, message).await {
+        return Ok(());
+    }
+    let http = &cache_http.http;
+    let author = DiscordId::try_from(message.author.id.0)?;
+    let id = submit(
+        http,
+        db,
+        message.channel_id,
+        author,
+        message.content.trim(),
+        message.timestamp.unix_timestamp(),
+    )
+    .await?;
+    info!(id, "Reposted message as suggestion");
+    message.delete(http).await.context("Failed to delete reposted suggestion message")
+}
+
+/// Update the message of a suggestion to reflect its current status and votes
+pub async fn update_message(http: &Http, suggestion: &Suggestion) -> Result<()> {
+    let (channel, message) = match (suggestion.channel, suggestion.message) {
+        (Some(channel), Some(message)) => (channel, message),
+        _ => return Ok(()),
+    };
+    ChannelId(u64::try_from(channel)?)
+        .edit_message(http, MessageId(u64::try_from(message)?), |m| m.content(make_content(suggestion)))
+        .await
+        .context("Failed to update suggestion message")?;
+    Ok(())
+}
+
+/// Respond to a vote button press on a suggestion, other interactions are ignored
+pub async fn respond(ctx: &Context, interaction: &MessageComponentInteraction) -> Result<()> {
+    let up = match interaction.data.custom_id.as_str() {
+        UP_ID => true,
+        DOWN_ID => false,
+        _ => return Ok(()),
+    };
+    let db = {
+        let data = ctx.data.read().await;
+        Arc::clone(data.get::<DB>().context("Failed to get db")?)
+    };
+    let user = DiscordId::try_from(interaction.user.id.0)?;
+    let message = i64::try_from(interaction.message.id.0)?;
+
+    let suggestion = {
+        let db = db.write().await;
+        let id = some!(suggestion::suggestion_by_message(&mut db.exe(), message).await?, return Ok(()));
+        let mut tx = db.begin().await?;
+        suggestion::vote_suggestion(&mut tx, id, user, up).await?;
+        let suggestion =
+            suggestion::get_suggestion(&mut tx.exe(), id).await?.context("Missing suggestion")?;
+        tx.commit().await?;
+        suggestion
+    };
+
+    interaction
+        .create_interaction_response(ctx, |r| {
+            r.kind(InteractionResponseType::UpdateMessage)
+                .interaction_response_data(|d| d.content(make_content(&suggestion)))
+        })
+        .await
+        .context("Failed to respond to suggestion vote button")
+}
@@ -0,0 +1,166 @@
+//! The rank roles and nickname a discord member should have.
+//!
+//! [`DiscordSyncState`] is computed from the database in one place, and diffed against the
+//! discord member to get the changes that need to be made, so everything that updates a member's
+//! roles or nickname agrees on what they should be.
+use std::collections::HashSet;
+
+use anyhow::{Context, Result};
+use serenity::http::Http;
+use serenity::model::guild::{Guild, Member};
+use serenity::model::id::RoleId;
+use tokio::sync::RwLock;
+
+use memberdb::model::discord::DiscordId;
+use memberdb::model::member::{MemberId, MemberRank, MANAGED_MEMBER_RANKS};
+use memberdb::DB;
+use util::ctx;
+
+use super::discord::extract_custom_nick;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// Rank roles and nickname a discord member should have
+pub struct DiscordSyncState {
+    /// Rank roles the discord member should have
+    pub roles: HashSet<RoleId>,
+    /// Nickname the discord member should have, `None` if they shouldn't have one
+    pub nick: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+/// Changes needed to bring a discord member to its [`DiscordSyncState`]
+pub struct SyncDiff {
+    /// Rank roles to add
+    pub add_roles: Vec<RoleId>,
+    /// Rank roles to remove
+    pub remove_roles: Vec<RoleId>,
+    /// Nickname to set, an empty string removes it. `None` if the nickname is already correct.
+    pub nick: Option<String>,
+}
+
+impl DiscordSyncState {
+    /// State of a member with `rank` and `ign`.
+    /// If `custom_nick` is none, their current custom nick is preserved.
+    pub fn member(
+        rank: MemberRank, ign: Option<&str>, guild: &Guild, member: &Member, custom_nick: Option<&str>,
+    ) -> Self {
+        let mut roles = HashSet::new();
+        if MANAGED_MEMBER_RANKS.contains(&rank) {
+            roles.extend(rank.get_role(guild).map(|role| role.id));
+            roles.extend(rank.get_group_role(guild).map(|role| role.id));
+        }
+
+        let name = ign.unwrap_or(&member.user.name);
+        let custom_nick = match custom_nick {
+            Some(s) => s,
+            None => match &member.nick {
+                Some(nick) => extract_custom_nick(nick),
+                None => "",
+            },
+        };
+        let nick = format!("{} {} {}", rank.get_symbol(), name, custom_nick).trim_end().to_string();
+
+        Self { roles, nick: Some(nick) }
+    }
+
+    /// State of a discord user that isn't a member, which has no rank roles or nickname
+    pub fn non_member() -> Self {
+        Self { roles: HashSet::new(), nick: None }
+    }
+
+    /// State of a guest, which has no rank roles and keeps their own nickname
+    pub fn guest(member: &Member) -> Self {
+        Self { roles: HashSet::new(), nick: member.nick.clone() }
+    }
+
+    /// Get the state of a member from the database.
+    /// If `custom_nick` is none, their current custom nick is preserved.
+    pub async fn of_member(
+        db: &RwLock<DB>, mid: MemberId, guild: &Guild, member: &Member, custom_nick: Option<&str>,
+    ) -> Result<Self> {
+        let db = db.read().await;
+        if ctx!(mid.kind(&mut db.exe()).await)?.is_guest() {
+            return Ok(Self::guest(member));
+        }
+        let rank = ctx!(mid.rank(&mut db.exe()).await)?;
+        let ign = match ctx!(mid.links(&mut db.exe()).await)?.1 {
+            Some(mcid) => mcid.ign(&mut db.exe()).await.ok(),
+            None => None,
+        };
+        Ok(Self::member(rank, ign.as_deref(), guild, member, custom_nick))
+    }
+
+    /// Get the state of a discord member from the database, which is
+    /// [`non_member`](Self::non_member) if they aren't linked to a member.
+    /// If `custom_nick` is none, their current custom nick is preserved.
+    pub async fn fetch(
+        db: &RwLock<DB>, guild: &Guild, member: &Member, custom_nick: Option<&str>,
+    ) -> Result<Self> {
+        let discord_id = DiscordId::try_from(member.user.id.0)?;
+        let mid = {
+            let db = db.read().await;
+            ctx!(discord_id.mid(&mut db.exe()).await)?
+        };
+        match mid {
+            Some(mid) => Self::of_member(db, mid, guild, member, custom_nick).await,
+            None => Ok(Self::non_member()),
+        }
+    }
+
+    /// Get the changes needed to bring a discord member to this state
+    pub fn diff(&self, guild: &Guild, member: &Member) -> SyncDiff {
+        let managed: HashSet<RoleId> = MANAGED_MEMBER_RANKS
+            .iter()
+            .flat_map(|rank| [rank.get_role(guild), rank.get_group_role(guild)])
+            .flatten()
+            .map(|role| role.id)
+            .collect();
+
+        let add_roles = self.roles.iter().filter(|id| !member.roles.contains(id)).copied().collect();
+        let remove_roles = member
+            .roles
+            .iter()
+            .filter(|id| managed.contains(id) && !self.roles.contains(id))
+            .copied()
+            .collect();
+        let nick = match (&self.nick, &member.nick) {
+            (Some(nick), Some(current)) if nick == current => None,
+            (Some(nick), _) => Some(nick.clone()),
+            (None, Some(_)) => Some(String::new()),
+            (None, None) => None,
+        };
+
+        SyncDiff { add_roles, remove_roles, nick }
+    }
+}
+
+impl SyncDiff {
+    /// Checks if the discord member's roles need to be changed
+    pub fn changes_roles(&self) -> bool {
+        !self.add_roles.is_empty() || !self.remove_roles.is_empty()
+    }
+
+    /// Apply the role changes to a discord member
+    pub async fn apply_roles(&self, http: &Http, member: &mut Member) -> Result<()> {
+        if !self.changes_roles() {
+            return Ok(());
+        }
+        let roles: Vec<RoleId> = member
+            .roles
+            .iter()
+            .filter(|id| !self.remove_roles.contains(id))
+            .chain(self.add_roles.iter())
+            .copied()
+            .collect();
+        *member = member.edit(http, |e| e.roles(&roles)).await.context("Failed to edit roles")?;
+        Ok(())
+    }
+
+    /// Apply the nickname change to a discord member
+    pub async fn apply_nick(&self, http: &Http, member: &mut Member) -> Result<()> {
+        if let Some(nick) = &self.nick {
+            *member = member.edit(http, |e| e.nickname(nick)).await.context("Failed to edit nickname")?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,366 @@
+//! Recruitment tickets, private threads of a [`TextChannelTag::Recruitment`] channel where an
+//! applicant talks with staff.
+//!
+//! Tickets are opened with the button of the panel posted by [`post_panel`], and closed with the
+//! button posted in the ticket. Button presses are answered by [`respond`].
+//! When a ticket is closed, its transcript is posted to [`TextChannelTag::TicketLog`] channels.
+//! A ticket is only marked closed once its thread is archived, so closing it can be retried if
+//! that fails.
+use std::borrow::Cow;
+use std::collections::BTreeSet;
+use std::fmt::Write as _;
+use std::sync::{Arc, Mutex};
+
+use anyhow::{Context as AHContext, Result};
+use chrono::NaiveDateTime;
+use serenity::client::Context;
+use serenity::http::Http;
+use serenity::model::application::component::ButtonStyle;
+use serenity::model::application::interaction::message_component::MessageComponentInteraction;
+use serenity::model::application::interaction::InteractionResponseType;
+use serenity::model::channel::{AttachmentType, ChannelType, Message};
+use serenity::model::id::{ChannelId, GuildId, MessageId};
+use tokio::sync::RwLock;
+use tracing::{info, warn};
+
+use config::tag::TextChannelTag;
+use config::Config;
+use memberdb::model::discord::DiscordId;
+use memberdb::model::member::MemberRank;
+use memberdb::ticket::{self, Ticket};
+use memberdb::DB;
+use util::some;
+
+use crate::util::discord::{is_staff_interaction, reply_ephemeral};
+
+/// Custom id of the button that opens a ticket
+const OPEN_ID: &str = "ticket_open";
+/// Prefix of the custom id of the button that closes a ticket, followed by the ticket's id
+const CLOSE_ID: &str = "ticket_close";
+/// Max amount of messages fetched per request when making a transcript
+const TRANSCRIPT_PAGE_LEN: u64 = 100;
+
+/// Ids of the tickets being closed, so a ticket isn't closed twice at once
+static CLOSING: Mutex<BTreeSet<i64>> = Mutex::new(BTreeSet::new());
+
+/// Post the panel applicants open tickets with to `channel_id`
+pub async fn post_panel(http: &Http, channel_id: ChannelId) -> Result<Message> {
+    channel_id
+        .send_message(http, |m| {
+            m.content(
+                "> **Recruitment**\nInterested in joining the guild? Press the button below to open a \
+                ticket, a private thread where you can talk with staff.",
+            )
+            .components(|c| {
+                c.create_action_row(|ar| {
+                    ar.create_button(|b| {
+                        b.custom_id(OPEN_ID).label("Open ticket").style(ButtonStyle::Primary)
+                    })
+                })
+            })
+        })
+        .await
+        .context("Failed to post ticket panel")
+}
+
+/// Describe the member record linked to an applicant
+pub async fn describe_applicant(db: &RwLock<DB>, applicant: DiscordId) -> Result<String> {
+    let db = db.read().await;
+    let mid = match applicant.mid(&mut db.exe()).await? {
+        Some(mid) => mid,
+        None => return Ok("not in the member database".to_string()),
+    };
+    let rank = mid.rank(&mut db.exe()).await?;
+    let ign = match mid.links(&mut db.exe()).await?.1 {
+        Some(mcid) => Some(mcid.ign(&mut db.exe()).await?),
+        None => None,
+    };
+    Ok(match ign {
+        Some(ign) => format!("member `{}` ({}, id {})", ign, rank, mid.0),
+        None => format!("member {} (id {})", rank, mid.0),
+    })
+}
+
+/// Check if a custom id is of a ticket panel's or a ticket's button
+/// ```
+/// # use haxbotjr::util::ticket::is_ticket_button;
+/// assert!(is_ticket_button("ticket_open"));
+/// assert!(is_ticket_button("ticket_close:12"));
+/// assert!(!is_ticket_button("ticket_close"));
+/// assert!(!is_ticket_button("weekly_reset_skip"));
+/// ```
+pub fn is_ticket_button(custom_id: &str) -> bool {
+    match custom_id.split_once(':') {
+        Some((action, _)) => action == CLOSE_ID,
+        None => custom_id == OPEN_ID,
+    }
+}
+
+/// Respond to a button press on a ticket panel or a ticket, other interactions are ignored
+pub async fn respond(ctx: &Context, interaction: &MessageComponentInteraction) -> Result<()> {
+    let custom_id = interaction.data.custom_id.as_str();
+    if custom_id == OPEN_ID {
+        return open(ctx, interaction).await;
+    }
+    let (action, id) = some!(custom_id.split_once(':'), return Ok(()));
+    if action != CLOSE_ID {
+        return Ok(());
+    }
+    let id: i64 = id.parse().context("Invalid ticket id")?;
+
+    let (db, config) = {
+        let data = ctx.data.read().await;
+        (
+            Arc::clone(data.get::<DB>().context("Failed to get db")?),
+            Arc::clone(data.get::<Config>().context("Failed to get config")?),
+        )
+    };
+    let ticket = {
+        let db = db.read().await;
+        ticket::ticket_by_channel(&mut db.exe(), i64::try_from(interaction.channel_id.0)?).await?
+    };
+    let ticket = match ticket {
+        Some(ticket) if ticket.id == id && ticket.closed.is_none() => ticket,
+        _ => return reply_ephemeral(ctx, interaction, "This ticket is already closed").await,
+    };
+    let closer = DiscordId::try_from(interaction.user.id.0)?;
+    if closer != ticket.applicant && !is_staff_interaction(ctx, interaction) {
+        return reply_ephemeral(ctx, interaction, "Only the applicant or staff can close this ticket").await;
+    }
+
+    interaction
+        .create_interaction_response(ctx, |r| {
+            r.kind(InteractionResponseType::ChannelMessageWithSource)
+                .interaction_response_data(|d| d.content(format!("Ticket closed by <@{}>", closer)))
+        })
+        .await
+        .context("Failed to respond to ticket close button")?;
+    close(ctx, &db, &config, &ticket, closer).await
+}
+
+/// Open a ticket for the user that pressed the button of a ticket panel
+async fn open(ctx: &Context, interaction: &MessageComponentInteraction) -> Result<()> {
+    let (db, config) = {
+        let data = ctx.data.read().await;
+        (
+            Arc::clone(data.get::<DB>().context("Failed to get db")?),
+            Arc::clone(data.get::<Config>().context("Failed to get config")?),
+        )
+    };
+    let is_recruitment = {
+        let config = config.read().await;
+        config.text_channel_tags.tagged(&interaction.channel_id.0, &TextChannelTag::Recruitment)
+    };
+    if !is_recruitment {
+        return reply_ephemeral(ctx, interaction, "Tickets can't be opened in this channel").await;
+    }
+
+    let applicant = DiscordId::try_from(interaction.user.id.0)?;
+    let existing = {
+        let db = db.read().await;
+        ticket::open_ticket_of(&mut db.exe(), applicant).await?
+    };
+    if let Some(existing) = existing {
+        let content = format!("You already have an open ticket: <#{}>", existing.channel);
+        return reply_ephemeral(ctx, interaction, &content).await;
+    }
+
+    let thread = interaction
+        .channel_id
+        .create_private_thread(ctx, |t| {
+            t.name(format!("ticket-{}", interaction.user.name)).kind(ChannelType::PrivateThread)
+        })
+        .await
+        .context("Failed to create ticket thread")?;
+    thread
+        .id
+        .add_thread_member(ctx, interaction.user.id)
+        .await
+        .context("Failed to add applicant to ticket")?;
+    let id = {
+        let db = db.write().await;
+        let mut tx = db.begin().await?;
+        let id = ticket::open_ticket(&mut tx, applicant, i64::try_from(thread.id.0)?, now()).await?;
+        tx.commit().await?;
+        id
+    };
+    let id = match id {
+        Some(id) => id,
+        None => {
+            // Another ticket of the applicant is opened in the meantime
+            if let Err(why) = thread.id.delete(ctx).await {
+                warn!("Failed to delete duplicated ticket thread: {:#}", why);
+            }
+            return reply_ephemeral(ctx, interaction, "You already have an open ticket").await;
+        }
+    };
+    info!(id, applicant = interaction.user.id.0, "Opened ticket");
+
+    // Mentioning the staff role adds the staff to the private thread
+    let staff = interaction
+        .guild_id
+        .and_then(|guild_id| staff_role_mention(ctx, guild_id))
+        .unwrap_or_else(|| "staff".to_string());
+    let record = describe_applicant(&db, applicant).await?;
+    thread
+        .send_message(ctx, |m| {
+            m.content(format!(
+                "> **Ticket #{}**\n<@{}> opened a ticket, {} will be with you shortly.\nApplicant: {}",
+                id, applicant, staff, record
+            ))
+            .components(|c| {
+                c.create_action_row(|ar| {
+                    ar.create_button(|b| {
+                        b.custom_id(format!("{}:{}", CLOSE_ID, id))
+                            .label("Close ticket")
+                            .style(ButtonStyle::Danger)
+                    })
+                })
+            })
+        })
+        .await
+        .context("Failed to post ticket message")?;
+    reply_ephemeral(ctx, interaction, &format!("Your ticket is opened: <#{}>", thread.id)).await
+}
+
+/// Get the mention of the staff role, which is the group role of the highest rank
+fn staff_role_mention(ctx: &Context, guild_id: GuildId) -> Option<String> {
+    let guild = guild_id.to_guild_cached(ctx)?;
+    MemberRank::Zero.get_group_role(&guild).map(|role| format!("<@&{}>", role.id))
+}
+
+/// Close a ticket, post its transcript to [`TextChannelTag::TicketLog`] channels, then archive and
+/// lock its thread.
+///
+/// The ticket is marked closed last, so if posting the transcript or archiving the thread fails,
+/// it stays open and closing it can be retried. If the thread is deleted, the ticket is closed
+/// without a transcript.
+pub async fn close(
+    ctx: &Context, db: &RwLock<DB>, config: &RwLock<Config>, ticket: &Ticket, closer: DiscordId,
+) -> Result<()> {
+    if !CLOSING.lock().unwrap().insert(ticket.id) {
+        return Ok(());
+    }
+    let result = archive(ctx, db, config, ticket, closer).await;
+    CLOSING.lock().unwrap().remove(&ticket.id);
+    result
+}
+
+async fn archive(
+    ctx: &Context, db: &RwLock<DB>, config: &RwLock<Config>, ticket: &Ticket, closer: DiscordId,
+) -> Result<()> {
+    let is_open = {
+        let db = db.read().await;
+        ticket::ticket_by_channel(&mut db.exe(), ticket.channel).await?.is_some_and(|t| t.closed.is_none())
+    };
+    if !is_open {
+        return Ok(());
+    }
+
+    let thread = ChannelId(u64::try_from(ticket.channel)?);
+    match fetch_all_messages(ctx, thread).await {
+        Ok(messages) => {
+            post_transcript(ctx, db, config, ticket, closer, &messages).await?;
+            thread
+                .edit_thread(ctx, |t| t.archived(true).locked(true))
+                .await
+                .context("Failed to archive ticket thread")?;
+        }
+        Err(why) if is_unknown_channel(&why) => {
+            warn!(ticket.id, "Ticket thread is deleted, closing the ticket without a transcript");
+        }
+        Err(why) => return Err(why),
+    }
+
+    let db = db.write().await;
+    let mut tx = db.begin().await?;
+    if ticket::close_ticket(&mut tx, ticket.id, closer, now()).await? {
+        info!(ticket.id, ?closer, "Closed ticket");
+    }
+    tx.commit().await?;
+    Ok(())
+}
+
+/// Post the transcript of a ticket to [`TextChannelTag::TicketLog`] channels
+async fn post_transcript(
+    ctx: &Context, db: &RwLock<DB>, config: &RwLock<Config>, ticket: &Ticket, closer: DiscordId,
+    messages: &[Message],
+) -> Result<()> {
+    let transcript = format_transcript(messages);
+    let record = describe_applicant(db, ticket.applicant).await?;
+    let content = format!(
+        "> **Ticket #{}** closed\nApplicant: <@{}>, {}\nOpened <t:{}:R>, closed by <@{}>, {} messages",
+        ticket.id,
+        ticket.applicant,
+        record,
+        ticket.opened,
+        closer,
+        messages.len()
+    );
+    let channels: Vec<u64> = {
+        let config = config.read().await;
+        config.text_channel_tags.tagged_objects(&TextChannelTag::TicketLog).copied().collect()
+    };
+    for channel_id in channels {
+        let file = AttachmentType::Bytes {
+            data: Cow::Owned(transcript.clone().into_bytes()),
+            filename: format!("ticket-{}.txt", ticket.id),
+        };
+        let result = ChannelId(channel_id).send_message(ctx, |m| m.content(&content).add_file(file)).await;
+        if let Err(why) = result {
+            warn!(channel_id, "Failed to post ticket transcript: {:#}", why);
+        }
+    }
+    Ok(())
+}
+
+/// Check if a request failed because the channel doesn't exist
+fn is_unknown_channel(why: &anyhow::Error) -> bool {
+    match why.downcast_ref::<serenity::Error>() {
+        Some(serenity::Error::Http(why)) => why.status_code().map(|code| code.as_u16()) == Some(404),
+        _ => false,
+    }
+}
+
+/// Fetch all messages of a channel, oldest first
+async fn fetch_all_messages(http: impl AsRef<Http>, channel_id: ChannelId) -> Result<Vec<Message>> {
+    let mut messages = Vec::new();
+    let mut before: Option<MessageId> = None;
+    loop {
+        let page = channel_id
+            .messages(&http, |r| match before {
+                Some(before) => r.before(before).limit(TRANSCRIPT_PAGE_LEN),
+                None => r.limit(TRANSCRIPT_PAGE_LEN),
+            })
+            .await
+            .context("Failed to fetch ticket messages")?;
+        let done = (page.len() as u64) < TRANSCRIPT_PAGE_LEN;
+        before = page.last().map(|message| message.id);
+        messages.extend(page);
+        if done || before.is_none() {
+            break;
+        }
+    }
+    messages.reverse();
+    Ok(messages)
+}
+
+/// Format messages into a plain text transcript
+fn format_transcript(messages: &[Message]) -> String {
+    let mut transcript = String::new();
+    for message in messages {
+        let time = NaiveDateTime::from_timestamp_opt(message.timestamp.unix_timestamp(), 0)
+            .map(|time| time.format("%Y-%m-%d %H:%M").to_string())
+            .unwrap_or_default();
+        let _ = writeln!(transcript, "[{}] {}: {}", time, message.author.tag(), message.content);
+        for attachment in &message.attachments {
+            let _ = writeln!(transcript, "    {}", attachment.url);
+        }
+    }
+    transcript
+}
+
+/// Get the current unix timestamp
+fn now() -> i64 {
+    chrono::Utc::now().timestamp()
+}
@@ -0,0 +1,152 @@
+//! Guild slots, and the waitlist of recruits accepted while the in-game guild is full.
+//!
+//! The guild api reports the guild's members but not its member cap, so the cap is set with
+//! [`Config::guild_member_cap`]. When a guild member leaves and the guild has open slots while
+//! there are waitlisted recruits, staff are notified in [`TextChannelTag::Waitlist`] channels.
+//! Waitlisted recruits are taken off the waitlist once they join the guild.
+use std::fmt::Write as _;
+use std::sync::Arc;
+
+use anyhow::Result;
+use serenity::CacheAndHttp;
+use tokio::sync::RwLock;
+use tracing::info;
+
+use config::tag::TextChannelTag;
+use config::Config;
+use memberdb::waitlist::{self, Recruit};
+use memberdb::DB;
+use util::task::{RestartPolicy, Spawner};
+use util::{ctx, ok};
+use wynn::cache::Cache;
+use wynn::events::{WynnEvent, WynnSignal};
+
+/// Max amount of recruits listed when a slot opens
+const NOTIFY_LIST_LEN: usize = 5;
+
+/// Member count and member cap of the in-game guild
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Slots {
+    /// Amount of guild members, `None` if the guild isn't fetched yet
+    pub members: Option<u64>,
+    /// Max amount of guild members, `None` if it isn't set in the config
+    pub cap: Option<u64>,
+}
+
+impl Slots {
+    /// Get the current guild slots
+    pub async fn get(wynn_cache: &Cache, config: &RwLock<Config>) -> Self {
+        let members = wynn_cache.members.read().await.as_ref().map(|members| members.len() as u64);
+        let cap = config.read().await.guild_member_cap;
+        Self { members, cap }
+    }
+
+    /// Get the amount of open slots, `None` if it is unknown
+    pub fn open(&self) -> Option<u64> {
+        Some(self.cap?.saturating_sub(self.members?))
+    }
+
+    /// Describe the slots
+    pub fn describe(&self) -> String {
+        match (self.members, self.cap, self.open()) {
+            (Some(members), Some(cap), Some(open)) => format!("**{}/{}** ({} open)", members, cap, open),
+            (Some(members), None, _) => format!("**{}** members, the member cap isn't set", members),
+            _ => "unknown, the guild isn't fetched yet".to_string(),
+        }
+    }
+}
+
+/// Format a waitlisted recruit as a line of a list
+pub fn format_recruit(recruit: &Recruit) -> String {
+    let mut line = format!("#{} `{}`", recruit.id, recruit.ign);
+    if let Some(discord) = recruit.discord {
+        let _ = write!(line, " (<@{}>)", discord);
+    }
+    let _ = write!(line, ", waitlisted <t:{}:R> by <@{}>", recruit.added, recruit.added_by);
+    line
+}
+
+/// Notify [`TextChannelTag::Waitlist`] channels that guild slots are open, if there are
+/// waitlisted recruits
+async fn notify_open_slots(
+    cache_http: &CacheAndHttp, db: &RwLock<DB>, config: &RwLock<Config>, slots: Slots,
+) -> Result<()> {
+    let open = match slots.open() {
+        Some(open) if open > 0 => open,
+        _ => return Ok(()),
+    };
+    let recruits = {
+        let db = db.read().await;
+        waitlist::waitlist(&mut db.exe()).await?
+    };
+    if recruits.is_empty() {
+        return Ok(());
+    }
+    info!(open, waitlisted = recruits.len(), "Guild slot opened for waitlisted recruits");
+
+    let mut content = format!(
+        "> **A guild slot opened**\nGuild slots: {}, {} recruits waitlisted, next up:",
+        slots.describe(),
+        recruits.len()
+    );
+    for recruit in recruits.iter().take(NOTIFY_LIST_LEN) {
+        write!(content, "\n{}", format_recruit(recruit))?;
+    }
+    config::send(config, cache_http, &TextChannelTag::Waitlist, &content).await?;
+    Ok(())
+}
+
+/// Start the loop that takes recruits off the waitlist when they join the guild, and notifies
+/// staff when a guild member leaves while there are waitlisted recruits
+pub async fn start_slot_loop(
+    spawner: &impl Spawner, cache_http: Arc<CacheAndHttp>, config: Arc<RwLock<Config>>, db: Arc<RwLock<DB>>,
+    wynn_cache: Arc<Cache>, signal: WynnSignal,
+) {
+    spawner.spawn("guild slots (wynn event)", RestartPolicy::Always, move || {
+        let cache_http = cache_http.clone();
+        let config = config.clone();
+        let db = db.clone();
+        let wynn_cache = wynn_cache.clone();
+        let signal = signal.clone();
+        async move {
+            info!("Starting guild slot loop (wynn event)");
+            // The cache is only updated after the events are sent, so it contains the guild members
+            // before the first batch of events
+            let mut members = wynn_cache.members.read().await.as_ref().map(|members| members.len() as u64);
+            let mut recv = signal.connect();
+            loop {
+                let events =
+                    ok!(ctx!(recv.recv().await, "Failed to receive wynn event in guild slot loop"), continue);
+
+                let (mut joins, mut leaves) = (0, 0);
+                for event in events.as_ref() {
+                    match event {
+                        WynnEvent::MemberJoin { ign, .. } => {
+                            joins += 1;
+                            let db = db.write().await;
+                            let mut tx = ok!(ctx!(db.begin().await), continue);
+                            let _ = ctx!(waitlist::remove_ign(&mut tx, ign).await);
+                            let _ = ctx!(tx.commit().await);
+                        }
+                        WynnEvent::MemberLeave { .. } => leaves += 1,
+                        _ => {}
+                    }
+                }
+                if joins == 0 && leaves == 0 {
+                    continue;
+                }
+                // If the guild wasn't fetched before, the first batch adds all guild members
+                let count = members.map_or(joins, |old| (old + joins).saturating_sub(leaves));
+                members = Some(count);
+                if leaves > 0 {
+                    let cap = config.read().await.guild_member_cap;
+                    let slots = Slots { members: Some(count), cap };
+                    let _ = ctx!(
+                        notify_open_slots(&cache_http, &db, &config, slots).await,
+                        "Failed to notify open guild slots"
+                    );
+                }
+            }
+        }
+    });
+}
@@ -0,0 +1,109 @@
+//! Staff confirmation of the weekly reset.
+//!
+//! The report made before each weekly reset is posted to [`TextChannelTag::WeeklyReport`]
+//! channels, with buttons to proceed with or skip the reset if it waits for staff.
+//! Button presses are answered by [`respond`], which passes the decision to [`ResetGate`].
+use std::sync::Arc;
+
+use anyhow::{Context as AHContext, Result};
+use serenity::client::Context;
+use serenity::model::application::component::ButtonStyle;
+use serenity::model::application::interaction::message_component::MessageComponentInteraction;
+use serenity::model::application::interaction::InteractionResponseType;
+use serenity::model::id::ChannelId;
+use serenity::CacheAndHttp;
+use tokio::sync::RwLock;
+use tracing::{info, warn};
+
+use config::tag::TextChannelTag;
+use config::Config;
+use memberdb::reset_gate::{ResetDecision, ResetGate};
+use memberdb::weekly_report::WeeklyReport;
+
+use crate::util::discord::{is_staff_interaction, reply_ephemeral};
+
+/// Custom id of the button that proceeds with the reset
+const PROCEED_ID: &str = "weekly_reset_proceed";
+/// Custom id of the button that skips the reset
+const SKIP_ID: &str = "weekly_reset_skip";
+
+/// Post a weekly report to [`TextChannelTag::WeeklyReport`] channels.
+///
+/// If `deadline` is given, the reset is waiting for staff, so buttons to decide on it are added.
+pub async fn post_report(
+    cache_http: &CacheAndHttp, config: &RwLock<Config>, report: &WeeklyReport, deadline: Option<i64>,
+) -> Result<()> {
+    let mut content = report.summary();
+    if let Some(deadline) = deadline {
+        content
+            .push_str(&format!("\n\nThe weekly reset proceeds <t:{}:R> unless a staff skips it.", deadline));
+    }
+
+    let channels: Vec<u64> = {
+        let config = config.read().await;
+        config.text_channel_tags.tagged_objects(&TextChannelTag::WeeklyReport).copied().collect()
+    };
+    if channels.is_empty() && deadline.is_some() {
+        warn!("No channels to post the weekly report in, the reset proceeds after the grace window");
+    }
+    for channel_id in channels {
+        let result = ChannelId(channel_id)
+            .send_message(&cache_http.http, |m| {
+                m.content(&content);
+                if deadline.is_some() {
+                    m.components(|c| {
+                        c.create_action_row(|ar| {
+                            ar.create_button(|b| {
+                                b.custom_id(PROCEED_ID).label("Reset now").style(ButtonStyle::Danger)
+                            })
+                            .create_button(|b| {
+                                b.custom_id(SKIP_ID).label("Skip reset").style(ButtonStyle::Secondary)
+                            })
+                        })
+                    });
+                }
+                m
+            })
+            .await;
+        if let Err(why) = result {
+            warn!(channel_id, "Failed to post weekly report: {:#}", why);
+        }
+    }
+    Ok(())
+}
+
+/// Respond to a button press on a weekly report, other interactions are ignored
+pub async fn respond(ctx: &Context, interaction: &MessageComponentInteraction) -> Result<()> {
+    let decision = match interaction.data.custom_id.as_str() {
+        PROCEED_ID => ResetDecision::Proceed,
+        SKIP_ID => ResetDecision::Skip,
+        _ => return Ok(()),
+    };
+
+    if !is_staff_interaction(ctx, interaction) {
+        return reply_ephemeral(ctx, interaction, "Only staff can decide on the weekly reset").await;
+    }
+
+    let gate = {
+        let data = ctx.data.read().await;
+        Arc::clone(data.get::<ResetGate>().context("Failed to get reset gate")?)
+    };
+    if !gate.decide(decision) {
+        return reply_ephemeral(ctx, interaction, "The weekly reset isn't waiting for staff anymore").await;
+    }
+    info!(?decision, user = interaction.user.id.0, "Staff decided on the weekly reset");
+
+    let result = match decision {
+        ResetDecision::Proceed => "proceeded with",
+        ResetDecision::Skip => "skipped",
+    };
+    let content =
+        format!("{}\n\n<@{}> {} the weekly reset.", interaction.message.content, interaction.user.id, result);
+    interaction
+        .create_interaction_response(ctx, |r| {
+            r.kind(InteractionResponseType::UpdateMessage)
+                .interaction_response_data(|d| d.content(content).components(|c| c))
+        })
+        .await
+        .context("Failed to respond to weekly reset button")
+}
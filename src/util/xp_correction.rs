@@ -0,0 +1,132 @@
+//! Staff review of suspicious guild xp contributions.
+//!
+//! Contributions held back by the database are posted to [`TextChannelTag::XpCorrection`]
+//! channels with buttons to approve or discard them.
+//! Button presses are answered by [`respond`], which applies or drops the contribution.
+use std::sync::Arc;
+
+use anyhow::{Context as AHContext, Result};
+use serenity::client::Context;
+use serenity::model::application::component::ButtonStyle;
+use serenity::model::application::interaction::message_component::MessageComponentInteraction;
+use serenity::model::application::interaction::InteractionResponseType;
+use serenity::model::id::ChannelId;
+use serenity::CacheAndHttp;
+use tokio::sync::RwLock;
+use tracing::{info, warn};
+
+use config::tag::TextChannelTag;
+use config::Config;
+use memberdb::stat_increment::{self, AuditedStat};
+use memberdb::xp_correction::{self, XpCorrection};
+use memberdb::DB;
+use util::some;
+use util::string::StatFormat;
+
+use crate::util::discord::{is_staff_interaction, reply_ephemeral};
+
+/// Prefix of the custom id of the button that approves a contribution, followed by its id
+const APPROVE_ID: &str = "xp_correction_approve";
+/// Prefix of the custom id of the button that discards a contribution, followed by its id
+const DISCARD_ID: &str = "xp_correction_discard";
+
+/// Post a held back contribution to [`TextChannelTag::XpCorrection`] channels
+pub async fn post_correction(
+    cache_http: &CacheAndHttp, config: &RwLock<Config>, correction: &XpCorrection, fmt: &StatFormat,
+) -> Result<()> {
+    let content = format!(
+        "> **Suspicious xp contribution #{}**\n`{}` contributed **{}** xp <t:{}:R>, \
+        it isn't counted until a staff approves it.",
+        correction.id,
+        correction.ign,
+        fmt.num(correction.amount, false),
+        correction.time
+    );
+    let channels: Vec<u64> = {
+        let config = config.read().await;
+        config.text_channel_tags.tagged_objects(&TextChannelTag::XpCorrection).copied().collect()
+    };
+    for channel_id in channels {
+        let result = ChannelId(channel_id)
+            .send_message(&cache_http.http, |m| {
+                m.content(&content).components(|c| {
+                    c.create_action_row(|ar| {
+                        ar.create_button(|b| {
+                            b.custom_id(format!("{}:{}", APPROVE_ID, correction.id))
+                                .label("Approve")
+                                .style(ButtonStyle::Success)
+                        })
+                        .create_button(|b| {
+                            b.custom_id(format!("{}:{}", DISCARD_ID, correction.id))
+                                .label("Discard")
+                                .style(ButtonStyle::Danger)
+                        })
+                    })
+                })
+            })
+            .await;
+        if let Err(why) = result {
+            warn!(channel_id, "Failed to post xp correction: {:#}", why);
+        }
+    }
+    Ok(())
+}
+
+/// Respond to a button press on a held back contribution, other interactions are ignored
+pub async fn respond(ctx: &Context, interaction: &MessageComponentInteraction) -> Result<()> {
+    let (action, id) = some!(interaction.data.custom_id.split_once(':'), return Ok(()));
+    let approve = match action {
+        APPROVE_ID => true,
+        DISCARD_ID => false,
+        _ => return Ok(()),
+    };
+    let id: i64 = id.parse().context("Invalid xp correction id")?;
+
+    if !is_staff_interaction(ctx, interaction) {
+        return reply_ephemeral(ctx, interaction, "Only staff can review xp contributions").await;
+    }
+
+    let db = {
+        let data = ctx.data.read().await;
+        Arc::clone(data.get::<DB>().context("Failed to get db")?)
+    };
+    let correction = {
+        let db = db.write().await;
+        let mut tx = db.begin().await?;
+        let correction = if approve {
+            let correction = xp_correction::approve_correction(&mut tx, id).await?;
+            if let Some(correction) = &correction {
+                let now = chrono::Utc::now().timestamp();
+                stat_increment::record_wynn(
+                    &mut tx,
+                    &correction.mcid,
+                    AuditedStat::Xp,
+                    correction.amount,
+                    "XpCorrection",
+                    now,
+                )
+                .await?;
+            }
+            correction
+        } else {
+            xp_correction::discard_correction(&mut tx, id).await?
+        };
+        tx.commit().await?;
+        correction
+    };
+    if correction.is_none() {
+        return reply_ephemeral(ctx, interaction, "This xp contribution is already reviewed").await;
+    }
+    info!(id, approve, user = interaction.user.id.0, "Staff reviewed xp contribution");
+
+    let result = if approve { "approved" } else { "discarded" };
+    let content =
+        format!("{}\n\n<@{}> {} the contribution.", interaction.message.content, interaction.user.id, result);
+    interaction
+        .create_interaction_response(ctx, |r| {
+            r.kind(InteractionResponseType::UpdateMessage)
+                .interaction_response_data(|d| d.content(content).components(|c| c))
+        })
+        .await
+        .context("Failed to respond to xp correction button")
+}